@@ -0,0 +1,178 @@
+//
+// Benchmarks of the proving code paths, each run with 1, 2, 4 ... threads up to the number of CPUs,
+// the pool of worker threads being started once for all of them:
+//
+//     cargo bench --bench prover [fft] [msm] [sha256]
+//
+// `sha256` proves the two 216 bits numbers hash of `Sha256_2`, from test_material's circomlib sha256
+// circuits, with Groth16 after removing the linear constraints as `snarkrs compile --simplify` does.
+// Its setup is run once, with the default number of threads, and is not measured.
+//
+
+extern crate libsnarkrs;
+
+use std::time::{Duration, Instant};
+
+use libsnarkrs::circuit::optimize;
+use libsnarkrs::curve::bn254::{G1, G1Affine};
+use libsnarkrs::curve::multi_exp;
+use libsnarkrs::elaborator;
+use libsnarkrs::field::bn254::Fr;
+use libsnarkrs::field::domain::Domain;
+use libsnarkrs::field::PrimeField;
+use libsnarkrs::groth16;
+use libsnarkrs::parallel;
+use libsnarkrs::parser::compile;
+use libsnarkrs::rng::{Rng, TestRng};
+use libsnarkrs::witness;
+
+const RUNS: usize = 3;
+
+fn thread_counts() -> Vec<usize> {
+
+    parallel::set_threads(0);
+    let available = parallel::threads();
+
+    let mut counts = vec!(1);
+    while counts[counts.len() - 1] * 2 < available {
+        let next = counts[counts.len() - 1] * 2;
+        counts.push(next);
+    }
+    if available > 1 {
+        counts.push(available);
+    }
+
+    counts
+
+}
+
+///
+/// Fastest of `RUNS` runs of `task`, for each number of threads.
+///
+fn measure<F: FnMut()>(name: & str, mut task: F) {
+
+    let mut single = None;
+
+    for threads in thread_counts() {
+
+        parallel::set_threads(threads);
+
+        let best = (0..RUNS).map(|_| {
+            let start = Instant::now();
+            task();
+            start.elapsed()
+        }).min().unwrap();
+
+        let speedup = single.get_or_insert(best).as_secs_f64() / best.as_secs_f64();
+        println!("{:<24} {:>3} thread(s) {:>10.2} ms {:>6.2}x", name, threads, millis(best), speedup);
+
+    }
+
+    parallel::set_threads(0);
+
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn random_scalars(rng: & mut TestRng, count: usize) -> Vec<Fr> {
+    (0..count).map(|_| rng.field()).collect()
+}
+
+fn bench_fft(rng: & mut TestRng) {
+    for log_size in &[14, 16, 18] {
+
+        let domain = Domain::<Fr>::new(1 << log_size).unwrap();
+        let shift = Fr::from_u64(7);
+        let coefficients = random_scalars(rng, domain.size);
+
+        measure(&format!("fft 2^{}", log_size), || {
+            let mut values = coefficients.clone();
+            domain.fft(& mut values);
+        });
+
+        measure(&format!("coset fft 2^{}", log_size), || {
+            let mut values = coefficients.clone();
+            domain.coset_fft(& mut values, &shift);
+        });
+
+    }
+}
+
+fn bench_msm(rng: & mut TestRng) {
+
+    let bases: Vec<G1Affine> = random_scalars(rng, 1 << 16).iter().map(|scalar| G1::generator().mul_scalar(scalar).to_affine()).collect();
+
+    for log_size in &[12, 14, 16] {
+        let scalars = random_scalars(rng, 1 << log_size);
+        measure(&format!("msm G1 2^{}", log_size), || {
+            multi_exp(&bases[..scalars.len()], &scalars);
+        });
+    }
+
+    // The setups and phase 2 run one small multi-scalar multiplication per signal, where the cost
+    // of starting a parallel section shows
+    let scalars = random_scalars(rng, 1 << 9);
+    measure("64 msm G1 2^9", || {
+        for _ in 0..64 {
+            multi_exp(&bases[..scalars.len()], &scalars);
+        }
+    });
+
+}
+
+fn bench_sha256(rng: & mut TestRng) {
+
+    // Main component given in memory, next to the circuit it includes
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/lib/parser/test_material/circuits/sha256/sha256_2_main.circom");
+    let mut sources = std::collections::HashMap::new();
+    sources.insert(path.clone(), "include \"sha256_2.circom\";\n\ncomponent main = Sha256_2();\n".to_string());
+    let ctx = compile::build_context_with_sources(&path, &[], &sources);
+    assert!(ctx.errors().is_empty(), "{:?}", ctx.errors());
+    let circuit = elaborator::elaborate::<Fr>(&ctx).expect("Elaboration failed");
+
+    let mut r1cs = circuit.r1cs().expect("Constraint generation failed");
+    let symbols = circuit.symbols();
+    let mut simplified = symbols.clone();
+    optimize::simplify(& mut r1cs, & mut simplified);
+
+    let inputs = witness::input::parse_inputs::<Fr>("{\"a\": 1, \"b\": 2}").unwrap();
+    let witness = witness::calculate_witness(&circuit, &inputs).expect("Witness calculation failed");
+    let witness = optimize::remap_witness(&witness, &symbols, &simplified, r1cs.n_wires);
+    assert!(r1cs.unsatisfied(&witness).is_empty(), "Invalid witness");
+
+    println!("sha256: {} constraints, {} wires", r1cs.constraints.len(), r1cs.n_wires);
+
+    let start = Instant::now();
+    let pk: groth16::ProvingKey = groth16::setup(&r1cs, rng).unwrap();
+    println!("sha256: setup in {:.2} ms", millis(start.elapsed()));
+
+    let vk = pk.verifying_key();
+    let public = &witness[1..=r1cs.n_public()];
+
+    measure("groth16 prove sha256", || {
+        let proof = groth16::prove(&pk, &witness, & mut TestRng::new(1)).unwrap();
+        assert_eq!(groth16::verify(&vk, public, &proof), Ok(true));
+    });
+
+}
+
+fn main() {
+
+    let filters: Vec<String> = std::env::args().skip(1).filter(|arg| !arg.starts_with('-')).collect();
+    let enabled = |name: & str| filters.is_empty() || filters.iter().any(|filter| filter == name);
+
+    let mut rng = TestRng::new(1);
+
+    if enabled("fft") {
+        bench_fft(& mut rng);
+    }
+    if enabled("msm") {
+        bench_msm(& mut rng);
+    }
+    if enabled("sha256") {
+        bench_sha256(& mut rng);
+    }
+
+}
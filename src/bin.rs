@@ -1,6 +1,174 @@
 extern crate libsnarkrs;
+extern crate serde_json;
+#[macro_use]
+extern crate clap;
 
-fn main() {
-    println!("Hello");
+#[macro_use]
+mod commands;
+
+use clap::{App, AppSettings, Arg, SubCommand};
+
+use commands::Failure;
+
+fn prime_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("prime")
+        .long("prime")
+        .short("p")
+        .takes_value(true)
+        .possible_values(commands::PRIMES)
+        .default_value("bn128")
+        .help("Prime field of the circuit")
 }
 
+fn include_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("include")
+        .long("include")
+        .short("l")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .help("Directory where includes not found next to the including file are looked up")
+}
+
+///
+/// Exit codes: 0 on success, 1 when the input is invalid (compile errors, unsatisfied constraints)
+/// and 2 when the command cannot run (invalid arguments, unreadable files).
+///
+fn main() {
+
+    let app = App::new("snarkrs")
+        .version(crate_version!())
+        .about("circom circuit compiler")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(Arg::with_name("threads").long("threads").takes_value(true).global(true).help("Number of threads of the provers and key generation, defaults to the number of CPUs"))
+        .subcommand(SubCommand::with_name("check")
+            .about("Parses a circuit and its includes and elaborates its main component, if any")
+            .arg(Arg::with_name("file").required(true).help("Main circuit file"))
+            .arg(include_arg())
+            .arg(prime_arg()))
+        .subcommand(SubCommand::with_name("parse")
+            .about("Parses a single file, includes are not followed")
+            .arg(Arg::with_name("file").required(true).help("Circuit file"))
+            .arg(Arg::with_name("dump").long("dump").takes_value(true).possible_values(&["tokens", "ast"]).help("Prints the token tree or the typed AST"))
+            .arg(Arg::with_name("format").long("format").takes_value(true).possible_values(&["text", "json", "sexp"]).help("Format of the dump, defaults to indented text")))
+        .subcommand(SubCommand::with_name("compile")
+            .about("Compiles a circuit to .r1cs and .sym files, and a .wtns witness when inputs are given")
+            .arg(Arg::with_name("file").required(true).help("Main circuit file"))
+            .arg(include_arg())
+            .arg(prime_arg())
+            .arg(Arg::with_name("output").long("output").short("o").takes_value(true).help("Output directory, defaults to the current one"))
+            .arg(Arg::with_name("simplify").long("simplify").help("Removes linear constraints and duplicates"))
+            .arg(Arg::with_name("input").long("input").short("i").takes_value(true).help("input.json file to compute the witness from"))
+            .arg(Arg::with_name("c").long("c").help("Writes a C witness calculator, <name>.c"))
+            .arg(Arg::with_name("rust").long("rust").help("Writes a Rust witness calculator module, <name>.rs"))
+            .arg(Arg::with_name("wasm").long("wasm").help("Writes a WebAssembly witness calculator for witness_calculator.js, <name>.wasm and its text <name>.wat")))
+        .subcommand(SubCommand::with_name("check-witness")
+            .about("Checks that a witness satisfies every constraint of a circuit")
+            .arg(Arg::with_name("r1cs").required(true).help("Constraint system, in .r1cs format"))
+            .arg(Arg::with_name("witness").required(true).help("Witness, in .wtns format"))
+            .arg(Arg::with_name("sym").long("sym").takes_value(true).help("Symbol map used to name the signals, defaults to the .sym file next to the constraint system"))
+            .arg(prime_arg()))
+        .subcommand(SubCommand::with_name("fmt")
+            .about("Formats circuit files, printing the result unless --write or --check is given")
+            .arg(Arg::with_name("file").required(true).multiple(true).help("Circuit files"))
+            .arg(Arg::with_name("indent").long("indent").takes_value(true).default_value("4").help("Number of spaces per indentation level"))
+            .arg(Arg::with_name("tabs").long("tabs").help("Indents with tabs"))
+            .arg(Arg::with_name("write").long("write").short("w").conflicts_with("check").help("Rewrites the files in place"))
+            .arg(Arg::with_name("check").long("check").help("Lists the files that are not formatted, failing if there are any")))
+        .subcommand(SubCommand::with_name("lint")
+            .about("Warns about likely mistakes in circuit files, failing if there are any")
+            .arg(Arg::with_name("file").required(true).multiple(true).help("Circuit files"))
+            .arg(Arg::with_name("config").long("config").short("c").takes_value(true).help("JSON file enabling or disabling rules, defaults to snarkrs-lint.json if present")))
+        .subcommand(SubCommand::with_name("audit")
+            .about("Reports the signals of a circuit left under-constrained by its constraint system")
+            .arg(Arg::with_name("file").required(true).help("Main circuit file"))
+            .arg(include_arg())
+            .arg(prime_arg()))
+        .subcommand(SubCommand::with_name("stats")
+            .about("Prints the size of a circuit: constraints, wires, inputs, outputs and components per template")
+            .arg(Arg::with_name("file").required(true).help("Main circuit file"))
+            .arg(include_arg())
+            .arg(prime_arg())
+            .arg(Arg::with_name("format").long("format").takes_value(true).possible_values(&["table", "json"]).default_value("table").help("Output format"))
+            .arg(Arg::with_name("top").long("top").takes_value(true).default_value("10").help("Number of templates listed in the table, by constraint count")))
+        .subcommand(SubCommand::with_name("zkey")
+            .about("Prints the sizes of a Groth16 .zkey proving key, checking it against a constraint system if one is given")
+            .arg(Arg::with_name("zkey").required(true).help("Proving key, in .zkey format"))
+            .arg(Arg::with_name("r1cs").long("r1cs").takes_value(true).help("Constraint system the key is expected to be for, in .r1cs format")))
+        .subcommand(SubCommand::with_name("zkey-new")
+            .about("Derives the initial Groth16 phase 2 key of a circuit from a .ptau powers of tau file")
+            .arg(Arg::with_name("r1cs").required(true).help("Constraint system, in .r1cs format"))
+            .arg(Arg::with_name("ptau").required(true).help("Powers of tau, in .ptau format"))
+            .arg(Arg::with_name("zkey").required(true).help("Output key, in .zkey format")))
+        .subcommand(SubCommand::with_name("zkey-contribute")
+            .about("Adds a phase 2 contribution to a Groth16 key and prints its hash")
+            .arg(Arg::with_name("input").required(true).help("Key to contribute to, in .zkey format"))
+            .arg(Arg::with_name("output").required(true).help("Output key, in .zkey format"))
+            .arg(Arg::with_name("name").long("name").short("n").takes_value(true).help("Name recorded with the contribution")))
+        .subcommand(SubCommand::with_name("zkey-verify")
+            .about("Checks that a Groth16 key comes from a circuit and powers of tau through valid contributions")
+            .arg(Arg::with_name("r1cs").required(true).help("Constraint system, in .r1cs format"))
+            .arg(Arg::with_name("ptau").required(true).help("Powers of tau, in .ptau format"))
+            .arg(Arg::with_name("zkey").required(true).help("Key, in .zkey format")))
+        .subcommand(SubCommand::with_name("zkey-solidity")
+            .about("Writes a Solidity contract verifying the proofs of a Groth16 key")
+            .arg(Arg::with_name("zkey").required(true).help("Proving key, in .zkey format"))
+            .arg(Arg::with_name("output").required(true).help("Output contract, a .sol file")))
+        .subcommand(SubCommand::with_name("zkey-export-vk")
+            .about("Writes the verification key of a Groth16 key in the snarkjs JSON format")
+            .arg(Arg::with_name("zkey").required(true).help("Proving key, in .zkey format"))
+            .arg(Arg::with_name("output").required(true).help("Output verification key, a .json file")))
+        .subcommand(SubCommand::with_name("verify")
+            .about("Checks a Groth16 or PLONK proof given as snarkjs JSON files")
+            .arg(Arg::with_name("verification_key").required(true).help("Verification key, as verification_key.json"))
+            .arg(Arg::with_name("public").required(true).help("Public signals, as public.json"))
+            .arg(Arg::with_name("proof").required(true).help("Proof, as proof.json")));
+
+    let matches = match app.get_matches_safe() {
+        Ok(matches) => matches,
+        Err(error) => {
+            if error.use_stderr() {
+                eprintln!("{}", error.message);
+                std::process::exit(2);
+            }
+            error.exit();
+        }
+    };
+
+    if let Some(threads) = matches.value_of("threads") {
+        match threads.parse::<usize>() {
+            Ok(threads) if threads > 0 => libsnarkrs::parallel::set_threads(threads),
+            _ => {
+                eprintln!("Invalid number of threads {}", threads);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let result = match matches.subcommand() {
+        ("check", Some(matches)) => commands::check::run(matches),
+        ("parse", Some(matches)) => commands::parse::run(matches),
+        ("compile", Some(matches)) => commands::compile::run(matches),
+        ("check-witness", Some(matches)) => commands::check_witness::run(matches),
+        ("fmt", Some(matches)) => commands::fmt::run(matches),
+        ("lint", Some(matches)) => commands::lint::run(matches),
+        ("audit", Some(matches)) => commands::audit::run(matches),
+        ("stats", Some(matches)) => commands::stats::run(matches),
+        ("zkey", Some(matches)) => commands::zkey::run(matches),
+        ("zkey-new", Some(matches)) => commands::ceremony::new(matches),
+        ("zkey-contribute", Some(matches)) => commands::ceremony::contribute(matches),
+        ("zkey-verify", Some(matches)) => commands::ceremony::verify(matches),
+        ("zkey-solidity", Some(matches)) => commands::zkey::solidity(matches),
+        ("zkey-export-vk", Some(matches)) => commands::zkey::export_vk(matches),
+        ("verify", Some(matches)) => commands::verify::run(matches),
+        _ => Err(Failure::Error(matches.usage().to_string()))
+    };
+
+    if let Err(failure) = result {
+        if let Failure::Error(reason) = &failure {
+            eprintln!("{}", reason);
+        }
+        std::process::exit(failure.exit_code());
+    }
+
+}
@@ -0,0 +1,52 @@
+use clap::ArgMatches;
+
+use libsnarkrs::field::PrimeField;
+use libsnarkrs::elaborator::{self, underconstrained};
+use libsnarkrs::elaborator::underconstrained::IssueKind;
+use libsnarkrs::parser::compile;
+
+use commands::{self, CommandResult, Failure};
+
+///
+/// `snarkrs audit <file>`: elaborates the main component and reports the signals its constraints
+/// leave free, failing if there are any. Signals only conditionally determined are reported as
+/// warnings.
+///
+pub fn run(matches: & ArgMatches) -> CommandResult {
+    with_prime!(matches, audit)
+}
+
+fn audit<F: PrimeField>(matches: & ArgMatches) -> CommandResult {
+
+    let path = commands::source_path(matches, "file")?;
+    let ctx = compile::build_context_with_include_paths(&path, &commands::include_paths(matches));
+
+    commands::report_errors(ctx.errors())?;
+
+    if !elaborator::declares_main(&ctx) {
+        return Err(Failure::Error(format!("{} does not declare a main component", path.display())));
+    }
+
+    let (circuit, r1cs) = commands::elaborate::<F>(&ctx)?;
+    let issues = underconstrained::find_issues(&circuit, &r1cs);
+
+    for issue in &issues {
+        commands::print(&format!("{}\n", issue))?;
+    }
+
+    let conditional = issues.iter().filter(|issue| issue.kind == IssueKind::ConditionallyDetermined).count();
+    let failures = issues.len() - conditional;
+
+    if conditional > 0 {
+        eprintln!("{} conditionally determined signal{} found", conditional, if conditional > 1 { "s" } else { "" });
+    }
+
+    if failures == 0 {
+        commands::print(&format!("No under-constrained signal found in {} signals\n", circuit.signals.len()))?;
+        Ok(())
+    } else {
+        eprintln!("{} under-constrained signal{} found", failures, if failures > 1 { "s" } else { "" });
+        Err(Failure::Rejected)
+    }
+
+}
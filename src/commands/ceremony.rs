@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use clap::ArgMatches;
+
+use libsnarkrs::blake2b;
+use libsnarkrs::circuit::r1cs_file::R1CSFile;
+use libsnarkrs::field::bn254::Fr;
+use libsnarkrs::groth16::{phase2, zkey, ProvingKey};
+use libsnarkrs::ptau::Ptau;
+use libsnarkrs::rng::OsRng;
+
+use commands::{self, CommandResult, Failure};
+
+fn load_r1cs(matches: & ArgMatches) -> Result<R1CSFile<Fr>, Failure> {
+    let path = Path::new(matches.value_of("r1cs").unwrap());
+    R1CSFile::load(path).map_err(|error| Failure::Error(format!("{}: {}", path.display(), error)))
+}
+
+fn load_ptau(matches: & ArgMatches) -> Result<Ptau, Failure> {
+    let path = Path::new(matches.value_of("ptau").unwrap());
+    Ptau::load(path).map_err(|error| Failure::Error(format!("{}: {}", path.display(), error)))
+}
+
+fn load_zkey(path: & str) -> Result<ProvingKey, Failure> {
+    zkey::load(Path::new(path)).map_err(|error| Failure::Error(format!("{}: {}", path, error)))
+}
+
+fn save_zkey(pk: & ProvingKey, path: & str) -> CommandResult {
+    zkey::save(pk, Path::new(path)).map_err(|error| Failure::Error(format!("{}: {}", path, error)))?;
+    commands::print(&format!("Written {}\n", path))?;
+    Ok(())
+}
+
+fn os_rng() -> Result<OsRng, Failure> {
+    OsRng::new().map_err(|error| Failure::Error(error.to_string()))
+}
+
+///
+/// `snarkrs zkey-new <r1cs> <ptau> <zkey>`: derives the initial phase 2 key of a circuit from
+/// powers of tau.
+///
+pub fn new(matches: & ArgMatches) -> CommandResult {
+
+    let r1cs = load_r1cs(matches)?;
+    let ptau = load_ptau(matches)?;
+
+    ptau.check(& mut os_rng()?).map_err(|error| Failure::Error(error.to_string()))?;
+
+    let pk = phase2::new_key(&r1cs.r1cs, &ptau).map_err(|error| Failure::Error(error.to_string()))?;
+    commands::print(&format!("Circuit hash: {}\n", blake2b::to_hex(&pk.mpc.as_ref().unwrap().cs_hash)))?;
+
+    save_zkey(&pk, matches.value_of("zkey").unwrap())
+
+}
+
+///
+/// `snarkrs zkey-contribute <input> <output>`: adds a contribution with fresh randomness and prints
+/// its hash.
+///
+pub fn contribute(matches: & ArgMatches) -> CommandResult {
+
+    let mut pk = load_zkey(matches.value_of("input").unwrap())?;
+
+    let hash = phase2::contribute(& mut pk, matches.value_of("name"), & mut os_rng()?).map_err(|error| Failure::Error(error.to_string()))?;
+    commands::print(&format!("Contribution hash: {}\n", blake2b::to_hex(&hash)))?;
+
+    save_zkey(&pk, matches.value_of("output").unwrap())
+
+}
+
+///
+/// `snarkrs zkey-verify <r1cs> <ptau> <zkey>`: checks that a key comes from the circuit and the
+/// powers of tau through valid contributions, and lists them.
+///
+pub fn verify(matches: & ArgMatches) -> CommandResult {
+
+    let r1cs = load_r1cs(matches)?;
+    let ptau = load_ptau(matches)?;
+    let pk = load_zkey(matches.value_of("zkey").unwrap())?;
+
+    let mut rng = os_rng()?;
+    ptau.check(& mut rng).map_err(|error| Failure::Error(error.to_string()))?;
+
+    if let Err(error) = phase2::verify_contributions(&pk, &r1cs.r1cs, &ptau, & mut rng) {
+        eprintln!("{}", error);
+        return Err(Failure::Rejected);
+    }
+
+    let contributions = &pk.mpc.as_ref().unwrap().contributions;
+    for (idx, contribution) in contributions.iter().enumerate() {
+        commands::print(&format!("#{} {}: {}\n", idx + 1, contribution.name.as_deref().unwrap_or("<unnamed>"), blake2b::to_hex(&contribution.transcript)))?;
+    }
+    commands::print(&format!("The key is valid, with {} contribution{}\n", contributions.len(), if contributions.len() == 1 { "" } else { "s" }))?;
+    if contributions.is_empty() {
+        eprintln!("warning: without contributions, the toxic waste of the key is known and proofs can be forged");
+    }
+
+    Ok(())
+
+}
@@ -0,0 +1,33 @@
+use clap::ArgMatches;
+
+use libsnarkrs::field::PrimeField;
+use libsnarkrs::elaborator;
+use libsnarkrs::parser::compile;
+
+use commands::{self, CommandResult};
+
+///
+/// `snarkrs check <file>`: parses the file and its includes and, if it declares a `main`
+/// component, elaborates it and generates its constraints.
+///
+pub fn run(matches: & ArgMatches) -> CommandResult {
+    with_prime!(matches, check)
+}
+
+fn check<F: PrimeField>(matches: & ArgMatches) -> CommandResult {
+
+    let path = commands::source_path(matches, "file")?;
+    let ctx = compile::build_context_with_include_paths(&path, &commands::include_paths(matches));
+
+    commands::report_errors(ctx.errors())?;
+
+    if elaborator::declares_main(&ctx) {
+        let (circuit, r1cs) = commands::elaborate::<F>(&ctx)?;
+        commands::print(&format!("main: {} ({} components, {} signals, {} constraints)\n", circuit.main().template, circuit.components.len(), circuit.signals.len(), r1cs.constraints.len()))?;
+    }
+
+    commands::print(&format!("No errors found in {} file(s)\n", ctx.files().count()))?;
+
+    Ok(())
+
+}
@@ -0,0 +1,57 @@
+use std::path::{Path, PathBuf};
+
+use clap::ArgMatches;
+
+use libsnarkrs::field::PrimeField;
+use libsnarkrs::circuit::check;
+use libsnarkrs::circuit::r1cs_file::R1CSFile;
+use libsnarkrs::circuit::sym::SymbolMap;
+use libsnarkrs::witness::wtns;
+
+use commands::{self, CommandResult, Failure};
+
+///
+/// `snarkrs check-witness <r1cs> <wtns>`: checks a witness against a constraint system. The symbol
+/// map defaults to the `.sym` file next to the constraint system, if there is one.
+///
+pub fn run(matches: & ArgMatches) -> CommandResult {
+    with_prime!(matches, check_witness)
+}
+
+fn check_witness<F: PrimeField>(matches: & ArgMatches) -> CommandResult {
+
+    let r1cs_path = Path::new(matches.value_of("r1cs").unwrap());
+    let witness_path = Path::new(matches.value_of("witness").unwrap());
+
+    let file: R1CSFile<F> = R1CSFile::load(r1cs_path).map_err(|error| Failure::Error(format!("{}: {}", r1cs_path.display(), error)))?;
+    let witness: Vec<F> = wtns::load(witness_path).map_err(|error| Failure::Error(format!("{}: {}", witness_path.display(), error)))?;
+
+    let symbols = match matches.value_of("sym") {
+        Some(path) => Some(SymbolMap::load(Path::new(path)).map_err(|error| Failure::Error(format!("{}: {:?}", path, error)))?),
+        None => {
+            let default: PathBuf = r1cs_path.with_extension("sym");
+            if default.is_file() {
+                SymbolMap::load(&default).ok()
+            } else {
+                None
+            }
+        }
+    };
+
+    let failures = check::check_witness(&file.r1cs, &witness).map_err(|error| Failure::Error(error.to_string()))?;
+
+    for failure in &failures {
+        let constraint = &file.r1cs.constraints[failure.index];
+        commands::print(&format!("Constraint #{} is not satisfied: {}\n", failure.index, check::render_constraint(constraint, symbols.as_ref())))?;
+        commands::print(&format!("\tA = {}, B = {}, C = {}\n", failure.a, failure.b, failure.c))?;
+    }
+
+    if failures.is_empty() {
+        commands::print(&format!("All {} constraints are satisfied\n", file.r1cs.constraints.len()))?;
+        Ok(())
+    } else {
+        commands::print(&format!("{} of {} constraints are not satisfied\n", failures.len(), file.r1cs.constraints.len()))?;
+        Err(Failure::Rejected)
+    }
+
+}
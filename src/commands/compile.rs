@@ -0,0 +1,120 @@
+use std::path::{Path, PathBuf};
+
+use clap::ArgMatches;
+
+use libsnarkrs::field::PrimeField;
+use libsnarkrs::codegen;
+use libsnarkrs::circuit::optimize;
+use libsnarkrs::circuit::r1cs_file::R1CSFile;
+use libsnarkrs::parser::compile;
+use libsnarkrs::witness::{self, input, wtns};
+
+use commands::{self, CommandResult, Failure};
+
+fn write_error(path: & Path, error: std::io::Error) -> Failure {
+    Failure::Error(format!("{}: {}", path.display(), error))
+}
+
+///
+/// `snarkrs compile <file>`: elaborates the main component and writes `<name>.r1cs` and
+/// `<name>.sym` to the output directory, `<name>.wtns` when inputs are given, the C witness
+/// calculator `<name>.c` with `--c`, the Rust one `<name>.rs` with `--rust` and the WebAssembly
+/// one `<name>.wasm`, with its text `<name>.wat`, with `--wasm`.
+///
+pub fn run(matches: & ArgMatches) -> CommandResult {
+    with_prime!(matches, compile)
+}
+
+fn compile<F: PrimeField>(matches: & ArgMatches) -> CommandResult {
+
+    let path = commands::source_path(matches, "file")?;
+    let ctx = compile::build_context_with_include_paths(&path, &commands::include_paths(matches));
+
+    commands::report_errors(ctx.errors())?;
+
+    let (circuit, mut r1cs) = commands::elaborate::<F>(&ctx)?;
+    let original_symbols = circuit.symbols();
+    let mut symbols = original_symbols.clone();
+
+    if matches.is_present("simplify") {
+        commands::print(&format!("{}", optimize::simplify(& mut r1cs, & mut symbols)))?;
+    }
+
+    let output = PathBuf::from(matches.value_of("output").unwrap_or("."));
+    let name = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| "circuit".to_string());
+
+    std::fs::create_dir_all(&output).map_err(|error| write_error(&output, error))?;
+
+    let witness = match matches.value_of("input") {
+        Some(input_path) => {
+
+            let inputs = input::load_inputs::<F>(Path::new(input_path)).map_err(|error| Failure::Error(format!("{}: {}", input_path, error)))?;
+
+            match witness::calculate_witness(&circuit, &inputs) {
+                Ok(witness) => Some(optimize::remap_witness(&witness, &original_symbols, &symbols, r1cs.n_wires)),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    return Err(Failure::Rejected);
+                }
+            }
+
+        },
+        None => None
+    };
+
+    let program = match matches.is_present("c") || matches.is_present("rust") || matches.is_present("wasm") {
+        true => Some(codegen::lower(&circuit, &symbols, r1cs.n_wires).map_err(|error| {
+            eprintln!("{}", error);
+            Failure::Rejected
+        })?),
+        false => None
+    };
+
+    let r1cs_path = output.join(format!("{}.r1cs", name));
+    let sym_path = output.join(format!("{}.sym", name));
+
+    commands::print(&format!("template: {}\n", circuit.main().template))?;
+    commands::print(&format!("wires: {}, constraints: {}\n", r1cs.n_wires, r1cs.constraints.len()))?;
+    commands::print(&format!("outputs: {}, public inputs: {}, private inputs: {}\n", r1cs.n_outputs, r1cs.n_public_inputs, r1cs.n_private_inputs))?;
+
+    R1CSFile::new(r1cs, &symbols).save(&r1cs_path).map_err(|error| write_error(&r1cs_path, error))?;
+    symbols.save(&sym_path).map_err(|error| write_error(&sym_path, error))?;
+
+    commands::print(&format!("Written {}\n", r1cs_path.display()))?;
+    commands::print(&format!("Written {}\n", sym_path.display()))?;
+
+    if let Some(witness) = witness {
+        let wtns_path = output.join(format!("{}.wtns", name));
+        wtns::save(&witness, &wtns_path).map_err(|error| write_error(&wtns_path, error))?;
+        commands::print(&format!("Written {}\n", wtns_path.display()))?;
+    }
+
+    if let Some(program) = program {
+
+        if matches.is_present("c") {
+            let c_path = output.join(format!("{}.c", name));
+            std::fs::write(&c_path, codegen::c::generate(&program)).map_err(|error| write_error(&c_path, error))?;
+            commands::print(&format!("Written {}\n", c_path.display()))?;
+        }
+
+        if matches.is_present("rust") {
+            let rust_path = output.join(format!("{}.rs", name));
+            std::fs::write(&rust_path, codegen::rust::generate(&program)).map_err(|error| write_error(&rust_path, error))?;
+            commands::print(&format!("Written {}\n", rust_path.display()))?;
+        }
+
+        if matches.is_present("wasm") {
+            let text = codegen::wasm::generate_text(&program);
+            let wat_path = output.join(format!("{}.wat", name));
+            let wasm_path = output.join(format!("{}.wasm", name));
+            std::fs::write(&wat_path, &text).map_err(|error| write_error(&wat_path, error))?;
+            std::fs::write(&wasm_path, codegen::wasm::assembler::assemble(&text).map_err(Failure::Error)?).map_err(|error| write_error(&wasm_path, error))?;
+            commands::print(&format!("Written {}\n", wat_path.display()))?;
+            commands::print(&format!("Written {}\n", wasm_path.display()))?;
+        }
+
+    }
+
+    Ok(())
+
+}
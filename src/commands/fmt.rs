@@ -0,0 +1,69 @@
+use clap::ArgMatches;
+
+use libsnarkrs::formatter::{self, FormatOptions};
+use libsnarkrs::parser::ast;
+use libsnarkrs::parser::errors;
+
+use commands::{self, CommandResult, Failure};
+
+fn options(matches: & ArgMatches) -> Result<FormatOptions, Failure> {
+
+    if matches.is_present("tabs") {
+        return Ok(FormatOptions { indent: "\t".to_string() });
+    }
+
+    match matches.value_of("indent") {
+        Some(indent) => match indent.parse::<usize>() {
+            Ok(width) => Ok(FormatOptions { indent: " ".repeat(width) }),
+            Err(_) => Err(Failure::Error(format!("Invalid indent width {}", indent)))
+        },
+        None => Ok(FormatOptions::default())
+    }
+
+}
+
+///
+/// `snarkrs fmt <files>...`: prints the formatted files. `--write` rewrites them in place instead,
+/// `--check` only lists the files that are not formatted and is rejected if there are any.
+///
+pub fn run(matches: & ArgMatches) -> CommandResult {
+
+    let options = options(matches)?;
+    let mut unformatted = 0;
+
+    for file in matches.values_of("file").unwrap() {
+
+        let path = std::fs::canonicalize(file).map_err(|error| Failure::Error(format!("{}: {}", file, error)))?;
+
+        let parsed = match ast::parse_file(&path) {
+            Ok(parsed) => parsed,
+            Err(error) => return commands::report_errors(&[errors::from_pest_parsing(&path, &error)])
+        };
+
+        let formatted = match formatter::format_root(&parsed.root, &options) {
+            Ok(formatted) => formatted,
+            Err(error) => return commands::report_errors(&[errors::from_matching(&path, &error)])
+        };
+
+        if matches.is_present("check") {
+            if formatted != parsed.root.source {
+                commands::print(&format!("{}\n", file))?;
+                unformatted += 1;
+            }
+        } else if matches.is_present("write") {
+            if formatted != parsed.root.source {
+                std::fs::write(&path, formatted).map_err(|error| Failure::Error(format!("{}: {}", file, error)))?;
+            }
+        } else {
+            commands::print(&formatted)?;
+        }
+
+    }
+
+    if unformatted > 0 {
+        Err(Failure::Rejected)
+    } else {
+        Ok(())
+    }
+
+}
@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use clap::ArgMatches;
+
+use libsnarkrs::lint::{self, LintConfig};
+use libsnarkrs::parser::ast;
+use libsnarkrs::parser::errors;
+
+use commands::{self, CommandResult, Failure};
+
+///
+/// Configuration looked up in the current directory when `--config` is not given.
+///
+const DEFAULT_CONFIG: & str = "snarkrs-lint.json";
+
+fn config(matches: & ArgMatches) -> Result<LintConfig, Failure> {
+    match matches.value_of("config") {
+        Some(path) => LintConfig::load(Path::new(path)).map_err(Failure::Error),
+        None if Path::new(DEFAULT_CONFIG).is_file() => LintConfig::load(Path::new(DEFAULT_CONFIG)).map_err(Failure::Error),
+        None => Ok(LintConfig::default())
+    }
+}
+
+///
+/// `snarkrs lint <files>...`: prints the warnings of the enabled lint rules, and is rejected if
+/// there are any. Includes are not followed.
+///
+pub fn run(matches: & ArgMatches) -> CommandResult {
+
+    let config = config(matches)?;
+    let mut warnings = 0;
+
+    for file in matches.values_of("file").unwrap() {
+
+        let path = std::fs::canonicalize(file).map_err(|error| Failure::Error(format!("{}: {}", file, error)))?;
+
+        let parsed = match ast::parse_file(&path) {
+            Ok(parsed) => parsed,
+            Err(error) => return commands::report_errors(&[errors::from_pest_parsing(&path, &error)])
+        };
+
+        let lints = match lint::lint_file(&parsed, &config) {
+            Ok(lints) => lints,
+            Err(error) => return commands::report_errors(&[errors::from_matching(&path, &error)])
+        };
+
+        for lint in lints {
+            commands::print(&format!("{}\n", lint))?;
+            warnings += 1;
+        }
+
+    }
+
+    if warnings > 0 {
+        eprintln!("{} warning{} found", warnings, if warnings > 1 { "s" } else { "" });
+        Err(Failure::Rejected)
+    } else {
+        Ok(())
+    }
+
+}
@@ -0,0 +1,139 @@
+///
+/// Runs a command generic over the prime field, the prime being given by the `prime` argument.
+///
+macro_rules! with_prime {
+    ($matches: expr, $command: ident) => {
+        match $matches.value_of("prime").unwrap_or("bn128") {
+            "bn128" => $command::<::libsnarkrs::field::bn254::Fr>($matches),
+            "bls12381" => $command::<::libsnarkrs::field::bls12_381::Fr>($matches),
+            prime => Err(::commands::Failure::Error(format!("Unsupported prime {}", prime)))
+        }
+    };
+}
+
+pub mod check;
+pub mod parse;
+pub mod compile;
+pub mod check_witness;
+pub mod fmt;
+pub mod lint;
+pub mod audit;
+pub mod stats;
+pub mod zkey;
+pub mod ceremony;
+pub mod verify;
+
+use std::path::PathBuf;
+
+use clap::ArgMatches;
+
+use libsnarkrs::field::PrimeField;
+use libsnarkrs::circuit::r1cs::R1CS;
+use libsnarkrs::elaborator::{self, ElaboratedCircuit};
+use libsnarkrs::parser::compile::Context;
+use libsnarkrs::parser::errors::CompileError;
+
+///
+/// Primes the commands can work with, named as in circom.
+///
+pub const PRIMES: & [& str] = &["bn128", "bls12381"];
+
+///
+/// Why a command did not succeed.
+///
+#[derive(Debug)]
+pub enum Failure {
+
+    ///
+    /// The input was processed and found invalid (compile errors, unsatisfied constraints ...), the
+    /// details have already been reported. Exit code 1.
+    ///
+    Rejected,
+
+    ///
+    /// The command could not run: unreadable file, invalid argument ... Exit code 2.
+    ///
+    Error(std::string::String)
+
+}
+
+impl Failure {
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Failure::Rejected => 1,
+            Failure::Error(_) => 2
+        }
+    }
+
+}
+
+pub type CommandResult = Result<(), Failure>;
+
+///
+/// Path given as a required argument, checked to exist and made absolute: include resolution and
+/// error reporting rely on absolute paths.
+///
+pub fn source_path(matches: & ArgMatches, name: & str) -> Result<PathBuf, Failure> {
+    let path = matches.value_of(name).unwrap();
+    std::fs::canonicalize(path).map_err(|error| Failure::Error(format!("{}: {}", path, error)))
+}
+
+pub fn include_paths(matches: & ArgMatches) -> Vec<PathBuf> {
+    match matches.values_of("include") {
+        Some(values) => values.map(PathBuf::from).collect(),
+        None => Vec::new()
+    }
+}
+
+///
+/// Writes the output of a command to stdout. A reader closing the pipe early (`| head`) is not a
+/// failure of the command.
+///
+pub fn print(output: & str) -> CommandResult {
+
+    use std::io::Write;
+
+    let stdout = std::io::stdout();
+    let mut handle = stdout.lock();
+
+    match handle.write_all(output.as_bytes()).and_then(|_| handle.flush()) {
+        Err(ref error) if error.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        Err(error) => Err(Failure::Error(format!("Cannot write the output: {}", error))),
+        Ok(()) => Ok(())
+    }
+
+}
+
+pub fn report_errors(errors: & [CompileError]) -> CommandResult {
+
+    for error in errors {
+        eprintln!("{}", error);
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        eprintln!("{} error{} found", errors.len(), if errors.len() > 1 { "s" } else { "" });
+        Err(Failure::Rejected)
+    }
+
+}
+
+///
+/// Elaborates the main component of a context free of errors and generates its constraint system,
+/// reporting elaboration errors.
+///
+pub fn elaborate<F: PrimeField>(ctx: & Context) -> Result<(ElaboratedCircuit<F>, R1CS<F>), Failure> {
+
+    let circuit = elaborator::elaborate::<F>(ctx).map_err(|error| {
+        report_errors(&[CompileError::ElaborationError(error)]).unwrap_err()
+    })?;
+
+    let r1cs = circuit.r1cs().map_err(|error| {
+        report_errors(&[CompileError::ElaborationError(error)]).unwrap_err()
+    })?;
+
+    Ok((circuit, r1cs))
+
+}
@@ -0,0 +1,70 @@
+use std::fmt::Write;
+
+use clap::ArgMatches;
+
+use libsnarkrs::parser::ast::{self, tokens::Token};
+use libsnarkrs::parser::dump;
+use libsnarkrs::parser::errors;
+use libsnarkrs::parser::matchers;
+
+use commands::{self, CommandResult};
+
+fn write_token(output: & mut std::string::String, token: & Token, depth: usize) {
+    match token {
+        Token::Terminal(terminal) => {
+            let _ = writeln!(output, "{}{:?} [{}, {}] {:?}", "  ".repeat(depth), terminal.rule, terminal.span.0, terminal.span.1, terminal.content);
+        },
+        Token::NonTerminal(non_terminal) => {
+            let _ = writeln!(output, "{}{:?} [{}, {}]", "  ".repeat(depth), non_terminal.rule, non_terminal.span.0, non_terminal.span.1);
+            for subrule in &non_terminal.subrules {
+                write_token(output, subrule, depth + 1);
+            }
+        }
+    }
+}
+
+///
+/// `snarkrs parse <file>`: parses a single file, includes are not followed. `--dump tokens` prints
+/// the token tree, `--dump ast` the typed AST, as indented text or in the `--format` given. A format
+/// without dump prints the typed AST.
+///
+pub fn run(matches: & ArgMatches) -> CommandResult {
+
+    let path = commands::source_path(matches, "file")?;
+
+    let file = match ast::parse_file(&path) {
+        Ok(file) => file,
+        Err(error) => return commands::report_errors(&[errors::from_pest_parsing(&path, &error)])
+    };
+
+    let format = matches.value_of("format").unwrap_or("text");
+    let dump = match matches.value_of("dump") {
+        Some(dump) => dump,
+        None if format != "text" => "ast",
+        None => return Ok(())
+    };
+
+    if dump == "tokens" {
+        return match format {
+            "json" => commands::print(&format!("{}\n", serde_json::to_string_pretty(&dump::tokens_to_json(&file.root)).unwrap())),
+            "sexp" => commands::print(&dump::tokens_to_sexp(&file.root)),
+            _ => {
+                let mut output = std::string::String::new();
+                file.root.ast.iter().for_each(|token| write_token(& mut output, token, 0));
+                commands::print(&output)
+            }
+        };
+    }
+
+    let circuit = match matchers::circuit::process_circuit(&file.root) {
+        Ok(circuit) => circuit,
+        Err(error) => return commands::report_errors(&[errors::from_matching(&path, &error)])
+    };
+
+    match format {
+        "json" => commands::print(&format!("{}\n", serde_json::to_string_pretty(&dump::circuit_to_json(&circuit)).unwrap())),
+        "sexp" => commands::print(&dump::circuit_to_sexp(&circuit)),
+        _ => commands::print(&format!("{:#?}\n", circuit))
+    }
+
+}
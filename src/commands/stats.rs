@@ -0,0 +1,48 @@
+use clap::ArgMatches;
+
+use libsnarkrs::field::PrimeField;
+use libsnarkrs::elaborator::{self, stats::CircuitStats};
+use libsnarkrs::parser::compile;
+use libsnarkrs::parser::errors::CompileError;
+
+use commands::{self, CommandResult, Failure};
+
+///
+/// `snarkrs stats <file>`: elaborates the main component and prints the size of the circuit, as a
+/// table listing the `--top` templates contributing the most constraints or, with `--format json`,
+/// as JSON listing all of them.
+///
+pub fn run(matches: & ArgMatches) -> CommandResult {
+    with_prime!(matches, stats)
+}
+
+fn stats<F: PrimeField>(matches: & ArgMatches) -> CommandResult {
+
+    let top = match matches.value_of("top") {
+        Some(top) => top.parse::<usize>().map_err(|_| Failure::Error(format!("Invalid template count {}", top)))?,
+        None => 10
+    };
+
+    let path = commands::source_path(matches, "file")?;
+    let ctx = compile::build_context_with_include_paths(&path, &commands::include_paths(matches));
+
+    commands::report_errors(ctx.errors())?;
+
+    if !elaborator::declares_main(&ctx) {
+        return Err(Failure::Error(format!("{} does not declare a main component", path.display())));
+    }
+
+    let circuit = elaborator::elaborate::<F>(&ctx).map_err(|error| {
+        commands::report_errors(&[CompileError::ElaborationError(error)]).unwrap_err()
+    })?;
+
+    let stats = CircuitStats::new(&circuit).map_err(|error| {
+        commands::report_errors(&[CompileError::ElaborationError(error)]).unwrap_err()
+    })?;
+
+    match matches.value_of("format").unwrap_or("table") {
+        "json" => commands::print(&format!("{}\n", serde_json::to_string_pretty(&stats).unwrap())),
+        _ => commands::print(&stats.to_table(top))
+    }
+
+}
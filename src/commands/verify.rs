@@ -0,0 +1,57 @@
+use std::path::Path;
+
+use clap::ArgMatches;
+
+use libsnarkrs::curve::bn254::json::{self, JsonError};
+use libsnarkrs::field::bn254::Fr;
+use libsnarkrs::{groth16, plonk};
+
+use serde_json::Value;
+
+use commands::{self, CommandResult, Failure};
+
+fn load(matches: & ArgMatches, name: & str) -> Result<Value, Failure> {
+    let path = Path::new(matches.value_of(name).unwrap());
+    json::load(path).map_err(|error| Failure::Error(format!("{}: {}", path.display(), error)))
+}
+
+fn invalid(matches: & ArgMatches, name: & str, error: JsonError) -> Failure {
+    Failure::Error(format!("{}: {}", matches.value_of(name).unwrap(), error))
+}
+
+///
+/// `snarkrs verify <verification_key> <public> <proof>`: checks a proof given as snarkjs JSON files,
+/// the protocol being the one of the verification key.
+///
+pub fn run(matches: & ArgMatches) -> CommandResult {
+
+    let vk = load(matches, "verification_key")?;
+    let public = load(matches, "public")?;
+    let proof = load(matches, "proof")?;
+
+    let public: Vec<Fr> = json::scalars_from_json(&public, "public signals").map_err(|error| invalid(matches, "public", error))?;
+
+    let valid = match vk.get("protocol").and_then(Value::as_str) {
+        Some("groth16") => {
+            let vk = groth16::json::verifying_key_from_json(&vk).map_err(|error| invalid(matches, "verification_key", error))?;
+            let proof = groth16::json::proof_from_json(&proof).map_err(|error| invalid(matches, "proof", error))?;
+            groth16::verify(&vk, &public, &proof).map_err(|error| Failure::Error(error.to_string()))?
+        },
+        Some("plonk") => {
+            let vk = plonk::json::verifying_key_from_json(&vk).map_err(|error| invalid(matches, "verification_key", error))?;
+            let proof = plonk::json::proof_from_json(&proof).map_err(|error| invalid(matches, "proof", error))?;
+            plonk::verify(&vk, &public, &proof).map_err(|error| Failure::Error(error.to_string()))?
+        },
+        Some(protocol) => return Err(Failure::Error(format!("Unsupported protocol {}", protocol))),
+        None => return Err(Failure::Error(format!("{}: missing protocol", matches.value_of("verification_key").unwrap())))
+    };
+
+    if valid {
+        commands::print("The proof is valid\n")?;
+        Ok(())
+    } else {
+        eprintln!("The proof is invalid");
+        Err(Failure::Rejected)
+    }
+
+}
@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use clap::ArgMatches;
+
+use libsnarkrs::circuit::r1cs_file::R1CSFile;
+use libsnarkrs::field::bn254::Fr;
+use libsnarkrs::curve::bn254::json;
+use libsnarkrs::groth16::{self, solidity, zkey, Matrix};
+use libsnarkrs::rng::OsRng;
+
+use commands::{self, CommandResult, Failure};
+
+///
+/// `snarkrs zkey <zkey> [--r1cs <r1cs>]`: prints the sizes of a Groth16 proving key and, given the
+/// constraint system it was made for, checks that they correspond.
+///
+pub fn run(matches: & ArgMatches) -> CommandResult {
+
+    let zkey_path = Path::new(matches.value_of("zkey").unwrap());
+    let pk = zkey::load(zkey_path).map_err(|error| Failure::Error(format!("{}: {}", zkey_path.display(), error)))?;
+
+    let count = |matrix: Matrix| pk.coefficients.iter().filter(|coefficient| coefficient.matrix == matrix).count();
+
+    commands::print("protocol:       groth16\n")?;
+    commands::print(&format!("variables:      {}\n", pk.n_vars))?;
+    commands::print(&format!("public signals: {}\n", pk.n_public))?;
+    commands::print(&format!("domain size:    {}\n", pk.domain_size))?;
+    commands::print(&format!("coefficients:   {} (A: {}, B: {})\n", pk.coefficients.len(), count(Matrix::A), count(Matrix::B)))?;
+    if let Some(mpc) = &pk.mpc {
+        commands::print(&format!("contributions:  {}\n", mpc.contributions.len()))?;
+    }
+
+    let r1cs_path = match matches.value_of("r1cs") {
+        Some(path) => Path::new(path),
+        None => return Ok(())
+    };
+
+    let file: R1CSFile<Fr> = R1CSFile::load(r1cs_path).map_err(|error| Failure::Error(format!("{}: {}", r1cs_path.display(), error)))?;
+    let mut rng = OsRng::new().map_err(|error| Failure::Error(error.to_string()))?;
+
+    match zkey::check_r1cs(&pk, &file.r1cs, & mut rng) {
+        Ok(()) => {
+            commands::print(&format!("The key matches {}\n", r1cs_path.display()))?;
+            Ok(())
+        },
+        Err(mismatch) => {
+            eprintln!("{}", mismatch);
+            Err(Failure::Rejected)
+        }
+    }
+
+}
+
+///
+/// `snarkrs zkey-solidity <zkey> <output>`: writes a Solidity contract verifying the proofs of a
+/// Groth16 proving key.
+///
+pub fn solidity(matches: & ArgMatches) -> CommandResult {
+
+    let zkey_path = Path::new(matches.value_of("zkey").unwrap());
+    let pk = zkey::load(zkey_path).map_err(|error| Failure::Error(format!("{}: {}", zkey_path.display(), error)))?;
+
+    let output = matches.value_of("output").unwrap();
+    let contract = solidity::verifier_contract(&pk.verifying_key());
+    std::fs::write(output, contract).map_err(|error| Failure::Error(format!("{}: {}", output, error)))?;
+
+    commands::print(&format!("Written {}\n", output))?;
+    Ok(())
+
+}
+
+///
+/// `snarkrs zkey-export-vk <zkey> <output>`: writes the verification key of a Groth16 proving key
+/// in the snarkjs `verification_key.json` format.
+///
+pub fn export_vk(matches: & ArgMatches) -> CommandResult {
+
+    let zkey_path = Path::new(matches.value_of("zkey").unwrap());
+    let pk = zkey::load(zkey_path).map_err(|error| Failure::Error(format!("{}: {}", zkey_path.display(), error)))?;
+
+    let output = matches.value_of("output").unwrap();
+    let document = groth16::json::verifying_key_to_json(&pk.verifying_key());
+    json::save(&document, Path::new(output)).map_err(|error| Failure::Error(format!("{}: {}", output, error)))?;
+
+    commands::print(&format!("Written {}\n", output))?;
+    Ok(())
+
+}
@@ -0,0 +1,243 @@
+use std::io::{Read, Write};
+
+use field::PrimeField;
+
+///
+/// Container shared by the iden3 binary formats (`.wtns`, `.r1cs`, `.zkey`):
+///
+/// `magic[4] version:u32 n_sections:u32 (type:u32 size:u64 data[size])*`
+///
+/// All integers are little endian. Section types are format specific and can appear in any order.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinFile {
+    pub magic: [u8; 4],
+    pub version: u32,
+    pub sections: Vec<(u32, Vec<u8>)>
+}
+
+#[derive(Debug)]
+pub enum BinFileError {
+    IOError(std::io::Error),
+    FormatError(std::string::String)
+}
+
+impl std::fmt::Display for BinFileError {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BinFileError::IOError(error) => write!(formatter, "{}", error),
+            BinFileError::FormatError(reason) => write!(formatter, "Invalid file: {}", reason)
+        }
+    }
+}
+
+impl From<std::io::Error> for BinFileError {
+    fn from(error: std::io::Error) -> BinFileError {
+        BinFileError::IOError(error)
+    }
+}
+
+impl BinFile {
+
+    pub fn new(magic: & [u8; 4], version: u32) -> BinFile {
+        BinFile {
+            magic: *magic,
+            version,
+            sections: Vec::new()
+        }
+    }
+
+    pub fn add_section(& mut self, section_type: u32, data: Vec<u8>) {
+        self.sections.push((section_type, data));
+    }
+
+    ///
+    /// Data of the first section of the given type.
+    ///
+    pub fn section(&self, section_type: u32) -> Option<& [u8]> {
+        self.sections.iter()
+            .find(|(current_type, _)| *current_type == section_type)
+            .map(|(_, data)| data.as_slice())
+    }
+
+    ///
+    /// Same as `section`, but a missing section is a format error.
+    ///
+    pub fn required_section(&self, section_type: u32) -> Result<& [u8], BinFileError> {
+        match self.section(section_type) {
+            Some(data) => Ok(data),
+            None => Err(BinFileError::FormatError(format!("missing section {}", section_type)))
+        }
+    }
+
+    pub fn write<W: Write>(&self, writer: & mut W) -> std::io::Result<()> {
+
+        writer.write_all(&self.magic)?;
+        writer.write_all(&self.version.to_le_bytes())?;
+        writer.write_all(&(self.sections.len() as u32).to_le_bytes())?;
+
+        for (section_type, data) in &self.sections {
+            writer.write_all(&section_type.to_le_bytes())?;
+            writer.write_all(&(data.len() as u64).to_le_bytes())?;
+            writer.write_all(data)?;
+        }
+
+        Ok(())
+
+    }
+
+    ///
+    /// Reads a file, checking its magic value.
+    ///
+    pub fn read<R: Read>(mut reader: R, magic: & [u8; 4]) -> Result<BinFile, BinFileError> {
+
+        let mut content: Vec<u8> = Vec::new();
+        reader.read_to_end(& mut content)?;
+
+        let mut cursor = SectionReader::new(&content);
+
+        if cursor.read_bytes(4)? != magic {
+            return Err(BinFileError::FormatError(format!("expected magic value {:?}", std::string::String::from_utf8_lossy(magic))));
+        }
+
+        let version = cursor.read_u32()?;
+        let n_sections = cursor.read_u32()?;
+        let mut file = BinFile::new(magic, version);
+
+        for _ in 0..n_sections {
+            let section_type = cursor.read_u32()?;
+            let size = cursor.read_u64()?;
+            if size > cursor.remaining() as u64 {
+                return Err(BinFileError::FormatError(format!("section {} is truncated", section_type)));
+            }
+            file.add_section(section_type, cursor.read_bytes(size as usize)?.to_vec());
+        }
+
+        Ok(file)
+
+    }
+
+    pub fn save(&self, path: & std::path::Path) -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write(& mut file)?;
+        file.flush()
+    }
+
+    pub fn load(path: & std::path::Path, magic: & [u8; 4]) -> Result<BinFile, BinFileError> {
+        BinFile::read(std::fs::File::open(path)?, magic)
+    }
+
+}
+
+///
+/// Number of bytes used to store an element of the field: the size of the prime, rounded up to a
+/// multiple of 8.
+///
+pub fn field_size<F: PrimeField>() -> usize {
+    F::modulus().bits().div_ceil(64) * 8
+}
+
+pub fn push_u32(data: & mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+pub fn push_u64(data: & mut Vec<u8>, value: u64) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+///
+/// Appends the canonical little endian encoding of an element, on `field_size` bytes.
+///
+pub fn push_field<F: PrimeField>(data: & mut Vec<u8>, value: & F) {
+    data.extend_from_slice(&value.to_le_bytes()[..field_size::<F>()]);
+}
+
+///
+/// Appends the prime of the field, as stored in the headers of the iden3 formats.
+///
+pub fn push_prime<F: PrimeField>(data: & mut Vec<u8>) {
+    data.extend_from_slice(&F::modulus().to_le_bytes()[..field_size::<F>()]);
+}
+
+///
+/// Sequential reader over the data of a section.
+///
+pub struct SectionReader<'a> {
+    data: &'a [u8],
+    position: usize
+}
+
+impl<'a> SectionReader<'a> {
+
+    pub fn new(data: &'a [u8]) -> SectionReader<'a> {
+        SectionReader {
+            data,
+            position: 0
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    pub fn read_bytes(& mut self, size: usize) -> Result<&'a [u8], BinFileError> {
+
+        if size > self.remaining() {
+            return Err(BinFileError::FormatError("unexpected end of data".to_string()));
+        }
+
+        let bytes = &self.data[self.position..self.position + size];
+        self.position += size;
+
+        Ok(bytes)
+
+    }
+
+    pub fn read_u32(& mut self) -> Result<u32, BinFileError> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.read_bytes(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    pub fn read_u64(& mut self) -> Result<u64, BinFileError> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    ///
+    /// Reads an element stored on `field_size` bytes. Values that are not reduced are rejected.
+    ///
+    pub fn read_field<F: PrimeField>(& mut self) -> Result<F, BinFileError> {
+
+        let mut bytes = [0u8; 32];
+        bytes[..field_size::<F>()].copy_from_slice(self.read_bytes(field_size::<F>())?);
+
+        match F::from_le_bytes(&bytes) {
+            Some(value) => Ok(value),
+            None => Err(BinFileError::FormatError("field element is not reduced".to_string()))
+        }
+
+    }
+
+    ///
+    /// Reads a prime header and checks it is the one of the field.
+    ///
+    pub fn expect_prime<F: PrimeField>(& mut self, n8: usize) -> Result<(), BinFileError> {
+
+        if n8 != field_size::<F>() {
+            return Err(BinFileError::FormatError(format!("expected {} bytes field elements, found {}", field_size::<F>(), n8)));
+        }
+
+        let mut bytes = [0u8; 32];
+        bytes[..n8].copy_from_slice(self.read_bytes(n8)?);
+
+        if ::field::bigint::U256::from_le_bytes(&bytes) != F::modulus() {
+            return Err(BinFileError::FormatError(format!("the file does not use the {} prime", F::NAME)));
+        }
+
+        Ok(())
+
+    }
+
+}
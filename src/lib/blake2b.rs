@@ -0,0 +1,177 @@
+//
+// BLAKE2b (RFC 7693), the hash snarkjs uses for ceremony transcripts, unkeyed and with a 64 bytes
+// digest.
+//
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0]
+];
+
+pub const DIGEST_SIZE: usize = 64;
+
+const BLOCK_SIZE: usize = 128;
+
+#[derive(Clone)]
+pub struct Blake2b {
+    state: [u64; 8],
+    buffer: [u8; BLOCK_SIZE],
+    buffered: usize,
+    length: u128
+}
+
+impl Default for Blake2b {
+    fn default() -> Blake2b {
+        Blake2b::new()
+    }
+}
+
+fn mix(v: & mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+impl Blake2b {
+
+    pub fn new() -> Blake2b {
+
+        let mut state = IV;
+        state[0] ^= 0x01010000 ^ DIGEST_SIZE as u64;
+
+        Blake2b {
+            state,
+            buffer: [0; BLOCK_SIZE],
+            buffered: 0,
+            length: 0
+        }
+
+    }
+
+    fn compress(& mut self, last: bool) {
+
+        let mut m = [0u64; 16];
+        for (idx, word) in m.iter_mut().enumerate() {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&self.buffer[idx * 8..idx * 8 + 8]);
+            *word = u64::from_le_bytes(bytes);
+        }
+
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(&self.state);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= self.length as u64;
+        v[13] ^= (self.length >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+
+        for round in 0..12 {
+            let s = &SIGMA[round % 10];
+            mix(& mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            mix(& mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            mix(& mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            mix(& mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            mix(& mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            mix(& mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            mix(& mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            mix(& mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for idx in 0..8 {
+            self.state[idx] ^= v[idx] ^ v[idx + 8];
+        }
+
+    }
+
+    pub fn update(& mut self, mut data: & [u8]) {
+
+        while !data.is_empty() {
+
+            // The last block is compressed by `digest`, so a full buffer is only compressed once
+            // more data comes.
+            if self.buffered == BLOCK_SIZE {
+                self.length += BLOCK_SIZE as u128;
+                self.compress(false);
+                self.buffered = 0;
+            }
+
+            let taken = (BLOCK_SIZE - self.buffered).min(data.len());
+            self.buffer[self.buffered..self.buffered + taken].copy_from_slice(&data[..taken]);
+            self.buffered += taken;
+            data = &data[taken..];
+
+        }
+
+    }
+
+    pub fn digest(mut self) -> [u8; DIGEST_SIZE] {
+
+        self.length += self.buffered as u128;
+        for byte in self.buffer[self.buffered..].iter_mut() {
+            *byte = 0;
+        }
+        self.compress(true);
+
+        let mut digest = [0u8; DIGEST_SIZE];
+        for (idx, word) in self.state.iter().enumerate() {
+            digest[idx * 8..idx * 8 + 8].copy_from_slice(&word.to_le_bytes());
+        }
+
+        digest
+
+    }
+
+}
+
+pub fn hash(data: & [u8]) -> [u8; DIGEST_SIZE] {
+    let mut hasher = Blake2b::new();
+    hasher.update(data);
+    hasher.digest()
+}
+
+pub fn to_hex(digest: & [u8]) -> std::string::String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod blake2b_test {
+
+    use blake2b::{self, Blake2b};
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_blake2b() {
+
+        expect_that!(&blake2b::to_hex(&blake2b::hash(b"abc")), is(eq("ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923".to_string())));
+        expect_that!(&blake2b::to_hex(&blake2b::hash(b"")), is(eq("786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce".to_string())));
+
+        let data: Vec<u8> = (0..1000).map(|idx| (idx % 251) as u8).collect();
+        let mut hasher = Blake2b::new();
+        for chunk in data.chunks(77) {
+            hasher.update(chunk);
+        }
+        expect_that!(&blake2b::to_hex(&hasher.digest()), is(eq("c11e1c0340bd7e5a1b275f1230c962fad215ecb1391486e74e31b960a2f2996381a5fad092da06841d5f26e38f6ecfeaf441acbcd1c2de61aef121e7927175f5".to_string())));
+
+    }
+
+}
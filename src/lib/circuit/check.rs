@@ -0,0 +1,169 @@
+use field::PrimeField;
+use circuit::r1cs::{R1CS, Constraint, LinearCombination};
+use circuit::sym::SymbolMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckError {
+    WitnessSize(usize, usize),
+    InvalidOne
+}
+
+impl std::fmt::Display for CheckError {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CheckError::WitnessSize(expected, found) => write!(formatter, "The constraint system has {} wires but the witness has {} values", expected, found),
+            CheckError::InvalidOne => write!(formatter, "The first value of the witness must be 1")
+        }
+    }
+}
+
+///
+/// Constraint not satisfied by a witness, with the values taken by its linear combinations.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedConstraint<F: PrimeField> {
+    pub index: usize,
+    pub a: F,
+    pub b: F,
+    pub c: F
+}
+
+///
+/// Evaluates every constraint of the system against the witness and returns the ones that do not
+/// hold, in order.
+///
+pub fn check_witness<F: PrimeField>(r1cs: & R1CS<F>, witness: & [F]) -> Result<Vec<FailedConstraint<F>>, CheckError> {
+
+    if witness.len() != r1cs.n_wires {
+        return Err(CheckError::WitnessSize(r1cs.n_wires, witness.len()));
+    }
+
+    if !witness[0].is_one() {
+        return Err(CheckError::InvalidOne);
+    }
+
+    Ok(r1cs.unsatisfied(witness).into_iter()
+        .map(|index| {
+            let constraint = &r1cs.constraints[index];
+            FailedConstraint {
+                index,
+                a: constraint.a.evaluate(witness),
+                b: constraint.b.evaluate(witness),
+                c: constraint.c.evaluate(witness)
+            }
+        })
+        .collect())
+
+}
+
+///
+/// Elements above `p/2` are displayed as negative numbers, `-1` reads better than `p - 1`.
+///
+fn signed<F: PrimeField>(value: & F) -> (bool, F) {
+    if value.to_u256() > F::modulus().shr(1) {
+        (true, -*value)
+    } else {
+        (false, *value)
+    }
+}
+
+///
+/// Name of a wire: the first signal bound to it in the symbol map, `w<id>` if there is none.
+///
+fn wire_name(wire: usize, symbols: Option<& SymbolMap>) -> std::string::String {
+    match symbols.and_then(|symbols| symbols.names_of(wire).first().map(|name| name.to_string())) {
+        Some(name) => name,
+        None => format!("w{}", wire)
+    }
+}
+
+///
+/// Renders a linear combination as `2*main.a - main.b + 3`, wire 0 being the constant term.
+///
+pub fn render_lc<F: PrimeField>(lc: & LinearCombination<F>, symbols: Option<& SymbolMap>) -> std::string::String {
+
+    let mut rendered = std::string::String::new();
+
+    for (wire, coefficient) in lc.terms() {
+
+        let (negative, magnitude) = signed(coefficient);
+
+        if rendered.is_empty() {
+            if negative {
+                rendered.push('-');
+            }
+        } else {
+            rendered.push_str(if negative { " - " } else { " + " });
+        }
+
+        if *wire == 0 {
+            rendered.push_str(&magnitude.to_string());
+        } else if magnitude.is_one() {
+            rendered.push_str(&wire_name(*wire, symbols));
+        } else {
+            rendered.push_str(&format!("{}*{}", magnitude, wire_name(*wire, symbols)));
+        }
+
+    }
+
+    if rendered.is_empty() {
+        rendered.push('0');
+    }
+
+    rendered
+
+}
+
+///
+/// Renders a constraint as `(A) * (B) = (C)`.
+///
+pub fn render_constraint<F: PrimeField>(constraint: & Constraint<F>, symbols: Option<& SymbolMap>) -> std::string::String {
+    format!("({}) * ({}) = ({})", render_lc(&constraint.a, symbols), render_lc(&constraint.b, symbols), render_lc(&constraint.c, symbols))
+}
+
+#[cfg(test)]
+mod check_test {
+
+    use field::PrimeField;
+    use field::bn254::Fr;
+    use circuit::check;
+    use circuit::r1cs::{R1CS, Constraint, LinearCombination};
+    use circuit::sym::SymbolMap;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_check_witness() {
+
+        let mut r1cs: R1CS<Fr> = R1CS::new(1, 2, 0);
+
+        let mut c = LinearCombination::wire(1);
+        c.add_term(0, -Fr::from_u64(3));
+        r1cs.add_constraint(Constraint::new(LinearCombination::wire(2), LinearCombination::wire(3), c));
+        r1cs.add_constraint(Constraint::linear(LinearCombination::term(2, Fr::from_u64(2))));
+
+        let mut symbols = SymbolMap::new();
+        symbols.push(Some(1), 0, "main.out");
+        symbols.push(Some(2), 0, "main.a");
+
+        let valid = vec!(Fr::one(), Fr::from_u64(3), Fr::zero(), Fr::from_u64(7));
+        let invalid = vec!(Fr::one(), Fr::from_u64(4), Fr::from_u64(1), Fr::from_u64(2));
+
+        expect_that!(&check::check_witness(&r1cs, &valid), is(eq(Ok(vec!()))));
+
+        let failures = check::check_witness(&r1cs, &invalid).unwrap();
+
+        expect_that!(&failures.len(), is(eq(2)));
+        expect_that!(&failures[0].index, is(eq(0)));
+        expect_that!(&failures[0].a, is(eq(Fr::from_u64(1))));
+        expect_that!(&failures[0].b, is(eq(Fr::from_u64(2))));
+        expect_that!(&failures[0].c, is(eq(Fr::from_u64(1))));
+
+        expect_that!(&check::render_constraint(&r1cs.constraints[0], Some(&symbols)), is(eq("(main.a) * (w3) = (-3 + main.out)".to_string())));
+        expect_that!(&check::render_constraint(&r1cs.constraints[1], None), is(eq("(0) * (0) = (-2*w2)".to_string())));
+
+        expect_that!(&check::check_witness(&r1cs, &invalid[..3]), is(eq(Err(check::CheckError::WitnessSize(4, 3)))));
+
+    }
+
+}
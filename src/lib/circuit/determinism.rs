@@ -0,0 +1,404 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use circuit::r1cs::{Constraint, LinearCombination, R1CS};
+use field::PrimeField;
+
+///
+/// Wires appearing in at least one constraint, by wire index.
+///
+pub fn constrained_wires<F: PrimeField>(r1cs: & R1CS<F>) -> Vec<bool> {
+
+    let mut constrained = vec!(false; r1cs.n_wires);
+
+    for constraint in &r1cs.constraints {
+        for wire in constraint.wires() {
+            constrained[wire] = true;
+        }
+    }
+
+    constrained
+
+}
+
+///
+/// True if the constraint only involves `wire` and `one`, and holds exactly for `wire` in {0, 1}:
+/// `(ka x + a0) * (kb x + b0) = kc x + c0` reduces to `ka kb x^2 + (ka b0 + kb a0 - kc) x + a0 b0 - c0 = 0`.
+///
+fn binary_wire<F: PrimeField>(constraint: & Constraint<F>) -> Option<usize> {
+
+    let wires: HashSet<usize> = constraint.wires().filter(|wire| *wire != 0).collect();
+    if wires.len() != 1 {
+        return None;
+    }
+    let wire = *wires.iter().next().unwrap();
+
+    let (ka, kb, kc) = (constraint.a.coefficient(wire), constraint.b.coefficient(wire), constraint.c.coefficient(wire));
+    let (a0, b0, c0) = (constraint.a.constant_value(), constraint.b.constant_value(), constraint.c.constant_value());
+
+    let square = ka * kb;
+    let linear = ka * b0 + kb * a0 - kc;
+    let constant = a0 * b0 - c0;
+
+    if !square.is_zero() && (square + linear).is_zero() && constant.is_zero() {
+        Some(wire)
+    } else {
+        None
+    }
+
+}
+
+///
+/// Powers of two `2^k` for `-bits <= k <= bits`, by value.
+///
+fn powers_of_two<F: PrimeField>(bits: usize) -> HashMap<F, i64> {
+
+    let mut powers = HashMap::new();
+    let two = F::from_u64(2);
+    let half = two.inverse().unwrap();
+
+    let (mut up, mut down) = (F::one(), F::one());
+    for exponent in 0..=bits as i64 {
+        powers.insert(up, exponent);
+        powers.insert(down, -exponent);
+        up *= two;
+        down *= half;
+    }
+
+    powers
+
+}
+
+///
+/// True if `lc` is a non-zero multiple of the non-constant combination `other`.
+///
+fn proportional<F: PrimeField>(lc: & LinearCombination<F>, other: & LinearCombination<F>) -> bool {
+
+    let (wire, coefficient) = match other.terms().next() {
+        Some(term) => term,
+        None => return false
+    };
+
+    let ratio = lc.coefficient(*wire) * coefficient.inverse().unwrap();
+
+    !ratio.is_zero() && lc.len() == other.len()
+        && other.terms().all(|(wire, coefficient)| lc.coefficient(*wire) == *coefficient * ratio)
+
+}
+
+struct Propagation<'a, F: PrimeField> {
+    r1cs: &'a R1CS<F>,
+    known: Vec<bool>,
+    binary: Vec<bool>,
+    powers: HashMap<F, i64>,
+
+    ///
+    /// Indexes of the constraints where each wire appears.
+    ///
+    occurrences: Vec<Vec<usize>>,
+
+    ///
+    /// Whether a factor multiplying an unknown wire and depending on other wires is assumed not to
+    /// vanish, as in `x * y === z` solving `x` when `y` is known.
+    ///
+    assume_nonzero: bool,
+
+    ///
+    /// Largest spread of exponents for a binary decomposition to be unique: the weighted sum of
+    /// the bits must stay below the modulus.
+    ///
+    max_spread: i64
+}
+
+impl<'a, F: PrimeField> Propagation<'a, F> {
+
+    fn unknowns(&self, constraint: & Constraint<F>) -> Vec<usize> {
+        let wires: HashSet<usize> = constraint.wires().filter(|wire| !self.known[*wire]).collect();
+        let mut wires: Vec<usize> = wires.into_iter().collect();
+        wires.sort();
+        wires
+    }
+
+    ///
+    /// Factor multiplying `wire` in a constraint where it is the only unknown, the constraint being
+    /// rewritten `factor * wire = rest`, or `None` when the constraint is not of degree one in `wire`:
+    /// `(ka x + a0) * b = kc x + c0` gives `factor = ka b - kc`.
+    ///
+    fn factor(&self, constraint: & Constraint<F>, wire: usize) -> Option<LinearCombination<F>> {
+
+        let (ka, kb, kc) = (constraint.a.coefficient(wire), constraint.b.coefficient(wire), constraint.c.coefficient(wire));
+
+        let (coefficient, other) = match (ka.is_zero(), kb.is_zero()) {
+            (true, true) => return Some(LinearCombination::constant(-kc)),
+            (false, false) => return None,
+            (false, true) => (ka, &constraint.b),
+            (true, false) => (kb, &constraint.a)
+        };
+
+        let mut factor = other.clone();
+        factor.scale(coefficient);
+        factor.add_term(0, -kc);
+        Some(factor)
+
+    }
+
+    ///
+    /// Whether a constraint where `wire` is the only unknown fixes its value: the constraint must be
+    /// of degree one in `wire`. When the factor multiplying `wire` depends on other wires, it fixes
+    /// it if that factor is assumed not to vanish, if another constraint fixes `wire` when it does,
+    /// or if `wire` cannot change any other wire while it does.
+    ///
+    fn solves(&self, constraint: & Constraint<F>, wire: usize) -> bool {
+
+        match self.factor(constraint, wire) {
+            None => false,
+            Some(factor) if factor.is_constant() => !factor.is_empty(),
+            Some(factor) => self.assume_nonzero || self.switches(&factor, wire) || self.isolated(wire)
+        }
+
+    }
+
+    ///
+    /// Whether a constraint fixes `wire` when `factor` vanishes, as `out` in `IsZero` where
+    /// `in * out === 0` leaves it free when `in` is zero but `-in * inv === out - 1` then reads
+    /// `out === 1`: one side of the product must be proportional to `factor`, and `wire` must be the
+    /// only unknown of the other side of the equation.
+    ///
+    fn switches(&self, factor: & LinearCombination<F>, wire: usize) -> bool {
+
+        self.occurrences[wire].iter().any(|index| {
+
+            let constraint = &self.r1cs.constraints[*index];
+
+            !constraint.a.contains(wire) && !constraint.b.contains(wire)
+                && constraint.c.wires().all(|other| other == wire || self.known[other])
+                && (proportional(&constraint.a, factor) || proportional(&constraint.b, factor))
+
+        })
+
+    }
+
+    ///
+    /// Whether `wire` is internal and appears in a single constraint: when its factor vanishes it is
+    /// free but multiplies zero, so no other wire depends on its value, as `inv` in `IsZero`.
+    ///
+    fn isolated(&self, wire: usize) -> bool {
+        !self.r1cs.is_interface(wire) && self.occurrences[wire].len() == 1
+    }
+
+    ///
+    /// Whether a linear constraint whose unknowns are all binary is a bit decomposition, the unknowns
+    /// being weighted by distinct powers of two (up to a common factor).
+    ///
+    fn decomposes(&self, constraint: & Constraint<F>, unknowns: & [usize]) -> bool {
+
+        if !unknowns.iter().all(|wire| self.binary[*wire]) {
+            return false;
+        }
+
+        let lc = match constraint.as_linear() {
+            Some(lc) => lc,
+            None => return false
+        };
+
+        let base = match lc.coefficient(unknowns[0]).inverse() {
+            Some(base) => base,
+            None => return false
+        };
+
+        let mut exponents = Vec::new();
+        for wire in unknowns {
+            match self.powers.get(&(lc.coefficient(*wire) * base)) {
+                Some(exponent) => exponents.push(*exponent),
+                None => return false
+            }
+        }
+
+        exponents.sort();
+        exponents.dedup();
+
+        exponents.len() == unknowns.len() && exponents[exponents.len() - 1] - exponents[0] <= self.max_spread
+
+    }
+
+}
+
+///
+/// Wires whose value is uniquely determined by the inputs of the main component through the
+/// constraints, by wire index.
+///
+/// Starting from `one` and the inputs, a wire becomes determined when a constraint where it is the
+/// only undetermined wire is of degree one in it with a constant coefficient, or when it is one of
+/// the bits of a binary decomposition: a linear constraint weighting by distinct powers of two
+/// undetermined wires that are all constrained to be 0 or 1.
+///
+/// A coefficient depending on other wires is enough when another constraint fixes the wire once
+/// the coefficient vanishes, as `out` in `IsZero`, or when the wire is internal and appears in no
+/// other constraint, as the inverse `inv` of `IsZero` whose value is then irrelevant.
+///
+pub fn determined_wires<F: PrimeField>(r1cs: & R1CS<F>) -> Vec<bool> {
+    propagate(r1cs, false)
+}
+
+///
+/// Wires determined as in `determined_wires`, a coefficient depending on other wires being assumed
+/// not to vanish: `x * y === z` determines `x` only as long as `y` is not zero.
+///
+/// Wires only found here are conditionally determined.
+///
+pub fn conditionally_determined_wires<F: PrimeField>(r1cs: & R1CS<F>) -> Vec<bool> {
+    propagate(r1cs, true)
+}
+
+fn propagate<F: PrimeField>(r1cs: & R1CS<F>, assume_nonzero: bool) -> Vec<bool> {
+
+    let bits = F::modulus().bits();
+
+    let mut propagation = Propagation {
+        r1cs,
+        known: vec!(false; r1cs.n_wires),
+        binary: vec!(false; r1cs.n_wires),
+        powers: powers_of_two(bits),
+        occurrences: vec!(Vec::new(); r1cs.n_wires),
+        assume_nonzero,
+        max_spread: bits as i64 - 2
+    };
+
+    let first_input = 1 + r1cs.n_outputs;
+    for wire in (0..1).chain(first_input..first_input + r1cs.n_public_inputs + r1cs.n_private_inputs) {
+        propagation.known[wire] = true;
+    }
+
+    for (index, constraint) in r1cs.constraints.iter().enumerate() {
+        if let Some(wire) = binary_wire(constraint) {
+            propagation.binary[wire] = true;
+        }
+        let wires: HashSet<usize> = constraint.wires().collect();
+        for wire in wires {
+            propagation.occurrences[wire].push(index);
+        }
+    }
+
+    let mut queue: VecDeque<usize> = (0..propagation.r1cs.constraints.len()).collect();
+
+    while let Some(index) = queue.pop_front() {
+
+        let constraint = &propagation.r1cs.constraints[index];
+        let unknowns = propagation.unknowns(constraint);
+
+        let solved = match unknowns.len() {
+            0 => continue,
+            1 => propagation.solves(constraint, unknowns[0]),
+            _ => propagation.decomposes(constraint, &unknowns)
+        };
+
+        if !solved {
+            continue ;
+        }
+
+        for wire in unknowns {
+            propagation.known[wire] = true;
+            queue.extend(propagation.occurrences[wire].iter().filter(|other| **other != index));
+        }
+
+    }
+
+    propagation.known
+
+}
+
+#[cfg(test)]
+mod determinism_test {
+
+    use circuit::determinism;
+    use circuit::r1cs::{Constraint, LinearCombination, R1CS};
+    use field::PrimeField;
+    use field::bn254::Fr;
+
+    use galvanic_assert::matchers::*;
+
+    fn wire(wire: usize) -> LinearCombination<Fr> {
+        LinearCombination::wire(wire)
+    }
+
+    ///
+    /// `bits` wires 2.. decomposing the input wire 1, the binary constraints being optional.
+    ///
+    fn decomposition(bits: usize, binary: bool) -> R1CS<Fr> {
+
+        let mut r1cs = R1CS::new(0, 1, 0);
+        let mut sum = LinearCombination::new();
+
+        for bit in 0..bits {
+            let wire = r1cs.add_wire();
+            sum.add_term(wire, Fr::from_u64(1 << bit));
+            if binary {
+                let mut minus_one = self::wire(wire);
+                minus_one.add_term(0, -Fr::one());
+                r1cs.add_constraint(Constraint::new(self::wire(wire), minus_one, LinearCombination::new()));
+            }
+        }
+
+        sum.add_term(1, -Fr::one());
+        r1cs.add_constraint(Constraint::linear(sum));
+        r1cs
+
+    }
+
+    #[test]
+    fn test_determined_wires() {
+
+        let r1cs = decomposition(4, true);
+        expect_that!(&determinism::determined_wires(&r1cs), is(eq(vec!(true; 6))));
+        expect_that!(&determinism::constrained_wires(&r1cs), is(eq(vec!(true; 6))));
+
+        let r1cs = decomposition(4, false);
+        expect_that!(&determinism::determined_wires(&r1cs), is(eq(vec!(true, true, false, false, false, false))));
+
+        // out * out = in has two solutions, out * 2 = in only one
+        let mut r1cs = R1CS::new(1, 1, 0);
+        r1cs.add_constraint(Constraint::new(wire(1), wire(1), wire(2)));
+        expect_that!(&determinism::determined_wires(&r1cs), is(eq(vec!(true, false, true))));
+
+        let mut r1cs = R1CS::new(1, 1, 0);
+        r1cs.add_constraint(Constraint::new(wire(1), LinearCombination::constant(Fr::from_u64(2)), wire(2)));
+        r1cs.add_wire();
+        expect_that!(&determinism::determined_wires(&r1cs), is(eq(vec!(true, true, true, false))));
+        expect_that!(&determinism::constrained_wires(&r1cs), is(eq(vec!(true, true, true, false))));
+
+        // out * in = 1 fixes out only when in is not zero
+        let mut r1cs = R1CS::new(1, 1, 0);
+        r1cs.add_constraint(Constraint::new(wire(1), wire(2), LinearCombination::constant(Fr::one())));
+        expect_that!(&determinism::determined_wires(&r1cs), is(eq(vec!(true, false, true))));
+        expect_that!(&determinism::conditionally_determined_wires(&r1cs), is(eq(vec!(true, true, true))));
+
+        // x * y = z with the inputs y and z, and out = x + 1 depending on x
+        let mut r1cs = R1CS::new(1, 2, 0);
+        let x = r1cs.add_wire();
+        let mut plus_one = wire(x);
+        plus_one.add_term(0, Fr::one());
+        plus_one.add_term(1, -Fr::one());
+        r1cs.add_constraint(Constraint::new(wire(x), wire(2), wire(3)));
+        r1cs.add_constraint(Constraint::linear(plus_one));
+        expect_that!(&determinism::determined_wires(&r1cs), is(eq(vec!(true, false, true, true, false))));
+        expect_that!(&determinism::conditionally_determined_wires(&r1cs), is(eq(vec!(true; 5))));
+
+        // IsZero: in * out = 0 and -in * inv = out - 1, out being 1 when in is zero
+        let is_zero = |a: LinearCombination<Fr>| {
+            let mut r1cs = R1CS::new(1, 1, 0);
+            let inv = r1cs.add_wire();
+            let mut minus_one = wire(1);
+            minus_one.add_term(0, -Fr::one());
+            r1cs.add_constraint(Constraint::new(wire(2), wire(1), LinearCombination::new()));
+            r1cs.add_constraint(Constraint::new(a, wire(inv), minus_one));
+            r1cs
+        };
+        expect_that!(&determinism::determined_wires(&is_zero(LinearCombination::term(2, -Fr::one()))), is(eq(vec!(true; 4))));
+
+        // (in + 1) * inv = out - 1 no longer fixes out when in is zero
+        let mut a = wire(2);
+        a.add_term(0, Fr::one());
+        expect_that!(&determinism::determined_wires(&is_zero(a)), is(eq(vec!(true, false, true, false))));
+
+    }
+
+}
@@ -0,0 +1,6 @@
+pub mod sym;
+pub mod r1cs;
+pub mod r1cs_file;
+pub mod optimize;
+pub mod check;
+pub mod determinism;
@@ -0,0 +1,343 @@
+use circuit::r1cs::{Constraint, LinearCombination, R1CS};
+use circuit::sym::SymbolMap;
+use field::PrimeField;
+
+///
+/// Outcome of a `simplify` run.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SimplificationReport {
+    pub constraints_before: usize,
+    pub constraints_after: usize,
+    pub wires_before: usize,
+    pub wires_after: usize,
+
+    pub linear_removed: usize,
+    pub duplicates_removed: usize,
+    pub trivial_removed: usize
+}
+
+impl SimplificationReport {
+
+    pub fn constraints_removed(&self) -> usize {
+        self.constraints_before - self.constraints_after
+    }
+
+    pub fn wires_removed(&self) -> usize {
+        self.wires_before - self.wires_after
+    }
+
+}
+
+impl std::fmt::Display for SimplificationReport {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(formatter, "constraints: {} -> {} (-{})", self.constraints_before, self.constraints_after, self.constraints_removed())?;
+        writeln!(formatter, "\tlinear substitutions: {}", self.linear_removed)?;
+        writeln!(formatter, "\tduplicates: {}", self.duplicates_removed)?;
+        writeln!(formatter, "\ttrivially satisfied: {}", self.trivial_removed)?;
+        writeln!(formatter, "wires: {} -> {} (-{})", self.wires_before, self.wires_after, self.wires_removed())
+    }
+}
+
+///
+/// Rewrites linear constraints in their `0 * 0 - c = 0` form, so that they can be recognized and
+/// compared.
+///
+fn normalize<F: PrimeField>(constraint: Constraint<F>) -> Constraint<F> {
+    match constraint.as_linear() {
+        Some(lc) => Constraint::linear(lc),
+        None => constraint
+    }
+}
+
+///
+/// Scales a linear constraint so that the coefficient of its highest wire is one. Two linear
+/// constraints that only differ by a factor are then equal.
+///
+fn canonical<F: PrimeField>(constraint: & Constraint<F>) -> Constraint<F> {
+
+    if let Some(mut lc) = constraint.as_linear() {
+        if let Some((_, coefficient)) = lc.terms().next_back() {
+            let factor = coefficient.inverse().unwrap();
+            lc.scale(factor);
+        }
+        return Constraint::linear(lc);
+    }
+
+    constraint.clone()
+
+}
+
+///
+/// Only `0 = 0` is trivially satisfied, a non zero constant linear constraint can never be satisfied
+/// and is kept so that the error can be reported.
+///
+fn is_trivial<F: PrimeField>(constraint: & Constraint<F>) -> bool {
+    match constraint.as_linear() {
+        Some(lc) => lc.is_empty(),
+        None => false
+    }
+}
+
+///
+/// Picks the wire a linear constraint is solved for: the highest internal wire, if any.
+///
+fn elimination_wire<F: PrimeField>(r1cs: & R1CS<F>, lc: & LinearCombination<F>) -> Option<usize> {
+    lc.wires().filter(|wire| !r1cs.is_interface(*wire)).max()
+}
+
+///
+/// Optimization pass over the constraint system, similar to circom's `--O2`:
+///
+/// - linear constraints are removed by solving them for one of their internal wires and substituting
+///   the result in all the other constraints,
+/// - trivially satisfied constraints are dropped,
+/// - identical constraints are deduplicated.
+///
+/// Removed wires are dropped from the system, remaining wires are renumbered and the symbol map is
+/// updated accordingly, removed signals ending up without wire.
+///
+pub fn simplify<F: PrimeField>(r1cs: & mut R1CS<F>, symbols: & mut SymbolMap) -> SimplificationReport {
+
+    let mut report = SimplificationReport {
+        constraints_before: r1cs.constraints.len(),
+        wires_before: r1cs.n_wires,
+        ..SimplificationReport::default()
+    };
+
+    let mut constraints: Vec<Option<Constraint<F>>> = r1cs.constraints.drain(..).map(|constraint| Some(normalize(constraint))).collect();
+    let mut occurrences: std::collections::HashMap<usize, Vec<usize>> = std::collections::HashMap::new();
+    let mut removed_wires: std::collections::HashSet<usize> = std::collections::HashSet::new();
+    let mut queue: std::collections::VecDeque<usize> = std::collections::VecDeque::new();
+
+    for (idx, constraint) in constraints.iter().enumerate() {
+        let constraint = constraint.as_ref().unwrap();
+        for wire in constraint.wires() {
+            occurrences.entry(wire).or_default().push(idx);
+        }
+        if constraint.is_linear() {
+            queue.push_back(idx);
+        }
+    }
+
+    while let Some(idx) = queue.pop_front() {
+
+        let lc = match &constraints[idx] {
+            Some(constraint) => match constraint.as_linear() {
+                Some(lc) => lc,
+                None => continue
+            },
+            None => continue
+        };
+
+        if lc.is_empty() {
+            constraints[idx] = None;
+            report.trivial_removed += 1;
+            continue ;
+        }
+
+        let wire = match elimination_wire(r1cs, &lc) {
+            Some(wire) => wire,
+            None => continue
+        };
+
+        // lc = k * wire + rest = 0, so wire = -rest / k
+        let mut value = lc.clone();
+        let factor = -value.coefficient(wire).inverse().unwrap();
+        value.substitute(wire, &LinearCombination::new());
+        value.scale(factor);
+
+        constraints[idx] = None;
+        removed_wires.insert(wire);
+        report.linear_removed += 1;
+
+        let users = occurrences.remove(&wire).unwrap_or_default();
+
+        for user in users {
+
+            let updated = match &mut constraints[user] {
+                Some(constraint) => constraint.substitute(wire, &value),
+                None => false
+            };
+
+            if !updated {
+                continue ;
+            }
+
+            let constraint = normalize(constraints[user].take().unwrap());
+
+            for new_wire in value.wires() {
+                let entry = occurrences.entry(new_wire).or_default();
+                if entry.last() != Some(&user) {
+                    entry.push(user);
+                }
+            }
+
+            if constraint.is_linear() {
+                queue.push_back(user);
+            }
+
+            constraints[user] = Some(constraint);
+
+        }
+
+    }
+
+    let mut seen: std::collections::HashSet<Constraint<F>> = std::collections::HashSet::new();
+
+    for constraint in constraints.into_iter().flatten() {
+
+        if is_trivial(&constraint) {
+            report.trivial_removed += 1;
+            continue ;
+        }
+
+        if !seen.insert(canonical(&constraint)) {
+            report.duplicates_removed += 1;
+            continue ;
+        }
+
+        r1cs.constraints.push(constraint);
+
+    }
+
+    let mut mapping: Vec<Option<usize>> = Vec::with_capacity(r1cs.n_wires);
+    let mut n_wires = 0;
+
+    for wire in 0..r1cs.n_wires {
+        if removed_wires.contains(&wire) {
+            mapping.push(None);
+        } else {
+            mapping.push(Some(n_wires));
+            n_wires += 1;
+        }
+    }
+
+    r1cs.constraints = r1cs.constraints.iter().map(|constraint| constraint.remap(&mapping)).collect();
+    r1cs.n_wires = n_wires;
+    symbols.remap_wires(&mapping);
+
+    report.constraints_after = r1cs.constraints.len();
+    report.wires_after = r1cs.n_wires;
+
+    report
+
+}
+
+///
+/// Moves the values of a witness to the wires the signals ended up on after simplification.
+///
+pub fn remap_witness<F: PrimeField>(witness: & [F], before: & SymbolMap, after: & SymbolMap, n_wires: usize) -> Vec<F> {
+
+    let mut remapped = vec!(F::zero(); n_wires);
+    remapped[0] = witness[0];
+
+    for (old, new) in before.iter().zip(after.iter()) {
+        if let (Some(old), Some(new)) = (old.wire_id, new.wire_id) {
+            remapped[new] = witness[old];
+        }
+    }
+
+    remapped
+
+}
+
+#[cfg(test)]
+mod optimize_test {
+
+    use circuit::optimize;
+    use circuit::r1cs::{Constraint, LinearCombination, R1CS};
+    use circuit::sym::SymbolMap;
+    use field::bn254::Fr;
+    use field::PrimeField;
+
+    use galvanic_assert::matchers::*;
+
+    fn lc(terms: & [(usize, i64)]) -> LinearCombination<Fr> {
+        let mut lc = LinearCombination::new();
+        for (wire, coefficient) in terms {
+            lc.add_term(*wire, Fr::parse(&coefficient.to_string()).unwrap());
+        }
+        lc
+    }
+
+    ///
+    /// Hand written equivalent of `Num2Bits(2)` followed by an `AND` of the bits.
+    ///
+    /// Wires: 0 one, 1 out, 2 in, 3 bit 0, 4 bit 1, 5 lc1
+    ///
+    fn sample() -> (R1CS<Fr>, SymbolMap) {
+
+        let mut r1cs: R1CS<Fr> = R1CS::new(1, 0, 1);
+        r1cs.add_wire();
+        r1cs.add_wire();
+        r1cs.add_wire();
+
+        r1cs.add_constraint(Constraint::new(lc(&[(3, 1)]), lc(&[(3, 1), (0, -1)]), lc(&[])));
+        r1cs.add_constraint(Constraint::new(lc(&[(4, 1)]), lc(&[(4, 1), (0, -1)]), lc(&[])));
+        r1cs.add_constraint(Constraint::new(lc(&[(0, 1)]), lc(&[(3, 1), (4, 2)]), lc(&[(5, 1)])));
+        r1cs.add_constraint(Constraint::linear(lc(&[(2, 1), (5, -1)])));
+        r1cs.add_constraint(Constraint::new(lc(&[(3, 1)]), lc(&[(4, 1)]), lc(&[(1, 1)])));
+        r1cs.add_constraint(Constraint::new(lc(&[(3, 2)]), lc(&[(3, 1), (0, -1)]), lc(&[])));
+        r1cs.add_constraint(Constraint::new(lc(&[]), lc(&[(3, 1)]), lc(&[])));
+
+        let mut symbols = SymbolMap::new();
+        symbols.push(Some(1), 0, "main.out");
+        symbols.push(Some(2), 0, "main.in");
+        symbols.push(Some(3), 1, "main.n2b.out[0]");
+        symbols.push(Some(4), 1, "main.n2b.out[1]");
+        symbols.push(Some(5), 1, "main.n2b.lc1");
+
+        (r1cs, symbols)
+
+    }
+
+    #[test]
+    fn test_simplify() {
+
+        let (mut r1cs, mut symbols) = sample();
+
+        let witness: Vec<Fr> = [1, 1, 3, 1, 1, 3].iter().map(|value| Fr::from_u64(*value)).collect();
+        expect_that!(&r1cs.unsatisfied(&witness).len(), is(eq(0)));
+
+        let report = optimize::simplify(& mut r1cs, & mut symbols);
+
+        expect_that!(&report.linear_removed, is(eq(2)));
+        expect_that!(&report.trivial_removed, is(eq(1)));
+        expect_that!(&report.duplicates_removed, is(eq(0)));
+        expect_that!(&report.constraints_after, is(eq(4)));
+        expect_that!(&report.wires_removed(), is(eq(2)));
+        expect_that!(&r1cs.n_wires, is(eq(4)));
+
+        expect_that!(&symbols.wire_of("main.n2b.out[0]"), is(eq(Some(3))));
+        expect_that!(&symbols.wire_of("main.n2b.out[1]"), is(eq(None)));
+        expect_that!(&symbols.wire_of("main.n2b.lc1"), is(eq(None)));
+
+        let reduced: Vec<Fr> = [1, 1, 3, 1].iter().map(|value| Fr::from_u64(*value)).collect();
+        expect_that!(&r1cs.unsatisfied(&reduced).len(), is(eq(0)));
+
+        let invalid: Vec<Fr> = [1, 0, 3, 1].iter().map(|value| Fr::from_u64(*value)).collect();
+        expect_that!(&r1cs.unsatisfied(&invalid).len(), is(eq(1)));
+
+    }
+
+    #[test]
+    fn test_simplify_deduplicates() {
+
+        let mut r1cs: R1CS<Fr> = R1CS::new(0, 0, 2);
+
+        r1cs.add_constraint(Constraint::new(lc(&[(1, 1)]), lc(&[(2, 1)]), lc(&[(0, 1)])));
+        r1cs.add_constraint(Constraint::new(lc(&[(1, 1)]), lc(&[(2, 1)]), lc(&[(0, 1)])));
+        r1cs.add_constraint(Constraint::linear(lc(&[(1, 1), (2, 1)])));
+        r1cs.add_constraint(Constraint::linear(lc(&[(1, 3), (2, 3)])));
+
+        let report = optimize::simplify(& mut r1cs, & mut SymbolMap::new());
+
+        expect_that!(&report.duplicates_removed, is(eq(2)));
+        expect_that!(&report.linear_removed, is(eq(0)));
+        expect_that!(&r1cs.constraints.len(), is(eq(2)));
+        expect_that!(&r1cs.n_wires, is(eq(3)));
+
+    }
+
+}
@@ -0,0 +1,296 @@
+use field::PrimeField;
+
+///
+/// Linear combination of wires, `sum(coefficient * wire)`. Wire 0 is the constant `one` signal, so
+/// its coefficient is the constant part of the combination.
+///
+/// Terms with a zero coefficient are never stored.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LinearCombination<F: PrimeField> {
+    terms: std::collections::BTreeMap<usize, F>
+}
+
+impl<F: PrimeField> LinearCombination<F> {
+
+    pub fn new() -> LinearCombination<F> {
+        LinearCombination {
+            terms: std::collections::BTreeMap::new()
+        }
+    }
+
+    pub fn constant(value: F) -> LinearCombination<F> {
+        LinearCombination::term(0, value)
+    }
+
+    pub fn wire(wire: usize) -> LinearCombination<F> {
+        LinearCombination::term(wire, F::one())
+    }
+
+    pub fn term(wire: usize, coefficient: F) -> LinearCombination<F> {
+        let mut lc = LinearCombination::new();
+        lc.add_term(wire, coefficient);
+        lc
+    }
+
+    pub fn add_term(& mut self, wire: usize, coefficient: F) {
+
+        let value = match self.terms.get(&wire) {
+            Some(current) => *current + coefficient,
+            None => coefficient
+        };
+
+        if value.is_zero() {
+            self.terms.remove(&wire);
+        } else {
+            self.terms.insert(wire, value);
+        }
+
+    }
+
+    ///
+    /// Adds `factor * other` to the combination.
+    ///
+    pub fn add_scaled(& mut self, other: & LinearCombination<F>, factor: F) {
+        for (wire, coefficient) in &other.terms {
+            self.add_term(*wire, *coefficient * factor);
+        }
+    }
+
+    pub fn scale(& mut self, factor: F) {
+
+        if factor.is_zero() {
+            self.terms.clear();
+            return ;
+        }
+
+        for coefficient in self.terms.values_mut() {
+            *coefficient *= factor;
+        }
+
+    }
+
+    pub fn coefficient(&self, wire: usize) -> F {
+        match self.terms.get(&wire) {
+            Some(coefficient) => *coefficient,
+            None => F::zero()
+        }
+    }
+
+    pub fn contains(&self, wire: usize) -> bool {
+        self.terms.contains_key(&wire)
+    }
+
+    pub fn terms(&self) -> std::collections::btree_map::Iter<'_, usize, F> {
+        self.terms.iter()
+    }
+
+    pub fn wires<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
+        self.terms.keys().cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    ///
+    /// True if the combination only depends on the `one` wire.
+    ///
+    pub fn is_constant(&self) -> bool {
+        self.terms.keys().all(|wire| *wire == 0)
+    }
+
+    pub fn constant_value(&self) -> F {
+        self.coefficient(0)
+    }
+
+    ///
+    /// Replaces `wire` by the linear combination `value`. Returns false if `wire` was not present.
+    ///
+    pub fn substitute(& mut self, wire: usize, value: & LinearCombination<F>) -> bool {
+        match self.terms.remove(&wire) {
+            Some(coefficient) => {
+                self.add_scaled(value, coefficient);
+                true
+            },
+            None => false
+        }
+    }
+
+    ///
+    /// Rebuilds the combination with new wire indexes, `mapping` being indexed by the old ones.
+    ///
+    /// Panics if a present wire is mapped to `None`.
+    ///
+    pub fn remap(&self, mapping: & [Option<usize>]) -> LinearCombination<F> {
+        LinearCombination {
+            terms: self.terms.iter().map(|(wire, coefficient)| match mapping[*wire] {
+                Some(new_wire) => (new_wire, *coefficient),
+                None => panic!("Wire {} has been removed but is still used", wire)
+            }).collect()
+        }
+    }
+
+    pub fn evaluate(&self, witness: & [F]) -> F {
+        let mut result = F::zero();
+        for (wire, coefficient) in &self.terms {
+            result += *coefficient * witness[*wire];
+        }
+        result
+    }
+
+}
+
+///
+/// Rank 1 constraint `a * b - c = 0`.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Constraint<F: PrimeField> {
+    pub a: LinearCombination<F>,
+    pub b: LinearCombination<F>,
+    pub c: LinearCombination<F>
+}
+
+impl<F: PrimeField> Constraint<F> {
+
+    pub fn new(a: LinearCombination<F>, b: LinearCombination<F>, c: LinearCombination<F>) -> Constraint<F> {
+        Constraint {
+            a,
+            b,
+            c
+        }
+    }
+
+    ///
+    /// Linear constraint `lc = 0`, stored as `0 * 0 - (-lc) = 0`.
+    ///
+    pub fn linear(mut lc: LinearCombination<F>) -> Constraint<F> {
+        lc.scale(-F::one());
+        Constraint::new(LinearCombination::new(), LinearCombination::new(), lc)
+    }
+
+    ///
+    /// True if the constraint has no quadratic part.
+    ///
+    pub fn is_linear(&self) -> bool {
+        self.a.is_constant() || self.b.is_constant()
+    }
+
+    ///
+    /// Returns the linear combination `lc` such that the constraint is `lc = 0`, if the constraint is
+    /// linear.
+    ///
+    pub fn as_linear(&self) -> Option<LinearCombination<F>> {
+
+        let mut lc = if self.a.is_constant() {
+            let mut lc = self.b.clone();
+            lc.scale(self.a.constant_value());
+            lc
+        } else if self.b.is_constant() {
+            let mut lc = self.a.clone();
+            lc.scale(self.b.constant_value());
+            lc
+        } else {
+            return None;
+        };
+
+        lc.add_scaled(&self.c, -F::one());
+        Some(lc)
+
+    }
+
+    pub fn wires<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
+        self.a.wires().chain(self.b.wires()).chain(self.c.wires())
+    }
+
+    pub fn contains(&self, wire: usize) -> bool {
+        self.a.contains(wire) || self.b.contains(wire) || self.c.contains(wire)
+    }
+
+    pub fn substitute(& mut self, wire: usize, value: & LinearCombination<F>) -> bool {
+        let in_a = self.a.substitute(wire, value);
+        let in_b = self.b.substitute(wire, value);
+        let in_c = self.c.substitute(wire, value);
+        in_a || in_b || in_c
+    }
+
+    pub fn remap(&self, mapping: & [Option<usize>]) -> Constraint<F> {
+        Constraint::new(self.a.remap(mapping), self.b.remap(mapping), self.c.remap(mapping))
+    }
+
+    pub fn is_satisfied(&self, witness: & [F]) -> bool {
+        self.a.evaluate(witness) * self.b.evaluate(witness) == self.c.evaluate(witness)
+    }
+
+}
+
+///
+/// Rank 1 constraint system over the prime field `F`.
+///
+/// Wires are laid out the circom way: wire 0 is the constant `one`, followed by the outputs, the
+/// public inputs, the private inputs and finally the internal signals.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct R1CS<F: PrimeField> {
+    pub n_wires: usize,
+    pub n_outputs: usize,
+    pub n_public_inputs: usize,
+    pub n_private_inputs: usize,
+    pub constraints: Vec<Constraint<F>>
+}
+
+impl<F: PrimeField> R1CS<F> {
+
+    pub fn new(n_outputs: usize, n_public_inputs: usize, n_private_inputs: usize) -> R1CS<F> {
+        R1CS {
+            n_wires: 1 + n_outputs + n_public_inputs + n_private_inputs,
+            n_outputs,
+            n_public_inputs,
+            n_private_inputs,
+            constraints: Vec::new()
+        }
+    }
+
+    ///
+    /// Allocates a new internal wire and returns its index.
+    ///
+    pub fn add_wire(& mut self) -> usize {
+        self.n_wires += 1;
+        self.n_wires - 1
+    }
+
+    pub fn add_constraint(& mut self, constraint: Constraint<F>) {
+        self.constraints.push(constraint);
+    }
+
+    ///
+    /// Number of public signals (outputs and public inputs), the `one` wire excluded.
+    ///
+    pub fn n_public(&self) -> usize {
+        self.n_outputs + self.n_public_inputs
+    }
+
+    ///
+    /// True for the `one` wire and the wires of the main component inputs and outputs. These wires
+    /// can never be optimized away.
+    ///
+    pub fn is_interface(&self, wire: usize) -> bool {
+        wire <= self.n_outputs + self.n_public_inputs + self.n_private_inputs
+    }
+
+    ///
+    /// Returns the indexes of the constraints not satisfied by the given witness.
+    ///
+    pub fn unsatisfied(&self, witness: & [F]) -> Vec<usize> {
+        self.constraints.iter()
+            .enumerate()
+            .filter(|(_, constraint)| !constraint.is_satisfied(witness))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+}
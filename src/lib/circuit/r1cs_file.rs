@@ -0,0 +1,220 @@
+use field::PrimeField;
+use binfile::{self, BinFile, BinFileError, SectionReader};
+use circuit::r1cs::{R1CS, Constraint, LinearCombination};
+use circuit::sym::SymbolMap;
+
+///
+/// iden3 `.r1cs` constraint system file (version 1):
+///
+/// - section 1, header: `n8:u32 prime[n8] n_wires:u32 n_pub_out:u32 n_pub_in:u32 n_prv_in:u32
+///   n_labels:u64 n_constraints:u32`
+/// - section 2, constraints: `A B C` per constraint, each being `n_terms:u32 (wire:u32 coefficient[n8])*`
+/// - section 3, wire to label map: `n_wires` label ids, as `u64`
+///
+pub const MAGIC: & [u8; 4] = b"r1cs";
+pub const VERSION: u32 = 1;
+
+const HEADER_SECTION: u32 = 1;
+const CONSTRAINTS_SECTION: u32 = 2;
+const WIRE_TO_LABEL_SECTION: u32 = 3;
+
+///
+/// Content of a `.r1cs` file: the constraint system and the label id (as listed in the `.sym`
+/// file) of the signal each wire comes from.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct R1CSFile<F: PrimeField> {
+    pub r1cs: R1CS<F>,
+    pub n_labels: u64,
+    pub wire_labels: Vec<u64>
+}
+
+impl<F: PrimeField> R1CSFile<F> {
+
+    ///
+    /// Builds the file content of a constraint system. Each wire is given the label of the first
+    /// signal using it in the symbol map, wire 0 having label 0.
+    ///
+    pub fn new(r1cs: R1CS<F>, symbols: & SymbolMap) -> R1CSFile<F> {
+
+        let mut wire_labels: Vec<Option<u64>> = vec!(None; r1cs.n_wires);
+        wire_labels[0] = Some(0);
+
+        for symbol in symbols.iter() {
+            if let Some(wire) = symbol.wire_id {
+                if wire < wire_labels.len() && wire_labels[wire].is_none() {
+                    wire_labels[wire] = Some(symbol.label_id as u64);
+                }
+            }
+        }
+
+        R1CSFile {
+            r1cs,
+            n_labels: symbols.len() as u64 + 1,
+            wire_labels: wire_labels.into_iter().enumerate().map(|(wire, label)| label.unwrap_or(wire as u64)).collect()
+        }
+
+    }
+
+    pub fn to_binfile(&self) -> BinFile {
+
+        let r1cs = &self.r1cs;
+        let mut file = BinFile::new(MAGIC, VERSION);
+
+        let mut header: Vec<u8> = Vec::new();
+        binfile::push_u32(& mut header, binfile::field_size::<F>() as u32);
+        binfile::push_prime::<F>(& mut header);
+        binfile::push_u32(& mut header, r1cs.n_wires as u32);
+        binfile::push_u32(& mut header, r1cs.n_outputs as u32);
+        binfile::push_u32(& mut header, r1cs.n_public_inputs as u32);
+        binfile::push_u32(& mut header, r1cs.n_private_inputs as u32);
+        binfile::push_u64(& mut header, self.n_labels);
+        binfile::push_u32(& mut header, r1cs.constraints.len() as u32);
+        file.add_section(HEADER_SECTION, header);
+
+        let mut constraints: Vec<u8> = Vec::new();
+        for constraint in &r1cs.constraints {
+            for lc in &[&constraint.a, &constraint.b, &constraint.c] {
+                binfile::push_u32(& mut constraints, lc.len() as u32);
+                for (wire, coefficient) in lc.terms() {
+                    binfile::push_u32(& mut constraints, *wire as u32);
+                    binfile::push_field(& mut constraints, coefficient);
+                }
+            }
+        }
+        file.add_section(CONSTRAINTS_SECTION, constraints);
+
+        let mut labels: Vec<u8> = Vec::new();
+        for label in &self.wire_labels {
+            binfile::push_u64(& mut labels, *label);
+        }
+        file.add_section(WIRE_TO_LABEL_SECTION, labels);
+
+        file
+
+    }
+
+    pub fn from_binfile(file: & BinFile) -> Result<R1CSFile<F>, BinFileError> {
+
+        if file.version != VERSION {
+            return Err(BinFileError::FormatError(format!("unsupported r1cs version {}", file.version)));
+        }
+
+        let mut header = SectionReader::new(file.required_section(HEADER_SECTION)?);
+        let n8 = header.read_u32()? as usize;
+        header.expect_prime::<F>(n8)?;
+
+        let n_wires = header.read_u32()? as usize;
+        let n_outputs = header.read_u32()? as usize;
+        let n_public_inputs = header.read_u32()? as usize;
+        let n_private_inputs = header.read_u32()? as usize;
+        let n_labels = header.read_u64()?;
+        let n_constraints = header.read_u32()? as usize;
+
+        if 1 + n_outputs + n_public_inputs + n_private_inputs > n_wires {
+            return Err(BinFileError::FormatError("more inputs and outputs than wires".to_string()));
+        }
+
+        let mut r1cs = R1CS::new(n_outputs, n_public_inputs, n_private_inputs);
+        r1cs.n_wires = n_wires;
+
+        let mut constraints = SectionReader::new(file.required_section(CONSTRAINTS_SECTION)?);
+        let read_lc = |reader: & mut SectionReader| -> Result<LinearCombination<F>, BinFileError> {
+            let mut lc = LinearCombination::new();
+            for _ in 0..reader.read_u32()? {
+                let wire = reader.read_u32()? as usize;
+                if wire >= n_wires {
+                    return Err(BinFileError::FormatError(format!("constraint uses unknown wire {}", wire)));
+                }
+                lc.add_term(wire, reader.read_field()?);
+            }
+            Ok(lc)
+        };
+
+        for _ in 0..n_constraints {
+            let a = read_lc(& mut constraints)?;
+            let b = read_lc(& mut constraints)?;
+            let c = read_lc(& mut constraints)?;
+            r1cs.add_constraint(Constraint::new(a, b, c));
+        }
+
+        let wire_labels = match file.section(WIRE_TO_LABEL_SECTION) {
+            Some(data) => {
+                let mut reader = SectionReader::new(data);
+                (0..n_wires).map(|_| reader.read_u64()).collect::<Result<Vec<u64>, BinFileError>>()?
+            },
+            None => (0..n_wires as u64).collect()
+        };
+
+        Ok(R1CSFile {
+            r1cs,
+            n_labels,
+            wire_labels
+        })
+
+    }
+
+    pub fn write<W: std::io::Write>(&self, writer: & mut W) -> std::io::Result<()> {
+        self.to_binfile().write(writer)
+    }
+
+    pub fn read<R: std::io::Read>(reader: R) -> Result<R1CSFile<F>, BinFileError> {
+        R1CSFile::from_binfile(&BinFile::read(reader, MAGIC)?)
+    }
+
+    pub fn save(&self, path: & std::path::Path) -> std::io::Result<()> {
+        self.to_binfile().save(path)
+    }
+
+    pub fn load(path: & std::path::Path) -> Result<R1CSFile<F>, BinFileError> {
+        R1CSFile::from_binfile(&BinFile::load(path, MAGIC)?)
+    }
+
+}
+
+#[cfg(test)]
+mod r1cs_file_test {
+
+    use field::PrimeField;
+    use field::bn254::Fr;
+    use circuit::r1cs::{R1CS, Constraint, LinearCombination};
+    use circuit::r1cs_file::R1CSFile;
+    use circuit::sym::SymbolMap;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_write_read_roundtrip() {
+
+        let mut r1cs: R1CS<Fr> = R1CS::new(1, 1, 1);
+        let internal = r1cs.add_wire();
+
+        let mut c = LinearCombination::wire(internal);
+        c.add_term(0, -Fr::from_u64(3));
+        r1cs.add_constraint(Constraint::new(LinearCombination::wire(2), LinearCombination::wire(3), c));
+        r1cs.add_constraint(Constraint::linear(LinearCombination::term(1, Fr::from_u64(2))));
+
+        let mut symbols = SymbolMap::new();
+        symbols.push(Some(1), 0, "main.out");
+        symbols.push(Some(2), 0, "main.a");
+        symbols.push(Some(3), 0, "main.b");
+        symbols.push(Some(1), 1, "main.sub.out");
+        symbols.push(Some(4), 0, "main.t");
+
+        let file = R1CSFile::new(r1cs, &symbols);
+
+        expect_that!(&file.n_labels, is(eq(6)));
+        expect_that!(&file.wire_labels, is(eq(vec!(0, 1, 2, 3, 5))));
+
+        let mut buffer: Vec<u8> = Vec::new();
+        file.write(& mut buffer).unwrap();
+
+        expect_that!(&&buffer[0..4], is(eq(&b"r1cs"[..])));
+
+        let read: R1CSFile<Fr> = R1CSFile::read(buffer.as_slice()).unwrap();
+
+        expect_that!(&read, is(eq(file)));
+
+    }
+
+}
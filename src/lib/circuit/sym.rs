@@ -0,0 +1,275 @@
+use std::io::BufRead;
+
+///
+/// Single line of a `.sym` file: a fully qualified signal name and the wire it ended up on.
+///
+/// The wire is `None` when the signal has no wire anymore (removed by an optimization pass). It is
+/// written as `-1` in the `.sym` format.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub label_id: usize,
+    pub wire_id: Option<usize>,
+    pub component_id: usize,
+    pub name: std::string::String
+}
+
+#[derive(Debug)]
+pub enum SymError {
+    IOError(std::io::Error),
+    FormatError(usize, std::string::String)
+}
+
+///
+/// Symbol map of an elaborated circuit. Follows the circom `.sym` format, one symbol per line:
+///
+/// `label_id,wire_id,component_id,name`
+///
+/// Label ids are attributed in insertion order, starting at 1 (the label 0 being the constant `one`
+/// signal, which is never listed).
+///
+#[derive(Debug, Clone, Default)]
+pub struct SymbolMap {
+    symbols: Vec<Symbol>,
+    names: std::collections::HashMap<std::string::String, usize>
+}
+
+///
+/// Returns true if `name` is `prefix` or a signal of the component or array named `prefix`.
+///
+fn is_under(name: & str, prefix: & str) -> bool {
+
+    if !name.starts_with(prefix) {
+        return false;
+    }
+
+    matches!(name.as_bytes().get(prefix.len()), None | Some(b'.') | Some(b'['))
+
+}
+
+impl SymbolMap {
+
+    pub fn new() -> SymbolMap {
+        SymbolMap::default()
+    }
+
+    ///
+    /// Registers a new signal name and returns its label id.
+    ///
+    /// Panics if the name is already registered, hierarchical names are unique by construction.
+    ///
+    pub fn push(& mut self, wire_id: Option<usize>, component_id: usize, name: & str) -> usize {
+
+        if self.names.contains_key(name) {
+            panic!("Symbol {} already registered", name);
+        }
+
+        let label_id = self.symbols.len() + 1;
+
+        self.names.insert(name.to_string(), self.symbols.len());
+        self.symbols.push(Symbol {
+            label_id,
+            wire_id,
+            component_id,
+            name: name.to_string()
+        });
+
+        label_id
+
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Symbol> {
+        self.symbols.iter()
+    }
+
+    ///
+    /// Retrieves a symbol by its fully qualified name, i.e. `main.sha256compression.T1[3].out[12]`.
+    ///
+    pub fn get(&self, name: & str) -> Option<& Symbol> {
+        self.names.get(name).map(|idx| &self.symbols[*idx])
+    }
+
+    ///
+    /// Retrieves the wire id of a signal by its fully qualified name. Returns `None` if the signal is
+    /// unknown or has been removed from the constraint system.
+    ///
+    pub fn wire_of(&self, name: & str) -> Option<usize> {
+        self.get(name).and_then(|symbol| symbol.wire_id)
+    }
+
+    ///
+    /// Lists all the names bound to a given wire.
+    ///
+    pub fn names_of(&self, wire_id: usize) -> Vec<& str> {
+        self.symbols.iter()
+            .filter(|symbol| symbol.wire_id == Some(wire_id))
+            .map(|symbol| symbol.name.as_str())
+            .collect()
+    }
+
+    ///
+    /// Lists the symbols under a component or signal array, `main.n2b` matches `main.n2b.out[0]` but
+    /// not `main.n2bx.out`.
+    ///
+    pub fn under<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a Symbol> + 'a {
+        self.symbols.iter().filter(move |symbol| is_under(&symbol.name, prefix))
+    }
+
+    ///
+    /// Applies a new wire numbering to all the symbols. `mapping` is indexed by the old wire id and
+    /// contains the new wire id, or `None` if the wire has been removed.
+    ///
+    pub fn remap_wires(& mut self, mapping: & [Option<usize>]) {
+        for symbol in & mut self.symbols {
+            symbol.wire_id = symbol.wire_id.and_then(|wire_id| mapping.get(wire_id).cloned().unwrap_or(None));
+        }
+    }
+
+    pub fn write<W: std::io::Write>(&self, writer: & mut W) -> std::io::Result<()> {
+
+        for symbol in &self.symbols {
+            let wire_id = match symbol.wire_id {
+                Some(wire_id) => wire_id as i64,
+                None => -1
+            };
+            writeln!(writer, "{},{},{},{}", symbol.label_id, wire_id, symbol.component_id, symbol.name)?;
+        }
+
+        Ok(())
+
+    }
+
+    pub fn read<R: std::io::Read>(reader: R) -> Result<SymbolMap, SymError> {
+
+        let mut map = SymbolMap::new();
+
+        for (idx, line) in std::io::BufReader::new(reader).lines().enumerate() {
+
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => return Err(SymError::IOError(error))
+            };
+
+            if line.trim().is_empty() {
+                continue ;
+            }
+
+            let fields: Vec<&str> = line.splitn(4, ',').collect();
+            if fields.len() != 4 {
+                return Err(SymError::FormatError(idx + 1, line.clone()));
+            }
+
+            let label_id: usize = fields[0].trim().parse().map_err(|_| SymError::FormatError(idx + 1, line.clone()))?;
+            let wire_id: i64 = fields[1].trim().parse().map_err(|_| SymError::FormatError(idx + 1, line.clone()))?;
+            let component_id: usize = fields[2].trim().parse().map_err(|_| SymError::FormatError(idx + 1, line.clone()))?;
+            let name = fields[3].trim();
+
+            if map.names.contains_key(name) || wire_id < -1 {
+                return Err(SymError::FormatError(idx + 1, line.clone()));
+            }
+
+            map.names.insert(name.to_string(), map.symbols.len());
+            map.symbols.push(Symbol {
+                label_id,
+                wire_id: if wire_id < 0 { None } else { Some(wire_id as usize) },
+                component_id,
+                name: name.to_string()
+            });
+
+        }
+
+        Ok(map)
+
+    }
+
+    pub fn save(&self, path: & std::path::Path) -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write(& mut file)
+    }
+
+    pub fn load(path: & std::path::Path) -> Result<SymbolMap, SymError> {
+        match std::fs::File::open(path) {
+            Ok(file) => SymbolMap::read(file),
+            Err(error) => Err(SymError::IOError(error))
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod sym_test {
+
+    use circuit::sym::{SymbolMap, SymError};
+
+    use galvanic_assert::matchers::*;
+
+    fn sample() -> SymbolMap {
+
+        let mut map = SymbolMap::new();
+
+        map.push(Some(1), 0, "main.out");
+        map.push(Some(2), 0, "main.in");
+        map.push(Some(3), 1, "main.n2b.out[0]");
+        map.push(Some(4), 1, "main.n2b.out[1]");
+        map.push(Some(2), 1, "main.n2b.in");
+        map.push(Some(5), 2, "main.n2bx.out");
+
+        map
+
+    }
+
+    #[test]
+    fn test_symbol_queries() {
+
+        let map = sample();
+
+        expect_that!(&map.len(), is(eq(6)));
+        expect_that!(&map.wire_of("main.n2b.out[1]"), is(eq(Some(4))));
+        expect_that!(&map.wire_of("main.n2b.out"), is(eq(None)));
+        expect_that!(&map.get("main.in").unwrap().label_id, is(eq(2)));
+        expect_that!(&map.names_of(2), is(eq(vec!("main.in", "main.n2b.in"))));
+        expect_that!(&map.under("main.n2b").count(), is(eq(3)));
+        expect_that!(&map.under("main.n2b.out").count(), is(eq(2)));
+
+    }
+
+    #[test]
+    fn test_write_read_roundtrip() {
+
+        let mut map = sample();
+        map.remap_wires(&[Some(0), Some(1), Some(2), None, Some(3), Some(4)]);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        map.write(& mut buffer).expect("Cannot write symbols");
+
+        let content = std::string::String::from_utf8(buffer.clone()).unwrap();
+        expect_that!(&content.lines().nth(2).unwrap(), is(eq("3,-1,1,main.n2b.out[0]")));
+
+        let read = SymbolMap::read(buffer.as_slice()).expect("Cannot read symbols");
+
+        expect_that!(&read.iter().cloned().collect::<Vec<_>>(), is(eq(map.iter().cloned().collect::<Vec<_>>())));
+        expect_that!(&read.wire_of("main.n2b.out[1]"), is(eq(Some(3))));
+
+    }
+
+    #[test]
+    fn test_read_invalid_line() {
+
+        match SymbolMap::read("1,1,0,main.out\n2,x,0,main.in\n".as_bytes()) {
+            Err(SymError::FormatError(line, _)) => {
+                expect_that!(&line, is(eq(2)));
+            },
+            other => panic!("Unexpected result {:?}", other)
+        }
+
+    }
+
+}
@@ -0,0 +1,1042 @@
+//
+// C witness calculators, in the spirit of circom's `--c` output: a single self contained file built
+// with any C99 compiler supporting `unsigned __int128` (gcc, clang, MinGW on windows, but not MSVC):
+//
+//     cc -O2 -o circuit circuit.c
+//     ./circuit input.json witness.wtns
+//
+// The tests build the generated files with `$CC`, or `cc` when it is not set, and are skipped when
+// no compiler can be run.
+//
+// Field elements are 4 limbs of 64 bits holding the canonical value, products going through
+// Montgomery multiplication. Signals live in a global array indexed by signal id, and `WIRES` maps
+// them to the wires of the witness. Temporaries are shared by all the component functions, a
+// statement being done with its temporaries before the next one runs.
+//
+// The program exits with 1 when the inputs are invalid or a constraint does not hold, printing the
+// location of the failing statement, and with 2 when the files cannot be read or written.
+//
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use binfile;
+use codegen::{self, Block, Operation, Program, Statement, Step};
+use field::bigint::U256;
+use field::PrimeField;
+
+const RUNTIME: & str = r#"
+typedef unsigned __int128 u128;
+
+static inline uint64_t add_raw(fr_t *r, const fr_t *a, const fr_t *b) {
+    u128 carry = 0;
+    for (int i = 0; i < 4; i++) {
+        carry = (u128) a->v[i] + b->v[i] + (carry >> 64);
+        r->v[i] = (uint64_t) carry;
+    }
+    return (uint64_t) (carry >> 64);
+}
+
+static inline uint64_t sub_raw(fr_t *r, const fr_t *a, const fr_t *b) {
+    uint64_t borrow = 0;
+    for (int i = 0; i < 4; i++) {
+        uint64_t left = a->v[i], right = b->v[i];
+        r->v[i] = left - right - borrow;
+        borrow = (left < right) || (left == right && borrow);
+    }
+    return borrow;
+}
+
+static inline int geq_raw(const fr_t *a, const fr_t *b) {
+    for (int i = 3; i >= 0; i--) {
+        if (a->v[i] != b->v[i]) {
+            return a->v[i] > b->v[i];
+        }
+    }
+    return 1;
+}
+
+static inline int fr_is_zero(const fr_t *a) {
+    return (a->v[0] | a->v[1] | a->v[2] | a->v[3]) == 0;
+}
+
+static inline int fr_equal(const fr_t *a, const fr_t *b) {
+    return a->v[0] == b->v[0] && a->v[1] == b->v[1] && a->v[2] == b->v[2] && a->v[3] == b->v[3];
+}
+
+static inline void fr_from_u64(fr_t *r, uint64_t value) {
+    r->v[0] = value;
+    r->v[1] = r->v[2] = r->v[3] = 0;
+}
+
+static inline void fr_reduce(fr_t *r) {
+    while (geq_raw(r, &P)) {
+        sub_raw(r, r, &P);
+    }
+}
+
+/* a * b / 2^256 mod p */
+static inline void mont_mul(fr_t *r, const fr_t *a, const fr_t *b) {
+    uint64_t t[6] = {0, 0, 0, 0, 0, 0};
+    for (int i = 0; i < 4; i++) {
+        u128 c = 0;
+        for (int j = 0; j < 4; j++) {
+            c = (u128) t[j] + (u128) a->v[j] * b->v[i] + (uint64_t) (c >> 64);
+            t[j] = (uint64_t) c;
+        }
+        c = (u128) t[4] + (uint64_t) (c >> 64);
+        t[4] = (uint64_t) c;
+        t[5] = (uint64_t) (c >> 64);
+        uint64_t m = t[0] * INV;
+        c = (u128) t[0] + (u128) m * P.v[0];
+        for (int j = 1; j < 4; j++) {
+            c = (u128) t[j] + (u128) m * P.v[j] + (uint64_t) (c >> 64);
+            t[j - 1] = (uint64_t) c;
+        }
+        c = (u128) t[4] + (uint64_t) (c >> 64);
+        t[3] = (uint64_t) c;
+        t[4] = t[5] + (uint64_t) (c >> 64);
+    }
+    fr_t result = {{t[0], t[1], t[2], t[3]}};
+    if (t[4] || geq_raw(&result, &P)) {
+        sub_raw(&result, &result, &P);
+    }
+    *r = result;
+}
+
+static inline void fr_boolean(fr_t *r, int value) {
+    fr_from_u64(r, value ? 1 : 0);
+}
+
+static inline void fr_copy(fr_t *r, const fr_t *a) {
+    *r = *a;
+}
+
+static inline void fr_add(fr_t *r, const fr_t *a, const fr_t *b) {
+    if (add_raw(r, a, b) || geq_raw(r, &P)) {
+        sub_raw(r, r, &P);
+    }
+}
+
+static inline void fr_sub(fr_t *r, const fr_t *a, const fr_t *b) {
+    if (sub_raw(r, a, b)) {
+        add_raw(r, r, &P);
+    }
+}
+
+static inline void fr_neg(fr_t *r, const fr_t *a) {
+    if (fr_is_zero(a)) {
+        *r = *a;
+    } else {
+        sub_raw(r, &P, a);
+    }
+}
+
+static inline void fr_mul(fr_t *r, const fr_t *a, const fr_t *b) {
+    mont_mul(r, a, b);
+    mont_mul(r, r, &R2);
+}
+
+static inline void fr_inc(fr_t *r, const fr_t *a) {
+    fr_t one;
+    fr_from_u64(&one, 1);
+    fr_add(r, a, &one);
+}
+
+static inline void fr_dec(fr_t *r, const fr_t *a) {
+    fr_t one;
+    fr_from_u64(&one, 1);
+    fr_sub(r, a, &one);
+}
+
+static inline int bit_of(const fr_t *a, int bit) {
+    return (a->v[bit / 64] >> (bit % 64)) & 1;
+}
+
+/* a^e, e being taken as an integer */
+static inline void fr_pow(fr_t *r, const fr_t *a, const fr_t *e) {
+    fr_t result, base = *a;
+    fr_from_u64(&result, 1);
+    for (int bit = 255; bit >= 0; bit--) {
+        fr_mul(&result, &result, &result);
+        if (bit_of(e, bit)) {
+            fr_mul(&result, &result, &base);
+        }
+    }
+    *r = result;
+}
+
+static inline int fr_div(fr_t *r, const fr_t *a, const fr_t *b) {
+    if (fr_is_zero(b)) {
+        return 1;
+    }
+    fr_t exponent, two, inverse;
+    fr_from_u64(&two, 2);
+    sub_raw(&exponent, &P, &two);
+    fr_pow(&inverse, b, &exponent);
+    fr_mul(r, a, &inverse);
+    return 0;
+}
+
+/* Integer division of the canonical values, b being non zero */
+static inline void divrem(fr_t *quotient, fr_t *remainder, const fr_t *a, const fr_t *b) {
+    fr_t q = {{0, 0, 0, 0}}, rem = {{0, 0, 0, 0}};
+    for (int bit = 255; bit >= 0; bit--) {
+        uint64_t overflow = rem.v[3] >> 63;
+        for (int i = 3; i > 0; i--) {
+            rem.v[i] = (rem.v[i] << 1) | (rem.v[i - 1] >> 63);
+        }
+        rem.v[0] = (rem.v[0] << 1) | (uint64_t) bit_of(a, bit);
+        if (overflow || geq_raw(&rem, b)) {
+            sub_raw(&rem, &rem, b);
+            q.v[bit / 64] |= (uint64_t) 1 << (bit % 64);
+        }
+    }
+    *quotient = q;
+    *remainder = rem;
+}
+
+static inline int fr_idiv(fr_t *r, const fr_t *a, const fr_t *b) {
+    fr_t remainder;
+    if (fr_is_zero(b)) {
+        return 1;
+    }
+    divrem(r, &remainder, a, b);
+    return 0;
+}
+
+static inline int fr_mod(fr_t *r, const fr_t *a, const fr_t *b) {
+    fr_t quotient;
+    if (fr_is_zero(b)) {
+        return 1;
+    }
+    divrem(&quotient, r, a, b);
+    return 0;
+}
+
+static inline void fr_not(fr_t *r, const fr_t *a) {
+    fr_boolean(r, fr_is_zero(a));
+}
+
+static inline void fr_lor(fr_t *r, const fr_t *a, const fr_t *b) {
+    fr_boolean(r, !fr_is_zero(a) || !fr_is_zero(b));
+}
+
+static inline void fr_land(fr_t *r, const fr_t *a, const fr_t *b) {
+    fr_boolean(r, !fr_is_zero(a) && !fr_is_zero(b));
+}
+
+static inline void fr_bnot(fr_t *r, const fr_t *a) {
+    for (int i = 0; i < 4; i++) {
+        r->v[i] = ~a->v[i] & MASK.v[i];
+    }
+    fr_reduce(r);
+}
+
+static inline void fr_bor(fr_t *r, const fr_t *a, const fr_t *b) {
+    for (int i = 0; i < 4; i++) {
+        r->v[i] = a->v[i] | b->v[i];
+    }
+    fr_reduce(r);
+}
+
+static inline void fr_bxor(fr_t *r, const fr_t *a, const fr_t *b) {
+    for (int i = 0; i < 4; i++) {
+        r->v[i] = a->v[i] ^ b->v[i];
+    }
+    fr_reduce(r);
+}
+
+static inline void fr_band(fr_t *r, const fr_t *a, const fr_t *b) {
+    for (int i = 0; i < 4; i++) {
+        r->v[i] = a->v[i] & b->v[i];
+    }
+    fr_reduce(r);
+}
+
+static inline void fr_eq(fr_t *r, const fr_t *a, const fr_t *b) {
+    fr_boolean(r, fr_equal(a, b));
+}
+
+static inline void fr_neq(fr_t *r, const fr_t *a, const fr_t *b) {
+    fr_boolean(r, !fr_equal(a, b));
+}
+
+/* Values above p/2 are negative */
+static inline int is_negative(const fr_t *a) {
+    return !geq_raw(&HALF, a);
+}
+
+static inline int compare(const fr_t *a, const fr_t *b) {
+    int negative_a = is_negative(a), negative_b = is_negative(b);
+    if (negative_a != negative_b) {
+        return negative_a ? -1 : 1;
+    }
+    if (fr_equal(a, b)) {
+        return 0;
+    }
+    return geq_raw(a, b) ? 1 : -1;
+}
+
+static inline void fr_lt(fr_t *r, const fr_t *a, const fr_t *b) {
+    fr_boolean(r, compare(a, b) < 0);
+}
+
+static inline void fr_leq(fr_t *r, const fr_t *a, const fr_t *b) {
+    fr_boolean(r, compare(a, b) <= 0);
+}
+
+static inline void fr_gt(fr_t *r, const fr_t *a, const fr_t *b) {
+    fr_boolean(r, compare(a, b) > 0);
+}
+
+static inline void fr_geq(fr_t *r, const fr_t *a, const fr_t *b) {
+    fr_boolean(r, compare(a, b) >= 0);
+}
+
+static inline void shift(fr_t *r, const fr_t *a, const fr_t *amount, int left) {
+    fr_t absolute = *amount;
+    if (is_negative(amount)) {
+        sub_raw(&absolute, &P, amount);
+        left = !left;
+    }
+    unsigned count = (absolute.v[1] | absolute.v[2] | absolute.v[3] || absolute.v[0] >= 65536) ? 256 : (unsigned) absolute.v[0];
+    fr_t result = {{0, 0, 0, 0}};
+    if (count < 256) {
+        unsigned limbs = count / 64, bits = count % 64;
+        for (int i = 0; i < 4; i++) {
+            int from = left ? i - (int) limbs : i + (int) limbs;
+            if (from < 0 || from > 3) {
+                continue;
+            }
+            result.v[i] = left ? a->v[from] << bits : a->v[from] >> bits;
+            if (bits && left && from > 0) {
+                result.v[i] |= a->v[from - 1] >> (64 - bits);
+            }
+            if (bits && !left && from < 3) {
+                result.v[i] |= a->v[from + 1] << (64 - bits);
+            }
+        }
+    }
+    if (left) {
+        for (int i = 0; i < 4; i++) {
+            result.v[i] &= MASK.v[i];
+        }
+        fr_reduce(&result);
+    }
+    *r = result;
+}
+
+static inline void fr_shl(fr_t *r, const fr_t *a, const fr_t *b) {
+    shift(r, a, b, 1);
+}
+
+static inline void fr_shr(fr_t *r, const fr_t *a, const fr_t *b) {
+    shift(r, a, b, 0);
+}
+
+static inline void fr_to_decimal(char *buffer, const fr_t *a) {
+    char digits[80];
+    int count = 0;
+    fr_t value = *a;
+    do {
+        u128 remainder = 0;
+        for (int i = 3; i >= 0; i--) {
+            u128 current = (remainder << 64) | value.v[i];
+            value.v[i] = (uint64_t) (current / 10);
+            remainder = current % 10;
+        }
+        digits[count++] = (char) ('0' + (int) remainder);
+    } while (!fr_is_zero(&value));
+    for (int i = 0; i < count; i++) {
+        buffer[i] = digits[count - 1 - i];
+    }
+    buffer[count] = 0;
+}
+"#;
+
+const ERRORS: & str = r#"
+static inline int fail(size_t location, const char *reason) {
+    fprintf(stderr, "%s at %s\n", reason, LOCATIONS[location]);
+    return 1;
+}
+
+static inline int constraint_failed(size_t location, const fr_t *left, const fr_t *right) {
+    char left_value[80], right_value[80];
+    fr_to_decimal(left_value, left);
+    fr_to_decimal(right_value, right);
+    fprintf(stderr, "Constraint not satisfied at %s\n\tleft: %s\n\tright: %s\n", LOCATIONS[location], left_value, right_value);
+    return 1;
+}
+"#;
+
+const MAIN: & str = r#"
+static const char *cursor;
+
+static int syntax_error(const char *expected) {
+    fprintf(stderr, "Invalid JSON: expected %s\n", expected);
+    return 1;
+}
+
+static void skip_spaces(void) {
+    while (*cursor == ' ' || *cursor == '\t' || *cursor == '\n' || *cursor == '\r') {
+        cursor++;
+    }
+}
+
+/* Reads a string into buffer, escaped characters being kept as is */
+static int read_string(char *buffer, size_t size) {
+    size_t length = 0;
+    if (*cursor != '"') {
+        return syntax_error("a string");
+    }
+    cursor++;
+    while (*cursor != '"') {
+        if (*cursor == 0) {
+            return syntax_error("the end of a string");
+        }
+        if (*cursor == '\\' && cursor[1] != 0) {
+            cursor++;
+        }
+        if (length + 1 >= size) {
+            fprintf(stderr, "Invalid JSON: string too long\n");
+            return 1;
+        }
+        buffer[length++] = *cursor++;
+    }
+    cursor++;
+    buffer[length] = 0;
+    return 0;
+}
+
+static int digit_of(char character, unsigned base) {
+    int digit = -1;
+    if (character >= '0' && character <= '9') {
+        digit = character - '0';
+    } else if (character >= 'a' && character <= 'f') {
+        digit = character - 'a' + 10;
+    } else if (character >= 'A' && character <= 'F') {
+        digit = character - 'A' + 10;
+    }
+    return digit < (int) base ? digit : -1;
+}
+
+/* Decimal, possibly negative, or 0x prefixed hexadecimal number, reduced modulo p */
+static int parse_number(fr_t *value, const char *text, const char *name) {
+    const char *digits = text;
+    int negative = 0;
+    unsigned base = 10;
+    fr_t radix, digit;
+    if (*digits == '-') {
+        negative = 1;
+        digits++;
+    }
+    if (digits[0] == '0' && (digits[1] == 'x' || digits[1] == 'X')) {
+        base = 16;
+        digits += 2;
+    }
+    if (*digits == 0) {
+        fprintf(stderr, "Invalid value for input signal %s: \"%s\" is not a decimal or hexadecimal number\n", name, text);
+        return 1;
+    }
+    fr_from_u64(value, 0);
+    fr_from_u64(&radix, base);
+    for (; *digits; digits++) {
+        int next = digit_of(*digits, base);
+        if (next < 0) {
+            fprintf(stderr, "Invalid value for input signal %s: \"%s\" is not a decimal or hexadecimal number\n", name, text);
+            return 1;
+        }
+        fr_from_u64(&digit, (uint64_t) next);
+        fr_mul(value, value, &radix);
+        fr_add(value, value, &digit);
+    }
+    if (negative) {
+        fr_neg(value, value);
+    }
+    return 0;
+}
+
+static int read_scalar(fr_t *value, const char *name) {
+    char text[256];
+    size_t length = 0;
+    if (*cursor == '"') {
+        return read_string(text, sizeof text) || parse_number(value, text, name);
+    }
+    while (*cursor == '-' || *cursor == '+' || *cursor == '.' || (*cursor >= '0' && *cursor <= '9') || *cursor == 'e' || *cursor == 'E') {
+        if (length + 1 >= sizeof text) {
+            fprintf(stderr, "Invalid value for input signal %s: number too long, large values must be given as strings\n", name);
+            return 1;
+        }
+        text[length++] = *cursor++;
+    }
+    text[length] = 0;
+    if (length == 0) {
+        return syntax_error("a number, a string or an array");
+    }
+    if (strpbrk(text, ".eE+") != NULL) {
+        fprintf(stderr, "Invalid value for input signal %s: %s is not an integer\n", name, text);
+        return 1;
+    }
+    return parse_number(value, text, name);
+}
+
+/* Reads the array at the cursor up to its end, parsing the scalars it contains and counting its
+   elements; *flat is cleared when one of them is an array */
+static int skip_array(const char *name, size_t *length, int *flat) {
+    fr_t value;
+    size_t inner_length;
+    int inner_flat;
+    cursor++;
+    *length = 0;
+    *flat = 1;
+    skip_spaces();
+    if (*cursor == ']') {
+        cursor++;
+        return 0;
+    }
+    for (;;) {
+        skip_spaces();
+        if (*cursor == '[') {
+            *flat = 0;
+            if (skip_array(name, &inner_length, &inner_flat)) {
+                return 1;
+            }
+        } else if (read_scalar(&value, name)) {
+            return 1;
+        }
+        (*length)++;
+        skip_spaces();
+        if (*cursor == ']') {
+            cursor++;
+            return 0;
+        }
+        if (*cursor != ',') {
+            return syntax_error(", or ]");
+        }
+        cursor++;
+    }
+}
+
+/* Reads the value of the input from the given dimension on, storing it in the signals of the input
+   in order; nested arrays follow the dimensions, or are flat as snarkjs writes them */
+static int read_values(const input_t *input, size_t dimension, const char *name, size_t *count) {
+    const char *start, *end;
+    char element[512];
+    size_t length, size, product = 1;
+    int flat;
+    fr_t value;
+    skip_spaces();
+    if (dimension == input->n_dimensions) {
+        if (*cursor == '[') {
+            fprintf(stderr, "Invalid value for input signal %s: expected a single value, found an array\n", name);
+            return 1;
+        }
+        if (read_scalar(&value, input->name)) {
+            return 1;
+        }
+        s[INPUT_SIGNALS[input->first + (*count)++]] = value;
+        return 0;
+    }
+    if (*cursor != '[') {
+        if (read_scalar(&value, input->name)) {
+            return 1;
+        }
+        fprintf(stderr, "Invalid value for input signal %s: expected an array, found a single value\n", name);
+        return 1;
+    }
+    start = cursor;
+    if (skip_array(input->name, &length, &flat)) {
+        return 1;
+    }
+    end = cursor;
+    size = INPUT_DIMENSIONS[input->dimension + dimension];
+    for (size_t inner = dimension; inner < input->n_dimensions; inner++) {
+        product *= INPUT_DIMENSIONS[input->dimension + inner];
+    }
+    if (length != size && !(flat && length == product)) {
+        fprintf(stderr, "Invalid value for input signal %s: expected %zu values, found %zu\n", name, size, length);
+        return 1;
+    }
+    cursor = start + 1;
+    for (size_t index = 0; index < length; index++) {
+        if (length == size) {
+            snprintf(element, sizeof element, "%s[%zu]", name, index);
+            if (read_values(input, dimension + 1, element, count)) {
+                return 1;
+            }
+        } else {
+            skip_spaces();
+            if (read_scalar(&value, input->name)) {
+                return 1;
+            }
+            s[INPUT_SIGNALS[input->first + (*count)++]] = value;
+        }
+        skip_spaces();
+        cursor++;
+    }
+    cursor = end;
+    return 0;
+}
+
+static int read_inputs(const char *text) {
+    char name[256];
+    int given[N_INPUTS + 1] = {0};
+    cursor = text;
+    skip_spaces();
+    if (*cursor != '{') {
+        return syntax_error("an object mapping input names to values");
+    }
+    cursor++;
+    skip_spaces();
+    if (*cursor == '}') {
+        cursor++;
+    } else {
+        for (;;) {
+            size_t input = 0, count = 0;
+            skip_spaces();
+            if (read_string(name, sizeof name)) {
+                return 1;
+            }
+            skip_spaces();
+            if (*cursor != ':') {
+                return syntax_error(":");
+            }
+            cursor++;
+            while (input < N_INPUTS && strcmp(INPUTS[input].name, name) != 0) {
+                input++;
+            }
+            if (input == N_INPUTS) {
+                fprintf(stderr, "The main component has no input signal %s\n", name);
+                return 1;
+            }
+            if (read_values(&INPUTS[input], 0, INPUTS[input].name, &count)) {
+                return 1;
+            }
+            given[input] = 1;
+            skip_spaces();
+            if (*cursor == '}') {
+                cursor++;
+                break;
+            }
+            if (*cursor != ',') {
+                return syntax_error(", or }");
+            }
+            cursor++;
+        }
+    }
+    for (size_t input = 0; input < N_INPUTS; input++) {
+        if (!given[input]) {
+            fprintf(stderr, "No value given for input signal %s\n", INPUTS[input].name);
+            return 1;
+        }
+    }
+    return 0;
+}
+
+static char *read_file(const char *path) {
+    FILE *file = fopen(path, "rb");
+    char *content = NULL;
+    size_t length = 0, capacity = 0, count;
+    if (file == NULL) {
+        return NULL;
+    }
+    do {
+        if (length + 4096 + 1 > capacity) {
+            capacity = 2 * capacity + 4096 + 1;
+            content = realloc(content, capacity);
+            if (content == NULL) {
+                fclose(file);
+                return NULL;
+            }
+        }
+        count = fread(content + length, 1, 4096, file);
+        length += count;
+    } while (count > 0);
+    content[length] = 0;
+    fclose(file);
+    return content;
+}
+
+static void put_u32(FILE *file, uint32_t value) {
+    for (int i = 0; i < 4; i++) {
+        fputc((int) ((value >> (8 * i)) & 0xff), file);
+    }
+}
+
+static void put_u64(FILE *file, uint64_t value) {
+    put_u32(file, (uint32_t) value);
+    put_u32(file, (uint32_t) (value >> 32));
+}
+
+static void put_fr(FILE *file, const fr_t *value) {
+    for (int i = 0; i < N8; i++) {
+        fputc((int) ((value->v[i / 8] >> (8 * (i % 8))) & 0xff), file);
+    }
+}
+
+/* iden3 .wtns file, version 2: a header section and the witness in wire order */
+static int write_witness(const char *path) {
+    static fr_t witness[N_WIRES];
+    FILE *file = fopen(path, "wb");
+    if (file == NULL) {
+        return 1;
+    }
+    fr_from_u64(&witness[0], 1);
+    for (size_t signal = 0; signal < N_SIGNALS; signal++) {
+        if (WIRES[signal] >= 0) {
+            witness[WIRES[signal]] = s[signal];
+        }
+    }
+    fwrite("wtns", 1, 4, file);
+    put_u32(file, 2);
+    put_u32(file, 2);
+    put_u32(file, 1);
+    put_u64(file, 4 + N8 + 4);
+    put_u32(file, N8);
+    put_fr(file, &P);
+    put_u32(file, N_WIRES);
+    put_u32(file, 2);
+    put_u64(file, (uint64_t) N_WIRES * N8);
+    for (size_t wire = 0; wire < N_WIRES; wire++) {
+        put_fr(file, &witness[wire]);
+    }
+    int failed = ferror(file);
+    return fclose(file) != 0 || failed;
+}
+
+int main(int argc, char **argv) {
+    char *input;
+    if (argc != 3) {
+        fprintf(stderr, "Usage: %s <input.json> <witness.wtns>\n", argv[0]);
+        return 2;
+    }
+    input = read_file(argv[1]);
+    if (input == NULL) {
+        perror(argv[1]);
+        return 2;
+    }
+    if (read_inputs(input) || component_0()) {
+        free(input);
+        return 1;
+    }
+    free(input);
+    if (write_witness(argv[2])) {
+        perror(argv[2]);
+        return 2;
+    }
+    return 0;
+}
+"#;
+
+fn limbs(value: & U256) -> std::string::String {
+    format!("{{{{0x{:016x}, 0x{:016x}, 0x{:016x}, 0x{:016x}}}}}", value.0[0], value.0[1], value.0[2], value.0[3])
+}
+
+///
+/// C string literal, for the locations of the statements.
+///
+fn quoted(text: & str) -> std::string::String {
+
+    let mut quoted = std::string::String::from("\"");
+
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => {},
+            '?' => quoted.push_str("\\?"),
+            character => quoted.push(character)
+        }
+    }
+
+    quoted.push('"');
+    quoted
+
+}
+
+struct Emitter<F> {
+    code: std::string::String,
+    constants: Vec<F>,
+    constant_ids: HashMap<[u64; 4], usize>
+}
+
+impl<F: PrimeField> Emitter<F> {
+
+    fn constant(& mut self, value: F) -> usize {
+        let constants = & mut self.constants;
+        *self.constant_ids.entry(value.to_u256().0).or_insert_with(|| {
+            constants.push(value);
+            constants.len() - 1
+        })
+    }
+
+    fn line(& mut self, indent: usize, line: & str) {
+        self.code.push_str(&"    ".repeat(indent));
+        self.code.push_str(line);
+        self.code.push('\n');
+    }
+
+    fn block(& mut self, block: & Block<F>, location: usize, indent: usize) {
+        for step in &block.steps {
+            match step {
+                Step::Let(temp, Operation::Constant(value)) => {
+                    let constant = self.constant(*value);
+                    self.line(indent, &format!("t[{}] = CONSTANTS[{}];", temp, constant));
+                },
+                Step::Let(temp, Operation::Signal(signal)) => self.line(indent, &format!("t[{}] = s[{}];", temp, signal)),
+                Step::Let(temp, Operation::Prefix(operator, operand)) => {
+                    self.line(indent, &format!("fr_{}(&t[{}], &t[{}]);", codegen::prefix_name(*operator), temp, operand))
+                },
+                Step::Let(temp, Operation::Binary(operator, left, right)) => {
+                    let call = format!("fr_{}(&t[{}], &t[{}], &t[{}])", codegen::binary_name(*operator), temp, left, right);
+                    if codegen::is_division(*operator) {
+                        self.line(indent, &format!("if ({}) return fail({}, \"Division by zero\");", call, location));
+                    } else {
+                        self.line(indent, &format!("{};", call));
+                    }
+                },
+                Step::Select { temp, condition, if_true, if_false } => {
+                    self.line(indent, &format!("if (!fr_is_zero(&t[{}])) {{", condition));
+                    self.block(if_true, location, indent + 1);
+                    self.line(indent + 1, &format!("t[{}] = t[{}];", temp, if_true.result));
+                    self.line(indent, "} else {");
+                    self.block(if_false, location, indent + 1);
+                    self.line(indent + 1, &format!("t[{}] = t[{}];", temp, if_false.result));
+                    self.line(indent, "}");
+                }
+            }
+        }
+    }
+
+    fn statement(& mut self, statement: & Statement<F>) {
+        match statement {
+            Statement::Assign { signal, value, location } => {
+                self.block(value, *location, 1);
+                self.line(1, &format!("s[{}] = t[{}];", signal, value.result));
+            },
+            Statement::Check { left, right, location } => {
+                self.block(left, *location, 1);
+                self.block(right, *location, 1);
+                self.line(1, &format!("if (!fr_equal(&t[{l}], &t[{r}])) return constraint_failed({}, &t[{l}], &t[{r}]);", location, l = left.result, r = right.result));
+            },
+            Statement::Run(component) => self.line(1, &format!("if (component_{}()) return 1;", component))
+        }
+    }
+
+}
+
+///
+/// C source of the witness calculator of a program.
+///
+pub fn generate<F: PrimeField>(program: & Program<F>) -> std::string::String {
+
+    let mut emitter = Emitter { code: std::string::String::new(), constants: Vec::new(), constant_ids: HashMap::new() };
+
+    for (id, component) in program.components.iter().enumerate() {
+        emitter.line(0, &format!("/* {}: {} */", component.name, component.template));
+        emitter.line(0, &format!("static int component_{}(void) {{", id));
+        for statement in &component.statements {
+            emitter.statement(statement);
+        }
+        emitter.line(1, "return 0;");
+        emitter.line(0, "}");
+        emitter.line(0, "");
+    }
+
+    let modulus = F::modulus();
+    let bits = modulus.bits();
+    let mask = U256::ONE.shl(bits).overflowing_sub(&U256::ONE).0;
+
+    let mut source = std::string::String::new();
+
+    let _ = write!(source, "\
+/*
+ * Witness calculator of {template}, generated by snarkrs:
+ *
+ *     cc -O2 -o witness <file>.c
+ *     ./witness input.json witness.wtns
+ */
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define N8 {n8}
+#define N_SIGNALS {signals}
+#define N_WIRES {wires}
+#define N_INPUTS {inputs}
+
+typedef struct {{
+    uint64_t v[4];
+}} fr_t;
+
+typedef struct {{
+    const char *name;
+    size_t first;
+    size_t dimension;
+    size_t n_dimensions;
+}} input_t;
+
+static const fr_t P = {p};
+static const fr_t R2 = {r2};
+static const fr_t HALF = {half};
+static const fr_t MASK = {mask};
+static const uint64_t INV = 0x{inv:016x}u;
+",
+        template = program.template,
+        n8 = binfile::field_size::<F>(),
+        signals = program.n_signals(),
+        wires = program.n_wires,
+        inputs = program.inputs.len(),
+        p = limbs(&modulus),
+        r2 = limbs(&F::from_u64(2).pow(&U256::from_u64(512)).to_u256()),
+        half = limbs(&modulus.shr(1)),
+        mask = limbs(&mask),
+        inv = codegen::montgomery_inverse(modulus.0[0])
+    );
+
+    source.push_str(RUNTIME);
+
+    let constants: Vec<std::string::String> = emitter.constants.iter().map(|constant| limbs(&constant.to_u256())).collect();
+    let _ = writeln!(source, "\nstatic const fr_t CONSTANTS[] = {{\n    {}\n}};", if constants.is_empty() { "{{0, 0, 0, 0}}".to_string() } else { constants.join(",\n    ") });
+
+    let locations: Vec<std::string::String> = program.locations.iter().map(|location| quoted(location)).collect();
+    let _ = writeln!(source, "\nstatic const char *LOCATIONS[] = {{\n    {}\n}};", if locations.is_empty() { "\"\"".to_string() } else { locations.join(",\n    ") });
+
+    let wires: Vec<std::string::String> = program.wires.iter().map(|wire| match wire {
+        Some(wire) => wire.to_string(),
+        None => "-1".to_string()
+    }).collect();
+    let _ = writeln!(source, "\nstatic const long WIRES[N_SIGNALS] = {{{}}};", wires.join(", "));
+
+    let input_signals: Vec<std::string::String> = program.inputs.iter().flat_map(|input| input.signals.iter().map(usize::to_string)).collect();
+    let _ = writeln!(source, "\nstatic const size_t INPUT_SIGNALS[] = {{{}}};", if input_signals.is_empty() { "0".to_string() } else { input_signals.join(", ") });
+
+    let input_dimensions: Vec<std::string::String> = program.inputs.iter().flat_map(|input| input.dimensions.iter().map(usize::to_string)).collect();
+    let _ = writeln!(source, "\nstatic const size_t INPUT_DIMENSIONS[] = {{{}}};", if input_dimensions.is_empty() { "0".to_string() } else { input_dimensions.join(", ") });
+
+    let mut first = 0;
+    let mut dimension = 0;
+    let mut inputs: Vec<std::string::String> = Vec::new();
+    for input in &program.inputs {
+        inputs.push(format!("{{{}, {}, {}, {}}}", quoted(&input.name), first, dimension, input.dimensions.len()));
+        first += input.signals.len();
+        dimension += input.dimensions.len();
+    }
+    let _ = writeln!(source, "\nstatic const input_t INPUTS[] = {{\n    {}\n}};", if inputs.is_empty() { "{\"\", 0, 0, 0}".to_string() } else { inputs.join(",\n    ") });
+
+    let _ = writeln!(source, "\nstatic fr_t s[N_SIGNALS];\nstatic fr_t t[{}];", program.n_temps.max(1));
+    source.push_str(ERRORS);
+    source.push('\n');
+
+    for id in 0..program.components.len() {
+        let _ = writeln!(source, "static int component_{}(void);", id);
+    }
+    source.push('\n');
+
+    source.push_str(&emitter.code);
+    source.push_str(MAIN.trim_start());
+
+    source
+
+}
+
+#[cfg(test)]
+mod c_test {
+
+    use std::ffi::{OsStr, OsString};
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    use codegen::backend_test::{self, expected, load, run};
+    use codegen::{self, c};
+    use elaborator::ElaboratedCircuit;
+    use field::bn254::Fr;
+
+    use galvanic_assert::matchers::*;
+
+    ///
+    /// The C compiler of the tests, `$CC` defaulting to `cc`, or `None` when it cannot be run.
+    ///
+    fn compiler() -> Option<OsString> {
+        let compiler = std::env::var_os("CC").unwrap_or_else(|| "cc".into());
+        match Command::new(&compiler).arg("--version").output() {
+            Ok(_) => Some(compiler),
+            Err(error) => {
+                eprintln!("Skipping, cannot run the C compiler {:?}: {}", compiler, error);
+                None
+            }
+        }
+    }
+
+    ///
+    /// Generates and compiles the witness calculator of a circuit.
+    ///
+    fn build(compiler: & OsStr, circuit: & ElaboratedCircuit<Fr>, name: & str) -> PathBuf {
+
+        let directory = std::env::temp_dir().join(format!("snarkrs_c_test_{}_{}", std::process::id(), name));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let program = codegen::lower(circuit, &circuit.symbols(), circuit.n_wires()).expect("Lowering failed");
+        let source = directory.join(format!("{}.c", name));
+        std::fs::write(&source, c::generate(&program)).unwrap();
+
+        let binary = directory.join(name);
+        let output = Command::new(compiler).args(["-O1", "-std=c99", "-Wall", "-Wextra", "-Werror", "-o"]).arg(&binary).arg(&source).output().unwrap();
+        assert!(output.status.success(), "{}", std::string::String::from_utf8_lossy(&output.stderr));
+
+        binary
+
+    }
+
+    #[test]
+    fn test_generated_witness() {
+        let compiler = match compiler() { Some(compiler) => compiler, None => return };
+        backend_test::check_witness(|name, circuit, inputs| {
+            let binary = build(&compiler, circuit, name);
+            let witnesses = inputs.iter().map(|inputs| run(&binary, inputs)).collect();
+            std::fs::remove_dir_all(binary.parent().unwrap()).unwrap();
+            witnesses
+        });
+    }
+
+    #[test]
+    fn test_generated_locations() {
+        backend_test::check_locations(|program| c::generate(program).into_bytes());
+    }
+
+    #[test]
+    fn test_generated_input_errors() {
+
+        let compiler = match compiler() { Some(compiler) => compiler, None => return };
+        let circuit = load("matrix");
+        let binary = build(&compiler, &circuit, "matrix_inputs");
+
+        for inputs in &[
+            r#"{"x": 0}"#,
+            r#"{"m": [1, 2, 3, 4], "x": 0, "y": 1}"#,
+            r#"{"m": [[1, 2], [3]], "x": 0}"#,
+            r#"{"m": [[1, 2], [3, 4], [5, 6]], "x": 0}"#,
+            r#"{"m": [[1, [2]], [3, 4]], "x": 0}"#,
+            r#"{"m": [1, 2, [3], 4], "x": 0}"#,
+            r#"{"m": 1, "x": 0}"#,
+            r#"{"m": [[1, 2], 3], "x": 0}"#,
+            r#"{"m": [[1, 2], [3, 4]], "x": [0]}"#,
+            r#"{"m": [1, 2, 3], "x": 0}"#
+        ] {
+            expect_that!(&run(&binary, inputs), is(eq(expected(&circuit, inputs))));
+        }
+
+        expect_that!(&run(&binary, r#"{"m": [[1, 2, 3], [4, 5]], "x": 0}"#), is(eq(Err("Invalid value for input signal m[0]: expected 2 values, found 3".to_string()))));
+        expect_that!(&run(&binary, r#"{"m": [1, 2, 3, 4], "x": 1.5}"#), is(eq(Err("Invalid value for input signal x: 1.5 is not an integer".to_string()))));
+        expect_that!(&run(&binary, r#"{"m": [1, 2, 3, "z"], "x": 0}"#), is(eq(Err("Invalid value for input signal m: \"z\" is not a decimal or hexadecimal number".to_string()))));
+
+        std::fs::remove_dir_all(binary.parent().unwrap()).unwrap();
+
+    }
+
+}
@@ -0,0 +1,740 @@
+//
+// Witness calculators generated from an elaborated circuit, for computing witnesses without the
+// interpreter.
+//
+// The circuit is first lowered to a `Program` the backends translate: one function per component
+// (template instance), running its assignments in order as circom does. A component runs once all
+// its input signals are assigned, the statement assigning the last one calling it, and components
+// without inputs run when their parent starts. Assignments having no side effect, the ones reading
+// signals that are not known yet are postponed until they are. Each constraint is checked as soon as all its
+// signals are known. The order is decided when generating the code, so the generated code needs
+// no counters nor scheduling.
+//
+// Expressions are lowered to steps computing temporaries, sub expressions shared by the
+// interpreter being computed once. Ternary branches stay lazy, and residual function calls are
+// inlined (see `ElaboratedCircuit::inline_function`).
+//
+
+pub mod c;
+pub mod rust;
+pub mod wasm;
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use circuit::sym::SymbolMap;
+use elaborator::{ElaboratedCircuit, Location};
+use elaborator::ir::Ir;
+use field::PrimeField;
+use parser::typed_ast::{BinaryOperator, PrefixOperator, SignalDirection, Span};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodegenError {
+    Unsupported { location: Box<Location>, reason: std::string::String },
+    UnassignedSignal(std::string::String)
+}
+
+impl std::fmt::Display for CodegenError {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CodegenError::Unsupported { location, reason } => write!(formatter, "{} at {}", reason, location),
+            CodegenError::UnassignedSignal(name) => write!(formatter, "Signal {} is never assigned", name)
+        }
+    }
+}
+
+///
+/// Computation of a temporary from the temporaries computed before it.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation<F> {
+    Constant(F),
+    Signal(usize),
+    Prefix(PrefixOperator, usize),
+    Binary(BinaryOperator, usize, usize)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step<F> {
+    Let(usize, Operation<F>),
+
+    ///
+    /// `temp = condition != 0 ? if_true : if_false`, only the taken branch being computed.
+    ///
+    Select { temp: usize, condition: usize, if_true: Block<F>, if_false: Block<F> }
+}
+
+///
+/// Steps computing the temporary `result`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block<F> {
+    pub steps: Vec<Step<F>>,
+    pub result: usize
+}
+
+///
+/// Statement of a component function. Temporaries are local to a statement, and `location` is an
+/// index in `Program::locations`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement<F> {
+    Assign { signal: usize, value: Block<F>, location: usize },
+    Check { left: Block<F>, right: Block<F>, location: usize },
+    Run(usize)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentCode<F> {
+    pub name: std::string::String,
+    pub template: std::string::String,
+    pub statements: Vec<Statement<F>>
+}
+
+///
+/// Input signal of the main component, `signals` listing the ids of its elements in row major order.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Input {
+    pub name: std::string::String,
+    pub dimensions: Vec<usize>,
+    pub signals: Vec<usize>
+}
+
+///
+/// Witness calculator of a circuit. Component 0 is the main one, the entry point.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program<F> {
+    pub template: std::string::String,
+    pub components: Vec<ComponentCode<F>>,
+    pub inputs: Vec<Input>,
+
+    ///
+    /// Statements of the error messages, their files being relative to the directory of the main
+    /// one so that the generated code does not depend on where the circuit is.
+    ///
+    pub locations: Vec<std::string::String>,
+
+    ///
+    /// Wire of each signal, `None` for the signals removed from the constraint system.
+    ///
+    pub wires: Vec<Option<usize>>,
+    pub n_wires: usize,
+    pub n_outputs: usize,
+    pub n_public_inputs: usize,
+    pub n_private_inputs: usize,
+
+    ///
+    /// Largest number of temporaries of a statement.
+    ///
+    pub n_temps: usize
+}
+
+impl<F> Program<F> {
+
+    pub fn n_signals(&self) -> usize {
+        self.wires.len()
+    }
+
+}
+
+pub fn prefix_name(operator: PrefixOperator) -> & 'static str {
+    match operator {
+        PrefixOperator::Plus => "copy",
+        PrefixOperator::Minus => "neg",
+        PrefixOperator::Not => "not",
+        PrefixOperator::Complement => "bnot",
+        PrefixOperator::Increment => "inc",
+        PrefixOperator::Decrement => "dec"
+    }
+}
+
+pub fn binary_name(operator: BinaryOperator) -> & 'static str {
+    match operator {
+        BinaryOperator::Add => "add",
+        BinaryOperator::Sub => "sub",
+        BinaryOperator::Mul => "mul",
+        BinaryOperator::Div => "div",
+        BinaryOperator::IntDiv => "idiv",
+        BinaryOperator::Mod => "mod",
+        BinaryOperator::Pow => "pow",
+        BinaryOperator::Or => "lor",
+        BinaryOperator::And => "land",
+        BinaryOperator::BitOr => "bor",
+        BinaryOperator::BitXor => "bxor",
+        BinaryOperator::BitAnd => "band",
+        BinaryOperator::Equal => "eq",
+        BinaryOperator::NotEqual => "neq",
+        BinaryOperator::Lesser => "lt",
+        BinaryOperator::LesserEqual => "leq",
+        BinaryOperator::Greater => "gt",
+        BinaryOperator::GreaterEqual => "geq",
+        BinaryOperator::ShiftLeft => "shl",
+        BinaryOperator::ShiftRight => "shr"
+    }
+}
+
+///
+/// True for the operators failing on a zero right operand.
+///
+pub fn is_division(operator: BinaryOperator) -> bool {
+    matches!(operator, BinaryOperator::Div | BinaryOperator::IntDiv | BinaryOperator::Mod)
+}
+
+///
+/// `-p^-1 mod 2^64`, by Newton iterations, for Montgomery multiplication.
+///
+pub fn montgomery_inverse(p0: u64) -> u64 {
+
+    let mut inverse: u64 = 1;
+    for _ in 0..6 {
+        inverse = inverse.wrapping_mul(2u64.wrapping_sub(p0.wrapping_mul(inverse)));
+    }
+
+    inverse.wrapping_neg()
+
+}
+
+struct Lowering<'c, F: PrimeField> {
+    circuit: &'c ElaboratedCircuit<F>,
+    components: Vec<ComponentCode<F>>,
+    directory: PathBuf,
+    locations: Vec<std::string::String>,
+    location_ids: HashMap<(usize, Span), usize>,
+
+    known: Vec<bool>,
+    children: Vec<Vec<usize>>,
+    assignments: Vec<Vec<usize>>,
+    missing_inputs: Vec<usize>,
+    checks: Vec<Vec<usize>>,
+    unknown_signals: Vec<usize>,
+
+    n_temps: usize,
+    temps: usize,
+    scopes: Vec<HashMap<*const Ir<F>, usize>>,
+    inlined: Vec<Box<Ir<F>>>
+}
+
+impl<'c, F: PrimeField> Lowering<'c, F> {
+
+    fn location(& mut self, component: usize, span: Span) -> usize {
+
+        if let Some(id) = self.location_ids.get(&(component, span)) {
+            return *id;
+        }
+
+        let mut location = self.circuit.location(component, span);
+        location.file = relative_path(&location.file, &self.directory);
+
+        self.locations.push(location.to_string());
+        self.location_ids.insert((component, span), self.locations.len() - 1);
+
+        self.locations.len() - 1
+
+    }
+
+    fn unsupported(&self, component: usize, span: Span, reason: std::string::String) -> CodegenError {
+        CodegenError::Unsupported { location: Box::new(self.circuit.location(component, span)), reason }
+    }
+
+    fn temp(& mut self) -> usize {
+        self.temps += 1;
+        self.temps - 1
+    }
+
+    fn remember(& mut self, ir: & Ir<F>, temp: usize) -> usize {
+        self.scopes.last_mut().unwrap().insert(ir as *const Ir<F>, temp);
+        temp
+    }
+
+    ///
+    /// Starts a statement: temporaries are numbered from 0 again and nothing is shared with the
+    /// previous statements.
+    ///
+    fn begin_statement(& mut self) {
+        self.n_temps = self.n_temps.max(self.temps);
+        self.temps = 0;
+        self.scopes = vec!(HashMap::new());
+        self.inlined.clear();
+    }
+
+    fn lower(& mut self, ir: & Ir<F>, steps: & mut Vec<Step<F>>) -> Result<usize, std::string::String> {
+
+        let key = ir as *const Ir<F>;
+        if let Some(temp) = self.scopes.iter().rev().find_map(|scope| scope.get(&key)) {
+            return Ok(*temp);
+        }
+
+        let operation = match ir {
+            Ir::Constant(value) => Operation::Constant(*value),
+            Ir::Signal(signal) => Operation::Signal(*signal),
+            Ir::Prefix(operator, operand) => Operation::Prefix(*operator, self.lower(operand, steps)?),
+            Ir::Binary(operator, left, right) => {
+                let left = self.lower(left, steps)?;
+                let right = self.lower(right, steps)?;
+                Operation::Binary(*operator, left, right)
+            },
+            Ir::Ternary(condition, if_true, if_false) => {
+                let condition = self.lower(condition, steps)?;
+                let if_true = self.branch(if_true)?;
+                let if_false = self.branch(if_false)?;
+                let temp = self.temp();
+                steps.push(Step::Select { temp, condition, if_true, if_false });
+                return Ok(self.remember(ir, temp));
+            },
+            Ir::Call(name, arguments) => {
+                let inlined = match self.circuit.inline_function(name, (**arguments).clone()) {
+                    Ok(inlined) => Box::new(inlined),
+                    Err(reason) => return Err(format!("Cannot generate the code of a call to {}: {}", name, reason))
+                };
+                let temp = self.lower(&inlined, steps)?;
+                // Kept until the end of the statement, its nodes being remembered by address
+                self.inlined.push(inlined);
+                return Ok(self.remember(ir, temp));
+            }
+        };
+
+        let temp = self.temp();
+        steps.push(Step::Let(temp, operation));
+
+        Ok(self.remember(ir, temp))
+
+    }
+
+    fn block(& mut self, ir: & Ir<F>) -> Result<Block<F>, std::string::String> {
+        let mut steps: Vec<Step<F>> = Vec::new();
+        let result = self.lower(ir, & mut steps)?;
+        Ok(Block { steps, result })
+    }
+
+    fn branch(& mut self, ir: & Ir<F>) -> Result<Block<F>, std::string::String> {
+        self.scopes.push(HashMap::new());
+        let block = self.block(ir);
+        self.scopes.pop();
+        block
+    }
+
+    fn check(& mut self, index: usize) -> Result<Statement<F>, CodegenError> {
+
+        let check = &self.circuit.constraints[index];
+
+        self.begin_statement();
+
+        let left = self.block(&check.left).map_err(|reason| self.unsupported(check.component, check.span, reason))?;
+        let right = self.block(&check.right).map_err(|reason| self.unsupported(check.component, check.span, reason))?;
+
+        Ok(Statement::Check { left, right, location: self.location(check.component, check.span) })
+
+    }
+
+    ///
+    /// Marks a signal as known, appending the checks it completes.
+    ///
+    fn resolve(& mut self, signal: usize, statements: & mut Vec<Statement<F>>) -> Result<(), CodegenError> {
+
+        self.known[signal] = true;
+
+        for index in std::mem::take(& mut self.checks[signal]) {
+            self.unknown_signals[index] -= 1;
+            if self.unknown_signals[index] == 0 {
+                statements.push(self.check(index)?);
+            }
+        }
+
+        Ok(())
+
+    }
+
+    fn is_ready(&self, assignment: usize) -> bool {
+        self.circuit.assignments[assignment].value.signals().into_iter().all(|signal| self.known[signal])
+    }
+
+    ///
+    /// Appends an assignment, and the run of the component whose inputs it completes.
+    ///
+    fn assign(& mut self, component: usize, index: usize, statements: & mut Vec<Statement<F>>) -> Result<(), CodegenError> {
+
+        let assignment = &self.circuit.assignments[index];
+
+        self.begin_statement();
+
+        let value = match self.block(&assignment.value) {
+            Ok(value) => value,
+            Err(reason) => return Err(self.unsupported(component, assignment.span, reason))
+        };
+
+        statements.push(Statement::Assign { signal: assignment.signal, value, location: self.location(component, assignment.span) });
+        self.resolve(assignment.signal, statements)?;
+
+        let signal = &self.circuit.signals[assignment.signal];
+        if signal.direction == SignalDirection::Input && signal.component != component {
+            self.missing_inputs[signal.component] -= 1;
+            if self.missing_inputs[signal.component] == 0 {
+                statements.push(Statement::Run(signal.component));
+                self.run(signal.component, Vec::new())?;
+            }
+        }
+
+        Ok(())
+
+    }
+
+    ///
+    /// Lowers a component, and the components it runs.
+    ///
+    /// Assignments reading signals not known yet, typically outputs of a subcomponent read in the
+    /// loop assigning its inputs, are postponed until these signals are known.
+    ///
+    fn run(& mut self, component: usize, mut statements: Vec<Statement<F>>) -> Result<(), CodegenError> {
+
+        for child in self.children[component].clone() {
+            if self.missing_inputs[child] == 0 {
+                statements.push(Statement::Run(child));
+                self.run(child, Vec::new())?;
+            }
+        }
+
+        let mut postponed: Vec<usize> = Vec::new();
+
+        for index in self.assignments[component].clone() {
+
+            if !self.is_ready(index) {
+                postponed.push(index);
+                continue ;
+            }
+
+            self.assign(component, index, & mut statements)?;
+
+            while let Some(position) = postponed.iter().position(|index| self.is_ready(*index)) {
+                let index = postponed.remove(position);
+                self.assign(component, index, & mut statements)?;
+            }
+
+        }
+
+        if let Some(index) = postponed.first() {
+            let assignment = &self.circuit.assignments[*index];
+            let signal = assignment.value.signals().into_iter().find(|signal| !self.known[*signal]).unwrap();
+            return Err(self.unsupported(component, assignment.span, format!("Signal {} cannot be computed before this statement", self.circuit.signals[signal].name)));
+        }
+
+        self.components[component].statements = statements;
+
+        Ok(())
+
+    }
+
+}
+
+///
+/// `path` relative to `directory`, or `path` itself when one is absolute and the other is not.
+///
+fn relative_path(path: & Path, directory: & Path) -> PathBuf {
+
+    if path.is_absolute() != directory.is_absolute() {
+        return path.to_path_buf();
+    }
+
+    let common = path.components().zip(directory.components()).take_while(|(left, right)| left == right).count();
+
+    directory.components().skip(common).map(|_| Component::ParentDir)
+        .chain(path.components().skip(common))
+        .collect()
+
+}
+
+///
+/// Lowers an elaborated circuit. `symbols` gives the wires of the signals, the ones of the circuit or
+/// the ones left by `circuit::optimize::simplify`, and `n_wires` the size of the witness.
+///
+pub fn lower<F: PrimeField>(circuit: & ElaboratedCircuit<F>, symbols: & SymbolMap, n_wires: usize) -> Result<Program<F>, CodegenError> {
+
+    let n_components = circuit.components.len();
+
+    let mut lowering = Lowering {
+        circuit,
+        components: circuit.components.iter()
+            .map(|component| ComponentCode { name: component.name.clone(), template: component.template.clone(), statements: Vec::new() })
+            .collect(),
+        directory: circuit.file.parent().map(Path::to_path_buf).unwrap_or_default(),
+        locations: Vec::new(),
+        location_ids: HashMap::new(),
+        known: vec!(false; circuit.signals.len()),
+        children: vec!(Vec::new(); n_components),
+        assignments: vec!(Vec::new(); n_components),
+        missing_inputs: vec!(0; n_components),
+        checks: vec!(Vec::new(); circuit.signals.len()),
+        unknown_signals: vec!(0; circuit.constraints.len()),
+        n_temps: 0,
+        temps: 0,
+        scopes: Vec::new(),
+        inlined: Vec::new()
+    };
+
+    for (id, component) in circuit.components.iter().enumerate() {
+        if let Some(parent) = component.parent {
+            lowering.children[parent].push(id);
+        }
+    }
+
+    for (index, assignment) in circuit.assignments.iter().enumerate() {
+        lowering.assignments[assignment.component].push(index);
+    }
+
+    for signal in &circuit.signals {
+        if signal.direction == SignalDirection::Input && signal.component != 0 {
+            lowering.missing_inputs[signal.component] += 1;
+        }
+    }
+
+    let inputs: Vec<Input> = circuit.inputs()
+        .map(|(name, tree)| Input { name: name.clone(), dimensions: tree.dimensions(), signals: tree.signals() })
+        .collect();
+
+    for input in &inputs {
+        for signal in &input.signals {
+            lowering.known[*signal] = true;
+        }
+    }
+
+    // Checks on the inputs only come first
+    let mut statements: Vec<Statement<F>> = Vec::new();
+
+    for (index, check) in circuit.constraints.iter().enumerate() {
+
+        let mut signals = check.left.signals();
+        signals.extend(check.right.signals());
+        signals.sort_unstable();
+        signals.dedup();
+
+        signals.retain(|signal| !lowering.known[*signal]);
+
+        for signal in signals {
+            lowering.checks[signal].push(index);
+            lowering.unknown_signals[index] += 1;
+        }
+
+        if lowering.unknown_signals[index] == 0 {
+            statements.push(lowering.check(index)?);
+        }
+
+    }
+
+    lowering.run(0, statements)?;
+    lowering.begin_statement();
+
+    if let Some(signal) = lowering.known.iter().position(|known| !known) {
+        return Err(CodegenError::UnassignedSignal(circuit.signals[signal].name.clone()));
+    }
+
+    Ok(Program {
+        template: circuit.main().template.clone(),
+        components: lowering.components,
+        inputs,
+        locations: lowering.locations,
+        wires: symbols.iter().map(|symbol| symbol.wire_id).collect(),
+        n_wires,
+        n_outputs: circuit.n_outputs,
+        n_public_inputs: circuit.n_public_inputs,
+        n_private_inputs: circuit.n_private_inputs,
+        n_temps: lowering.n_temps
+    })
+
+}
+
+///
+/// Checks shared by the tests of the backends, which give the way their code is generated and run.
+///
+#[cfg(test)]
+mod backend_test {
+
+    use std::path::Path;
+    use std::process::Command;
+
+    use codegen::{self, Program};
+    use elaborator::{self, ElaboratedCircuit};
+    use field::bn254::Fr;
+    use parser::compile;
+    use witness::{self, input, wtns};
+
+    use galvanic_assert::matchers::*;
+
+    pub fn load(name: & str) -> ElaboratedCircuit<Fr> {
+
+        let path = std::fs::canonicalize(format!("./src/lib/parser/test_material/witness/{}.circom", name)).expect("Invalid Path");
+        let ctx = compile::build_context(&path);
+
+        elaborator::elaborate::<Fr>(&ctx).expect("Elaboration failed")
+
+    }
+
+    ///
+    /// Runs a compiled witness calculator, returning the witness or the error it printed.
+    ///
+    pub fn run(binary: & Path, inputs: & str) -> Result<Vec<Fr>, std::string::String> {
+
+        let input_path = binary.with_extension("json");
+        let wtns_path = binary.with_extension("wtns");
+        std::fs::write(&input_path, inputs).unwrap();
+
+        let output = Command::new(binary).arg(&input_path).arg(&wtns_path).output().unwrap();
+
+        if output.status.success() {
+            Ok(wtns::load::<Fr>(&wtns_path).unwrap())
+        } else {
+            expect_that!(&output.status.code(), is(eq(Some(1))));
+            Err(std::string::String::from_utf8_lossy(&output.stderr).trim_end().to_string())
+        }
+
+    }
+
+    ///
+    /// Result of the interpreter, the files of its errors made relative to the directory of the main
+    /// one as in the generated code.
+    ///
+    pub fn expected(circuit: & ElaboratedCircuit<Fr>, inputs: & str) -> Result<Vec<Fr>, std::string::String> {
+        let directory = format!("{}/", circuit.file.parent().unwrap().display());
+        witness::calculate_witness(circuit, &input::parse_inputs::<Fr>(inputs).unwrap()).map_err(|error| error.to_string().replace(&directory, ""))
+    }
+
+    ///
+    /// Compares the witnesses computed by a backend with the ones of the interpreter. `calculate`
+    /// generates the calculator of a circuit and runs it on each of the inputs.
+    ///
+    pub fn check_witness<C>(mut calculate: C) where C: FnMut(& str, & ElaboratedCircuit<Fr>, & [& str]) -> Vec<Result<Vec<Fr>, std::string::String>> {
+
+        for (name, inputs) in &[
+            ("num2bits", vec!(r#"{"in": 13}"#, r#"{"in": "0xff"}"#, r#"{"in": 256}"#)),
+            ("matrix", vec!(r#"{"m": [[1, 2], [3, 4]], "x": 0}"#, r#"{"m": ["1", "-2", "3", "0x04"], "x": "2"}"#)),
+            ("invalid_constraint", vec!(r#"{"a": 1}"#, r#"{"a": 3}"#)),
+            ("out_of_order", vec!(r#"{"a": 5}"#)),
+            ("operators", vec!(
+                r#"{"a": 7, "b": 3}"#,
+                r#"{"a": -5, "b": 2}"#,
+                r#"{"a": 3, "b": -2}"#,
+                r#"{"a": 3, "b": 0}"#,
+                r#"{"a": "0x1234567890abcdef1234567890abcdef1234567890abcdef", "b": 70}"#,
+                r#"{"a": "21888242871839275222246405745257275088548364400416034343698204186575808495616", "b": 254}"#
+            ))
+        ] {
+
+            let circuit = load(name);
+            let witnesses = calculate(name, &circuit, inputs);
+            expect_that!(&witnesses.len(), is(eq(inputs.len())));
+
+            for (inputs, witness) in inputs.iter().zip(witnesses.iter()) {
+                expect_that!(witness, is(eq(expected(&circuit, inputs))));
+            }
+
+        }
+
+    }
+
+    ///
+    /// Checks that the locations of the errors in the generated code are relative to the directory
+    /// of the main file, whatever the directory the code is generated from.
+    ///
+    pub fn check_locations<G>(generate: G) where G: Fn(& Program<Fr>) -> Vec<u8> {
+
+        let circuit = load("num2bits");
+        let program = codegen::lower(&circuit, &circuit.symbols(), circuit.n_wires()).expect("Lowering failed");
+        let code = generate(&program);
+
+        let contains = |text: & str| code.windows(text.len()).any(|window| window == text.as_bytes());
+
+        expect_that!(&contains("../circuits/bitify.circom:"), is(eq(true)));
+        expect_that!(&contains(std::env::current_dir().unwrap().to_str().unwrap()), is(eq(false)));
+
+    }
+
+}
+
+#[cfg(test)]
+mod codegen_test {
+
+    use codegen::backend_test::load;
+    use codegen::{self, CodegenError, Statement};
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_lower() {
+
+        let circuit = load("matrix");
+        let program = codegen::lower(&circuit, &circuit.symbols(), circuit.n_wires()).expect("Lowering failed");
+
+        expect_that!(&program.template.as_str(), is(eq("Matrix")));
+        expect_that!(&program.components.len(), is(eq(2)));
+        expect_that!(&program.components[1].name.as_str(), is(eq("main.isz")));
+        expect_that!(&program.inputs.len(), is(eq(2)));
+        expect_that!(&program.inputs[0].name.as_str(), is(eq("m")));
+        expect_that!(&program.inputs[0].dimensions, is(eq(vec!(2, 2))));
+        expect_that!(&program.inputs[0].signals.len(), is(eq(4)));
+        expect_that!(&program.n_signals(), is(eq(circuit.signals.len())));
+
+        // IsZero runs once its input is assigned, before its output is read
+        let main = &program.components[0].statements;
+        let run = main.iter().position(|statement| *statement == Statement::Run(1)).expect("IsZero never runs");
+        let isz_in = circuit.components[1].signal("in").unwrap().signals()[0];
+        let zero = circuit.main().signal("zero").unwrap().signals()[0];
+
+        expect_that!(&(main.iter().position(|statement| matches!(statement, Statement::Assign { signal, .. } if *signal == isz_in)).unwrap() < run), is(eq(true)));
+        expect_that!(&(main.iter().position(|statement| matches!(statement, Statement::Assign { signal, .. } if *signal == zero)).unwrap() > run), is(eq(true)));
+
+        let checks = program.components.iter()
+            .flat_map(|component| component.statements.iter())
+            .filter(|statement| matches!(statement, Statement::Check { .. }))
+            .count();
+
+        expect_that!(&checks, is(eq(circuit.constraints.len())));
+
+    }
+
+    #[test]
+    fn test_relative_locations() {
+
+        // Num2Bits comes from a file included from a sibling directory
+        let circuit = load("num2bits");
+        let program = codegen::lower(&circuit, &circuit.symbols(), circuit.n_wires()).expect("Lowering failed");
+
+        expect_that!(&program.locations.is_empty(), is(eq(false)));
+        expect_that!(&program.locations.iter().all(|location| location.starts_with("../circuits/bitify.circom:")), is(eq(true)));
+
+    }
+
+    #[test]
+    fn test_postponed_assignments() {
+
+        let circuit = load("out_of_order");
+        let program = codegen::lower(&circuit, &circuit.symbols(), circuit.n_wires()).expect("Lowering failed");
+
+        let assigned: Vec<usize> = program.components[0].statements.iter()
+            .filter_map(|statement| match statement {
+                Statement::Assign { signal, .. } => Some(*signal),
+                _ => None
+            })
+            .collect();
+
+        let signal = |name: & str| circuit.main().signal(name).unwrap().signals()[0];
+
+        expect_that!(&assigned, is(eq(vec!(signal("c"), signal("b")))));
+
+    }
+
+    #[test]
+    fn test_lower_errors() {
+
+        let circuit = load("cycle");
+
+        match codegen::lower(&circuit, &circuit.symbols(), circuit.n_wires()) {
+            Err(CodegenError::Unsupported { location, reason }) => {
+                expect_that!(&location.source.as_str(), is(eq("b <-- c + a")));
+                expect_that!(&reason.as_str(), is(eq("Signal main.c cannot be computed before this statement")));
+            },
+            other => panic!("Unexpected result {:?}", other)
+        }
+
+    }
+
+}
@@ -0,0 +1,572 @@
+//
+// Native Rust witness calculators: a module compiled into the crate using it, linking against
+// `libsnarkrs` for the field arithmetic and the input values.
+//
+// The module declares the field type `F`, an `Input` struct with a field per input signal of the
+// main component, arrays following the declared dimensions, and `calculate_witness` computing the
+// witness in wire order. Component functions take the signals and the field constants, statements
+// computing their temporaries as local variables the compiler keeps in registers. `+ - *` are
+// native field operations, the other operators go through `elaborator::ir`.
+//
+// Inputs keep the names of the signals, Rust keywords being escaped (`in` becomes `r#in`).
+//
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use codegen::{self, Block, Operation, Program, Statement, Step};
+use elaborator;
+use field::PrimeField;
+use parser::compile;
+use parser::errors::CompileError;
+use parser::typed_ast::{BinaryOperator, PrefixOperator};
+
+const RUNTIME: & str = r#"
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    ConstraintFailed { location: &'static str, left: F, right: F },
+    EvaluationError { location: &'static str, reason: String }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::ConstraintFailed { location, left, right } => write!(formatter, "Constraint not satisfied at {}\n\tleft: {}\n\tright: {}", location, left, right),
+            Error::EvaluationError { location, reason } => write!(formatter, "{} at {}", reason, location)
+        }
+    }
+}
+
+#[allow(dead_code)]
+fn input_value(values: &HashMap<String, InputValue<F>>, name: &str, dimensions: &[usize]) -> Result<Vec<F>, WitnessError> {
+    match values.get(name) {
+        Some(value) => {
+            let mut flat: Vec<F> = Vec::new();
+            witness::flatten_input(name, dimensions, value, &mut flat)?;
+            Ok(flat)
+        },
+        None => Err(WitnessError::MissingInput(name.to_string()))
+    }
+}
+
+#[allow(dead_code)]
+fn prefix(operator: PrefixOperator, value: F) -> F {
+    ir::apply_prefix(operator, value).expect("Prefix operators do not fail")
+}
+
+#[allow(dead_code)]
+fn binary(operator: BinaryOperator, left: F, right: F) -> F {
+    ir::apply_binary(operator, left, right).expect("Only divisions fail")
+}
+
+#[allow(dead_code)]
+fn divide(operator: BinaryOperator, left: F, right: F, location: usize) -> Result<F, Error> {
+    ir::apply_binary(operator, left, right).map_err(|reason| Error::EvaluationError { location: LOCATIONS[location], reason })
+}
+
+#[allow(dead_code)]
+fn check(left: F, right: F, location: usize) -> Result<(), Error> {
+    match left == right {
+        true => Ok(()),
+        false => Err(Error::ConstraintFailed { location: LOCATIONS[location], left, right })
+    }
+}
+"#;
+
+const KEYWORDS: & [& str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where",
+    "while", "yield"
+];
+
+///
+/// Rust field name of an input, keywords being raw identifiers. The ones that cannot be raw get a
+/// trailing underscore.
+///
+pub fn field_name(name: & str) -> std::string::String {
+    match name {
+        "_" | "crate" | "self" | "Self" | "super" => format!("{}_", name),
+        name if KEYWORDS.contains(&name) => format!("r#{}", name),
+        name => name.to_string()
+    }
+}
+
+///
+/// Path of the `libsnarkrs` type of a field.
+///
+fn field_type<F: PrimeField>() -> & 'static str {
+    match F::NAME {
+        "bn128" => "libsnarkrs::field::bn254::Fr",
+        "bn128_base" => "libsnarkrs::field::bn254::Fq",
+        "bls12381" => "libsnarkrs::field::bls12_381::Fr",
+        name => panic!("No Rust type for the prime {}", name)
+    }
+}
+
+fn array_type(dimensions: & [usize]) -> std::string::String {
+    dimensions.iter().rev().fold("F".to_string(), |element, size| format!("[{}; {}]", element, size))
+}
+
+///
+/// Array literal of the elements of `flat`, in row major order, from `first`.
+///
+fn array_literal(flat: & str, dimensions: & [usize], first: usize) -> std::string::String {
+    match dimensions.split_first() {
+        None => format!("{}[{}]", flat, first),
+        Some((size, inner)) => {
+            let stride: usize = inner.iter().product();
+            let elements: Vec<std::string::String> = (0..*size).map(|index| array_literal(flat, inner, first + index * stride)).collect();
+            format!("[{}]", elements.join(", "))
+        }
+    }
+}
+
+///
+/// Expression reading an element of an input, `index` being its position in row major order.
+///
+fn input_element(name: & str, dimensions: & [usize], mut index: usize) -> std::string::String {
+
+    let mut indices: Vec<usize> = vec!(0; dimensions.len());
+
+    for (position, size) in dimensions.iter().enumerate().rev() {
+        indices[position] = index % size;
+        index /= size;
+    }
+
+    indices.iter().fold(format!("input.{}", field_name(name)), |element, index| format!("{}[{}]", element, index))
+
+}
+
+struct Emitter<F> {
+    code: std::string::String,
+    constants: Vec<F>,
+    constant_ids: HashMap<[u64; 4], usize>
+}
+
+impl<F: PrimeField> Emitter<F> {
+
+    fn constant(& mut self, value: F) -> usize {
+        let constants = & mut self.constants;
+        *self.constant_ids.entry(value.to_u256().0).or_insert_with(|| {
+            constants.push(value);
+            constants.len() - 1
+        })
+    }
+
+    fn line(& mut self, indent: usize, line: & str) {
+        self.code.push_str(&"    ".repeat(indent));
+        self.code.push_str(line);
+        self.code.push('\n');
+    }
+
+    fn operation(& mut self, operation: & Operation<F>, location: usize) -> std::string::String {
+        match operation {
+            Operation::Constant(value) => format!("c[{}]", self.constant(*value)),
+            Operation::Signal(signal) => format!("s[{}]", signal),
+            Operation::Prefix(PrefixOperator::Plus, operand) => format!("t{}", operand),
+            Operation::Prefix(PrefixOperator::Minus, operand) => format!("-t{}", operand),
+            Operation::Prefix(operator, operand) => format!("prefix(PrefixOperator::{:?}, t{})", operator, operand),
+            Operation::Binary(BinaryOperator::Add, left, right) => format!("t{} + t{}", left, right),
+            Operation::Binary(BinaryOperator::Sub, left, right) => format!("t{} - t{}", left, right),
+            Operation::Binary(BinaryOperator::Mul, left, right) => format!("t{} * t{}", left, right),
+            Operation::Binary(operator, left, right) if codegen::is_division(*operator) => {
+                format!("divide(BinaryOperator::{:?}, t{}, t{}, {})?", operator, left, right, location)
+            },
+            Operation::Binary(operator, left, right) => format!("binary(BinaryOperator::{:?}, t{}, t{})", operator, left, right)
+        }
+    }
+
+    fn block(& mut self, block: & Block<F>, location: usize, indent: usize) {
+        for step in &block.steps {
+            match step {
+                Step::Let(temp, operation) => {
+                    let value = self.operation(operation, location);
+                    self.line(indent, &format!("let t{} = {};", temp, value));
+                },
+                Step::Select { temp, condition, if_true, if_false } => {
+                    self.line(indent, &format!("let t{} = if !t{}.is_zero() {{", temp, condition));
+                    self.block(if_true, location, indent + 1);
+                    self.line(indent + 1, &format!("t{}", if_true.result));
+                    self.line(indent, "} else {");
+                    self.block(if_false, location, indent + 1);
+                    self.line(indent + 1, &format!("t{}", if_false.result));
+                    self.line(indent, "};");
+                }
+            }
+        }
+    }
+
+    fn statement(& mut self, statement: & Statement<F>) {
+        match statement {
+            Statement::Assign { signal, value, location } => {
+                self.line(1, "{");
+                self.block(value, *location, 2);
+                self.line(2, &format!("s[{}] = t{};", signal, value.result));
+                self.line(1, "}");
+            },
+            Statement::Check { left, right, location } => {
+                self.line(1, "{");
+                self.block(left, *location, 2);
+                self.block(right, *location, 2);
+                self.line(2, &format!("check(t{}, t{}, {})?;", left.result, right.result, location));
+                self.line(1, "}");
+            },
+            Statement::Run(component) => self.line(1, &format!("component_{}(s, c)?;", component))
+        }
+    }
+
+}
+
+///
+/// Rust source of the witness calculator of a program, a module to include in a crate depending on
+/// `libsnarkrs`.
+///
+pub fn generate<F: PrimeField>(program: & Program<F>) -> std::string::String {
+
+    let mut emitter = Emitter { code: std::string::String::new(), constants: Vec::new(), constant_ids: HashMap::new() };
+
+    for (id, component) in program.components.iter().enumerate() {
+        emitter.line(0, &format!("// {}: {}", component.name, component.template));
+        emitter.line(0, "#[allow(unused_variables, clippy::all)]");
+        emitter.line(0, &format!("fn component_{}(s: &mut [F], c: &[F]) -> Result<(), Error> {{", id));
+        for statement in &component.statements {
+            emitter.statement(statement);
+        }
+        emitter.line(1, "Ok(())");
+        emitter.line(0, "}");
+        emitter.line(0, "");
+    }
+
+    let mut source = std::string::String::new();
+
+    let _ = write!(source, "\
+// Witness calculator of {template}, generated by snarkrs. Include it as a module of a crate
+// depending on libsnarkrs:
+//
+//     mod circuit {{
+//         include!(concat!(env!(\"OUT_DIR\"), \"/circuit.rs\"));
+//     }}
+//
+//     let witness = circuit::calculate_witness(&input)?;
+
+use std::collections::HashMap;
+
+use libsnarkrs::elaborator::ir;
+use libsnarkrs::field::bigint::U256;
+use libsnarkrs::field::PrimeField;
+use libsnarkrs::parser::typed_ast::{{BinaryOperator, PrefixOperator}};
+use libsnarkrs::witness::{{self, InputValue, WitnessError}};
+
+pub type F = {field};
+
+pub const N_SIGNALS: usize = {signals};
+pub const N_WIRES: usize = {wires};
+pub const N_OUTPUTS: usize = {outputs};
+pub const N_PUBLIC_INPUTS: usize = {public_inputs};
+pub const N_PRIVATE_INPUTS: usize = {private_inputs};
+",
+        template = program.template,
+        field = field_type::<F>(),
+        signals = program.n_signals(),
+        wires = program.n_wires,
+        outputs = program.n_outputs,
+        public_inputs = program.n_public_inputs,
+        private_inputs = program.n_private_inputs
+    );
+
+    source.push_str("\n///\n/// Input signals of the main component.\n///\n#[derive(Debug, Clone, PartialEq)]\npub struct Input {");
+    let fields: Vec<std::string::String> = program.inputs.iter()
+        .map(|input| format!("\n    pub {}: {}", field_name(&input.name), array_type(&input.dimensions)))
+        .collect();
+    source.push_str(&fields.join(","));
+    source.push_str(if fields.is_empty() { "}\n" } else { "\n}\n" });
+
+    let names: Vec<std::string::String> = program.inputs.iter().map(|input| format!("{:?}", input.name)).collect();
+    let _ = write!(source, "
+impl Input {{
+
+    ///
+    /// Input from the values of an input.json file, see `libsnarkrs::witness::input`.
+    ///
+    pub fn from_values(values: &HashMap<String, InputValue<F>>) -> Result<Input, WitnessError> {{
+
+        let mut names: Vec<&String> = values.keys().collect();
+        names.sort();
+
+        for name in names {{
+            if ![{names}].contains(&name.as_str()) {{
+                return Err(WitnessError::UnknownInput(name.clone()));
+            }}
+        }}
+
+        Ok(Input {{",
+        names = names.join(", ")
+    );
+
+    let fields: Vec<std::string::String> = program.inputs.iter()
+        .map(|input| {
+            let dimensions: Vec<std::string::String> = input.dimensions.iter().map(usize::to_string).collect();
+            let value = format!("input_value(values, {:?}, &[{}])?", input.name, dimensions.join(", "));
+            match input.dimensions.is_empty() {
+                true => format!("\n            {}: {}[0]", field_name(&input.name), value),
+                false => format!("\n            {}: {{\n                let flat = {};\n                {}\n            }}", field_name(&input.name), value, array_literal("flat", &input.dimensions, 0))
+            }
+        })
+        .collect();
+    source.push_str(&fields.join(","));
+    source.push_str(if fields.is_empty() { "})\n\n    }\n\n}\n" } else { "\n        })\n\n    }\n\n}\n" });
+
+    let locations: Vec<std::string::String> = program.locations.iter().map(|location| format!("{:?}", location)).collect();
+    let _ = writeln!(source, "\nstatic LOCATIONS: [&str; {}] = [\n    {}\n];", locations.len(), locations.join(",\n    "));
+
+    let wires: Vec<std::string::String> = program.wires.iter().map(|wire| match wire {
+        Some(wire) => format!("Some({})", wire),
+        None => "None".to_string()
+    }).collect();
+    let _ = writeln!(source, "\nstatic WIRES: [Option<usize>; N_SIGNALS] = [{}];", wires.join(", "));
+
+    let constants: Vec<std::string::String> = emitter.constants.iter()
+        .map(|constant| {
+            let limbs = constant.to_u256().0;
+            format!("[0x{:016x}, 0x{:016x}, 0x{:016x}, 0x{:016x}]", limbs[0], limbs[1], limbs[2], limbs[3])
+        })
+        .collect();
+    let _ = writeln!(source, "\nstatic CONSTANTS: [[u64; 4]; {}] = [\n    {}\n];", constants.len(), constants.join(",\n    "));
+
+    source.push_str(RUNTIME);
+
+    source.push_str("
+///
+/// Computes all the signals of the circuit from the inputs of its main component, returning the
+/// witness in wire order, wire 0 being the constant `1`.
+///
+pub fn calculate_witness(input: &Input) -> Result<Vec<F>, Error> {
+
+    let c: Vec<F> = CONSTANTS.iter().map(|limbs| F::from_u256(&U256(*limbs))).collect();
+    let mut s: Vec<F> = vec![F::zero(); N_SIGNALS];
+
+");
+
+    for input in &program.inputs {
+        for (index, signal) in input.signals.iter().enumerate() {
+            let _ = writeln!(source, "    s[{}] = {};", signal, input_element(&input.name, &input.dimensions, index));
+        }
+    }
+
+    source.push_str("
+    component_0(&mut s, &c)?;
+
+    let mut witness: Vec<F> = vec![F::zero(); N_WIRES];
+    witness[0] = F::one();
+
+    for (signal, wire) in WIRES.iter().enumerate() {
+        if let Some(wire) = wire {
+            witness[*wire] = s[signal];
+        }
+    }
+
+    Ok(witness)
+
+}
+
+");
+
+    source.push_str(&emitter.code);
+
+    source
+
+}
+
+///
+/// Generates the witness calculator of a circom file from a build script, writing it to `output`,
+/// typically a file of `OUT_DIR`:
+///
+/// ```ignore
+/// codegen::rust::build::<Fr>(Path::new("circuits/multiplier.circom"), &[], &out_dir.join("multiplier.rs"))
+/// ```
+///
+/// The build reruns when the circuit or one of its includes changes.
+///
+pub fn build<F: PrimeField>(source: & Path, include_paths: & [PathBuf], output: & Path) -> Result<(), std::string::String> {
+
+    let path = std::fs::canonicalize(source).map_err(|error| format!("{}: {}", source.display(), error))?;
+    let ctx = compile::build_context_with_include_paths(&path, include_paths);
+
+    for file in ctx.files() {
+        println!("cargo:rerun-if-changed={}", file.path.display());
+    }
+
+    if !ctx.errors().is_empty() {
+        let errors: Vec<std::string::String> = ctx.errors().iter().map(|error| error.to_string()).collect();
+        return Err(errors.join("\n"));
+    }
+
+    let circuit = elaborator::elaborate::<F>(&ctx).map_err(|error| CompileError::ElaborationError(error).to_string())?;
+    let program = codegen::lower(&circuit, &circuit.symbols(), circuit.n_wires()).map_err(|error| error.to_string())?;
+
+    std::fs::write(output, generate(&program)).map_err(|error| format!("{}: {}", output.display(), error))
+
+}
+
+#[cfg(test)]
+mod rust_test {
+
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    use codegen::backend_test::{self, expected, load, run};
+    use codegen::{self, rust};
+    use elaborator::ElaboratedCircuit;
+    use field::bn254::Fr;
+
+    use galvanic_assert::matchers::*;
+
+    const MAIN: & str = r#"
+use std::path::Path;
+
+use libsnarkrs::witness::{input, wtns};
+
+mod circuit {
+    include!("circuit.rs");
+}
+
+fn main() {
+    let arguments: Vec<String> = std::env::args().collect();
+    let values = input::load_inputs::<circuit::F>(Path::new(&arguments[1])).unwrap();
+    let witness = circuit::Input::from_values(&values)
+        .map_err(|error| error.to_string())
+        .and_then(|input| circuit::calculate_witness(&input).map_err(|error| error.to_string()));
+    match witness {
+        Ok(witness) => wtns::save(&witness, Path::new(&arguments[2])).unwrap(),
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+}
+"#;
+
+    ///
+    /// Builds `libsnarkrs` as a library, with the profile of the tests, and returns its target
+    /// directory.
+    ///
+    fn library() -> PathBuf {
+
+        let mut command = Command::new(env!("CARGO"));
+        command.args(["build", "--lib", "--manifest-path", concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")]);
+        if !cfg!(debug_assertions) {
+            command.arg("--release");
+        }
+
+        let output = command.output().expect("No cargo");
+        assert!(output.status.success(), "{}", std::string::String::from_utf8_lossy(&output.stderr));
+
+        // Tests run from target/<profile>/deps
+        std::env::current_exe().unwrap().parent().unwrap().parent().unwrap().to_path_buf()
+
+    }
+
+    ///
+    /// Compiles the generated `circuit.rs` of a directory in a program reading input.json files and
+    /// writing .wtns files.
+    ///
+    fn compile(directory: & Path, name: & str) -> PathBuf {
+
+        std::fs::write(directory.join("main.rs"), MAIN).unwrap();
+
+        let target = library();
+        let binary = directory.join(name);
+        let output = Command::new("rustc")
+            .args(["--edition", "2018", "-D", "warnings", "-A", "dead_code", "-o"]).arg(&binary)
+            .arg("--extern").arg(format!("libsnarkrs={}", target.join("liblibsnarkrs.rlib").display()))
+            .arg("-L").arg(format!("dependency={}", target.join("deps").display()))
+            .arg(directory.join("main.rs"))
+            .output().expect("No Rust compiler");
+        assert!(output.status.success(), "{}", std::string::String::from_utf8_lossy(&output.stderr));
+
+        binary
+
+    }
+
+    fn directory(name: & str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("snarkrs_rust_test_{}_{}", std::process::id(), name));
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    ///
+    /// Generates the witness calculator of a circuit and compiles it.
+    ///
+    fn build(circuit: & ElaboratedCircuit<Fr>, name: & str) -> PathBuf {
+
+        let directory = directory(name);
+
+        let program = codegen::lower(circuit, &circuit.symbols(), circuit.n_wires()).expect("Lowering failed");
+        std::fs::write(directory.join("circuit.rs"), rust::generate(&program)).unwrap();
+
+        compile(&directory, name)
+
+    }
+
+    #[test]
+    fn test_field_name() {
+        expect_that!(&rust::field_name("in"), is(eq("r#in".to_string())));
+        expect_that!(&rust::field_name("self"), is(eq("self_".to_string())));
+        expect_that!(&rust::field_name("out"), is(eq("out".to_string())));
+    }
+
+    #[test]
+    fn test_generated_locations() {
+        backend_test::check_locations(|program| rust::generate(program).into_bytes());
+    }
+
+    #[test]
+    fn test_generated_witness() {
+        backend_test::check_witness(|name, circuit, inputs| {
+            let binary = build(circuit, name);
+            let witnesses = inputs.iter().map(|inputs| run(&binary, inputs)).collect();
+            std::fs::remove_dir_all(binary.parent().unwrap()).unwrap();
+            witnesses
+        });
+    }
+
+    #[test]
+    fn test_generated_input_errors() {
+
+        let circuit = load("matrix");
+        let binary = build(&circuit, "matrix_inputs");
+
+        for inputs in &[r#"{"x": 0}"#, r#"{"m": [1, 2, 3], "x": 0}"#] {
+            expect_that!(&run(&binary, inputs), is(eq(expected(&circuit, inputs))));
+        }
+
+        std::fs::remove_dir_all(binary.parent().unwrap()).unwrap();
+
+    }
+
+    #[test]
+    fn test_build_script() {
+
+        let directory = directory("build_script");
+        let source = Path::new("./src/lib/parser/test_material/witness/num2bits.circom");
+
+        rust::build::<Fr>(source, &[], &directory.join("circuit.rs")).expect("Generation failed");
+
+        let circuit = load("num2bits");
+        let binary = compile(&directory, "num2bits");
+
+        for inputs in &[r#"{"in": 173}"#, r#"{"in": 256}"#] {
+            expect_that!(&run(&binary, inputs), is(eq(expected(&circuit, inputs))));
+        }
+
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        let missing = rust::build::<Fr>(Path::new("./missing.circom"), &[], &directory.join("circuit.rs"));
+        expect_that!(&missing.unwrap_err().starts_with("./missing.circom: "), is(eq(true)));
+
+    }
+
+}
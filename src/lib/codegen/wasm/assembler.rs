@@ -0,0 +1,837 @@
+//
+// Assembler for the WebAssembly text format written by the generator, producing the binary module
+// without any external toolchain.
+//
+// Only the subset the generator uses is supported: function imports, one memory, globals, functions
+// with inline exports and data segments. Function bodies are flat instruction sequences (no folded
+// expressions), blocks have no results, and functions, locals, globals and labels are referred to by
+// `$name` or index.
+//
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Immediate {
+    None,
+
+    ///
+    /// `block`, `loop` and `if`, with an optional label and no result.
+    ///
+    Block,
+    Label,
+    Function,
+    Local,
+    Global,
+    I32,
+    I64,
+
+    ///
+    /// `offset=` and `align=`, the natural alignment being given as its base 2 logarithm.
+    ///
+    Memory(u32)
+}
+
+///
+/// Supported instructions, with their opcode and immediate.
+///
+pub const INSTRUCTIONS: & [(& str, u8, Immediate)] = &[
+    ("unreachable", 0x00, Immediate::None),
+    ("nop", 0x01, Immediate::None),
+    ("block", 0x02, Immediate::Block),
+    ("loop", 0x03, Immediate::Block),
+    ("if", 0x04, Immediate::Block),
+    ("else", 0x05, Immediate::None),
+    ("end", 0x0b, Immediate::None),
+    ("br", 0x0c, Immediate::Label),
+    ("br_if", 0x0d, Immediate::Label),
+    ("return", 0x0f, Immediate::None),
+    ("call", 0x10, Immediate::Function),
+    ("drop", 0x1a, Immediate::None),
+    ("select", 0x1b, Immediate::None),
+    ("local.get", 0x20, Immediate::Local),
+    ("local.set", 0x21, Immediate::Local),
+    ("local.tee", 0x22, Immediate::Local),
+    ("global.get", 0x23, Immediate::Global),
+    ("global.set", 0x24, Immediate::Global),
+    ("i32.load", 0x28, Immediate::Memory(2)),
+    ("i64.load", 0x29, Immediate::Memory(3)),
+    ("i32.load8_u", 0x2d, Immediate::Memory(0)),
+    ("i64.load32_u", 0x35, Immediate::Memory(2)),
+    ("i32.store", 0x36, Immediate::Memory(2)),
+    ("i64.store", 0x37, Immediate::Memory(3)),
+    ("i32.store8", 0x3a, Immediate::Memory(0)),
+    ("i64.store32", 0x3e, Immediate::Memory(2)),
+    ("i32.const", 0x41, Immediate::I32),
+    ("i64.const", 0x42, Immediate::I64),
+    ("i32.eqz", 0x45, Immediate::None),
+    ("i32.eq", 0x46, Immediate::None),
+    ("i32.ne", 0x47, Immediate::None),
+    ("i32.lt_s", 0x48, Immediate::None),
+    ("i32.lt_u", 0x49, Immediate::None),
+    ("i32.gt_s", 0x4a, Immediate::None),
+    ("i32.gt_u", 0x4b, Immediate::None),
+    ("i32.le_s", 0x4c, Immediate::None),
+    ("i32.le_u", 0x4d, Immediate::None),
+    ("i32.ge_s", 0x4e, Immediate::None),
+    ("i32.ge_u", 0x4f, Immediate::None),
+    ("i64.eqz", 0x50, Immediate::None),
+    ("i64.eq", 0x51, Immediate::None),
+    ("i64.ne", 0x52, Immediate::None),
+    ("i64.lt_u", 0x54, Immediate::None),
+    ("i64.gt_u", 0x56, Immediate::None),
+    ("i32.add", 0x6a, Immediate::None),
+    ("i32.sub", 0x6b, Immediate::None),
+    ("i32.mul", 0x6c, Immediate::None),
+    ("i32.and", 0x71, Immediate::None),
+    ("i32.or", 0x72, Immediate::None),
+    ("i32.xor", 0x73, Immediate::None),
+    ("i32.shl", 0x74, Immediate::None),
+    ("i32.shr_s", 0x75, Immediate::None),
+    ("i32.shr_u", 0x76, Immediate::None),
+    ("i64.add", 0x7c, Immediate::None),
+    ("i64.sub", 0x7d, Immediate::None),
+    ("i64.mul", 0x7e, Immediate::None),
+    ("i64.div_u", 0x80, Immediate::None),
+    ("i64.rem_u", 0x82, Immediate::None),
+    ("i64.and", 0x83, Immediate::None),
+    ("i64.or", 0x84, Immediate::None),
+    ("i64.xor", 0x85, Immediate::None),
+    ("i64.shl", 0x86, Immediate::None),
+    ("i64.shr_u", 0x88, Immediate::None),
+    ("i32.wrap_i64", 0xa7, Immediate::None),
+    ("i64.extend_i32_u", 0xad, Immediate::None)
+];
+
+pub const I32: u8 = 0x7f;
+pub const I64: u8 = 0x7e;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Sexp<'t> {
+    Atom(&'t str),
+    Text(Vec<u8>),
+    List(Vec<Sexp<'t>>)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'t> {
+    Open,
+    Close,
+    Atom(&'t str),
+    Text(Vec<u8>)
+}
+
+fn hex_digit(character: u8) -> Option<u8> {
+    (character as char).to_digit(16).map(|digit| digit as u8)
+}
+
+///
+/// Tokens of the text. Function bodies are read token by token rather than as lists, generated
+/// modules having millions of instructions.
+///
+#[derive(Clone)]
+struct Reader<'t> {
+    text: &'t str,
+    position: usize
+}
+
+impl<'t> Reader<'t> {
+
+    fn skip(& mut self) -> Result<(), std::string::String> {
+
+        let text = self.text.as_bytes();
+
+        loop {
+            match text.get(self.position..) {
+                Some([b' ' | b'\t' | b'\n' | b'\r', ..]) => self.position += 1,
+                Some([b';', b';', ..]) => {
+                    while self.position < text.len() && text[self.position] != b'\n' {
+                        self.position += 1;
+                    }
+                },
+                Some([b'(', b';', ..]) => {
+                    match text[self.position..].windows(2).position(|window| window == b";)") {
+                        Some(end) => self.position += end + 2,
+                        None => return Err("Unterminated block comment".to_string())
+                    }
+                },
+                _ => return Ok(())
+            }
+        }
+
+    }
+
+    fn string(& mut self) -> Result<Vec<u8>, std::string::String> {
+
+        let text = self.text.as_bytes();
+        let mut bytes: Vec<u8> = Vec::new();
+        self.position += 1;
+
+        loop {
+            match text.get(self.position) {
+                None => return Err("Unterminated string".to_string()),
+                Some(b'"') => {
+                    self.position += 1;
+                    return Ok(bytes);
+                },
+                Some(b'\\') => {
+                    let escaped = match text.get(self.position + 1) {
+                        Some(b'n') => b'\n',
+                        Some(b't') => b'\t',
+                        Some(b'\\') => b'\\',
+                        Some(b'"') => b'"',
+                        Some(b'\'') => b'\'',
+                        Some(high) => match (hex_digit(*high), text.get(self.position + 2).and_then(|low| hex_digit(*low))) {
+                            (Some(high), Some(low)) => {
+                                self.position += 1;
+                                high * 16 + low
+                            },
+                            _ => return Err(format!("Invalid escape at {}", self.position))
+                        },
+                        None => return Err("Unterminated string".to_string())
+                    };
+                    bytes.push(escaped);
+                    self.position += 2;
+                },
+                Some(byte) => {
+                    bytes.push(*byte);
+                    self.position += 1;
+                }
+            }
+        }
+
+    }
+
+    fn token(& mut self) -> Result<Option<Token<'t>>, std::string::String> {
+
+        self.skip()?;
+
+        let text = self.text.as_bytes();
+
+        Ok(match text.get(self.position) {
+            None => None,
+            Some(b'(') => {
+                self.position += 1;
+                Some(Token::Open)
+            },
+            Some(b')') => {
+                self.position += 1;
+                Some(Token::Close)
+            },
+            Some(b'"') => Some(Token::Text(self.string()?)),
+            Some(_) => {
+                let start = self.position;
+                while self.position < text.len() && !b" \t\n\r()\";".contains(&text[self.position]) {
+                    self.position += 1;
+                }
+                Some(Token::Atom(&self.text[start..self.position]))
+            }
+        })
+
+    }
+
+    fn peek(& mut self) -> Result<Option<Token<'t>>, std::string::String> {
+        self.clone().token()
+    }
+
+    ///
+    /// Items of a list whose `(` was read.
+    ///
+    fn items(& mut self) -> Result<Vec<Sexp<'t>>, std::string::String> {
+
+        let mut items: Vec<Sexp<'t>> = Vec::new();
+
+        loop {
+            match self.token()? {
+                None => return Err("Unexpected end of input".to_string()),
+                Some(Token::Close) => return Ok(items),
+                Some(Token::Open) => items.push(Sexp::List(self.items()?)),
+                Some(Token::Atom(atom)) => items.push(Sexp::Atom(atom)),
+                Some(Token::Text(text)) => items.push(Sexp::Text(text))
+            }
+        }
+
+    }
+
+    ///
+    /// Atom operand of an instruction.
+    ///
+    fn operand(& mut self, instruction: & str) -> Result<&'t str, std::string::String> {
+        match self.token()? {
+            Some(Token::Atom(atom)) => Ok(atom),
+            _ => Err(format!("Missing operand of {}", instruction))
+        }
+    }
+
+}
+
+///
+/// Module field: its keyword and items, the body of a function being left in the text at `body`.
+///
+struct Field<'t> {
+    kind: &'t str,
+    items: Vec<Sexp<'t>>,
+    body: usize
+}
+
+fn fields(source: & str) -> Result<Vec<Field<'_>>, std::string::String> {
+
+    let mut reader = Reader { text: source, position: 0 };
+
+    if reader.token()? != Some(Token::Open) || reader.token()? != Some(Token::Atom("module")) {
+        return Err("Expected a module".to_string());
+    }
+
+    let mut fields: Vec<Field> = Vec::new();
+
+    loop {
+
+        match reader.token()? {
+            Some(Token::Close) => break ,
+            Some(Token::Open) => {},
+            other => return Err(format!("Unexpected module field {:?}", other))
+        }
+
+        let kind = reader.operand("module field")?;
+
+        if kind != "func" {
+            fields.push(Field { kind, items: reader.items()?, body: 0 });
+            continue ;
+        }
+
+        let mut items: Vec<Sexp> = Vec::new();
+        loop {
+            match reader.peek()? {
+                Some(Token::Open) => {
+                    reader.token()?;
+                    items.push(Sexp::List(reader.items()?));
+                },
+                Some(Token::Atom(name)) if name.starts_with('$') && items.is_empty() => {
+                    reader.token()?;
+                    items.push(Sexp::Atom(name));
+                },
+                _ => break
+            }
+        }
+
+        let body = reader.position;
+
+        loop {
+            match reader.token()? {
+                Some(Token::Atom(_)) => {},
+                Some(Token::Close) => break ,
+                Some(Token::Open) => return Err("Folded instructions are not supported".to_string()),
+                other => return Err(format!("Unexpected {:?} in a function", other))
+            }
+        }
+
+        fields.push(Field { kind, items, body });
+
+    }
+
+    Ok(fields)
+
+}
+
+pub fn push_unsigned(bytes: & mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return ;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+pub fn push_signed(bytes: & mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            bytes.push(byte);
+            return ;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn push_name(bytes: & mut Vec<u8>, name: & [u8]) {
+    push_unsigned(bytes, name.len() as u64);
+    bytes.extend_from_slice(name);
+}
+
+fn push_section(module: & mut Vec<u8>, id: u8, count: usize, content: & [u8]) {
+    if count == 0 {
+        return ;
+    }
+    let mut section: Vec<u8> = Vec::new();
+    push_unsigned(& mut section, count as u64);
+    section.extend_from_slice(content);
+    module.push(id);
+    push_unsigned(module, section.len() as u64);
+    module.extend(section);
+}
+
+fn atom<'s>(sexp: &'s Sexp) -> Option<&'s str> {
+    match sexp {
+        Sexp::Atom(atom) => Some(atom),
+        _ => None
+    }
+}
+
+fn text<'s>(sexp: &'s Sexp) -> Result<&'s [u8], std::string::String> {
+    match sexp {
+        Sexp::Text(text) => Ok(text),
+        other => Err(format!("Expected a string, found {:?}", other))
+    }
+}
+
+///
+/// Keyword and items of a list such as `(param $x i32)`.
+///
+fn keyword<'s, 't>(sexp: &'s Sexp<'t>) -> Option<(&'s str, &'s [Sexp<'t>])> {
+    match sexp {
+        Sexp::List(items) => atom(items.first()?).map(|keyword| (keyword, &items[1..])),
+        _ => None
+    }
+}
+
+fn value_type(name: & str) -> Result<u8, std::string::String> {
+    match name {
+        "i32" => Ok(I32),
+        "i64" => Ok(I64),
+        other => Err(format!("Unsupported value type {}", other))
+    }
+}
+
+fn integer(text: & str) -> Result<i64, std::string::String> {
+
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text)
+    };
+
+    let digits = digits.replace('_', "");
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => digits.parse::<u64>()
+    }.map_err(|_| format!("Invalid integer {}", text))?;
+
+    Ok(if negative { (value as i64).wrapping_neg() } else { value as i64 })
+
+}
+
+///
+/// Index of a `$name` or of a plain index.
+///
+fn index(names: & HashMap<& str, u32>, reference: & str) -> Result<u32, std::string::String> {
+    match reference.starts_with('$') {
+        true => names.get(reference).copied().ok_or_else(|| format!("Unknown identifier {}", reference)),
+        false => reference.parse::<u32>().map_err(|_| format!("Invalid index {}", reference))
+    }
+}
+
+struct Signature<'t> {
+    params: Vec<(Option<&'t str>, u8)>,
+    results: Vec<u8>
+}
+
+///
+/// Signature of a function, with its exports and the number of items read.
+///
+fn signature<'t>(items: & [Sexp<'t>]) -> Result<(Signature<'t>, Vec<Vec<u8>>, usize), std::string::String> {
+
+    let mut signature = Signature { params: Vec::new(), results: Vec::new() };
+    let mut exports: Vec<Vec<u8>> = Vec::new();
+    let mut position = 0;
+
+    while let Some((keyword, arguments)) = items.get(position).and_then(keyword) {
+        match keyword {
+            "export" => exports.push(text(&arguments[0])?.to_vec()),
+            "param" => match arguments.first() {
+                Some(Sexp::Atom(name)) if name.starts_with('$') => signature.params.push((Some(name), value_type(atom(&arguments[1]).unwrap_or(""))?)),
+                _ => for argument in arguments {
+                    signature.params.push((None, value_type(atom(argument).unwrap_or(""))?));
+                }
+            },
+            "result" => for argument in arguments {
+                signature.results.push(value_type(atom(argument).unwrap_or(""))?);
+            },
+            _ => break
+        }
+        position += 1;
+    }
+
+    Ok((signature, exports, position))
+
+}
+
+struct Assembler<'t> {
+    source: &'t str,
+    types: Vec<(Vec<u8>, Vec<u8>)>,
+    functions: HashMap<&'t str, u32>,
+    globals: HashMap<&'t str, u32>
+}
+
+impl<'t> Assembler<'t> {
+
+    fn type_index(& mut self, signature: & Signature) -> u32 {
+
+        let key = (signature.params.iter().map(|(_, kind)| *kind).collect::<Vec<u8>>(), signature.results.clone());
+
+        match self.types.iter().position(|existing| *existing == key) {
+            Some(index) => index as u32,
+            None => {
+                self.types.push(key);
+                self.types.len() as u32 - 1
+            }
+        }
+
+    }
+
+    ///
+    /// Code of a function: its locals, declared by `items`, and the body starting at `body`.
+    ///
+    fn body(&self, signature: & Signature<'t>, items: & [Sexp<'t>], body: usize) -> Result<Vec<u8>, std::string::String> {
+
+        let mut locals: HashMap<& str, u32> = HashMap::new();
+        for (index, (name, _)) in signature.params.iter().enumerate() {
+            if let Some(name) = name {
+                locals.insert(name, index as u32);
+            }
+        }
+
+        let mut declared: Vec<u8> = Vec::new();
+
+        for item in items {
+            match keyword(item) {
+                Some(("local", arguments)) => match arguments.first() {
+                    Some(Sexp::Atom(name)) if name.starts_with('$') => {
+                        locals.insert(name, (signature.params.len() + declared.len()) as u32);
+                        declared.push(value_type(atom(&arguments[1]).unwrap_or(""))?);
+                    },
+                    _ => for argument in arguments {
+                        declared.push(value_type(atom(argument).unwrap_or(""))?);
+                    }
+                },
+                _ => return Err(format!("Unexpected {:?} in a function", item))
+            }
+        }
+
+        let mut code: Vec<u8> = Vec::new();
+
+        let mut runs: Vec<(usize, u8)> = Vec::new();
+        for kind in declared {
+            match runs.last_mut() {
+                Some((count, last)) if *last == kind => *count += 1,
+                _ => runs.push((1, kind))
+            }
+        }
+
+        push_unsigned(& mut code, runs.len() as u64);
+        for (count, kind) in runs {
+            push_unsigned(& mut code, count as u64);
+            code.push(kind);
+        }
+
+        let mut reader = Reader { text: self.source, position: body };
+        let mut labels: Vec<Option<&'t str>> = Vec::new();
+
+        while let Some(Token::Atom(word)) = reader.token()? {
+
+            let (_, opcode, immediate) = INSTRUCTIONS.iter().find(|(name, _, _)| *name == word).ok_or_else(|| format!("Unsupported instruction {}", word))?;
+
+            code.push(*opcode);
+
+            match immediate {
+                Immediate::None => if word == "end" {
+                    labels.pop().ok_or_else(|| "Unbalanced end".to_string())?;
+                },
+                Immediate::Block => {
+                    let label = match reader.peek()? {
+                        Some(Token::Atom(label)) if label.starts_with('$') => Some(label),
+                        _ => None
+                    };
+                    if label.is_some() {
+                        reader.token()?;
+                    }
+                    labels.push(label);
+                    code.push(0x40);
+                },
+                Immediate::Label => {
+                    let label = reader.operand(word)?;
+                    let depth = match label.starts_with('$') {
+                        true => labels.iter().rev().position(|name| *name == Some(label)).ok_or_else(|| format!("Unknown label {}", label))? as u32,
+                        false => label.parse::<u32>().map_err(|_| format!("Invalid label {}", label))?
+                    };
+                    push_unsigned(& mut code, depth as u64);
+                },
+                Immediate::Function => push_unsigned(& mut code, index(&self.functions, reader.operand(word)?)? as u64),
+                Immediate::Local => push_unsigned(& mut code, index(&locals, reader.operand(word)?)? as u64),
+                Immediate::Global => push_unsigned(& mut code, index(&self.globals, reader.operand(word)?)? as u64),
+                Immediate::I32 => push_signed(& mut code, integer(reader.operand(word)?)? as i32 as i64),
+                Immediate::I64 => push_signed(& mut code, integer(reader.operand(word)?)?),
+                Immediate::Memory(natural) => {
+                    let mut offset: u64 = 0;
+                    let mut align: u32 = *natural;
+                    while let Some(Token::Atom(argument)) = reader.peek()? {
+                        if let Some(value) = argument.strip_prefix("offset=") {
+                            offset = integer(value)? as u64;
+                        } else if let Some(value) = argument.strip_prefix("align=") {
+                            align = (integer(value)? as u64).trailing_zeros();
+                        } else {
+                            break ;
+                        }
+                        reader.token()?;
+                    }
+                    push_unsigned(& mut code, align as u64);
+                    push_unsigned(& mut code, offset);
+                }
+            }
+
+        }
+
+        if !labels.is_empty() {
+            return Err("Unterminated block".to_string());
+        }
+
+        code.push(0x0b);
+
+        Ok(code)
+
+    }
+
+}
+
+///
+/// Binary encoding of a module in the text format.
+///
+pub fn assemble(source: & str) -> Result<Vec<u8>, std::string::String> {
+
+    let fields = fields(source)?;
+
+    let mut assembler = Assembler { source, types: Vec::new(), functions: HashMap::new(), globals: HashMap::new() };
+
+    // Imported functions are numbered first
+    let mut n_imports = 0;
+    let mut n_functions = 0;
+    for field in &fields {
+        match (field.kind, field.items.first()) {
+            ("import", _) => {
+                if let Some(("func", [Sexp::Atom(name), ..])) = field.items.get(2).and_then(keyword) {
+                    assembler.functions.insert(name, n_imports);
+                }
+                n_imports += 1;
+            },
+            ("global", Some(Sexp::Atom(name))) => {
+                let index = assembler.globals.len() as u32;
+                assembler.globals.insert(name, index);
+            },
+            _ => {}
+        }
+    }
+    for field in &fields {
+        if field.kind == "func" {
+            if let Some(Sexp::Atom(name)) = field.items.first() {
+                assembler.functions.insert(name, n_imports + n_functions);
+            }
+            n_functions += 1;
+        }
+    }
+
+    let mut imports: Vec<u8> = Vec::new();
+    let mut functions: Vec<u8> = Vec::new();
+    let mut memories: Vec<u8> = Vec::new();
+    let mut globals: Vec<u8> = Vec::new();
+    let mut exports: Vec<u8> = Vec::new();
+    let mut codes: Vec<u8> = Vec::new();
+    let mut data: Vec<u8> = Vec::new();
+    let (mut n_memories, mut n_globals, mut n_exports, mut n_data) = (0, 0, 0, 0);
+    let mut function_index = n_imports;
+
+    for field in &fields {
+
+        // Skips the identifier
+        let arguments = match field.items.first().and_then(atom) {
+            Some(name) if name.starts_with('$') => &field.items[1..],
+            _ => &field.items[..]
+        };
+
+        match field.kind {
+            "import" => {
+                push_name(& mut imports, text(&arguments[0])?);
+                push_name(& mut imports, text(&arguments[1])?);
+                let items = match arguments.get(2).and_then(keyword) {
+                    Some(("func", items)) => items,
+                    _ => return Err("Only functions can be imported".to_string())
+                };
+                let items = match items.first().and_then(atom) {
+                    Some(name) if name.starts_with('$') => &items[1..],
+                    _ => items
+                };
+                let (signature, _, _) = signature(items)?;
+                imports.push(0x00);
+                push_unsigned(& mut imports, assembler.type_index(&signature) as u64);
+            },
+            "memory" => {
+                let mut position = 0;
+                while let Some(("export", [name])) = arguments.get(position).and_then(keyword) {
+                    push_name(& mut exports, text(name)?);
+                    exports.push(0x02);
+                    push_unsigned(& mut exports, n_memories as u64);
+                    n_exports += 1;
+                    position += 1;
+                }
+                memories.push(0x00);
+                push_unsigned(& mut memories, integer(arguments.get(position).and_then(atom).ok_or("Missing memory size")?)? as u64);
+                n_memories += 1;
+            },
+            "global" => {
+                let (kind, mutable) = match &arguments[0] {
+                    Sexp::Atom(kind) => (value_type(kind)?, 0x00),
+                    mutable => match keyword(mutable) {
+                        Some(("mut", [Sexp::Atom(kind)])) => (value_type(kind)?, 0x01),
+                        _ => return Err(format!("Invalid global type {:?}", mutable))
+                    }
+                };
+                globals.push(kind);
+                globals.push(mutable);
+                match arguments.get(1).and_then(keyword) {
+                    Some(("i32.const", [Sexp::Atom(value)])) => {
+                        globals.push(0x41);
+                        push_signed(& mut globals, integer(value)? as i32 as i64);
+                    },
+                    Some(("i64.const", [Sexp::Atom(value)])) => {
+                        globals.push(0x42);
+                        push_signed(& mut globals, integer(value)?);
+                    },
+                    _ => return Err("Globals must be initialized with a constant".to_string())
+                }
+                globals.push(0x0b);
+                n_globals += 1;
+            },
+            "func" => {
+                let (signature, names, position) = signature(arguments)?;
+                push_unsigned(& mut functions, assembler.type_index(&signature) as u64);
+                for name in names {
+                    push_name(& mut exports, &name);
+                    exports.push(0x00);
+                    push_unsigned(& mut exports, function_index as u64);
+                    n_exports += 1;
+                }
+                let code = assembler.body(&signature, &arguments[position..], field.body)?;
+                push_unsigned(& mut codes, code.len() as u64);
+                codes.extend(code);
+                function_index += 1;
+            },
+            "data" => {
+                let offset = match arguments.first().and_then(keyword) {
+                    Some(("i32.const", [Sexp::Atom(value)])) => integer(value)?,
+                    _ => return Err("Data segments must have a constant offset".to_string())
+                };
+                let mut bytes: Vec<u8> = Vec::new();
+                for argument in &arguments[1..] {
+                    bytes.extend_from_slice(text(argument)?);
+                }
+                data.push(0x00);
+                data.push(0x41);
+                push_signed(& mut data, offset as i32 as i64);
+                data.push(0x0b);
+                push_name(& mut data, &bytes);
+                n_data += 1;
+            },
+            other => return Err(format!("Unsupported module field {}", other))
+        }
+
+    }
+
+    let mut types: Vec<u8> = Vec::new();
+    for (params, results) in &assembler.types {
+        types.push(0x60);
+        push_name(& mut types, params);
+        push_name(& mut types, results);
+    }
+
+    let mut module: Vec<u8> = b"\0asm".to_vec();
+    module.extend_from_slice(&1u32.to_le_bytes());
+
+    push_section(& mut module, 1, assembler.types.len(), &types);
+    push_section(& mut module, 2, n_imports as usize, &imports);
+    push_section(& mut module, 3, n_functions as usize, &functions);
+    push_section(& mut module, 5, n_memories, &memories);
+    push_section(& mut module, 6, n_globals, &globals);
+    push_section(& mut module, 7, n_exports, &exports);
+    push_section(& mut module, 10, n_functions as usize, &codes);
+    push_section(& mut module, 11, n_data, &data);
+
+    Ok(module)
+
+}
+
+#[cfg(test)]
+mod assembler_test {
+
+    use codegen::wasm::assembler;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_assemble() {
+
+        let module = assembler::assemble(r#"
+            (module
+              (import "env" "log" (func $log (param i32)))
+              (memory (export "memory") 1)
+              (global $count (mut i32) (i32.const -1))
+              ;; Counts down from n
+              (func (export "count") (param $n i32) (result i32)
+                (local $i i64)
+                block $done
+                  loop $next
+                    local.get $n
+                    i32.eqz
+                    br_if $done
+                    local.get $n
+                    call $log
+                    local.get $n
+                    i32.const 1
+                    i32.sub
+                    local.set $n
+                    br 0
+                  end
+                end
+                i32.const 0
+                i32.load offset=4)
+              (data (i32.const 4) "\2a\00" "\n"))
+        "#).expect("Assembly failed");
+
+        expect_that!(&module, is(eq(vec!(
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+            // Types
+            0x01, 0x0a, 0x02, 0x60, 0x01, 0x7f, 0x00, 0x60, 0x01, 0x7f, 0x01, 0x7f,
+            // Imports
+            0x02, 0x0b, 0x01, 0x03, b'e', b'n', b'v', 0x03, b'l', b'o', b'g', 0x00, 0x00,
+            // Functions
+            0x03, 0x02, 0x01, 0x01,
+            // Memory
+            0x05, 0x03, 0x01, 0x00, 0x01,
+            // Globals
+            0x06, 0x06, 0x01, 0x7f, 0x01, 0x41, 0x7f, 0x0b,
+            // Exports
+            0x07, 0x12, 0x02, 0x06, b'm', b'e', b'm', b'o', b'r', b'y', 0x02, 0x00, 0x05, b'c', b'o', b'u', b'n', b't', 0x00, 0x01,
+            // Code
+            0x0a, 0x23, 0x01, 0x21, 0x01, 0x01, 0x7e,
+            0x02, 0x40, 0x03, 0x40, 0x20, 0x00, 0x45, 0x0d, 0x01, 0x20, 0x00, 0x10, 0x00,
+            0x20, 0x00, 0x41, 0x01, 0x6b, 0x21, 0x00, 0x0c, 0x00, 0x0b, 0x0b,
+            0x41, 0x00, 0x28, 0x02, 0x04, 0x0b,
+            // Data
+            0x0b, 0x09, 0x01, 0x00, 0x41, 0x04, 0x0b, 0x03, 0x2a, 0x00, 0x0a
+        ))));
+
+        expect_that!(&assembler::assemble("(module (func br $missing))"), is(eq(Err("Unknown label $missing".to_string()))));
+        expect_that!(&assembler::assemble("(module (func i32.const 1 f32.neg))"), is(eq(Err("Unsupported instruction f32.neg".to_string()))));
+
+    }
+
+}
@@ -0,0 +1,468 @@
+//
+// Interpreter for the modules the generator writes, running the witness calculators in the tests
+// as a JavaScript engine would.
+//
+// Only the instructions of `assembler::INSTRUCTIONS` are supported, and the module is trusted: it
+// is decoded but not validated.
+//
+
+use codegen::wasm::assembler::{Immediate, INSTRUCTIONS};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trap {
+    Unreachable,
+    OutOfBounds,
+
+    ///
+    /// Raised by an imported function, such as `exceptionHandler` throwing with a code.
+    ///
+    Host(i32)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Instruction {
+    opcode: u8,
+    immediate: i64,
+    offset: u32,
+
+    ///
+    /// Index of the matching `end` of a block, and of the `else` of an `if`.
+    ///
+    end: usize,
+    alternative: Option<usize>
+}
+
+struct Function {
+    n_params: usize,
+    n_results: usize,
+    n_locals: usize,
+    code: Vec<Instruction>
+}
+
+struct Label {
+    start: usize,
+    end: usize,
+    is_loop: bool,
+    height: usize
+}
+
+pub struct Instance {
+    imports: Vec<(std::string::String, usize)>,
+    types: Vec<(usize, usize)>,
+    functions: Vec<Function>,
+    exports: Vec<(std::string::String, usize)>,
+    globals: Vec<u64>,
+    pub memory: Vec<u8>
+}
+
+struct Decoder<'b> {
+    bytes: &'b [u8],
+    position: usize
+}
+
+impl<'b> Decoder<'b> {
+
+    fn byte(& mut self) -> Result<u8, std::string::String> {
+        let byte = *self.bytes.get(self.position).ok_or("Unexpected end of module")?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn unsigned(& mut self) -> Result<u64, std::string::String> {
+        let mut value: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn signed(& mut self) -> Result<i64, std::string::String> {
+        let mut value: i64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as i64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+                return Ok(value);
+            }
+        }
+    }
+
+    fn bytes(& mut self) -> Result<&'b [u8], std::string::String> {
+        let length = self.unsigned()? as usize;
+        let bytes = self.bytes.get(self.position..self.position + length).ok_or("Unexpected end of module")?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn name(& mut self) -> Result<std::string::String, std::string::String> {
+        Ok(std::string::String::from_utf8_lossy(self.bytes()?).to_string())
+    }
+
+    ///
+    /// Constant expression: `i32.const` or `i64.const` followed by `end`.
+    ///
+    fn constant(& mut self) -> Result<i64, std::string::String> {
+        self.byte()?;
+        let value = self.signed()?;
+        self.byte()?;
+        Ok(value)
+    }
+
+    fn code(& mut self, end: usize) -> Result<Vec<Instruction>, std::string::String> {
+
+        let mut code: Vec<Instruction> = Vec::new();
+        let mut blocks: Vec<usize> = Vec::new();
+
+        while self.position < end {
+
+            let opcode = self.byte()?;
+            let (_, _, immediate) = INSTRUCTIONS.iter().find(|(_, code, _)| *code == opcode).ok_or_else(|| format!("Unsupported opcode 0x{:02x}", opcode))?;
+            let mut instruction = Instruction { opcode, immediate: 0, offset: 0, end: 0, alternative: None };
+
+            match immediate {
+                Immediate::None => {},
+                Immediate::Block => {
+                    self.byte()?;
+                    blocks.push(code.len());
+                },
+                Immediate::Label | Immediate::Function | Immediate::Local | Immediate::Global => instruction.immediate = self.unsigned()? as i64,
+                Immediate::I32 | Immediate::I64 => instruction.immediate = self.signed()?,
+                Immediate::Memory(_) => {
+                    self.unsigned()?;
+                    instruction.offset = self.unsigned()? as u32;
+                }
+            }
+
+            match opcode {
+                0x05 => {
+                    let block = *blocks.last().ok_or("else outside of a block")?;
+                    code[block].alternative = Some(code.len());
+                },
+                0x0b => if let Some(block) = blocks.pop() {
+                    code[block].end = code.len();
+                    if let Some(alternative) = code[block].alternative {
+                        code[alternative].end = code.len();
+                    }
+                },
+                _ => {}
+            }
+
+            code.push(instruction);
+
+        }
+
+        Ok(code)
+
+    }
+
+}
+
+fn load(memory: & [u8], address: u64, size: usize) -> Result<u64, Trap> {
+    let address = address as usize;
+    let bytes = memory.get(address..address + size).ok_or(Trap::OutOfBounds)?;
+    Ok(bytes.iter().rev().fold(0, |value, byte| (value << 8) | *byte as u64))
+}
+
+fn store(memory: & mut [u8], address: u64, size: usize, value: u64) -> Result<(), Trap> {
+    let address = address as usize;
+    let bytes = memory.get_mut(address..address + size).ok_or(Trap::OutOfBounds)?;
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte = (value >> (8 * index)) as u8;
+    }
+    Ok(())
+}
+
+fn boolean(value: bool) -> u64 {
+    value as u64
+}
+
+///
+/// Host functions: called with the name of the import and the arguments, returning the result.
+///
+pub type Host<'h> = dyn FnMut(& str, & [u64]) -> Result<Option<u64>, Trap> + 'h;
+
+impl Instance {
+
+    pub fn new(module: & [u8]) -> Result<Instance, std::string::String> {
+
+        if module.get(0..8) != Some(b"\0asm\x01\0\0\0") {
+            return Err("Not a WebAssembly module".to_string());
+        }
+
+        let mut instance = Instance { imports: Vec::new(), types: Vec::new(), functions: Vec::new(), exports: Vec::new(), globals: Vec::new(), memory: Vec::new() };
+        let mut decoder = Decoder { bytes: module, position: 8 };
+        let mut function_types: Vec<usize> = Vec::new();
+        let mut data: Vec<(usize, & [u8])> = Vec::new();
+
+        while decoder.position < module.len() {
+
+            let id = decoder.byte()?;
+            let size = decoder.unsigned()? as usize;
+            let end = decoder.position + size;
+            let count = decoder.unsigned()?;
+
+            for index in 0..count {
+                match id {
+                    1 => {
+                        decoder.byte()?;
+                        let params = decoder.bytes()?.len();
+                        let results = decoder.bytes()?.len();
+                        instance.types.push((params, results));
+                    },
+                    2 => {
+                        decoder.name()?;
+                        let name = decoder.name()?;
+                        decoder.byte()?;
+                        instance.imports.push((name, decoder.unsigned()? as usize));
+                    },
+                    3 => function_types.push(decoder.unsigned()? as usize),
+                    5 => {
+                        decoder.byte()?;
+                        instance.memory = vec!(0; decoder.unsigned()? as usize * 65536);
+                    },
+                    6 => {
+                        decoder.byte()?;
+                        decoder.byte()?;
+                        let value = decoder.constant()?;
+                        instance.globals.push(value as u64);
+                    },
+                    7 => {
+                        let name = decoder.name()?;
+                        decoder.byte()?;
+                        instance.exports.push((name, decoder.unsigned()? as usize));
+                    },
+                    10 => {
+                        let size = decoder.unsigned()? as usize;
+                        let end = decoder.position + size;
+                        let mut n_locals = 0;
+                        for _ in 0..decoder.unsigned()? {
+                            n_locals += decoder.unsigned()? as usize;
+                            decoder.byte()?;
+                        }
+                        let (n_params, n_results) = instance.types[function_types[index as usize]];
+                        let code = decoder.code(end)?;
+                        instance.functions.push(Function { n_params, n_results, n_locals, code });
+                    },
+                    11 => {
+                        decoder.byte()?;
+                        let offset = decoder.constant()? as usize;
+                        data.push((offset, decoder.bytes()?));
+                    },
+                    other => return Err(format!("Unsupported section {}", other))
+                }
+            }
+
+            if decoder.position != end {
+                return Err(format!("Invalid section {}", id));
+            }
+
+        }
+
+        for (offset, bytes) in data {
+            instance.memory.get_mut(offset..offset + bytes.len()).ok_or("Data segment out of the memory")?.copy_from_slice(bytes);
+        }
+
+        Ok(instance)
+
+    }
+
+    ///
+    /// Calls an exported function. i32 values are given and returned zero extended.
+    ///
+    pub fn invoke(& mut self, name: & str, arguments: & [u64], host: & mut Host) -> Result<Option<u64>, Trap> {
+        let index = self.exports.iter().find(|(export, _)| export == name).map(|(_, index)| *index).unwrap_or_else(|| panic!("No export {}", name));
+        self.call(index, arguments.to_vec(), host)
+    }
+
+    fn call(& mut self, index: usize, arguments: Vec<u64>, host: & mut Host) -> Result<Option<u64>, Trap> {
+
+        if index < self.imports.len() {
+            let (name, _) = &self.imports[index];
+            return host(name, &arguments);
+        }
+
+        let function = &self.functions[index - self.imports.len()];
+        let n_results = function.n_results;
+        let mut locals = arguments;
+        locals.resize(function.n_params + function.n_locals, 0);
+
+        let mut stack: Vec<u64> = Vec::new();
+        let mut labels: Vec<Label> = Vec::new();
+        let mut pc = 0;
+
+        macro_rules! pop {
+            () => { stack.pop().unwrap() }
+        }
+
+        macro_rules! binary {
+            ($operation:expr) => {{
+                let right = pop!();
+                let left = pop!();
+                #[allow(clippy::redundant_closure_call)]
+                stack.push($operation(left, right));
+            }}
+        }
+
+        macro_rules! unary {
+            ($operation:expr) => {{
+                let operand = pop!();
+                #[allow(clippy::redundant_closure_call)]
+                stack.push($operation(operand));
+            }}
+        }
+
+        loop {
+
+            let instruction = self.functions[index - self.imports.len()].code[pc];
+            let immediate = instruction.immediate;
+            pc += 1;
+
+            match instruction.opcode {
+                0x00 => return Err(Trap::Unreachable),
+                0x01 => {},
+                0x02 | 0x03 => labels.push(Label { start: pc, end: instruction.end, is_loop: instruction.opcode == 0x03, height: stack.len() }),
+                0x04 => {
+                    let condition = pop!();
+                    labels.push(Label { start: pc, end: instruction.end, is_loop: false, height: stack.len() });
+                    if condition as u32 == 0 {
+                        pc = match instruction.alternative {
+                            Some(alternative) => alternative + 1,
+                            None => instruction.end
+                        };
+                    }
+                },
+                0x05 => pc = instruction.end,
+                0x0b => if labels.pop().is_none() {
+                    break ;
+                },
+                0x0c | 0x0d => {
+                    if instruction.opcode == 0x0d && pop!() as u32 == 0 {
+                        continue ;
+                    }
+                    let depth = immediate as usize;
+                    if depth >= labels.len() {
+                        break ;
+                    }
+                    let label = labels.len() - 1 - depth;
+                    stack.truncate(labels[label].height);
+                    if labels[label].is_loop {
+                        pc = labels[label].start;
+                        labels.truncate(label + 1);
+                    } else {
+                        pc = labels[label].end + 1;
+                        labels.truncate(label);
+                    }
+                },
+                0x0f => break ,
+                0x10 => {
+                    let callee = immediate as usize;
+                    let n_params = match callee < self.imports.len() {
+                        true => self.types[self.imports[callee].1].0,
+                        false => self.functions[callee - self.imports.len()].n_params
+                    };
+                    let arguments = stack.split_off(stack.len() - n_params);
+                    if let Some(result) = self.call(callee, arguments, host)? {
+                        stack.push(result);
+                    }
+                },
+                0x1a => {
+                    pop!();
+                },
+                0x1b => {
+                    let condition = pop!();
+                    let if_false = pop!();
+                    let if_true = pop!();
+                    stack.push(if condition as u32 != 0 { if_true } else { if_false });
+                },
+                0x20 => stack.push(locals[immediate as usize]),
+                0x21 => locals[immediate as usize] = pop!(),
+                0x22 => locals[immediate as usize] = *stack.last().unwrap(),
+                0x23 => stack.push(self.globals[immediate as usize]),
+                0x24 => self.globals[immediate as usize] = pop!(),
+                0x28 | 0x29 | 0x2d | 0x35 => {
+                    let size = match instruction.opcode {
+                        0x28 | 0x35 => 4,
+                        0x29 => 8,
+                        _ => 1
+                    };
+                    let address = (pop!() as u32) as u64 + instruction.offset as u64;
+                    stack.push(load(&self.memory, address, size)?);
+                },
+                0x36 | 0x37 | 0x3a | 0x3e => {
+                    let size = match instruction.opcode {
+                        0x36 | 0x3e => 4,
+                        0x37 => 8,
+                        _ => 1
+                    };
+                    let value = pop!();
+                    let address = (pop!() as u32) as u64 + instruction.offset as u64;
+                    store(& mut self.memory, address, size, value)?;
+                },
+                0x41 => stack.push(immediate as i32 as u32 as u64),
+                0x42 => stack.push(immediate as u64),
+                0x45 => unary!(|a: u64| boolean(a as u32 == 0)),
+                0x46 => binary!(|a: u64, b: u64| boolean(a as u32 == b as u32)),
+                0x47 => binary!(|a: u64, b: u64| boolean(a as u32 != b as u32)),
+                0x48 => binary!(|a: u64, b: u64| boolean((a as i32) < b as i32)),
+                0x49 => binary!(|a: u64, b: u64| boolean((a as u32) < b as u32)),
+                0x4a => binary!(|a: u64, b: u64| boolean(a as i32 > b as i32)),
+                0x4b => binary!(|a: u64, b: u64| boolean(a as u32 > b as u32)),
+                0x4c => binary!(|a: u64, b: u64| boolean(a as i32 <= b as i32)),
+                0x4d => binary!(|a: u64, b: u64| boolean(a as u32 <= b as u32)),
+                0x4e => binary!(|a: u64, b: u64| boolean(a as i32 >= b as i32)),
+                0x4f => binary!(|a: u64, b: u64| boolean(a as u32 >= b as u32)),
+                0x50 => unary!(|a: u64| boolean(a == 0)),
+                0x51 => binary!(|a: u64, b: u64| boolean(a == b)),
+                0x52 => binary!(|a: u64, b: u64| boolean(a != b)),
+                0x54 => binary!(|a: u64, b: u64| boolean(a < b)),
+                0x56 => binary!(|a: u64, b: u64| boolean(a > b)),
+                0x6a => binary!(|a: u64, b: u64| (a as u32).wrapping_add(b as u32) as u64),
+                0x6b => binary!(|a: u64, b: u64| (a as u32).wrapping_sub(b as u32) as u64),
+                0x6c => binary!(|a: u64, b: u64| (a as u32).wrapping_mul(b as u32) as u64),
+                0x71 => binary!(|a: u64, b: u64| (a as u32 & b as u32) as u64),
+                0x72 => binary!(|a: u64, b: u64| (a as u32 | b as u32) as u64),
+                0x73 => binary!(|a: u64, b: u64| (a as u32 ^ b as u32) as u64),
+                0x74 => binary!(|a: u64, b: u64| (a as u32).wrapping_shl(b as u32) as u64),
+                0x75 => binary!(|a: u64, b: u64| (a as i32).wrapping_shr(b as u32) as u32 as u64),
+                0x76 => binary!(|a: u64, b: u64| (a as u32).wrapping_shr(b as u32) as u64),
+                0x7c => binary!(|a: u64, b: u64| a.wrapping_add(b)),
+                0x7d => binary!(|a: u64, b: u64| a.wrapping_sub(b)),
+                0x7e => binary!(|a: u64, b: u64| a.wrapping_mul(b)),
+                0x80 | 0x82 => {
+                    let right = pop!();
+                    let left = pop!();
+                    if right == 0 {
+                        return Err(Trap::Unreachable);
+                    }
+                    stack.push(if instruction.opcode == 0x80 { left / right } else { left % right });
+                },
+                0x83 => binary!(|a: u64, b: u64| a & b),
+                0x84 => binary!(|a: u64, b: u64| a | b),
+                0x85 => binary!(|a: u64, b: u64| a ^ b),
+                0x86 => binary!(|a: u64, b: u64| a.wrapping_shl(b as u32)),
+                0x88 => binary!(|a: u64, b: u64| a.wrapping_shr(b as u32)),
+                0xa7 => unary!(|a: u64| a as u32 as u64),
+                0xad => unary!(|a: u64| a as u32 as u64),
+                other => panic!("Unsupported opcode 0x{:02x}", other)
+            }
+
+        }
+
+        Ok(match n_results {
+            0 => None,
+            _ => stack.pop()
+        })
+
+    }
+
+}
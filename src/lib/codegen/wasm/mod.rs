@@ -0,0 +1,1917 @@
+//
+// WebAssembly witness calculators with the interface of circom's `--wasm` output, to be loaded by
+// its `witness_calculator.js`. The module is written in the text format and encoded by
+// `assembler`, with no external toolchain.
+//
+// Field elements are 8 little endian 32 bit words holding the canonical value, products going
+// through Montgomery multiplication. The memory starts with the shared memory the JavaScript side
+// reads and writes values through, followed by the field constants and scratch space, then by the
+// signals, the temporaries of the statements and the tables of the circuit.
+//
+// The main component runs when the last input signal is set. A failing constraint or division by
+// zero writes its location to the message `getMessageChar` reads, calls `printErrorMessage` and
+// raises the exception 4, `Assert Failed`. The sanity check flag of `init` is ignored, constraints
+// being always checked.
+//
+
+pub mod assembler;
+#[cfg(test)]
+mod interpreter;
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use binfile;
+use codegen::{self, Block, Operation, Program, Statement, Step};
+use field::bigint::U256;
+use field::PrimeField;
+
+const P: usize = 32;
+const R2: usize = 64;
+const HALF: usize = 96;
+const MASK: usize = 128;
+const ONE: usize = 160;
+const MONT: usize = 192;
+const SCRATCH: [usize; 4] = [256, 288, 320, 352];
+const DIGITS: usize = 384;
+const RUNTIME_END: usize = 480;
+
+const RUNTIME: & str = r#"
+  ;; Field arithmetic, on pointers to 8 words values
+
+  (func $fr_copy (param $r i32) (param $a i32)
+    local.get $r
+    local.get $a
+    i64.load
+    i64.store
+    local.get $r
+    local.get $a
+    i64.load offset=8
+    i64.store offset=8
+    local.get $r
+    local.get $a
+    i64.load offset=16
+    i64.store offset=16
+    local.get $r
+    local.get $a
+    i64.load offset=24
+    i64.store offset=24)
+
+  (func $fr_set (param $r i32) (param $value i64)
+    local.get $r
+    local.get $value
+    i64.store
+    local.get $r
+    i64.const 0
+    i64.store offset=8
+    local.get $r
+    i64.const 0
+    i64.store offset=16
+    local.get $r
+    i64.const 0
+    i64.store offset=24)
+
+  (func $fr_boolean (param $r i32) (param $value i32)
+    local.get $r
+    local.get $value
+    i64.extend_i32_u
+    call $fr_set)
+
+  (func $fr_is_zero (param $a i32) (result i32)
+    local.get $a
+    i64.load
+    local.get $a
+    i64.load offset=8
+    i64.or
+    local.get $a
+    i64.load offset=16
+    i64.or
+    local.get $a
+    i64.load offset=24
+    i64.or
+    i64.eqz)
+
+  (func $fr_equal (param $a i32) (param $b i32) (result i32)
+    local.get $a
+    i64.load
+    local.get $b
+    i64.load
+    i64.eq
+    local.get $a
+    i64.load offset=8
+    local.get $b
+    i64.load offset=8
+    i64.eq
+    i32.and
+    local.get $a
+    i64.load offset=16
+    local.get $b
+    i64.load offset=16
+    i64.eq
+    i32.and
+    local.get $a
+    i64.load offset=24
+    local.get $b
+    i64.load offset=24
+    i64.eq
+    i32.and)
+
+  (func $add_raw (param $r i32) (param $a i32) (param $b i32) (result i32)
+    (local $i i32) (local $carry i64)
+    loop $words
+      local.get $r
+      local.get $i
+      i32.add
+      local.get $a
+      local.get $i
+      i32.add
+      i64.load32_u
+      local.get $b
+      local.get $i
+      i32.add
+      i64.load32_u
+      i64.add
+      local.get $carry
+      i64.add
+      local.tee $carry
+      i64.store32
+      local.get $carry
+      i64.const 32
+      i64.shr_u
+      local.set $carry
+      local.get $i
+      i32.const 4
+      i32.add
+      local.tee $i
+      i32.const 32
+      i32.lt_u
+      br_if $words
+    end
+    local.get $carry
+    i32.wrap_i64)
+
+  (func $sub_raw (param $r i32) (param $a i32) (param $b i32) (result i32)
+    (local $i i32) (local $difference i64) (local $borrow i64)
+    loop $words
+      local.get $a
+      local.get $i
+      i32.add
+      i64.load32_u
+      local.get $b
+      local.get $i
+      i32.add
+      i64.load32_u
+      i64.sub
+      local.get $borrow
+      i64.sub
+      local.set $difference
+      local.get $r
+      local.get $i
+      i32.add
+      local.get $difference
+      i64.store32
+      local.get $difference
+      i64.const 63
+      i64.shr_u
+      local.set $borrow
+      local.get $i
+      i32.const 4
+      i32.add
+      local.tee $i
+      i32.const 32
+      i32.lt_u
+      br_if $words
+    end
+    local.get $borrow
+    i32.wrap_i64)
+
+  (func $geq_raw (param $a i32) (param $b i32) (result i32)
+    (local $i i32) (local $x i32) (local $y i32)
+    i32.const 28
+    local.set $i
+    loop $words
+      local.get $a
+      local.get $i
+      i32.add
+      i32.load
+      local.set $x
+      local.get $b
+      local.get $i
+      i32.add
+      i32.load
+      local.set $y
+      local.get $x
+      local.get $y
+      i32.ne
+      if
+        local.get $x
+        local.get $y
+        i32.gt_u
+        return
+      end
+      local.get $i
+      i32.const 4
+      i32.sub
+      local.tee $i
+      i32.const 0
+      i32.ge_s
+      br_if $words
+    end
+    i32.const 1)
+
+  (func $reduce (param $r i32)
+    block $done
+      loop $subtract
+        local.get $r
+        global.get $P
+        call $geq_raw
+        i32.eqz
+        br_if $done
+        local.get $r
+        local.get $r
+        global.get $P
+        call $sub_raw
+        drop
+        br $subtract
+      end
+    end)
+
+  ;; a * b / 2^256 mod p
+  (func $mont_mul (param $r i32) (param $a i32) (param $b i32)
+    (local $t i32) (local $i i32) (local $j i32) (local $word i64) (local $m i64) (local $c i64)
+    global.get $MONT
+    local.tee $t
+    i64.const 0
+    i64.store
+    local.get $t
+    i64.const 0
+    i64.store offset=8
+    local.get $t
+    i64.const 0
+    i64.store offset=16
+    local.get $t
+    i64.const 0
+    i64.store offset=24
+    local.get $t
+    i64.const 0
+    i64.store offset=32
+    loop $rows
+      local.get $b
+      local.get $i
+      i32.add
+      i64.load32_u
+      local.set $word
+      i64.const 0
+      local.set $c
+      i32.const 0
+      local.set $j
+      ;; c = t[j] + a[j] * b[i] + (c >> 32), t[j] = c
+      loop $products
+        local.get $t
+        local.get $j
+        i32.add
+        local.get $t
+        local.get $j
+        i32.add
+        i64.load32_u
+        local.get $a
+        local.get $j
+        i32.add
+        i64.load32_u
+        local.get $word
+        i64.mul
+        i64.add
+        local.get $c
+        i64.const 32
+        i64.shr_u
+        i64.add
+        local.tee $c
+        i64.store32
+        local.get $j
+        i32.const 4
+        i32.add
+        local.tee $j
+        i32.const 32
+        i32.lt_u
+        br_if $products
+      end
+      local.get $t
+      local.get $t
+      i64.load32_u offset=32
+      local.get $c
+      i64.const 32
+      i64.shr_u
+      i64.add
+      local.tee $c
+      i64.store32 offset=32
+      local.get $t
+      local.get $c
+      i64.const 32
+      i64.shr_u
+      i64.store32 offset=36
+      ;; m = t[0] * -p^-1 mod 2^32
+      local.get $t
+      i64.load32_u
+      global.get $INV
+      i64.mul
+      i64.const 0xffffffff
+      i64.and
+      local.set $m
+      local.get $t
+      i64.load32_u
+      local.get $m
+      global.get $P
+      i64.load32_u
+      i64.mul
+      i64.add
+      local.set $c
+      i32.const 4
+      local.set $j
+      ;; c = t[j] + m * p[j] + (c >> 32), t[j - 1] = c
+      loop $reduction
+        local.get $t
+        local.get $j
+        i32.add
+        i32.const 4
+        i32.sub
+        local.get $t
+        local.get $j
+        i32.add
+        i64.load32_u
+        local.get $m
+        global.get $P
+        local.get $j
+        i32.add
+        i64.load32_u
+        i64.mul
+        i64.add
+        local.get $c
+        i64.const 32
+        i64.shr_u
+        i64.add
+        local.tee $c
+        i64.store32
+        local.get $j
+        i32.const 4
+        i32.add
+        local.tee $j
+        i32.const 32
+        i32.lt_u
+        br_if $reduction
+      end
+      local.get $t
+      local.get $t
+      i64.load32_u offset=32
+      local.get $c
+      i64.const 32
+      i64.shr_u
+      i64.add
+      local.tee $c
+      i64.store32 offset=28
+      local.get $t
+      local.get $t
+      i64.load32_u offset=36
+      local.get $c
+      i64.const 32
+      i64.shr_u
+      i64.add
+      i64.store32 offset=32
+      local.get $i
+      i32.const 4
+      i32.add
+      local.tee $i
+      i32.const 32
+      i32.lt_u
+      br_if $rows
+    end
+    local.get $t
+    i32.load offset=32
+    local.get $t
+    global.get $P
+    call $geq_raw
+    i32.or
+    if
+      local.get $t
+      local.get $t
+      global.get $P
+      call $sub_raw
+      drop
+    end
+    local.get $r
+    local.get $t
+    call $fr_copy)
+
+  (func $fr_add (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    local.get $b
+    call $add_raw
+    local.get $r
+    global.get $P
+    call $geq_raw
+    i32.or
+    if
+      local.get $r
+      local.get $r
+      global.get $P
+      call $sub_raw
+      drop
+    end)
+
+  (func $fr_sub (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    local.get $b
+    call $sub_raw
+    if
+      local.get $r
+      local.get $r
+      global.get $P
+      call $add_raw
+      drop
+    end)
+
+  (func $fr_neg (param $r i32) (param $a i32)
+    local.get $a
+    call $fr_is_zero
+    if
+      local.get $r
+      local.get $a
+      call $fr_copy
+    else
+      local.get $r
+      global.get $P
+      local.get $a
+      call $sub_raw
+      drop
+    end)
+
+  (func $fr_mul (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    local.get $b
+    call $mont_mul
+    local.get $r
+    local.get $r
+    global.get $R2
+    call $mont_mul)
+
+  (func $fr_inc (param $r i32) (param $a i32)
+    local.get $r
+    local.get $a
+    global.get $ONE
+    call $fr_add)
+
+  (func $fr_dec (param $r i32) (param $a i32)
+    local.get $r
+    local.get $a
+    global.get $ONE
+    call $fr_sub)
+
+  (func $bit_of (param $a i32) (param $bit i32) (result i32)
+    local.get $a
+    local.get $bit
+    i32.const 5
+    i32.shr_u
+    i32.const 2
+    i32.shl
+    i32.add
+    i32.load
+    local.get $bit
+    i32.shr_u
+    i32.const 1
+    i32.and)
+
+  ;; a^e, e being taken as an integer
+  (func $fr_pow (param $r i32) (param $a i32) (param $e i32)
+    (local $bit i32)
+    global.get $S1
+    i64.const 1
+    call $fr_set
+    global.get $S2
+    local.get $a
+    call $fr_copy
+    i32.const 255
+    local.set $bit
+    loop $bits
+      global.get $S1
+      global.get $S1
+      global.get $S1
+      call $fr_mul
+      local.get $e
+      local.get $bit
+      call $bit_of
+      if
+        global.get $S1
+        global.get $S1
+        global.get $S2
+        call $fr_mul
+      end
+      local.get $bit
+      i32.const 1
+      i32.sub
+      local.tee $bit
+      i32.const 0
+      i32.ge_s
+      br_if $bits
+    end
+    local.get $r
+    global.get $S1
+    call $fr_copy)
+
+  (func $fr_div (param $r i32) (param $a i32) (param $b i32) (param $location i32)
+    local.get $b
+    call $fr_is_zero
+    if
+      local.get $location
+      call $division_by_zero
+    end
+    global.get $S4
+    i64.const 2
+    call $fr_set
+    global.get $S3
+    global.get $P
+    global.get $S4
+    call $sub_raw
+    drop
+    global.get $S4
+    local.get $b
+    global.get $S3
+    call $fr_pow
+    local.get $r
+    local.get $a
+    global.get $S4
+    call $fr_mul)
+
+  ;; Integer division of the canonical values, the quotient and remainder going to S1 and S2
+  (func $divrem (param $a i32) (param $b i32) (param $location i32)
+    (local $q i32) (local $rem i32) (local $bit i32) (local $i i32) (local $overflow i32)
+    local.get $b
+    call $fr_is_zero
+    if
+      local.get $location
+      call $division_by_zero
+    end
+    global.get $S1
+    local.tee $q
+    i64.const 0
+    call $fr_set
+    global.get $S2
+    local.tee $rem
+    i64.const 0
+    call $fr_set
+    i32.const 255
+    local.set $bit
+    loop $bits
+      local.get $rem
+      i32.load offset=28
+      i32.const 31
+      i32.shr_u
+      local.set $overflow
+      i32.const 28
+      local.set $i
+      loop $words
+        local.get $rem
+        local.get $i
+        i32.add
+        local.get $rem
+        local.get $i
+        i32.add
+        i32.load
+        i32.const 1
+        i32.shl
+        local.get $rem
+        local.get $i
+        i32.add
+        i32.const 4
+        i32.sub
+        i32.load
+        i32.const 31
+        i32.shr_u
+        i32.or
+        i32.store
+        local.get $i
+        i32.const 4
+        i32.sub
+        local.tee $i
+        br_if $words
+      end
+      local.get $rem
+      local.get $rem
+      i32.load
+      i32.const 1
+      i32.shl
+      local.get $a
+      local.get $bit
+      call $bit_of
+      i32.or
+      i32.store
+      local.get $overflow
+      local.get $rem
+      local.get $b
+      call $geq_raw
+      i32.or
+      if
+        local.get $rem
+        local.get $rem
+        local.get $b
+        call $sub_raw
+        drop
+        local.get $q
+        local.get $bit
+        i32.const 5
+        i32.shr_u
+        i32.const 2
+        i32.shl
+        i32.add
+        local.tee $i
+        local.get $i
+        i32.load
+        i32.const 1
+        local.get $bit
+        i32.shl
+        i32.or
+        i32.store
+      end
+      local.get $bit
+      i32.const 1
+      i32.sub
+      local.tee $bit
+      i32.const 0
+      i32.ge_s
+      br_if $bits
+    end)
+
+  (func $fr_idiv (param $r i32) (param $a i32) (param $b i32) (param $location i32)
+    local.get $a
+    local.get $b
+    local.get $location
+    call $divrem
+    local.get $r
+    global.get $S1
+    call $fr_copy)
+
+  (func $fr_mod (param $r i32) (param $a i32) (param $b i32) (param $location i32)
+    local.get $a
+    local.get $b
+    local.get $location
+    call $divrem
+    local.get $r
+    global.get $S2
+    call $fr_copy)
+
+  (func $fr_not (param $r i32) (param $a i32)
+    local.get $r
+    local.get $a
+    call $fr_is_zero
+    call $fr_boolean)
+
+  (func $fr_lor (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    call $fr_is_zero
+    local.get $b
+    call $fr_is_zero
+    i32.and
+    i32.eqz
+    call $fr_boolean)
+
+  (func $fr_land (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    call $fr_is_zero
+    local.get $b
+    call $fr_is_zero
+    i32.or
+    i32.eqz
+    call $fr_boolean)
+
+  (func $fr_bnot (param $r i32) (param $a i32)
+    (local $i i32)
+    loop $words
+      local.get $r
+      local.get $i
+      i32.add
+      local.get $a
+      local.get $i
+      i32.add
+      i64.load
+      i64.const -1
+      i64.xor
+      global.get $MASK
+      local.get $i
+      i32.add
+      i64.load
+      i64.and
+      i64.store
+      local.get $i
+      i32.const 8
+      i32.add
+      local.tee $i
+      i32.const 32
+      i32.lt_u
+      br_if $words
+    end
+    local.get $r
+    call $reduce)
+
+  (func $fr_bor (param $r i32) (param $a i32) (param $b i32)
+    (local $i i32)
+    loop $words
+      local.get $r
+      local.get $i
+      i32.add
+      local.get $a
+      local.get $i
+      i32.add
+      i64.load
+      local.get $b
+      local.get $i
+      i32.add
+      i64.load
+      i64.or
+      i64.store
+      local.get $i
+      i32.const 8
+      i32.add
+      local.tee $i
+      i32.const 32
+      i32.lt_u
+      br_if $words
+    end
+    local.get $r
+    call $reduce)
+
+  (func $fr_bxor (param $r i32) (param $a i32) (param $b i32)
+    (local $i i32)
+    loop $words
+      local.get $r
+      local.get $i
+      i32.add
+      local.get $a
+      local.get $i
+      i32.add
+      i64.load
+      local.get $b
+      local.get $i
+      i32.add
+      i64.load
+      i64.xor
+      i64.store
+      local.get $i
+      i32.const 8
+      i32.add
+      local.tee $i
+      i32.const 32
+      i32.lt_u
+      br_if $words
+    end
+    local.get $r
+    call $reduce)
+
+  (func $fr_band (param $r i32) (param $a i32) (param $b i32)
+    (local $i i32)
+    loop $words
+      local.get $r
+      local.get $i
+      i32.add
+      local.get $a
+      local.get $i
+      i32.add
+      i64.load
+      local.get $b
+      local.get $i
+      i32.add
+      i64.load
+      i64.and
+      i64.store
+      local.get $i
+      i32.const 8
+      i32.add
+      local.tee $i
+      i32.const 32
+      i32.lt_u
+      br_if $words
+    end
+    local.get $r
+    call $reduce)
+
+  (func $fr_eq (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    local.get $b
+    call $fr_equal
+    call $fr_boolean)
+
+  (func $fr_neq (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    local.get $b
+    call $fr_equal
+    i32.eqz
+    call $fr_boolean)
+
+  ;; Values above p/2 are negative
+  (func $is_negative (param $a i32) (result i32)
+    global.get $HALF
+    local.get $a
+    call $geq_raw
+    i32.eqz)
+
+  (func $compare (param $a i32) (param $b i32) (result i32)
+    (local $negative i32)
+    local.get $a
+    call $is_negative
+    local.tee $negative
+    local.get $b
+    call $is_negative
+    i32.ne
+    if
+      i32.const -1
+      i32.const 1
+      local.get $negative
+      select
+      return
+    end
+    local.get $a
+    local.get $b
+    call $fr_equal
+    if
+      i32.const 0
+      return
+    end
+    i32.const 1
+    i32.const -1
+    local.get $a
+    local.get $b
+    call $geq_raw
+    select)
+
+  (func $fr_lt (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    local.get $b
+    call $compare
+    i32.const 0
+    i32.lt_s
+    call $fr_boolean)
+
+  (func $fr_leq (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    local.get $b
+    call $compare
+    i32.const 0
+    i32.le_s
+    call $fr_boolean)
+
+  (func $fr_gt (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    local.get $b
+    call $compare
+    i32.const 0
+    i32.gt_s
+    call $fr_boolean)
+
+  (func $fr_geq (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    local.get $b
+    call $compare
+    i32.const 0
+    i32.ge_s
+    call $fr_boolean)
+
+  ;; Shift by a signed amount, a negative one shifting the other way
+  (func $shift (param $r i32) (param $a i32) (param $amount i32) (param $left i32)
+    (local $absolute i32) (local $result i32) (local $count i32) (local $words i32) (local $bits i32)
+    (local $i i32) (local $from i32) (local $word i32)
+    global.get $S1
+    local.tee $absolute
+    local.get $amount
+    call $fr_copy
+    local.get $amount
+    call $is_negative
+    if
+      local.get $absolute
+      global.get $P
+      local.get $amount
+      call $sub_raw
+      drop
+      local.get $left
+      i32.eqz
+      local.set $left
+    end
+    i32.const 256
+    local.get $absolute
+    i32.load
+    local.get $absolute
+    i32.load
+    i32.const 65536
+    i32.ge_u
+    local.get $absolute
+    i32.load offset=4
+    i32.or
+    local.get $absolute
+    i32.load offset=8
+    i32.or
+    local.get $absolute
+    i32.load offset=12
+    i32.or
+    local.get $absolute
+    i32.load offset=16
+    i32.or
+    local.get $absolute
+    i32.load offset=20
+    i32.or
+    local.get $absolute
+    i32.load offset=24
+    i32.or
+    local.get $absolute
+    i32.load offset=28
+    i32.or
+    select
+    local.set $count
+    global.get $S2
+    local.tee $result
+    i64.const 0
+    call $fr_set
+    local.get $count
+    i32.const 256
+    i32.lt_u
+    if
+      local.get $count
+      i32.const 5
+      i32.shr_u
+      local.set $words
+      local.get $count
+      i32.const 31
+      i32.and
+      local.set $bits
+      loop $shifted
+        local.get $i
+        local.get $words
+        i32.sub
+        local.get $i
+        local.get $words
+        i32.add
+        local.get $left
+        select
+        local.tee $from
+        i32.const 8
+        i32.lt_u
+        if
+          local.get $a
+          local.get $from
+          i32.const 2
+          i32.shl
+          i32.add
+          i32.load
+          local.set $word
+          local.get $word
+          local.get $bits
+          i32.shl
+          local.get $word
+          local.get $bits
+          i32.shr_u
+          local.get $left
+          select
+          local.set $word
+          local.get $bits
+          if
+            local.get $left
+            if
+              local.get $from
+              if
+                local.get $word
+                local.get $a
+                local.get $from
+                i32.const 2
+                i32.shl
+                i32.add
+                i32.const 4
+                i32.sub
+                i32.load
+                i32.const 32
+                local.get $bits
+                i32.sub
+                i32.shr_u
+                i32.or
+                local.set $word
+              end
+            else
+              local.get $from
+              i32.const 7
+              i32.lt_u
+              if
+                local.get $word
+                local.get $a
+                local.get $from
+                i32.const 2
+                i32.shl
+                i32.add
+                i32.load offset=4
+                i32.const 32
+                local.get $bits
+                i32.sub
+                i32.shl
+                i32.or
+                local.set $word
+              end
+            end
+          end
+          local.get $result
+          local.get $i
+          i32.const 2
+          i32.shl
+          i32.add
+          local.get $word
+          i32.store
+        end
+        local.get $i
+        i32.const 1
+        i32.add
+        local.tee $i
+        i32.const 8
+        i32.lt_u
+        br_if $shifted
+      end
+    end
+    local.get $left
+    if
+      local.get $result
+      local.get $result
+      global.get $MASK
+      call $fr_band
+    end
+    local.get $r
+    local.get $result
+    call $fr_copy)
+
+  (func $fr_shl (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    local.get $b
+    i32.const 1
+    call $shift)
+
+  (func $fr_shr (param $r i32) (param $a i32) (param $b i32)
+    local.get $r
+    local.get $a
+    local.get $b
+    i32.const 0
+    call $shift)
+
+  ;; Errors
+
+  ;; Appends a string, returning the position of its terminating zero
+  (func $append (param $to i32) (param $string i32) (result i32)
+    (local $character i32)
+    block $done
+      loop $characters
+        local.get $string
+        i32.load8_u
+        local.tee $character
+        i32.eqz
+        br_if $done
+        local.get $to
+        local.get $character
+        i32.store8
+        local.get $to
+        i32.const 1
+        i32.add
+        local.set $to
+        local.get $string
+        i32.const 1
+        i32.add
+        local.set $string
+        br $characters
+      end
+    end
+    local.get $to
+    i32.const 0
+    i32.store8
+    local.get $to)
+
+  (func $append_decimal (param $to i32) (param $a i32) (result i32)
+    (local $value i32) (local $count i32) (local $i i32) (local $current i64) (local $remainder i64)
+    global.get $S1
+    local.tee $value
+    local.get $a
+    call $fr_copy
+    loop $digits
+      i64.const 0
+      local.set $remainder
+      i32.const 28
+      local.set $i
+      loop $words
+        local.get $remainder
+        i64.const 32
+        i64.shl
+        local.get $value
+        local.get $i
+        i32.add
+        i64.load32_u
+        i64.or
+        local.set $current
+        local.get $value
+        local.get $i
+        i32.add
+        local.get $current
+        i64.const 10
+        i64.div_u
+        i64.store32
+        local.get $current
+        i64.const 10
+        i64.rem_u
+        local.set $remainder
+        local.get $i
+        i32.const 4
+        i32.sub
+        local.tee $i
+        i32.const 0
+        i32.ge_s
+        br_if $words
+      end
+      global.get $DIGITS
+      local.get $count
+      i32.add
+      local.get $remainder
+      i32.wrap_i64
+      i32.const 48
+      i32.add
+      i32.store8
+      local.get $count
+      i32.const 1
+      i32.add
+      local.set $count
+      local.get $value
+      call $fr_is_zero
+      i32.eqz
+      br_if $digits
+    end
+    loop $copy
+      local.get $to
+      global.get $DIGITS
+      local.get $count
+      i32.const 1
+      i32.sub
+      local.tee $count
+      i32.add
+      i32.load8_u
+      i32.store8
+      local.get $to
+      i32.const 1
+      i32.add
+      local.set $to
+      local.get $count
+      br_if $copy
+    end
+    local.get $to
+    i32.const 0
+    i32.store8
+    local.get $to)
+
+  (func $throw (param $code i32)
+    local.get $code
+    call $exceptionHandler
+    unreachable)
+
+  ;; Raises an exception with the message written
+  (func $raise (param $code i32)
+    i32.const 0
+    global.set $cursor
+    call $printErrorMessage
+    local.get $code
+    call $throw)
+
+  (func $division_by_zero (param $location i32)
+    global.get $MESSAGE
+    global.get $DIVISION_BY_ZERO
+    call $append
+    global.get $AT
+    call $append
+    local.get $location
+    call $append
+    drop
+    i32.const 4
+    call $raise)
+
+  (func $constraint_failed (param $location i32) (param $left i32) (param $right i32)
+    global.get $MESSAGE
+    global.get $CONSTRAINT_NOT_SATISFIED
+    call $append
+    global.get $AT
+    call $append
+    local.get $location
+    call $append
+    global.get $LEFT
+    call $append
+    local.get $left
+    call $append_decimal
+    global.get $RIGHT
+    call $append
+    local.get $right
+    call $append_decimal
+    drop
+    i32.const 4
+    call $raise)
+
+  ;; Interface of witness_calculator.js
+
+  (func (export "getVersion") (result i32)
+    i32.const 2)
+
+  (func (export "getMinorVersion") (result i32)
+    i32.const 0)
+
+  (func (export "getPatchVersion") (result i32)
+    i32.const 0)
+
+  (func (export "getSharedRWMemoryStart") (result i32)
+    i32.const 0)
+
+  (func (export "readSharedRWMemory") (param $i i32) (result i32)
+    local.get $i
+    i32.const 2
+    i32.shl
+    i32.load)
+
+  (func (export "writeSharedRWMemory") (param $i i32) (param $value i32)
+    local.get $i
+    i32.const 2
+    i32.shl
+    local.get $value
+    i32.store)
+
+  (func (export "getFieldNumLen32") (result i32)
+    global.get $N32)
+
+  (func (export "getRawPrime")
+    i32.const 0
+    global.get $P
+    call $fr_copy)
+
+  (func (export "getWitnessSize") (result i32)
+    global.get $N_WIRES)
+
+  (func (export "getInputSize") (result i32)
+    global.get $N_INPUT_SIGNALS)
+
+  (func (export "getMessageChar") (result i32)
+    (local $character i32)
+    global.get $MESSAGE
+    global.get $cursor
+    i32.add
+    i32.load8_u
+    local.tee $character
+    if
+      global.get $cursor
+      i32.const 1
+      i32.add
+      global.set $cursor
+    end
+    local.get $character)
+
+  (func (export "init") (param $sanity_check i32)
+    (local $element i32)
+    i32.const 0
+    global.set $inputs_set
+    i32.const 0
+    global.set $cursor
+    global.get $MESSAGE
+    i32.const 0
+    i32.store8
+    block $done
+      loop $elements
+        local.get $element
+        global.get $N_INPUT_SIGNALS
+        i32.ge_u
+        br_if $done
+        global.get $INPUT_SET
+        local.get $element
+        i32.add
+        i32.const 0
+        i32.store8
+        local.get $element
+        i32.const 1
+        i32.add
+        local.set $element
+        br $elements
+      end
+    end
+    global.get $N_INPUT_SIGNALS
+    i32.eqz
+    if
+      call $component_0
+    end)
+
+  ;; Entry of the input with this hash in INPUTS, or -1
+  (func $find_input (param $msb i32) (param $lsb i32) (result i32)
+    (local $input i32) (local $entry i32)
+    block $done
+      loop $inputs
+        local.get $input
+        global.get $N_INPUTS
+        i32.ge_u
+        br_if $done
+        global.get $INPUTS
+        local.get $input
+        i32.const 4
+        i32.shl
+        i32.add
+        local.tee $entry
+        i32.load
+        local.get $msb
+        i32.eq
+        local.get $entry
+        i32.load offset=4
+        local.get $lsb
+        i32.eq
+        i32.and
+        if
+          local.get $entry
+          return
+        end
+        local.get $input
+        i32.const 1
+        i32.add
+        local.set $input
+        br $inputs
+      end
+    end
+    i32.const -1)
+
+  (func (export "getInputSignalSize") (param $msb i32) (param $lsb i32) (result i32)
+    (local $entry i32)
+    local.get $msb
+    local.get $lsb
+    call $find_input
+    local.tee $entry
+    i32.const 0
+    i32.lt_s
+    if
+      i32.const -1
+      return
+    end
+    local.get $entry
+    i32.load offset=12)
+
+  ;; Sets an element of an input from the shared memory, running the main component after the last one
+  (func (export "setInputSignal") (param $msb i32) (param $lsb i32) (param $position i32)
+    (local $entry i32) (local $element i32) (local $signal i32)
+    local.get $msb
+    local.get $lsb
+    call $find_input
+    local.tee $entry
+    i32.const 0
+    i32.lt_s
+    if
+      i32.const 1
+      call $throw
+    end
+    local.get $position
+    local.get $entry
+    i32.load offset=12
+    i32.ge_u
+    if
+      i32.const 6
+      call $throw
+    end
+    local.get $entry
+    i32.load offset=8
+    local.get $position
+    i32.add
+    local.set $element
+    global.get $INPUT_SET
+    local.get $element
+    i32.add
+    i32.load8_u
+    if
+      i32.const 3
+      call $throw
+    end
+    global.get $INPUT_SET
+    local.get $element
+    i32.add
+    i32.const 1
+    i32.store8
+    global.get $SIGNALS
+    global.get $INPUT_SIGNALS
+    local.get $element
+    i32.const 2
+    i32.shl
+    i32.add
+    i32.load
+    i32.const 5
+    i32.shl
+    i32.add
+    local.tee $signal
+    i32.const 0
+    call $fr_copy
+    local.get $signal
+    call $reduce
+    global.get $inputs_set
+    i32.const 1
+    i32.add
+    global.set $inputs_set
+    global.get $inputs_set
+    global.get $N_INPUT_SIGNALS
+    i32.eq
+    if
+      call $component_0
+    end)
+
+  ;; Copies a wire of the witness to the shared memory
+  (func (export "getWitness") (param $wire i32)
+    (local $signal i32)
+    i32.const 0
+    global.get $SIGNALS
+    global.get $WITNESS
+    local.get $wire
+    i32.const 2
+    i32.shl
+    i32.add
+    i32.load
+    local.tee $signal
+    i32.const 5
+    i32.shl
+    i32.add
+    global.get $ONE
+    local.get $signal
+    i32.const 0
+    i32.ge_s
+    select
+    call $fr_copy)
+"#;
+
+///
+/// 64 bit FNV-1a hash of an input name, as `witness_calculator.js` computes it.
+///
+pub fn fnv_hash(name: & str) -> u64 {
+    name.bytes().fold(0xcbf29ce484222325, |hash: u64, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+fn value_bytes(value: & U256) -> Vec<u8> {
+    value.0.iter().flat_map(|limb| limb.to_le_bytes()).collect()
+}
+
+///
+/// Data segment, printable characters being kept as is.
+///
+fn data(offset: usize, bytes: & [u8]) -> std::string::String {
+
+    let mut segment = format!("  (data (i32.const {}) \"", offset);
+
+    for byte in bytes {
+        match byte {
+            b'"' | b'\\' => { let _ = write!(segment, "\\{:02x}", byte); },
+            0x20..=0x7e => segment.push(*byte as char),
+            _ => { let _ = write!(segment, "\\{:02x}", byte); }
+        }
+    }
+
+    segment.push_str("\")\n");
+    segment
+
+}
+
+fn align(offset: usize) -> usize {
+    offset.div_ceil(8) * 8
+}
+
+struct Emitter<F> {
+    code: std::string::String,
+    constants: Vec<F>,
+    constant_ids: HashMap<[u64; 4], usize>,
+    signals: usize,
+    temps: usize,
+    constants_start: usize,
+    locations: Vec<usize>,
+
+    ///
+    /// Address of each temporary of the statement: the signal or constant it reads, rather than a
+    /// copy, or its slot.
+    ///
+    addresses: HashMap<usize, usize>
+}
+
+impl<F: PrimeField> Emitter<F> {
+
+    fn line(& mut self, indent: usize, line: & str) {
+        self.code.push_str(&"  ".repeat(indent));
+        self.code.push_str(line);
+        self.code.push('\n');
+    }
+
+    fn temp(&self, temp: usize) -> usize {
+        self.addresses[&temp]
+    }
+
+    fn slot(& mut self, temp: usize) -> usize {
+        self.addresses.insert(temp, self.temps + 32 * temp);
+        self.temps + 32 * temp
+    }
+
+    fn signal(&self, signal: usize) -> usize {
+        self.signals + 32 * signal
+    }
+
+    fn constant(& mut self, value: F) -> usize {
+        let constants = & mut self.constants;
+        let id = *self.constant_ids.entry(value.to_u256().0).or_insert_with(|| {
+            constants.push(value);
+            constants.len() - 1
+        });
+        self.constants_start + 32 * id
+    }
+
+    ///
+    /// Calls a function with pointer or location arguments.
+    ///
+    fn call(& mut self, indent: usize, function: & str, arguments: & [usize]) {
+        for argument in arguments {
+            self.line(indent, &format!("i32.const {}", argument));
+        }
+        self.line(indent, &format!("call ${}", function));
+    }
+
+    fn block(& mut self, block: & Block<F>, location: usize, indent: usize) {
+        for step in &block.steps {
+            match step {
+                Step::Let(temp, Operation::Constant(value)) => {
+                    let constant = self.constant(*value);
+                    self.addresses.insert(*temp, constant);
+                },
+                Step::Let(temp, Operation::Signal(signal)) => {
+                    self.addresses.insert(*temp, self.signal(*signal));
+                },
+                Step::Let(temp, Operation::Prefix(operator, operand)) => {
+                    let arguments = [self.slot(*temp), self.temp(*operand)];
+                    self.call(indent, &format!("fr_{}", codegen::prefix_name(*operator)), &arguments)
+                },
+                Step::Let(temp, Operation::Binary(operator, left, right)) => {
+                    let mut arguments = vec!(self.slot(*temp), self.temp(*left), self.temp(*right));
+                    if codegen::is_division(*operator) {
+                        arguments.push(self.locations[location]);
+                    }
+                    self.call(indent, &format!("fr_{}", codegen::binary_name(*operator)), &arguments);
+                },
+                Step::Select { temp, condition, if_true, if_false } => {
+                    let result = self.slot(*temp);
+                    self.call(indent, "fr_is_zero", &[self.temp(*condition)]);
+                    self.line(indent, "if");
+                    self.block(if_false, location, indent + 1);
+                    self.call(indent + 1, "fr_copy", &[result, self.temp(if_false.result)]);
+                    self.line(indent, "else");
+                    self.block(if_true, location, indent + 1);
+                    self.call(indent + 1, "fr_copy", &[result, self.temp(if_true.result)]);
+                    self.line(indent, "end");
+                }
+            }
+        }
+    }
+
+    fn statement(& mut self, statement: & Statement<F>) {
+        match statement {
+            Statement::Assign { signal, value, location } => {
+                self.block(value, *location, 2);
+                self.call(2, "fr_copy", &[self.signal(*signal), self.temp(value.result)]);
+            },
+            Statement::Check { left, right, location } => {
+                self.block(left, *location, 2);
+                self.block(right, *location, 2);
+                let (left, right) = (self.temp(left.result), self.temp(right.result));
+                self.call(2, "fr_equal", &[left, right]);
+                self.line(2, "i32.eqz");
+                self.line(2, "if");
+                self.call(3, "constraint_failed", &[self.locations[*location], left, right]);
+                self.line(2, "end");
+            },
+            Statement::Run(component) => self.line(2, &format!("call $component_{}", component))
+        }
+    }
+
+}
+
+///
+/// Text of the witness calculator module of a program.
+///
+pub fn generate_text<F: PrimeField>(program: & Program<F>) -> std::string::String {
+
+    let modulus = F::modulus();
+    let mask = U256::ONE.shl(modulus.bits()).overflowing_sub(&U256::ONE).0;
+
+    // Tables, then the strings, the message and the constants
+    let signals = RUNTIME_END;
+    let temps = signals + 32 * program.n_signals();
+    let inputs = temps + 32 * program.n_temps.max(1);
+    let input_signals = inputs + 16 * program.inputs.len();
+    let n_input_signals: usize = program.inputs.iter().map(|input| input.signals.len()).sum();
+    let witness = align(input_signals + 4 * n_input_signals);
+    let input_set = witness + 4 * program.n_wires;
+    let strings = align(input_set + n_input_signals);
+
+    let mut string_data: Vec<u8> = Vec::new();
+    let mut string = |text: & str| {
+        let address = strings + string_data.len();
+        string_data.extend_from_slice(text.as_bytes());
+        string_data.push(0);
+        address
+    };
+
+    let fixed = [
+        ("CONSTRAINT_NOT_SATISFIED", string("Constraint not satisfied")),
+        ("DIVISION_BY_ZERO", string("Division by zero")),
+        ("AT", string(" at ")),
+        ("LEFT", string("\n\tleft: ")),
+        ("RIGHT", string("\n\tright: "))
+    ];
+    let locations: Vec<usize> = program.locations.iter().map(|location| string(location)).collect();
+
+    let message = align(strings + string_data.len());
+    let longest = program.locations.iter().map(|location| location.len()).max().unwrap_or(0);
+    let constants = align(message + longest + 256);
+
+    let mut emitter = Emitter { code: std::string::String::new(), constants: Vec::new(), constant_ids: HashMap::new(), signals, temps, constants_start: constants, locations, addresses: HashMap::new() };
+
+    for (id, component) in program.components.iter().enumerate() {
+        emitter.line(1, &format!(";; {}: {}", component.name, component.template));
+        emitter.line(1, &format!("(func $component_{}", id));
+        for statement in &component.statements {
+            emitter.statement(statement);
+        }
+        emitter.code.pop();
+        emitter.code.push_str(")\n\n");
+    }
+
+    let size = constants + 32 * emitter.constants.len();
+
+    let mut source = std::string::String::new();
+
+    let _ = write!(source, "\
+;;
+;; Witness calculator of {template}, generated by snarkrs, for circom's witness_calculator.js
+;;
+(module
+  (import \"runtime\" \"exceptionHandler\" (func $exceptionHandler (param i32)))
+  (import \"runtime\" \"printErrorMessage\" (func $printErrorMessage))
+  (memory (export \"memory\") {pages})
+
+  (global $N32 i32 (i32.const {n32}))
+  (global $N_WIRES i32 (i32.const {wires}))
+  (global $N_INPUTS i32 (i32.const {inputs}))
+  (global $N_INPUT_SIGNALS i32 (i32.const {input_signals}))
+  (global $INV i64 (i64.const 0x{inv:08x}))
+
+",
+        template = program.template,
+        pages = size.div_ceil(65536).max(1),
+        n32 = binfile::field_size::<F>() / 4,
+        wires = program.n_wires,
+        inputs = program.inputs.len(),
+        input_signals = n_input_signals,
+        inv = codegen::montgomery_inverse(modulus.0[0]) as u32
+    );
+
+    let addresses = [
+        ("P", P), ("R2", R2), ("HALF", HALF), ("MASK", MASK), ("ONE", ONE), ("MONT", MONT),
+        ("S1", SCRATCH[0]), ("S2", SCRATCH[1]), ("S3", SCRATCH[2]), ("S4", SCRATCH[3]), ("DIGITS", DIGITS),
+        ("SIGNALS", signals), ("INPUTS", inputs), ("INPUT_SIGNALS", input_signals), ("WITNESS", witness),
+        ("INPUT_SET", input_set), ("MESSAGE", message)
+    ];
+
+    for (name, address) in addresses.iter().chain(fixed.iter()) {
+        let _ = writeln!(source, "  (global ${} i32 (i32.const {}))", name, address);
+    }
+
+    source.push_str("\n  (global $cursor (mut i32) (i32.const 0))\n  (global $inputs_set (mut i32) (i32.const 0))\n\n");
+
+    source.push_str(&data(P, &value_bytes(&modulus)));
+    source.push_str(&data(R2, &value_bytes(&F::from_u64(2).pow(&U256::from_u64(512)).to_u256())));
+    source.push_str(&data(HALF, &value_bytes(&modulus.shr(1))));
+    source.push_str(&data(MASK, &value_bytes(&mask)));
+    source.push_str(&data(ONE, &value_bytes(&U256::ONE)));
+
+    let mut table: Vec<u8> = Vec::new();
+    let mut first: u32 = 0;
+    for input in &program.inputs {
+        let hash = fnv_hash(&input.name);
+        for value in &[(hash >> 32) as u32, hash as u32, first, input.signals.len() as u32] {
+            binfile::push_u32(& mut table, *value);
+        }
+        first += input.signals.len() as u32;
+    }
+    source.push_str(&data(inputs, &table));
+
+    let mut table: Vec<u8> = Vec::new();
+    for signal in program.inputs.iter().flat_map(|input| input.signals.iter()) {
+        binfile::push_u32(& mut table, *signal as u32);
+    }
+    source.push_str(&data(input_signals, &table));
+
+    // Signal of each wire, wire 0 being the constant one
+    let mut wire_signals: Vec<i32> = vec!(-1; program.n_wires);
+    for (signal, wire) in program.wires.iter().enumerate() {
+        if let Some(wire) = wire {
+            wire_signals[*wire] = signal as i32;
+        }
+    }
+    let table: Vec<u8> = wire_signals.iter().flat_map(|signal| signal.to_le_bytes()).collect();
+    source.push_str(&data(witness, &table));
+
+    source.push_str(&data(strings, &string_data));
+
+    let table: Vec<u8> = emitter.constants.iter().flat_map(|constant| value_bytes(&constant.to_u256())).collect();
+    source.push_str(&data(constants, &table));
+
+    source.push_str(RUNTIME);
+    source.push_str("\n  ;; Components\n\n");
+    source.push_str(emitter.code.trim_end());
+    source.push_str(")\n");
+
+    source
+
+}
+
+///
+/// Binary witness calculator module of a program.
+///
+pub fn generate<F: PrimeField>(program: & Program<F>) -> Vec<u8> {
+    assembler::assemble(&generate_text(program)).expect("Invalid generated module")
+}
+
+#[cfg(test)]
+mod wasm_test {
+
+    use std::collections::HashMap;
+
+    use codegen::backend_test::{self, load};
+    use codegen::{self, wasm};
+    use codegen::wasm::interpreter::{Instance, Trap};
+    use elaborator::ElaboratedCircuit;
+    use field::bn254::Fr;
+    use field::bigint::U256;
+    use field::PrimeField;
+    use witness::{input, InputValue};
+
+    use galvanic_assert::matchers::*;
+
+    fn build(circuit: & ElaboratedCircuit<Fr>) -> Instance {
+        let program = codegen::lower(circuit, &circuit.symbols(), circuit.n_wires()).expect("Lowering failed");
+        Instance::new(&wasm::generate(&program)).expect("Invalid module")
+    }
+
+    fn flatten(value: & InputValue<Fr>, values: & mut Vec<Fr>) {
+        match value {
+            InputValue::Scalar(value) => values.push(*value),
+            InputValue::Array(items) => items.iter().for_each(|item| flatten(item, values))
+        }
+    }
+
+    fn host(name: & str, arguments: & [u64]) -> Result<Option<u64>, Trap> {
+        match name {
+            "exceptionHandler" => Err(Trap::Host(arguments[0] as i32)),
+            _ => Ok(None)
+        }
+    }
+
+    ///
+    /// Computes a witness as `witness_calculator.js` does, returning the witness or the message of
+    /// the exception raised.
+    ///
+    fn calculate(instance: & mut Instance, inputs: & str) -> Result<Vec<Fr>, std::string::String> {
+
+        let inputs: HashMap<std::string::String, InputValue<Fr>> = input::parse_inputs(inputs).unwrap();
+        let mut names: Vec<& std::string::String> = inputs.keys().collect();
+        names.sort();
+
+        let n32 = instance.invoke("getFieldNumLen32", &[], & mut host).unwrap().unwrap() as usize;
+        instance.invoke("init", &[0], & mut host).unwrap();
+
+        let mut count = 0;
+
+        for name in names {
+
+            let hash = wasm::fnv_hash(name);
+            let (msb, lsb) = (hash >> 32, hash & 0xffffffff);
+            let size = instance.invoke("getInputSignalSize", &[msb, lsb], & mut host).unwrap().unwrap() as u32 as i32;
+
+            if size < 0 {
+                return Err(format!("Signal {} not found", name));
+            }
+
+            let mut values: Vec<Fr> = Vec::new();
+            flatten(&inputs[name], & mut values);
+
+            if values.len() != size as usize {
+                return Err(format!("Expected {} values for input signal {}", size, name));
+            }
+
+            for (position, value) in values.iter().enumerate() {
+
+                let words = value.to_u256().0;
+                for word in 0..n32 {
+                    instance.invoke("writeSharedRWMemory", &[word as u64, (words[word / 2] >> (32 * (word % 2))) & 0xffffffff], & mut host).unwrap();
+                }
+
+                match instance.invoke("setInputSignal", &[msb, lsb, position as u64], & mut host) {
+                    Ok(_) => count += 1,
+                    Err(Trap::Host(4)) => return Err(message(instance)),
+                    Err(trap) => panic!("Unexpected trap {:?}", trap)
+                }
+
+            }
+
+        }
+
+        if count < instance.invoke("getInputSize", &[], & mut host).unwrap().unwrap() {
+            return Err("Not all inputs have been set".to_string());
+        }
+
+        let n_wires = instance.invoke("getWitnessSize", &[], & mut host).unwrap().unwrap();
+
+        Ok((0..n_wires).map(|wire| {
+            instance.invoke("getWitness", &[wire], & mut host).unwrap();
+            shared_value(instance, n32)
+        }).collect())
+
+    }
+
+    fn shared_value(instance: & mut Instance, n32: usize) -> Fr {
+        let mut words = [0u64; 4];
+        for word in 0..n32 {
+            words[word / 2] |= instance.invoke("readSharedRWMemory", &[word as u64], & mut host).unwrap().unwrap() << (32 * (word % 2));
+        }
+        Fr::from_u256(&U256(words))
+    }
+
+    fn message(instance: & mut Instance) -> std::string::String {
+        let mut message = std::string::String::new();
+        loop {
+            match instance.invoke("getMessageChar", &[], & mut host).unwrap().unwrap() {
+                0 => return message,
+                character => message.push(character as u8 as char)
+            }
+        }
+    }
+
+    #[test]
+    fn test_fnv_hash() {
+        expect_that!(&wasm::fnv_hash(""), is(eq(0xcbf29ce484222325)));
+        expect_that!(&wasm::fnv_hash("in"), is(eq(0x08b73807b55c4bbe)));
+    }
+
+    #[test]
+    fn test_interface() {
+
+        let mut instance = build(&load("matrix"));
+
+        expect_that!(&instance.invoke("getVersion", &[], & mut host), is(eq(Ok(Some(2)))));
+        expect_that!(&instance.invoke("getFieldNumLen32", &[], & mut host), is(eq(Ok(Some(8)))));
+        expect_that!(&instance.invoke("getInputSize", &[], & mut host), is(eq(Ok(Some(5)))));
+
+        instance.invoke("getRawPrime", &[], & mut host).unwrap();
+        expect_that!(&shared_value(& mut instance, 8).to_u256(), is(eq(U256::ZERO)));
+        expect_that!(&instance.memory[0..32].to_vec(), is(eq(Fr::modulus().0.iter().flat_map(|limb| limb.to_le_bytes()).collect::<Vec<u8>>())));
+
+        let m = wasm::fnv_hash("m");
+        instance.invoke("init", &[1], & mut host).unwrap();
+
+        expect_that!(&instance.invoke("getInputSignalSize", &[m >> 32, m & 0xffffffff], & mut host), is(eq(Ok(Some(4)))));
+        expect_that!(&instance.invoke("getInputSignalSize", &[0, 0], & mut host), is(eq(Ok(Some(0xffffffff)))));
+        expect_that!(&instance.invoke("setInputSignal", &[0, 0, 0], & mut host), is(eq(Err(Trap::Host(1)))));
+        expect_that!(&instance.invoke("setInputSignal", &[m >> 32, m & 0xffffffff, 4], & mut host), is(eq(Err(Trap::Host(6)))));
+        expect_that!(&instance.invoke("setInputSignal", &[m >> 32, m & 0xffffffff, 0], & mut host), is(eq(Ok(None))));
+        expect_that!(&instance.invoke("setInputSignal", &[m >> 32, m & 0xffffffff, 0], & mut host), is(eq(Err(Trap::Host(3)))));
+
+    }
+
+    #[test]
+    fn test_generated_witness() {
+        backend_test::check_witness(|_, circuit, inputs| {
+            let mut instance = build(circuit);
+            inputs.iter().map(|inputs| calculate(& mut instance, inputs)).collect()
+        });
+    }
+
+    #[test]
+    fn test_generated_text() {
+
+        let circuit = load("num2bits");
+        let program = codegen::lower(&circuit, &circuit.symbols(), circuit.n_wires()).expect("Lowering failed");
+        let text = wasm::generate_text(&program);
+
+        expect_that!(&text.starts_with(";;\n;; Witness calculator of Num2Bits, generated by snarkrs"), is(eq(true)));
+        expect_that!(&text.contains("(func $component_0\n"), is(eq(true)));
+        expect_that!(&wasm::assembler::assemble(&text), is(eq(Ok(wasm::generate(&program)))));
+
+    }
+
+    #[test]
+    fn test_generated_locations() {
+        backend_test::check_locations(wasm::generate);
+    }
+
+}
@@ -0,0 +1,147 @@
+//
+// Encoding of points in the iden3 binary formats (`.zkey`, `.ptau`): affine coordinates in
+// Montgomery form, little endian, `x` then `y`, the coefficients of Fq2 elements `c0` first. The
+// identity is encoded as zeros.
+//
+// Transcripts hash points in the uncompressed encoding of snarkjs instead: canonical big endian
+// coordinates, with the identity flagged by `0x40` in the first byte.
+//
+
+use binfile::{BinFileError, SectionReader};
+use curve::Field;
+use curve::bn254::{Fq2, G1Affine, G2Affine};
+use field::bigint::U256;
+use field::bn254::{Fq, FqParams};
+use field::fp::FpParams;
+
+pub const G1_SIZE: usize = 64;
+pub const G2_SIZE: usize = 128;
+
+fn push_fq(data: & mut Vec<u8>, value: & Fq) {
+    data.extend_from_slice(&U256(value.to_montgomery_limbs()).to_le_bytes());
+}
+
+fn read_fq(reader: & mut SectionReader) -> Result<Fq, BinFileError> {
+
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(reader.read_bytes(32)?);
+
+    let limbs = U256::from_le_bytes(&bytes);
+    if limbs >= U256(FqParams::MODULUS) {
+        return Err(BinFileError::FormatError("point coordinate is not reduced".to_string()));
+    }
+
+    Ok(Fq::from_montgomery_limbs(limbs.0))
+
+}
+
+fn read_fq2(reader: & mut SectionReader) -> Result<Fq2, BinFileError> {
+    let c0 = read_fq(reader)?;
+    let c1 = read_fq(reader)?;
+    Ok(Fq2::new(c0, c1))
+}
+
+pub fn push_g1(data: & mut Vec<u8>, point: & G1Affine) {
+    if point.infinity {
+        data.extend_from_slice(&[0u8; G1_SIZE]);
+    } else {
+        push_fq(data, &point.x);
+        push_fq(data, &point.y);
+    }
+}
+
+pub fn push_g2(data: & mut Vec<u8>, point: & G2Affine) {
+    if point.infinity {
+        data.extend_from_slice(&[0u8; G2_SIZE]);
+    } else {
+        for coordinate in &[point.x.c0, point.x.c1, point.y.c0, point.y.c1] {
+            push_fq(data, coordinate);
+        }
+    }
+}
+
+fn push_fq_be(data: & mut Vec<u8>, value: & Fq) {
+    use field::PrimeField;
+    let mut bytes = value.to_le_bytes();
+    bytes.reverse();
+    data.extend_from_slice(&bytes);
+}
+
+pub fn push_g1_uncompressed(data: & mut Vec<u8>, point: & G1Affine) {
+    if point.infinity {
+        let mut bytes = [0u8; G1_SIZE];
+        bytes[0] = 0x40;
+        data.extend_from_slice(&bytes);
+    } else {
+        push_fq_be(data, &point.x);
+        push_fq_be(data, &point.y);
+    }
+}
+
+pub fn push_g2_uncompressed(data: & mut Vec<u8>, point: & G2Affine) {
+    if point.infinity {
+        let mut bytes = [0u8; G2_SIZE];
+        bytes[0] = 0x40;
+        data.extend_from_slice(&bytes);
+    } else {
+        for coordinate in &[point.x.c0, point.x.c1, point.y.c0, point.y.c1] {
+            push_fq_be(data, coordinate);
+        }
+    }
+}
+
+///
+/// Reads a point, rejecting the ones that are not on the curve.
+///
+pub fn read_g1(reader: & mut SectionReader) -> Result<G1Affine, BinFileError> {
+
+    let x = read_fq(reader)?;
+    let y = read_fq(reader)?;
+
+    let point = if x.is_zero() && y.is_zero() {
+        G1Affine::identity()
+    } else {
+        G1Affine::new(x, y)
+    };
+
+    if !point.is_on_curve() {
+        return Err(BinFileError::FormatError("G1 point is not on the curve".to_string()));
+    }
+
+    Ok(point)
+
+}
+
+///
+/// Reads a point, rejecting the ones that are not on the twist or not in its subgroup of order `r`.
+///
+pub fn read_g2(reader: & mut SectionReader) -> Result<G2Affine, BinFileError> {
+
+    let x = read_fq2(reader)?;
+    let y = read_fq2(reader)?;
+
+    let point = if x.is_zero() && y.is_zero() {
+        G2Affine::identity()
+    } else {
+        G2Affine::new(x, y)
+    };
+
+    if !point.is_on_curve() {
+        return Err(BinFileError::FormatError("G2 point is not on the curve".to_string()));
+    }
+
+    if !point.is_in_subgroup() {
+        return Err(BinFileError::FormatError("G2 point is not in the subgroup".to_string()));
+    }
+
+    Ok(point)
+
+}
+
+pub fn read_g1_points(reader: & mut SectionReader, count: usize) -> Result<Vec<G1Affine>, BinFileError> {
+    (0..count).map(|_| read_g1(reader)).collect()
+}
+
+pub fn read_g2_points(reader: & mut SectionReader, count: usize) -> Result<Vec<G2Affine>, BinFileError> {
+    (0..count).map(|_| read_g2(reader)).collect()
+}
@@ -0,0 +1,97 @@
+use curve::Field;
+use curve::bn254::fq6::Fq6;
+
+///
+/// Quadratic extension `Fq6[w] / (w^2 - v)`, `c0 + c1 * w`, the target group of the pairing being
+/// a subgroup of its units.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Fq12 {
+    pub c0: Fq6,
+    pub c1: Fq6
+}
+
+impl Fq12 {
+
+    pub fn new(c0: Fq6, c1: Fq6) -> Fq12 {
+        Fq12 {
+            c0,
+            c1
+        }
+    }
+
+    ///
+    /// Conjugate `c0 - c1 * w`, which is `x^(p^6)`.
+    ///
+    pub fn conjugate(&self) -> Fq12 {
+        Fq12::new(self.c0, -self.c1)
+    }
+
+}
+
+impl Field for Fq12 {
+
+    fn zero() -> Fq12 {
+        Fq12::new(Fq6::zero(), Fq6::zero())
+    }
+
+    fn one() -> Fq12 {
+        Fq12::new(Fq6::one(), Fq6::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero()
+    }
+
+    fn inverse(&self) -> Option<Fq12> {
+        (self.c0.square() - self.c1.square().mul_by_nonresidue()).inverse()
+            .map(|norm_inverse| Fq12::new(self.c0 * norm_inverse, -(self.c1 * norm_inverse)))
+    }
+
+    fn square(&self) -> Fq12 {
+
+        let product = self.c0 * self.c1;
+        let c0 = (self.c0 + self.c1) * (self.c0 + self.c1.mul_by_nonresidue()) - product - product.mul_by_nonresidue();
+
+        Fq12::new(c0, product.double())
+
+    }
+
+}
+
+impl std::ops::Add for Fq12 {
+    type Output = Fq12;
+
+    fn add(self, other: Fq12) -> Fq12 {
+        Fq12::new(self.c0 + other.c0, self.c1 + other.c1)
+    }
+}
+
+impl std::ops::Sub for Fq12 {
+    type Output = Fq12;
+
+    fn sub(self, other: Fq12) -> Fq12 {
+        Fq12::new(self.c0 - other.c0, self.c1 - other.c1)
+    }
+}
+
+impl std::ops::Mul for Fq12 {
+    type Output = Fq12;
+
+    fn mul(self, other: Fq12) -> Fq12 {
+
+        let v0 = self.c0 * other.c0;
+        let v1 = self.c1 * other.c1;
+
+        Fq12::new(v0 + v1.mul_by_nonresidue(), (self.c0 + self.c1) * (other.c0 + other.c1) - v0 - v1)
+
+    }
+}
+
+impl std::ops::Neg for Fq12 {
+    type Output = Fq12;
+
+    fn neg(self) -> Fq12 {
+        Fq12::new(-self.c0, -self.c1)
+    }
+}
@@ -0,0 +1,157 @@
+use curve::Field;
+use field::bigint::U256;
+use field::bn254::Fq;
+use field;
+
+///
+/// Square root in Fq, `value^((q + 1) / 4)` since `q = 3 mod 4`, or `None` for non residues.
+///
+pub fn sqrt_fq(value: & Fq) -> Option<Fq> {
+
+    let exponent = <Fq as field::PrimeField>::modulus().overflowing_add(&U256::from_u64(1)).0.shr(2);
+    let root = field::PrimeField::pow(value, &exponent);
+
+    if root.square() == *value {
+        Some(root)
+    } else {
+        None
+    }
+
+}
+
+///
+/// Quadratic extension `Fq[u] / (u^2 + 1)`, `c0 + c1 * u`.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Fq2 {
+    pub c0: Fq,
+    pub c1: Fq
+}
+
+impl Fq2 {
+
+    pub fn new(c0: Fq, c1: Fq) -> Fq2 {
+        Fq2 {
+            c0,
+            c1
+        }
+    }
+
+    pub fn scale(&self, factor: & Fq) -> Fq2 {
+        Fq2::new(self.c0 * *factor, self.c1 * *factor)
+    }
+
+    ///
+    /// Conjugate `c0 - c1 * u`, which is also the Frobenius map `x^p`.
+    ///
+    pub fn conjugate(&self) -> Fq2 {
+        Fq2::new(self.c0, -self.c1)
+    }
+
+    ///
+    /// Multiplication by `xi = 9 + u`, the non-residue the sextic extension is built with.
+    ///
+    pub fn mul_by_nonresidue(&self) -> Fq2 {
+
+        let nine_c0 = self.c0.double().double().double() + self.c0;
+        let nine_c1 = self.c1.double().double().double() + self.c1;
+
+        Fq2::new(nine_c0 - self.c1, self.c0 + nine_c1)
+
+    }
+
+    ///
+    /// One of the square roots, or `None` for non residues. With `u^2 = -1`, the root of `a0 + a1 * u`
+    /// is `x0 + a1 / (2 * x0) * u` where `x0^2 = (a0 +- sqrt(a0^2 + a1^2)) / 2`.
+    ///
+    pub fn sqrt(&self) -> Option<Fq2> {
+
+        if self.c1.is_zero() {
+            return match sqrt_fq(&self.c0) {
+                Some(root) => Some(Fq2::new(root, Fq::zero())),
+                None => sqrt_fq(&-self.c0).map(|root| Fq2::new(Fq::zero(), root))
+            };
+        }
+
+        let norm_root = sqrt_fq(&(self.c0.square() + self.c1.square()))?;
+        let half = <Fq as field::PrimeField>::from_u64(2).inverse().unwrap();
+
+        let x0 = sqrt_fq(&((self.c0 + norm_root) * half))
+            .or_else(|| sqrt_fq(&((self.c0 - norm_root) * half)))?;
+        let root = Fq2::new(x0, self.c1 * x0.double().inverse().unwrap());
+
+        if root.square() == *self {
+            Some(root)
+        } else {
+            None
+        }
+
+    }
+
+}
+
+impl Field for Fq2 {
+
+    fn zero() -> Fq2 {
+        Fq2::new(Fq::zero(), Fq::zero())
+    }
+
+    fn one() -> Fq2 {
+        Fq2::new(Fq::one(), Fq::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero()
+    }
+
+    fn inverse(&self) -> Option<Fq2> {
+        (self.c0.square() + self.c1.square()).inverse()
+            .map(|norm_inverse| Fq2::new(self.c0 * norm_inverse, -self.c1 * norm_inverse))
+    }
+
+    fn square(&self) -> Fq2 {
+
+        let product = self.c0 * self.c1;
+
+        Fq2::new((self.c0 + self.c1) * (self.c0 - self.c1), product.double())
+
+    }
+
+}
+
+impl std::ops::Add for Fq2 {
+    type Output = Fq2;
+
+    fn add(self, other: Fq2) -> Fq2 {
+        Fq2::new(self.c0 + other.c0, self.c1 + other.c1)
+    }
+}
+
+impl std::ops::Sub for Fq2 {
+    type Output = Fq2;
+
+    fn sub(self, other: Fq2) -> Fq2 {
+        Fq2::new(self.c0 - other.c0, self.c1 - other.c1)
+    }
+}
+
+impl std::ops::Mul for Fq2 {
+    type Output = Fq2;
+
+    fn mul(self, other: Fq2) -> Fq2 {
+
+        let v0 = self.c0 * other.c0;
+        let v1 = self.c1 * other.c1;
+
+        Fq2::new(v0 - v1, (self.c0 + self.c1) * (other.c0 + other.c1) - v0 - v1)
+
+    }
+}
+
+impl std::ops::Neg for Fq2 {
+    type Output = Fq2;
+
+    fn neg(self) -> Fq2 {
+        Fq2::new(-self.c0, -self.c1)
+    }
+}
@@ -0,0 +1,101 @@
+use curve::Field;
+use curve::bn254::fq2::Fq2;
+
+///
+/// Cubic extension `Fq2[v] / (v^3 - xi)`, `c0 + c1 * v + c2 * v^2`.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Fq6 {
+    pub c0: Fq2,
+    pub c1: Fq2,
+    pub c2: Fq2
+}
+
+impl Fq6 {
+
+    pub fn new(c0: Fq2, c1: Fq2, c2: Fq2) -> Fq6 {
+        Fq6 {
+            c0,
+            c1,
+            c2
+        }
+    }
+
+    ///
+    /// Multiplication by `v`, the non-residue the dodecic extension is built with.
+    ///
+    pub fn mul_by_nonresidue(&self) -> Fq6 {
+        Fq6::new(self.c2.mul_by_nonresidue(), self.c0, self.c1)
+    }
+
+}
+
+impl Field for Fq6 {
+
+    fn zero() -> Fq6 {
+        Fq6::new(Fq2::zero(), Fq2::zero(), Fq2::zero())
+    }
+
+    fn one() -> Fq6 {
+        Fq6::new(Fq2::one(), Fq2::zero(), Fq2::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero() && self.c2.is_zero()
+    }
+
+    fn inverse(&self) -> Option<Fq6> {
+
+        let t0 = self.c0.square() - (self.c1 * self.c2).mul_by_nonresidue();
+        let t1 = self.c2.square().mul_by_nonresidue() - self.c0 * self.c1;
+        let t2 = self.c1.square() - self.c0 * self.c2;
+
+        let norm = self.c0 * t0 + (self.c2 * t1 + self.c1 * t2).mul_by_nonresidue();
+
+        norm.inverse().map(|norm_inverse| Fq6::new(t0 * norm_inverse, t1 * norm_inverse, t2 * norm_inverse))
+
+    }
+
+}
+
+impl std::ops::Add for Fq6 {
+    type Output = Fq6;
+
+    fn add(self, other: Fq6) -> Fq6 {
+        Fq6::new(self.c0 + other.c0, self.c1 + other.c1, self.c2 + other.c2)
+    }
+}
+
+impl std::ops::Sub for Fq6 {
+    type Output = Fq6;
+
+    fn sub(self, other: Fq6) -> Fq6 {
+        Fq6::new(self.c0 - other.c0, self.c1 - other.c1, self.c2 - other.c2)
+    }
+}
+
+impl std::ops::Mul for Fq6 {
+    type Output = Fq6;
+
+    fn mul(self, other: Fq6) -> Fq6 {
+
+        let v0 = self.c0 * other.c0;
+        let v1 = self.c1 * other.c1;
+        let v2 = self.c2 * other.c2;
+
+        let c0 = ((self.c1 + self.c2) * (other.c1 + other.c2) - v1 - v2).mul_by_nonresidue() + v0;
+        let c1 = (self.c0 + self.c1) * (other.c0 + other.c1) - v0 - v1 + v2.mul_by_nonresidue();
+        let c2 = (self.c0 + self.c2) * (other.c0 + other.c2) - v0 - v2 + v1;
+
+        Fq6::new(c0, c1, c2)
+
+    }
+}
+
+impl std::ops::Neg for Fq6 {
+    type Output = Fq6;
+
+    fn neg(self) -> Fq6 {
+        Fq6::new(-self.c0, -self.c1, -self.c2)
+    }
+}
@@ -0,0 +1,256 @@
+//
+// Encoding of field elements and points in the snarkjs JSON files (`proof.json`, `public.json`,
+// `verification_key.json`): field elements are decimal strings, points are given in Jacobian
+// projective coordinates `[x, y, z]`, the coefficients of Fq2 elements being `[c0, c1]`. snarkjs
+// writes affine points with `z = 1` and the identity as `["0", "1", "0"]`.
+//
+
+use curve::{Affine, CurveParams, Field};
+use curve::bn254::{Fq12, Fq2, Fq6, G1Affine, G2Affine};
+use field::bigint::U256;
+use field::bn254::Fq;
+use field::PrimeField;
+
+use serde_json::Value;
+
+#[derive(Debug)]
+pub enum JsonError {
+    IOError(std::io::Error),
+    JsonError(serde_json::Error),
+    FormatError(std::string::String)
+}
+
+impl std::fmt::Display for JsonError {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            JsonError::IOError(error) => write!(formatter, "{}", error),
+            JsonError::JsonError(error) => write!(formatter, "Invalid JSON: {}", error),
+            JsonError::FormatError(reason) => write!(formatter, "Invalid file: {}", reason)
+        }
+    }
+}
+
+fn format_error<T>(reason: std::string::String) -> Result<T, JsonError> {
+    Err(JsonError::FormatError(reason))
+}
+
+pub fn load(path: & std::path::Path) -> Result<Value, JsonError> {
+    let source = std::fs::read_to_string(path).map_err(JsonError::IOError)?;
+    serde_json::from_str(&source).map_err(JsonError::JsonError)
+}
+
+///
+/// Writes a document indented by a single space, as snarkjs does.
+///
+pub fn save(document: & Value, path: & std::path::Path) -> Result<(), JsonError> {
+
+    let mut bytes = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(& mut bytes, formatter);
+
+    serde::Serialize::serialize(document, & mut serializer).map_err(JsonError::JsonError)?;
+
+    std::fs::write(path, bytes).map_err(JsonError::IOError)
+
+}
+
+///
+/// Member of an object, an error naming it being returned when it is missing.
+///
+pub fn member<'a>(document: &'a Value, name: & str) -> Result<&'a Value, JsonError> {
+    match document.get(name) {
+        Some(value) => Ok(value),
+        None => format_error(format!("missing {}", name))
+    }
+}
+
+///
+/// Checks the `protocol` and `curve` members of a document, when present.
+///
+pub fn check_header(document: & Value, protocol: & str) -> Result<(), JsonError> {
+
+    match document.get("protocol").and_then(Value::as_str) {
+        Some(found) if found != protocol => return format_error(format!("expected a {} file, found {}", protocol, found)),
+        _ => ()
+    }
+
+    match document.get("curve").and_then(Value::as_str) {
+        Some(found) if found != "bn128" => format_error(format!("unsupported curve {}", found)),
+        _ => Ok(())
+    }
+
+}
+
+fn elements<'a>(value: &'a Value, count: usize, name: & str) -> Result<&'a [Value], JsonError> {
+    match value.as_array() {
+        Some(array) if array.len() == count => Ok(array),
+        _ => format_error(format!("{} is not an array of {} elements", name, count))
+    }
+}
+
+pub fn scalar_to_json<F: PrimeField>(value: & F) -> Value {
+    Value::String(value.to_string())
+}
+
+///
+/// Decodes a field element given as a decimal string, rejecting values that are not reduced.
+///
+pub fn scalar_from_json<F: PrimeField>(value: & Value, name: & str) -> Result<F, JsonError> {
+
+    let parsed = value.as_str()
+        .filter(|string| string.chars().all(|character| character.is_ascii_digit()))
+        .and_then(U256::parse);
+
+    match parsed {
+        Some(parsed) if F::from_u256(&parsed).to_u256() == parsed => Ok(F::from_u256(&parsed)),
+        Some(_) => format_error(format!("{} is not reduced", name)),
+        None => format_error(format!("{} is not a decimal string", name))
+    }
+
+}
+
+pub fn scalars_to_json<F: PrimeField>(values: & [F]) -> Value {
+    Value::Array(values.iter().map(scalar_to_json).collect())
+}
+
+pub fn scalars_from_json<F: PrimeField>(value: & Value, name: & str) -> Result<Vec<F>, JsonError> {
+    match value.as_array() {
+        Some(array) => array.iter().enumerate()
+            .map(|(idx, element)| scalar_from_json(element, &format!("{}[{}]", name, idx)))
+            .collect(),
+        None => format_error(format!("{} is not an array", name))
+    }
+}
+
+fn fq2_to_json(value: & Fq2) -> Value {
+    json!([scalar_to_json(&value.c0), scalar_to_json(&value.c1)])
+}
+
+fn fq2_from_json(value: & Value, name: & str) -> Result<Fq2, JsonError> {
+    let coefficients = elements(value, 2, name)?;
+    let c0: Fq = scalar_from_json(&coefficients[0], name)?;
+    let c1: Fq = scalar_from_json(&coefficients[1], name)?;
+    Ok(Fq2::new(c0, c1))
+}
+
+///
+/// Affine point of Jacobian coordinates `(x, y, z)`: `(x / z^2, y / z^3)`.
+///
+fn from_jacobian<C: CurveParams>(x: C::Base, y: C::Base, z: C::Base, name: & str) -> Result<Affine<C>, JsonError> {
+
+    let point = match z.inverse() {
+        Some(inverse) => {
+            let inverse_2 = inverse.square();
+            Affine::new(x * inverse_2, y * inverse_2 * inverse)
+        },
+        None => Affine::identity()
+    };
+
+    if point.is_on_curve() {
+        Ok(point)
+    } else {
+        format_error(format!("{} is not on the curve", name))
+    }
+
+}
+
+pub fn g1_to_json(point: & G1Affine) -> Value {
+    if point.infinity {
+        json!(["0", "1", "0"])
+    } else {
+        json!([scalar_to_json(&point.x), scalar_to_json(&point.y), "1"])
+    }
+}
+
+pub fn g1_from_json(value: & Value, name: & str) -> Result<G1Affine, JsonError> {
+    let coordinates = elements(value, 3, name)?;
+    let x: Fq = scalar_from_json(&coordinates[0], name)?;
+    let y: Fq = scalar_from_json(&coordinates[1], name)?;
+    let z: Fq = scalar_from_json(&coordinates[2], name)?;
+    from_jacobian(x, y, z, name)
+}
+
+pub fn g2_to_json(point: & G2Affine) -> Value {
+    if point.infinity {
+        json!([["0", "0"], ["1", "0"], ["0", "0"]])
+    } else {
+        json!([fq2_to_json(&point.x), fq2_to_json(&point.y), ["1", "0"]])
+    }
+}
+
+pub fn g2_from_json(value: & Value, name: & str) -> Result<G2Affine, JsonError> {
+    let coordinates = elements(value, 3, name)?;
+    let x = fq2_from_json(&coordinates[0], name)?;
+    let y = fq2_from_json(&coordinates[1], name)?;
+    let z = fq2_from_json(&coordinates[2], name)?;
+    let point: G2Affine = from_jacobian(x, y, z, name)?;
+    if point.is_in_subgroup() {
+        Ok(point)
+    } else {
+        format_error(format!("{} is not in the subgroup", name))
+    }
+}
+
+fn fq6_to_json(value: & Fq6) -> Value {
+    json!([fq2_to_json(&value.c0), fq2_to_json(&value.c1), fq2_to_json(&value.c2)])
+}
+
+pub fn fq12_to_json(value: & Fq12) -> Value {
+    json!([fq6_to_json(&value.c0), fq6_to_json(&value.c1)])
+}
+
+#[cfg(test)]
+mod json_test {
+
+    use curve::bn254::json;
+    use curve::bn254::{G1, G2, G1Affine, G2Affine};
+    use field::bn254::{Fq, Fr};
+    use field::PrimeField;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_points() {
+
+        let g1 = G1::generator().to_affine();
+        let g2 = G2::generator().to_affine();
+
+        expect_that!(&json::g1_to_json(&g1), is(eq(json!(["1", "2", "1"]))));
+        expect_that!(&json::g2_to_json(&g2)[0][1], is(eq(json!("11559732032986387107991004021392285783925812861821192530917403151452391805634"))));
+        expect_that!(&json::g2_to_json(&g2)[2], is(eq(json!(["1", "0"]))));
+
+        expect_that!(&json::g1_from_json(&json::g1_to_json(&g1), "A").unwrap(), is(eq(g1)));
+        expect_that!(&json::g2_from_json(&json::g2_to_json(&g2), "B").unwrap(), is(eq(g2)));
+        expect_that!(&json::g1_from_json(&json::g1_to_json(&G1Affine::identity()), "A").unwrap(), is(eq(G1Affine::identity())));
+        expect_that!(&json::g2_from_json(&json::g2_to_json(&G2Affine::identity()), "B").unwrap(), is(eq(G2Affine::identity())));
+
+        // (4, 16, 2) in Jacobian coordinates is the generator
+        let z = Fq::from_u64(2);
+        let jacobian = json!([Fq::from_u64(4).to_string(), Fq::from_u64(16).to_string(), z.to_string()]);
+        expect_that!(&json::g1_from_json(&jacobian, "A").unwrap(), is(eq(g1)));
+
+        expect_that!(&json::g1_from_json(&json!(["1", "3", "1"]), "A").is_err(), is(eq(true)));
+        expect_that!(&json::g1_from_json(&json!(["1", "2"]), "A").is_err(), is(eq(true)));
+        expect_that!(&json::g1_from_json(&json!([1, 2, 1]), "A").is_err(), is(eq(true)));
+
+        let outside = json::g2_to_json(&super::super::bn254_test::twist_point());
+        expect_that!(&json::g2_from_json(&outside, "B").is_err(), is(eq(true)));
+
+    }
+
+    #[test]
+    fn test_scalars() {
+
+        let values = vec!(Fr::from_u64(33), -Fr::one());
+        let encoded = json::scalars_to_json(&values);
+
+        expect_that!(&encoded, is(eq(json!(["33", "21888242871839275222246405745257275088548364400416034343698204186575808495616"]))));
+        expect_that!(&json::scalars_from_json::<Fr>(&encoded, "public").unwrap(), is(eq(values)));
+
+        expect_that!(&json::scalar_from_json::<Fr>(&json!("21888242871839275222246405745257275088548364400416034343698204186575808495617"), "x").is_err(), is(eq(true)));
+        expect_that!(&json::scalar_from_json::<Fr>(&json!("-1"), "x").is_err(), is(eq(true)));
+        expect_that!(&json::scalar_from_json::<Fr>(&json!("0x10"), "x").is_err(), is(eq(true)));
+
+    }
+
+}
@@ -0,0 +1,232 @@
+//
+// BN254 (alt_bn128), the curve of the default circom prime and of the Ethereum precompiles.
+//
+// G1 is `y^2 = x^3 + 3` over Fq, G2 the sextic twist `y^2 = x^3 + 3 / xi` over Fq2, and the
+// pairing maps into Fq12 (see `pairing`).
+//
+
+pub mod fq2;
+pub mod fq6;
+pub mod fq12;
+pub mod pairing;
+pub mod binary;
+pub mod json;
+
+use curve::{Affine, CurveParams, Engine, Field, Projective};
+use field::bigint::U256;
+use field::bn254::{Fq, Fr};
+
+pub use self::fq2::Fq2;
+pub use self::fq6::Fq6;
+pub use self::fq12::Fq12;
+
+fn fq(value: & str) -> Fq {
+    use field::PrimeField;
+    Fq::parse(value).unwrap()
+}
+
+///
+/// `xi = 9 + u`.
+///
+pub fn nonresidue() -> Fq2 {
+    Fq2::new(fq("9"), Fq::one())
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct G1Params;
+
+impl CurveParams for G1Params {
+
+    type Base = Fq;
+
+    fn b() -> Fq {
+        fq("3")
+    }
+
+    fn generator() -> Affine<G1Params> {
+        Affine::new(Fq::one(), fq("2"))
+    }
+
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct G2Params;
+
+impl CurveParams for G2Params {
+
+    type Base = Fq2;
+
+    fn b() -> Fq2 {
+        Fq2::new(fq("3"), Fq::zero()) * nonresidue().inverse().unwrap()
+    }
+
+    ///
+    /// Generator used by snarkjs and the Ethereum pairing precompile.
+    ///
+    fn generator() -> Affine<G2Params> {
+        Affine::new(
+            Fq2::new(
+                fq("10857046999023057135944570762232829481370756359578518086990519993285655852781"),
+                fq("11559732032986387107991004021392285783925812861821192530917403151452391805634")
+            ),
+            Fq2::new(
+                fq("8495653923123431417604973247489272438418190587263600148770280649306958101930"),
+                fq("4082367875863433681332203403145435568316851327593401208105741076214120093531")
+            )
+        )
+    }
+
+}
+
+///
+/// `6x^2` for the BN parameter `x`, little endian limbs.
+///
+const SIX_X_SQUARED: [u64; 4] = [0xf83e9682e87cfd46, 0x6f4d8248eeb859fb, 0, 0];
+
+impl Affine<G2Params> {
+
+    ///
+    /// Whether the point is in the subgroup of order `r`, which the twist does not reduce to as
+    /// its cofactor is not one: `psi(Q) = [6x^2] Q` (El Housni, Guillevic and Piellard, 2022).
+    ///
+    pub fn is_in_subgroup(&self) -> bool {
+        pairing::psi(self).to_projective() == self.mul(&U256(SIX_X_SQUARED))
+    }
+
+}
+
+pub type G1Affine = Affine<G1Params>;
+pub type G1 = Projective<G1Params>;
+pub type G2Affine = Affine<G2Params>;
+pub type G2 = Projective<G2Params>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Bn254;
+
+impl Engine for Bn254 {
+
+    type Fr = Fr;
+    type G1 = G1Params;
+    type G2 = G2Params;
+    type Gt = Fq12;
+
+    fn miller_loop(pairs: & [(G1Affine, G2Affine)]) -> Fq12 {
+        pairing::miller_loop(pairs)
+    }
+
+    fn final_exponentiation(f: & Fq12) -> Fq12 {
+        pairing::final_exponentiation(f)
+    }
+
+}
+
+#[cfg(test)]
+mod bn254_test {
+
+    use curve::{Affine, CurveParams, Engine, Field, Projective};
+    use curve::bn254::{fq, Bn254, Fq2, G1, G1Affine, G2, G2Affine, G2Params};
+    use field::bigint::U256;
+    use field::bn254::{Fq, Fr};
+    use field::PrimeField;
+
+    use galvanic_assert::matchers::*;
+
+    ///
+    /// Point of the twist of smallest `x = n + u`, outside of the subgroup of order `r`.
+    ///
+    pub fn twist_point() -> G2Affine {
+        (1..).find_map(|n| {
+            let x = Fq2::new(Fq::from_u64(n), Fq::from_u64(1));
+            (x * x * x + G2Params::b()).sqrt().map(|y| G2Affine::new(x, y))
+        }).unwrap()
+    }
+
+    ///
+    /// Group and pairing laws any engine must satisfy.
+    ///
+    fn check_engine<E: Engine>() {
+
+        let p = Projective::<E::G1>::generator();
+        let q = Projective::<E::G2>::generator();
+        let order = E::Fr::modulus();
+
+        expect_that!(&p.mul(&order).is_identity(), is(eq(true)));
+        expect_that!(&q.mul(&order).is_identity(), is(eq(true)));
+        expect_that!(&p.add_affine(&E::G1::generator()), is(eq(p.double())));
+        expect_that!(&p.double().add_affine(&-E::G1::generator()), is(eq(p)));
+        expect_that!(&q.double().add_affine(&E::G2::generator()), is(eq(q.mul(&U256::from_u64(3)))));
+
+        let e = E::pairing(&p.to_affine(), &q.to_affine());
+        expect_that!(&(e == E::Gt::one()), is(eq(false)));
+        expect_that!(&(e.pow_limbs(&order.0) == E::Gt::one()), is(eq(true)));
+
+        let a = E::Fr::from_u64(5);
+        let b = E::Fr::from_u64(7);
+        let ab = E::pairing(&p.mul_scalar(&a).to_affine(), &q.mul_scalar(&b).to_affine());
+        expect_that!(&(ab == e.pow_limbs(&[35])), is(eq(true)));
+
+        let lhs = -p.mul_scalar(&(a * b)).to_affine();
+        expect_that!(&E::pairing_check(&[(lhs, q.to_affine()), (p.mul_scalar(&a).to_affine(), q.mul_scalar(&b).to_affine())]), is(eq(true)));
+        expect_that!(&E::pairing_check(&[(lhs, q.to_affine()), (p.mul_scalar(&b).to_affine(), q.mul_scalar(&b).to_affine())]), is(eq(false)));
+
+    }
+
+    #[test]
+    fn test_engine() {
+        check_engine::<Bn254>();
+    }
+
+    #[test]
+    fn test_known_points() {
+
+        let g = G1::generator();
+
+        expect_that!(&g.double().to_affine(), is(eq(G1Affine::new(
+            fq("1368015179489954701390400359078579693043519447331113978918064868415326638035"),
+            fq("9918110051302171585080402603319702774565515993150576347155970296011118125764")
+        ))));
+        expect_that!(&g.mul(&U256::from_u64(3)).to_affine(), is(eq(G1Affine::new(
+            fq("3353031288059533942658390886683067124040920775575537747144343083137631628272"),
+            fq("19321533766552368860946552437480515441416830039777911637913418824951667761761")
+        ))));
+        expect_that!(&g.mul_scalar(&-Fr::from_u64(1)).to_affine(), is(eq(-g.to_affine())));
+
+        // 2 * G2, from the test vectors of the Ethereum pairing precompile
+        expect_that!(&G2::generator().double().to_affine(), is(eq(Affine::new(
+            Fq2::new(
+                fq("18029695676650738226693292988307914797657423701064905010927197838374790804409"),
+                fq("14583779054894525174450323658765874724019480979794335525732096752006891875705")
+            ),
+            Fq2::new(
+                fq("2140229616977736810657479771656733941598412651537078903776637920509952744750"),
+                fq("11474861747383700316476719153975578001603231366361248090558603872215261634898")
+            )
+        ))));
+
+    }
+
+    #[test]
+    fn test_subgroup() {
+
+        let g = G2::generator();
+        let order = Fr::modulus();
+
+        expect_that!(&g.to_affine().is_in_subgroup(), is(eq(true)));
+        expect_that!(&g.mul(&U256::from_u64(12345)).to_affine().is_in_subgroup(), is(eq(true)));
+        expect_that!(&G2Affine::identity().is_in_subgroup(), is(eq(true)));
+
+        let point = twist_point();
+        expect_that!(&point.is_on_curve(), is(eq(true)));
+        expect_that!(&point.mul(&order).is_identity(), is(eq(false)));
+        expect_that!(&point.is_in_subgroup(), is(eq(false)));
+        expect_that!(&(g + point.to_projective()).to_affine().is_in_subgroup(), is(eq(false)));
+
+        // Multiplying by the cofactor lands in the subgroup
+        let cofactor = U256::parse("0x30644e72e131a029b85045b68181585e06ceecda572a2489345f2299c0f9fa8d").unwrap();
+        let cleared = point.mul(&cofactor);
+        expect_that!(&cleared.mul(&order).is_identity(), is(eq(true)));
+        expect_that!(&cleared.to_affine().is_in_subgroup(), is(eq(true)));
+
+    }
+
+}
@@ -0,0 +1,395 @@
+//
+// Optimal ate pairing on BN254.
+//
+// The Miller loop runs over `6x + 2` with `T` kept in affine coordinates on the twist, the lines
+// being evaluated at `P` through the untwisting `(x, y) -> (x * w^2, y * w^3)`. Vertical lines and
+// factors in proper subfields are dropped, the final exponentiation sending them to one.
+//
+
+use curve::Field;
+use curve::bn254::{nonresidue, Fq2, Fq6, Fq12, G1Affine, G2Affine};
+use field::bn254::{Fq, FqParams};
+use field::fp::FpParams;
+use field::bigint::U256;
+
+///
+/// `6x + 2` for the BN parameter `x = 4965661367192848881`.
+///
+const ATE_LOOP_COUNT: u128 = 0x19d797039be763ba8;
+
+///
+/// `p^2`, little endian limbs.
+///
+const P_SQUARED: [u64; 8] = [
+    0x3b5458a2275d69b1, 0xa602072d09eac101, 0x4a50189c6d96cadc, 0x04689e957a1242c8,
+    0x26edfa5c34c6b38d, 0xb00b855116375606, 0x599a6f7c0348d21c, 0x0925c4b8763cbf9c
+];
+
+///
+/// `(p^4 - p^2 + 1) / r`, little endian limbs.
+///
+const HARD_EXPONENT: [u64; 12] = [
+    0xe81bb482ccdf42b1, 0x5abf5cc4f49c36d4, 0xf1154e7e1da014fd, 0xdcc7b44c87cdbacf,
+    0xaaa441e3954bcf8a, 0x6b887d56d5095f23, 0x79581e16f3fd90c6, 0x3b1b1355d189227d,
+    0x4e529a5861876f6b, 0x6c0eb522d5b12278, 0x331ec15183177faf, 0x01baaa710b0759ad
+];
+
+///
+/// `xi^((p - 1) / divisor)`, the constants of the Frobenius map on the twist.
+///
+fn frobenius_coefficient(divisor: u64) -> Fq2 {
+    let (p_minus_one, _) = U256(FqParams::MODULUS).overflowing_sub(&U256::ONE);
+    nonresidue().pow_limbs(&p_minus_one.divrem_u64(divisor).0 .0)
+}
+
+///
+/// Frobenius map `(x, y) -> (x^p, y^p)` on E(Fq12), expressed on the twist.
+///
+fn frobenius(point: & G2Affine, coefficients: & (Fq2, Fq2)) -> G2Affine {
+    G2Affine::new(point.x.conjugate() * coefficients.0, point.y.conjugate() * coefficients.1)
+}
+
+///
+/// Line of slope `lambda` through `t`, evaluated at `p`.
+///
+fn line(t: & G2Affine, lambda: & Fq2, p: & G1Affine) -> Fq12 {
+    Fq12::new(
+        Fq6::new(Fq2::new(p.y, Fq::zero()), Fq2::zero(), Fq2::zero()),
+        Fq6::new(-lambda.scale(&p.x), *lambda * t.x - t.y, Fq2::zero())
+    )
+}
+
+///
+/// `psi(x, y) = (x^p * xi^((p - 1) / 3), y^p * xi^((p - 1) / 2))`, the endomorphism of the twist
+/// acting as the Frobenius map on E(Fq12).
+///
+pub fn psi(point: & G2Affine) -> G2Affine {
+    if point.infinity {
+        *point
+    } else {
+        frobenius(point, &(frobenius_coefficient(3), frobenius_coefficient(2)))
+    }
+}
+
+fn double_step(t: & mut G2Affine, p: & G1Affine) -> Fq12 {
+
+    let x_2 = t.x.square();
+    let lambda = match t.y.double().inverse() {
+        Some(inverse) => (x_2.double() + x_2) * inverse,
+        None => {
+            // The tangent is vertical (or `T` is the identity): `2T` is the identity. Points of
+            // the subgroup never get there, as `T` stays a multiple of `Q` below `r`.
+            *t = G2Affine::identity();
+            return Fq12::one();
+        }
+    };
+    let evaluation = line(t, &lambda, p);
+
+    let x = lambda.square() - t.x.double();
+    let y = lambda * (t.x - x) - t.y;
+    *t = G2Affine::new(x, y);
+
+    evaluation
+
+}
+
+fn add_step(t: & mut G2Affine, q: & G2Affine, p: & G1Affine) -> Fq12 {
+
+    if t.infinity {
+        *t = *q;
+        return Fq12::one();
+    }
+
+    let lambda = match (q.x - t.x).inverse() {
+        Some(inverse) => (q.y - t.y) * inverse,
+        None if t.y == q.y => return double_step(t, p),
+        None => {
+            // T = -Q: the line is vertical and the sum is the identity, which only happens after
+            // the last step of the loop.
+            *t = G2Affine::identity();
+            return Fq12::one();
+        }
+    };
+    let evaluation = line(t, &lambda, p);
+
+    let x = lambda.square() - t.x - q.x;
+    let y = lambda * (t.x - x) - t.y;
+    *t = G2Affine::new(x, y);
+
+    evaluation
+
+}
+
+///
+/// Product of the Miller loops of the pairs, pairs with an identity contributing one.
+///
+pub fn miller_loop(pairs: & [(G1Affine, G2Affine)]) -> Fq12 {
+
+    let pairs: Vec<& (G1Affine, G2Affine)> = pairs.iter().filter(|(p, q)| !p.infinity && !q.infinity).collect();
+    let mut points: Vec<G2Affine> = pairs.iter().map(|(_, q)| *q).collect();
+
+    let mut f = Fq12::one();
+
+    for bit in (0..64).rev() {
+
+        f = f.square();
+
+        for ((p, q), t) in pairs.iter().zip(points.iter_mut()) {
+            f = f * double_step(t, p);
+            if (ATE_LOOP_COUNT >> bit) & 1 == 1 {
+                f = f * add_step(t, q, p);
+            }
+        }
+
+    }
+
+    let coefficients = (frobenius_coefficient(3), frobenius_coefficient(2));
+
+    for ((p, q), t) in pairs.iter().zip(points.iter_mut()) {
+        let q1 = frobenius(q, &coefficients);
+        let q2 = frobenius(&q1, &coefficients);
+        f = f * add_step(t, &q1, p);
+        f = f * add_step(t, &-q2, p);
+    }
+
+    f
+
+}
+
+///
+/// `f^((p^12 - 1) / r)`.
+///
+pub fn final_exponentiation(f: & Fq12) -> Fq12 {
+
+    let f = f.conjugate() * f.inverse().unwrap();
+    let f = f.pow_limbs(&P_SQUARED) * f;
+
+    f.pow_limbs(&HARD_EXPONENT)
+
+}
+
+pub fn pairing(p: & G1Affine, q: & G2Affine) -> Fq12 {
+    final_exponentiation(&miller_loop(&[(*p, *q)]))
+}
+
+///
+/// Whether the product of the pairings of the pairs is one, with a single final exponentiation.
+///
+pub fn pairing_check(pairs: & [(G1Affine, G2Affine)]) -> bool {
+    final_exponentiation(&miller_loop(pairs)) == Fq12::one()
+}
+
+#[cfg(test)]
+mod pairing_test {
+
+    use curve::{Affine, CurveParams, Field, Projective};
+    use curve::bn254::{fq, Fq2, Fq6, Fq12, G1, G1Affine, G1Params, G2, G2Affine, G2Params};
+    use field::bn254::Fr;
+    use field::PrimeField;
+    use curve::bn254::pairing::{pairing, pairing_check};
+    use field::bigint::U256;
+
+    use galvanic_assert::matchers::*;
+
+    // Pairing checks of the Ethereum precompile (EIP-197), from the go-ethereum test vectors: the
+    // 32 bytes big endian words of the input and the expected result
+    const PAIRINGS: [(& str, & [& str], bool); 11] = [
+        ("jeff1", &[
+            "1c76476f4def4bb94541d57ebba1193381ffa7aa76ada664dd31c16024c43f59", "3034dd2920f673e204fee2811c678745fc819b55d3e9d294e45c9b03a76aef41",
+            "209dd15ebff5d46c4bd888e51a93cf99a7329636c63514396b4a452003a35bf7", "04bf11ca01483bfa8b34b43561848d28905960114c8ac04049af4b6315a41678",
+            "2bb8324af6cfc93537a2ad1a445cfd0ca2a71acd7ac41fadbf933c2a51be344d", "120a2a4cf30c1bf9845f20c6fe39e07ea2cce61f0c9bb048165fe5e4de877550",
+            "111e129f1cf1097710d41c4ac70fcdfa5ba2023c6ff1cbeac322de49d1b6df7c", "2032c61a830e3c17286de9462bf242fca2883585b93870a73853face6a6bf411",
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2", "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b", "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"
+        ], true),
+        ("jeff2", &[
+            "2eca0c7238bf16e83e7a1e6c5d49540685ff51380f309842a98561558019fc02", "03d3260361bb8451de5ff5ecd17f010ff22f5c31cdf184e9020b06fa5997db84",
+            "1213d2149b006137fcfb23036606f848d638d576a120ca981b5b1a5f9300b3ee", "2276cf730cf493cd95d64677bbb75fc42db72513a4c1e387b476d056f80aa75f",
+            "21ee6226d31426322afcda621464d0611d226783262e21bb3bc86b537e986237", "096df1f82dff337dd5972e32a8ad43e28a78a96a823ef1cd4debe12b6552ea5f",
+            "06967a1237ebfeca9aaae0d6d0bab8e28c198c5a339ef8a2407e31cdac516db9", "22160fa257a5fd5b280642ff47b65eca77e626cb685c84fa6d3b6882a283ddd1",
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2", "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b", "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"
+        ], true),
+        ("jeff3", &[
+            "0f25929bcb43d5a57391564615c9e70a992b10eafa4db109709649cf48c50dd2", "16da2f5cb6be7a0aa72c440c53c9bbdfec6c36c7d515536431b3a865468acbba",
+            "2e89718ad33c8bed92e210e81d1853435399a271913a6520736a4729cf0d51eb", "01a9e2ffa2e92599b68e44de5bcf354fa2642bd4f26b259daa6f7ce3ed57aeb3",
+            "14a9a87b789a58af499b314e13c3d65bede56c07ea2d418d6874857b70763713", "178fb49a2d6cd347dc58973ff49613a20757d0fcc22079f9abd10c3baee24590",
+            "1b9e027bd5cfc2cb5db82d4dc9677ac795ec500ecd47deee3b5da006d6d049b8", "11d7511c78158de484232fc68daf8a45cf217d1c2fae693ff5871e8752d73b21",
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2", "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b", "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"
+        ], true),
+        ("jeff4", &[
+            "2f2ea0b3da1e8ef11914acf8b2e1b32d99df51f5f4f206fc6b947eae860eddb6", "068134ddb33dc888ef446b648d72338684d678d2eb2371c61a50734d78da4b72",
+            "25f83c8b6ab9de74e7da488ef02645c5a16a6652c3c71a15dc37fe3a5dcb7cb1", "22acdedd6308e3bb230d226d16a105295f523a8a02bfc5e8bd2da135ac4c245d",
+            "065bbad92e7c4e31bf3757f1fe7362a63fbfee50e7dc68da116e67d600d9bf68", "06d302580dc0661002994e7cd3a7f224e7ddc27802777486bf80f40e4ca3cfdb",
+            "186bac5188a98c45e6016873d107f5cd131f3a3e339d0375e58bd6219347b008", "122ae2b09e539e152ec5364e7e2204b03d11d3caa038bfc7cd499f8176aacbee",
+            "1f39e4e4afc4bc74790a4a028aff2c3d2538731fb755edefd8cb48d6ea589b5e", "283f150794b6736f670d6a1033f9b46c6f5204f50813eb85c8dc4b59db1c5d39",
+            "140d97ee4d2b36d99bc49974d18ecca3e7ad51011956051b464d9e27d46cc25e", "0764bb98575bd466d32db7b15f582b2d5c452b36aa394b789366e5e3ca5aabd4",
+            "15794ab061441e51d01e94640b7e3084a07e02c78cf3103c542bc5b298669f21", "1b88da1679b0b64a63b7e0e7bfe52aae524f73a55be7fe70c7e9bfc94b4cf0da",
+            "1213d2149b006137fcfb23036606f848d638d576a120ca981b5b1a5f9300b3ee", "2276cf730cf493cd95d64677bbb75fc42db72513a4c1e387b476d056f80aa75f",
+            "21ee6226d31426322afcda621464d0611d226783262e21bb3bc86b537e986237", "096df1f82dff337dd5972e32a8ad43e28a78a96a823ef1cd4debe12b6552ea5f"
+        ], true),
+        ("jeff5", &[
+            "20a754d2071d4d53903e3b31a7e98ad6882d58aec240ef981fdf0a9d22c5926a", "29c853fcea789887315916bbeb89ca37edb355b4f980c9a12a94f30deeed3021",
+            "1213d2149b006137fcfb23036606f848d638d576a120ca981b5b1a5f9300b3ee", "2276cf730cf493cd95d64677bbb75fc42db72513a4c1e387b476d056f80aa75f",
+            "21ee6226d31426322afcda621464d0611d226783262e21bb3bc86b537e986237", "096df1f82dff337dd5972e32a8ad43e28a78a96a823ef1cd4debe12b6552ea5f",
+            "1abb4a25eb9379ae96c84fff9f0540abcfc0a0d11aeda02d4f37e4baf74cb0c1", "1073b3ff2cdbb38755f8691ea59e9606696b3ff278acfc098fa8226470d03869",
+            "217cee0a9ad79a4493b5253e2e4e3a39fc2df38419f230d341f60cb064a0ac29", "0a3d76f140db8418ba512272381446eb73958670f00cf46f1d9e64cba057b53c",
+            "26f64a8ec70387a13e41430ed3ee4a7db2059cc5fc13c067194bcc0cb49a9855", "2fd72bd9edb657346127da132e5b82ab908f5816c826acb499e22f2412d1a2d7",
+            "0f25929bcb43d5a57391564615c9e70a992b10eafa4db109709649cf48c50dd2", "198a1f162a73261f112401aa2db79c7dab1533c9935c77290a6ce3b191f2318d",
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2", "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b", "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"
+        ], true),
+        ("jeff6", &[
+            "1c76476f4def4bb94541d57ebba1193381ffa7aa76ada664dd31c16024c43f59", "3034dd2920f673e204fee2811c678745fc819b55d3e9d294e45c9b03a76aef41",
+            "209dd15ebff5d46c4bd888e51a93cf99a7329636c63514396b4a452003a35bf7", "04bf11ca01483bfa8b34b43561848d28905960114c8ac04049af4b6315a41678",
+            "2bb8324af6cfc93537a2ad1a445cfd0ca2a71acd7ac41fadbf933c2a51be344d", "120a2a4cf30c1bf9845f20c6fe39e07ea2cce61f0c9bb048165fe5e4de877550",
+            "111e129f1cf1097710d41c4ac70fcdfa5ba2023c6ff1cbeac322de49d1b6df7c", "103188585e2364128fe25c70558f1560f4f9350baf3959e603cc91486e110936",
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2", "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b", "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"
+        ], false),
+        ("empty_data", &[], true),
+        ("one_point", &[
+            "0000000000000000000000000000000000000000000000000000000000000001", "0000000000000000000000000000000000000000000000000000000000000002",
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2", "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b", "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"
+        ], false),
+        ("two_point_match_2", &[
+            "0000000000000000000000000000000000000000000000000000000000000001", "0000000000000000000000000000000000000000000000000000000000000002",
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2", "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b", "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+            "0000000000000000000000000000000000000000000000000000000000000001", "0000000000000000000000000000000000000000000000000000000000000002",
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2", "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "275dc4a288d1afb3cbb1ac09187524c7db36395df7be3b99e673b13a075a65ec", "1d9befcd05a5323e6da4d435f3b617cdb3af83285c2df711ef39c01571827f9d"
+        ], true),
+        ("two_point_match_3", &[
+            "0000000000000000000000000000000000000000000000000000000000000001", "0000000000000000000000000000000000000000000000000000000000000002",
+            "203e205db4f19b37b60121b83a7333706db86431c6d835849957ed8c3928ad79", "27dc7234fd11d3e8c36c59277c3e6f149d5cd3cfa9a62aee49f8130962b4b3b9",
+            "195e8aa5b7827463722b8c153931579d3505566b4edf48d498e185f0509de152", "04bb53b8977e5f92a0bc372742c4830944a59b4fe6b1c0466e2a6dad122b5d2e",
+            "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3", "1a76dae6d3272396d0cbe61fced2bc532edac647851e3ac53ce1cc9c7e645a83",
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2", "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b", "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"
+        ], true),
+        ("two_point_match_4", &[
+            "105456a333e6d636854f987ea7bb713dfd0ae8371a72aea313ae0c32c0bf1016", "0cf031d41b41557f3e7e3ba0c51bebe5da8e6ecd855ec50fc87efcdeac168bcc",
+            "0476be093a6d2b4bbf907172049874af11e1b6267606e00804d3ff0037ec57fd", "3010c68cb50161b7d1d96bb71edfec9880171954e56871abf3d93cc94d745fa1",
+            "14c059d74e5b6c4ec14ae5864ebe23a71781d86c29fb8fb6cce94f70d3de7a21", "01b33461f39d9e887dbb100f170a2345dde3c07e256d1dfa2b657ba5cd030427",
+            "0000000000000000000000000000000000000000000000000000000000000001", "0000000000000000000000000000000000000000000000000000000000000002",
+            "1a2c3013d2ea92e13c800cde68ef56a294b883f6ac35d25f587c09b1b3c635f7", "290158a80cd3d66530f74dc94c94adb88f5cdb481acca997b6e60071f08a115f",
+            "2f997f3dbd66a7afe07fe7862ce239edba9e05c5afff7f8a1259c9733b2dfbb9", "29d1691530ca701b4a106054688728c9972c8512e9789e9567aae23e302ccd75"
+        ], true)
+    ];
+
+    // Points of the precompile input: `x` and `y` for G1, then `x` and `y` for G2 with the imaginary
+    // part of the coefficients first, zeros standing for the identity
+    fn precompile_pairs(words: & [& str]) -> Vec<(G1Affine, G2Affine)> {
+
+        let words: Vec<_> = words.iter().map(|word| fq(&format!("0x{}", word))).collect();
+
+        words.chunks(6).map(|chunk| {
+            let p = if chunk[..2].iter().all(Field::is_zero) {
+                Affine::identity()
+            } else {
+                Affine::new(chunk[0], chunk[1])
+            };
+            let q = if chunk[2..].iter().all(Field::is_zero) {
+                Affine::identity()
+            } else {
+                Affine::new(Fq2::new(chunk[3], chunk[2]), Fq2::new(chunk[5], chunk[4]))
+            };
+            (p, q)
+        }).collect()
+
+    }
+
+    #[test]
+    fn test_pairing() {
+
+        expect_that!(&G1Params::generator().is_on_curve(), is(eq(true)));
+        expect_that!(&G2Params::generator().is_on_curve(), is(eq(true)));
+        expect_that!(&G1::generator().mul(&Fr::modulus()).is_identity(), is(eq(true)));
+        expect_that!(&G2::generator().mul(&Fr::modulus()).is_identity(), is(eq(true)));
+        expect_that!(&(G1::generator().double() + G1::generator()), is(eq(G1::generator().mul(&U256::from_u64(3)))));
+        expect_that!(&(G2::generator() - G2::generator()), is(eq(Projective::identity())));
+
+        let p = G1::generator();
+        let q = G2::generator();
+
+        let e = pairing(&p.to_affine(), &q.to_affine());
+        expect_that!(&(e == Fq12::one()), is(eq(false)));
+
+        let p6 = p.mul(&U256::from_u64(6)).to_affine();
+        let q6 = q.mul(&U256::from_u64(6)).to_affine();
+        let p2 = p.mul(&U256::from_u64(2)).to_affine();
+        let q3 = q.mul(&U256::from_u64(3)).to_affine();
+        let e6 = e.pow_limbs(&[6]);
+
+        expect_that!(&pairing(&p6, &q.to_affine()), is(eq(e6)));
+        expect_that!(&pairing(&p.to_affine(), &q6), is(eq(e6)));
+        expect_that!(&pairing(&p2, &q3), is(eq(e6)));
+
+        expect_that!(&pairing_check(&[(-p6, q.to_affine()), (p2, q3)]), is(eq(true)));
+        expect_that!(&pairing_check(&[(p6, q.to_affine()), (p2, q3)]), is(eq(false)));
+
+    }
+
+
+    #[test]
+    fn test_known_pairing() {
+
+        // e(G1, G2)^(2x(6x^2 + 3x + 1)), as computed by arkworks (ark-bn254 0.4), whose final
+        // exponentiation raises the pairing to this extra power (Fuentes-Castaneda et al.)
+        let fq2 = |c0, c1| Fq2::new(fq(c0), fq(c1));
+        let expected = Fq12::new(
+            Fq6::new(
+                fq2("17264119758069723980713015158403419364912226240334615592005620718956030922389", "1300711225518851207585954685848229181392358478699795190245709208408267917898"),
+                fq2("8894217292938489450175280157304813535227569267786222825147475294561798790624", "1829859855596098509359522796979920150769875799037311140071969971193843357227"),
+                fq2("4968700049505451466697923764727215585075098085662966862137174841375779106779", "12814315002058128940449527172080950701976819591738376253772993495204862218736")
+            ),
+            Fq6::new(
+                fq2("4233474252585134102088637248223601499779641130562251948384759786370563844606", "9420544134055737381096389798327244442442230840902787283326002357297404128074"),
+                fq2("13457906610892676317612909831857663099224588803620954529514857102808143524905", "5122435115068592725432309312491733755581898052459744089947319066829791570839"),
+                fq2("8891987925005301465158626530377582234132838601606565363865129986128301774627", "440796048150724096437130979851431985500142692666486515369083499585648077975")
+            )
+        );
+
+        let exponent = [0x2e5d4e223ddedaf4, 0x1ea96b02d9d9e38d, 0x3bec47df15e307c8];
+        expect_that!(&pairing(&G1Params::generator(), &G2Params::generator()).pow_limbs(&exponent), is(eq(expected)));
+
+    }
+
+    #[test]
+    fn test_precompile_vectors() {
+        for (name, words, expected) in PAIRINGS.iter() {
+            expect_that!(&(name, pairing_check(&precompile_pairs(words))), is(eq((name, *expected))));
+        }
+    }
+
+    #[test]
+    fn test_degenerate_steps() {
+
+        let p = G1::generator().to_affine();
+        let q = G2::generator().to_affine();
+
+        // T = -Q, then doubling and adding to the identity, as when the loop reaches a multiple of
+        // the order of a point outside of the subgroup
+        let mut t = -q;
+        expect_that!(&super::add_step(& mut t, &q, &p), is(eq(Fq12::one())));
+        expect_that!(&t, is(eq(G2Affine::identity())));
+        expect_that!(&super::double_step(& mut t, &p), is(eq(Fq12::one())));
+        expect_that!(&t, is(eq(G2Affine::identity())));
+        expect_that!(&super::add_step(& mut t, &q, &p), is(eq(Fq12::one())));
+        expect_that!(&t, is(eq(q)));
+
+        // T = Q is a doubling
+        let mut doubled = q;
+        super::add_step(& mut t, &q, &p);
+        super::double_step(& mut doubled, &p);
+        expect_that!(&t, is(eq(doubled)));
+
+        let point = super::super::bn254_test::twist_point();
+        expect_that!(&pairing_check(&[(p, point)]), is(eq(false)));
+
+    }
+
+}
@@ -0,0 +1,445 @@
+//
+// Elliptic curves in short Weierstrass form `y^2 = x^3 + b` and the pairings the proving systems
+// are built on.
+//
+// Points are added in Jacobian coordinates (`x = X / Z^2`, `y = Y / Z^3`), and stored or serialized
+// in affine coordinates. Curves are described by `CurveParams` and pairings by `Engine`, BN254 being
+// the one implemented (see `bn254`). Groth16 is generic over the engine, PLONK is tied to BN254.
+//
+
+pub mod bn254;
+pub mod msm;
+
+use field::bigint::U256;
+use field::domain::{self, Domain};
+use field::fp::{Fp, FpParams};
+use field::PrimeField;
+
+pub use self::msm::multi_exp;
+
+///
+/// Field the coordinates of curve points live in: a prime field or one of its extensions.
+///
+pub trait Field:
+    Copy + Clone + PartialEq + Eq + std::fmt::Debug + Send + Sync +
+    std::ops::Add<Output = Self> + std::ops::Sub<Output = Self> + std::ops::Mul<Output = Self> + std::ops::Neg<Output = Self>
+{
+
+    fn zero() -> Self;
+
+    fn one() -> Self;
+
+    fn is_zero(&self) -> bool;
+
+    fn inverse(&self) -> Option<Self>;
+
+    fn square(&self) -> Self {
+        *self * *self
+    }
+
+    fn double(&self) -> Self {
+        *self + *self
+    }
+
+    ///
+    /// `self^exponent`, the exponent being given as little endian 64 bits limbs.
+    ///
+    fn pow_limbs(&self, exponent: & [u64]) -> Self {
+
+        let mut result = Self::one();
+
+        for limb in exponent.iter().rev() {
+            for bit in (0..64).rev() {
+                result = result.square();
+                if (limb >> bit) & 1 == 1 {
+                    result = result * *self;
+                }
+            }
+        }
+
+        result
+
+    }
+
+}
+
+impl<P: FpParams> Field for Fp<P> {
+
+    fn zero() -> Fp<P> {
+        <Fp<P> as PrimeField>::zero()
+    }
+
+    fn one() -> Fp<P> {
+        <Fp<P> as PrimeField>::one()
+    }
+
+    fn is_zero(&self) -> bool {
+        <Fp<P> as PrimeField>::is_zero(self)
+    }
+
+    fn inverse(&self) -> Option<Fp<P>> {
+        <Fp<P> as PrimeField>::inverse(self)
+    }
+
+    fn square(&self) -> Fp<P> {
+        <Fp<P> as PrimeField>::square(self)
+    }
+
+}
+
+///
+/// Curve `y^2 = x^3 + b` over `Base`, restricted to the subgroup generated by `generator`.
+///
+pub trait CurveParams: 'static + Copy + Clone + std::fmt::Debug + PartialEq + Eq + Send + Sync {
+
+    type Base: Field;
+
+    fn b() -> Self::Base;
+
+    fn generator() -> Affine<Self>;
+
+}
+
+///
+/// Pairing friendly curve: groups `G1` and `G2` of prime order `r` and a bilinear pairing into the
+/// multiplicative group `Gt`, computed as a Miller loop followed by a final exponentiation.
+///
+pub trait Engine {
+
+    type Fr: PrimeField;
+    type G1: CurveParams;
+    type G2: CurveParams;
+    type Gt: Field;
+
+    fn miller_loop(pairs: & [PairingInput<Self>]) -> Self::Gt;
+
+    fn final_exponentiation(f: & Self::Gt) -> Self::Gt;
+
+    fn pairing(p: & Affine<Self::G1>, q: & Affine<Self::G2>) -> Self::Gt {
+        Self::final_exponentiation(&Self::miller_loop(&[(*p, *q)]))
+    }
+
+    ///
+    /// Whether the product of the pairings of the pairs is one, with a single final exponentiation.
+    ///
+    fn pairing_check(pairs: & [PairingInput<Self>]) -> bool {
+        Self::final_exponentiation(&Self::miller_loop(pairs)) == Self::Gt::one()
+    }
+
+}
+
+///
+/// Pair of points whose pairing is computed.
+///
+pub type PairingInput<E> = (Affine<<E as Engine>::G1>, Affine<<E as Engine>::G2>);
+
+///
+/// Point in affine coordinates, `infinity` being the identity.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Affine<C: CurveParams> {
+    pub x: C::Base,
+    pub y: C::Base,
+    pub infinity: bool
+}
+
+impl<C: CurveParams> Affine<C> {
+
+    pub fn new(x: C::Base, y: C::Base) -> Affine<C> {
+        Affine {
+            x,
+            y,
+            infinity: false
+        }
+    }
+
+    pub fn identity() -> Affine<C> {
+        Affine {
+            x: C::Base::zero(),
+            y: C::Base::zero(),
+            infinity: true
+        }
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        self.infinity || self.y.square() == self.x.square() * self.x + C::b()
+    }
+
+    pub fn to_projective(&self) -> Projective<C> {
+        if self.infinity {
+            Projective::identity()
+        } else {
+            Projective {
+                x: self.x,
+                y: self.y,
+                z: C::Base::one()
+            }
+        }
+    }
+
+    pub fn mul(&self, scalar: & U256) -> Projective<C> {
+        self.to_projective().mul(scalar)
+    }
+
+}
+
+impl<C: CurveParams> std::ops::Neg for Affine<C> {
+    type Output = Affine<C>;
+
+    fn neg(self) -> Affine<C> {
+        Affine {
+            x: self.x,
+            y: if self.infinity { self.y } else { -self.y },
+            infinity: self.infinity
+        }
+    }
+}
+
+///
+/// Point in Jacobian coordinates, `z = 0` being the identity.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct Projective<C: CurveParams> {
+    pub x: C::Base,
+    pub y: C::Base,
+    pub z: C::Base
+}
+
+impl<C: CurveParams> Projective<C> {
+
+    pub fn identity() -> Projective<C> {
+        Projective {
+            x: C::Base::one(),
+            y: C::Base::one(),
+            z: C::Base::zero()
+        }
+    }
+
+    pub fn generator() -> Projective<C> {
+        C::generator().to_projective()
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    pub fn to_affine(&self) -> Affine<C> {
+
+        let z_inverse = match self.z.inverse() {
+            Some(z_inverse) => z_inverse,
+            None => return Affine::identity()
+        };
+
+        let z_inverse_2 = z_inverse.square();
+
+        Affine::new(self.x * z_inverse_2, self.y * z_inverse_2 * z_inverse)
+
+    }
+
+    pub fn double(&self) -> Projective<C> {
+
+        if self.is_identity() {
+            return *self;
+        }
+
+        let a = self.x.square();
+        let b = self.y.square();
+        let c = b.square();
+        let d = ((self.x + b).square() - a - c).double();
+        let e = a.double() + a;
+        let f = e.square();
+
+        let x = f - d.double();
+        let y = e * (d - x) - c.double().double().double();
+        let z = (self.y * self.z).double();
+
+        Projective {
+            x,
+            y,
+            z
+        }
+
+    }
+
+    ///
+    /// `self * scalar` by double and add.
+    ///
+    pub fn mul(&self, scalar: & U256) -> Projective<C> {
+
+        let mut result = Projective::identity();
+
+        for idx in (0..scalar.bits()).rev() {
+            result = result.double();
+            if scalar.bit(idx) {
+                result += *self;
+            }
+        }
+
+        result
+
+    }
+
+    pub fn mul_scalar<F: PrimeField>(&self, scalar: & F) -> Projective<C> {
+        self.mul(&scalar.to_u256())
+    }
+
+    ///
+    /// `self + other`, cheaper than the addition of two Jacobian points as `other` has `z = 1`.
+    ///
+    pub fn add_affine(&self, other: & Affine<C>) -> Projective<C> {
+
+        if other.infinity {
+            return *self;
+        }
+        if self.is_identity() {
+            return other.to_projective();
+        }
+
+        let z1_2 = self.z.square();
+        let u2 = other.x * z1_2;
+        let s2 = other.y * self.z * z1_2;
+
+        if self.x == u2 {
+            return if self.y == s2 { self.double() } else { Projective::identity() };
+        }
+
+        let h = u2 - self.x;
+        let h_2 = h.square();
+        let i = h_2.double().double();
+        let j = h * i;
+        let r = (s2 - self.y).double();
+        let v = self.x * i;
+
+        let x = r.square() - j - v.double();
+        let y = r * (v - x) - (self.y * j).double();
+        let z = (self.z + h).square() - z1_2 - h_2;
+
+        Projective {
+            x,
+            y,
+            z
+        }
+
+    }
+
+}
+
+impl<C: CurveParams> PartialEq for Projective<C> {
+    fn eq(&self, other: & Projective<C>) -> bool {
+
+        if self.is_identity() || other.is_identity() {
+            return self.is_identity() && other.is_identity();
+        }
+
+        let z1_2 = self.z.square();
+        let z2_2 = other.z.square();
+
+        self.x * z2_2 == other.x * z1_2 && self.y * z2_2 * other.z == other.y * z1_2 * self.z
+
+    }
+}
+
+impl<C: CurveParams> Eq for Projective<C> {}
+
+impl<C: CurveParams> std::ops::Add for Projective<C> {
+    type Output = Projective<C>;
+
+    fn add(self, other: Projective<C>) -> Projective<C> {
+
+        if self.is_identity() {
+            return other;
+        }
+        if other.is_identity() {
+            return self;
+        }
+
+        let z1_2 = self.z.square();
+        let z2_2 = other.z.square();
+        let u1 = self.x * z2_2;
+        let u2 = other.x * z1_2;
+        let s1 = self.y * other.z * z2_2;
+        let s2 = other.y * self.z * z1_2;
+
+        if u1 == u2 {
+            return if s1 == s2 { self.double() } else { Projective::identity() };
+        }
+
+        let h = u2 - u1;
+        let i = h.double().square();
+        let j = h * i;
+        let r = (s2 - s1).double();
+        let v = u1 * i;
+
+        let x = r.square() - j - v.double();
+        let y = r * (v - x) - (s1 * j).double();
+        let z = ((self.z + other.z).square() - z1_2 - z2_2) * h;
+
+        Projective {
+            x,
+            y,
+            z
+        }
+
+    }
+}
+
+impl<C: CurveParams> std::ops::Neg for Projective<C> {
+    type Output = Projective<C>;
+
+    fn neg(self) -> Projective<C> {
+        Projective {
+            x: self.x,
+            y: -self.y,
+            z: self.z
+        }
+    }
+}
+
+impl<C: CurveParams> std::ops::Sub for Projective<C> {
+    type Output = Projective<C>;
+
+    fn sub(self, other: Projective<C>) -> Projective<C> {
+        self + (-other)
+    }
+}
+
+impl<C: CurveParams> std::ops::AddAssign for Projective<C> {
+    fn add_assign(& mut self, other: Projective<C>) {
+        *self = *self + other;
+    }
+}
+
+///
+/// Points `L_i(tau) * G` of the Lagrange basis of `domain` from the points `tau^i * G`, by an inverse
+/// FFT in the exponent.
+///
+pub fn lagrange_basis<C: CurveParams, F: PrimeField>(powers: & [Affine<C>], domain: & Domain<F>) -> Vec<Affine<C>> {
+
+    let mut points: Vec<Projective<C>> = powers[..domain.size].iter().map(|point| point.to_projective()).collect();
+
+    domain::bit_reverse(& mut points, domain.log_size);
+
+    let mut half = 1;
+    while half < domain.size {
+
+        let step = domain.omega_inverse.pow(&U256::from_u64((domain.size / (2 * half)) as u64));
+        let twiddles = domain::powers(&step, half);
+
+        for start in (0..domain.size).step_by(2 * half) {
+            for (offset, twiddle) in twiddles.iter().enumerate() {
+                let even = points[start + offset];
+                let odd = points[start + offset + half].mul(&twiddle.to_u256());
+                points[start + offset] = even + odd;
+                points[start + offset + half] = even - odd;
+            }
+        }
+
+        half *= 2;
+
+    }
+
+    let size_inverse = domain.size_inverse.to_u256();
+
+    points.iter().map(|point| point.mul(&size_inverse).to_affine()).collect()
+
+}
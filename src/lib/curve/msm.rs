@@ -0,0 +1,368 @@
+//
+// Multi-scalar multiplication `sum(scalars[i] * bases[i])` with Pippenger's bucket method.
+//
+// Scalars are cut in windows of `c` bits. For each window, bases are added into the bucket of their
+// digit, and the buckets are summed with running sums so that bucket `d` counts `d` times. The
+// window sums are combined from the most significant one by doubling `c` times between windows,
+// which costs about `(256 / c) * (n + 2^c)` additions instead of `256 * n / 2` for double and add.
+//
+// Large inputs are split between the threads (see `parallel`), each summing its share.
+//
+
+use curve::{Affine, CurveParams, Projective};
+use field::bigint::U256;
+use field::PrimeField;
+use parallel;
+
+///
+/// Below this number of bases, double and add is faster than filling buckets.
+///
+const PIPPENGER_THRESHOLD: usize = 8;
+
+///
+/// Smallest number of bases handled by a thread.
+///
+const MIN_CHUNK: usize = 1 << 8;
+
+///
+/// Window size minimizing the number of additions, about `ln(count) + 2` bits.
+///
+fn window_size(count: usize) -> usize {
+    if count < 32 {
+        3
+    } else {
+        let log2 = (usize::BITS - count.leading_zeros()) as usize;
+        log2 * 69 / 100 + 2
+    }
+}
+
+///
+/// Bits `[start, start + width)` of a scalar, `width` being at most 64.
+///
+fn digit(scalar: & U256, start: usize, width: usize) -> usize {
+
+    let limb = start / 64;
+    let shift = start % 64;
+
+    let mut value = scalar.0[limb] >> shift;
+    if shift + width > 64 && limb + 1 < 4 {
+        value |= scalar.0[limb + 1] << (64 - shift);
+    }
+
+    (value & ((1 << width) - 1)) as usize
+
+}
+
+fn double_and_add<C: CurveParams>(bases: & [Affine<C>], scalars: & [U256]) -> Projective<C> {
+
+    let mut result = Projective::identity();
+
+    for (base, scalar) in bases.iter().zip(scalars.iter()) {
+        if !scalar.is_zero() && !base.infinity {
+            result += base.mul(scalar);
+        }
+    }
+
+    result
+
+}
+
+///
+/// Sum of the windows of `c` bits starting at `start` of the scalars times the bases.
+///
+fn window_sum<C: CurveParams>(bases: & [Affine<C>], scalars: & [U256], start: usize, c: usize) -> Projective<C> {
+
+    let mut buckets = vec!(Projective::<C>::identity(); (1 << c) - 1);
+
+    for (base, scalar) in bases.iter().zip(scalars.iter()) {
+        let digit = digit(scalar, start, c);
+        if digit != 0 && !base.infinity {
+            buckets[digit - 1] = buckets[digit - 1].add_affine(base);
+        }
+    }
+
+    let mut running = Projective::identity();
+    let mut sum = Projective::identity();
+
+    for bucket in buckets.iter().rev() {
+        running += *bucket;
+        sum += running;
+    }
+
+    sum
+
+}
+
+fn pippenger<C: CurveParams>(bases: & [Affine<C>], scalars: & [U256]) -> Projective<C> {
+
+    if bases.len() < PIPPENGER_THRESHOLD {
+        return double_and_add(bases, scalars);
+    }
+
+    let bits = scalars.iter().map(U256::bits).max().unwrap_or(0);
+    let c = window_size(bases.len());
+
+    let mut result = Projective::identity();
+
+    for start in (0..bits).step_by(c).rev() {
+        for _ in 0..c {
+            result = result.double();
+        }
+        result += window_sum(bases, scalars, start, c);
+    }
+
+    result
+
+}
+
+///
+/// `sum(scalars[i] * bases[i])`, extra bases or scalars being ignored.
+///
+pub fn multi_exp<C: CurveParams, F: PrimeField>(bases: & [Affine<C>], scalars: & [F]) -> Projective<C> {
+    multi_exp_with_threads(bases, scalars, parallel::threads())
+}
+
+///
+/// `multi_exp` split between `threads` threads rather than the ones of the provers.
+///
+pub fn multi_exp_with_threads<C: CurveParams, F: PrimeField>(bases: & [Affine<C>], scalars: & [F], threads: usize) -> Projective<C> {
+
+    let count = bases.len().min(scalars.len());
+    let scalars: Vec<U256> = scalars[..count].iter().map(|scalar| scalar.to_u256()).collect();
+
+    parallel::map_chunks(threads, &bases[..count], MIN_CHUNK, |offset, chunk| pippenger(chunk, &scalars[offset..offset + chunk.len()]))
+        .into_iter()
+        .fold(Projective::identity(), |sum, part| sum + part)
+
+}
+
+#[cfg(test)]
+mod msm_test {
+
+    use curve::{msm, Affine, Projective};
+    use curve::bn254::{Fq2, G1, G1Params, G2, G2Params};
+    use field::bigint::U256;
+    use field::bn254::{Fq, Fr};
+    use field::PrimeField;
+    use rng::{Rng, TestRng};
+
+    use galvanic_assert::matchers::*;
+
+    fn random_scalars(rng: & mut TestRng, count: usize) -> Vec<Fr> {
+        (0..count).map(|_| rng.field()).collect()
+    }
+
+    // Scalar multiplications of the Ethereum precompile (EIP-196), from the go-ethereum test vectors:
+    // the point, the scalar and the product as 32 bytes big endian words
+    const MULTIPLICATIONS: [(& str, [& str; 2], & str, [& str; 2]); 18] = [
+        (
+            "chfast1",
+            ["2bd3e6d0f3b142924f5ca7b49ce5b9d54c4703d7ae5648e61d02268b1a0a9fb7", "21611ce0a6af85915e2f1d70300909ce2e49dfad4a4619c8390cae66cefdb204"],
+            "00000000000000000000000000000000000000000000000011138ce750fa15c2",
+            ["070a8d6a982153cae4be29d434e8faef8a47b274a053f5a4ee2a6c9c13c31e5c", "031b8ce914eba3a9ffb989f9cdd5b0f01943074bf4f0f315690ec3cec6981afc"]
+        ),
+        (
+            "chfast2",
+            ["070a8d6a982153cae4be29d434e8faef8a47b274a053f5a4ee2a6c9c13c31e5c", "031b8ce914eba3a9ffb989f9cdd5b0f01943074bf4f0f315690ec3cec6981afc"],
+            "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd46",
+            ["025a6f4181d2b4ea8b724290ffb40156eb0adb514c688556eb79cdea0752c2bb", "2eff3f31dea215f1eb86023a133a996eb6300b44da664d64251d05381bb8a02e"]
+        ),
+        (
+            "chfast3",
+            ["025a6f4181d2b4ea8b724290ffb40156eb0adb514c688556eb79cdea0752c2bb", "2eff3f31dea215f1eb86023a133a996eb6300b44da664d64251d05381bb8a02e"],
+            "183227397098d014dc2822db40c0ac2ecbc0b548b438e5469e10460b6c3e7ea3",
+            ["14789d0d4a730b354403b5fac948113739e276c23e0258d8596ee72f9cd9d323", "0af18a63153e0ec25ff9f2951dd3fa90ed0197bfef6e2a1a62b5095b9d2b4a27"]
+        ),
+        (
+            "cdetrio1",
+            ["1a87b0584ce92f4593d161480614f2989035225609f08058ccfa3d0f940febe3", "1a2f3c951f6dadcc7ee9007dff81504b0fcd6d7cf59996efdc33d92bf7f9f8f6"],
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            ["2cde5879ba6f13c0b5aa4ef627f159a3347df9722efce88a9afbb20b763b4c41", "1aa7e43076f6aee272755a7f9b84832e71559ba0d2e0b17d5f9f01755e5b0d11"]
+        ),
+        (
+            "cdetrio2",
+            ["1a87b0584ce92f4593d161480614f2989035225609f08058ccfa3d0f940febe3", "1a2f3c951f6dadcc7ee9007dff81504b0fcd6d7cf59996efdc33d92bf7f9f8f6"],
+            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000",
+            ["1a87b0584ce92f4593d161480614f2989035225609f08058ccfa3d0f940febe3", "163511ddc1c3f25d396745388200081287b3fd1472d8339d5fecb2eae0830451"]
+        ),
+        (
+            "cdetrio3",
+            ["1a87b0584ce92f4593d161480614f2989035225609f08058ccfa3d0f940febe3", "1a2f3c951f6dadcc7ee9007dff81504b0fcd6d7cf59996efdc33d92bf7f9f8f6"],
+            "0000000000000000000000000000000100000000000000000000000000000000",
+            ["1051acb0700ec6d42a88215852d582efbaef31529b6fcbc3277b5c1b300f5cf0", "135b2394bb45ab04b8bd7611bd2dfe1de6a4e6e2ccea1ea1955f577cd66af85b"]
+        ),
+        (
+            "cdetrio4",
+            ["1a87b0584ce92f4593d161480614f2989035225609f08058ccfa3d0f940febe3", "1a2f3c951f6dadcc7ee9007dff81504b0fcd6d7cf59996efdc33d92bf7f9f8f6"],
+            "0000000000000000000000000000000000000000000000000000000000000009",
+            ["1dbad7d39dbc56379f78fac1bca147dc8e66de1b9d183c7b167351bfe0aeab74", "2cd757d51289cd8dbd0acf9e673ad67d0f0a89f912af47ed1be53664f5692575"]
+        ),
+        (
+            "cdetrio5",
+            ["1a87b0584ce92f4593d161480614f2989035225609f08058ccfa3d0f940febe3", "1a2f3c951f6dadcc7ee9007dff81504b0fcd6d7cf59996efdc33d92bf7f9f8f6"],
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            ["1a87b0584ce92f4593d161480614f2989035225609f08058ccfa3d0f940febe3", "1a2f3c951f6dadcc7ee9007dff81504b0fcd6d7cf59996efdc33d92bf7f9f8f6"]
+        ),
+        (
+            "cdetrio6",
+            ["17c139df0efee0f766bc0204762b774362e4ded88953a39ce849a8a7fa163fa9", "01e0559bacb160664764a357af8a9fe70baa9258e0b959273ffc5718c6d4cc7c"],
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            ["29e587aadd7c06722aabba753017c093f70ba7eb1f1c0104ec0564e7e3e21f60", "22b1143f6a41008e7755c71c3d00b6b915d386de21783ef590486d8afa8453b1"]
+        ),
+        (
+            "cdetrio7",
+            ["17c139df0efee0f766bc0204762b774362e4ded88953a39ce849a8a7fa163fa9", "01e0559bacb160664764a357af8a9fe70baa9258e0b959273ffc5718c6d4cc7c"],
+            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000",
+            ["17c139df0efee0f766bc0204762b774362e4ded88953a39ce849a8a7fa163fa9", "2e83f8d734803fc370eba25ed1f6b8768bd6d83887b87165fc2434fe11a830cb"]
+        ),
+        (
+            "cdetrio8",
+            ["17c139df0efee0f766bc0204762b774362e4ded88953a39ce849a8a7fa163fa9", "01e0559bacb160664764a357af8a9fe70baa9258e0b959273ffc5718c6d4cc7c"],
+            "0000000000000000000000000000000100000000000000000000000000000000",
+            ["221a3577763877920d0d14a91cd59b9479f83b87a653bb41f82a3f6f120cea7c", "2752c7f64cdd7f0e494bff7b60419f242210f2026ed2ec70f89f78a4c56a1f15"]
+        ),
+        (
+            "cdetrio9",
+            ["17c139df0efee0f766bc0204762b774362e4ded88953a39ce849a8a7fa163fa9", "01e0559bacb160664764a357af8a9fe70baa9258e0b959273ffc5718c6d4cc7c"],
+            "0000000000000000000000000000000000000000000000000000000000000009",
+            ["228e687a379ba154554040f8821f4e41ee2be287c201aa9c3bc02c9dd12f1e69", "1e0fd6ee672d04cfd924ed8fdc7ba5f2d06c53c1edc30f65f2af5a5b97f0a76a"]
+        ),
+        (
+            "cdetrio10",
+            ["17c139df0efee0f766bc0204762b774362e4ded88953a39ce849a8a7fa163fa9", "01e0559bacb160664764a357af8a9fe70baa9258e0b959273ffc5718c6d4cc7c"],
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            ["17c139df0efee0f766bc0204762b774362e4ded88953a39ce849a8a7fa163fa9", "01e0559bacb160664764a357af8a9fe70baa9258e0b959273ffc5718c6d4cc7c"]
+        ),
+        (
+            "cdetrio11",
+            ["039730ea8dff1254c0fee9c0ea777d29a9c710b7e616683f194f18c43b43b869", "073a5ffcc6fc7a28c30723d6e58ce577356982d65b833a5a5c15bf9024b43d98"],
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            ["00a1a234d08efaa2616607e31eca1980128b00b415c845ff25bba3afcb81dc00", "242077290ed33906aeb8e42fd98c41bcb9057ba03421af3f2d08cfc441186024"]
+        ),
+        (
+            "cdetrio12",
+            ["039730ea8dff1254c0fee9c0ea777d29a9c710b7e616683f194f18c43b43b869", "073a5ffcc6fc7a28c30723d6e58ce577356982d65b833a5a5c15bf9024b43d98"],
+            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000",
+            ["039730ea8dff1254c0fee9c0ea777d29a9c710b7e616683f194f18c43b43b869", "2929ee761a352600f54921df9bf472e66217e7bb0cee9032e00acc86b3c8bfaf"]
+        ),
+        (
+            "cdetrio13",
+            ["039730ea8dff1254c0fee9c0ea777d29a9c710b7e616683f194f18c43b43b869", "073a5ffcc6fc7a28c30723d6e58ce577356982d65b833a5a5c15bf9024b43d98"],
+            "0000000000000000000000000000000100000000000000000000000000000000",
+            ["1071b63011e8c222c5a771dfa03c2e11aac9666dd097f2c620852c3951a4376a", "2f46fe2f73e1cf310a168d56baa5575a8319389d7bfa6b29ee2d908305791434"]
+        ),
+        (
+            "cdetrio14",
+            ["039730ea8dff1254c0fee9c0ea777d29a9c710b7e616683f194f18c43b43b869", "073a5ffcc6fc7a28c30723d6e58ce577356982d65b833a5a5c15bf9024b43d98"],
+            "0000000000000000000000000000000000000000000000000000000000000009",
+            ["19f75b9dd68c080a688774a6213f131e3052bd353a304a189d7a2ee367e3c258", "2612f545fb9fc89fde80fd81c68fc7dcb27fea5fc124eeda69433cf5c46d2d7f"]
+        ),
+        (
+            "cdetrio15",
+            ["039730ea8dff1254c0fee9c0ea777d29a9c710b7e616683f194f18c43b43b869", "073a5ffcc6fc7a28c30723d6e58ce577356982d65b833a5a5c15bf9024b43d98"],
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            ["039730ea8dff1254c0fee9c0ea777d29a9c710b7e616683f194f18c43b43b869", "073a5ffcc6fc7a28c30723d6e58ce577356982d65b833a5a5c15bf9024b43d98"]
+        )
+    ];
+
+    fn precompile_point(words: & [& str; 2]) -> Affine<G1Params> {
+        let x = Fq::parse(&format!("0x{}", words[0])).unwrap();
+        let y = Fq::parse(&format!("0x{}", words[1])).unwrap();
+        Affine::new(x, y)
+    }
+
+    #[test]
+    fn test_digits() {
+
+        let scalar = U256([0xf000_0000_0000_000f, 0x1234, 0, 0x8000_0000_0000_0000]);
+
+        expect_that!(&msm::digit(&scalar, 0, 4), is(eq(0xf)));
+        expect_that!(&msm::digit(&scalar, 60, 8), is(eq(0x4f)));
+        expect_that!(&msm::digit(&scalar, 64, 16), is(eq(0x1234)));
+        expect_that!(&msm::digit(&scalar, 253, 5), is(eq(0x4)));
+
+    }
+
+    #[test]
+    fn test_multi_exp() {
+
+        let mut rng = TestRng::new(7);
+
+        for count in &[0, 1, 7, 8, 33, 100] {
+
+            let bases: Vec<Affine<G1Params>> = random_scalars(& mut rng, *count).iter().map(|scalar| G1::generator().mul_scalar(scalar).to_affine()).collect();
+            let scalars = random_scalars(& mut rng, *count);
+            let expected = bases.iter().zip(scalars.iter()).fold(Projective::identity(), |acc, (base, scalar)| acc + base.mul(&scalar.to_u256()));
+
+            expect_that!(&msm::multi_exp(&bases, &scalars), is(eq(expected)));
+
+        }
+
+        // sum(i * G) for i < 64, with the identity and the largest scalar among the inputs
+        let mut bases: Vec<Affine<G1Params>> = vec!(G1::generator().to_affine(); 64);
+        let mut scalars: Vec<Fr> = (0..64).map(Fr::from_u64).collect();
+        bases[10] = Affine::identity();
+        scalars[20] = -Fr::one();
+
+        let expected = G1::generator().mul_scalar(&Fr::from_u64(63 * 64 / 2 - 10 - 20 - 1));
+        expect_that!(&msm::multi_exp(&bases, &scalars), is(eq(expected)));
+
+        let bases: Vec<Affine<G2Params>> = random_scalars(& mut rng, 20).iter().map(|scalar| G2::generator().mul_scalar(scalar).to_affine()).collect();
+        let scalars = random_scalars(& mut rng, 20);
+        let expected = bases.iter().zip(scalars.iter()).fold(Projective::identity(), |acc, (base, scalar)| acc + base.mul(&scalar.to_u256()));
+
+        expect_that!(&msm::multi_exp(&bases, &scalars), is(eq(expected)));
+
+    }
+
+
+    #[test]
+    fn test_known_vectors() {
+
+        let mut bases = Vec::new();
+        let mut scalars = Vec::new();
+        let mut expected = Projective::identity();
+
+        for (name, point, scalar, product) in MULTIPLICATIONS.iter() {
+
+            let base = precompile_point(point);
+            let scalar = U256::parse(&format!("0x{}", scalar)).unwrap();
+            let product = precompile_point(product);
+
+            expect_that!(&(name, base.mul(&scalar).to_affine()), is(eq((name, product))));
+            expect_that!(&(name, msm::multi_exp(&[base], &[Fr::from_u256(&scalar)]).to_affine()), is(eq((name, product))));
+
+            bases.push(base);
+            scalars.push(Fr::from_u256(&scalar));
+            expected = expected.add_affine(&product);
+
+        }
+
+        expect_that!(&msm::multi_exp(&bases, &scalars), is(eq(expected)));
+
+        // sum(-(1 + 1000003 * i^2) * (i + 1) * G2) for i < 10, as computed by arkworks (ark-bn254 0.4)
+        let bases: Vec<Affine<G2Params>> = (0..10).map(|idx| G2::generator().mul(&U256::from_u64(idx + 1)).to_affine()).collect();
+        let scalars: Vec<Fr> = (0..10).map(|idx| -Fr::from_u64(1 + idx * idx * 1000003)).collect();
+        let fq2 = |c0, c1| Fq2::new(Fq::parse(c0).unwrap(), Fq::parse(c1).unwrap());
+
+        expect_that!(&msm::multi_exp(&bases, &scalars).to_affine(), is(eq(Affine::new(
+            fq2("8236776096061490702795818800306174796286275963257190737478701345660395929836", "19376747289027482814844166024910647411522108087430393450530833712963010557138"),
+            fq2("16168607501746924518120738070421870447345195910542174523954702975042539626779", "17965566991396979705675689150040398273039926714637798017717197804480012918087")
+        ))));
+
+    }
+
+    #[test]
+    fn test_parallel_multi_exp() {
+
+        let mut rng = TestRng::new(8);
+        let bases: Vec<Affine<G1Params>> = (0..1000).map(|idx| G1::generator().mul(&U256::from_u64(idx + 1)).to_affine()).collect();
+        let scalars = random_scalars(& mut rng, 1000);
+        let expected = bases.iter().zip(scalars.iter()).fold(Projective::identity(), |acc, (base, scalar)| acc + base.mul(&scalar.to_u256()));
+
+        expect_that!(&msm::multi_exp_with_threads(&bases, &scalars, 3), is(eq(expected)));
+        expect_that!(&msm::multi_exp_with_threads(&bases, &scalars, 1), is(eq(expected)));
+
+    }
+
+}
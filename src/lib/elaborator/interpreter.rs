@@ -0,0 +1,879 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use field::PrimeField;
+use parser::typed_ast::*;
+use parser::errors::elaboration::ElaborationError;
+use elaborator::ir::{Ir, Value};
+use elaborator::{Signal, Component, SignalTree, Assignment, ConstraintCheck};
+
+type Result<T> = std::result::Result<T, ElaborationError>;
+
+///
+/// Maximum depth of nested function calls and template instantiations.
+///
+const MAX_DEPTH: usize = 256;
+
+enum Flow<F> {
+    Next,
+    Break,
+    Continue,
+    Return(Value<F>)
+}
+
+enum Accessor {
+    Index(usize),
+    Member(std::string::String)
+}
+
+#[derive(Debug, Clone)]
+enum ComponentSlot {
+    Instance(Option<usize>),
+    Array(Vec<ComponentSlot>)
+}
+
+impl ComponentSlot {
+
+    fn new(dimensions: & [usize]) -> ComponentSlot {
+        match dimensions.split_first() {
+            None => ComponentSlot::Instance(None),
+            Some((size, rest)) => ComponentSlot::Array((0..*size).map(|_| ComponentSlot::new(rest)).collect())
+        }
+    }
+
+    fn get_mut(& mut self, indices: & [usize]) -> Option<& mut ComponentSlot> {
+        match indices.split_first() {
+            None => Some(self),
+            Some((index, rest)) => match self {
+                ComponentSlot::Array(slots) => slots.get_mut(*index).and_then(|slot| slot.get_mut(rest)),
+                ComponentSlot::Instance(_) => None
+            }
+        }
+    }
+
+}
+
+///
+/// Lists all the index combinations of an array with the given dimensions.
+///
+fn index_combinations(dimensions: & [usize]) -> Vec<Vec<usize>> {
+    match dimensions.split_first() {
+        None => vec!(Vec::new()),
+        Some((size, rest)) => {
+            let tails = index_combinations(rest);
+            (0..*size).flat_map(|index| tails.iter().map(move |tail| {
+                let mut combination = vec!(index);
+                combination.extend_from_slice(tail);
+                combination
+            })).collect()
+        }
+    }
+}
+
+fn value_at<'v, F>(value: &'v Value<F>, indices: & [usize]) -> Option<&'v Value<F>> {
+    match indices.split_first() {
+        None => Some(value),
+        Some((index, rest)) => match value {
+            Value::Array(values) => values.get(*index).and_then(|value| value_at(value, rest)),
+            Value::Scalar(_) => None
+        }
+    }
+}
+
+fn value_at_mut<'v, F>(value: &'v mut Value<F>, indices: & [usize]) -> Option<&'v mut Value<F>> {
+    match indices.split_first() {
+        None => Some(value),
+        Some((index, rest)) => match value {
+            Value::Array(values) => values.get_mut(*index).and_then(|value| value_at_mut(value, rest)),
+            Value::Scalar(_) => None
+        }
+    }
+}
+
+fn binary_of_assignment(operator: AssignmentOperator) -> Option<BinaryOperator> {
+    match operator {
+        AssignmentOperator::Assign => None,
+        AssignmentOperator::Add => Some(BinaryOperator::Add),
+        AssignmentOperator::Sub => Some(BinaryOperator::Sub),
+        AssignmentOperator::Mul => Some(BinaryOperator::Mul),
+        AssignmentOperator::Div => Some(BinaryOperator::Div),
+        AssignmentOperator::Mod => Some(BinaryOperator::Mod),
+        AssignmentOperator::ShiftLeft => Some(BinaryOperator::ShiftLeft),
+        AssignmentOperator::ShiftRight => Some(BinaryOperator::ShiftRight),
+        AssignmentOperator::BitAnd => Some(BinaryOperator::BitAnd),
+        AssignmentOperator::BitXor => Some(BinaryOperator::BitXor),
+        AssignmentOperator::BitOr => Some(BinaryOperator::BitOr)
+    }
+}
+
+///
+/// Execution frame of a template instance or a function call.
+///
+struct Frame<'a, F> {
+    component: Option<usize>,
+    file: &'a Path,
+    variables: HashMap<std::string::String, Value<F>>,
+    components: HashMap<std::string::String, ComponentSlot>
+}
+
+impl<'a, F> Frame<'a, F> {
+
+    fn new(component: Option<usize>, file: &'a Path, parameters: & [std::string::String], arguments: Vec<Value<F>>) -> Frame<'a, F> {
+        Frame {
+            component,
+            file,
+            variables: parameters.iter().cloned().zip(arguments).collect(),
+            components: HashMap::new()
+        }
+    }
+
+}
+
+///
+/// Symbolic interpreter: runs templates once their parameters are known, creating signals and
+/// components, and recording signal assignments and constraints as `Ir` expressions of the signals.
+/// Functions are run with the values they are called with, or kept as residual calls when their
+/// arguments depend on signals.
+///
+pub struct Interpreter<'a, F> {
+    templates: &'a HashMap<std::string::String, (Template, PathBuf)>,
+    functions: &'a HashMap<std::string::String, (Function, PathBuf)>,
+    sources: &'a HashMap<PathBuf, std::string::String>,
+    depth: usize,
+    assigned: Vec<bool>,
+
+    pub signals: Vec<Signal>,
+    pub components: Vec<Component>,
+    pub assignments: Vec<Assignment<F>>,
+    pub constraints: Vec<ConstraintCheck<F>>
+}
+
+impl<'a, F: PrimeField> Interpreter<'a, F> {
+
+    pub fn new(
+        templates: &'a HashMap<std::string::String, (Template, PathBuf)>,
+        functions: &'a HashMap<std::string::String, (Function, PathBuf)>,
+        sources: &'a HashMap<PathBuf, std::string::String>
+    ) -> Interpreter<'a, F> {
+        Interpreter {
+            templates,
+            functions,
+            sources,
+            depth: 0,
+            assigned: Vec::new(),
+            signals: Vec::new(),
+            components: Vec::new(),
+            assignments: Vec::new(),
+            constraints: Vec::new()
+        }
+    }
+
+    fn error<S: Into<std::string::String>>(&self, file: & Path, span: Span, reason: S) -> ElaborationError {
+        ElaborationError {
+            file_error: file.to_path_buf(),
+            source_error: self.sources.get(file)
+                .and_then(|source| source.get(span.0..span.1))
+                .unwrap_or("")
+                .to_string(),
+            span_error: span,
+            reason: reason.into()
+        }
+    }
+
+    ///
+    /// Instantiates the main component from its `component main = T(args)` declaration.
+    ///
+    pub fn instantiate_main(& mut self, declaration: & Declaration, file: &'a Path) -> Result<usize> {
+
+        let mut frame: Frame<F> = Frame::new(None, file, &[], Vec::new());
+
+        match (&declaration.value, &declaration.constructor) {
+            (Some(value), None) | (None, Some(value)) => {
+                frame.components.insert(declaration.name.clone(), ComponentSlot::new(&[]));
+                self.instantiate_into(& mut frame, &declaration.name, &[], value, declaration.span)
+            },
+            _ => Err(self.error(file, declaration.span, "The main component must be instantiated where it is declared"))
+        }
+
+    }
+
+    ///
+    /// Runs a function with known arguments.
+    ///
+    pub fn run_function(& mut self, name: & str, arguments: Vec<Value<F>>, span: Span, file: & Path) -> Result<Value<F>> {
+
+        let functions = self.functions;
+        let (function, function_file) = match functions.get(name) {
+            Some(function) => function,
+            None => return Err(self.error(file, span, format!("Undefined function {}", name)))
+        };
+
+        if function.parameters.len() != arguments.len() {
+            return Err(self.error(file, span, format!("Function {} expects {} arguments, {} given", name, function.parameters.len(), arguments.len())));
+        }
+
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error(file, span, "Maximum call depth exceeded"));
+        }
+
+        self.depth += 1;
+
+        let mut frame = Frame::new(None, function_file, &function.parameters, arguments);
+        let mut result = Err(self.error(function_file, function.span, format!("Function {} ends without returning a value", name)));
+
+        for statement in &function.body {
+            match self.execute(& mut frame, statement) {
+                Ok(Flow::Next) => {},
+                Ok(Flow::Return(value)) => {
+                    result = Ok(value);
+                    break ;
+                },
+                Ok(_) => {
+                    result = Err(self.error(function_file, statement.span(), "break and continue are only allowed in loops"));
+                    break ;
+                },
+                Err(error) => {
+                    result = Err(error);
+                    break ;
+                }
+            }
+        }
+
+        self.depth -= 1;
+
+        result
+
+    }
+
+    fn instantiate(& mut self, name: & str, arguments: Vec<Value<F>>, path: std::string::String, parent: Option<usize>, span: Span, file: & Path) -> Result<usize> {
+
+        let templates = self.templates;
+        let (template, template_file) = match templates.get(name) {
+            Some(template) => template,
+            None => return Err(self.error(file, span, format!("Undefined template {}", name)))
+        };
+
+        if template.parameters.len() != arguments.len() {
+            return Err(self.error(file, span, format!("Template {} expects {} arguments, {} given", name, template.parameters.len(), arguments.len())));
+        }
+
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error(file, span, "Maximum component depth exceeded"));
+        }
+
+        let id = self.components.len();
+
+        self.components.push(Component {
+            name: path,
+            template: name.to_string(),
+            file: template_file.clone(),
+            parent,
+            signals: Vec::new()
+        });
+
+        self.depth += 1;
+
+        let mut frame = Frame::new(Some(id), template_file, &template.parameters, arguments);
+
+        for statement in &template.body {
+            match self.execute(& mut frame, statement)? {
+                Flow::Next => {},
+                Flow::Return(_) => return Err(self.error(template_file, statement.span(), "Templates cannot return a value")),
+                _ => return Err(self.error(template_file, statement.span(), "break and continue are only allowed in loops"))
+            }
+        }
+
+        self.depth -= 1;
+
+        Ok(id)
+
+    }
+
+    ///
+    /// Instantiates the template called by `value` into the component slot `name[indices]`.
+    ///
+    fn instantiate_into(& mut self, frame: & mut Frame<'a, F>, name: & str, indices: & [usize], value: & Expression, span: Span) -> Result<usize> {
+
+        let (template, arguments) = match value {
+            Expression::Call { callee, arguments, .. } => match &**callee {
+                Expression::Variable { name, .. } if self.templates.contains_key(name) => (name, arguments),
+                Expression::Variable { name, .. } => return Err(self.error(frame.file, value.span(), format!("Undefined template {}", name))),
+                _ => return Err(self.error(frame.file, value.span(), "Components must be assigned a template instance"))
+            },
+            _ => return Err(self.error(frame.file, value.span(), "Components must be assigned a template instance"))
+        };
+
+        let mut values: Vec<Value<F>> = Vec::new();
+        for argument in arguments {
+            let argument_value = self.evaluate(frame, argument)?;
+            if !argument_value.is_constant() {
+                return Err(self.error(frame.file, argument.span(), "Template parameters must be known at compile time"));
+            }
+            values.push(argument_value);
+        }
+
+        match frame.components.get_mut(name).and_then(|slot| slot.get_mut(indices)) {
+            Some(ComponentSlot::Instance(None)) => {},
+            Some(ComponentSlot::Instance(Some(_))) => return Err(self.error(frame.file, span, format!("Component {} is instantiated twice", name))),
+            _ => return Err(self.error(frame.file, span, format!("Invalid access to component {}", name)))
+        }
+
+        let mut path = match frame.component {
+            Some(parent) => format!("{}.{}", self.components[parent].name, name),
+            None => name.to_string()
+        };
+        for index in indices {
+            path.push_str(&format!("[{}]", index));
+        }
+
+        let id = self.instantiate(template, values, path, frame.component, span, frame.file)?;
+
+        if let Some(slot) = frame.components.get_mut(name).and_then(|slot| slot.get_mut(indices)) {
+            *slot = ComponentSlot::Instance(Some(id));
+        }
+
+        Ok(id)
+
+    }
+
+    fn declare_signals(& mut self, component: usize, name: std::string::String, dimensions: & [usize], direction: SignalDirection, private: bool, span: Span) -> SignalTree {
+        match dimensions.split_first() {
+            None => {
+                self.signals.push(Signal {
+                    name,
+                    component,
+                    direction,
+                    private,
+                    span
+                });
+                self.assigned.push(false);
+                SignalTree::Signal(self.signals.len() - 1)
+            },
+            Some((size, rest)) => SignalTree::Array(
+                (0..*size).map(|index| self.declare_signals(component, format!("{}[{}]", name, index), rest, direction, private, span)).collect()
+            )
+        }
+    }
+
+    fn declare(& mut self, frame: & mut Frame<'a, F>, declaration: & Declaration) -> Result<()> {
+
+        let mut dimensions: Vec<usize> = Vec::new();
+        for dimension in &declaration.dimensions {
+            dimensions.push(self.evaluate_index(frame, dimension)?);
+        }
+
+        match declaration.kind {
+
+            DeclarationKind::Variable => {
+                let value = match &declaration.value {
+                    Some(value) => self.evaluate(frame, value)?,
+                    None => Value::zeros(&dimensions)
+                };
+                frame.variables.insert(declaration.name.clone(), value);
+            },
+
+            DeclarationKind::Signal { private, direction } => {
+                let component = match frame.component {
+                    Some(component) => component,
+                    None => return Err(self.error(frame.file, declaration.span, "Signals can only be declared in templates"))
+                };
+                if declaration.value.is_some() {
+                    return Err(self.error(frame.file, declaration.span, "Signals cannot be initialized at declaration"));
+                }
+                if self.components[component].signal(&declaration.name).is_some() {
+                    return Err(self.error(frame.file, declaration.span, format!("Signal {} is declared twice", declaration.name)));
+                }
+                let name = format!("{}.{}", self.components[component].name, declaration.name);
+                let tree = self.declare_signals(component, name, &dimensions, direction, private, declaration.span);
+                self.components[component].signals.push((declaration.name.clone(), tree));
+            },
+
+            DeclarationKind::Component => {
+                if frame.component.is_none() {
+                    return Err(self.error(frame.file, declaration.span, "Components can only be declared in templates"));
+                }
+                frame.components.insert(declaration.name.clone(), ComponentSlot::new(&dimensions));
+                if let Some(value) = &declaration.value {
+                    self.instantiate_into(frame, &declaration.name, &[], value, declaration.span)?;
+                }
+                if let Some(constructor) = &declaration.constructor {
+                    for indices in index_combinations(&dimensions) {
+                        self.instantiate_into(frame, &declaration.name, &indices, constructor, declaration.span)?;
+                    }
+                }
+            }
+
+        }
+
+        Ok(())
+
+    }
+
+    fn execute(& mut self, frame: & mut Frame<'a, F>, statement: & Statement) -> Result<Flow<F>> {
+
+        match statement {
+
+            Statement::Declaration(declaration) => {
+                self.declare(frame, declaration)?;
+                Ok(Flow::Next)
+            },
+
+            Statement::Expression { expression, .. } => {
+                self.evaluate(frame, expression)?;
+                Ok(Flow::Next)
+            },
+
+            Statement::Block { statements, .. } => {
+                for statement in statements {
+                    match self.execute(frame, statement)? {
+                        Flow::Next => {},
+                        flow => return Ok(flow)
+                    }
+                }
+                Ok(Flow::Next)
+            },
+
+            Statement::If { condition, if_true, if_false, .. } => {
+                if self.evaluate_condition(frame, condition)? {
+                    self.execute(frame, if_true)
+                } else if let Some(if_false) = if_false {
+                    self.execute(frame, if_false)
+                } else {
+                    Ok(Flow::Next)
+                }
+            },
+
+            Statement::While { condition, body, .. } => {
+                while self.evaluate_condition(frame, condition)? {
+                    match self.execute(frame, body)? {
+                        Flow::Break => break ,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                }
+                Ok(Flow::Next)
+            },
+
+            Statement::DoWhile { body, condition, .. } => {
+                loop {
+                    match self.execute(frame, body)? {
+                        Flow::Break => break ,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                    if !self.evaluate_condition(frame, condition)? {
+                        break ;
+                    }
+                }
+                Ok(Flow::Next)
+            },
+
+            Statement::For { init, condition, step, body, .. } => {
+                if let Some(init) = init {
+                    self.execute(frame, init)?;
+                }
+                loop {
+                    if let Some(condition) = condition {
+                        if !self.evaluate_condition(frame, condition)? {
+                            break ;
+                        }
+                    }
+                    match self.execute(frame, body)? {
+                        Flow::Break => break ,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                    if let Some(step) = step {
+                        self.evaluate(frame, step)?;
+                    }
+                }
+                Ok(Flow::Next)
+            },
+
+            Statement::Return { value, .. } => Ok(Flow::Return(self.evaluate(frame, value)?)),
+            Statement::Break { .. } => Ok(Flow::Break),
+            Statement::Continue { .. } => Ok(Flow::Continue)
+
+        }
+
+    }
+
+    fn access_path(& mut self, frame: & mut Frame<'a, F>, expression: & Expression) -> Result<(std::string::String, Vec<Accessor>)> {
+        match expression {
+            Expression::Variable { name, .. } => Ok((name.clone(), Vec::new())),
+            Expression::Index { target, index, .. } => {
+                let (name, mut accessors) = self.access_path(frame, target)?;
+                accessors.push(Accessor::Index(self.evaluate_index(frame, index)?));
+                Ok((name, accessors))
+            },
+            Expression::Member { target, member, .. } => {
+                let (name, mut accessors) = self.access_path(frame, target)?;
+                accessors.push(Accessor::Member(member.clone()));
+                Ok((name, accessors))
+            },
+            _ => Err(self.error(frame.file, expression.span(), "Expected a variable, a signal or a component"))
+        }
+    }
+
+    fn indices_of(&self, frame: & Frame<'a, F>, accessors: & [Accessor], span: Span) -> Result<Vec<usize>> {
+        accessors.iter()
+            .map(|accessor| match accessor {
+                Accessor::Index(index) => Ok(*index),
+                Accessor::Member(member) => Err(self.error(frame.file, span, format!("Invalid member access .{}", member)))
+            })
+            .collect()
+    }
+
+    fn read(&self, frame: & Frame<'a, F>, name: & str, accessors: & [Accessor], span: Span) -> Result<Value<F>> {
+
+        if let Some(value) = frame.variables.get(name) {
+            let indices = self.indices_of(frame, accessors, span)?;
+            return match value_at(value, &indices) {
+                Some(value) => Ok(value.clone()),
+                None => Err(self.error(frame.file, span, format!("Invalid access to variable {}", name)))
+            };
+        }
+
+        if let Some(component) = frame.component {
+            if let Some(tree) = self.components[component].signal(name) {
+                let indices = self.indices_of(frame, accessors, span)?;
+                return match tree.get(&indices) {
+                    Some(tree) => Ok(tree.to_value()),
+                    None => Err(self.error(frame.file, span, format!("Invalid access to signal {}", name)))
+                };
+            }
+        }
+
+        if let Some(slot) = frame.components.get(name) {
+
+            let mut slot = slot;
+            let mut position = 0;
+
+            while let (ComponentSlot::Array(slots), Some(Accessor::Index(index))) = (slot, accessors.get(position)) {
+                slot = match slots.get(*index) {
+                    Some(slot) => slot,
+                    None => return Err(self.error(frame.file, span, format!("Invalid access to component {}", name)))
+                };
+                position += 1;
+            }
+
+            let component = match slot {
+                ComponentSlot::Instance(Some(component)) => *component,
+                ComponentSlot::Instance(None) => return Err(self.error(frame.file, span, format!("Component {} is used before being instantiated", name))),
+                ComponentSlot::Array(_) => return Err(self.error(frame.file, span, format!("Invalid access to component {}", name)))
+            };
+
+            let member = match accessors.get(position) {
+                Some(Accessor::Member(member)) => member,
+                _ => return Err(self.error(frame.file, span, format!("Components can only be accessed through their signals, {}", name)))
+            };
+
+            let indices = self.indices_of(frame, &accessors[position + 1..], span)?;
+
+            return match self.components[component].signal(member).and_then(|tree| tree.get(&indices)) {
+                Some(tree) => Ok(tree.to_value()),
+                None => Err(self.error(frame.file, span, format!("Invalid access to signal {}.{}", name, member)))
+            };
+
+        }
+
+        Err(self.error(frame.file, span, format!("Undefined symbol {}", name)))
+
+    }
+
+    fn write_variable(&self, frame: & mut Frame<'a, F>, name: & str, accessors: & [Accessor], value: Value<F>, span: Span) -> Result<()> {
+
+        let indices = self.indices_of(frame, accessors, span)?;
+
+        match frame.variables.get_mut(name).and_then(|variable| value_at_mut(variable, &indices)) {
+            Some(slot) => {
+                *slot = value;
+                Ok(())
+            },
+            None => Err(self.error(frame.file, span, format!("Invalid access to variable {}", name)))
+        }
+
+    }
+
+    fn evaluate_scalar(& mut self, frame: & mut Frame<'a, F>, expression: & Expression) -> Result<Ir<F>> {
+        match self.evaluate(frame, expression)? {
+            Value::Scalar(ir) => Ok(ir),
+            Value::Array(_) => Err(self.error(frame.file, expression.span(), "Expected a single value, found an array"))
+        }
+    }
+
+    fn evaluate_constant(& mut self, frame: & mut Frame<'a, F>, expression: & Expression) -> Result<F> {
+        match self.evaluate_scalar(frame, expression)?.as_constant() {
+            Some(value) => Ok(value),
+            None => Err(self.error(frame.file, expression.span(), "Expected a value known at compile time, found an expression depending on signals"))
+        }
+    }
+
+    fn evaluate_condition(& mut self, frame: & mut Frame<'a, F>, expression: & Expression) -> Result<bool> {
+        Ok(!self.evaluate_constant(frame, expression)?.is_zero())
+    }
+
+    fn evaluate_index(& mut self, frame: & mut Frame<'a, F>, expression: & Expression) -> Result<usize> {
+
+        let value = self.evaluate_constant(frame, expression)?.to_u256();
+
+        if value.bits() > 32 {
+            return Err(self.error(frame.file, expression.span(), format!("Invalid index or size {}", value)));
+        }
+
+        Ok(value.0[0] as usize)
+
+    }
+
+    ///
+    /// Applies `operator` to a variable and `operand`. Returns the previous and the new value.
+    ///
+    fn update(& mut self, frame: & mut Frame<'a, F>, target: & Expression, operator: BinaryOperator, operand: Ir<F>, span: Span) -> Result<(Value<F>, Value<F>)> {
+
+        let (name, accessors) = self.access_path(frame, target)?;
+
+        if !frame.variables.contains_key(&name) {
+            return Err(self.error(frame.file, span, format!("Only variables can be modified with this operator, {} is not a variable", name)));
+        }
+
+        let previous = match self.read(frame, &name, &accessors, span)? {
+            Value::Scalar(ir) => ir,
+            Value::Array(_) => return Err(self.error(frame.file, span, "Expected a single value, found an array"))
+        };
+
+        let value = match Ir::binary(operator, previous.clone(), operand) {
+            Ok(value) => Value::Scalar(value),
+            Err(reason) => return Err(self.error(frame.file, span, reason))
+        };
+
+        self.write_variable(frame, &name, &accessors, value.clone(), span)?;
+
+        Ok((Value::Scalar(previous), value))
+
+    }
+
+    fn assign(& mut self, frame: & mut Frame<'a, F>, operator: AssignmentOperator, target: & Expression, value: & Expression, span: Span) -> Result<Value<F>> {
+
+        let (name, accessors) = self.access_path(frame, target)?;
+
+        if frame.components.contains_key(&name) && !frame.variables.contains_key(&name) {
+            if operator != AssignmentOperator::Assign {
+                return Err(self.error(frame.file, span, "Components can only be assigned with ="));
+            }
+            let indices = self.indices_of(frame, &accessors, span)?;
+            self.instantiate_into(frame, &name, &indices, value, span)?;
+            return Ok(Value::constant(F::zero()));
+        }
+
+        if !frame.variables.contains_key(&name) {
+            return Err(match self.read(frame, &name, &accessors, span) {
+                Ok(_) => self.error(frame.file, span, format!("{} is a signal, signals are assigned with <== or <--", name)),
+                Err(error) => error
+            });
+        }
+
+        let value = match binary_of_assignment(operator) {
+            None => self.evaluate(frame, value)?,
+            Some(operator) => {
+                let operand = self.evaluate_scalar(frame, value)?;
+                return self.update(frame, target, operator, operand, span).map(|(_, value)| value);
+            }
+        };
+
+        self.write_variable(frame, &name, &accessors, value.clone(), span)?;
+
+        Ok(value)
+
+    }
+
+    fn assign_signal(& mut self, frame: & mut Frame<'a, F>, operator: SignalOperator, target: & Expression, value: & Expression, span: Span) -> Result<Value<F>> {
+
+        let component = match frame.component {
+            Some(component) => component,
+            None => return Err(self.error(frame.file, span, "Signals can only be assigned in templates"))
+        };
+
+        let (name, accessors) = self.access_path(frame, target)?;
+        let signal = match self.read(frame, &name, &accessors, span)? {
+            Value::Scalar(Ir::Signal(signal)) if !frame.variables.contains_key(&name) => signal,
+            _ => return Err(self.error(frame.file, target.span(), "Expected a single signal"))
+        };
+
+        let value = self.evaluate_scalar(frame, value)?;
+
+        let owner = self.signals[signal].component;
+        let direction = self.signals[signal].direction;
+
+        if owner == component && direction == SignalDirection::Input {
+            return Err(self.error(frame.file, span, format!("Input signal {} cannot be assigned from its own template", self.signals[signal].name)));
+        }
+
+        if owner != component && (direction != SignalDirection::Input || self.components[owner].parent != Some(component)) {
+            return Err(self.error(frame.file, span, format!("Signal {} can only be assigned by its component", self.signals[signal].name)));
+        }
+
+        if self.assigned[signal] {
+            return Err(self.error(frame.file, span, format!("Signal {} is assigned twice", self.signals[signal].name)));
+        }
+
+        self.assigned[signal] = true;
+
+        let constrained = operator == SignalOperator::ConstrainedAssign;
+
+        self.assignments.push(Assignment {
+            signal,
+            value: value.clone(),
+            constrained,
+            component,
+            span
+        });
+
+        if constrained {
+            self.constraints.push(ConstraintCheck {
+                left: Ir::Signal(signal),
+                right: value,
+                component,
+                span
+            });
+        }
+
+        Ok(Value::Scalar(Ir::Signal(signal)))
+
+    }
+
+    fn call(& mut self, frame: & mut Frame<'a, F>, callee: & Expression, arguments: & [Expression], span: Span) -> Result<Value<F>> {
+
+        let name = match callee {
+            Expression::Variable { name, .. } => name,
+            _ => return Err(self.error(frame.file, callee.span(), "Invalid function call"))
+        };
+
+        if !self.functions.contains_key(name) {
+            return Err(self.error(frame.file, span, match self.templates.contains_key(name) {
+                true => format!("Template {} can only be instantiated by a component", name),
+                false => format!("Undefined function {}", name)
+            }));
+        }
+
+        let mut values: Vec<Value<F>> = Vec::new();
+        for argument in arguments {
+            values.push(self.evaluate(frame, argument)?);
+        }
+
+        if values.iter().all(Value::is_constant) {
+            self.run_function(name, values, span, frame.file)
+        } else {
+            Ok(Value::Scalar(Ir::Call(name.clone(), Rc::new(values))))
+        }
+
+    }
+
+    fn evaluate(& mut self, frame: & mut Frame<'a, F>, expression: & Expression) -> Result<Value<F>> {
+
+        let span = expression.span();
+
+        match expression {
+
+            Expression::Number { value, .. } => Ok(Value::constant(F::from_u256(value))),
+
+            Expression::Variable { .. } | Expression::Index { .. } | Expression::Member { .. } => {
+                let (name, accessors) = self.access_path(frame, expression)?;
+                self.read(frame, &name, &accessors, span)
+            },
+
+            Expression::Array { elements, .. } => {
+                let mut values: Vec<Value<F>> = Vec::new();
+                for element in elements {
+                    values.push(self.evaluate(frame, element)?);
+                }
+                Ok(Value::Array(values))
+            },
+
+            Expression::Prefix { operator: PrefixOperator::Increment, operand, .. } =>
+                self.update(frame, operand, BinaryOperator::Add, Ir::Constant(F::one()), span).map(|(_, value)| value),
+
+            Expression::Prefix { operator: PrefixOperator::Decrement, operand, .. } =>
+                self.update(frame, operand, BinaryOperator::Sub, Ir::Constant(F::one()), span).map(|(_, value)| value),
+
+            Expression::Prefix { operator, operand, .. } => {
+                let operand = self.evaluate_scalar(frame, operand)?;
+                Ir::prefix(*operator, operand)
+                    .map(Value::Scalar)
+                    .map_err(|reason| self.error(frame.file, span, reason))
+            },
+
+            Expression::Postfix { operator, operand, .. } => {
+                let operator = match operator {
+                    PostfixOperator::Increment => BinaryOperator::Add,
+                    PostfixOperator::Decrement => BinaryOperator::Sub
+                };
+                self.update(frame, operand, operator, Ir::Constant(F::one()), span).map(|(previous, _)| previous)
+            },
+
+            Expression::Binary { operator, left, right, .. } => {
+                let left = self.evaluate_scalar(frame, left)?;
+                let short_circuit = match (operator, left.as_constant()) {
+                    (BinaryOperator::And, Some(value)) if value.is_zero() => Some(F::zero()),
+                    (BinaryOperator::Or, Some(value)) if !value.is_zero() => Some(F::one()),
+                    _ => None
+                };
+                if let Some(value) = short_circuit {
+                    return Ok(Value::constant(value));
+                }
+                let right = self.evaluate_scalar(frame, right)?;
+                Ir::binary(*operator, left, right)
+                    .map(Value::Scalar)
+                    .map_err(|reason| self.error(frame.file, span, reason))
+            },
+
+            Expression::Ternary { condition, if_true, if_false, .. } => {
+                match self.evaluate_scalar(frame, condition)? {
+                    Ir::Constant(condition) => if condition.is_zero() {
+                        self.evaluate(frame, if_false)
+                    } else {
+                        self.evaluate(frame, if_true)
+                    },
+                    condition => {
+                        let if_true = self.evaluate_scalar(frame, if_true)?;
+                        let if_false = self.evaluate_scalar(frame, if_false)?;
+                        Ok(Value::Scalar(Ir::ternary(condition, if_true, if_false)))
+                    }
+                }
+            },
+
+            Expression::Assignment { operator, target, value, .. } => self.assign(frame, *operator, target, value, span),
+
+            Expression::SignalAssignment { operator, target, value, .. } => self.assign_signal(frame, *operator, target, value, span),
+
+            Expression::Constraint { left, right, .. } => {
+                let component = match frame.component {
+                    Some(component) => component,
+                    None => return Err(self.error(frame.file, span, "Constraints can only be declared in templates"))
+                };
+                let left = self.evaluate_scalar(frame, left)?;
+                let right = self.evaluate_scalar(frame, right)?;
+                self.constraints.push(ConstraintCheck {
+                    left: left.clone(),
+                    right,
+                    component,
+                    span
+                });
+                Ok(Value::Scalar(left))
+            },
+
+            Expression::Sequence { expressions, .. } => {
+                let mut result = Value::constant(F::zero());
+                for expression in expressions {
+                    result = self.evaluate(frame, expression)?;
+                }
+                Ok(result)
+            },
+
+            Expression::Call { callee, arguments, .. } => self.call(frame, callee, arguments, span)
+
+        }
+
+    }
+
+}
@@ -0,0 +1,277 @@
+use std::rc::Rc;
+
+use field::PrimeField;
+use field::bigint::U256;
+use parser::typed_ast::{BinaryOperator, PrefixOperator};
+
+///
+/// Expression left once everything known at elaboration time has been folded: what remains depends
+/// on signal values, which are only known when computing the witness.
+///
+/// Sub expressions are reference counted, values holding them are cloned a lot by the interpreter.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ir<F> {
+    Constant(F),
+    Signal(usize),
+    Prefix(PrefixOperator, Rc<Ir<F>>),
+    Binary(BinaryOperator, Rc<Ir<F>>, Rc<Ir<F>>),
+    Ternary(Rc<Ir<F>>, Rc<Ir<F>>, Rc<Ir<F>>),
+    Call(std::string::String, Rc<Vec<Value<F>>>)
+}
+
+///
+/// Value of a variable or of an expression: a scalar or a (possibly nested) array.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<F> {
+    Scalar(Ir<F>),
+    Array(Vec<Value<F>>)
+}
+
+impl<F: PrimeField> Value<F> {
+
+    pub fn constant(value: F) -> Value<F> {
+        Value::Scalar(Ir::Constant(value))
+    }
+
+    ///
+    /// Builds a zero filled value with the given dimensions.
+    ///
+    pub fn zeros(dimensions: & [usize]) -> Value<F> {
+        match dimensions.split_first() {
+            None => Value::constant(F::zero()),
+            Some((size, rest)) => Value::Array((0..*size).map(|_| Value::zeros(rest)).collect())
+        }
+    }
+
+    pub fn is_constant(&self) -> bool {
+        match self {
+            Value::Scalar(ir) => ir.as_constant().is_some(),
+            Value::Array(values) => values.iter().all(Value::is_constant)
+        }
+    }
+
+    pub fn collect_signals(&self, signals: & mut Vec<usize>) {
+        match self {
+            Value::Scalar(ir) => ir.collect_signals(signals),
+            Value::Array(values) => values.iter().for_each(|value| value.collect_signals(signals))
+        }
+    }
+
+    ///
+    /// Replaces every scalar by the constant `evaluate` returns for it.
+    ///
+    pub fn map_constants<E: FnMut(& Ir<F>) -> Result<F, std::string::String>>(&self, evaluate: & mut E) -> Result<Value<F>, std::string::String> {
+        match self {
+            Value::Scalar(ir) => Ok(Value::constant(evaluate(ir)?)),
+            Value::Array(values) => values.iter()
+                .map(|value| value.map_constants(evaluate))
+                .collect::<Result<Vec<Value<F>>, std::string::String>>()
+                .map(Value::Array)
+        }
+    }
+
+}
+
+impl<F: PrimeField> Ir<F> {
+
+    pub fn as_constant(&self) -> Option<F> {
+        match self {
+            Ir::Constant(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    ///
+    /// Appends the ids of the signals the expression reads, duplicates included.
+    ///
+    pub fn collect_signals(&self, signals: & mut Vec<usize>) {
+        match self {
+            Ir::Constant(_) => {},
+            Ir::Signal(signal) => signals.push(*signal),
+            Ir::Prefix(_, operand) => operand.collect_signals(signals),
+            Ir::Binary(_, left, right) => {
+                left.collect_signals(signals);
+                right.collect_signals(signals);
+            },
+            Ir::Ternary(condition, if_true, if_false) => {
+                condition.collect_signals(signals);
+                if_true.collect_signals(signals);
+                if_false.collect_signals(signals);
+            },
+            Ir::Call(_, arguments) => arguments.iter().for_each(|argument| argument.collect_signals(signals))
+        }
+    }
+
+    pub fn signals(&self) -> Vec<usize> {
+
+        let mut signals: Vec<usize> = Vec::new();
+
+        self.collect_signals(& mut signals);
+        signals.sort_unstable();
+        signals.dedup();
+
+        signals
+
+    }
+
+    ///
+    /// Applies a prefix operator, folded when the operand is constant.
+    ///
+    pub fn prefix(operator: PrefixOperator, operand: Ir<F>) -> Result<Ir<F>, std::string::String> {
+        match operand {
+            Ir::Constant(value) => Ok(Ir::Constant(apply_prefix(operator, value)?)),
+            operand => Ok(Ir::Prefix(operator, Rc::new(operand)))
+        }
+    }
+
+    ///
+    /// Applies a binary operator, folded when both operands are constant.
+    ///
+    pub fn binary(operator: BinaryOperator, left: Ir<F>, right: Ir<F>) -> Result<Ir<F>, std::string::String> {
+        match (left, right) {
+            (Ir::Constant(left), Ir::Constant(right)) => Ok(Ir::Constant(apply_binary(operator, left, right)?)),
+            (left, right) => Ok(Ir::Binary(operator, Rc::new(left), Rc::new(right)))
+        }
+    }
+
+    pub fn ternary(condition: Ir<F>, if_true: Ir<F>, if_false: Ir<F>) -> Ir<F> {
+        match condition {
+            Ir::Constant(condition) => if condition.is_zero() { if_false } else { if_true },
+            condition => Ir::Ternary(Rc::new(condition), Rc::new(if_true), Rc::new(if_false))
+        }
+    }
+
+    ///
+    /// Computes the value of the expression. `signal` provides signal values, `call` runs residual
+    /// function calls once their arguments are known.
+    ///
+    /// Ternary branches are evaluated lazily, so `x != 0 ? 1 / x : 0` never divides by zero.
+    ///
+    pub fn evaluate<S, C>(&self, signal: & S, call: & C) -> Result<F, std::string::String>
+        where S: Fn(usize) -> F, C: Fn(& str, Vec<Value<F>>) -> Result<F, std::string::String> {
+
+        match self {
+            Ir::Constant(value) => Ok(*value),
+            Ir::Signal(id) => Ok(signal(*id)),
+            Ir::Prefix(operator, operand) => apply_prefix(*operator, operand.evaluate(signal, call)?),
+            Ir::Binary(operator, left, right) => apply_binary(*operator, left.evaluate(signal, call)?, right.evaluate(signal, call)?),
+            Ir::Ternary(condition, if_true, if_false) => {
+                if condition.evaluate(signal, call)?.is_zero() {
+                    if_false.evaluate(signal, call)
+                } else {
+                    if_true.evaluate(signal, call)
+                }
+            },
+            Ir::Call(name, arguments) => {
+                let arguments = arguments.iter()
+                    .map(|argument| argument.map_constants(& mut |ir| ir.evaluate(signal, call)))
+                    .collect::<Result<Vec<Value<F>>, std::string::String>>()?;
+                call(name, arguments)
+            }
+        }
+
+    }
+
+}
+
+fn boolean<F: PrimeField>(value: bool) -> F {
+    if value { F::one() } else { F::zero() }
+}
+
+///
+/// All ones on the bit size of the prime, bitwise results are truncated to it before being reduced.
+///
+fn mask<F: PrimeField>() -> U256 {
+    U256::ONE.shl(F::modulus().bits()).overflowing_sub(&U256::ONE).0
+}
+
+///
+/// Elements above `p/2` stand for negative values in comparisons and shifts. Returns the sign and
+/// the absolute value.
+///
+fn signed<F: PrimeField>(value: F) -> (bool, U256) {
+
+    let half = F::modulus().shr(1);
+    let value = value.to_u256();
+
+    if value > half {
+        (true, F::modulus().overflowing_sub(&value).0)
+    } else {
+        (false, value)
+    }
+
+}
+
+fn compare<F: PrimeField>(left: F, right: F) -> std::cmp::Ordering {
+    match (signed(left), signed(right)) {
+        ((true, left), (true, right)) => right.cmp(&left),
+        ((false, left), (false, right)) => left.cmp(&right),
+        ((true, _), (false, _)) => std::cmp::Ordering::Less,
+        ((false, _), (true, _)) => std::cmp::Ordering::Greater
+    }
+}
+
+fn shift<F: PrimeField>(value: F, amount: F, left: bool) -> F {
+
+    let (negative, amount) = signed(amount);
+    let left = left != negative;
+    let amount = if amount.bits() > 16 { 256 } else { amount.0[0] as usize };
+
+    if left {
+        F::from_u256(&value.to_u256().shl(amount).and(&mask::<F>()))
+    } else {
+        F::from_u256(&value.to_u256().shr(amount))
+    }
+
+}
+
+pub fn apply_prefix<F: PrimeField>(operator: PrefixOperator, value: F) -> Result<F, std::string::String> {
+    match operator {
+        PrefixOperator::Plus => Ok(value),
+        PrefixOperator::Minus => Ok(-value),
+        PrefixOperator::Not => Ok(boolean(value.is_zero())),
+        PrefixOperator::Complement => Ok(F::from_u256(&value.to_u256().not().and(&mask::<F>()))),
+        PrefixOperator::Increment => Ok(value + F::one()),
+        PrefixOperator::Decrement => Ok(value - F::one())
+    }
+}
+
+///
+/// Circom semantics of the binary operators. `+ - * / **` are field operations, `\ %`, the bitwise
+/// operators and the shifts work on the canonical integer value, comparisons consider values above
+/// `p/2` as negative.
+///
+pub fn apply_binary<F: PrimeField>(operator: BinaryOperator, left: F, right: F) -> Result<F, std::string::String> {
+    match operator {
+        BinaryOperator::Add => Ok(left + right),
+        BinaryOperator::Sub => Ok(left - right),
+        BinaryOperator::Mul => Ok(left * right),
+        BinaryOperator::Div => match right.inverse() {
+            Some(inverse) => Ok(left * inverse),
+            None => Err("Division by zero".to_string())
+        },
+        BinaryOperator::IntDiv | BinaryOperator::Mod => {
+            if right.is_zero() {
+                return Err("Division by zero".to_string());
+            }
+            let (quotient, remainder) = left.to_u256().divrem(&right.to_u256());
+            Ok(F::from_u256(if operator == BinaryOperator::IntDiv { &quotient } else { &remainder }))
+        },
+        BinaryOperator::Pow => Ok(left.pow(&right.to_u256())),
+        BinaryOperator::Or => Ok(boolean(!left.is_zero() || !right.is_zero())),
+        BinaryOperator::And => Ok(boolean(!left.is_zero() && !right.is_zero())),
+        BinaryOperator::BitOr => Ok(F::from_u256(&left.to_u256().or(&right.to_u256()))),
+        BinaryOperator::BitXor => Ok(F::from_u256(&left.to_u256().xor(&right.to_u256()))),
+        BinaryOperator::BitAnd => Ok(F::from_u256(&left.to_u256().and(&right.to_u256()))),
+        BinaryOperator::Equal => Ok(boolean(left == right)),
+        BinaryOperator::NotEqual => Ok(boolean(left != right)),
+        BinaryOperator::Lesser => Ok(boolean(compare(left, right) == std::cmp::Ordering::Less)),
+        BinaryOperator::LesserEqual => Ok(boolean(compare(left, right) != std::cmp::Ordering::Greater)),
+        BinaryOperator::Greater => Ok(boolean(compare(left, right) == std::cmp::Ordering::Greater)),
+        BinaryOperator::GreaterEqual => Ok(boolean(compare(left, right) != std::cmp::Ordering::Less)),
+        BinaryOperator::ShiftLeft => Ok(shift(left, right, true)),
+        BinaryOperator::ShiftRight => Ok(shift(left, right, false))
+    }
+}
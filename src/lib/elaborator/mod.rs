@@ -0,0 +1,506 @@
+pub mod ir;
+pub mod quadratic;
+pub mod underconstrained;
+pub mod stats;
+mod interpreter;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use field::PrimeField;
+use circuit::r1cs::R1CS;
+use circuit::sym::SymbolMap;
+use parser::compile::Context;
+use parser::errors::elaboration::ElaborationError;
+use parser::matchers;
+use parser::typed_ast::{Span, SignalDirection, DeclarationKind, Template, Function};
+use elaborator::ir::{Ir, Value};
+use elaborator::interpreter::Interpreter;
+
+///
+/// Signal of an elaborated circuit, named after its full path (`main.n2b.out[3]`).
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signal {
+    pub name: std::string::String,
+    pub component: usize,
+    pub direction: SignalDirection,
+    pub private: bool,
+
+    ///
+    /// Span of the declaration, relative to the file of the component.
+    ///
+    pub span: Span
+}
+
+///
+/// Signal ids of a (possibly multi dimensional) signal declaration.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignalTree {
+    Signal(usize),
+    Array(Vec<SignalTree>)
+}
+
+impl SignalTree {
+
+    pub fn get(&self, indices: & [usize]) -> Option<& SignalTree> {
+        match indices.split_first() {
+            None => Some(self),
+            Some((index, rest)) => match self {
+                SignalTree::Array(trees) => trees.get(*index).and_then(|tree| tree.get(rest)),
+                SignalTree::Signal(_) => None
+            }
+        }
+    }
+
+    ///
+    /// All the signal ids, in declaration order.
+    ///
+    pub fn signals(&self) -> Vec<usize> {
+        match self {
+            SignalTree::Signal(signal) => vec!(*signal),
+            SignalTree::Array(trees) => trees.iter().flat_map(SignalTree::signals).collect()
+        }
+    }
+
+    ///
+    /// Sizes of the dimensions, empty for a single signal.
+    ///
+    pub fn dimensions(&self) -> Vec<usize> {
+        match self {
+            SignalTree::Signal(_) => Vec::new(),
+            SignalTree::Array(trees) => {
+                let mut sizes = vec!(trees.len());
+                if let Some(first) = trees.first() {
+                    sizes.extend(first.dimensions());
+                }
+                sizes
+            }
+        }
+    }
+
+    pub fn to_value<F: PrimeField>(&self) -> Value<F> {
+        match self {
+            SignalTree::Signal(signal) => Value::Scalar(Ir::Signal(*signal)),
+            SignalTree::Array(trees) => Value::Array(trees.iter().map(SignalTree::to_value).collect())
+        }
+    }
+
+}
+
+///
+/// Template instance. The main component has id 0 and is named `main`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    pub name: std::string::String,
+    pub template: std::string::String,
+    pub file: PathBuf,
+    pub parent: Option<usize>,
+    pub signals: Vec<(std::string::String, SignalTree)>
+}
+
+impl Component {
+
+    pub fn signal(&self, name: & str) -> Option<& SignalTree> {
+        self.signals.iter()
+            .find(|(signal_name, _)| signal_name == name)
+            .map(|(_, tree)| tree)
+    }
+
+}
+
+///
+/// `<==` or `<--` assignment. `span` is relative to the file of the component.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment<F> {
+    pub signal: usize,
+    pub value: Ir<F>,
+    pub constrained: bool,
+    pub component: usize,
+    pub span: Span
+}
+
+///
+/// `left === right` constraint, `<==` assignments produce one too.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintCheck<F> {
+    pub left: Ir<F>,
+    pub right: Ir<F>,
+    pub component: usize,
+    pub span: Span
+}
+
+///
+/// Position of a statement of an elaborated circuit, for reporting.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub component: std::string::String,
+    pub template: std::string::String,
+    pub file: PathBuf,
+    pub span: Span,
+    pub line: usize,
+    pub column: usize,
+    pub source: std::string::String
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "{}:{}:{} in {} ({}): {}", self.file.display(), self.line, self.column, self.component, self.template, self.source)
+    }
+}
+
+///
+/// Circuit with all its templates instantiated.
+///
+/// Wires follow the circom layout: wire 0 is the constant `one`, then come the outputs, the public
+/// inputs and the private inputs of the main component, then all the other signals.
+///
+#[derive(Debug, Clone)]
+pub struct ElaboratedCircuit<F> {
+    pub signals: Vec<Signal>,
+    pub components: Vec<Component>,
+    pub assignments: Vec<Assignment<F>>,
+    pub constraints: Vec<ConstraintCheck<F>>,
+
+    pub n_outputs: usize,
+    pub n_public_inputs: usize,
+    pub n_private_inputs: usize,
+
+    ///
+    /// File declaring the `main` component.
+    ///
+    pub file: PathBuf,
+
+    wires: Vec<usize>,
+    functions: HashMap<std::string::String, (Function, PathBuf)>,
+    sources: HashMap<PathBuf, std::string::String>
+}
+
+impl<F: PrimeField> ElaboratedCircuit<F> {
+
+    pub fn main(&self) -> & Component {
+        &self.components[0]
+    }
+
+    pub fn n_wires(&self) -> usize {
+        self.signals.len() + 1
+    }
+
+    pub fn wire_of(&self, signal: usize) -> usize {
+        self.wires[signal]
+    }
+
+    ///
+    /// Input signals of the main component, as declared.
+    ///
+    pub fn inputs(&self) -> impl Iterator<Item = &(std::string::String, SignalTree)> {
+        let signals = &self.signals;
+        self.main().signals.iter().filter(move |(_, tree)| match tree.signals().first() {
+            Some(signal) => signals[*signal].direction == SignalDirection::Input,
+            None => false
+        })
+    }
+
+    ///
+    /// Symbol map of the circuit, signals listed in declaration order.
+    ///
+    pub fn symbols(&self) -> SymbolMap {
+
+        let mut symbols = SymbolMap::new();
+
+        for (id, signal) in self.signals.iter().enumerate() {
+            symbols.push(Some(self.wires[id]), signal.component, &signal.name);
+        }
+
+        symbols
+
+    }
+
+    pub fn location(&self, component: usize, span: Span) -> Location {
+
+        let component = &self.components[component];
+        let source = self.sources.get(&component.file).map(|source| source.as_str()).unwrap_or("");
+        let before = source.get(..span.0).unwrap_or("");
+
+        Location {
+            component: component.name.clone(),
+            template: component.template.clone(),
+            file: component.file.clone(),
+            span,
+            line: before.matches('\n').count() + 1,
+            column: before.chars().rev().take_while(|character| *character != '\n').count() + 1,
+            source: source.get(span.0..span.1).unwrap_or("").to_string()
+        }
+
+    }
+
+    ///
+    /// Error pointing at a statement of the circuit.
+    ///
+    pub fn error(&self, component: usize, span: Span, reason: std::string::String) -> ElaborationError {
+
+        let location = self.location(component, span);
+
+        ElaborationError {
+            file_error: location.file,
+            source_error: location.source,
+            span_error: span,
+            reason
+        }
+
+    }
+
+    ///
+    /// Constraint system of the circuit: one constraint per `===` or `<==`, the ones that always hold
+    /// being dropped. Fails on constraints that are not quadratic.
+    ///
+    pub fn r1cs(&self) -> Result<R1CS<F>, ElaborationError> {
+
+        let mut r1cs = R1CS::new(self.n_outputs, self.n_public_inputs, self.n_private_inputs);
+        r1cs.n_wires = self.n_wires();
+
+        let wire_of = |signal: usize| self.wire_of(signal);
+
+        for check in &self.constraints {
+            match quadratic::constraint(&check.left, &check.right, &wire_of) {
+                Ok(Some(constraint)) => r1cs.add_constraint(constraint),
+                Ok(None) => {},
+                Err(reason) => return Err(self.error(check.component, check.span, reason))
+            }
+        }
+
+        Ok(r1cs)
+
+    }
+
+    ///
+    /// Runs a function of the circuit, used for residual calls once signal values are known.
+    ///
+    pub fn call_function(&self, name: & str, arguments: Vec<Value<F>>) -> Result<F, std::string::String> {
+
+        let templates: HashMap<std::string::String, (Template, PathBuf)> = HashMap::new();
+        let mut interpreter: Interpreter<F> = Interpreter::new(&templates, &self.functions, &self.sources);
+
+        match interpreter.run_function(name, arguments, (0, 0), Path::new("")) {
+            Ok(Value::Scalar(Ir::Constant(value))) => Ok(value),
+            Ok(_) => Err(format!("Function {} did not return a single value", name)),
+            Err(error) => Err(error.reason)
+        }
+
+    }
+
+    ///
+    /// Runs a function on arguments depending on signals, for the code generators: the result is the
+    /// expression computing the call. Fails if the loops, branches or indices of the function depend
+    /// on the arguments.
+    ///
+    pub fn inline_function(&self, name: & str, arguments: Vec<Value<F>>) -> Result<Ir<F>, std::string::String> {
+
+        let templates: HashMap<std::string::String, (Template, PathBuf)> = HashMap::new();
+        let mut interpreter: Interpreter<F> = Interpreter::new(&templates, &self.functions, &self.sources);
+
+        match interpreter.run_function(name, arguments, (0, 0), Path::new("")) {
+            Ok(Value::Scalar(ir)) => Ok(ir),
+            Ok(_) => Err(format!("Function {} did not return a single value", name)),
+            Err(error) => Err(error.reason)
+        }
+
+    }
+
+}
+
+fn insert_definition<T: PartialEq>(definitions: & mut HashMap<std::string::String, (T, PathBuf)>, name: & str, definition: T, file: & Path, span: Span) -> Result<(), ElaborationError> {
+
+    if let Some((existing, _)) = definitions.get(name) {
+        if *existing == definition {
+            return Ok(());
+        }
+        return Err(ElaborationError {
+            file_error: file.to_path_buf(),
+            source_error: name.to_string(),
+            span_error: span,
+            reason: format!("{} is defined twice", name)
+        });
+    }
+
+    definitions.insert(name.to_string(), (definition, file.to_path_buf()));
+
+    Ok(())
+
+}
+
+///
+/// True if the main file of the context declares a `main` component, libraries of templates do
+/// not and cannot be elaborated.
+///
+pub fn declares_main(ctx: & Context) -> bool {
+    match ctx.file(ctx.main()) {
+        Some(file) => match matchers::circuit::process_circuit(&file.root) {
+            Ok(circuit) => circuit.declarations()
+                .any(|declaration| declaration.kind == DeclarationKind::Component && declaration.name == "main"),
+            Err(_) => false
+        },
+        None => false
+    }
+}
+
+///
+/// Instantiates the `main` component declared in the main file of the context, and all the
+/// components it uses.
+///
+/// The context is expected to be free of errors.
+///
+pub fn elaborate<F: PrimeField>(ctx: & Context) -> Result<ElaboratedCircuit<F>, ElaborationError> {
+
+    let mut templates: HashMap<std::string::String, (Template, PathBuf)> = HashMap::new();
+    let mut functions: HashMap<std::string::String, (Function, PathBuf)> = HashMap::new();
+    let mut sources: HashMap<PathBuf, std::string::String> = HashMap::new();
+    let mut main = None;
+
+    for file in ctx.files() {
+
+        let circuit = match matchers::circuit::process_circuit(&file.root) {
+            Ok(circuit) => circuit,
+            Err(error) => return Err(ElaborationError {
+                file_error: file.path.clone(),
+                source_error: file.root.source.get(error.span.0..error.span.1).unwrap_or_default().to_string(),
+                span_error: error.span,
+                reason: error.reason
+            })
+        };
+
+        for template in circuit.templates() {
+            insert_definition(& mut templates, &template.name, template.clone(), &file.path, template.span)?;
+        }
+
+        for function in circuit.functions() {
+            insert_definition(& mut functions, &function.name, function.clone(), &file.path, function.span)?;
+        }
+
+        if file.path == ctx.main() {
+            main = circuit.declarations()
+                .find(|declaration| declaration.kind == DeclarationKind::Component && declaration.name == "main")
+                .cloned();
+        }
+
+        sources.insert(file.path.clone(), file.root.source.clone());
+
+    }
+
+    let main = match main {
+        Some(main) => main,
+        None => return Err(ElaborationError {
+            file_error: ctx.main().to_path_buf(),
+            source_error: std::string::String::new(),
+            span_error: (0, 0),
+            reason: "No main component declared".to_string()
+        })
+    };
+
+    let main_file = ctx.main().to_path_buf();
+    let (signals, components, assignments, constraints) = {
+        let mut interpreter: Interpreter<F> = Interpreter::new(&templates, &functions, &sources);
+        interpreter.instantiate_main(&main, &main_file)?;
+        (interpreter.signals, interpreter.components, interpreter.assignments, interpreter.constraints)
+    };
+
+    let main_signals = |direction: SignalDirection, private: Option<bool>| -> Vec<usize> {
+        signals.iter().enumerate()
+            .filter(|(_, signal)| signal.component == 0 && signal.direction == direction && private.is_none_or(|private| signal.private == private))
+            .map(|(id, _)| id)
+            .collect()
+    };
+
+    let outputs = main_signals(SignalDirection::Output, None);
+    let public_inputs = main_signals(SignalDirection::Input, Some(false));
+    let private_inputs = main_signals(SignalDirection::Input, Some(true));
+
+    let mut wires: Vec<Option<usize>> = vec!(None; signals.len());
+    let mut next_wire = 1;
+
+    for signal in outputs.iter().chain(public_inputs.iter()).chain(private_inputs.iter()) {
+        wires[*signal] = Some(next_wire);
+        next_wire += 1;
+    }
+
+    for wire in wires.iter_mut().filter(|wire| wire.is_none()) {
+        *wire = Some(next_wire);
+        next_wire += 1;
+    }
+
+    Ok(ElaboratedCircuit {
+        n_outputs: outputs.len(),
+        n_public_inputs: public_inputs.len(),
+        n_private_inputs: private_inputs.len(),
+        file: main_file,
+        wires: wires.into_iter().map(|wire| wire.unwrap()).collect(),
+        signals,
+        components,
+        assignments,
+        constraints,
+        functions,
+        sources
+    })
+
+}
+
+#[cfg(test)]
+mod elaborator_test {
+
+    use elaborator;
+    use field::bn254::Fr;
+    use parser::compile;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_elaborate() {
+
+        let path = std::fs::canonicalize("./src/lib/parser/test_material/witness/num2bits.circom").expect("Invalid Path");
+        let ctx = compile::build_context(&path);
+        let circuit = elaborator::elaborate::<Fr>(&ctx).expect("Elaboration failed");
+
+        expect_that!(&circuit.components.len(), is(eq(1)));
+        expect_that!(&circuit.main().template.as_str(), is(eq("Num2Bits")));
+        expect_that!(&circuit.signals.len(), is(eq(9)));
+        expect_that!(&circuit.assignments.len(), is(eq(8)));
+        expect_that!(&circuit.constraints.len(), is(eq(9)));
+        expect_that!(&circuit.n_outputs, is(eq(8)));
+        expect_that!(&circuit.n_public_inputs, is(eq(1)));
+        expect_that!(&circuit.n_private_inputs, is(eq(0)));
+
+        let symbols = circuit.symbols();
+
+        expect_that!(&symbols.wire_of("main.out[0]"), is(eq(Some(1))));
+        expect_that!(&symbols.wire_of("main.in"), is(eq(Some(9))));
+
+        let r1cs = circuit.r1cs().expect("Constraint generation failed");
+
+        expect_that!(&r1cs.n_wires, is(eq(10)));
+        expect_that!(&r1cs.constraints.len(), is(eq(9)));
+        expect_that!(&r1cs.constraints.iter().filter(|constraint| constraint.is_linear()).count(), is(eq(1)));
+
+    }
+
+    #[test]
+    fn test_elaborate_errors() {
+
+        let path = std::fs::canonicalize("./src/lib/parser/test_material/witness/invalid_double_assignment.circom").expect("Invalid Path");
+        let ctx = compile::build_context(&path);
+
+        match elaborator::elaborate::<Fr>(&ctx) {
+            Ok(_) => panic!("Double assignment should be rejected"),
+            Err(error) => {
+                expect_that!(&error.reason.as_str(), is(eq("Signal main.out is assigned twice")));
+                expect_that!(&error.source_error.as_str(), is(eq("out <-- in")));
+            }
+        }
+
+    }
+
+}
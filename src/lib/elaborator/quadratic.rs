@@ -0,0 +1,199 @@
+use field::PrimeField;
+use circuit::r1cs::{Constraint, LinearCombination};
+use parser::typed_ast::{BinaryOperator, PrefixOperator};
+use elaborator::ir::Ir;
+
+///
+/// Expression over wires of degree at most two: `Linear(lc)` or `Quadratic(a, b, c)` standing for
+/// `a * b + c`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Quadratic<F: PrimeField> {
+    Linear(LinearCombination<F>),
+    Quadratic(LinearCombination<F>, LinearCombination<F>, LinearCombination<F>)
+}
+
+impl<F: PrimeField> Quadratic<F> {
+
+    fn as_constant(&self) -> Option<F> {
+        match self {
+            Quadratic::Linear(lc) if lc.is_constant() => Some(lc.constant_value()),
+            _ => None
+        }
+    }
+
+    fn scale(self, factor: F) -> Quadratic<F> {
+        match self {
+            Quadratic::Linear(mut lc) => {
+                lc.scale(factor);
+                Quadratic::Linear(lc)
+            },
+            Quadratic::Quadratic(mut a, b, mut c) => {
+                a.scale(factor);
+                c.scale(factor);
+                Quadratic::Quadratic(a, b, c)
+            }
+        }
+    }
+
+    ///
+    /// `self + factor * other`, at most one side being quadratic.
+    ///
+    fn add_scaled(self, other: Quadratic<F>, factor: F) -> Result<Quadratic<F>, std::string::String> {
+        match (self, other) {
+            (Quadratic::Linear(mut left), Quadratic::Linear(right)) => {
+                left.add_scaled(&right, factor);
+                Ok(Quadratic::Linear(left))
+            },
+            (Quadratic::Quadratic(a, b, mut c), Quadratic::Linear(right)) => {
+                c.add_scaled(&right, factor);
+                Ok(Quadratic::Quadratic(a, b, c))
+            },
+            (Quadratic::Linear(left), quadratic) => quadratic.scale(factor).add_scaled(Quadratic::Linear(left), F::one()),
+            _ => Err("Non quadratic constraint: sum of two products".to_string())
+        }
+    }
+
+    fn mul(self, other: Quadratic<F>) -> Result<Quadratic<F>, std::string::String> {
+
+        if let Some(factor) = other.as_constant() {
+            return Ok(self.scale(factor));
+        }
+
+        if let Some(factor) = self.as_constant() {
+            return Ok(other.scale(factor));
+        }
+
+        match (self, other) {
+            (Quadratic::Linear(left), Quadratic::Linear(right)) => Ok(Quadratic::Quadratic(left, right, LinearCombination::new())),
+            _ => Err("Non quadratic constraint: product of degree higher than two".to_string())
+        }
+
+    }
+
+}
+
+///
+/// Lowers an expression of a constraint to its quadratic form, `wire_of` giving the wire of each
+/// signal. Fails on anything that is not a sum, difference or product of signals and constants,
+/// or a division by a constant.
+///
+pub fn lower<F: PrimeField, W: Fn(usize) -> usize>(ir: & Ir<F>, wire_of: & W) -> Result<Quadratic<F>, std::string::String> {
+    match ir {
+        Ir::Constant(value) => Ok(Quadratic::Linear(LinearCombination::constant(*value))),
+        Ir::Signal(signal) => Ok(Quadratic::Linear(LinearCombination::wire(wire_of(*signal)))),
+        Ir::Prefix(PrefixOperator::Plus, operand) => lower(operand, wire_of),
+        Ir::Prefix(PrefixOperator::Minus, operand) => Ok(lower(operand, wire_of)?.scale(-F::one())),
+        Ir::Binary(operator, left, right) => {
+
+            let left = lower(left, wire_of)?;
+            let right = lower(right, wire_of)?;
+
+            match operator {
+                BinaryOperator::Add => left.add_scaled(right, F::one()),
+                BinaryOperator::Sub => left.add_scaled(right, -F::one()),
+                BinaryOperator::Mul => left.mul(right),
+                BinaryOperator::Div => match right.as_constant() {
+                    Some(divisor) => match divisor.inverse() {
+                        Some(inverse) => Ok(left.scale(inverse)),
+                        None => Err("Division by zero".to_string())
+                    },
+                    None => Err("Non quadratic constraint: division by a signal".to_string())
+                },
+                _ => Err(format!("Non quadratic constraint: operator {:?} applied to signals", operator))
+            }
+
+        },
+        Ir::Prefix(operator, _) => Err(format!("Non quadratic constraint: operator {:?} applied to signals", operator)),
+        Ir::Ternary(_, _, _) => Err("Non quadratic constraint: condition depending on signals".to_string()),
+        Ir::Call(name, _) => Err(format!("Non quadratic constraint: call to {} with signal arguments", name))
+    }
+}
+
+///
+/// Builds the constraint `left - right = 0`. Returns `None` for constraints that always hold and
+/// an error for the ones that never do.
+///
+pub fn constraint<F: PrimeField, W: Fn(usize) -> usize>(left: & Ir<F>, right: & Ir<F>, wire_of: & W) -> Result<Option<Constraint<F>>, std::string::String> {
+
+    let left = lower(left, wire_of)?;
+    let right = lower(right, wire_of)?;
+
+    // Keeps the product on the positive side, `out === a * b` giving `a * b - out = 0`
+    let difference = match right {
+        Quadratic::Quadratic(_, _, _) => right.add_scaled(left, -F::one())?,
+        Quadratic::Linear(_) => left.add_scaled(right, -F::one())?
+    };
+
+    match difference {
+        Quadratic::Linear(lc) => {
+            if lc.is_empty() {
+                Ok(None)
+            } else if lc.is_constant() {
+                Err("Constraint can never be satisfied".to_string())
+            } else {
+                Ok(Some(Constraint::linear(lc)))
+            }
+        },
+        Quadratic::Quadratic(a, b, mut c) => {
+            c.scale(-F::one());
+            Ok(Some(Constraint::new(a, b, c)))
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod quadratic_test {
+
+    use std::rc::Rc;
+
+    use field::PrimeField;
+    use field::bn254::Fr;
+    use circuit::r1cs::{Constraint, LinearCombination};
+    use parser::typed_ast::BinaryOperator;
+    use elaborator::ir::Ir;
+    use elaborator::quadratic;
+
+    use galvanic_assert::matchers::*;
+
+    fn binary(operator: BinaryOperator, left: Ir<Fr>, right: Ir<Fr>) -> Ir<Fr> {
+        Ir::Binary(operator, Rc::new(left), Rc::new(right))
+    }
+
+    #[test]
+    fn test_constraint() {
+
+        let wire_of = |signal: usize| signal + 1;
+
+        // out === (a - 1) * (b / 2) + 3
+        let product = binary(BinaryOperator::Mul,
+            binary(BinaryOperator::Sub, Ir::Signal(1), Ir::Constant(Fr::one())),
+            binary(BinaryOperator::Div, Ir::Signal(2), Ir::Constant(Fr::from_u64(2))));
+        let right = binary(BinaryOperator::Add, product, Ir::Constant(Fr::from_u64(3)));
+
+        let constraint = quadratic::constraint(&Ir::Signal(0), &right, &wire_of).unwrap().unwrap();
+
+        let mut a = LinearCombination::wire(2);
+        a.add_term(0, -Fr::one());
+        let half = Fr::from_u64(2).inverse().unwrap();
+        let mut c = LinearCombination::wire(1);
+        c.add_term(0, -Fr::from_u64(3));
+
+        expect_that!(&constraint, is(eq(Constraint::new(a.clone(), LinearCombination::term(3, half), c))));
+
+        let witness = vec!(Fr::one(), Fr::from_u64(13), Fr::from_u64(6), Fr::from_u64(4));
+        expect_that!(&constraint.is_satisfied(&witness), is(eq(true)));
+
+        let linear = quadratic::constraint(&Ir::Signal(0), &binary(BinaryOperator::Mul, Ir::Constant(Fr::from_u64(2)), Ir::Signal(1)), &wire_of).unwrap();
+        expect_that!(&linear.map(|constraint| constraint.is_linear()), is(eq(Some(true))));
+
+        expect_that!(&quadratic::constraint(&Ir::Signal::<Fr>(0), &Ir::Signal(0), &wire_of), is(eq(Ok(None))));
+        expect_that!(&quadratic::constraint(&Ir::Constant(Fr::one()), &Ir::Constant(Fr::zero()), &wire_of).is_err(), is(eq(true)));
+
+        let cubic = binary(BinaryOperator::Mul, binary(BinaryOperator::Mul, Ir::Signal(0), Ir::Signal(1)), Ir::Signal(2));
+        expect_that!(&quadratic::constraint(&cubic, &Ir::Signal(0), &wire_of).is_err(), is(eq(true)));
+
+    }
+
+}
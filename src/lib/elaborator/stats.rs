@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use elaborator::quadratic;
+use elaborator::ElaboratedCircuit;
+use field::PrimeField;
+use parser::errors::elaboration::ElaborationError;
+
+///
+/// Instances of a template and the constraints they generate, the constraints of their
+/// sub-components excluded.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TemplateStats {
+    pub template: std::string::String,
+    pub instances: usize,
+    pub constraints: usize,
+    pub linear_constraints: usize
+}
+
+///
+/// Size of an elaborated circuit and of its constraint system.
+///
+/// `templates` lists every instantiated template, the ones contributing the most constraints
+/// first.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CircuitStats {
+    pub prime: std::string::String,
+    pub main: std::string::String,
+    pub constraints: usize,
+    pub linear_constraints: usize,
+    pub non_linear_constraints: usize,
+    pub wires: usize,
+    pub signals: usize,
+    pub outputs: usize,
+    pub public_inputs: usize,
+    pub private_inputs: usize,
+    pub components: usize,
+    pub templates: Vec<TemplateStats>
+}
+
+impl CircuitStats {
+
+    ///
+    /// Counts the constraints the way `ElaboratedCircuit::r1cs` generates them, the ones that always
+    /// hold being dropped.
+    ///
+    pub fn new<F: PrimeField>(circuit: & ElaboratedCircuit<F>) -> Result<CircuitStats, ElaborationError> {
+
+        let mut templates: HashMap<& str, TemplateStats> = HashMap::new();
+
+        for component in &circuit.components {
+            templates.entry(&component.template)
+                .or_insert_with(|| TemplateStats {
+                    template: component.template.clone(),
+                    instances: 0,
+                    constraints: 0,
+                    linear_constraints: 0
+                })
+                .instances += 1;
+        }
+
+        let wire_of = |signal: usize| circuit.wire_of(signal);
+
+        for check in &circuit.constraints {
+
+            let constraint = match quadratic::constraint(&check.left, &check.right, &wire_of) {
+                Ok(Some(constraint)) => constraint,
+                Ok(None) => continue,
+                Err(reason) => return Err(circuit.error(check.component, check.span, reason))
+            };
+
+            let stats = templates.get_mut(circuit.components[check.component].template.as_str()).unwrap();
+            stats.constraints += 1;
+            if constraint.is_linear() {
+                stats.linear_constraints += 1;
+            }
+
+        }
+
+        let mut templates: Vec<TemplateStats> = templates.into_values().collect();
+        templates.sort_by(|left, right| right.constraints.cmp(&left.constraints).then_with(|| left.template.cmp(&right.template)));
+
+        let constraints = templates.iter().map(|stats| stats.constraints).sum();
+        let linear_constraints = templates.iter().map(|stats| stats.linear_constraints).sum();
+
+        Ok(CircuitStats {
+            prime: F::NAME.to_string(),
+            main: circuit.main().template.clone(),
+            constraints,
+            linear_constraints,
+            non_linear_constraints: constraints - linear_constraints,
+            wires: circuit.n_wires(),
+            signals: circuit.signals.len(),
+            outputs: circuit.n_outputs,
+            public_inputs: circuit.n_public_inputs,
+            private_inputs: circuit.n_private_inputs,
+            components: circuit.components.len(),
+            templates
+        })
+
+    }
+
+    ///
+    /// Table of the summary and of the `top` templates contributing the most constraints.
+    ///
+    pub fn to_table(&self, top: usize) -> std::string::String {
+
+        let mut table = std::string::String::new();
+
+        let summary = [
+            ("main", self.main.clone()),
+            ("prime", self.prime.clone()),
+            ("constraints", format!("{} ({} linear, {} non-linear)", self.constraints, self.linear_constraints, self.non_linear_constraints)),
+            ("wires", self.wires.to_string()),
+            ("outputs", self.outputs.to_string()),
+            ("public inputs", self.public_inputs.to_string()),
+            ("private inputs", self.private_inputs.to_string()),
+            ("components", self.components.to_string())
+        ];
+
+        for (name, value) in summary.iter() {
+            table.push_str(&format!("{:<16}{}\n", format!("{}:", name), value));
+        }
+
+        let shown = &self.templates[..top.min(self.templates.len())];
+        let width = shown.iter().map(|stats| stats.template.len()).chain(std::iter::once("template".len())).max().unwrap();
+
+        table.push('\n');
+        table.push_str(&format!("{:<width$}  {:>9}  {:>11}  {:>6}  {:>6}\n", "template", "instances", "constraints", "linear", "share", width = width));
+
+        for stats in shown {
+            let share = if self.constraints == 0 { 0.0 } else { 100.0 * stats.constraints as f64 / self.constraints as f64 };
+            table.push_str(&format!("{:<width$}  {:>9}  {:>11}  {:>6}  {:>5.1}%\n", stats.template, stats.instances, stats.constraints, stats.linear_constraints, share, width = width));
+        }
+
+        if shown.len() < self.templates.len() {
+            table.push_str(&format!("... {} more template(s)\n", self.templates.len() - shown.len()));
+        }
+
+        table
+
+    }
+
+}
+
+#[cfg(test)]
+mod stats_test {
+
+    use elaborator::{self, stats::CircuitStats};
+    use field::bn254::Fr;
+    use parser::compile;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_stats() {
+
+        let path = std::fs::canonicalize("./src/lib/parser/test_material/underconstrained/issues.circom").expect("Invalid Path");
+        let ctx = compile::build_context(&path);
+        let circuit = elaborator::elaborate::<Fr>(&ctx).expect("Elaboration failed");
+        let stats = CircuitStats::new(&circuit).expect("Constraint generation failed");
+
+        expect_that!(&stats.constraints, is(eq(circuit.r1cs().unwrap().constraints.len())));
+        expect_that!(&stats.constraints, is(eq(3)));
+        expect_that!(&stats.linear_constraints, is(eq(2)));
+        expect_that!(&stats.non_linear_constraints, is(eq(1)));
+        expect_that!(&stats.wires, is(eq(7)));
+        expect_that!(&stats.public_inputs, is(eq(1)));
+        expect_that!(&stats.components, is(eq(2)));
+
+        let templates: Vec<(std::string::String, usize, usize)> = stats.templates.iter()
+            .map(|stats| (stats.template.clone(), stats.instances, stats.constraints))
+            .collect();
+        expect_that!(&templates, is(eq(vec!(("Issues".to_string(), 1, 2), ("Root".to_string(), 1, 1)))));
+
+        let table = stats.to_table(1);
+        expect_that!(&table.contains("constraints:    3 (2 linear, 1 non-linear)"), is(eq(true)));
+        expect_that!(&table.contains("Issues"), is(eq(true)));
+        expect_that!(&table.contains("... 1 more template(s)"), is(eq(true)));
+
+        let json = serde_json::to_value(&stats).unwrap();
+        expect_that!(&json["templates"][1]["template"], is(eq(json!("Root"))));
+
+    }
+
+}
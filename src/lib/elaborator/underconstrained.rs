@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use circuit::determinism;
+use circuit::r1cs::R1CS;
+use elaborator::{Assignment, ElaboratedCircuit, Location};
+use field::PrimeField;
+use parser::typed_ast::SignalDirection;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IssueKind {
+
+    ///
+    /// Signal appearing in no constraint: any value is accepted for it.
+    ///
+    Unconstrained,
+
+    ///
+    /// Output of the main component whose value is not fixed by the inputs through the constraints.
+    ///
+    UndeterminedOutput,
+
+    ///
+    /// `<--` assignment whose signal is constrained, but not enough for its value to be fixed.
+    ///
+    UndeterminedAssignment,
+
+    ///
+    /// Output of the main component or `<--` assignment whose value is fixed by the constraints
+    /// only as long as a factor depending on other signals does not vanish, as `x` in
+    /// `x * y === z` when `y` can be zero.
+    ///
+    ConditionallyDetermined
+
+}
+
+///
+/// Signal that a dishonest prover may be able to set to a value the circuit does not compute. The
+/// location is the assignment of the signal, or its declaration if it is never assigned.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub signal: std::string::String,
+    pub location: Location
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.kind {
+            IssueKind::Unconstrained => write!(formatter, "Signal {} appears in no constraint", self.signal)?,
+            IssueKind::UndeterminedOutput => write!(formatter, "Output {} is not determined by the inputs through the constraints", self.signal)?,
+            IssueKind::UndeterminedAssignment => write!(formatter, "Signal {} is assigned with <-- but not determined by the constraints", self.signal)?,
+            IssueKind::ConditionallyDetermined => write!(formatter, "Signal {} is determined by the constraints only while a factor depending on other signals is not zero", self.signal)?
+        }
+        write!(formatter, "\n\t{}", self.location)
+    }
+}
+
+///
+/// Under-constrained signals of a circuit, from the analysis of its constraint system.
+///
+/// Determination is propagated from the inputs (see `determinism::determined_wires`), so a signal
+/// depending on an under-constrained one is reported as well. Signals that are only conditionally
+/// determined (see `determinism::conditionally_determined_wires`) are reported apart.
+///
+pub fn find_issues<F: PrimeField>(circuit: & ElaboratedCircuit<F>, r1cs: & R1CS<F>) -> Vec<Issue> {
+
+    let constrained = determinism::constrained_wires(r1cs);
+    let determined = determinism::determined_wires(r1cs);
+    let conditionally_determined = determinism::conditionally_determined_wires(r1cs);
+
+    let assignments: HashMap<usize, & Assignment<F>> = circuit.assignments.iter()
+        .map(|assignment| (assignment.signal, assignment))
+        .collect();
+
+    let mut issues = Vec::new();
+
+    for (id, signal) in circuit.signals.iter().enumerate() {
+
+        let wire = circuit.wire_of(id);
+        let assignment = assignments.get(&id);
+
+        let output = signal.component == 0 && signal.direction == SignalDirection::Output;
+        let unconstrained_assignment = assignment.is_some_and(|assignment| !assignment.constrained);
+
+        let kind = if !constrained[wire] {
+            IssueKind::Unconstrained
+        } else if determined[wire] || !(output || unconstrained_assignment) {
+            continue ;
+        } else if conditionally_determined[wire] {
+            IssueKind::ConditionallyDetermined
+        } else if output {
+            IssueKind::UndeterminedOutput
+        } else {
+            IssueKind::UndeterminedAssignment
+        };
+
+        let location = match assignment {
+            Some(assignment) => circuit.location(assignment.component, assignment.span),
+            None => circuit.location(signal.component, signal.span)
+        };
+
+        issues.push(Issue {
+            kind,
+            signal: signal.name.clone(),
+            location
+        });
+
+    }
+
+    issues
+
+}
+
+#[cfg(test)]
+mod underconstrained_test {
+
+    use elaborator::{self, underconstrained};
+    use elaborator::underconstrained::IssueKind;
+    use field::bn254::Fr;
+    use parser::compile;
+
+    use galvanic_assert::matchers::*;
+
+    fn issues(file: & str) -> Vec<(IssueKind, std::string::String, std::string::String, std::string::String)> {
+
+        let path = std::fs::canonicalize(format!("./src/lib/parser/test_material/underconstrained/{}", file)).expect("Invalid Path");
+        let ctx = compile::build_context(&path);
+        let circuit = elaborator::elaborate::<Fr>(&ctx).expect("Elaboration failed");
+        let r1cs = circuit.r1cs().expect("Constraint generation failed");
+
+        underconstrained::find_issues(&circuit, &r1cs).into_iter()
+            .map(|issue| (issue.kind, issue.signal, issue.location.component, issue.location.source))
+            .collect()
+
+    }
+
+    fn owned(issues: Vec<(IssueKind, & str, & str, & str)>) -> Vec<(IssueKind, std::string::String, std::string::String, std::string::String)> {
+        issues.into_iter()
+            .map(|(kind, signal, component, source)| (kind, signal.to_string(), component.to_string(), source.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_find_issues() {
+
+        expect_that!(&issues("num2bits.circom").is_empty(), is(eq(true)));
+        expect_that!(&issues("is_zero.circom").is_empty(), is(eq(true)));
+        expect_that!(&issues("less_than.circom").is_empty(), is(eq(true)));
+
+        let expected = vec!(
+            (IssueKind::UndeterminedOutput, "main.out[0]", "main", "out[i] <-- (in >> i) & 1"),
+            (IssueKind::UndeterminedOutput, "main.out[1]", "main", "out[i] <-- (in >> i) & 1")
+        );
+        expect_that!(&owned(expected), is(eq(issues("num2bits_unsafe.circom"))));
+
+        let expected = vec!(
+            (IssueKind::UndeterminedOutput, "main.out", "main", "out <== root.out + in"),
+            (IssueKind::Unconstrained, "main.unused", "main", "signal unused"),
+            (IssueKind::Unconstrained, "main.free", "main", "free <-- in + 1"),
+            (IssueKind::UndeterminedAssignment, "main.root.out", "main.root", "out <-- in / 2")
+        );
+        expect_that!(&owned(expected), is(eq(issues("issues.circom"))));
+
+        let expected = vec!(
+            (IssueKind::ConditionallyDetermined, "main.out", "main", "out <== x + 1"),
+            (IssueKind::ConditionallyDetermined, "main.x", "main", "x <-- z / y")
+        );
+        expect_that!(&owned(expected), is(eq(issues("conditional.circom"))));
+
+    }
+
+}
@@ -0,0 +1,341 @@
+///
+/// Unsigned 256 bits integer, stored as 4 little endian 64 bits limbs.
+///
+/// Used as the canonical representation of field elements, and for the operations circom defines on
+/// the integer value of an element (comparisons, shifts, bitwise operators, integer division).
+///
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct U256(pub [u64; 4]);
+
+impl U256 {
+
+    pub const ZERO: U256 = U256([0, 0, 0, 0]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+
+    pub fn from_u64(value: u64) -> U256 {
+        U256([value, 0, 0, 0])
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0, 0, 0, 0]
+    }
+
+    ///
+    /// Returns the value of the bit at the given index, bit 0 being the least significant one.
+    ///
+    pub fn bit(&self, idx: usize) -> bool {
+        if idx >= 256 {
+            return false;
+        }
+        (self.0[idx / 64] >> (idx % 64)) & 1 == 1
+    }
+
+    ///
+    /// Number of significant bits.
+    ///
+    pub fn bits(&self) -> usize {
+        for limb in (0..4).rev() {
+            if self.0[limb] != 0 {
+                return limb * 64 + 64 - self.0[limb].leading_zeros() as usize;
+            }
+        }
+        0
+    }
+
+    pub fn overflowing_add(&self, other: & U256) -> (U256, bool) {
+
+        let mut result = [0u64; 4];
+        let mut carry = 0u128;
+
+        for (idx, limb) in result.iter_mut().enumerate() {
+            let sum = self.0[idx] as u128 + other.0[idx] as u128 + carry;
+            *limb = sum as u64;
+            carry = sum >> 64;
+        }
+
+        (U256(result), carry != 0)
+
+    }
+
+    pub fn overflowing_sub(&self, other: & U256) -> (U256, bool) {
+
+        let mut result = [0u64; 4];
+        let mut borrow = false;
+
+        for (idx, limb) in result.iter_mut().enumerate() {
+            let (diff, borrow_a) = self.0[idx].overflowing_sub(other.0[idx]);
+            let (diff, borrow_b) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = borrow_a || borrow_b;
+        }
+
+        (U256(result), borrow)
+
+    }
+
+    ///
+    /// Multiplies by a small value and adds another one, modulo `2^256`. The boolean is set on
+    /// overflow.
+    ///
+    pub fn overflowing_mul_add_u64(&self, mul: u64, add: u64) -> (U256, bool) {
+
+        let mut result = [0u64; 4];
+        let mut carry = add as u128;
+
+        for (idx, limb) in result.iter_mut().enumerate() {
+            let value = self.0[idx] as u128 * mul as u128 + carry;
+            *limb = value as u64;
+            carry = value >> 64;
+        }
+
+        (U256(result), carry != 0)
+
+    }
+
+    ///
+    /// Multiplies by a small value and adds another one, returns `None` on overflow.
+    ///
+    pub fn checked_mul_add_u64(&self, mul: u64, add: u64) -> Option<U256> {
+        match self.overflowing_mul_add_u64(mul, add) {
+            (result, false) => Some(result),
+            (_, true) => None
+        }
+    }
+
+    ///
+    /// Divides by a small value, returns the quotient and the remainder.
+    ///
+    pub fn divrem_u64(&self, divisor: u64) -> (U256, u64) {
+
+        let mut result = [0u64; 4];
+        let mut remainder = 0u128;
+
+        for idx in (0..4).rev() {
+            let value = (remainder << 64) | self.0[idx] as u128;
+            result[idx] = (value / divisor as u128) as u64;
+            remainder = value % divisor as u128;
+        }
+
+        (U256(result), remainder as u64)
+
+    }
+
+    ///
+    /// Euclidean division. Panics if `divisor` is zero.
+    ///
+    pub fn divrem(&self, divisor: & U256) -> (U256, U256) {
+
+        if divisor.is_zero() {
+            panic!("Division by zero");
+        }
+
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+
+        for idx in (0..self.bits()).rev() {
+            remainder = remainder.shl(1);
+            if self.bit(idx) {
+                remainder.0[0] |= 1;
+            }
+            if remainder >= *divisor {
+                remainder = remainder.overflowing_sub(divisor).0;
+                quotient.0[idx / 64] |= 1 << (idx % 64);
+            }
+        }
+
+        (quotient, remainder)
+
+    }
+
+    pub fn shl(&self, shift: usize) -> U256 {
+
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+
+        let limbs = shift / 64;
+        let bits = shift % 64;
+        let mut result = [0u64; 4];
+
+        for idx in (limbs..4).rev() {
+            result[idx] = self.0[idx - limbs] << bits;
+            if bits != 0 && idx > limbs {
+                result[idx] |= self.0[idx - limbs - 1] >> (64 - bits);
+            }
+        }
+
+        U256(result)
+
+    }
+
+    pub fn shr(&self, shift: usize) -> U256 {
+
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+
+        let limbs = shift / 64;
+        let bits = shift % 64;
+        let mut result = [0u64; 4];
+
+        for (idx, limb) in result.iter_mut().enumerate().take(4 - limbs) {
+            *limb = self.0[idx + limbs] >> bits;
+            if bits != 0 && idx + limbs + 1 < 4 {
+                *limb |= self.0[idx + limbs + 1] << (64 - bits);
+            }
+        }
+
+        U256(result)
+
+    }
+
+    pub fn and(&self, other: & U256) -> U256 {
+        U256([self.0[0] & other.0[0], self.0[1] & other.0[1], self.0[2] & other.0[2], self.0[3] & other.0[3]])
+    }
+
+    pub fn or(&self, other: & U256) -> U256 {
+        U256([self.0[0] | other.0[0], self.0[1] | other.0[1], self.0[2] | other.0[2], self.0[3] | other.0[3]])
+    }
+
+    pub fn xor(&self, other: & U256) -> U256 {
+        U256([self.0[0] ^ other.0[0], self.0[1] ^ other.0[1], self.0[2] ^ other.0[2], self.0[3] ^ other.0[3]])
+    }
+
+    pub fn not(&self) -> U256 {
+        U256([!self.0[0], !self.0[1], !self.0[2], !self.0[3]])
+    }
+
+    ///
+    /// Parses a decimal value, or an hexadecimal one when prefixed by `0x`. Returns `None` on invalid
+    /// characters or if the value does not fit in 256 bits.
+    ///
+    pub fn parse(value: & str) -> Option<U256> {
+
+        let (digits, radix) = if value.starts_with("0x") || value.starts_with("0X") {
+            (&value[2..], 16)
+        } else {
+            (value, 10)
+        };
+
+        if digits.is_empty() {
+            return None;
+        }
+
+        let mut result = U256::ZERO;
+
+        for character in digits.chars() {
+            let digit = character.to_digit(radix)?;
+            result = result.checked_mul_add_u64(radix as u64, digit as u64)?;
+        }
+
+        Some(result)
+
+    }
+
+    pub fn to_le_bytes(&self) -> [u8; 32] {
+
+        let mut bytes = [0u8; 32];
+
+        for (idx, limb) in self.0.iter().enumerate() {
+            bytes[idx * 8..idx * 8 + 8].copy_from_slice(&limb.to_le_bytes());
+        }
+
+        bytes
+
+    }
+
+    pub fn from_le_bytes(bytes: & [u8; 32]) -> U256 {
+
+        let mut limbs = [0u64; 4];
+
+        for (idx, limb) in limbs.iter_mut().enumerate() {
+            let mut limb_bytes = [0u8; 8];
+            limb_bytes.copy_from_slice(&bytes[idx * 8..idx * 8 + 8]);
+            *limb = u64::from_le_bytes(limb_bytes);
+        }
+
+        U256(limbs)
+
+    }
+
+    pub fn to_hex_string(&self) -> std::string::String {
+
+        let mut result = std::string::String::new();
+
+        for limb in self.0.iter().rev() {
+            result.push_str(&format!("{:016x}", limb));
+        }
+
+        let trimmed = result.trim_start_matches('0');
+
+        if trimmed.is_empty() {
+            "0".to_string()
+        } else {
+            trimmed.to_string()
+        }
+
+    }
+
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: & U256) -> std::cmp::Ordering {
+        for idx in (0..4).rev() {
+            match self.0[idx].cmp(&other.0[idx]) {
+                std::cmp::Ordering::Equal => continue,
+                ordering => return ordering
+            }
+        }
+        std::cmp::Ordering::Equal
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: & U256) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for U256 {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+
+        if self.is_zero() {
+            return write!(formatter, "0");
+        }
+
+        let mut digits: Vec<u8> = Vec::new();
+        let mut value = *self;
+
+        while !value.is_zero() {
+            let (quotient, remainder) = value.divrem_u64(10);
+            digits.push(b'0' + remainder as u8);
+            value = quotient;
+        }
+
+        digits.reverse();
+        write!(formatter, "{}", std::string::String::from_utf8(digits).unwrap())
+
+    }
+}
+
+///
+/// Serialized as a decimal string, JSON numbers cannot hold 256 bits integers.
+///
+impl serde::Serialize for U256 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for U256 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+        let value: std::string::String = serde::Deserialize::deserialize(deserializer)?;
+        U256::parse(&value).ok_or_else(|| serde::de::Error::custom(format!("invalid integer {:?}", value)))
+    }
+}
+
+impl std::fmt::Debug for U256 {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "U256({})", self)
+    }
+}
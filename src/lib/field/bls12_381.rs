@@ -0,0 +1,41 @@
+use field::fp::{Fp, FpParams};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FrParams;
+
+impl FpParams for FrParams {
+    const NAME: &'static str = "bls12381";
+    const MODULUS: [u64; 4] = [0xffffffff00000001, 0x53bda402fffe5bfe, 0x3339d80809a1d805, 0x73eda753299d7d48];
+    const R: [u64; 4] = [0x00000001fffffffe, 0x5884b7fa00034802, 0x998c4fefecbc4ff5, 0x1824b159acc5056f];
+    const R2: [u64; 4] = [0xc999e990f3f29c6d, 0x2b6cedcb87925c23, 0x05d314967254398f, 0x0748d9d99f59ff11];
+    const INV: u64 = 0xfffffffeffffffff;
+}
+
+///
+/// Scalar field of the BLS12-381 curve, circom's `bls12381` prime.
+///
+pub type Fr = Fp<FrParams>;
+
+#[cfg(test)]
+mod bls12_381_test {
+
+    use field::bls12_381::Fr;
+    use field::PrimeField;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_fr_arithmetic() {
+
+        let minus_one = Fr::parse("52435875175126190479447740508185965837690552500527637822603658699938581184512").unwrap();
+
+        expect_that!(&minus_one, is(eq(-Fr::one())));
+        expect_that!(&(minus_one * minus_one), is(eq(Fr::one())));
+        expect_that!(&(Fr::from_u64(6) * Fr::from_u64(7)).to_string(), is(eq("42".to_string())));
+
+        let value = Fr::parse("0x1234567890abcdef1234567890abcdef1234567890abcdef").unwrap();
+        expect_that!(&(value * value.inverse().unwrap()), is(eq(Fr::one())));
+
+    }
+
+}
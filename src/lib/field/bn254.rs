@@ -0,0 +1,79 @@
+use field::fp::{Fp, FpParams};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FrParams;
+
+impl FpParams for FrParams {
+    const NAME: &'static str = "bn128";
+    const MODULUS: [u64; 4] = [0x43e1f593f0000001, 0x2833e84879b97091, 0xb85045b68181585d, 0x30644e72e131a029];
+    const R: [u64; 4] = [0xac96341c4ffffffb, 0x36fc76959f60cd29, 0x666ea36f7879462e, 0x0e0a77c19a07df2f];
+    const R2: [u64; 4] = [0x1bb8e645ae216da7, 0x53fe3ab1e35c59e3, 0x8c49833d53bb8085, 0x0216d0b17f4e44a5];
+    const INV: u64 = 0xc2e1f593efffffff;
+}
+
+///
+/// Scalar field of the BN254 (alt_bn128) curve, the default circom prime.
+///
+pub type Fr = Fp<FrParams>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FqParams;
+
+impl FpParams for FqParams {
+    const NAME: &'static str = "bn128_base";
+    const MODULUS: [u64; 4] = [0x3c208c16d87cfd47, 0x97816a916871ca8d, 0xb85045b68181585d, 0x30644e72e131a029];
+    const R: [u64; 4] = [0xd35d438dc58f0d9d, 0x0a78eb28f5c70b3d, 0x666ea36f7879462c, 0x0e0a77c19a07df2f];
+    const R2: [u64; 4] = [0xf32cfc5b538afa89, 0xb5e71911d44501fb, 0x47ab1eff0a417ff6, 0x06d89f71cab8351f];
+    const INV: u64 = 0x87d20782e4866389;
+}
+
+///
+/// Base field of the BN254 curve, the field of the coordinates of its points.
+///
+pub type Fq = Fp<FqParams>;
+
+#[cfg(test)]
+mod bn254_test {
+
+    use field::bn254::Fr;
+    use field::bigint::U256;
+    use field::PrimeField;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_fr_arithmetic() {
+
+        let minus_one = Fr::parse("21888242871839275222246405745257275088548364400416034343698204186575808495616").unwrap();
+
+        expect_that!(&minus_one, is(eq(-Fr::one())));
+        expect_that!(&(minus_one + Fr::one()), is(eq(Fr::zero())));
+        expect_that!(&Fr::parse("-1").unwrap(), is(eq(minus_one)));
+        expect_that!(&(Fr::from_u64(6) * Fr::from_u64(7)).to_string(), is(eq("42".to_string())));
+        expect_that!(&(Fr::from_u64(3) - Fr::from_u64(5)), is(eq(-Fr::from_u64(2))));
+
+        let value = Fr::parse("0x1234567890abcdef1234567890abcdef").unwrap();
+        expect_that!(&(value * value.inverse().unwrap()), is(eq(Fr::one())));
+        expect_that!(&Fr::zero().inverse(), is(eq(None)));
+
+        expect_that!(&Fr::from_u64(2).pow(&U256::from_u64(10)), is(eq(Fr::from_u64(1024))));
+        expect_that!(&Fr::from_le_bytes(&value.to_le_bytes()), is(eq(Some(value))));
+        expect_that!(&Fr::from_le_bytes(&Fr::modulus().to_le_bytes()), is(eq(None)));
+
+    }
+
+    #[test]
+    fn test_u256_operations() {
+
+        let value = U256::parse("340282366920938463463374607431768211457").unwrap();
+
+        expect_that!(&value.to_string(), is(eq("340282366920938463463374607431768211457".to_string())));
+        expect_that!(&value.shr(128), is(eq(U256::from_u64(1))));
+        expect_that!(&U256::from_u64(1).shl(200).shr(199), is(eq(U256::from_u64(2))));
+        expect_that!(&value.divrem(&U256::from_u64(1000)).1, is(eq(U256::from_u64(457))));
+        expect_that!(&U256::parse("0xff").unwrap().and(&U256::from_u64(0x0f)), is(eq(U256::from_u64(0x0f))));
+        expect_that!(&U256::parse("0x1g"), is(eq(None)));
+
+    }
+
+}
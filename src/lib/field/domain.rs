@@ -0,0 +1,404 @@
+//
+// Multiplicative subgroups of size `2^k` of a prime field, over which polynomials are interpolated
+// and evaluated with the FFT.
+//
+// The roots of unity are the ones snarkjs uses: `w_k = g^((p - 1) / 2^k)` for `g` the smallest
+// quadratic non-residue, so that `w_(k+1)^2 = w_k`.
+//
+
+use field::bigint::U256;
+use field::PrimeField;
+use parallel;
+
+///
+/// Smallest number of values handled by a thread, below which the loops of the FFT are not split.
+///
+const MIN_CHUNK: usize = 1 << 10;
+
+///
+/// `s` such that `p - 1 = 2^s * t` with `t` odd.
+///
+pub fn two_adicity<F: PrimeField>() -> usize {
+
+    let (p_minus_one, _) = F::modulus().overflowing_sub(&U256::ONE);
+
+    (0..256).take_while(|idx| !p_minus_one.bit(*idx)).count()
+
+}
+
+///
+/// Smallest quadratic non-residue.
+///
+fn nonresidue<F: PrimeField>() -> F {
+
+    let (p_minus_one, _) = F::modulus().overflowing_sub(&U256::ONE);
+    let half = p_minus_one.shr(1);
+    let minus_one = -F::one();
+
+    let mut candidate = F::from_u64(2);
+    while candidate.pow(&half) != minus_one {
+        candidate += F::one();
+    }
+
+    candidate
+
+}
+
+///
+/// Primitive `2^log_size`-th root of unity, if the field has one.
+///
+pub fn root_of_unity<F: PrimeField>(log_size: usize) -> Option<F> {
+
+    if log_size > two_adicity::<F>() {
+        return None;
+    }
+
+    let (p_minus_one, _) = F::modulus().overflowing_sub(&U256::ONE);
+
+    Some(nonresidue::<F>().pow(&p_minus_one.shr(log_size)))
+
+}
+
+///
+/// Replaces each element by its inverse, with a single field inversion. Zeros are left as is.
+///
+pub fn batch_inverse<F: PrimeField>(values: & mut [F]) {
+
+    let mut products = Vec::with_capacity(values.len());
+    let mut product = F::one();
+
+    for value in values.iter() {
+        products.push(product);
+        if !value.is_zero() {
+            product *= *value;
+        }
+    }
+
+    let mut inverse = match product.inverse() {
+        Some(inverse) => inverse,
+        None => return
+    };
+
+    for (value, prefix) in values.iter_mut().zip(products).rev() {
+        if !value.is_zero() {
+            let next = inverse * *value;
+            *value = inverse * prefix;
+            inverse = next;
+        }
+    }
+
+}
+
+///
+/// Subgroup `{1, w, ..., w^(size - 1)}` generated by a primitive `size`-th root of unity.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Domain<F: PrimeField> {
+    pub size: usize,
+    pub log_size: usize,
+    pub omega: F,
+    pub omega_inverse: F,
+    pub size_inverse: F,
+
+    ///
+    /// Number of threads of the FFTs, `None` for the one of the provers (see `parallel`).
+    ///
+    pub threads: Option<usize>
+}
+
+impl<F: PrimeField> Domain<F> {
+
+    ///
+    /// Smallest domain of at least `min_size` elements, `None` if it exceeds the two-adicity of the
+    /// field.
+    ///
+    pub fn new(min_size: usize) -> Option<Domain<F>> {
+
+        let size = min_size.max(1).next_power_of_two();
+        let log_size = size.trailing_zeros() as usize;
+        let omega = root_of_unity::<F>(log_size)?;
+
+        Some(Domain {
+            size,
+            log_size,
+            omega,
+            omega_inverse: omega.inverse().unwrap(),
+            size_inverse: F::from_u64(size as u64).inverse().unwrap(),
+            threads: None
+        })
+
+    }
+
+    pub fn with_threads(self, threads: usize) -> Domain<F> {
+        Domain {
+            threads: Some(threads),
+            ..self
+        }
+    }
+
+    fn threads(&self) -> usize {
+        self.threads.unwrap_or_else(parallel::threads)
+    }
+
+    ///
+    /// `[1, w, ..., w^(size - 1)]`.
+    ///
+    pub fn elements(&self) -> Vec<F> {
+        powers(&self.omega, self.size)
+    }
+
+    ///
+    /// Evaluations at the domain elements of the polynomial of coefficients `values`, in place.
+    ///
+    pub fn fft(&self, values: & mut [F]) {
+        radix2(values, &self.omega, self.log_size, self.threads());
+    }
+
+    ///
+    /// Coefficients of the polynomial of evaluations `values`, in place.
+    ///
+    pub fn ifft(&self, values: & mut [F]) {
+        radix2(values, &self.omega_inverse, self.log_size, self.threads());
+        parallel::for_each_chunk_mut(self.threads(), values, MIN_CHUNK, |_, chunk| {
+            for value in chunk.iter_mut() {
+                *value *= self.size_inverse;
+            }
+        });
+    }
+
+    ///
+    /// Evaluations over the coset `shift * domain` of the polynomial of coefficients `values`.
+    ///
+    pub fn coset_fft(&self, values: & mut [F], shift: & F) {
+        scale_by_powers(values, shift, self.threads());
+        self.fft(values);
+    }
+
+    ///
+    /// Coefficients of the polynomial of evaluations `values` over the coset `shift * domain`.
+    ///
+    pub fn coset_ifft(&self, values: & mut [F], shift: & F) {
+        self.ifft(values);
+        scale_by_powers(values, &shift.inverse().unwrap(), self.threads());
+    }
+
+    ///
+    /// `x^size - 1`, the polynomial vanishing on the domain, evaluated at `x`.
+    ///
+    pub fn vanishing(&self, x: & F) -> F {
+        x.pow(&U256::from_u64(self.size as u64)) - F::one()
+    }
+
+    ///
+    /// Evaluations at `x` of the Lagrange polynomials of the domain, `L_i(x) = (x^n - 1) / n * w^i / (x - w^i)`.
+    ///
+    pub fn lagrange_coefficients(&self, x: & F) -> Vec<F> {
+
+        let elements = self.elements();
+
+        if let Some(idx) = elements.iter().position(|element| element == x) {
+            let mut coefficients = vec!(F::zero(); self.size);
+            coefficients[idx] = F::one();
+            return coefficients;
+        }
+
+        let mut coefficients: Vec<F> = elements.iter().map(|element| *x - *element).collect();
+        batch_inverse(& mut coefficients);
+
+        let factor = self.vanishing(x) * self.size_inverse;
+
+        coefficients.iter().zip(elements.iter()).map(|(inverse, element)| factor * *element * *inverse).collect()
+
+    }
+
+}
+
+///
+/// `[1, x, ..., x^(count - 1)]`.
+///
+pub fn powers<F: PrimeField>(x: & F, count: usize) -> Vec<F> {
+
+    let mut powers = Vec::with_capacity(count);
+    let mut power = F::one();
+
+    for _ in 0..count {
+        powers.push(power);
+        power *= *x;
+    }
+
+    powers
+
+}
+
+fn scale_by_powers<F: PrimeField>(values: & mut [F], x: & F, threads: usize) {
+    parallel::for_each_chunk_mut(threads, values, MIN_CHUNK, |offset, chunk| {
+        let mut power = x.pow(&U256::from_u64(offset as u64));
+        for value in chunk.iter_mut() {
+            *value *= power;
+            power *= *x;
+        }
+    });
+}
+
+///
+/// Permutes `2^log_size` values to the bit reversed order of their indices, as the iterative FFT
+/// expects them.
+///
+pub fn bit_reverse<T>(values: & mut [T], log_size: usize) {
+    for idx in 0..values.len() {
+        let reversed = if log_size == 0 { 0 } else { idx.reverse_bits() >> (usize::BITS as usize - log_size) };
+        if idx < reversed {
+            values.swap(idx, reversed);
+        }
+    }
+}
+
+fn butterflies<F: PrimeField>(even: & mut [F], odd: & mut [F], twiddles: & [F]) {
+    for ((even, odd), twiddle) in even.iter_mut().zip(odd.iter_mut()).zip(twiddles.iter()) {
+        let product = *odd * *twiddle;
+        *odd = *even - product;
+        *even += product;
+    }
+}
+
+///
+/// Iterative radix-2 Cooley-Tukey FFT: bit reversal, then butterflies of growing size.
+///
+/// The values are split once between the threads as a matrix of `rows` rows, `rows` being a power
+/// of two. The first stages only mix values of the same row, and run on whole rows. The last
+/// `log(rows)` ones pair values `half` apart, `half` being a multiple of the row length, so they
+/// only mix values of the same column, and run on ranges of columns.
+///
+fn radix2<F: PrimeField>(values: & mut [F], omega: & F, log_size: usize, threads: usize) {
+
+    let size = values.len();
+    assert_eq!(size, 1 << log_size, "FFT over a slice of the wrong size");
+
+    bit_reverse(values, log_size);
+
+    let mut rows = 1;
+    while 2 * rows <= threads && size / (2 * rows) >= MIN_CHUNK {
+        rows *= 2;
+    }
+    let width = size / rows;
+
+    // Twiddles of each stage, the one of `half` being the powers of a primitive `2 * half`-th root
+    let twiddles: Vec<Vec<F>> = (0..log_size)
+        .map(|stage| powers(&omega.pow(&U256::from_u64((size >> (stage + 1)) as u64)), 1 << stage))
+        .collect();
+
+    parallel::for_each_chunk_mut(threads, values, width, |_, chunk| {
+        let mut half = 1;
+        while half < width {
+            for block in chunk.chunks_mut(2 * half) {
+                let (even, odd) = block.split_at_mut(half);
+                butterflies(even, odd, &twiddles[half.trailing_zeros() as usize]);
+            }
+            half *= 2;
+        }
+    });
+
+    if rows == 1 {
+        return;
+    }
+
+    parallel::for_each_columns_mut(threads, values, rows, MIN_CHUNK / rows, |offset, columns| {
+        let mut half = 1;
+        while half < rows {
+            let twiddles = &twiddles[(half * width).trailing_zeros() as usize];
+            for start in (0..rows).step_by(2 * half) {
+                for row in start..start + half {
+                    let (even, odd) = columns.split_at_mut(row + half);
+                    butterflies(even[row], odd[0], &twiddles[(row - start) * width + offset..]);
+                }
+            }
+            half *= 2;
+        }
+    });
+
+}
+
+#[cfg(test)]
+mod domain_test {
+
+    use field::bn254::Fr;
+    use field::domain::{self, Domain};
+    use field::polynomial::evaluate;
+    use field::PrimeField;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_domain() {
+
+        expect_that!(&domain::two_adicity::<Fr>(), is(eq(28)));
+        expect_that!(&Domain::<Fr>::new(1 << 29), is(eq(None)));
+
+        let domain = Domain::<Fr>::new(5).unwrap();
+        expect_that!(&domain.size, is(eq(8)));
+        expect_that!(&domain.omega.pow(&::field::bigint::U256::from_u64(4)), is(eq(-Fr::one())));
+        expect_that!(&Domain::<Fr>::new(16).unwrap().omega.square(), is(eq(domain.omega)));
+
+        let coefficients: Vec<Fr> = (1..9).map(Fr::from_u64).collect();
+        let mut values = coefficients.clone();
+        domain.fft(& mut values);
+
+        let expected: Vec<Fr> = domain.elements().iter().map(|x| evaluate(&coefficients, x)).collect();
+        expect_that!(&values, is(eq(expected)));
+
+        domain.ifft(& mut values);
+        expect_that!(&values, is(eq(coefficients.clone())));
+
+        let shift = Fr::from_u64(7);
+        domain.coset_fft(& mut values, &shift);
+        expect_that!(&values[3], is(eq(evaluate(&coefficients, &(shift * domain.elements()[3])))));
+        domain.coset_ifft(& mut values, &shift);
+        expect_that!(&values, is(eq(coefficients.clone())));
+
+        let x = Fr::from_u64(123456789);
+        let mut evaluations = coefficients.clone();
+        domain.fft(& mut evaluations);
+        let interpolated = domain.lagrange_coefficients(&x).iter().zip(evaluations.iter())
+            .fold(Fr::zero(), |acc, (lagrange, evaluation)| acc + *lagrange * *evaluation);
+        expect_that!(&interpolated, is(eq(evaluate(&coefficients, &x))));
+
+        let mut inverses = vec!(Fr::from_u64(2), Fr::zero(), Fr::from_u64(5));
+        domain::batch_inverse(& mut inverses);
+        expect_that!(&inverses, is(eq(vec!(Fr::from_u64(2).inverse().unwrap(), Fr::zero(), Fr::from_u64(5).inverse().unwrap()))));
+
+    }
+
+
+    #[test]
+    fn test_parallel_fft() {
+
+        let coefficients: Vec<Fr> = (0..1 << 13).map(|idx| Fr::from_u64(idx * idx + 1)).collect();
+        let domain = Domain::<Fr>::new(1 << 13).unwrap().with_threads(1);
+        let mut expected = coefficients.clone();
+        domain.fft(& mut expected);
+
+        let elements = domain.elements();
+        for idx in &[0, 1, 1000, 4097, 8191] {
+            expect_that!(&expected[*idx], is(eq(evaluate(&coefficients, &elements[*idx]))));
+        }
+
+        // Up to 8 rows of 1024 values, with a number of threads which is not a power of two
+        for threads in &[2, 3, 8, 16] {
+
+            let domain = domain.clone().with_threads(*threads);
+
+            let mut values = coefficients.clone();
+            domain.fft(& mut values);
+            expect_that!(&values, is(eq(expected.clone())));
+
+            let shift = Fr::from_u64(5);
+            domain.coset_ifft(& mut values, &shift);
+            domain.coset_fft(& mut values, &shift);
+            domain.ifft(& mut values);
+            expect_that!(&values, is(eq(coefficients.clone())));
+
+        }
+
+    }
+
+}
@@ -0,0 +1,235 @@
+use field::bigint::U256;
+use field::PrimeField;
+
+///
+/// Constants describing a prime field of at most 256 bits, elements being stored in Montgomery form.
+///
+/// `R` is `2^256 mod MODULUS`, `R2` is `2^512 mod MODULUS` and `INV` is `-MODULUS^-1 mod 2^64`.
+///
+pub trait FpParams: 'static + Copy + Clone + std::fmt::Debug + PartialEq + Eq + std::hash::Hash + Send + Sync {
+    const NAME: &'static str;
+    const MODULUS: [u64; 4];
+    const R: [u64; 4];
+    const R2: [u64; 4];
+    const INV: u64;
+}
+
+///
+/// Element of the prime field described by `P`, in Montgomery form.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Fp<P: FpParams>([u64; 4], std::marker::PhantomData<P>);
+
+fn geq(left: & [u64; 4], right: & [u64; 4]) -> bool {
+    for idx in (0..4).rev() {
+        if left[idx] != right[idx] {
+            return left[idx] > right[idx];
+        }
+    }
+    true
+}
+
+fn sub_assign(left: & mut [u64; 4], right: & [u64; 4]) {
+    let mut borrow = false;
+    for idx in 0..4 {
+        let (diff, borrow_a) = left[idx].overflowing_sub(right[idx]);
+        let (diff, borrow_b) = diff.overflowing_sub(borrow as u64);
+        left[idx] = diff;
+        borrow = borrow_a || borrow_b;
+    }
+}
+
+impl<P: FpParams> Fp<P> {
+
+    ///
+    /// Montgomery multiplication (CIOS), computes `left * right * R^-1 mod MODULUS`.
+    ///
+    fn mont_mul(left: & [u64; 4], right: & [u64; 4]) -> [u64; 4] {
+
+        let mut t = [0u64; 6];
+
+        for left_limb in left.iter() {
+
+            let mut carry = 0u128;
+            for idx in 0..4 {
+                let value = t[idx] as u128 + *left_limb as u128 * right[idx] as u128 + carry;
+                t[idx] = value as u64;
+                carry = value >> 64;
+            }
+            let value = t[4] as u128 + carry;
+            t[4] = value as u64;
+            t[5] = (value >> 64) as u64;
+
+            let m = t[0].wrapping_mul(P::INV);
+            let value = t[0] as u128 + m as u128 * P::MODULUS[0] as u128;
+            let mut carry = value >> 64;
+            for idx in 1..4 {
+                let value = t[idx] as u128 + m as u128 * P::MODULUS[idx] as u128 + carry;
+                t[idx - 1] = value as u64;
+                carry = value >> 64;
+            }
+            let value = t[4] as u128 + carry;
+            t[3] = value as u64;
+            t[4] = t[5] + (value >> 64) as u64;
+
+        }
+
+        let mut result = [t[0], t[1], t[2], t[3]];
+        if t[4] != 0 || geq(&result, &P::MODULUS) {
+            sub_assign(& mut result, &P::MODULUS);
+        }
+
+        result
+
+    }
+
+    ///
+    /// Raw Montgomery representation, used by code that serializes elements in Montgomery form.
+    ///
+    pub fn to_montgomery_limbs(&self) -> [u64; 4] {
+        self.0
+    }
+
+    pub fn from_montgomery_limbs(limbs: [u64; 4]) -> Fp<P> {
+        Fp(limbs, std::marker::PhantomData)
+    }
+
+}
+
+impl<P: FpParams> PrimeField for Fp<P> {
+
+    const NAME: &'static str = P::NAME;
+
+    fn modulus() -> U256 {
+        U256(P::MODULUS)
+    }
+
+    fn zero() -> Fp<P> {
+        Fp([0, 0, 0, 0], std::marker::PhantomData)
+    }
+
+    fn one() -> Fp<P> {
+        Fp(P::R, std::marker::PhantomData)
+    }
+
+    fn from_u256(value: & U256) -> Fp<P> {
+
+        let mut reduced = value.0;
+        while geq(&reduced, &P::MODULUS) {
+            sub_assign(& mut reduced, &P::MODULUS);
+        }
+
+        Fp(Fp::<P>::mont_mul(&reduced, &P::R2), std::marker::PhantomData)
+
+    }
+
+    fn to_u256(&self) -> U256 {
+        U256(Fp::<P>::mont_mul(&self.0, &[1, 0, 0, 0]))
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == [0, 0, 0, 0]
+    }
+
+    fn square(&self) -> Fp<P> {
+        *self * *self
+    }
+
+    fn inverse(&self) -> Option<Fp<P>> {
+
+        if self.is_zero() {
+            return None;
+        }
+
+        let exponent = U256(P::MODULUS).overflowing_sub(&U256::from_u64(2)).0;
+        Some(self.pow(&exponent))
+
+    }
+
+}
+
+impl<P: FpParams> std::ops::Add for Fp<P> {
+    type Output = Fp<P>;
+
+    fn add(self, other: Fp<P>) -> Fp<P> {
+
+        let mut result = [0u64; 4];
+        let mut carry = 0u128;
+
+        for (idx, limb) in result.iter_mut().enumerate() {
+            let sum = self.0[idx] as u128 + other.0[idx] as u128 + carry;
+            *limb = sum as u64;
+            carry = sum >> 64;
+        }
+
+        if carry != 0 || geq(&result, &P::MODULUS) {
+            sub_assign(& mut result, &P::MODULUS);
+        }
+
+        Fp(result, std::marker::PhantomData)
+
+    }
+}
+
+impl<P: FpParams> std::ops::Sub for Fp<P> {
+    type Output = Fp<P>;
+
+    fn sub(self, other: Fp<P>) -> Fp<P> {
+        self + (-other)
+    }
+}
+
+impl<P: FpParams> std::ops::Neg for Fp<P> {
+    type Output = Fp<P>;
+
+    fn neg(self) -> Fp<P> {
+
+        if self.is_zero() {
+            return self;
+        }
+
+        let mut result = P::MODULUS;
+        sub_assign(& mut result, &self.0);
+
+        Fp(result, std::marker::PhantomData)
+
+    }
+}
+
+impl<P: FpParams> std::ops::Mul for Fp<P> {
+    type Output = Fp<P>;
+
+    fn mul(self, other: Fp<P>) -> Fp<P> {
+        Fp(Fp::<P>::mont_mul(&self.0, &other.0), std::marker::PhantomData)
+    }
+}
+
+impl<P: FpParams> std::ops::AddAssign for Fp<P> {
+    fn add_assign(& mut self, other: Fp<P>) {
+        *self = *self + other;
+    }
+}
+
+impl<P: FpParams> std::ops::SubAssign for Fp<P> {
+    fn sub_assign(& mut self, other: Fp<P>) {
+        *self = *self - other;
+    }
+}
+
+impl<P: FpParams> std::ops::MulAssign for Fp<P> {
+    fn mul_assign(& mut self, other: Fp<P>) {
+        *self = *self * other;
+    }
+}
+
+impl<P: FpParams> std::fmt::Display for Fp<P> {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "{}", self.to_u256())
+    }
+}
+
+impl<P: FpParams> std::fmt::Debug for Fp<P> {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "Fp({})", self.to_u256())
+    }
+}
@@ -0,0 +1,110 @@
+pub mod bigint;
+pub mod fp;
+pub mod bn254;
+pub mod bls12_381;
+pub mod domain;
+pub mod polynomial;
+
+use field::bigint::U256;
+
+///
+/// Prime field used to express constraints and signal values.
+///
+/// Elements are always reduced, `to_u256` returns the canonical integer in `[0, p)`.
+///
+pub trait PrimeField:
+    Copy + Clone + PartialEq + Eq + std::hash::Hash + std::fmt::Debug + std::fmt::Display + Send + Sync +
+    std::ops::Add<Output = Self> + std::ops::Sub<Output = Self> + std::ops::Mul<Output = Self> + std::ops::Neg<Output = Self> +
+    std::ops::AddAssign + std::ops::SubAssign + std::ops::MulAssign
+{
+
+    ///
+    /// Name of the prime, as used by circom (`bn128`, ...).
+    ///
+    const NAME: &'static str;
+
+    fn modulus() -> U256;
+
+    fn zero() -> Self;
+
+    fn one() -> Self;
+
+    ///
+    /// Builds an element from an integer, reducing it modulo `p`.
+    ///
+    fn from_u256(value: & U256) -> Self;
+
+    fn to_u256(&self) -> U256;
+
+    fn is_zero(&self) -> bool;
+
+    fn square(&self) -> Self;
+
+    fn inverse(&self) -> Option<Self>;
+
+    fn from_u64(value: u64) -> Self {
+        Self::from_u256(&U256::from_u64(value))
+    }
+
+    fn is_one(&self) -> bool {
+        *self == Self::one()
+    }
+
+    fn double(&self) -> Self {
+        *self + *self
+    }
+
+    fn pow(&self, exponent: & U256) -> Self {
+
+        let mut result = Self::one();
+
+        for idx in (0..exponent.bits()).rev() {
+            result = result.square();
+            if exponent.bit(idx) {
+                result *= *self;
+            }
+        }
+
+        result
+
+    }
+
+    ///
+    /// Parses a decimal value, an hexadecimal value prefixed by `0x`, or a negative decimal value.
+    /// Values are reduced modulo `p`.
+    ///
+    fn parse(value: & str) -> Option<Self> {
+
+        let value = value.trim();
+
+        if let Some(stripped) = value.strip_prefix('-') {
+            U256::parse(stripped).map(|parsed| -Self::from_u256(&parsed))
+        } else {
+            U256::parse(value).map(|parsed| Self::from_u256(&parsed))
+        }
+
+    }
+
+    ///
+    /// Canonical little endian encoding, as used by the iden3 binary formats.
+    ///
+    fn to_le_bytes(&self) -> [u8; 32] {
+        self.to_u256().to_le_bytes()
+    }
+
+    ///
+    /// Decodes a canonical little endian encoding. Returns `None` if the value is not reduced.
+    ///
+    fn from_le_bytes(bytes: & [u8; 32]) -> Option<Self> {
+
+        let value = U256::from_le_bytes(bytes);
+
+        if value >= Self::modulus() {
+            None
+        } else {
+            Some(Self::from_u256(&value))
+        }
+
+    }
+
+}
@@ -0,0 +1,53 @@
+//
+// Polynomials in coefficient form, the constant coefficient first.
+//
+
+use field::PrimeField;
+
+pub fn evaluate<F: PrimeField>(coefficients: & [F], x: & F) -> F {
+    coefficients.iter().rev().fold(F::zero(), |acc, coefficient| acc * *x + *coefficient)
+}
+
+///
+/// Quotient of the division by `X - z`, the remainder `p(z)` being dropped.
+///
+pub fn divide_by_linear<F: PrimeField>(coefficients: & [F], z: & F) -> Vec<F> {
+
+    if coefficients.is_empty() {
+        return Vec::new();
+    }
+
+    let mut quotient = vec!(F::zero(); coefficients.len() - 1);
+    let mut carry = F::zero();
+
+    for idx in (1..coefficients.len()).rev() {
+        carry = coefficients[idx] + carry * *z;
+        quotient[idx - 1] = carry;
+    }
+
+    quotient
+
+}
+
+#[cfg(test)]
+mod polynomial_test {
+
+    use field::bn254::Fr;
+    use field::polynomial;
+    use field::PrimeField;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_divide_by_linear() {
+
+        // (X^2 + 2X + 3)(X - 5) + 7
+        let coefficients: Vec<Fr> = [-Fr::from_u64(8), -Fr::from_u64(7), -Fr::from_u64(3), Fr::one()].to_vec();
+        let z = Fr::from_u64(5);
+
+        expect_that!(&polynomial::evaluate(&coefficients, &z), is(eq(Fr::from_u64(7))));
+        expect_that!(&polynomial::divide_by_linear(&coefficients, &z), is(eq(vec!(Fr::from_u64(3), Fr::from_u64(2), Fr::one()))));
+
+    }
+
+}
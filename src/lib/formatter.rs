@@ -0,0 +1,1109 @@
+use parser::ast::{self, Rule};
+use parser::ast::tokens::RootToken;
+use parser::cst::Cst;
+use parser::matchers::{self, MatchError};
+use parser::typed_ast::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOptions {
+
+    ///
+    /// Text used for one level of indentation.
+    ///
+    pub indent: std::string::String
+
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        FormatOptions {
+            indent: "    ".to_string()
+        }
+    }
+}
+
+///
+/// Precedence of an expression, following the `E_0` to `E_20` levels of the grammar. Operands with
+/// a lower precedence than required by their position get parentheses.
+///
+fn precedence(expression: & Expression) -> usize {
+    match expression {
+        Expression::Sequence { expressions, .. } if !expressions.is_empty() => 0,
+        Expression::Constraint { .. } => 1,
+        Expression::SignalAssignment { reversed: false, .. } => 2,
+        Expression::SignalAssignment { reversed: true, .. } => 3,
+        Expression::Assignment { .. } => 4,
+        Expression::Ternary { .. } => 5,
+        Expression::Binary { operator, .. } => binary_precedence(*operator),
+        Expression::Prefix { .. } => 17,
+        Expression::Postfix { .. } => 18,
+        Expression::Member { .. } | Expression::Call { .. } | Expression::Index { .. } => 19,
+        Expression::Number { .. } | Expression::Variable { .. } | Expression::Array { .. } | Expression::Sequence { .. } => 21
+    }
+}
+
+fn binary_precedence(operator: BinaryOperator) -> usize {
+    match operator {
+        BinaryOperator::Or => 6,
+        BinaryOperator::And => 7,
+        BinaryOperator::BitOr => 8,
+        BinaryOperator::BitXor => 9,
+        BinaryOperator::BitAnd => 10,
+        BinaryOperator::Equal | BinaryOperator::NotEqual => 11,
+        BinaryOperator::Lesser | BinaryOperator::LesserEqual | BinaryOperator::Greater | BinaryOperator::GreaterEqual => 12,
+        BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => 13,
+        BinaryOperator::Add | BinaryOperator::Sub => 14,
+        BinaryOperator::Mul | BinaryOperator::Div | BinaryOperator::IntDiv | BinaryOperator::Mod => 15,
+        BinaryOperator::Pow => 16
+    }
+}
+
+fn binary_symbol(operator: BinaryOperator) -> & 'static str {
+    match operator {
+        BinaryOperator::Or => "||",
+        BinaryOperator::And => "&&",
+        BinaryOperator::BitOr => "|",
+        BinaryOperator::BitXor => "^",
+        BinaryOperator::BitAnd => "&",
+        BinaryOperator::Equal => "==",
+        BinaryOperator::NotEqual => "!=",
+        BinaryOperator::Lesser => "<",
+        BinaryOperator::LesserEqual => "<=",
+        BinaryOperator::Greater => ">",
+        BinaryOperator::GreaterEqual => ">=",
+        BinaryOperator::ShiftLeft => "<<",
+        BinaryOperator::ShiftRight => ">>",
+        BinaryOperator::Add => "+",
+        BinaryOperator::Sub => "-",
+        BinaryOperator::Mul => "*",
+        BinaryOperator::Div => "/",
+        BinaryOperator::IntDiv => "\\",
+        BinaryOperator::Mod => "%",
+        BinaryOperator::Pow => "**"
+    }
+}
+
+fn prefix_symbol(operator: PrefixOperator) -> & 'static str {
+    match operator {
+        PrefixOperator::Not => "!",
+        PrefixOperator::Complement => "~",
+        PrefixOperator::Increment => "++",
+        PrefixOperator::Decrement => "--",
+        PrefixOperator::Plus => "+",
+        PrefixOperator::Minus => "-"
+    }
+}
+
+fn postfix_symbol(operator: PostfixOperator) -> & 'static str {
+    match operator {
+        PostfixOperator::Increment => "++",
+        PostfixOperator::Decrement => "--"
+    }
+}
+
+fn assignment_symbol(operator: AssignmentOperator) -> & 'static str {
+    match operator {
+        AssignmentOperator::Assign => "=",
+        AssignmentOperator::Add => "+=",
+        AssignmentOperator::Sub => "-=",
+        AssignmentOperator::Mul => "*=",
+        AssignmentOperator::Div => "/=",
+        AssignmentOperator::Mod => "%=",
+        AssignmentOperator::ShiftLeft => "<<=",
+        AssignmentOperator::ShiftRight => ">>=",
+        AssignmentOperator::BitAnd => "&=",
+        AssignmentOperator::BitXor => "^=",
+        AssignmentOperator::BitOr => "|="
+    }
+}
+
+const MAX_ARRAY_WIDTH: usize = 100;
+
+///
+/// True if the text spans at least one empty line.
+///
+fn has_blank_line(text: & str) -> bool {
+    let lines: Vec<& str> = text.split('\n').collect();
+    lines.len() > 2 && lines[1..lines.len() - 1].iter().any(|line| line.trim().is_empty())
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    options: &'a FormatOptions,
+    comments: Vec<(Span, &'a str)>,
+    next_comment: usize,
+    output: std::string::String,
+    depth: usize,
+    last_end: usize,
+    at_block_start: bool,
+
+    // The next line continues the current one, as `else` after `}`
+    continued: bool,
+
+    // Arrays being written over several lines
+    nesting: std::cell::Cell<usize>,
+
+    // Comments written inside a line, by index in `comments`
+    inlined: std::cell::RefCell<std::collections::HashSet<usize>>
+}
+
+impl<'a> Formatter<'a> {
+
+    ///
+    /// Position of the first character after `from` that is neither blank nor in a comment.
+    ///
+    fn next_token(&self, from: usize, to: usize) -> usize {
+        let mut token = from;
+        while token < to {
+            token += self.source[token..to].len() - self.source[token..to].trim_start().len();
+            match self.comments.iter().find(|((start, _), _)| *start == token) {
+                Some(((_, end), _)) => token = *end,
+                None => break
+            }
+        }
+        token.min(to)
+    }
+
+    ///
+    /// Block comments between `from` and `to` that fit in a line, each marked as written. The
+    /// ones before the first token found in between are returned apart from the ones after it, so
+    /// that they stay on the same side of the operator separating two operands.
+    ///
+    fn gap(&self, from: usize, to: usize) -> (std::string::String, std::string::String) {
+
+        let mut before = std::string::String::new();
+        let mut after = std::string::String::new();
+
+        if from >= to {
+            return (before, after);
+        }
+
+        let token = self.next_token(from, to);
+
+        for (index, ((start, end), comment)) in self.comments.iter().enumerate() {
+            if *start >= from && *end <= to && comment.starts_with("/*") && !comment.contains('\n') {
+                self.inlined.borrow_mut().insert(index);
+                if *start < token {
+                    before.push(' ');
+                    before.push_str(comment);
+                } else {
+                    after.push_str(comment);
+                    after.push(' ');
+                }
+            }
+        }
+
+        (before, after)
+
+    }
+
+    ///
+    /// Block comments between `from` and `to` that fit in a line, ending the text before them.
+    ///
+    fn trailing(&self, from: usize, to: usize) -> std::string::String {
+        let (before, after) = self.gap(from, to);
+        match after.is_empty() {
+            true => before,
+            false => format!("{} {}", before, after.trim_end())
+        }
+    }
+
+    fn expression(&self, expression: & Expression, minimum: usize) -> std::string::String {
+
+        let text = match expression {
+            Expression::Number { span, .. } => self.source[span.0..span.1].trim().to_string(),
+            Expression::Variable { name, .. } => name.clone(),
+            Expression::Array { elements, span } => self.array(elements, *span),
+            Expression::Prefix { operator, operand, span } => {
+                let (_, after) = self.gap(span.0, operand.span().0);
+                format!("{}{}{}", prefix_symbol(*operator), after, self.expression(operand, 18))
+            },
+            Expression::Postfix { operator, operand, span } => {
+                format!("{}{}{}", self.expression(operand, 18), self.trailing(operand.span().1, span.1), postfix_symbol(*operator))
+            },
+            Expression::Binary { operator, left, right, .. } => {
+                let level = binary_precedence(*operator);
+                self.operation(left, level, binary_symbol(*operator), right, level + 1)
+            },
+            Expression::Ternary { condition, if_true, if_false, .. } => {
+                let (before_first, after_first) = self.gap(condition.span().1, if_true.span().0);
+                let (before_second, after_second) = self.gap(if_true.span().1, if_false.span().0);
+                format!("{}{} ? {}{}{} : {}{}", self.expression(condition, 6), before_first, after_first, self.expression(if_true, 6), before_second, after_second, self.expression(if_false, 6))
+            },
+            Expression::Assignment { operator, target, value, .. } => self.operation(target, 5, assignment_symbol(*operator), value, 5),
+            Expression::SignalAssignment { operator, reversed: false, target, value, .. } => {
+                let symbol = if *operator == SignalOperator::ConstrainedAssign { "<==" } else { "<--" };
+                self.operation(target, 3, symbol, value, 3)
+            },
+            Expression::SignalAssignment { operator, reversed: true, target, value, .. } => {
+                let symbol = if *operator == SignalOperator::ConstrainedAssign { "==>" } else { "-->" };
+                self.operation(value, 3, symbol, target, 4)
+            },
+            Expression::Constraint { left, right, .. } => self.operation(left, 1, "===", right, 2),
+            Expression::Sequence { expressions, .. } if expressions.is_empty() => "()".to_string(),
+            Expression::Sequence { expressions, span } => self.list(expressions, span.1),
+            Expression::Call { callee, arguments, span } => {
+                let open = self.next_token(callee.span().1, span.1);
+                let spans: Vec<Span> = arguments.iter().map(Expression::span).collect();
+                let (before, after) = self.gap(callee.span().1, arguments.first().map_or(span.1, |argument| argument.span().0));
+                let arguments = if self.needs_lines(open, span.1, &spans) {
+                    self.nesting.set(self.nesting.get() + 1);
+                    let items: Vec<(Span, std::string::String)> = arguments.iter().map(|argument| (argument.span(), self.expression(argument, 1))).collect();
+                    self.nesting.set(self.nesting.get() - 1);
+                    self.lines_list(open, span.1, &items)
+                } else if arguments.is_empty() {
+                    after.trim_end().to_string()
+                } else {
+                    format!("{}{}", after, self.list(arguments, span.1))
+                };
+                format!("{}{}({})", self.expression(callee, 19), before, arguments)
+            },
+            Expression::Index { target, index, span } => {
+                let (before, after) = self.gap(target.span().1, index.span().0);
+                let index = match index.as_ref() {
+                    Expression::Sequence { expressions, .. } => self.list(expressions, span.1),
+                    index => format!("{}{}", self.expression(index, 1), self.trailing(index.span().1, span.1))
+                };
+                format!("{}{}[{}{}]", self.expression(target, 19), before, after, index)
+            },
+            Expression::Member { target, member, span } => {
+                let (before, after) = self.gap(target.span().1, span.1 - member.len());
+                format!("{}{}.{}{}", self.expression(target, 19), before, after, member)
+            }
+        };
+
+        if precedence(expression) < minimum {
+            format!("({})", text)
+        } else {
+            text
+        }
+
+    }
+
+    fn operation(&self, left: & Expression, left_minimum: usize, symbol: & str, right: & Expression, right_minimum: usize) -> std::string::String {
+        let (before, after) = self.gap(left.span().1, right.span().0);
+        format!("{}{} {} {}{}", self.expression(left, left_minimum), before, symbol, after, self.expression(right, right_minimum))
+    }
+
+    ///
+    /// Arrays longer than a line get one element per line.
+    ///
+    fn array(&self, elements: & [Expression], span: Span) -> std::string::String {
+
+        let (_, after) = self.gap(span.0, elements.first().map_or(span.1, |element| element.span().0));
+
+        let flat = format!("[{}{}]", after, self.list(elements, span.1));
+        if flat.len() <= MAX_ARRAY_WIDTH || elements.is_empty() {
+            return match elements.is_empty() {
+                true => format!("[{}]", after.trim_end()),
+                false => flat
+            };
+        }
+
+        self.nesting.set(self.nesting.get() + 1);
+        let indentation = self.options.indent.repeat(self.depth + self.nesting.get());
+        let mut lines = self.items(elements, span.1);
+        lines[0] = format!("{}{}", after, lines[0]);
+        let lines: Vec<std::string::String> = lines.into_iter().map(|line| format!("{}{}", indentation, line)).collect();
+        self.nesting.set(self.nesting.get() - 1);
+
+        format!("[\n{}\n{}]", lines.join(",\n"), self.options.indent.repeat(self.depth + self.nesting.get()))
+
+    }
+
+    ///
+    /// Texts of comma separated expressions, ending at `end`, with the comments between them.
+    ///
+    fn items(&self, expressions: & [Expression], end: usize) -> Vec<std::string::String> {
+
+        let mut items: Vec<std::string::String> = Vec::new();
+        let mut prefix = std::string::String::new();
+
+        for (index, expression) in expressions.iter().enumerate() {
+            let next = expressions.get(index + 1).map_or(end, |next| next.span().0);
+            let (before, after) = self.gap(expression.span().1, next);
+            items.push(format!("{}{}{}", prefix, self.expression(expression, 1), before));
+            prefix = after;
+        }
+
+        items
+
+    }
+
+    fn list(&self, expressions: & [Expression], end: usize) -> std::string::String {
+        self.items(expressions, end).join(", ")
+    }
+
+    ///
+    /// Indices of the comments between `open` and `close` that are outside of the items.
+    ///
+    fn list_comments(&self, open: usize, close: usize, items: & [Span]) -> Vec<usize> {
+        (0..self.comments.len())
+            .filter(|index| {
+                let (start, end) = self.comments[*index].0;
+                start > open && end <= close && !items.iter().any(|item| start >= item.0 && end <= item.1)
+            })
+            .collect()
+    }
+
+    ///
+    /// Whether a list has comments that cannot be written inside a line.
+    ///
+    fn needs_lines(&self, open: usize, close: usize, items: & [Span]) -> bool {
+        self.list_comments(open, close, items).into_iter()
+            .any(|index| !self.comments[index].1.starts_with("/*") || self.comments[index].1.contains('\n'))
+    }
+
+    ///
+    /// Comma separated list between `open` and `close` written over several lines, one item per
+    /// line. A comment on the line of the item or comment before it in the source follows it, a
+    /// block comment on the line of the next item precedes it, the others get their own line.
+    ///
+    fn lines_list(&self, open: usize, close: usize, items: & [(Span, std::string::String)]) -> std::string::String {
+
+        let spans: Vec<Span> = items.iter().map(|(span, _)| *span).collect();
+        let comments = self.list_comments(open, close, &spans);
+
+        let mut lines: Vec<std::string::String> = Vec::new();
+        let mut last_end = open;
+        let mut line_open = false;
+        let mut next_comment = 0;
+
+        for index in 0..=items.len() {
+
+            let until = spans.get(index).map_or(close, |span| span.0);
+            let mut prefix = std::string::String::new();
+
+            while let Some(&comment_index) = comments.get(next_comment).filter(|comment_index| self.comments[**comment_index].0.0 < until) {
+                let ((start, end), comment) = self.comments[comment_index];
+                self.inlined.borrow_mut().insert(comment_index);
+                let block = comment.starts_with("/*");
+                match lines.last_mut() {
+                    Some(line) if line_open && prefix.is_empty() && !self.source[last_end..start].contains('\n') => {
+                        line.push(' ');
+                        line.push_str(comment);
+                    },
+                    _ if block && index < items.len() && !self.source[end..until].contains('\n') => {
+                        prefix.push_str(comment);
+                        prefix.push(' ');
+                    },
+                    _ => lines.push(comment.to_string())
+                }
+                line_open = block;
+                last_end = end;
+                next_comment += 1;
+            }
+
+            if let Some((span, text)) = items.get(index) {
+                let separator = if index + 1 < items.len() { "," } else { "" };
+                lines.push(format!("{}{}{}", prefix, text, separator));
+                last_end = span.1;
+                line_open = true;
+            }
+
+        }
+
+        let indentation = self.options.indent.repeat(self.depth + self.nesting.get() + 1);
+        let lines: Vec<std::string::String> = lines.into_iter().map(|line| format!("{}{}", indentation, line)).collect();
+
+        format!("\n{}\n{}", lines.join("\n"), self.options.indent.repeat(self.depth + self.nesting.get()))
+
+    }
+
+    fn dimensions(&self, dimensions: & [Expression]) -> std::string::String {
+        dimensions.iter().map(|dimension| format!("[{}]", self.expression(dimension, 0))).collect()
+    }
+
+    fn declaration(&self, declaration: & Declaration) -> std::string::String {
+
+        let mut text = match declaration.kind {
+            DeclarationKind::Variable => "var ".to_string(),
+            DeclarationKind::Component => "component ".to_string(),
+            DeclarationKind::Signal { private, direction } => format!("signal {}{}",
+                if private { "private " } else { "" },
+                match direction {
+                    SignalDirection::Input => "input ",
+                    SignalDirection::Output => "output ",
+                    SignalDirection::Intermediate => ""
+                })
+        };
+
+        if let Some(constructor) = &declaration.constructor {
+            text.push_str(&self.expression(constructor, 19));
+            text.push(' ');
+        }
+
+        text.push_str(&declaration.name);
+        text.push_str(&self.dimensions(&declaration.dimensions));
+
+        if let Some(value) = &declaration.value {
+            let (_, after) = self.gap(self.source[..value.span().0].rfind('=').unwrap_or(value.span().0), value.span().0);
+            text.push_str(" = ");
+            text.push_str(&after);
+            text.push_str(&self.expression(value, 0));
+        }
+
+        text
+
+    }
+
+    fn indent(& mut self) {
+        if self.continued {
+            self.continued = false;
+            self.output.push(' ');
+        } else {
+            for _ in 0..self.depth {
+                self.output.push_str(&self.options.indent);
+            }
+        }
+    }
+
+    ///
+    /// Writes a line, followed by the comment that is on the same line in the source, if any.
+    ///
+    fn line(& mut self, text: & str, end: usize) {
+
+        self.indent();
+        self.output.push_str(text);
+        self.last_end = end;
+        self.at_block_start = false;
+        self.skip_inlined();
+
+        if let Some(&((start, comment_end), comment)) = self.comments.get(self.next_comment) {
+            if start >= self.last_end && self.source[self.last_end..start].chars().all(|character| character == ';' || (character != '\n' && character.is_whitespace())) && !comment.contains('\n') {
+                self.output.push(' ');
+                self.output.push_str(comment);
+                self.last_end = comment_end;
+                self.next_comment += 1;
+            }
+        }
+
+        self.output.push('\n');
+
+    }
+
+    ///
+    /// Separates the next element from the previous one by an empty line if there is one in the
+    /// source.
+    ///
+    fn separate(& mut self, start: usize) {
+        if !self.at_block_start && start >= self.last_end && has_blank_line(&self.source[self.last_end..start]) {
+            self.output.push('\n');
+        }
+    }
+
+    ///
+    /// Writes the comments starting before `position` on their own lines.
+    ///
+    fn comments_before(& mut self, position: usize) {
+
+        self.skip_inlined();
+
+        while let Some(&((start, end), comment)) = self.comments.get(self.next_comment) {
+
+            if start >= position {
+                break ;
+            }
+
+            self.separate(start);
+            self.indent();
+            self.output.push_str(comment);
+            self.output.push('\n');
+            self.last_end = end;
+            self.at_block_start = false;
+            self.next_comment += 1;
+            self.skip_inlined();
+
+        }
+
+    }
+
+    fn skip_inlined(& mut self) {
+        while self.inlined.borrow().contains(&self.next_comment) {
+            self.next_comment += 1;
+        }
+    }
+
+    fn open_block(& mut self, start: usize) {
+        self.depth += 1;
+        self.at_block_start = true;
+        self.last_end = start;
+    }
+
+    fn close_block(& mut self, end: usize) {
+        self.comments_before(end);
+        self.depth -= 1;
+    }
+
+    fn statements(& mut self, statements: & [Statement]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    ///
+    /// Writes a block as `prefix{`, its statements and `}suffix`, the closing line being left open
+    /// when `suffix` is `None` so that `else` can follow.
+    ///
+    fn block(& mut self, prefix: & str, span: Span, statements: & [Statement], suffix: Option<& str>) {
+
+        self.skip_inlined();
+
+        if statements.is_empty() && self.comments.get(self.next_comment).is_none_or(|((start, _), _)| *start >= span.1) {
+            match suffix {
+                Some(suffix) => self.line(&format!("{}{{}}{}", prefix, suffix), span.1),
+                None => {
+                    self.indent();
+                    self.output.push_str(&format!("{}{{}}", prefix));
+                }
+            }
+            return ;
+        }
+
+        self.line(&format!("{}{{", prefix), span.0 + 1);
+        self.open_block(span.0 + 1);
+        self.statements(statements);
+        self.close_block(span.1.saturating_sub(1));
+
+        match suffix {
+            Some(suffix) => self.line(&format!("}}{}", suffix), span.1),
+            None => {
+                self.indent();
+                self.output.push('}');
+            }
+        }
+
+    }
+
+    ///
+    /// Writes the body of a control statement after its header. Return, break and continue
+    /// statements do not include their `;`, which is parsed as an empty statement: it cannot be
+    /// written when `else` or the `while` of a do-while loop follows, hence `terminated`.
+    ///
+    fn body(& mut self, header: & str, body: & Statement, suffix: & str, terminated: bool) {
+        match body {
+            Statement::Block { span, statements } => self.block(&format!("{} ", header), *span, statements, Some(suffix)),
+            statement => {
+                let text = self.simple_statement(statement, terminated);
+                match text {
+                    Some(text) => self.line(&format!("{} {}{}", header, text, suffix), statement.span().1),
+                    None => {
+                        self.line(header, statement.span().0);
+                        self.depth += 1;
+                        self.statement(statement);
+                        self.depth -= 1;
+                        if !suffix.is_empty() {
+                            self.line(suffix.trim_start(), statement.span().1);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    ///
+    /// Text of the statements that fit on a line.
+    ///
+    fn simple_statement(&self, statement: & Statement, terminated: bool) -> Option<std::string::String> {
+        let end = if terminated { ";" } else { "" };
+        match statement {
+            Statement::Declaration(declaration) => {
+                let text = self.declaration(declaration);
+                let last = declaration.value.iter().chain(declaration.dimensions.last()).map(|expression| expression.span().1).max();
+                Some(format!("{}{};", text, last.map(|last| self.trailing(last, declaration.span.1)).unwrap_or_default()))
+            },
+            Statement::Expression { expression, span } => Some(format!("{}{};", self.expression(expression, 0), self.trailing(expression.span().1, span.1))),
+            Statement::Return { value, span } => Some(format!("return {}{}{}", self.expression(value, 0), self.trailing(value.span().1, span.1), end)),
+            Statement::Break { .. } => Some(format!("break{}", end)),
+            Statement::Continue { .. } => Some(format!("continue{}", end)),
+            _ => None
+        }
+    }
+
+    ///
+    /// Text of a condition between parentheses, the comments up to `next` included.
+    ///
+    fn condition(&self, condition: & Expression, next: usize) -> std::string::String {
+        let (before, after) = self.gap(condition.span().1, next);
+        match after.is_empty() {
+            true => format!("({}{})", self.expression(condition, 0), before),
+            false => format!("({}{}) {}", self.expression(condition, 0), before, after.trim_end())
+        }
+    }
+
+    ///
+    /// Header of a for loop. Comments between its parts stay on the same side of the separators.
+    ///
+    fn for_header(&self, init: & Option<Box<Statement>>, condition: & Option<Expression>, step: & Option<Expression>, next: usize) -> std::string::String {
+
+        let mut parts: Vec<Option<(usize, usize, std::string::String)>> = vec!(
+            match init.as_ref().map(|init| init.as_ref()) {
+                Some(Statement::Declaration(declaration)) => {
+                    let end = declaration.value.as_ref().map_or(declaration.span.1, |value| value.span().1);
+                    Some((declaration.span.0, end, self.declaration(declaration)))
+                },
+                Some(Statement::Expression { expression, .. }) => Some((expression.span().0, expression.span().1, self.expression(expression, 0))),
+                _ => None
+            },
+            condition.as_ref().map(|condition| (condition.span().0, condition.span().1, self.expression(condition, 0))),
+            step.as_ref().map(|step| (step.span().0, step.span().1, self.expression(step, 0)))
+        );
+
+        let present: Vec<usize> = (0..parts.len()).filter(|index| parts[*index].is_some()).collect();
+        let mut closing = std::string::String::new();
+
+        for (position, index) in present.iter().enumerate() {
+            let following = present.get(position + 1).cloned();
+            let end = parts[*index].as_ref().map_or(next, |part| part.1);
+            let start = following.and_then(|following| parts[following].as_ref()).map_or(next, |part| part.0);
+            let (before, after) = self.gap(end, start);
+            if let Some(part) = parts[*index].as_mut() {
+                part.2.push_str(&before);
+            }
+            match following.and_then(|following| parts[following].as_mut()) {
+                Some(part) => part.2.insert_str(0, &after),
+                None => closing = after
+            }
+        }
+
+        let text = |index: usize| parts[index].as_ref().map(|part| part.2.clone()).unwrap_or_default();
+        let step = match parts[2].is_some() {
+            true => format!(" {}", text(2)),
+            false => std::string::String::new()
+        };
+
+        match closing.is_empty() {
+            true => format!("for ({}; {};{})", text(0), text(1), step),
+            false => format!("for ({}; {};{}) {}", text(0), text(1), step, closing.trim_end())
+        }
+
+    }
+
+    fn if_statement(& mut self, header: & str, condition: & Expression, if_true: & Statement, if_false: & Option<Box<Statement>>) {
+
+        let header = format!("{}if {}", header, self.condition(condition, if_true.span().0));
+
+        let if_false = match if_false {
+            None => return self.body(&header, if_true, "", true),
+            Some(if_false) => if_false
+        };
+
+        match if_true {
+            Statement::Block { span, statements } => {
+                self.block(&format!("{} ", header), *span, statements, None);
+                self.at_block_start = false;
+                match if_false.as_ref() {
+                    Statement::If { condition, if_true, if_false, .. } => {
+                        self.continued = true;
+                        self.if_statement("else ", condition, if_true, if_false);
+                    },
+                    Statement::Block { span, statements } => {
+                        self.continued = true;
+                        self.line("else {", span.0 + 1);
+                        self.open_block(span.0 + 1);
+                        self.statements(statements);
+                        self.close_block(span.1.saturating_sub(1));
+                        self.line("}", span.1);
+                    },
+                    statement => {
+                        self.output.push('\n');
+                        self.body("else", statement, "", true);
+                    }
+                }
+            },
+            statement => {
+                self.body(&header, statement, "", false);
+                match if_false.as_ref() {
+                    Statement::If { condition, if_true, if_false, .. } => self.if_statement("else ", condition, if_true, if_false),
+                    statement => self.body("else", statement, "", true)
+                }
+            }
+        }
+
+    }
+
+    fn statement(& mut self, statement: & Statement) {
+
+        let span = statement.span();
+
+        self.comments_before(span.0);
+        self.separate(span.0);
+
+        if let Some(text) = self.simple_statement(statement, true) {
+            return self.line(&text, span.1);
+        }
+
+        match statement {
+            Statement::Block { span, statements } => self.block("", *span, statements, Some("")),
+            Statement::For { init, condition, step, body, .. } => {
+                let header = self.for_header(init, condition, step, body.span().0);
+                self.body(&header, body, "", true);
+            },
+            Statement::While { condition, body, .. } => {
+                let header = format!("while {}", self.condition(condition, body.span().0));
+                self.body(&header, body, "", true);
+            },
+            Statement::DoWhile { body, condition, span } => {
+                let suffix = format!(" while {};", self.condition(condition, span.1));
+                self.body("do", body, &suffix, false);
+            },
+            Statement::If { condition, if_true, if_false, .. } => self.if_statement("", condition, if_true, if_false),
+            _ => {}
+        }
+
+    }
+
+    ///
+    /// Parameters of a template or function, with the comments of the list. Their positions are
+    /// found back in the source, the typed AST only keeping their names.
+    ///
+    fn parameters(&self, span: Span, parameters: & [std::string::String]) -> std::string::String {
+
+        let open = match self.source[span.0..span.1].find('(') {
+            Some(offset) => span.0 + offset,
+            None => return parameters.join(", ")
+        };
+
+        let mut position = open + 1;
+        let mut spans: Vec<Span> = Vec::new();
+        for parameter in parameters {
+            let start = self.next_token(position, span.1);
+            spans.push((start, start + parameter.len()));
+            position = self.next_token(start + parameter.len(), span.1) + 1;
+        }
+
+        let close = spans.last().map_or_else(|| self.next_token(open + 1, span.1), |last| self.next_token(last.1, span.1)) + 1;
+
+        if self.needs_lines(open, close, &spans) {
+            let items: Vec<(Span, std::string::String)> = spans.into_iter().zip(parameters.iter().cloned()).collect();
+            return self.lines_list(open, close, &items);
+        }
+
+        let (_, mut prefix) = self.gap(open, spans.first().map_or(close, |first| first.0));
+
+        if parameters.is_empty() {
+            return prefix.trim_end().to_string();
+        }
+
+        let mut texts: Vec<std::string::String> = Vec::new();
+        for (index, parameter) in parameters.iter().enumerate() {
+            let next = spans.get(index + 1).map_or(close, |next| next.0);
+            let (before, after) = self.gap(spans[index].1, next);
+            texts.push(format!("{}{}{}", prefix, parameter, before));
+            prefix = after;
+        }
+
+        texts.join(", ")
+
+    }
+
+    fn callable(& mut self, keyword: & str, span: Span, name: & str, parameters: & [std::string::String], body: & [Statement]) {
+        let opening = self.source[span.0..span.1].find('{').map(|offset| span.0 + offset).unwrap_or(span.0);
+        let parameters = self.parameters((span.0, opening), parameters);
+        self.block(&format!("{} {}({}) ", keyword, name, parameters), (opening, span.1), body, Some(""));
+    }
+
+    fn circuit(& mut self, circuit: & Circuit) {
+
+        let mut previous: Option<& Item> = None;
+
+        for item in &circuit.items {
+
+            let span = match item {
+                Item::Include { span, .. } => *span,
+                Item::Function(function) => function.span,
+                Item::Template(template) => template.span,
+                Item::Declaration(declaration) => declaration.span
+            };
+
+            self.comments_before(span.0);
+
+            // Templates and functions are always separated from what surrounds them
+            let forced = !matches!((previous, item),
+                (None, _) | (Some(Item::Include { .. }), Item::Include { .. }) | (Some(Item::Declaration(_)), Item::Declaration(_)));
+
+            if forced && !self.at_block_start && !self.output.ends_with("\n\n") && !has_blank_line(&self.source[self.last_end.min(span.0)..span.0]) {
+                self.output.push('\n');
+            }
+            self.separate(span.0);
+
+            match item {
+                Item::Include { path, .. } => self.line(&format!("include \"{}\";", path), span.1),
+                Item::Function(function) => self.callable("function", function.span, &function.name, &function.parameters, &function.body),
+                Item::Template(template) => self.callable("template", template.span, &template.name, &template.parameters, &template.body),
+                Item::Declaration(declaration) => {
+                    let text = format!("{};", self.declaration(declaration));
+                    self.line(&text, span.1);
+                }
+            }
+
+            previous = Some(item);
+
+        }
+
+        self.comments_before(self.source.len() + 1);
+
+    }
+
+}
+
+///
+/// Re-prints a parsed file. Comments are kept, attached to the statement they precede or end the
+/// line of, block comments within a line staying between the same tokens. Single empty lines
+/// between statements are preserved.
+///
+/// The output parses to the same typed AST (spans aside), and formatting it again leaves it
+/// unchanged.
+///
+pub fn format_root(root: & RootToken, options: & FormatOptions) -> Result<std::string::String, MatchError> {
+
+    let circuit = matchers::circuit::process_circuit(root)?;
+
+    let comments: Vec<(Span, & str)> = Cst::new(root).comments()
+        .map(|comment| (comment.span, &root.source[comment.span.0..comment.span.1]))
+        .collect();
+
+    let mut formatter = Formatter {
+        source: &root.source,
+        options,
+        comments,
+        next_comment: 0,
+        output: std::string::String::new(),
+        depth: 0,
+        last_end: 0,
+        at_block_start: true,
+        continued: false,
+        nesting: std::cell::Cell::new(0),
+        inlined: std::cell::RefCell::new(std::collections::HashSet::new())
+    };
+
+    formatter.circuit(&circuit);
+
+    Ok(formatter.output)
+
+}
+
+///
+/// Source that cannot be formatted, either rejected by the grammar or by the matchers.
+///
+#[derive(Debug)]
+pub enum FormatError {
+    Parse(pest::error::Error<Rule>),
+    Match(MatchError)
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FormatError::Parse(error) => error.fmt(formatter),
+            FormatError::Match(error) => error.fmt(formatter)
+        }
+    }
+}
+
+impl From<pest::error::Error<Rule>> for FormatError {
+    fn from(error: pest::error::Error<Rule>) -> FormatError {
+        FormatError::Parse(error)
+    }
+}
+
+impl From<MatchError> for FormatError {
+    fn from(error: MatchError) -> FormatError {
+        FormatError::Match(error)
+    }
+}
+
+pub fn format_source(source: & str, options: & FormatOptions) -> Result<std::string::String, FormatError> {
+    Ok(format_root(&ast::parse_source(source, None)?, options)?)
+}
+
+#[cfg(test)]
+mod formatter_test {
+
+    use serde_json::Value;
+
+    use parser::ast;
+    use parser::dump;
+    use parser::matchers;
+    use formatter::{self, FormatOptions};
+
+    use galvanic_assert::matchers::*;
+
+    fn without_spans(value: Value) -> Value {
+        match value {
+            Value::Object(object) => Value::Object(object.into_iter()
+                .filter(|(name, _)| name != "span")
+                .map(|(name, value)| (name, without_spans(value)))
+                .collect()),
+            Value::Array(values) => Value::Array(values.into_iter().map(without_spans).collect()),
+            value => value
+        }
+    }
+
+    fn typed_ast(source: & str) -> Value {
+        let root = ast::parse_source(source, None).expect("Formatted source does not parse");
+        without_spans(dump::circuit_to_json(&matchers::circuit::process_circuit(&root).unwrap()))
+    }
+
+    fn circuit_files(directory: & std::path::Path, files: & mut Vec<std::path::PathBuf>) {
+        for entry in std::fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                circuit_files(&path, files);
+            } else if path.extension().map(|extension| extension == "circom").unwrap_or(false) {
+                files.push(path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_expressions() {
+
+        let source = "template A(n){signal input a;signal output b;var x=((1+2)*3)-(4-5);b<==(a*(a+1));x=-(-x);x+=n>0?(x):((n,x));// end\n}\ncomponent main=A(0x10);";
+        let formatted = formatter::format_source(source, &FormatOptions::default()).unwrap();
+
+        expect_that!(&formatted, is(eq([
+            "template A(n) {",
+            "    signal input a;",
+            "    signal output b;",
+            "    var x = (1 + 2) * 3 - (4 - 5);",
+            "    b <== a * (a + 1);",
+            "    x = -(-x);",
+            "    x += n > 0 ? x : (n, x); // end",
+            "}",
+            "",
+            "component main = A(0x10);",
+            ""
+        ].join("\n"))));
+
+        let tabs = FormatOptions { indent: "\t".to_string() };
+        let formatted = formatter::format_source("function f(a){if(a){return 1;}else if(a>1)return 2 else{while(a)a--;}return 0;}", &tabs).unwrap();
+
+        expect_that!(&formatted, is(eq([
+            "function f(a) {",
+            "\tif (a) {",
+            "\t\treturn 1;",
+            "\t} else if (a > 1) return 2",
+            "\telse {",
+            "\t\twhile (a) a--;",
+            "\t}",
+            "\treturn 0;",
+            "}",
+            ""
+        ].join("\n"))));
+
+    }
+
+    #[test]
+    fn test_format_inline_comments() {
+
+        let source = "template A(n){signal input a[2];signal output b;var x=/* init */[/* first */1,2/* last */];b<==a*/* k */2;b===a[0]/* q */;\
+            x[/* i */0]=f(/* none */)+g(a/* arg */,2);x=n>0/* c */?1:/* f */2;b<==-/* neg */a[0]/* before */+a[1];// end\n}";
+        let formatted = formatter::format_source(source, &FormatOptions::default()).unwrap();
+
+        expect_that!(&formatted, is(eq([
+            "template A(n) {",
+            "    signal input a[2];",
+            "    signal output b;",
+            "    var x = /* init */ [/* first */ 1, 2 /* last */];",
+            "    b <== a * /* k */ 2;",
+            "    b === a[0] /* q */;",
+            "    x[/* i */ 0] = f(/* none */) + g(a /* arg */, 2);",
+            "    x = n > 0 /* c */ ? 1 : /* f */ 2;",
+            "    b <== -/* neg */ a[0] /* before */ + a[1]; // end",
+            "}",
+            ""
+        ].join("\n"))));
+
+        expect_that!(&formatter::format_source(&formatted, &FormatOptions::default()).unwrap(), is(eq(formatted.clone())));
+
+        let source = "function f(/* a */n/* b */,m){for(var i=0/* i */;i<n;i++/* s */){n++;}while(n/* w */)n--;return n/* r */;}function g(/* none */){}";
+        let formatted = formatter::format_source(source, &FormatOptions::default()).unwrap();
+
+        expect_that!(&formatted, is(eq([
+            "function f(/* a */ n /* b */, m) {",
+            "    for (var i = 0 /* i */; i < n; i++ /* s */) {",
+            "        n++;",
+            "    }",
+            "    while (n /* w */) n--;",
+            "    return n /* r */;",
+            "}",
+            "",
+            "function g(/* none */) {}",
+            ""
+        ].join("\n"))));
+
+    }
+
+    #[test]
+    fn test_format_comments_in_lists() {
+
+        let source = "template T(a, // first\n b) {\n    signal input x;\n}\n\
+            function f(/* x */ a, b /* y */, c // z\n) {\n    var v = g(a, // arg\n[1, 2], h(3, // inner\n4));\n    return v;\n}\n\
+            component main = T(1, // n\n 2);";
+        let formatted = formatter::format_source(source, &FormatOptions::default()).unwrap();
+
+        expect_that!(&formatted, is(eq([
+            "template T(",
+            "    a, // first",
+            "    b",
+            ") {",
+            "    signal input x;",
+            "}",
+            "",
+            "function f(",
+            "    /* x */ a,",
+            "    b, /* y */",
+            "    c // z",
+            ") {",
+            "    var v = g(",
+            "        a, // arg",
+            "        [1, 2],",
+            "        h(",
+            "            3, // inner",
+            "            4",
+            "        )",
+            "    );",
+            "    return v;",
+            "}",
+            "",
+            "component main = T(",
+            "    1, // n",
+            "    2",
+            ");",
+            ""
+        ].join("\n"))));
+
+        expect_that!(&formatter::format_source(&formatted, &FormatOptions::default()).unwrap(), is(eq(formatted.clone())));
+        expect_that!(&typed_ast(&formatted), is(eq(typed_ast(source))));
+
+    }
+
+    #[test]
+    fn test_format_circuits() {
+
+        let mut files = Vec::new();
+        circuit_files(std::path::Path::new("./src/lib/parser/test_material/circuits"), & mut files);
+        files.sort();
+
+        expect_that!(&files.len(), is(eq(45)));
+
+        for path in &files {
+
+            let source = std::fs::read_to_string(path).unwrap();
+            let root = ast::parse_source(&source, None).unwrap_or_else(|error| panic!("Cannot parse {}: {}", path.display(), error));
+
+            let options = FormatOptions::default();
+            let formatted = formatter::format_root(&root, &options).unwrap();
+
+            expect_that!(&typed_ast(&formatted), is(eq(without_spans(dump::circuit_to_json(&matchers::circuit::process_circuit(&root).unwrap())))));
+            expect_that!(&formatter::format_source(&formatted, &options).unwrap(), is(eq(formatted.clone())));
+
+            let comments = |text: & str| text.matches("//").count() + text.matches("/*").count();
+            expect_that!(&comments(&formatted), is(eq(comments(&source))));
+
+        }
+
+    }
+
+}
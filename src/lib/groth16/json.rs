@@ -0,0 +1,151 @@
+//
+// snarkjs `proof.json` and `verification_key.json` files of Groth16 proofs (see
+// `curve::bn254::json` for the encoding of the points).
+//
+
+use curve::bn254::json::{self, JsonError};
+use curve::bn254::pairing;
+use groth16::{Proof, VerifyingKey};
+
+use serde_json::Value;
+
+pub fn proof_to_json(proof: & Proof) -> Value {
+    json!({
+        "pi_a": json::g1_to_json(&proof.a),
+        "pi_b": json::g2_to_json(&proof.b),
+        "pi_c": json::g1_to_json(&proof.c),
+        "protocol": "groth16",
+        "curve": "bn128"
+    })
+}
+
+pub fn proof_from_json(document: & Value) -> Result<Proof, JsonError> {
+
+    json::check_header(document, "groth16")?;
+
+    Ok(Proof {
+        a: json::g1_from_json(json::member(document, "pi_a")?, "pi_a")?,
+        b: json::g2_from_json(json::member(document, "pi_b")?, "pi_b")?,
+        c: json::g1_from_json(json::member(document, "pi_c")?, "pi_c")?
+    })
+
+}
+
+///
+/// Verification key with the `e(alpha, beta)` pairing snarkjs precomputes, which is not read back.
+///
+pub fn verifying_key_to_json(vk: & VerifyingKey) -> Value {
+    json!({
+        "protocol": "groth16",
+        "curve": "bn128",
+        "nPublic": vk.ic.len() - 1,
+        "vk_alpha_1": json::g1_to_json(&vk.alpha_g1),
+        "vk_beta_2": json::g2_to_json(&vk.beta_g2),
+        "vk_gamma_2": json::g2_to_json(&vk.gamma_g2),
+        "vk_delta_2": json::g2_to_json(&vk.delta_g2),
+        "vk_alphabeta_12": json::fq12_to_json(&pairing::pairing(&vk.alpha_g1, &vk.beta_g2)),
+        "IC": vk.ic.iter().map(json::g1_to_json).collect::<Vec<Value>>()
+    })
+}
+
+pub fn verifying_key_from_json(document: & Value) -> Result<VerifyingKey, JsonError> {
+
+    json::check_header(document, "groth16")?;
+
+    let ic = match json::member(document, "IC")?.as_array() {
+        Some(points) if !points.is_empty() => points.iter().enumerate()
+            .map(|(idx, point)| json::g1_from_json(point, &format!("IC[{}]", idx)))
+            .collect::<Result<Vec<_>, JsonError>>()?,
+        _ => return Err(JsonError::FormatError("IC is not a non empty array".to_string()))
+    };
+
+    if let Some(n_public) = document.get("nPublic") {
+        if n_public.as_u64() != Some(ic.len() as u64 - 1) {
+            return Err(JsonError::FormatError(format!("nPublic does not match the {} IC points", ic.len())));
+        }
+    }
+
+    Ok(VerifyingKey {
+        alpha_g1: json::g1_from_json(json::member(document, "vk_alpha_1")?, "vk_alpha_1")?,
+        beta_g2: json::g2_from_json(json::member(document, "vk_beta_2")?, "vk_beta_2")?,
+        gamma_g2: json::g2_from_json(json::member(document, "vk_gamma_2")?, "vk_gamma_2")?,
+        delta_g2: json::g2_from_json(json::member(document, "vk_delta_2")?, "vk_delta_2")?,
+        ic
+    })
+
+}
+
+#[cfg(test)]
+mod json_test {
+
+    use curve::bn254::json::{load, scalars_from_json, scalars_to_json};
+    use curve::bn254::{G1, G2};
+    use field::bn254::Fr;
+    use field::PrimeField;
+    use groth16::{self, json, zkey};
+    use groth16::{Proof, VerifyingKey};
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_proof_and_key() {
+
+        let proof = Proof {
+            a: G1::generator().to_affine(),
+            b: G2::generator().double().to_affine(),
+            c: G1::generator().mul_scalar(&Fr::from_u64(5)).to_affine()
+        };
+
+        let document = json::proof_to_json(&proof);
+        expect_that!(&document["pi_a"], is(eq(json!(["1", "2", "1"]))));
+        expect_that!(&document["protocol"], is(eq(json!("groth16"))));
+        expect_that!(&json::proof_from_json(&document).unwrap(), is(eq(proof)));
+
+        let vk = VerifyingKey {
+            alpha_g1: G1::generator().to_affine(),
+            beta_g2: G2::generator().to_affine(),
+            gamma_g2: G2::generator().double().to_affine(),
+            delta_g2: G2::generator().to_affine(),
+            ic: vec!(G1::generator().double().to_affine(), G1::generator().to_affine())
+        };
+
+        let document = json::verifying_key_to_json(&vk);
+        expect_that!(&document["nPublic"], is(eq(json!(1))));
+        expect_that!(&document["vk_alphabeta_12"].as_array().unwrap().len(), is(eq(2)));
+        expect_that!(&json::verifying_key_from_json(&document).unwrap(), is(eq(vk)));
+
+        let mut plonk = document.clone();
+        plonk["protocol"] = json!("plonk");
+        expect_that!(&json::verifying_key_from_json(&plonk).is_err(), is(eq(true)));
+
+        let mut mismatch = document;
+        mismatch["nPublic"] = json!(2);
+        expect_that!(&json::verifying_key_from_json(&mismatch).is_err(), is(eq(true)));
+
+    }
+
+    #[test]
+    #[ignore = "needs the fixtures written by src/lib/parser/test_material/snarkjs/generate.sh"]
+    fn test_snarkjs_files() {
+
+        let directory = std::path::Path::new("./src/lib/parser/test_material/snarkjs");
+
+        let vk_document = load(&directory.join("verification_key.json")).unwrap();
+        let proof_document = load(&directory.join("proof.json")).unwrap();
+        let public_document = load(&directory.join("public.json")).unwrap();
+
+        let vk = json::verifying_key_from_json(&vk_document).unwrap();
+        let proof = json::proof_from_json(&proof_document).unwrap();
+        let public: Vec<Fr> = scalars_from_json(&public_document, "public").unwrap();
+
+        expect_that!(&json::verifying_key_to_json(&vk), is(eq(vk_document)));
+        expect_that!(&json::proof_to_json(&proof), is(eq(proof_document)));
+        expect_that!(&scalars_to_json(&public), is(eq(public_document)));
+
+        let pk = zkey::load(&directory.join("num2bits.zkey")).unwrap();
+        expect_that!(&pk.verifying_key(), is(eq(vk.clone())));
+        expect_that!(&groth16::verify(&vk, &public, &proof), is(eq(Ok(true))));
+
+    }
+
+}
@@ -0,0 +1,238 @@
+//
+// Groth16 zk-SNARK, compatible with the snarkjs keys and proofs.
+//
+// Setup, proving and verification are generic over the pairing `Engine`, the keys and proofs
+// defaulting to BN254, the only curve of the snarkjs formats (`zkey`, `json`, `solidity`, `phase2`).
+//
+// The R1CS is turned into a QAP over the smallest power of two domain holding the constraints and
+// one extra `A` constraint per public signal (`1 * 0 = 0` for the one wire and each public
+// signal), which makes the public polynomials independent. As in snarkjs, the prover evaluates the
+// quotient on the odd points of the domain of double size, and the `H` query holds the matching
+// Lagrange polynomials of that domain (see `setup`).
+//
+
+pub mod setup;
+pub mod prover;
+pub mod verifier;
+pub mod zkey;
+pub mod phase2;
+pub mod solidity;
+pub mod json;
+
+use curve::{Affine, Engine};
+use curve::bn254::Bn254;
+use field::bn254::Fr;
+use field::PrimeField;
+use groth16::phase2::MpcParams;
+
+pub use self::setup::setup;
+pub use self::prover::prove;
+pub use self::verifier::verify;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Matrix {
+    A,
+    B
+}
+
+///
+/// Non zero entry of the `A` or `B` matrix of the constraint system, extra `A` constraints of the
+/// public signals included.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coefficient<F: PrimeField = Fr> {
+    pub matrix: Matrix,
+    pub constraint: usize,
+    pub signal: usize,
+    pub value: F
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyingKey<E: Engine = Bn254> {
+    pub alpha_g1: Affine<E::G1>,
+    pub beta_g2: Affine<E::G2>,
+    pub gamma_g2: Affine<E::G2>,
+    pub delta_g2: Affine<E::G2>,
+
+    ///
+    /// `(beta * A_i(tau) + alpha * B_i(tau) + C_i(tau)) / gamma` for the one wire and the public
+    /// signals.
+    ///
+    pub ic: Vec<Affine<E::G1>>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvingKey<E: Engine = Bn254> {
+    pub n_vars: usize,
+    pub n_public: usize,
+    pub domain_size: usize,
+    pub alpha_g1: Affine<E::G1>,
+    pub beta_g1: Affine<E::G1>,
+    pub beta_g2: Affine<E::G2>,
+    pub gamma_g2: Affine<E::G2>,
+    pub delta_g1: Affine<E::G1>,
+    pub delta_g2: Affine<E::G2>,
+    pub ic: Vec<Affine<E::G1>>,
+    pub coefficients: Vec<Coefficient<E::Fr>>,
+
+    ///
+    /// `A_i(tau)`, `B_i(tau)` in G1 and G2 for every signal.
+    ///
+    pub a_query: Vec<Affine<E::G1>>,
+    pub b_g1_query: Vec<Affine<E::G1>>,
+    pub b_g2_query: Vec<Affine<E::G2>>,
+
+    ///
+    /// `(beta * A_i(tau) + alpha * B_i(tau) + C_i(tau)) / delta` for the private signals, the first
+    /// one being `n_public + 1`.
+    ///
+    pub c_query: Vec<Affine<E::G1>>,
+
+    ///
+    /// `L_(2i+1)(tau) / delta` for `L` the Lagrange polynomials of the domain of size
+    /// `2 * domain_size`.
+    ///
+    pub h_query: Vec<Affine<E::G1>>,
+
+    ///
+    /// Ceremony the key comes from, `None` for keys from `setup`.
+    ///
+    pub mpc: Option<MpcParams>
+}
+
+impl<E: Engine> ProvingKey<E> {
+
+    pub fn verifying_key(&self) -> VerifyingKey<E> {
+        VerifyingKey {
+            alpha_g1: self.alpha_g1,
+            beta_g2: self.beta_g2,
+            gamma_g2: self.gamma_g2,
+            delta_g2: self.delta_g2,
+            ic: self.ic.clone()
+        }
+    }
+
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Proof<E: Engine = Bn254> {
+    pub a: Affine<E::G1>,
+    pub b: Affine<E::G2>,
+    pub c: Affine<E::G1>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Groth16Error {
+    DomainTooLarge(usize),
+    WitnessLength { expected: usize, found: usize },
+    PublicSignalCount { expected: usize, found: usize },
+    PtauTooSmall { needed: usize, available: usize }
+}
+
+impl std::fmt::Display for Groth16Error {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Groth16Error::DomainTooLarge(size) => write!(formatter, "The circuit needs a domain of {} elements, more than the field supports", size),
+            Groth16Error::WitnessLength { expected, found } => write!(formatter, "The witness has {} values but the circuit has {} wires", found, expected),
+            Groth16Error::PublicSignalCount { expected, found } => write!(formatter, "{} public signals given but the circuit has {}", found, expected),
+            Groth16Error::PtauTooSmall { needed, available } => write!(formatter, "The circuit needs {} powers of tau but the file only has {}", needed, available)
+        }
+    }
+}
+
+#[cfg(test)]
+mod groth16_test {
+
+    use circuit::r1cs_file::R1CSFile;
+    use elaborator;
+    use field::bn254::Fr;
+    use field::PrimeField;
+    use groth16::{self, Groth16Error, ProvingKey};
+    use parser::compile;
+    use rng::TestRng;
+    use witness::{self, wtns};
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_num2bits_fixtures() {
+
+        let r1cs = R1CSFile::<Fr>::load(std::path::Path::new("./src/lib/parser/test_material/proving/num2bits.r1cs")).unwrap().r1cs;
+        let witness = wtns::load::<Fr>(std::path::Path::new("./src/lib/parser/test_material/proving/num2bits.wtns")).unwrap();
+
+        let mut rng = TestRng::new(1);
+        let pk: ProvingKey = groth16::setup(&r1cs, & mut rng).unwrap();
+        let vk = pk.verifying_key();
+
+        expect_that!(&pk.domain_size, is(eq(32)));
+        expect_that!(&vk.ic.len(), is(eq(10)));
+
+        let proof = groth16::prove(&pk, &witness, & mut rng).unwrap();
+        let public = &witness[1..=r1cs.n_public()];
+
+        expect_that!(&groth16::verify(&vk, public, &proof), is(eq(Ok(true))));
+
+        let mut tampered = public.to_vec();
+        tampered[0] = Fr::zero();
+        expect_that!(&groth16::verify(&vk, &tampered, &proof), is(eq(Ok(false))));
+
+        let mut forged = proof;
+        forged.c = proof.a;
+        expect_that!(&groth16::verify(&vk, public, &forged), is(eq(Ok(false))));
+
+        expect_that!(&groth16::verify(&vk, &public[1..], &proof), is(eq(Err(Groth16Error::PublicSignalCount { expected: 9, found: 8 }))));
+        expect_that!(&groth16::prove(&pk, &witness[1..], & mut rng), is(eq(Err(Groth16Error::WitnessLength { expected: 10, found: 9 }))));
+
+        let mut invalid = witness.clone();
+        invalid[2] += Fr::one();
+        let proof = groth16::prove(&pk, &invalid, & mut rng).unwrap();
+        expect_that!(&groth16::verify(&vk, &invalid[1..=r1cs.n_public()], &proof), is(eq(Ok(false))));
+
+    }
+
+    #[test]
+    #[ignore = "needs the fixtures written by src/lib/parser/test_material/snarkjs/generate.sh"]
+    fn test_snarkjs_r1cs_and_witness() {
+
+        let r1cs = R1CSFile::<Fr>::load(std::path::Path::new("./src/lib/parser/test_material/snarkjs/num2bits.r1cs")).unwrap().r1cs;
+        let witness = wtns::load::<Fr>(std::path::Path::new("./src/lib/parser/test_material/snarkjs/num2bits.wtns")).unwrap();
+
+        // Bits of 173, then 173
+        let public: Vec<Fr> = vec!(1, 0, 1, 1, 0, 1, 0, 1, 173).into_iter().map(Fr::from_u64).collect();
+
+        expect_that!(&r1cs.n_public(), is(eq(9)));
+        expect_that!(&&witness[1..=r1cs.n_public()], is(eq(public.as_slice())));
+        expect_that!(&r1cs.constraints.iter().all(|constraint| constraint.is_satisfied(&witness)), is(eq(true)));
+
+        let mut rng = TestRng::new(8);
+        let pk: ProvingKey = groth16::setup(&r1cs, & mut rng).unwrap();
+        let proof = groth16::prove(&pk, &witness, & mut rng).unwrap();
+
+        expect_that!(&groth16::verify(&pk.verifying_key(), &public, &proof), is(eq(Ok(true))));
+
+    }
+
+    #[test]
+    fn test_num2bits_elaborated() {
+
+        let path = std::fs::canonicalize("./src/lib/parser/test_material/witness/num2bits.circom").expect("Invalid Path");
+        let ctx = compile::build_context(&path);
+        let circuit = elaborator::elaborate::<Fr>(&ctx).expect("Elaboration failed");
+        let r1cs = circuit.r1cs().expect("Constraint generation failed");
+
+        let inputs = witness::input::parse_inputs::<Fr>("{\"in\": 200}").unwrap();
+        let witness = witness::calculate_witness(&circuit, &inputs).unwrap();
+
+        let mut rng = TestRng::new(2);
+        let pk: ProvingKey = groth16::setup(&r1cs, & mut rng).unwrap();
+        let proof = groth16::prove(&pk, &witness, & mut rng).unwrap();
+
+        expect_that!(&groth16::verify(&pk.verifying_key(), &witness[1..=r1cs.n_public()], &proof), is(eq(Ok(true))));
+
+        let again = groth16::prove(&pk, &witness, & mut TestRng::new(3)).unwrap();
+        expect_that!(&(again == proof), is(eq(false)));
+        expect_that!(&groth16::verify(&pk.verifying_key(), &witness[1..=r1cs.n_public()], &again), is(eq(Ok(true))));
+
+    }
+
+}
@@ -0,0 +1,501 @@
+//
+// Circuit specific phase 2 of a Groth16 ceremony, on top of the powers of tau of phase 1.
+//
+// The initial key is derived from the Lagrange bases of the powers of tau with `gamma = delta = 1`.
+// Each contribution multiplies `delta` by a secret `d`, dividing the C and H queries by it, and
+// proves knowledge of `d` as snarkjs does: `g1_sx = d * g1_s` and `g2_spx = d * g2_sp`, where `g2_sp`
+// is the point `hashToG2` of snarkjs derives from the transcript hash of the contribution.
+// Transcripts are chained with BLAKE2b starting from the circuit hash of the initial key, computed
+// as snarkjs does, so that ceremonies can alternate snarkrs and snarkjs contributions.
+//
+
+use blake2b::{Blake2b, DIGEST_SIZE};
+use circuit::r1cs::R1CS;
+use curve::bn254::{binary, pairing};
+use curve::bn254::{Fq2, G1, G1Affine, G2, G2Affine, G2Params};
+use curve::{multi_exp, Affine, CurveParams};
+use field::bigint::U256;
+use field::bn254::{Fq, Fr};
+use field::PrimeField;
+use groth16::{setup, Groth16Error, Matrix, ProvingKey};
+use ptau::Ptau;
+use rng::{ChaCha, Rng};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contribution {
+
+    ///
+    /// `delta * G1` once the contribution is applied.
+    ///
+    pub delta_after: G1Affine,
+    pub g1_s: G1Affine,
+    pub g1_sx: G1Affine,
+    pub g2_spx: G2Affine,
+    pub transcript: [u8; DIGEST_SIZE],
+    pub name: Option<std::string::String>
+}
+
+///
+/// Phase 2 state of a key: the hash of the initial key derived from the circuit and the
+/// contributions applied since.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MpcParams {
+    pub cs_hash: [u8; DIGEST_SIZE],
+    pub contributions: Vec<Contribution>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Phase2Error {
+    Setup(Groth16Error),
+    NotCeremonyKey,
+    CircuitHash,
+    Mismatch(&'static str),
+    InvalidContribution(usize),
+    FinalDelta
+}
+
+impl std::fmt::Display for Phase2Error {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Phase2Error::Setup(error) => write!(formatter, "{}", error),
+            Phase2Error::NotCeremonyKey => write!(formatter, "The key is not the output of a phase 2 ceremony"),
+            Phase2Error::CircuitHash => write!(formatter, "The key was not initialized from this circuit and powers of tau"),
+            Phase2Error::Mismatch(points) => write!(formatter, "The {} of the key are not the ones derived from the circuit and the powers of tau", points),
+            Phase2Error::InvalidContribution(idx) => write!(formatter, "Contribution #{} is invalid", idx + 1),
+            Phase2Error::FinalDelta => write!(formatter, "The delta points of the key are not the ones of the last contribution")
+        }
+    }
+}
+
+fn hash_g1(hasher: & mut Blake2b, point: & G1Affine) {
+    let mut bytes = Vec::with_capacity(binary::G1_SIZE);
+    binary::push_g1_uncompressed(& mut bytes, point);
+    hasher.update(&bytes);
+}
+
+fn hash_g2(hasher: & mut Blake2b, point: & G2Affine) {
+    let mut bytes = Vec::with_capacity(binary::G2_SIZE);
+    binary::push_g2_uncompressed(& mut bytes, point);
+    hasher.update(&bytes);
+}
+
+fn hash_public_key(hasher: & mut Blake2b, contribution: & Contribution) {
+    hash_g1(hasher, &contribution.delta_after);
+    hash_g1(hasher, &contribution.g1_s);
+    hash_g1(hasher, &contribution.g1_sx);
+    hash_g2(hasher, &contribution.g2_spx);
+    hasher.update(&contribution.transcript);
+}
+
+fn transcript(cs_hash: & [u8; DIGEST_SIZE], previous: & [Contribution], g1_s: & G1Affine, g1_sx: & G1Affine) -> [u8; DIGEST_SIZE] {
+
+    let mut hasher = Blake2b::new();
+    hasher.update(cs_hash);
+    for contribution in previous {
+        hash_public_key(& mut hasher, contribution);
+    }
+    hash_g1(& mut hasher, g1_s);
+    hash_g1(& mut hasher, g1_sx);
+
+    hasher.digest()
+
+}
+
+fn is_negative(value: & Fq) -> bool {
+    value.to_u256() > Fq::modulus().shr(1)
+}
+
+///
+/// Field element drawn as ffjavascript does: the bits of the modulus from four 64 bits words, lowest
+/// first, rejected until reduced and read as a Montgomery representation.
+///
+fn fq_from_rng(rng: & mut ChaCha) -> Fq {
+
+    let bits = Fq::modulus().bits();
+    let mask = U256::from_u64(1).shl(bits).overflowing_sub(&U256::from_u64(1)).0;
+
+    loop {
+        let mut limbs = [0u64; 4];
+        for limb in limbs.iter_mut() {
+            *limb = rng.next_u64();
+        }
+        let value = U256(limbs).and(&mask);
+        if value < Fq::modulus() {
+            return Fq::from_montgomery_limbs(value.0);
+        }
+    }
+
+}
+
+///
+/// `hashToG2` of snarkjs: the point of G2 drawn from a ChaCha generator seeded with the first 32
+/// bytes of the transcript, read as big endian words. `x` is drawn until `x^3 + b` is a square, the
+/// root `y` is the one whose sign is a drawn bit, and the point is multiplied by the cofactor.
+///
+fn transcript_point(transcript: & [u8; DIGEST_SIZE]) -> G2Affine {
+
+    let mut seed = [0u32; 8];
+    for (idx, word) in seed.iter_mut().enumerate() {
+        *word = u32::from_be_bytes([transcript[4 * idx], transcript[4 * idx + 1], transcript[4 * idx + 2], transcript[4 * idx + 3]]);
+    }
+    let mut rng = ChaCha::new(&seed);
+
+    let (x, y, negative) = loop {
+        let x = Fq2::new(fq_from_rng(& mut rng), fq_from_rng(& mut rng));
+        let negative = rng.next_u32() & 1 == 1;
+        if let Some(y) = (x * x * x + G2Params::b()).sqrt() {
+            break (x, y, negative);
+        }
+    };
+
+    let sign = if y.c1.is_zero() { is_negative(&y.c0) } else { is_negative(&y.c1) };
+    let y = if sign == negative { y } else { -y };
+
+    let cofactor = U256::parse("0x30644e72e131a029b85045b68181585e06ceecda572a2489345f2299c0f9fa8d").unwrap();
+    G2Affine::new(x, y).mul(&cofactor).to_affine()
+
+}
+
+fn hash_g1_points(hasher: & mut Blake2b, points: & [G1Affine]) {
+    for point in points {
+        hash_g1(hasher, point);
+    }
+}
+
+///
+/// Circuit hash of snarkjs `zkey new`, identifying the initial key of a ceremony: the header points,
+/// then the IC, H, C, A, B1 and B2 points in uncompressed form. The H points hashed are
+/// `tau^(i + n) * G1 - tau^i * G1` from the powers of tau, not the ones of the key, and snarkjs
+/// hashes only `n - 1` of them when the domain fits in a single chunk of `2^14` points.
+///
+fn circuit_hash(pk: & ProvingKey, ptau: & Ptau) -> [u8; DIGEST_SIZE] {
+
+    const H_CHUNK_SIZE: usize = 1 << 14;
+
+    let mut hasher = Blake2b::new();
+
+    hash_g1_points(& mut hasher, &[pk.alpha_g1, pk.beta_g1]);
+    hash_g2(& mut hasher, &pk.beta_g2);
+    hash_g2(& mut hasher, &pk.gamma_g2);
+    hash_g1(& mut hasher, &pk.delta_g1);
+    hash_g2(& mut hasher, &pk.delta_g2);
+
+    hash_g1_points(& mut hasher, &pk.ic);
+
+    let n = pk.domain_size;
+    let h_points = if n - 1 <= H_CHUNK_SIZE { n - 1 } else { n };
+    let h: Vec<G1Affine> = (0..h_points)
+        .map(|idx| (ptau.tau_g1[idx + n].to_projective() - ptau.tau_g1[idx].to_projective()).to_affine())
+        .collect();
+    hash_g1_points(& mut hasher, &h);
+
+    hash_g1_points(& mut hasher, &pk.c_query);
+    hash_g1_points(& mut hasher, &pk.a_query);
+    hash_g1_points(& mut hasher, &pk.b_g1_query);
+    for point in &pk.b_g2_query {
+        hash_g2(& mut hasher, point);
+    }
+
+    hasher.digest()
+
+}
+
+fn combine<C: CurveParams>(terms: & [(Affine<C>, Fr)]) -> Affine<C> {
+    let (bases, scalars): (Vec<Affine<C>>, Vec<Fr>) = terms.iter().cloned().unzip();
+    multi_exp(&bases, &scalars).to_affine()
+}
+
+///
+/// Initial phase 2 key of a circuit, with no contribution yet. The powers of tau must hold at least
+/// twice as many powers as the domain of the circuit has elements.
+///
+pub fn new_key(r1cs: & R1CS<Fr>, ptau: & Ptau) -> Result<ProvingKey, Groth16Error> {
+
+    let n_public = r1cs.n_public();
+    let domain_size = (r1cs.constraints.len() + n_public + 1).next_power_of_two();
+
+    if 2 * domain_size > 1 << ptau.power {
+        return Err(Groth16Error::PtauTooSmall { needed: 2 * domain_size, available: 1 << ptau.power });
+    }
+
+    let tau_g1 = ptau.lagrange_tau_g1(domain_size);
+    let tau_g2 = ptau.lagrange_tau_g2(domain_size);
+    let alpha_tau_g1 = ptau.lagrange_alpha_tau_g1(domain_size);
+    let beta_tau_g1 = ptau.lagrange_beta_tau_g1(domain_size);
+
+    let coefficients = setup::coefficients(r1cs);
+
+    let mut a: Vec<Vec<(G1Affine, Fr)>> = vec!(Vec::new(); r1cs.n_wires);
+    let mut b_g1: Vec<Vec<(G1Affine, Fr)>> = vec!(Vec::new(); r1cs.n_wires);
+    let mut b_g2: Vec<Vec<(G2Affine, Fr)>> = vec!(Vec::new(); r1cs.n_wires);
+    let mut combined: Vec<Vec<(G1Affine, Fr)>> = vec!(Vec::new(); r1cs.n_wires);
+
+    for coefficient in &coefficients {
+        let (signal, constraint, value) = (coefficient.signal, coefficient.constraint, coefficient.value);
+        match coefficient.matrix {
+            Matrix::A => {
+                a[signal].push((tau_g1[constraint], value));
+                combined[signal].push((beta_tau_g1[constraint], value));
+            },
+            Matrix::B => {
+                b_g1[signal].push((tau_g1[constraint], value));
+                b_g2[signal].push((tau_g2[constraint], value));
+                combined[signal].push((alpha_tau_g1[constraint], value));
+            }
+        }
+    }
+
+    for (idx, constraint) in r1cs.constraints.iter().enumerate() {
+        for (wire, value) in constraint.c.terms() {
+            combined[*wire].push((tau_g1[idx], *value));
+        }
+    }
+
+    let h_query: Vec<G1Affine> = ptau.lagrange_tau_g1(2 * domain_size).into_iter().skip(1).step_by(2).collect();
+    let combined: Vec<G1Affine> = combined.iter().map(|terms| combine(terms)).collect();
+
+    let mut pk = ProvingKey {
+        n_vars: r1cs.n_wires,
+        n_public,
+        domain_size,
+        alpha_g1: ptau.alpha_tau_g1[0],
+        beta_g1: ptau.beta_tau_g1[0],
+        beta_g2: ptau.beta_g2,
+        gamma_g2: G2::generator().to_affine(),
+        delta_g1: G1::generator().to_affine(),
+        delta_g2: G2::generator().to_affine(),
+        ic: combined[..=n_public].to_vec(),
+        coefficients,
+        a_query: a.iter().map(|terms| combine(terms)).collect(),
+        b_g1_query: b_g1.iter().map(|terms| combine(terms)).collect(),
+        b_g2_query: b_g2.iter().map(|terms| combine(terms)).collect(),
+        c_query: combined[n_public + 1..].to_vec(),
+        h_query,
+        mpc: None
+    };
+
+    pk.mpc = Some(MpcParams {
+        cs_hash: circuit_hash(&pk, ptau),
+        contributions: Vec::new()
+    });
+
+    Ok(pk)
+
+}
+
+///
+/// Applies a contribution with a secret drawn from `rng`, returning its transcript hash, which the
+/// contributor publishes so that it can be found in the verified chain.
+///
+pub fn contribute<R: Rng>(pk: & mut ProvingKey, name: Option<& str>, rng: & mut R) -> Result<[u8; DIGEST_SIZE], Phase2Error> {
+
+    let mpc = pk.mpc.as_ref().ok_or(Phase2Error::NotCeremonyKey)?;
+
+    let secret: Fr = rng.nonzero_field();
+    let secret_inverse = secret.inverse().unwrap();
+
+    let g1_s = G1::generator().mul_scalar(&rng.nonzero_field::<Fr>()).to_affine();
+    let g1_sx = g1_s.mul(&secret.to_u256()).to_affine();
+    let transcript = transcript(&mpc.cs_hash, &mpc.contributions, &g1_s, &g1_sx);
+    let g2_spx = transcript_point(&transcript).mul(&secret.to_u256()).to_affine();
+
+    pk.delta_g1 = pk.delta_g1.mul(&secret.to_u256()).to_affine();
+    pk.delta_g2 = pk.delta_g2.mul(&secret.to_u256()).to_affine();
+
+    for point in pk.c_query.iter_mut().chain(pk.h_query.iter_mut()) {
+        *point = point.mul(&secret_inverse.to_u256()).to_affine();
+    }
+
+    let contribution = Contribution {
+        delta_after: pk.delta_g1,
+        g1_s,
+        g1_sx,
+        g2_spx,
+        transcript,
+        name: name.map(|name| name.to_string())
+    };
+
+    pk.mpc.as_mut().unwrap().contributions.push(contribution);
+
+    Ok(transcript)
+
+}
+
+fn same_ratio(g1: (& G1Affine, & G1Affine), g2: (& G2Affine, & G2Affine)) -> bool {
+    pairing::pairing_check(&[(-*g1.0, *g2.1), (*g1.1, *g2.0)])
+}
+
+///
+/// Checks that a key is the initial key of the circuit and powers of tau, transformed by a valid
+/// chain of contributions. The C and H queries are checked on random linear combinations drawn from
+/// `rng`.
+///
+pub fn verify_contributions<R: Rng>(pk: & ProvingKey, r1cs: & R1CS<Fr>, ptau: & Ptau, rng: & mut R) -> Result<(), Phase2Error> {
+
+    let mpc = pk.mpc.as_ref().ok_or(Phase2Error::NotCeremonyKey)?;
+    let initial = new_key(r1cs, ptau).map_err(Phase2Error::Setup)?;
+
+    if mpc.cs_hash != initial.mpc.as_ref().unwrap().cs_hash {
+        return Err(Phase2Error::CircuitHash);
+    }
+
+    let unchanged = [
+        ("sizes", (pk.n_vars, pk.n_public, pk.domain_size) == (initial.n_vars, initial.n_public, initial.domain_size)),
+        ("alpha and beta points", (pk.alpha_g1, pk.beta_g1, pk.beta_g2) == (initial.alpha_g1, initial.beta_g1, initial.beta_g2)),
+        ("gamma points", pk.gamma_g2 == initial.gamma_g2),
+        ("IC points", pk.ic == initial.ic),
+        ("coefficients", pk.coefficients == initial.coefficients),
+        ("A and B queries", (&pk.a_query, &pk.b_g1_query, &pk.b_g2_query) == (&initial.a_query, &initial.b_g1_query, &initial.b_g2_query)),
+        ("C and H query sizes", (pk.c_query.len(), pk.h_query.len()) == (initial.c_query.len(), initial.h_query.len()))
+    ];
+
+    if let Some((points, _)) = unchanged.iter().find(|(_, same)| !same) {
+        return Err(Phase2Error::Mismatch(points));
+    }
+
+    let mut delta = initial.delta_g1;
+
+    for (idx, contribution) in mpc.contributions.iter().enumerate() {
+
+        let expected = transcript(&mpc.cs_hash, &mpc.contributions[..idx], &contribution.g1_s, &contribution.g1_sx);
+        let g2_sp = transcript_point(&expected);
+
+        let valid = expected == contribution.transcript
+            && !contribution.g1_s.infinity
+            && !contribution.delta_after.infinity
+            && same_ratio((&contribution.g1_s, &contribution.g1_sx), (&g2_sp, &contribution.g2_spx))
+            && same_ratio((&delta, &contribution.delta_after), (&g2_sp, &contribution.g2_spx));
+
+        if !valid {
+            return Err(Phase2Error::InvalidContribution(idx));
+        }
+
+        delta = contribution.delta_after;
+
+    }
+
+    let g1 = G1::generator().to_affine();
+    let g2 = G2::generator().to_affine();
+
+    if pk.delta_g1 != delta || !same_ratio((&g1, &pk.delta_g1), (&g2, &pk.delta_g2)) {
+        return Err(Phase2Error::FinalDelta);
+    }
+
+    // delta * C = C_initial, and the same for H.
+    for (points, current, initial_points) in [("C query", &pk.c_query, &initial.c_query), ("H query", &pk.h_query, &initial.h_query)].iter() {
+        let factors: Vec<Fr> = (0..current.len()).map(|_| rng.field()).collect();
+        let current = multi_exp(current, &factors).to_affine();
+        let initial_points = multi_exp(initial_points, &factors).to_affine();
+        if !pairing::pairing_check(&[(-current, pk.delta_g2), (initial_points, g2)]) {
+            return Err(Phase2Error::Mismatch(points));
+        }
+    }
+
+    Ok(())
+
+}
+
+#[cfg(test)]
+mod phase2_test {
+
+    use circuit::r1cs_file::R1CSFile;
+    use curve::bn254::G1;
+    use field::bn254::Fr;
+    use field::PrimeField;
+    use groth16::phase2::{self, Phase2Error};
+    use groth16::{self, zkey, Groth16Error};
+    use ptau::Ptau;
+    use rng::TestRng;
+    use witness::wtns;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_ceremony() {
+
+        let r1cs = R1CSFile::<Fr>::load(std::path::Path::new("./src/lib/parser/test_material/proving/num2bits.r1cs")).unwrap().r1cs;
+        let witness = wtns::load::<Fr>(std::path::Path::new("./src/lib/parser/test_material/proving/num2bits.wtns")).unwrap();
+        let public = &witness[1..=r1cs.n_public()];
+
+        let mut rng = TestRng::new(6);
+        let ptau = Ptau::generate(6, & mut rng);
+
+        expect_that!(&phase2::new_key(&r1cs, &Ptau::generate(5, & mut rng)), is(eq(Err(Groth16Error::PtauTooSmall { needed: 64, available: 32 }))));
+
+        let mut pk = phase2::new_key(&r1cs, &ptau).unwrap();
+        expect_that!(&zkey::check_r1cs(&pk, &r1cs, & mut rng), is(eq(Ok(()))));
+        expect_that!(&phase2::verify_contributions(&pk, &r1cs, &ptau, & mut rng), is(eq(Ok(()))));
+
+        let first = phase2::contribute(& mut pk, Some("first"), & mut rng).unwrap();
+        phase2::contribute(& mut pk, None, & mut rng).unwrap();
+
+        let mut data: Vec<u8> = Vec::new();
+        zkey::write(&pk, & mut data).unwrap();
+        let pk = zkey::read(data.as_slice()).unwrap();
+
+        let contributions = &pk.mpc.as_ref().unwrap().contributions;
+        expect_that!(&contributions.len(), is(eq(2)));
+        expect_that!(&contributions[0].transcript, is(eq(first)));
+        expect_that!(&contributions[0].name, is(eq(Some("first".to_string()))));
+        expect_that!(&phase2::verify_contributions(&pk, &r1cs, &ptau, & mut rng), is(eq(Ok(()))));
+
+        let proof = groth16::prove(&pk, &witness, & mut rng).unwrap();
+        expect_that!(&groth16::verify(&pk.verifying_key(), public, &proof), is(eq(Ok(true))));
+
+        let mut tampered = pk.clone();
+        tampered.mpc.as_mut().unwrap().contributions[0].g1_sx = G1::generator().to_affine();
+        expect_that!(&phase2::verify_contributions(&tampered, &r1cs, &ptau, & mut rng), is(eq(Err(Phase2Error::InvalidContribution(0)))));
+
+        let mut tampered = pk.clone();
+        tampered.h_query[2] = G1::generator().to_affine();
+        expect_that!(&phase2::verify_contributions(&tampered, &r1cs, &ptau, & mut rng), is(eq(Err(Phase2Error::Mismatch("H query")))));
+
+        let mut tampered = pk.clone();
+        tampered.mpc.as_mut().unwrap().contributions.pop();
+        expect_that!(&phase2::verify_contributions(&tampered, &r1cs, &ptau, & mut rng), is(eq(Err(Phase2Error::FinalDelta))));
+
+        let mut other = r1cs.clone();
+        other.constraints.swap(0, 1);
+        expect_that!(&phase2::verify_contributions(&pk, &other, &ptau, & mut rng), is(eq(Err(Phase2Error::CircuitHash))));
+
+        let setup = groth16::setup(&r1cs, & mut rng).unwrap();
+        expect_that!(&phase2::verify_contributions(&setup, &r1cs, &ptau, & mut rng), is(eq(Err(Phase2Error::NotCeremonyKey))));
+
+    }
+
+    #[test]
+    fn test_transcript_point() {
+
+        let first = phase2::transcript_point(&[0u8; 64]);
+        let second = phase2::transcript_point(&[1u8; 64]);
+
+        expect_that!(&first.is_on_curve(), is(eq(true)));
+        expect_that!(&first.mul(&Fr::modulus()).is_identity(), is(eq(true)));
+        expect_that!(&first.infinity, is(eq(false)));
+        expect_that!(&(first == second), is(eq(false)));
+
+    }
+
+    #[test]
+    #[ignore = "needs the fixtures written by src/lib/parser/test_material/snarkjs/generate.sh"]
+    fn test_snarkjs_initial_key() {
+
+        let r1cs = R1CSFile::<Fr>::load(std::path::Path::new("./src/lib/parser/test_material/snarkjs/num2bits.r1cs")).unwrap().r1cs;
+        let ptau = Ptau::load(std::path::Path::new("./src/lib/parser/test_material/snarkjs/pot8.ptau")).unwrap();
+        let initial = zkey::load(std::path::Path::new("./src/lib/parser/test_material/snarkjs/num2bits_0000.zkey")).unwrap();
+        let contributed = zkey::load(std::path::Path::new("./src/lib/parser/test_material/snarkjs/num2bits.zkey")).unwrap();
+
+        let mut rng = TestRng::new(11);
+        expect_that!(&phase2::new_key(&r1cs, &ptau), is(eq(Ok(initial.clone()))));
+        expect_that!(&phase2::verify_contributions(&initial, &r1cs, &ptau, & mut rng), is(eq(Ok(()))));
+        expect_that!(&phase2::verify_contributions(&contributed, &r1cs, &ptau, & mut rng), is(eq(Ok(()))));
+
+        // A snarkrs contribution on top of the snarkjs one
+        let mut pk = contributed.clone();
+        phase2::contribute(& mut pk, Some("third"), & mut rng).unwrap();
+        expect_that!(&phase2::verify_contributions(&pk, &r1cs, &ptau, & mut rng), is(eq(Ok(()))));
+
+    }
+
+}
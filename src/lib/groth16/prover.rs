@@ -0,0 +1,70 @@
+use curve::{multi_exp, Engine};
+use field::domain::{self, Domain};
+use field::PrimeField;
+use groth16::{Groth16Error, Matrix, Proof, ProvingKey};
+use rng::Rng;
+
+///
+/// Evaluations of `A(x) * B(x) - C(x)` on the odd points of the domain of size `2 * domain_size`,
+/// the quotient by the vanishing polynomial being then applied by the `H` query.
+///
+fn quotient_evaluations<E: Engine>(pk: & ProvingKey<E>, witness: & [E::Fr]) -> Result<Vec<E::Fr>, Groth16Error> {
+
+    let domain = Domain::<E::Fr>::new(pk.domain_size).ok_or(Groth16Error::DomainTooLarge(pk.domain_size))?;
+    let shift = domain::root_of_unity::<E::Fr>(domain.log_size + 1).ok_or(Groth16Error::DomainTooLarge(2 * pk.domain_size))?;
+
+    let mut a = vec!(E::Fr::zero(); domain.size);
+    let mut b = vec!(E::Fr::zero(); domain.size);
+
+    for coefficient in &pk.coefficients {
+        let target = match coefficient.matrix {
+            Matrix::A => & mut a,
+            Matrix::B => & mut b
+        };
+        target[coefficient.constraint] += coefficient.value * witness[coefficient.signal];
+    }
+
+    // For a valid witness, C(x) agrees with A(x) * B(x) on the domain.
+    let mut c: Vec<E::Fr> = a.iter().zip(b.iter()).map(|(a, b)| *a * *b).collect();
+
+    for values in [& mut a, & mut b, & mut c] {
+        domain.ifft(values);
+        domain.coset_fft(values, &shift);
+    }
+
+    Ok(a.iter().zip(b.iter()).zip(c.iter()).map(|((a, b), c)| *a * *b - *c).collect())
+
+}
+
+///
+/// Proof for a full witness (the one wire first, as in `.wtns` files), blinded with randomness
+/// drawn from `rng`.
+///
+pub fn prove<E: Engine, R: Rng>(pk: & ProvingKey<E>, witness: & [E::Fr], rng: & mut R) -> Result<Proof<E>, Groth16Error> {
+
+    if witness.len() != pk.n_vars {
+        return Err(Groth16Error::WitnessLength { expected: pk.n_vars, found: witness.len() });
+    }
+
+    let r: E::Fr = rng.field();
+    let s: E::Fr = rng.field();
+
+    let h = quotient_evaluations(pk, witness)?;
+
+    let a = pk.alpha_g1.to_projective() + multi_exp(&pk.a_query, witness) + pk.delta_g1.mul(&r.to_u256());
+    let b = pk.beta_g2.to_projective() + multi_exp(&pk.b_g2_query, witness) + pk.delta_g2.mul(&s.to_u256());
+    let b_g1 = pk.beta_g1.to_projective() + multi_exp(&pk.b_g1_query, witness) + pk.delta_g1.mul(&s.to_u256());
+
+    let c = multi_exp(&pk.c_query, &witness[pk.n_public + 1..])
+        + multi_exp(&pk.h_query, &h)
+        + a.mul_scalar(&s)
+        + b_g1.mul_scalar(&r)
+        - pk.delta_g1.mul(&(r * s).to_u256());
+
+    Ok(Proof {
+        a: a.to_affine(),
+        b: b.to_affine(),
+        c: c.to_affine()
+    })
+
+}
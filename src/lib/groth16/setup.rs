@@ -0,0 +1,139 @@
+use circuit::r1cs::{LinearCombination, R1CS};
+use curve::{Affine, CurveParams, Engine, Projective};
+use field::domain::{self, Domain};
+use field::PrimeField;
+use groth16::{Coefficient, Groth16Error, Matrix, ProvingKey};
+use parallel;
+use rng::Rng;
+
+fn add_evaluations<F: PrimeField>(evaluations: & mut [F], lc: & LinearCombination<F>, lagrange: & F) {
+    for (wire, coefficient) in lc.terms() {
+        evaluations[*wire] += *coefficient * *lagrange;
+    }
+}
+
+///
+/// `scalar * G` for each scalar, `G` being the generator of the curve, computed by the threads.
+///
+fn mul_generator<C: CurveParams, F: PrimeField>(scalars: & [F]) -> Vec<Affine<C>> {
+
+    let generator = Projective::<C>::generator();
+
+    parallel::map_chunks(parallel::threads(), scalars, 64, |_, chunk| chunk.iter().map(|scalar| generator.mul_scalar(scalar).to_affine()).collect::<Vec<_>>())
+        .into_iter()
+        .flatten()
+        .collect()
+
+}
+
+///
+/// Entries of the `A` and `B` matrices of the QAP of a constraint system: the ones of the
+/// constraints, then the extra `A` constraints of the one wire and the public signals.
+///
+pub fn coefficients<F: PrimeField>(r1cs: & R1CS<F>) -> Vec<Coefficient<F>> {
+
+    let mut coefficients = Vec::new();
+
+    for (idx, constraint) in r1cs.constraints.iter().enumerate() {
+        for (matrix, lc) in [(Matrix::A, &constraint.a), (Matrix::B, &constraint.b)].iter() {
+            for (wire, value) in lc.terms() {
+                coefficients.push(Coefficient {
+                    matrix: *matrix,
+                    constraint: idx,
+                    signal: *wire,
+                    value: *value
+                });
+            }
+        }
+    }
+
+    for signal in 0..=r1cs.n_public() {
+        coefficients.push(Coefficient {
+            matrix: Matrix::A,
+            constraint: r1cs.constraints.len() + signal,
+            signal,
+            value: F::one()
+        });
+    }
+
+    coefficients
+
+}
+
+///
+/// Circuit specific setup with fresh toxic waste drawn from `rng`, which is forgotten once the keys
+/// are computed.
+///
+pub fn setup<E: Engine, R: Rng>(r1cs: & R1CS<E::Fr>, rng: & mut R) -> Result<ProvingKey<E>, Groth16Error> {
+
+    let n_public = r1cs.n_public();
+    let n_constraints = r1cs.constraints.len();
+    let min_size = n_constraints + n_public + 1;
+
+    // The prover works over the domain of double size.
+    let domain = match Domain::<E::Fr>::new(min_size) {
+        Some(domain) if domain.log_size < domain::two_adicity::<E::Fr>() => domain,
+        _ => return Err(Groth16Error::DomainTooLarge(min_size.next_power_of_two()))
+    };
+    let double_domain = Domain::<E::Fr>::new(2 * domain.size).unwrap();
+
+    let tau: E::Fr = rng.nonzero_field();
+    let alpha: E::Fr = rng.nonzero_field();
+    let beta: E::Fr = rng.nonzero_field();
+    let gamma: E::Fr = rng.nonzero_field();
+    let delta: E::Fr = rng.nonzero_field();
+
+    let lagrange = domain.lagrange_coefficients(&tau);
+
+    let mut a = vec!(E::Fr::zero(); r1cs.n_wires);
+    let mut b = vec!(E::Fr::zero(); r1cs.n_wires);
+    let mut c = vec!(E::Fr::zero(); r1cs.n_wires);
+
+    for (idx, constraint) in r1cs.constraints.iter().enumerate() {
+        add_evaluations(& mut a, &constraint.a, &lagrange[idx]);
+        add_evaluations(& mut b, &constraint.b, &lagrange[idx]);
+        add_evaluations(& mut c, &constraint.c, &lagrange[idx]);
+    }
+
+    for signal in 0..=n_public {
+        a[signal] += lagrange[n_constraints + signal];
+    }
+
+    let gamma_inverse = gamma.inverse().unwrap();
+    let delta_inverse = delta.inverse().unwrap();
+
+    let g1 = Projective::<E::G1>::generator();
+    let g2 = Projective::<E::G2>::generator();
+
+    let combined = |signal: usize| beta * a[signal] + alpha * b[signal] + c[signal];
+
+    let ic: Vec<E::Fr> = (0..=n_public).map(|signal| combined(signal) * gamma_inverse).collect();
+    let c_query: Vec<E::Fr> = (n_public + 1..r1cs.n_wires).map(|signal| combined(signal) * delta_inverse).collect();
+
+    let h_query: Vec<E::Fr> = double_domain.lagrange_coefficients(&tau).iter()
+        .skip(1)
+        .step_by(2)
+        .map(|lagrange| *lagrange * delta_inverse)
+        .collect();
+
+    Ok(ProvingKey {
+        n_vars: r1cs.n_wires,
+        n_public,
+        domain_size: domain.size,
+        alpha_g1: g1.mul_scalar(&alpha).to_affine(),
+        beta_g1: g1.mul_scalar(&beta).to_affine(),
+        beta_g2: g2.mul_scalar(&beta).to_affine(),
+        gamma_g2: g2.mul_scalar(&gamma).to_affine(),
+        delta_g1: g1.mul_scalar(&delta).to_affine(),
+        delta_g2: g2.mul_scalar(&delta).to_affine(),
+        ic: mul_generator::<E::G1, _>(&ic),
+        coefficients: coefficients(r1cs),
+        a_query: mul_generator::<E::G1, _>(&a),
+        b_g1_query: mul_generator::<E::G1, _>(&b),
+        b_g2_query: mul_generator::<E::G2, _>(&b),
+        c_query: mul_generator::<E::G1, _>(&c_query),
+        h_query: mul_generator::<E::G1, _>(&h_query),
+        mpc: None
+    })
+
+}
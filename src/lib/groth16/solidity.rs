@@ -0,0 +1,295 @@
+//
+// Solidity verifier contracts and the calldata of their `verifyProof` function, with the interface
+// of the contracts snarkjs generates:
+//
+//     verifyProof(uint[2] _pA, uint[2][2] _pB, uint[2] _pC, uint[nPublic] _pubSignals) returns (bool)
+//
+// The verifying key is embedded as constants and the pairing is checked with the BN254 precompiles
+// of EIP-196 and EIP-197, which expect the coefficients of Fq2 elements imaginary part first.
+//
+
+use std::fmt::Write;
+
+use curve::bn254::{Fq2, G1Affine};
+use field::bn254::{Fq, Fr};
+use field::PrimeField;
+use groth16::{Proof, VerifyingKey};
+
+const HEADER: & str = "\
+// SPDX-License-Identifier: GPL-3.0
+//
+// Groth16 verifier generated by snarkrs.
+//
+
+pragma solidity >=0.7.0 <0.9.0;
+
+contract Groth16Verifier {
+";
+
+const PAIRING: & str = "
+    // Memory layout: the accumulated public input point, then the input of the pairing check
+    uint16 constant pVk = 0;
+    uint16 constant pPairing = 128;
+
+    uint16 constant pLastMem = 896;
+";
+
+const FUNCTIONS: & str = "
+            // Returns false if v is not an element of the scalar field
+            function checkField(v) {
+                if iszero(lt(v, r)) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+            }
+
+            // Adds s * (x, y) to the point at pR, with the ecMul and ecAdd precompiles
+            function g1_mulAccC(pR, x, y, s) {
+                let success
+                let mIn := mload(0x40)
+                mstore(mIn, x)
+                mstore(add(mIn, 32), y)
+                mstore(add(mIn, 64), s)
+
+                success := staticcall(sub(gas(), 2000), 7, mIn, 96, mIn, 64)
+
+                if iszero(success) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+
+                mstore(add(mIn, 64), mload(pR))
+                mstore(add(mIn, 96), mload(add(pR, 32)))
+
+                success := staticcall(sub(gas(), 2000), 6, mIn, 128, pR, 64)
+
+                if iszero(success) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+            }
+";
+
+const CHECK_PAIRING: & str = "
+                // -A
+                mstore(_pPairing, calldataload(pA))
+                mstore(add(_pPairing, 32), mod(sub(q, calldataload(add(pA, 32))), q))
+
+                // B
+                mstore(add(_pPairing, 64), calldataload(pB))
+                mstore(add(_pPairing, 96), calldataload(add(pB, 32)))
+                mstore(add(_pPairing, 128), calldataload(add(pB, 64)))
+                mstore(add(_pPairing, 160), calldataload(add(pB, 96)))
+
+                // alpha1
+                mstore(add(_pPairing, 192), alphax)
+                mstore(add(_pPairing, 224), alphay)
+
+                // beta2
+                mstore(add(_pPairing, 256), betax1)
+                mstore(add(_pPairing, 288), betax2)
+                mstore(add(_pPairing, 320), betay1)
+                mstore(add(_pPairing, 352), betay2)
+
+                // vk_x
+                mstore(add(_pPairing, 384), mload(add(pMem, pVk)))
+                mstore(add(_pPairing, 416), mload(add(pMem, add(pVk, 32))))
+
+                // gamma2
+                mstore(add(_pPairing, 448), gammax1)
+                mstore(add(_pPairing, 480), gammax2)
+                mstore(add(_pPairing, 512), gammay1)
+                mstore(add(_pPairing, 544), gammay2)
+
+                // C
+                mstore(add(_pPairing, 576), calldataload(pC))
+                mstore(add(_pPairing, 608), calldataload(add(pC, 32)))
+
+                // delta2
+                mstore(add(_pPairing, 640), deltax1)
+                mstore(add(_pPairing, 672), deltax2)
+                mstore(add(_pPairing, 704), deltay1)
+                mstore(add(_pPairing, 736), deltay2)
+
+                let success := staticcall(sub(gas(), 2000), 8, _pPairing, 768, _pPairing, 0x20)
+
+                isOk := and(success, mload(_pPairing))
+            }
+";
+
+const FOOTER: & str = "
+            // Validate all evaluations
+            let isValid := checkPairing(_pA, _pB, _pC, _pubSignals, pMem)
+
+            mstore(0, isValid)
+            return(0, 0x20)
+        }
+    }
+}
+";
+
+///
+/// Coordinates of a point, the identity being `(0, 0)` as for the precompiles.
+///
+fn g1_coordinates(point: & G1Affine) -> (Fq, Fq) {
+    if point.infinity {
+        (Fq::zero(), Fq::zero())
+    } else {
+        (point.x, point.y)
+    }
+}
+
+fn write_g2_constants(source: & mut std::string::String, name: & str, x: & Fq2, y: & Fq2) {
+    let _ = writeln!(source, "    uint256 constant {}x1 = {};", name, x.c1);
+    let _ = writeln!(source, "    uint256 constant {}x2 = {};", name, x.c0);
+    let _ = writeln!(source, "    uint256 constant {}y1 = {};", name, y.c1);
+    let _ = writeln!(source, "    uint256 constant {}y2 = {};", name, y.c0);
+}
+
+///
+/// Source of a contract verifying the proofs of a key.
+///
+pub fn verifier_contract(vk: & VerifyingKey) -> std::string::String {
+
+    let n_public = vk.ic.len() - 1;
+    let mut source = std::string::String::from(HEADER);
+
+    let _ = writeln!(source, "    // Scalar field size");
+    let _ = writeln!(source, "    uint256 constant r    = {};", Fr::modulus());
+    let _ = writeln!(source, "    // Base field size");
+    let _ = writeln!(source, "    uint256 constant q   = {};", Fq::modulus());
+    let _ = writeln!(source);
+
+    let (alpha_x, alpha_y) = g1_coordinates(&vk.alpha_g1);
+    let _ = writeln!(source, "    // Verification Key data");
+    let _ = writeln!(source, "    uint256 constant alphax  = {};", alpha_x);
+    let _ = writeln!(source, "    uint256 constant alphay  = {};", alpha_y);
+    write_g2_constants(& mut source, "beta", &vk.beta_g2.x, &vk.beta_g2.y);
+    write_g2_constants(& mut source, "gamma", &vk.gamma_g2.x, &vk.gamma_g2.y);
+    write_g2_constants(& mut source, "delta", &vk.delta_g2.x, &vk.delta_g2.y);
+    let _ = writeln!(source);
+
+    for (idx, point) in vk.ic.iter().enumerate() {
+        let (x, y) = g1_coordinates(point);
+        let _ = writeln!(source, "    uint256 constant IC{}x = {};", idx, x);
+        let _ = writeln!(source, "    uint256 constant IC{}y = {};", idx, y);
+    }
+
+    source.push_str(PAIRING);
+    let _ = writeln!(source);
+    let _ = writeln!(source, "    function verifyProof(uint[2] calldata _pA, uint[2][2] calldata _pB, uint[2] calldata _pC, uint[{}] calldata _pubSignals) public view returns (bool) {{", n_public);
+    let _ = writeln!(source, "        assembly {{");
+    source.push_str(FUNCTIONS);
+
+    let _ = writeln!(source);
+    let _ = writeln!(source, "            function checkPairing(pA, pB, pC, pubSignals, pMem) -> isOk {{");
+    let _ = writeln!(source, "                let _pPairing := add(pMem, pPairing)");
+    let _ = writeln!(source, "                let _pVk := add(pMem, pVk)");
+    let _ = writeln!(source);
+    let _ = writeln!(source, "                mstore(_pVk, IC0x)");
+    let _ = writeln!(source, "                mstore(add(_pVk, 32), IC0y)");
+    let _ = writeln!(source);
+    let _ = writeln!(source, "                // Compute the linear combination vk_x");
+    for idx in 1..=n_public {
+        let _ = writeln!(source, "                g1_mulAccC(_pVk, IC{}x, IC{}y, calldataload(add(pubSignals, {})))", idx, idx, (idx - 1) * 32);
+    }
+    source.push_str(CHECK_PAIRING);
+
+    let _ = writeln!(source);
+    let _ = writeln!(source, "            let pMem := mload(0x40)");
+    let _ = writeln!(source, "            mstore(0x40, add(pMem, pLastMem))");
+    let _ = writeln!(source);
+    let _ = writeln!(source, "            // Validate that all evaluations are in F");
+    for idx in 0..n_public {
+        let _ = writeln!(source, "            checkField(calldataload(add(_pubSignals, {})))", idx * 32);
+    }
+    source.push_str(FOOTER);
+
+    source
+
+}
+
+fn word<F: PrimeField>(value: & F) -> std::string::String {
+    format!("\"0x{:0>64}\"", value.to_u256().to_hex_string())
+}
+
+///
+/// Arguments of `verifyProof` for a proof, in the format of `snarkjs zkey export soliditycalldata`.
+///
+pub fn calldata(proof: & Proof, public: & [Fr]) -> std::string::String {
+
+    let (a_x, a_y) = g1_coordinates(&proof.a);
+    let (c_x, c_y) = g1_coordinates(&proof.c);
+    let b = &proof.b;
+    let inputs: Vec<std::string::String> = public.iter().map(word).collect();
+
+    format!(
+        "[{}, {}],[[{}, {}],[{}, {}]],[{}, {}],[{}]",
+        word(&a_x), word(&a_y),
+        word(&b.x.c1), word(&b.x.c0), word(&b.y.c1), word(&b.y.c0),
+        word(&c_x), word(&c_y),
+        inputs.join(",")
+    )
+
+}
+
+#[cfg(test)]
+mod solidity_test {
+
+    use curve::bn254::{G1, G2};
+    use field::bn254::Fr;
+    use field::PrimeField;
+    use groth16::solidity;
+    use groth16::{Proof, VerifyingKey};
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_verifier_contract() {
+
+        let g2 = G2::generator().to_affine();
+        let vk = VerifyingKey {
+            alpha_g1: G1::generator().to_affine(),
+            beta_g2: g2,
+            gamma_g2: g2,
+            delta_g2: G2::generator().double().to_affine(),
+            ic: vec!(G1::generator().mul_scalar(&Fr::from_u64(3)).to_affine(), G1::generator().to_affine())
+        };
+
+        let source = solidity::verifier_contract(&vk);
+
+        expect_that!(&source.contains("contract Groth16Verifier {"), is(eq(true)));
+        expect_that!(&source.contains("uint256 constant r    = 21888242871839275222246405745257275088548364400416034343698204186575808495617;"), is(eq(true)));
+        expect_that!(&source.contains("uint256 constant q   = 21888242871839275222246405745257275088696311157297823662689037894645226208583;"), is(eq(true)));
+        expect_that!(&source.contains("uint256 constant alphax  = 1;\n    uint256 constant alphay  = 2;"), is(eq(true)));
+        expect_that!(&source.contains("uint256 constant betax1 = 11559732032986387107991004021392285783925812861821192530917403151452391805634;"), is(eq(true)));
+        expect_that!(&source.contains("uint256 constant betax2 = 10857046999023057135944570762232829481370756359578518086990519993285655852781;"), is(eq(true)));
+        expect_that!(&source.contains("uint256 constant gammay1 = 4082367875863433681332203403145435568316851327593401208105741076214120093531;"), is(eq(true)));
+        expect_that!(&source.contains("uint256 constant gammay2 = 8495653923123431417604973247489272438418190587263600148770280649306958101930;"), is(eq(true)));
+        expect_that!(&source.contains("uint256 constant IC0x = 3353031288059533942658390886683067124040920775575537747144343083137631628272;"), is(eq(true)));
+        expect_that!(&source.contains("uint256 constant IC1x = 1;\n    uint256 constant IC1y = 2;"), is(eq(true)));
+        expect_that!(&source.contains("uint[1] calldata _pubSignals"), is(eq(true)));
+        expect_that!(&source.contains("g1_mulAccC(_pVk, IC1x, IC1y, calldataload(add(pubSignals, 0)))"), is(eq(true)));
+        expect_that!(&source.contains("IC2x"), is(eq(false)));
+
+    }
+
+    #[test]
+    fn test_calldata() {
+
+        let proof = Proof {
+            a: G1::generator().to_affine(),
+            b: G2::generator().to_affine(),
+            c: G1::generator().to_affine()
+        };
+
+        let calldata = solidity::calldata(&proof, &[Fr::from_u64(33), Fr::from_u64(255)]);
+        let one = format!("\"0x{:0>64}\"", "1");
+        let two = format!("\"0x{:0>64}\"", "2");
+
+        expect_that!(&calldata.starts_with(&format!("[{}, {}],[[\"0x198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\", \"0x1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\"],", one, two)), is(eq(true)));
+        expect_that!(&calldata.ends_with(&format!("[{}, {}],[\"0x{:0>64}\",\"0x{:0>64}\"]", one, two, "21", "ff")), is(eq(true)));
+
+    }
+
+}
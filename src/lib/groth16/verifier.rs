@@ -0,0 +1,27 @@
+use curve::{multi_exp, Engine};
+use groth16::{Groth16Error, Proof, VerifyingKey};
+
+///
+/// Checks `e(A, B) = e(alpha, beta) * e(IC(public), gamma) * e(C, delta)`. Proofs whose points are
+/// not on the curves are rejected.
+///
+pub fn verify<E: Engine>(vk: & VerifyingKey<E>, public: & [E::Fr], proof: & Proof<E>) -> Result<bool, Groth16Error> {
+
+    if public.len() + 1 != vk.ic.len() {
+        return Err(Groth16Error::PublicSignalCount { expected: vk.ic.len() - 1, found: public.len() });
+    }
+
+    if !proof.a.is_on_curve() || !proof.b.is_on_curve() || !proof.c.is_on_curve() {
+        return Ok(false);
+    }
+
+    let ic = vk.ic[0].to_projective() + multi_exp(&vk.ic[1..], public);
+
+    Ok(E::pairing_check(&[
+        (-proof.a, proof.b),
+        (vk.alpha_g1, vk.beta_g2),
+        (ic.to_affine(), vk.gamma_g2),
+        (proof.c, vk.delta_g2)
+    ]))
+
+}
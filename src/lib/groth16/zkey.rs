@@ -0,0 +1,486 @@
+use std::collections::BTreeMap;
+
+use binfile::{self, BinFile, BinFileError, SectionReader};
+use circuit::r1cs::R1CS;
+use curve::bn254::binary::{self, G1_SIZE, G2_SIZE};
+use curve::bn254::pairing;
+use curve::bn254::{G1, G2};
+use curve::multi_exp;
+use field::bigint::U256;
+use field::bn254::{Fq, Fr, FrParams};
+use field::domain;
+use field::fp::FpParams;
+use field::PrimeField;
+use groth16::phase2::{Contribution, MpcParams};
+use groth16::{setup, Coefficient, Matrix, ProvingKey};
+use rng::Rng;
+
+///
+/// snarkjs `.zkey` Groth16 proving key file (version 1):
+///
+/// - section 1, header: `protocol:u32`, 1 for Groth16
+/// - section 2, Groth16 header: `n8q:u32 q[n8q] n8r:u32 r[n8r] n_vars:u32 n_public:u32
+///   domain_size:u32 alpha_1 beta_1 beta_2 gamma_2 delta_1 delta_2`
+/// - section 3, IC: `n_public + 1` G1 points
+/// - section 4, coefficients: `n:u32 (matrix:u32 constraint:u32 signal:u32 value[n8r])*`, the values
+///   being multiplied by `R^2` for provers working in Montgomery form
+/// - sections 5 to 9, queries: A (G1), B (G1), B (G2), C (G1, private signals only) and H (G1,
+///   `domain_size` points)
+/// - section 10, phase 2 contributions: `cs_hash[64] n:u32` then per contribution `delta_after
+///   g1_s g1_sx g2_spx transcript[64] type:u32 parameters_size:u32 parameters`, the parameters being
+///   `1:u8 size:u8 name[size]` for named contributions. Beacon contributions (type 1) are not
+///   supported.
+///
+/// Points are encoded as described in `curve::bn254::binary`.
+///
+pub const MAGIC: & [u8; 4] = b"zkey";
+pub const VERSION: u32 = 1;
+
+const GROTH16_PROTOCOL: u32 = 1;
+
+const HEADER_SECTION: u32 = 1;
+const GROTH16_HEADER_SECTION: u32 = 2;
+const IC_SECTION: u32 = 3;
+const COEFFICIENTS_SECTION: u32 = 4;
+const A_SECTION: u32 = 5;
+const B_G1_SECTION: u32 = 6;
+const B_G2_SECTION: u32 = 7;
+const C_SECTION: u32 = 8;
+const H_SECTION: u32 = 9;
+const CONTRIBUTIONS_SECTION: u32 = 10;
+
+const NAME_PARAMETER: u8 = 1;
+
+fn montgomery_factor() -> Fr {
+    Fr::from_u256(&U256(FrParams::R2))
+}
+
+pub fn to_binfile(pk: & ProvingKey) -> BinFile {
+
+    let mut file = BinFile::new(MAGIC, VERSION);
+
+    let mut header: Vec<u8> = Vec::new();
+    binfile::push_u32(& mut header, GROTH16_PROTOCOL);
+    file.add_section(HEADER_SECTION, header);
+
+    let mut groth16_header: Vec<u8> = Vec::new();
+    binfile::push_u32(& mut groth16_header, binfile::field_size::<Fq>() as u32);
+    binfile::push_prime::<Fq>(& mut groth16_header);
+    binfile::push_u32(& mut groth16_header, binfile::field_size::<Fr>() as u32);
+    binfile::push_prime::<Fr>(& mut groth16_header);
+    binfile::push_u32(& mut groth16_header, pk.n_vars as u32);
+    binfile::push_u32(& mut groth16_header, pk.n_public as u32);
+    binfile::push_u32(& mut groth16_header, pk.domain_size as u32);
+    binary::push_g1(& mut groth16_header, &pk.alpha_g1);
+    binary::push_g1(& mut groth16_header, &pk.beta_g1);
+    binary::push_g2(& mut groth16_header, &pk.beta_g2);
+    binary::push_g2(& mut groth16_header, &pk.gamma_g2);
+    binary::push_g1(& mut groth16_header, &pk.delta_g1);
+    binary::push_g2(& mut groth16_header, &pk.delta_g2);
+    file.add_section(GROTH16_HEADER_SECTION, groth16_header);
+
+    let g1_section = |points: & [::curve::bn254::G1Affine]| {
+        let mut data = Vec::with_capacity(points.len() * G1_SIZE);
+        for point in points {
+            binary::push_g1(& mut data, point);
+        }
+        data
+    };
+
+    file.add_section(IC_SECTION, g1_section(&pk.ic));
+
+    let factor = montgomery_factor();
+    let mut coefficients: Vec<u8> = Vec::new();
+    binfile::push_u32(& mut coefficients, pk.coefficients.len() as u32);
+    for coefficient in &pk.coefficients {
+        binfile::push_u32(& mut coefficients, match coefficient.matrix { Matrix::A => 0, Matrix::B => 1 });
+        binfile::push_u32(& mut coefficients, coefficient.constraint as u32);
+        binfile::push_u32(& mut coefficients, coefficient.signal as u32);
+        binfile::push_field(& mut coefficients, &(coefficient.value * factor));
+    }
+    file.add_section(COEFFICIENTS_SECTION, coefficients);
+
+    file.add_section(A_SECTION, g1_section(&pk.a_query));
+    file.add_section(B_G1_SECTION, g1_section(&pk.b_g1_query));
+
+    let mut b_g2: Vec<u8> = Vec::with_capacity(pk.b_g2_query.len() * G2_SIZE);
+    for point in &pk.b_g2_query {
+        binary::push_g2(& mut b_g2, point);
+    }
+    file.add_section(B_G2_SECTION, b_g2);
+
+    file.add_section(C_SECTION, g1_section(&pk.c_query));
+    file.add_section(H_SECTION, g1_section(&pk.h_query));
+
+    if let Some(mpc) = &pk.mpc {
+        file.add_section(CONTRIBUTIONS_SECTION, mpc_section(mpc));
+    }
+
+    file
+
+}
+
+fn mpc_section(mpc: & MpcParams) -> Vec<u8> {
+
+    let mut data: Vec<u8> = Vec::new();
+    data.extend_from_slice(&mpc.cs_hash);
+    binfile::push_u32(& mut data, mpc.contributions.len() as u32);
+
+    for contribution in &mpc.contributions {
+
+        binary::push_g1(& mut data, &contribution.delta_after);
+        binary::push_g1(& mut data, &contribution.g1_s);
+        binary::push_g1(& mut data, &contribution.g1_sx);
+        binary::push_g2(& mut data, &contribution.g2_spx);
+        data.extend_from_slice(&contribution.transcript);
+        binfile::push_u32(& mut data, 0);
+
+        let mut parameters: Vec<u8> = Vec::new();
+        if let Some(name) = &contribution.name {
+            let mut end = name.len().min(64);
+            while !name.is_char_boundary(end) {
+                end -= 1;
+            }
+            parameters.push(NAME_PARAMETER);
+            parameters.push(end as u8);
+            parameters.extend_from_slice(&name.as_bytes()[..end]);
+        }
+        binfile::push_u32(& mut data, parameters.len() as u32);
+        data.extend_from_slice(&parameters);
+
+    }
+
+    data
+
+}
+
+fn read_hash(reader: & mut SectionReader) -> Result<[u8; 64], BinFileError> {
+    let mut hash = [0u8; 64];
+    hash.copy_from_slice(reader.read_bytes(64)?);
+    Ok(hash)
+}
+
+fn read_mpc_section(data: & [u8]) -> Result<MpcParams, BinFileError> {
+
+    let mut reader = SectionReader::new(data);
+    let cs_hash = read_hash(& mut reader)?;
+    let mut contributions = Vec::new();
+
+    for _ in 0..reader.read_u32()? {
+
+        let delta_after = binary::read_g1(& mut reader)?;
+        let g1_s = binary::read_g1(& mut reader)?;
+        let g1_sx = binary::read_g1(& mut reader)?;
+        let g2_spx = binary::read_g2(& mut reader)?;
+        let transcript = read_hash(& mut reader)?;
+
+        let contribution_type = reader.read_u32()?;
+        if contribution_type != 0 {
+            return Err(BinFileError::FormatError(format!("unsupported contribution type {}", contribution_type)));
+        }
+
+        let size = reader.read_u32()? as usize;
+        let mut parameters = SectionReader::new(reader.read_bytes(size)?);
+        let mut name = None;
+
+        while parameters.remaining() > 0 {
+            match parameters.read_bytes(1)?[0] {
+                NAME_PARAMETER => {
+                    let size = parameters.read_bytes(1)?[0] as usize;
+                    name = Some(std::string::String::from_utf8_lossy(parameters.read_bytes(size)?).into_owned());
+                },
+                parameter => return Err(BinFileError::FormatError(format!("unknown contribution parameter {}", parameter)))
+            }
+        }
+
+        contributions.push(Contribution {
+            delta_after,
+            g1_s,
+            g1_sx,
+            g2_spx,
+            transcript,
+            name
+        });
+
+    }
+
+    Ok(MpcParams {
+        cs_hash,
+        contributions
+    })
+
+}
+
+pub fn from_binfile(file: & BinFile) -> Result<ProvingKey, BinFileError> {
+
+    if file.version != VERSION {
+        return Err(BinFileError::FormatError(format!("unsupported zkey version {}", file.version)));
+    }
+
+    let protocol = SectionReader::new(file.required_section(HEADER_SECTION)?).read_u32()?;
+    if protocol != GROTH16_PROTOCOL {
+        return Err(BinFileError::FormatError(format!("unsupported protocol {}, only Groth16 keys are supported", protocol)));
+    }
+
+    let mut header = SectionReader::new(file.required_section(GROTH16_HEADER_SECTION)?);
+    let n8q = header.read_u32()? as usize;
+    header.expect_prime::<Fq>(n8q)?;
+    let n8r = header.read_u32()? as usize;
+    header.expect_prime::<Fr>(n8r)?;
+
+    let n_vars = header.read_u32()? as usize;
+    let n_public = header.read_u32()? as usize;
+    let domain_size = header.read_u32()? as usize;
+
+    if n_public >= n_vars {
+        return Err(BinFileError::FormatError("more public signals than variables".to_string()));
+    }
+    if !domain_size.is_power_of_two() {
+        return Err(BinFileError::FormatError(format!("domain size {} is not a power of two", domain_size)));
+    }
+    // The prover evaluates the quotient on a coset of the domain of size 2 * domain_size.
+    if domain_size.trailing_zeros() as usize + 1 > domain::two_adicity::<Fr>() {
+        return Err(BinFileError::FormatError(format!("domain size {} is too large for the scalar field", domain_size)));
+    }
+
+    let alpha_g1 = binary::read_g1(& mut header)?;
+    let beta_g1 = binary::read_g1(& mut header)?;
+    let beta_g2 = binary::read_g2(& mut header)?;
+    let gamma_g2 = binary::read_g2(& mut header)?;
+    let delta_g1 = binary::read_g1(& mut header)?;
+    let delta_g2 = binary::read_g2(& mut header)?;
+
+    let g1_section = |section: u32, count: usize| {
+        binary::read_g1_points(& mut SectionReader::new(file.required_section(section)?), count)
+    };
+
+    let factor_inverse = montgomery_factor().inverse().unwrap();
+    let mut reader = SectionReader::new(file.required_section(COEFFICIENTS_SECTION)?);
+    let mut coefficients = Vec::new();
+
+    for _ in 0..reader.read_u32()? {
+
+        let matrix = match reader.read_u32()? {
+            0 => Matrix::A,
+            1 => Matrix::B,
+            matrix => return Err(BinFileError::FormatError(format!("unknown matrix {}", matrix)))
+        };
+        let constraint = reader.read_u32()? as usize;
+        let signal = reader.read_u32()? as usize;
+        let value: Fr = reader.read_field()?;
+
+        if constraint >= domain_size || signal >= n_vars {
+            return Err(BinFileError::FormatError(format!("coefficient out of range, constraint {} signal {}", constraint, signal)));
+        }
+
+        coefficients.push(Coefficient {
+            matrix,
+            constraint,
+            signal,
+            value: value * factor_inverse
+        });
+
+    }
+
+    Ok(ProvingKey {
+        n_vars,
+        n_public,
+        domain_size,
+        alpha_g1,
+        beta_g1,
+        beta_g2,
+        gamma_g2,
+        delta_g1,
+        delta_g2,
+        ic: g1_section(IC_SECTION, n_public + 1)?,
+        coefficients,
+        a_query: g1_section(A_SECTION, n_vars)?,
+        b_g1_query: g1_section(B_G1_SECTION, n_vars)?,
+        b_g2_query: binary::read_g2_points(& mut SectionReader::new(file.required_section(B_G2_SECTION)?), n_vars)?,
+        c_query: g1_section(C_SECTION, n_vars - n_public - 1)?,
+        h_query: g1_section(H_SECTION, domain_size)?,
+        mpc: match file.section(CONTRIBUTIONS_SECTION) {
+            Some(data) => Some(read_mpc_section(data)?),
+            None => None
+        }
+    })
+
+}
+
+pub fn write<W: std::io::Write>(pk: & ProvingKey, writer: & mut W) -> std::io::Result<()> {
+    to_binfile(pk).write(writer)
+}
+
+pub fn read<R: std::io::Read>(reader: R) -> Result<ProvingKey, BinFileError> {
+    from_binfile(&BinFile::read(reader, MAGIC)?)
+}
+
+pub fn save(pk: & ProvingKey, path: & std::path::Path) -> std::io::Result<()> {
+    to_binfile(pk).save(path)
+}
+
+pub fn load(path: & std::path::Path) -> Result<ProvingKey, BinFileError> {
+    from_binfile(&BinFile::load(path, MAGIC)?)
+}
+
+///
+/// Difference between a proving key and the constraint system it is checked against.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZKeyMismatch {
+    Header { field: &'static str, expected: usize, found: usize },
+    Coefficient { matrix: Matrix, constraint: usize, signal: usize },
+    InconsistentPoints(&'static str)
+}
+
+impl std::fmt::Display for ZKeyMismatch {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ZKeyMismatch::Header { field, expected, found } => write!(formatter, "The key has {} {} but the circuit needs {}", found, field, expected),
+            ZKeyMismatch::Coefficient { matrix, constraint, signal } => write!(formatter, "The key and the circuit differ on the coefficient of signal {} in constraint {} of matrix {:?}", signal, constraint, matrix),
+            ZKeyMismatch::InconsistentPoints(points) => write!(formatter, "The {} of the key are inconsistent", points)
+        }
+    }
+}
+
+fn coefficient_map(coefficients: & [Coefficient]) -> BTreeMap<(Matrix, usize, usize), Fr> {
+
+    let mut map: BTreeMap<(Matrix, usize, usize), Fr> = BTreeMap::new();
+
+    for coefficient in coefficients {
+        *map.entry((coefficient.matrix, coefficient.constraint, coefficient.signal)).or_insert_with(Fr::zero) += coefficient.value;
+    }
+
+    map.retain(|_, value| !value.is_zero());
+    map
+
+}
+
+///
+/// Checks that a proving key is one for a constraint system: the sizes and the QAP coefficients
+/// must be the ones `setup` derives from it, and the points given in both groups must encode the
+/// same values. Without the toxic waste, the points cannot be checked against the QAP itself.
+///
+/// The group consistency is checked on a random linear combination drawn from `rng`.
+///
+pub fn check_r1cs<R: Rng>(pk: & ProvingKey, r1cs: & R1CS<Fr>, rng: & mut R) -> Result<(), ZKeyMismatch> {
+
+    let domain_size = (r1cs.constraints.len() + r1cs.n_public() + 1).next_power_of_two();
+
+    for (field, expected, found) in [("variables", r1cs.n_wires, pk.n_vars), ("public signals", r1cs.n_public(), pk.n_public), ("domain elements", domain_size, pk.domain_size)].iter() {
+        if expected != found {
+            return Err(ZKeyMismatch::Header { field, expected: *expected, found: *found });
+        }
+    }
+
+    let expected = coefficient_map(&setup::coefficients(r1cs));
+    let found = coefficient_map(&pk.coefficients);
+
+    if let Some((matrix, constraint, signal)) = expected.keys().chain(found.keys()).find(|key| expected.get(key) != found.get(key)) {
+        return Err(ZKeyMismatch::Coefficient { matrix: *matrix, constraint: *constraint, signal: *signal });
+    }
+
+    let g1 = G1::generator().to_affine();
+    let g2 = G2::generator().to_affine();
+
+    let same_exponent = |p: & G1, q: & G2| pairing::pairing_check(&[(-p.to_affine(), g2), (g1, q.to_affine())]);
+
+    if !same_exponent(&pk.beta_g1.to_projective(), &pk.beta_g2.to_projective()) {
+        return Err(ZKeyMismatch::InconsistentPoints("beta points"));
+    }
+    if !same_exponent(&pk.delta_g1.to_projective(), &pk.delta_g2.to_projective()) {
+        return Err(ZKeyMismatch::InconsistentPoints("delta points"));
+    }
+
+    let factors: Vec<Fr> = (0..pk.n_vars).map(|_| rng.field()).collect();
+    if !same_exponent(&multi_exp(&pk.b_g1_query, &factors), &multi_exp(&pk.b_g2_query, &factors)) {
+        return Err(ZKeyMismatch::InconsistentPoints("B queries"));
+    }
+
+    Ok(())
+
+}
+
+#[cfg(test)]
+mod zkey_test {
+
+    use circuit::r1cs::LinearCombination;
+    use circuit::r1cs_file::R1CSFile;
+    use curve::bn254::G2;
+    use field::bn254::Fr;
+    use field::PrimeField;
+    use groth16::zkey::{self, ZKeyMismatch};
+    use groth16::{self, Groth16Error, Matrix};
+    use rng::TestRng;
+    use witness::wtns;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_zkey() {
+
+        let r1cs = R1CSFile::<Fr>::load(std::path::Path::new("./src/lib/parser/test_material/proving/num2bits.r1cs")).unwrap().r1cs;
+
+        let mut rng = TestRng::new(4);
+        let pk = groth16::setup(&r1cs, & mut rng).unwrap();
+
+        let mut data: Vec<u8> = Vec::new();
+        zkey::write(&pk, & mut data).unwrap();
+        expect_that!(&&data[..4], is(eq(&b"zkey"[..])));
+
+        let read = zkey::read(data.as_slice()).unwrap();
+        expect_that!(&read, is(eq(pk.clone())));
+        expect_that!(&zkey::check_r1cs(&read, &r1cs, & mut rng), is(eq(Ok(()))));
+
+        let mut truncated = data.clone();
+        truncated.truncate(data.len() - 1);
+        expect_that!(&zkey::read(truncated.as_slice()).is_err(), is(eq(true)));
+
+        let mut other = r1cs.clone();
+        other.constraints[0].b = LinearCombination::term(1, Fr::from_u64(2));
+        expect_that!(&zkey::check_r1cs(&pk, &other, & mut rng), is(eq(Err(ZKeyMismatch::Coefficient { matrix: Matrix::B, constraint: 0, signal: 1 }))));
+
+        other.constraints.pop();
+        expect_that!(&zkey::check_r1cs(&pk, &other, & mut rng).is_err(), is(eq(true)));
+
+        let mut tampered = pk.clone();
+        tampered.b_g2_query[3] = (tampered.b_g2_query[3].to_projective() + G2::generator()).to_affine();
+        expect_that!(&zkey::check_r1cs(&tampered, &r1cs, & mut rng), is(eq(Err(ZKeyMismatch::InconsistentPoints("B queries")))));
+
+        // The quotient needs a domain of 2^29 elements, beyond the two-adicity of Fr
+        let mut too_large = pk.clone();
+        too_large.domain_size = 1 << 28;
+        let mut data: Vec<u8> = Vec::new();
+        zkey::write(&too_large, & mut data).unwrap();
+        expect_that!(&zkey::read(data.as_slice()).is_err(), is(eq(true)));
+
+        let witness = wtns::load::<Fr>(std::path::Path::new("./src/lib/parser/test_material/proving/num2bits.wtns")).unwrap();
+        expect_that!(&groth16::prove(&too_large, &witness, & mut rng), is(eq(Err(Groth16Error::DomainTooLarge(1 << 29)))));
+
+    }
+
+    #[test]
+    #[ignore = "needs the fixtures written by src/lib/parser/test_material/snarkjs/generate.sh"]
+    fn test_snarkjs_zkey() {
+
+        let r1cs = R1CSFile::<Fr>::load(std::path::Path::new("./src/lib/parser/test_material/snarkjs/num2bits.r1cs")).unwrap().r1cs;
+        let witness = wtns::load::<Fr>(std::path::Path::new("./src/lib/parser/test_material/snarkjs/num2bits.wtns")).unwrap();
+        let pk = zkey::load(std::path::Path::new("./src/lib/parser/test_material/snarkjs/num2bits.zkey")).unwrap();
+
+        let mut rng = TestRng::new(9);
+        expect_that!(&zkey::check_r1cs(&pk, &r1cs, & mut rng), is(eq(Ok(()))));
+
+        let contributions = &pk.mpc.as_ref().unwrap().contributions;
+        expect_that!(&contributions.len(), is(eq(1)));
+        expect_that!(&contributions[0].name, is(eq(Some("second".to_string()))));
+
+        let mut data: Vec<u8> = Vec::new();
+        zkey::write(&pk, & mut data).unwrap();
+        expect_that!(&data, is(eq(std::fs::read("./src/lib/parser/test_material/snarkjs/num2bits.zkey").unwrap())));
+
+        let proof = groth16::prove(&pk, &witness, & mut rng).unwrap();
+        expect_that!(&groth16::verify(&pk.verifying_key(), &witness[1..=r1cs.n_public()], &proof), is(eq(Ok(true))));
+
+    }
+
+}
@@ -0,0 +1,166 @@
+//
+// Keccak-256, the hash of the snarkjs PLONK transcripts and of Ethereum. It is the original Keccak
+// padding (`0x01`), not the one of the standardized SHA3-256.
+//
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008
+];
+
+const ROTATIONS: [u32; 25] = [
+    0, 1, 62, 28, 27,
+    36, 44, 6, 55, 20,
+    3, 10, 43, 25, 39,
+    41, 45, 15, 21, 8,
+    18, 2, 61, 56, 14
+];
+
+pub const DIGEST_SIZE: usize = 32;
+
+const RATE: usize = 136;
+
+#[derive(Clone)]
+pub struct Keccak256 {
+    state: [u64; 25],
+    buffer: [u8; RATE],
+    buffered: usize
+}
+
+impl Default for Keccak256 {
+    fn default() -> Keccak256 {
+        Keccak256::new()
+    }
+}
+
+///
+/// Keccak-f[1600] permutation, the lanes being indexed by `x + 5 * y`.
+///
+fn permute(state: & mut [u64; 25]) {
+
+    for round_constant in ROUND_CONSTANTS.iter() {
+
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = state[x + 5 * y].rotate_left(ROTATIONS[x + 5 * y]);
+            }
+        }
+
+        for x in 0..5 {
+            for y in 0..5 {
+                state[x + 5 * y] = b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+
+        state[0] ^= round_constant;
+
+    }
+
+}
+
+impl Keccak256 {
+
+    pub fn new() -> Keccak256 {
+        Keccak256 {
+            state: [0; 25],
+            buffer: [0; RATE],
+            buffered: 0
+        }
+    }
+
+    fn absorb(& mut self) {
+
+        for (idx, lane) in self.state.iter_mut().take(RATE / 8).enumerate() {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&self.buffer[idx * 8..idx * 8 + 8]);
+            *lane ^= u64::from_le_bytes(bytes);
+        }
+
+        permute(& mut self.state);
+        self.buffered = 0;
+
+    }
+
+    pub fn update(& mut self, mut data: & [u8]) {
+
+        while !data.is_empty() {
+
+            let taken = (RATE - self.buffered).min(data.len());
+            self.buffer[self.buffered..self.buffered + taken].copy_from_slice(&data[..taken]);
+            self.buffered += taken;
+            data = &data[taken..];
+
+            if self.buffered == RATE {
+                self.absorb();
+            }
+
+        }
+
+    }
+
+    pub fn digest(mut self) -> [u8; DIGEST_SIZE] {
+
+        for byte in self.buffer[self.buffered..].iter_mut() {
+            *byte = 0;
+        }
+        self.buffer[self.buffered] ^= 0x01;
+        self.buffer[RATE - 1] ^= 0x80;
+        self.absorb();
+
+        let mut digest = [0u8; DIGEST_SIZE];
+        for (idx, lane) in self.state.iter().take(DIGEST_SIZE / 8).enumerate() {
+            digest[idx * 8..idx * 8 + 8].copy_from_slice(&lane.to_le_bytes());
+        }
+
+        digest
+
+    }
+
+}
+
+pub fn hash(data: & [u8]) -> [u8; DIGEST_SIZE] {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    hasher.digest()
+}
+
+#[cfg(test)]
+mod keccak_test {
+
+    use blake2b::to_hex;
+    use keccak::{self, Keccak256};
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_keccak() {
+
+        expect_that!(&to_hex(&keccak::hash(b"")), is(eq("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470".to_string())));
+        expect_that!(&to_hex(&keccak::hash(b"abc")), is(eq("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45".to_string())));
+
+        let data: Vec<u8> = (0..1000).map(|idx| (idx % 251) as u8).collect();
+        let mut hasher = Keccak256::new();
+        for chunk in data.chunks(61) {
+            hasher.update(chunk);
+        }
+        expect_that!(&hasher.digest(), is(eq(keccak::hash(&data))));
+
+    }
+
+}
@@ -2,10 +2,31 @@ extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 extern crate core;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_json;
 
 #[cfg(test)]
 #[macro_use]
 extern crate galvanic_assert;
 
 pub mod parser;
-
+pub mod field;
+pub mod circuit;
+pub mod elaborator;
+pub mod witness;
+pub mod binfile;
+pub mod formatter;
+pub mod lsp;
+pub mod lint;
+pub mod curve;
+pub mod rng;
+pub mod parallel;
+pub mod blake2b;
+pub mod keccak;
+pub mod ptau;
+pub mod groth16;
+pub mod plonk;
+pub mod codegen;
@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde_json::Value;
+
+use lint::LintRule;
+
+///
+/// Rules the linter runs. Every rule is enabled unless the configuration disables it.
+///
+/// The configuration file is a JSON object mapping rule names to a boolean:
+///
+/// ```json
+/// { "rules": { "unused-variable": false, "constant-condition": true } }
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LintConfig {
+    disabled: HashSet<LintRule>
+}
+
+impl LintConfig {
+
+    pub fn enabled(&self, rule: LintRule) -> bool {
+        !self.disabled.contains(&rule)
+    }
+
+    pub fn set(& mut self, rule: LintRule, enabled: bool) {
+        if enabled {
+            self.disabled.remove(&rule);
+        } else {
+            self.disabled.insert(rule);
+        }
+    }
+
+    pub fn parse(source: & str) -> Result<LintConfig, std::string::String> {
+
+        let value: Value = serde_json::from_str(source).map_err(|error| error.to_string())?;
+        let mut config = LintConfig::default();
+
+        let rules = match value.get("rules") {
+            None => return Ok(config),
+            Some(Value::Object(rules)) => rules,
+            Some(_) => return Err("\"rules\" must be an object".to_string())
+        };
+
+        for (name, enabled) in rules {
+
+            let rule = LintRule::from_name(name).ok_or_else(|| format!("Unknown lint rule {}", name))?;
+
+            match enabled {
+                Value::Bool(enabled) => config.set(rule, *enabled),
+                _ => return Err(format!("Lint rule {} must be set to true or false", name))
+            }
+
+        }
+
+        Ok(config)
+
+    }
+
+    pub fn load(path: & Path) -> Result<LintConfig, std::string::String> {
+        let source = std::fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+        LintConfig::parse(&source).map_err(|error| format!("{}: {}", path.display(), error))
+    }
+
+}
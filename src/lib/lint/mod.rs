@@ -0,0 +1,283 @@
+//
+// Linter: warnings for circuits that compile but are likely to be wrong or under-constrained.
+//
+// Each rule can be disabled through a `LintConfig`, and silenced at a given line with a comment
+// naming the rules to allow:
+//
+//     signal tmp; // snarkrs-allow(unused-signal)
+//
+//     // snarkrs-allow(unconstrained-assignment, unused-signal)
+//     inv <-- 1 / in;
+//
+// A comment sharing its line with code applies to that line, a comment on its own line to the
+// next one.
+//
+
+pub mod config;
+mod rules;
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use parser::ast::File;
+use parser::cst::Cst;
+use parser::matchers::{self, MatchError};
+use parser::typed_ast::Span;
+
+pub use lint::config::LintConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintRule {
+
+    ///
+    /// `<--` assignment to a signal that never appears in a constraint.
+    ///
+    UnconstrainedAssignment,
+
+    UnusedSignal,
+    UnusedVariable,
+    UnusedComponent,
+
+    ///
+    /// Input signal appearing in no constraint.
+    ///
+    UnconstrainedInput,
+
+    UnassignedOutput,
+
+    ///
+    /// Declaration reusing the name of a declaration or parameter of an enclosing scope.
+    ///
+    ShadowedVariable,
+
+    ///
+    /// `if`, loop or ternary condition made of number literals only.
+    ///
+    ConstantCondition,
+
+    ///
+    /// `signal private output`: outputs are public whatever their declaration says.
+    ///
+    PrivateOutput
+
+}
+
+impl LintRule {
+
+    pub const ALL: & 'static [LintRule] = &[
+        LintRule::UnconstrainedAssignment,
+        LintRule::UnusedSignal,
+        LintRule::UnusedVariable,
+        LintRule::UnusedComponent,
+        LintRule::UnconstrainedInput,
+        LintRule::UnassignedOutput,
+        LintRule::ShadowedVariable,
+        LintRule::ConstantCondition,
+        LintRule::PrivateOutput
+    ];
+
+    ///
+    /// Name of the rule in configuration files and `snarkrs-allow` comments.
+    ///
+    pub fn name(&self) -> & 'static str {
+        match self {
+            LintRule::UnconstrainedAssignment => "unconstrained-assignment",
+            LintRule::UnusedSignal => "unused-signal",
+            LintRule::UnusedVariable => "unused-variable",
+            LintRule::UnusedComponent => "unused-component",
+            LintRule::UnconstrainedInput => "unconstrained-input",
+            LintRule::UnassignedOutput => "unassigned-output",
+            LintRule::ShadowedVariable => "shadowed-variable",
+            LintRule::ConstantCondition => "constant-condition",
+            LintRule::PrivateOutput => "private-output"
+        }
+    }
+
+    pub fn from_name(name: & str) -> Option<LintRule> {
+        LintRule::ALL.iter().find(|rule| rule.name() == name).cloned()
+    }
+
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    pub rule: LintRule,
+    pub file: PathBuf,
+    pub span: Span,
+
+    ///
+    /// 1-based line and column of the start of the span.
+    ///
+    pub line: usize,
+    pub column: usize,
+
+    pub message: std::string::String
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "{}:{}:{}: warning[{}]: {}", self.file.display(), self.line, self.column, self.rule.name(), self.message)
+    }
+}
+
+const ALLOW: & str = "snarkrs-allow(";
+
+fn line_of(source: & str, offset: usize) -> usize {
+    source[..offset].matches('\n').count()
+}
+
+///
+/// (line, rule) pairs silenced by `snarkrs-allow` comments, lines being 0-based.
+///
+fn allowed(source: & str, cst: & Cst) -> HashSet<(usize, LintRule)> {
+
+    let mut allowed = HashSet::new();
+
+    for comment in cst.comments() {
+
+        let rules = match comment.text.find(ALLOW) {
+            Some(start) => &comment.text[start + ALLOW.len()..],
+            None => continue
+        };
+        let rules = &rules[..rules.find(')').unwrap_or(rules.len())];
+
+        let line_start = source[..comment.span.0].rfind('\n').map_or(0, |newline| newline + 1);
+        let line = line_of(source, comment.span.0);
+        let line = if source[line_start..comment.span.0].trim().is_empty() { line + 1 } else { line };
+
+        for rule in rules.split(',').filter_map(|name| LintRule::from_name(name.trim())) {
+            allowed.insert((line, rule));
+        }
+
+    }
+
+    allowed
+
+}
+
+///
+/// Warnings of the enabled rules for a parsed file, sorted by position.
+///
+pub fn lint_file(file: & File, config: & LintConfig) -> Result<Vec<Lint>, MatchError> {
+
+    let source = &file.root.source;
+    let circuit = matchers::circuit::process_circuit(&file.root)?;
+    let allowed = allowed(source, &Cst::new(&file.root));
+
+    let mut lints: Vec<Lint> = rules::check(&circuit).into_iter()
+        .filter(|(rule, span, _)| config.enabled(*rule) && !allowed.contains(&(line_of(source, span.0), *rule)))
+        .map(|(rule, span, message)| {
+            let line_start = source[..span.0].rfind('\n').map_or(0, |newline| newline + 1);
+            Lint {
+                rule,
+                file: file.path.clone(),
+                span,
+                line: line_of(source, span.0) + 1,
+                column: source[line_start..span.0].chars().count() + 1,
+                message
+            }
+        })
+        .collect();
+
+    lints.sort_by_key(|lint| (lint.span, lint.rule.name()));
+    Ok(lints)
+
+}
+
+#[cfg(test)]
+mod lint_test {
+
+    use std::path::PathBuf;
+
+    use parser::ast::{self, File};
+    use lint::{self, LintConfig, LintRule};
+
+    use galvanic_assert::matchers::*;
+
+    fn lint(source: & str, config: & LintConfig) -> Vec<(LintRule, usize)> {
+        let file = File {
+            path: PathBuf::from("test.circom"),
+            root: ast::parse_source(source, None).unwrap(),
+            includes: Vec::new()
+        };
+        lint::lint_file(&file, config).unwrap().into_iter().map(|lint| (lint.rule, lint.line)).collect()
+    }
+
+    #[test]
+    fn test_rules() {
+
+        let source = "\
+template A(n) {
+    signal input a;
+    signal input b;
+    signal private output c;
+    signal output d;
+    signal unused;
+    signal inv;
+    var x = 0;
+    var n = 2;
+    component never = B();
+    component used = B();
+    inv <-- 1 / a;
+    c <== a * 2;
+    used.in <== c;
+    if (1 == 1) {
+        var x = 3;
+        c * 2 === x;
+    }
+}
+template B() {
+    signal input in;
+    signal output out;
+    signal t;
+    t <-- in;
+    t === in;
+    out <== t;
+    signal spare; // snarkrs-allow(unused-signal)
+    // snarkrs-allow(unused-variable)
+    var i;
+    for (var k = 0; k < 2; k++) {
+        out * k === 0;
+    }
+    signal input carried;
+    var sum = 0;
+    sum += carried * 2;
+    sum === 0;
+}
+";
+
+        let expected = vec![
+            (LintRule::UnconstrainedInput, 3),
+            (LintRule::PrivateOutput, 4),
+            (LintRule::UnassignedOutput, 5),
+            (LintRule::UnusedSignal, 6),
+            (LintRule::UnusedSignal, 7),
+            (LintRule::UnusedVariable, 8),
+            (LintRule::ShadowedVariable, 9),
+            (LintRule::UnusedVariable, 9),
+            (LintRule::UnusedComponent, 10),
+            (LintRule::UnconstrainedAssignment, 12),
+            (LintRule::ConstantCondition, 15),
+            (LintRule::ShadowedVariable, 16)
+        ];
+        expect_that!(&lint(source, &LintConfig::default()), is(eq(expected)));
+
+        let config = LintConfig::parse("{ \"rules\": { \"unused-variable\": false, \"shadowed-variable\": false } }").unwrap();
+        let expected = vec![
+            (LintRule::UnconstrainedInput, 3),
+            (LintRule::PrivateOutput, 4),
+            (LintRule::UnassignedOutput, 5),
+            (LintRule::UnusedSignal, 6),
+            (LintRule::UnusedSignal, 7),
+            (LintRule::UnusedComponent, 10),
+            (LintRule::UnconstrainedAssignment, 12),
+            (LintRule::ConstantCondition, 15)
+        ];
+        expect_that!(&lint(source, &config), is(eq(expected)));
+
+        expect_that!(&LintConfig::parse("{ \"rules\": { \"unknown\": false } }").is_err(), is(eq(true)));
+        expect_that!(&LintConfig::parse("{ \"rules\": { \"unused-signal\": 0 } }").is_err(), is(eq(true)));
+
+    }
+
+}
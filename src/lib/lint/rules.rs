@@ -0,0 +1,306 @@
+use parser::typed_ast::*;
+use parser::visit::{self, Visitor};
+
+use lint::LintRule;
+
+//
+// The rules run in a single pass over each template and function, resolving names through a stack
+// of block scopes to the declaration they refer to, and recording how every declaration is used.
+//
+
+struct Entry<'a> {
+    declaration: Option<&'a Declaration>,
+    read: bool,
+    assigned: bool,
+    constrained: bool,
+
+    ///
+    /// First `<--` assignment to the signal.
+    ///
+    unconstrained_assignment: Option<Span>,
+
+    ///
+    /// Entries read in the values assigned to a variable: they are constrained when the variable is.
+    ///
+    sources: Vec<usize>
+}
+
+struct Pass<'a> {
+    entries: Vec<Entry<'a>>,
+    scopes: Vec<Vec<(&'a str, usize)>>,
+
+    ///
+    /// Number of enclosing constraints, the signals read in them are constrained.
+    ///
+    constraints: usize,
+
+    ///
+    /// Entries read so far, in order.
+    ///
+    reads: Vec<usize>,
+    warnings: Vec<(LintRule, Span, std::string::String)>
+}
+
+fn kind_name(kind: DeclarationKind) -> & 'static str {
+    match kind {
+        DeclarationKind::Variable => "Variable",
+        DeclarationKind::Signal { direction: SignalDirection::Input, .. } => "Input signal",
+        DeclarationKind::Signal { direction: SignalDirection::Output, .. } => "Output signal",
+        DeclarationKind::Signal { direction: SignalDirection::Intermediate, .. } => "Signal",
+        DeclarationKind::Component => "Component"
+    }
+}
+
+///
+/// Whether an expression only involves number literals.
+///
+fn is_constant(expression: & Expression) -> bool {
+    match expression {
+        Expression::Number { .. } => true,
+        Expression::Prefix { operator, operand, .. } => !matches!(operator, PrefixOperator::Increment | PrefixOperator::Decrement) && is_constant(operand),
+        Expression::Binary { left, right, .. } => is_constant(left) && is_constant(right),
+        Expression::Ternary { condition, if_true, if_false, .. } => is_constant(condition) && is_constant(if_true) && is_constant(if_false),
+        _ => false
+    }
+}
+
+impl<'a> Pass<'a> {
+
+    fn new(parameters: &'a [std::string::String]) -> Pass<'a> {
+
+        let mut pass = Pass {
+            entries: Vec::new(),
+            scopes: vec![Vec::new()],
+            constraints: 0,
+            reads: Vec::new(),
+            warnings: Vec::new()
+        };
+
+        for parameter in parameters {
+            pass.declare(parameter, None, Vec::new());
+        }
+
+        pass
+
+    }
+
+    fn declare(& mut self, name: &'a str, declaration: Option<&'a Declaration>, sources: Vec<usize>) {
+
+        self.entries.push(Entry {
+            declaration,
+            read: false,
+            assigned: declaration.is_none_or(|declaration| declaration.value.is_some()),
+            constrained: false,
+            unconstrained_assignment: None,
+            sources
+        });
+
+        let index = self.entries.len() - 1;
+        self.scopes.last_mut().unwrap().push((name, index));
+
+    }
+
+    fn resolve(&self, name: & str) -> Option<usize> {
+        self.scopes.iter().rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(declared, _)| *declared == name)
+            .map(|(_, index)| *index)
+    }
+
+    fn warn(& mut self, rule: LintRule, span: Span, message: std::string::String) {
+        self.warnings.push((rule, span, message));
+    }
+
+    fn condition(& mut self, condition: &'a Expression) {
+        if is_constant(condition) {
+            self.warn(LintRule::ConstantCondition, condition.span(), "Condition is constant".to_string());
+        }
+    }
+
+    ///
+    /// Assigned side of an assignment: the assigned name is not read, the indices and the component
+    /// of a `c.signal` target are.
+    ///
+    fn target(& mut self, target: &'a Expression, operator: Option<SignalOperator>, compound: bool, sources: & [usize]) {
+        match target {
+            Expression::Variable { name, .. } => {
+                if let Some(index) = self.resolve(name) {
+                    let entry = & mut self.entries[index];
+                    entry.assigned = true;
+                    entry.read |= compound;
+                    entry.sources.extend_from_slice(sources);
+                    match operator {
+                        Some(SignalOperator::ConstrainedAssign) => entry.constrained = true,
+                        Some(SignalOperator::Assign) if entry.unconstrained_assignment.is_none() => entry.unconstrained_assignment = Some(target.span()),
+                        _ => {}
+                    }
+                }
+            },
+            Expression::Index { target, index, .. } => {
+                let constraints = std::mem::replace(& mut self.constraints, 0);
+                self.visit_expression(index);
+                self.constraints = constraints;
+                self.target(target, operator, compound, sources);
+            },
+            _ => self.visit_expression(target)
+        }
+    }
+
+    fn finish(mut self) -> Vec<(LintRule, Span, std::string::String)> {
+
+        let mut entries = std::mem::take(& mut self.entries);
+
+        let mut constrained: Vec<usize> = (0..entries.len()).filter(|index| entries[*index].constrained).collect();
+        while let Some(index) = constrained.pop() {
+            for source in std::mem::take(& mut entries[index].sources) {
+                if !entries[source].constrained {
+                    entries[source].constrained = true;
+                    constrained.push(source);
+                }
+            }
+        }
+
+        for entry in entries {
+
+            let declaration = match entry.declaration {
+                Some(declaration) => declaration,
+                None => continue
+            };
+
+            let name = &declaration.name;
+
+            match declaration.kind {
+                DeclarationKind::Variable if !entry.read => {
+                    self.warn(LintRule::UnusedVariable, declaration.span, format!("Variable {} is never used", name));
+                },
+                DeclarationKind::Component if !entry.read => {
+                    self.warn(LintRule::UnusedComponent, declaration.span, format!("Component {} is never used", name));
+                },
+                DeclarationKind::Signal { direction: SignalDirection::Intermediate, .. } if !entry.read => {
+                    self.warn(LintRule::UnusedSignal, declaration.span, format!("Signal {} is never used", name));
+                },
+                DeclarationKind::Signal { direction: SignalDirection::Input, .. } if !entry.constrained => {
+                    self.warn(LintRule::UnconstrainedInput, declaration.span, format!("Input signal {} is never constrained", name));
+                },
+                DeclarationKind::Signal { direction: SignalDirection::Output, .. } if !entry.assigned => {
+                    self.warn(LintRule::UnassignedOutput, declaration.span, format!("Output signal {} is never assigned", name));
+                },
+                _ => {}
+            }
+
+            if let Some(span) = entry.unconstrained_assignment {
+                if !entry.constrained {
+                    self.warn(LintRule::UnconstrainedAssignment, span, format!("Signal {} is assigned with <-- but never constrained", name));
+                }
+            }
+
+        }
+
+        self.warnings
+
+    }
+
+}
+
+impl<'a> Visitor<'a> for Pass<'a> {
+
+    fn visit_statement(& mut self, statement: &'a Statement) {
+        match statement {
+            Statement::Block { .. } | Statement::For { .. } => {
+                if let Statement::For { condition: Some(condition), .. } = statement {
+                    self.condition(condition);
+                }
+                self.scopes.push(Vec::new());
+                visit::walk_statement(self, statement);
+                self.scopes.pop();
+            },
+            Statement::If { condition, .. } | Statement::While { condition, .. } | Statement::DoWhile { condition, .. } => {
+                self.condition(condition);
+                visit::walk_statement(self, statement);
+            },
+            _ => visit::walk_statement(self, statement)
+        }
+    }
+
+    fn visit_declaration(& mut self, declaration: &'a Declaration) {
+
+        let start = self.reads.len();
+        visit::walk_declaration(self, declaration);
+        let sources = self.reads[start..].to_vec();
+
+        if self.resolve(&declaration.name).is_some() {
+            self.warn(LintRule::ShadowedVariable, declaration.span, format!("{} {} shadows an earlier declaration", kind_name(declaration.kind), declaration.name));
+        }
+
+        if let DeclarationKind::Signal { private: true, direction: SignalDirection::Output } = declaration.kind {
+            self.warn(LintRule::PrivateOutput, declaration.span, format!("Output signal {} is declared private, outputs are always public", declaration.name));
+        }
+
+        self.declare(&declaration.name, Some(declaration), sources);
+
+    }
+
+    fn visit_expression(& mut self, expression: &'a Expression) {
+        match expression {
+            Expression::Variable { name, .. } => {
+                if let Some(index) = self.resolve(name) {
+                    let entry = & mut self.entries[index];
+                    entry.read = true;
+                    entry.constrained |= self.constraints > 0;
+                    self.reads.push(index);
+                }
+            },
+            Expression::Assignment { operator, target, value, .. } => {
+                let start = self.reads.len();
+                self.visit_expression(value);
+                let sources = self.reads[start..].to_vec();
+                self.target(target, None, *operator != AssignmentOperator::Assign, &sources);
+            },
+            Expression::SignalAssignment { operator, target, value, .. } => {
+                let constrained = *operator == SignalOperator::ConstrainedAssign;
+                self.constraints += constrained as usize;
+                self.visit_expression(value);
+                self.constraints -= constrained as usize;
+                self.target(target, Some(*operator), false, &[]);
+            },
+            Expression::Constraint { .. } => {
+                self.constraints += 1;
+                visit::walk_expression(self, expression);
+                self.constraints -= 1;
+            },
+            Expression::Ternary { condition, .. } => {
+                self.condition(condition);
+                visit::walk_expression(self, expression);
+            },
+            _ => visit::walk_expression(self, expression)
+        }
+    }
+
+}
+
+///
+/// Warnings of every rule for the templates and functions of a circuit, in no particular order.
+///
+pub fn check(circuit: & Circuit) -> Vec<(LintRule, Span, std::string::String)> {
+
+    let mut warnings = Vec::new();
+
+    for item in &circuit.items {
+
+        let (parameters, body) = match item {
+            Item::Template(template) => (&template.parameters, &template.body),
+            Item::Function(function) => (&function.parameters, &function.body),
+            _ => continue
+        };
+
+        let mut pass = Pass::new(parameters);
+        for statement in body {
+            pass.visit_statement(statement);
+        }
+        warnings.extend(pass.finish());
+
+    }
+
+    warnings
+
+}
@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use parser::ast::Rule;
+use parser::compile::Context;
+use parser::cst::Cst;
+use parser::matchers;
+use parser::typed_ast::*;
+use parser::visit::{self, Visitor};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub span: Span
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Template,
+    Function,
+    Signal,
+    Component,
+    Variable
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: std::string::String,
+    pub kind: SymbolKind,
+    pub detail: std::string::String,
+    pub span: Span,
+
+    ///
+    /// Span of the name of the symbol.
+    ///
+    pub selection: Span,
+    pub children: Vec<Symbol>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hover {
+    pub span: Span,
+
+    ///
+    /// Markdown description.
+    ///
+    pub contents: std::string::String
+}
+
+///
+/// Template or function.
+///
+#[derive(Clone, Copy)]
+struct Callable<'a> {
+    keyword: & 'static str,
+    name: &'a str,
+    parameters: &'a [std::string::String],
+    body: &'a [Statement],
+    span: Span
+}
+
+fn callables(circuit: & Circuit) -> impl Iterator<Item = Callable<'_>> {
+    circuit.items.iter().filter_map(|item| match item {
+        Item::Template(template) => Some(Callable {
+            keyword: "template",
+            name: &template.name,
+            parameters: &template.parameters,
+            body: &template.body,
+            span: template.span
+        }),
+        Item::Function(function) => Some(Callable {
+            keyword: "function",
+            name: &function.name,
+            parameters: &function.parameters,
+            body: &function.body,
+            span: function.span
+        }),
+        _ => None
+    })
+}
+
+///
+/// Identifier under the cursor, and what it refers to.
+///
+enum Reference<'a> {
+    Callable(std::string::String),
+    Local(Callable<'a>, std::string::String),
+    Member(Callable<'a>, std::string::String, std::string::String)
+}
+
+fn is_identifier_character(character: char) -> bool {
+    character.is_ascii_alphanumeric() || character == '_' || character == '$'
+}
+
+///
+/// Offset of the first occurrence of `word` in `text` from `from`, not being part of a longer
+/// identifier.
+///
+fn find_word(text: & str, word: & str, from: usize) -> Option<usize> {
+    text[from..].match_indices(word)
+        .map(|(idx, _)| from + idx)
+        .find(|idx| {
+            !text[..*idx].ends_with(is_identifier_character) && !text[idx + word.len()..].starts_with(is_identifier_character)
+        })
+}
+
+struct Statements<F>(F);
+
+impl<'a, F: FnMut(&'a Statement)> Visitor<'a> for Statements<F> {
+
+    fn visit_statement(& mut self, statement: &'a Statement) {
+        (self.0)(statement);
+        visit::walk_statement(self, statement);
+    }
+
+    fn visit_expression(& mut self, _expression: &'a Expression) {}
+
+}
+
+///
+/// Calls `visit` on `statements` and the statements nested in them, declarations in `for`
+/// initializations included.
+///
+fn walk<'a>(statements: &'a [Statement], visit: impl FnMut(&'a Statement)) {
+    let mut visitor = Statements(visit);
+    for statement in statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+fn declarations(statements: & [Statement]) -> Vec<& Declaration> {
+    let mut declarations = Vec::new();
+    walk(statements, |statement| if let Statement::Declaration(declaration) = statement {
+        declarations.push(declaration);
+    });
+    declarations
+}
+
+fn base_name(expression: & Expression) -> Option<& str> {
+    match expression {
+        Expression::Variable { name, .. } => Some(name),
+        Expression::Index { target, .. } => base_name(target),
+        _ => None
+    }
+}
+
+fn callee_name(expression: & Expression) -> Option<& str> {
+    match expression {
+        Expression::Call { callee, .. } => base_name(callee),
+        _ => None
+    }
+}
+
+///
+/// Template instantiated by a component, from its declaration or from the first assignment to it.
+///
+fn component_template<'a>(callable: & Callable<'a>, component: & str) -> Option<&'a str> {
+
+    let declared = declarations(callable.body).into_iter()
+        .find(|declaration| declaration.kind == DeclarationKind::Component && declaration.name == component)
+        .and_then(|declaration| declaration.constructor.as_ref().or(declaration.value.as_ref()))
+        .and_then(callee_name);
+
+    if declared.is_some() {
+        return declared;
+    }
+
+    let mut assigned = None;
+    walk(callable.body, |statement| if let Statement::Expression { expression: Expression::Assignment { target, value, .. }, .. } = statement {
+        if assigned.is_none() && base_name(target) == Some(component) {
+            assigned = callee_name(value);
+        }
+    });
+
+    assigned
+
+}
+
+fn collapse_whitespace(text: & str) -> std::string::String {
+    text.split_whitespace().collect::<Vec<& str>>().join(" ")
+}
+
+fn signal_kind(declaration: & Declaration) -> SymbolKind {
+    match declaration.kind {
+        DeclarationKind::Signal { .. } => SymbolKind::Signal,
+        DeclarationKind::Component => SymbolKind::Component,
+        DeclarationKind::Variable => SymbolKind::Variable
+    }
+}
+
+///
+/// Answers editor queries over a context: the files it holds are parsed once, when the analysis
+/// is built.
+///
+pub struct Analysis<'a> {
+    ctx: &'a Context,
+    circuits: HashMap<PathBuf, Circuit>
+}
+
+impl<'a> Analysis<'a> {
+
+    pub fn new(ctx: &'a Context) -> Analysis<'a> {
+        Analysis {
+            ctx,
+            // Files the matchers reject are reported by the context and left out of the analysis
+            circuits: ctx.files()
+                .filter_map(|file| matchers::circuit::process_circuit(&file.root).ok().map(|circuit| (file.path.clone(), circuit)))
+                .collect()
+        }
+    }
+
+    fn source(&self, path: & Path) -> & str {
+        self.ctx.file(path).map(|file| file.root.source.as_str()).unwrap_or("")
+    }
+
+    ///
+    /// Files of the context, in a stable order.
+    ///
+    fn paths(&self) -> Vec<& PathBuf> {
+        let mut paths: Vec<& PathBuf> = self.circuits.keys().collect();
+        paths.sort();
+        paths
+    }
+
+    fn name_span(&self, path: & Path, callable: & Callable) -> Span {
+        let source = self.source(path);
+        let start = callable.span.0 + callable.keyword.len();
+        match find_word(source, callable.name, start) {
+            Some(idx) if idx < callable.span.1 => (idx, idx + callable.name.len()),
+            _ => callable.span
+        }
+    }
+
+    fn declaration_name_span(&self, path: & Path, declaration: & Declaration) -> Span {
+        let source = self.source(path);
+        let from = declaration.constructor.as_ref().map(|constructor| constructor.span().1).unwrap_or(declaration.span.0);
+        match find_word(source, &declaration.name, from) {
+            Some(idx) if idx < declaration.span.1 => (idx, idx + declaration.name.len()),
+            _ => declaration.span
+        }
+    }
+
+    fn parameter_span(&self, path: & Path, callable: & Callable, name: & str) -> Option<Span> {
+        let source = self.source(path);
+        let opening = source[callable.span.0..callable.span.1].find('(')? + callable.span.0;
+        find_word(source, name, opening).map(|idx| (idx, idx + name.len()))
+    }
+
+    fn find_callable(&self, name: & str) -> Option<(& PathBuf, Callable<'_>)> {
+        self.paths().into_iter()
+            .find_map(|path| callables(&self.circuits[path]).find(|callable| callable.name == name).map(|callable| (path, callable)))
+    }
+
+    ///
+    /// Identifier token at the offset, the cursor being allowed right after it.
+    ///
+    fn identifier_at(&self, path: & Path, offset: usize) -> Option<(Span, std::string::String)> {
+
+        let cst = Cst::new(&self.ctx.file(path)?.root);
+        let identifiers = [Rule::E_VariableName, Rule::VariableName, Rule::TemplateName, Rule::FunctionName];
+
+        [Some(offset), offset.checked_sub(1)].iter()
+            .filter_map(|candidate| candidate.and_then(|candidate| cst.token_at(candidate)))
+            .filter(|token| token.span.0 <= offset && offset <= token.span.1)
+            .find(|token| token.rule.map(|rule| identifiers.contains(&rule)).unwrap_or(false))
+            .map(|token| (token.span, token.text.clone()))
+
+    }
+
+    fn reference_at(&self, path: & Path, offset: usize) -> Option<(Span, Reference<'_>)> {
+
+        let (span, name) = self.identifier_at(path, offset)?;
+        let source = self.source(path);
+
+        let callable = match callables(self.circuits.get(path)?).find(|callable| callable.span.0 <= span.0 && span.1 <= callable.span.1) {
+            Some(callable) => callable,
+            None => return Some((span, Reference::Callable(name)))
+        };
+
+        if self.name_span(path, &callable) == span {
+            return Some((span, Reference::Callable(name)));
+        }
+
+        // `component.member`, the component possibly being indexed
+        let before = source[..span.0].trim_end();
+        if let Some(before) = before.strip_suffix('.') {
+
+            let mut end = before.trim_end().len();
+            while source[..end].ends_with(']') {
+                let mut depth = 0;
+                let opening = source[..end].char_indices().rev().find(|(_, character)| {
+                    match character {
+                        ']' => depth += 1,
+                        '[' => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                })?.0;
+                end = source[..opening].trim_end().len();
+            }
+
+            let start = source[..end].rfind(|character: char| !is_identifier_character(character)).map(|idx| idx + 1).unwrap_or(0);
+            return Some((span, Reference::Member(callable, source[start..end].to_string(), name)));
+
+        }
+
+        let local = callable.parameters.contains(&name) || declarations(callable.body).iter().any(|declaration| declaration.name == name);
+        if local {
+            Some((span, Reference::Local(callable, name)))
+        } else {
+            Some((span, Reference::Callable(name)))
+        }
+
+    }
+
+    fn member_declaration(&self, callable: & Callable, component: & str, member: & str) -> Option<(& PathBuf, Callable<'_>, & Declaration)> {
+        let (path, template) = self.find_callable(component_template(callable, component)?)?;
+        let declaration = declarations(template.body).into_iter().find(|declaration| declaration.name == member)?;
+        Some((path, template, declaration))
+    }
+
+    pub fn definition(&self, path: & Path, offset: usize) -> Option<Location> {
+        match self.reference_at(path, offset)?.1 {
+            Reference::Callable(name) => {
+                let (path, callable) = self.find_callable(&name)?;
+                Some(Location { path: path.clone(), span: self.name_span(path, &callable) })
+            },
+            Reference::Local(callable, name) => {
+                let span = match declarations(callable.body).into_iter().find(|declaration| declaration.name == name) {
+                    Some(declaration) => self.declaration_name_span(path, declaration),
+                    None => self.parameter_span(path, &callable, &name)?
+                };
+                Some(Location { path: path.to_path_buf(), span })
+            },
+            Reference::Member(callable, component, member) => {
+                let (path, _, declaration) = self.member_declaration(&callable, &component, &member)?;
+                Some(Location { path: path.clone(), span: self.declaration_name_span(path, declaration) })
+            }
+        }
+    }
+
+    fn declaration_text(&self, path: & Path, declaration: & Declaration) -> std::string::String {
+        collapse_whitespace(&self.source(path)[declaration.span.0..declaration.span.1])
+    }
+
+    fn signature(callable: & Callable) -> std::string::String {
+        format!("{} {}({})", callable.keyword, callable.name, callable.parameters.join(", "))
+    }
+
+    pub fn hover(&self, path: & Path, offset: usize) -> Option<Hover> {
+
+        let (span, reference) = self.reference_at(path, offset)?;
+
+        let contents = match reference {
+            Reference::Callable(name) => {
+                let (path, callable) = self.find_callable(&name)?;
+                let mut lines = vec!(Analysis::signature(&callable));
+                for declaration in declarations(callable.body) {
+                    if let DeclarationKind::Signal { direction, .. } = declaration.kind {
+                        if direction != SignalDirection::Intermediate {
+                            lines.push(format!("    {}", self.declaration_text(path, declaration)));
+                        }
+                    }
+                }
+                format!("```circom\n{}\n```", lines.join("\n"))
+            },
+            Reference::Local(callable, name) => match declarations(callable.body).into_iter().find(|declaration| declaration.name == name) {
+                Some(declaration) => format!("```circom\n{}\n```", self.declaration_text(path, declaration)),
+                None => format!("```circom\n{}\n```\nParameter of {} `{}`", name, callable.keyword, callable.name)
+            },
+            Reference::Member(callable, component, member) => {
+                let (path, template, declaration) = self.member_declaration(&callable, &component, &member)?;
+                format!("```circom\n{}\n```\nIn template `{}`", self.declaration_text(path, declaration), template.name)
+            }
+        };
+
+        Some(Hover { span, contents })
+
+    }
+
+    fn declaration_symbol(&self, path: & Path, declaration: & Declaration) -> Symbol {
+        Symbol {
+            name: declaration.name.clone(),
+            kind: signal_kind(declaration),
+            detail: self.declaration_text(path, declaration),
+            span: declaration.span,
+            selection: self.declaration_name_span(path, declaration),
+            children: Vec::new()
+        }
+    }
+
+    ///
+    /// Templates and functions of a file with their declarations, and the declarations at the root
+    /// of the file.
+    ///
+    pub fn symbols(&self, path: & Path) -> Vec<Symbol> {
+
+        let circuit = match self.circuits.get(path) {
+            Some(circuit) => circuit,
+            None => return Vec::new()
+        };
+
+        let mut symbols: Vec<Symbol> = callables(circuit).map(|callable| Symbol {
+            name: callable.name.to_string(),
+            kind: if callable.keyword == "template" { SymbolKind::Template } else { SymbolKind::Function },
+            detail: Analysis::signature(&callable),
+            span: callable.span,
+            selection: self.name_span(path, &callable),
+            children: declarations(callable.body).into_iter().map(|declaration| self.declaration_symbol(path, declaration)).collect()
+        }).collect();
+
+        symbols.extend(circuit.declarations().map(|declaration| self.declaration_symbol(path, declaration)));
+        symbols.sort_by_key(|symbol| symbol.span.0);
+
+        symbols
+
+    }
+
+    ///
+    /// Templates and functions visible from the main file, sorted by name.
+    ///
+    pub fn completions(&self) -> Vec<Symbol> {
+
+        let mut completions: Vec<Symbol> = Vec::new();
+
+        for path in self.paths() {
+            for callable in callables(&self.circuits[path]) {
+                if completions.iter().all(|completion| completion.name != callable.name) {
+                    completions.push(Symbol {
+                        name: callable.name.to_string(),
+                        kind: if callable.keyword == "template" { SymbolKind::Template } else { SymbolKind::Function },
+                        detail: Analysis::signature(&callable),
+                        span: callable.span,
+                        selection: self.name_span(path, &callable),
+                        children: Vec::new()
+                    });
+                }
+            }
+        }
+
+        completions.sort_by(|left, right| left.name.cmp(&right.name));
+        completions
+
+    }
+
+}
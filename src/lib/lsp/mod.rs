@@ -0,0 +1,614 @@
+//
+// Language server for circom files, speaking LSP over JSON-RPC.
+//
+// Each open document is analysed as the main file of a context built with `build_context`, the
+// open documents overriding the files on disk. Syntax and include errors are reported as the
+// documents change, elaboration errors when they are opened and saved.
+//
+
+pub mod text;
+pub mod transport;
+pub mod analysis;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use elaborator;
+use field::bn254::Fr;
+use parser::compile::{self, Context};
+use parser::errors::CompileError;
+use parser::matchers;
+use parser::typed_ast::Item;
+
+use lsp::analysis::{Analysis, Location, Symbol, SymbolKind};
+use lsp::text::LineIndex;
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+type RequestResult = Result<Value, (i64, std::string::String)>;
+
+pub struct Server {
+    include_paths: Vec<PathBuf>,
+
+    ///
+    /// Content of the open documents, by canonical path.
+    ///
+    documents: HashMap<PathBuf, std::string::String>,
+
+    ///
+    /// Files diagnostics have been published for, by document analysed.
+    ///
+    published: HashMap<PathBuf, HashSet<PathBuf>>,
+
+    exited: bool
+}
+
+fn canonical(path: & Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn notification(method: & str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+fn document_path(params: & Value) -> Result<PathBuf, (i64, std::string::String)> {
+    params["textDocument"]["uri"].as_str()
+        .and_then(text::uri_to_path)
+        .map(|path| canonical(&path))
+        .ok_or_else(|| (INVALID_PARAMS, "Expected a file:// document URI".to_string()))
+}
+
+///
+/// Runs a part of the analysis, a crash being turned into an error message so that it does not take
+/// the server down. Errors in the sources are reported as diagnostics, this is only a last resort
+/// against bugs of the analysis.
+///
+fn guarded<T, F: FnOnce() -> T>(analysis: F) -> Result<T, std::string::String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(analysis)).map_err(|panic| {
+        let reason = match (panic.downcast_ref::<& str>(), panic.downcast_ref::<std::string::String>()) {
+            (Some(reason), _) => reason.to_string(),
+            (_, Some(reason)) => reason.clone(),
+            _ => "unknown error".to_string()
+        };
+        format!("Internal error: {}", reason)
+    })
+}
+
+fn symbol_kind(kind: SymbolKind) -> u64 {
+    match kind {
+        SymbolKind::Template => 5,
+        SymbolKind::Function => 12,
+        SymbolKind::Signal => 8,
+        SymbolKind::Component => 19,
+        SymbolKind::Variable => 13
+    }
+}
+
+impl Server {
+
+    pub fn new(include_paths: Vec<PathBuf>) -> Server {
+        Server {
+            include_paths,
+            documents: HashMap::new(),
+            published: HashMap::new(),
+            exited: false
+        }
+    }
+
+    ///
+    /// True once the client sent `exit`.
+    ///
+    pub fn exited(&self) -> bool {
+        self.exited
+    }
+
+    fn context(&self, path: & Path) -> Context {
+        compile::build_context_with_sources(&path.to_path_buf(), &self.include_paths, &self.documents)
+    }
+
+    ///
+    /// Content of a file, as open in the editor or else on disk.
+    ///
+    fn source(&self, ctx: & Context, path: & Path) -> std::string::String {
+        self.documents.get(&canonical(path)).cloned()
+            .or_else(|| ctx.file(path).map(|file| file.root.source.clone()))
+            .or_else(|| std::fs::read_to_string(path).ok())
+            .unwrap_or_default()
+    }
+
+    fn location(&self, ctx: & Context, location: & Location) -> Value {
+        let source = self.source(ctx, &location.path);
+        json!({
+            "uri": text::path_to_uri(&canonical(&location.path)),
+            "range": LineIndex::new(&source).range(location.span)
+        })
+    }
+
+    ///
+    /// Where to report an error: unreadable includes are reported on the include statement.
+    ///
+    fn error_location(ctx: & Context, error: & CompileError) -> (PathBuf, (usize, usize)) {
+
+        if let CompileError::UnknwonFileIncludeError(include) = error {
+            for file in ctx.files() {
+                if let Some(idx) = file.includes.iter().position(|path| *path == include.invalid_file) {
+                    let span = matchers::circuit::process_circuit(&file.root).map(|circuit| circuit.items).unwrap_or_default().iter()
+                        .filter_map(|item| match item {
+                            Item::Include { span, .. } => Some(*span),
+                            _ => None
+                        })
+                        .nth(idx)
+                        .unwrap_or((0, 0));
+                    return (canonical(&file.path), span);
+                }
+            }
+            return (canonical(ctx.main()), (0, 0));
+        }
+
+        (canonical(error.file()), error.span())
+
+    }
+
+    ///
+    /// Diagnostics of the context of a document, by file.
+    ///
+    fn diagnostics(&self, path: & Path, elaborate: bool) -> HashMap<PathBuf, Vec<Value>> {
+
+        let ctx = self.context(path);
+
+        let mut errors: Vec<CompileError> = Vec::new();
+        if ctx.errors().is_empty() && elaborate && elaborator::declares_main(&ctx) {
+            if let Err(error) = elaborator::elaborate::<Fr>(&ctx).and_then(|circuit| circuit.r1cs().map(|_| ())) {
+                errors.push(CompileError::ElaborationError(error));
+            }
+        }
+
+        let mut diagnostics: HashMap<PathBuf, Vec<Value>> = HashMap::new();
+        for error in ctx.errors().iter().chain(errors.iter()) {
+            let (file, span) = Server::error_location(&ctx, error);
+            let source = self.source(&ctx, &file);
+            diagnostics.entry(file).or_default().push(json!({
+                "range": LineIndex::new(&source).range(span),
+                "severity": 1,
+                "code": format!("E{}", error.code()),
+                "source": "snarkrs",
+                "message": error.message()
+            }));
+        }
+
+        diagnostics
+
+    }
+
+    fn publish_diagnostics(& mut self, path: & Path, elaborate: bool) -> Vec<Value> {
+
+        // A crash of the analysis is reported at the start of the document
+        let mut diagnostics = guarded(|| self.diagnostics(path, elaborate)).unwrap_or_else(|reason| {
+            let mut diagnostics = HashMap::new();
+            diagnostics.insert(path.to_path_buf(), vec!(json!({
+                "range": LineIndex::new("").range((0, 0)),
+                "severity": 1,
+                "source": "snarkrs",
+                "message": reason
+            })));
+            diagnostics
+        });
+
+        // Files that had errors and no longer do get an empty list
+        let mut files: Vec<PathBuf> = diagnostics.keys().cloned().collect();
+        files.extend(self.published.get(path).into_iter().flatten().cloned());
+        files.push(path.to_path_buf());
+        files.sort();
+        files.dedup();
+
+        self.published.insert(path.to_path_buf(), diagnostics.keys().cloned().collect());
+
+        files.into_iter().map(|file| {
+            let diagnostics = diagnostics.remove(&file).unwrap_or_default();
+            notification("textDocument/publishDiagnostics", json!({
+                "uri": text::path_to_uri(&file),
+                "diagnostics": diagnostics
+            }))
+        }).collect()
+
+    }
+
+    fn close(& mut self, path: & Path) -> Vec<Value> {
+        self.documents.remove(path);
+        self.published.remove(path).into_iter().flatten().map(|file| {
+            notification("textDocument/publishDiagnostics", json!({
+                "uri": text::path_to_uri(&file),
+                "diagnostics": []
+            }))
+        }).collect()
+    }
+
+    fn offset(&self, ctx: & Context, path: & Path, params: & Value) -> usize {
+        let source = self.source(ctx, path);
+        let line = params["position"]["line"].as_u64().unwrap_or(0) as usize;
+        let character = params["position"]["character"].as_u64().unwrap_or(0) as usize;
+        LineIndex::new(&source).offset(line, character)
+    }
+
+    fn definition(&self, params: & Value) -> RequestResult {
+        let path = document_path(params)?;
+        let ctx = self.context(&path);
+        let offset = self.offset(&ctx, &path, params);
+        Ok(match Analysis::new(&ctx).definition(&path, offset) {
+            Some(location) => self.location(&ctx, &location),
+            None => Value::Null
+        })
+    }
+
+    fn hover(&self, params: & Value) -> RequestResult {
+        let path = document_path(params)?;
+        let ctx = self.context(&path);
+        let offset = self.offset(&ctx, &path, params);
+        Ok(match Analysis::new(&ctx).hover(&path, offset) {
+            Some(hover) => json!({
+                "contents": { "kind": "markdown", "value": hover.contents },
+                "range": LineIndex::new(&self.source(&ctx, &path)).range(hover.span)
+            }),
+            None => Value::Null
+        })
+    }
+
+    fn document_symbols(&self, params: & Value) -> RequestResult {
+
+        let path = document_path(params)?;
+        let ctx = self.context(&path);
+        let source = self.source(&ctx, &path);
+        let index = LineIndex::new(&source);
+
+        fn to_json(index: & LineIndex, symbol: & Symbol) -> Value {
+            json!({
+                "name": symbol.name,
+                "detail": symbol.detail,
+                "kind": symbol_kind(symbol.kind),
+                "range": index.range(symbol.span),
+                "selectionRange": index.range(symbol.selection),
+                "children": symbol.children.iter().map(|child| to_json(index, child)).collect::<Vec<Value>>()
+            })
+        }
+
+        Ok(Value::Array(Analysis::new(&ctx).symbols(&path).iter().map(|symbol| to_json(&index, symbol)).collect()))
+
+    }
+
+    fn completion(&self, params: & Value) -> RequestResult {
+        let path = document_path(params)?;
+        let ctx = self.context(&path);
+        Ok(Value::Array(Analysis::new(&ctx).completions().into_iter().map(|completion| json!({
+            "label": completion.name,
+            "kind": if completion.kind == SymbolKind::Template { 7 } else { 3 },
+            "detail": completion.detail
+        })).collect()))
+    }
+
+    fn initialize(& mut self, params: & Value) -> RequestResult {
+
+        if let Some(paths) = params["initializationOptions"]["includePaths"].as_array() {
+            self.include_paths.extend(paths.iter().filter_map(|path| path.as_str()).map(PathBuf::from));
+        }
+
+        Ok(json!({
+            "capabilities": {
+                "textDocumentSync": { "openClose": true, "change": 1, "save": { "includeText": true } },
+                "definitionProvider": true,
+                "hoverProvider": true,
+                "documentSymbolProvider": true,
+                "completionProvider": { "triggerCharacters": [] }
+            },
+            "serverInfo": { "name": "snarkrs-lsp", "version": env!("CARGO_PKG_VERSION") }
+        }))
+
+    }
+
+    fn request(& mut self, method: & str, params: & Value) -> RequestResult {
+        match method {
+            "initialize" => self.initialize(params),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/documentSymbol" => self.document_symbols(params),
+            "textDocument/completion" => self.completion(params),
+            _ => Err((METHOD_NOT_FOUND, format!("Unsupported method {}", method)))
+        }
+    }
+
+    fn notify(& mut self, method: & str, params: & Value) -> Vec<Value> {
+
+        let path = match document_path(params) {
+            Ok(path) => path,
+            Err(_) => {
+                if method == "exit" {
+                    self.exited = true;
+                }
+                return Vec::new();
+            }
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let content = params["textDocument"]["text"].as_str().unwrap_or("").to_string();
+                self.documents.insert(path.clone(), content);
+                self.publish_diagnostics(&path, true)
+            },
+            "textDocument/didChange" => {
+                // Full synchronization: the last change holds the whole document
+                if let Some(content) = params["contentChanges"].as_array().and_then(|changes| changes.last()).and_then(|change| change["text"].as_str()) {
+                    self.documents.insert(path.clone(), content.to_string());
+                }
+                self.publish_diagnostics(&path, false)
+            },
+            "textDocument/didSave" => {
+                if let Some(content) = params["text"].as_str() {
+                    self.documents.insert(path.clone(), content.to_string());
+                }
+                self.publish_diagnostics(&path, true)
+            },
+            "textDocument/didClose" => self.close(&path),
+            _ => Vec::new()
+        }
+
+    }
+
+    ///
+    /// Handles a message from the client, returning the messages to send back: the response to a
+    /// request and the notifications it triggered.
+    ///
+    pub fn handle(& mut self, message: & Value) -> Vec<Value> {
+
+        let method = match message["method"].as_str() {
+            Some(method) => method,
+            // Responses to requests of the server, which sends none
+            None => return Vec::new()
+        };
+
+        let params = &message["params"];
+
+        match message.get("id") {
+            Some(id) => {
+                let response = match guarded(|| self.request(method, params)).unwrap_or_else(|reason| Err((INTERNAL_ERROR, reason))) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, reason)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": reason } })
+                };
+                vec!(response)
+            },
+            None => self.notify(method, params)
+        }
+
+    }
+
+}
+
+///
+/// Serves a client until it sends `exit` or closes the input.
+///
+pub fn run<R: std::io::BufRead, W: std::io::Write>(server: & mut Server, reader: & mut R, writer: & mut W) -> std::io::Result<()> {
+
+    while let Some(body) = transport::read_message(reader)? {
+
+        let replies = match body {
+            Ok(message) => server.handle(&message),
+            // The id of the request is unknown
+            Err(reason) => vec!(json!({ "jsonrpc": "2.0", "id": null, "error": { "code": PARSE_ERROR, "message": reason } }))
+        };
+
+        for reply in replies {
+            transport::write_message(writer, &reply)?;
+        }
+
+        if server.exited() {
+            break ;
+        }
+
+    }
+
+    Ok(())
+
+}
+
+#[cfg(test)]
+mod lsp_test {
+
+    use std::path::PathBuf;
+
+    use serde_json::Value;
+
+    use lsp::{self, Server};
+    use lsp::text;
+
+    use galvanic_assert::matchers::*;
+
+    ///
+    /// In-process client, sending messages straight to a server.
+    ///
+    struct Client {
+        server: Server,
+        next_id: u64
+    }
+
+    impl Client {
+
+        fn new() -> Client {
+            let mut client = Client { server: Server::new(Vec::new()), next_id: 0 };
+            client.request("initialize", json!({ "capabilities": {} }));
+            client.notify("initialized", json!({}));
+            client
+        }
+
+        fn request(& mut self, method: & str, params: Value) -> Value {
+            self.next_id += 1;
+            let replies = self.server.handle(&json!({ "jsonrpc": "2.0", "id": self.next_id, "method": method, "params": params }));
+            let response = replies.into_iter().find(|reply| reply["id"] == json!(self.next_id)).expect("No response");
+            response["result"].clone()
+        }
+
+        fn notify(& mut self, method: & str, params: Value) -> Vec<Value> {
+            self.server.handle(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+        }
+
+    }
+
+    fn document() -> (PathBuf, std::string::String) {
+        let directory = std::fs::canonicalize("./src/lib/parser/test_material/witness").unwrap();
+        let path = directory.join("lsp_main.circom");
+        let uri = text::path_to_uri(&path);
+        (path, uri)
+    }
+
+    fn position(source: & str, pattern: & str, shift: usize) -> Value {
+        let index = text::LineIndex::new(source);
+        let (line, character) = index.position(source.find(pattern).unwrap() + shift);
+        json!({ "line": line, "character": character })
+    }
+
+    const SOURCE: & str = "include \"../circuits/bitify.circom\";\n\ntemplate Main(n) {\n    signal input in;\n    signal output bits[n];\n    component n2b = Num2Bits(n);\n    n2b.in <== in;\n    for (var i = 0; i < n; i++) {\n        bits[i] <== n2b.out[i];\n    }\n}\n\ncomponent main = Main(8);\n";
+
+    #[test]
+    fn test_diagnostics() {
+
+        let (_, uri) = document();
+        let mut client = Client::new();
+
+        let published = client.notify("textDocument/didOpen", json!({ "textDocument": { "uri": uri, "languageId": "circom", "version": 1, "text": SOURCE } }));
+        let own: Vec<& Value> = published.iter().filter(|notification| notification["params"]["uri"] == json!(uri)).collect();
+        expect_that!(&own.len(), is(eq(1)));
+        expect_that!(&own[0]["params"]["diagnostics"], is(eq(json!([]))));
+
+        let broken = SOURCE.replace("n2b.in <== in;", "n2b.in <== ;");
+        let published = client.notify("textDocument/didChange", json!({ "textDocument": { "uri": uri, "version": 2 }, "contentChanges": [{ "text": broken }] }));
+        let diagnostics = &published.iter().find(|notification| notification["params"]["uri"] == json!(uri)).unwrap()["params"]["diagnostics"];
+        expect_that!(&diagnostics[0]["code"], is(eq(json!("E101"))));
+        expect_that!(&diagnostics[0]["range"]["start"]["line"], is(eq(json!(6))));
+
+        let missing = SOURCE.replace("bitify", "missing");
+        let published = client.notify("textDocument/didChange", json!({ "textDocument": { "uri": uri, "version": 3 }, "contentChanges": [{ "text": missing }] }));
+        let diagnostics = &published.iter().find(|notification| notification["params"]["uri"] == json!(uri)).unwrap()["params"]["diagnostics"];
+        expect_that!(&diagnostics[0]["code"], is(eq(json!("E201"))));
+        expect_that!(&diagnostics[0]["range"]["end"], is(eq(json!({ "line": 0, "character": 37 }))));
+
+        let unconstrained = SOURCE.replace("n2b.in <== in;", "n2b.in <== in;\n    n2b.in <== in;");
+        let published = client.notify("textDocument/didSave", json!({ "textDocument": { "uri": uri }, "text": unconstrained }));
+        let diagnostics = &published.iter().find(|notification| notification["params"]["uri"] == json!(uri)).unwrap()["params"]["diagnostics"];
+        expect_that!(&diagnostics[0]["code"], is(eq(json!("E301"))));
+
+        let published = client.notify("textDocument/didClose", json!({ "textDocument": { "uri": uri } }));
+        expect_that!(&published.iter().all(|notification| notification["params"]["diagnostics"] == json!([])), is(eq(true)));
+
+    }
+
+    #[test]
+    fn test_comments_in_parameters() {
+
+        let (_, uri) = document();
+        let source = "template A(n /* c */, m) { signal input a; }";
+        let mut client = Client::new();
+
+        let published = client.notify("textDocument/didOpen", json!({ "textDocument": { "uri": uri, "languageId": "circom", "version": 1, "text": source } }));
+        let own: Vec<& Value> = published.iter().filter(|notification| notification["params"]["uri"] == json!(uri)).collect();
+        expect_that!(&own.len(), is(eq(1)));
+        expect_that!(&own[0]["params"]["diagnostics"], is(eq(json!([]))));
+
+        let symbols = client.request("textDocument/documentSymbol", json!({ "textDocument": { "uri": uri } }));
+        expect_that!(&symbols[0]["detail"], is(eq(json!("template A(n, m)"))));
+
+    }
+
+    #[test]
+    fn test_guarded() {
+
+        expect_that!(&lsp::guarded(|| 1), is(eq(Ok(1))));
+        expect_that!(&lsp::guarded(|| -> usize { panic!("Invalid token") }), is(eq(Err("Internal error: Invalid token".to_string()))));
+        expect_that!(&lsp::guarded(|| -> usize { panic!("Invalid token {}", 1) }), is(eq(Err("Internal error: Invalid token 1".to_string()))));
+
+    }
+
+    #[test]
+    fn test_navigation() {
+
+        let (_, uri) = document();
+        let bitify = text::path_to_uri(&std::fs::canonicalize("./src/lib/parser/test_material/circuits/bitify.circom").unwrap());
+        let mut client = Client::new();
+
+        client.notify("textDocument/didOpen", json!({ "textDocument": { "uri": uri, "languageId": "circom", "version": 1, "text": SOURCE } }));
+
+        let definition = client.request("textDocument/definition", json!({ "textDocument": { "uri": uri }, "position": position(SOURCE, "Num2Bits", 3) }));
+        expect_that!(&definition["uri"], is(eq(json!(bitify))));
+        expect_that!(&definition["range"]["start"], is(eq(json!({ "line": 23, "character": 9 }))));
+
+        let definition = client.request("textDocument/definition", json!({ "textDocument": { "uri": uri }, "position": position(SOURCE, "n2b.out", 4) }));
+        expect_that!(&definition["uri"], is(eq(json!(bitify))));
+        expect_that!(&definition["range"]["start"], is(eq(json!({ "line": 25, "character": 18 }))));
+
+        let definition = client.request("textDocument/definition", json!({ "textDocument": { "uri": uri }, "position": position(SOURCE, "bits[i]", 0) }));
+        expect_that!(&definition["uri"], is(eq(json!(uri))));
+        expect_that!(&definition["range"]["start"], is(eq(json!({ "line": 4, "character": 18 }))));
+
+        let definition = client.request("textDocument/definition", json!({ "textDocument": { "uri": uri }, "position": position(SOURCE, "i < n", 4) }));
+        expect_that!(&definition["range"]["start"], is(eq(json!({ "line": 2, "character": 14 }))));
+
+        let hover = client.request("textDocument/hover", json!({ "textDocument": { "uri": uri }, "position": position(SOURCE, "Num2Bits", 0) }));
+        expect_that!(&hover["contents"]["value"], is(eq(json!("```circom\ntemplate Num2Bits(n)\n    signal input in\n    signal output out[n]\n```"))));
+
+        let hover = client.request("textDocument/hover", json!({ "textDocument": { "uri": uri }, "position": position(SOURCE, "n2b.in", 5) }));
+        expect_that!(&hover["contents"]["value"], is(eq(json!("```circom\nsignal input in\n```\nIn template `Num2Bits`"))));
+
+        let symbols = client.request("textDocument/documentSymbol", json!({ "textDocument": { "uri": uri } }));
+        let names: Vec<Value> = symbols.as_array().unwrap().iter().map(|symbol| symbol["name"].clone()).collect();
+        expect_that!(&names, is(eq(vec!(json!("Main"), json!("main")))));
+        let children: Vec<Value> = symbols[0]["children"].as_array().unwrap().iter().map(|child| json!([child["name"], child["kind"]])).collect();
+        expect_that!(&children, is(eq(vec!(json!(["in", 8]), json!(["bits", 8]), json!(["n2b", 19]), json!(["i", 13])))));
+
+        let completions = client.request("textDocument/completion", json!({ "textDocument": { "uri": uri }, "position": { "line": 0, "character": 0 } }));
+        let labels: Vec<& str> = completions.as_array().unwrap().iter().map(|completion| completion["label"].as_str().unwrap()).collect();
+        expect_that!(&labels.contains(&"Num2Bits"), is(eq(true)));
+        expect_that!(&labels.contains(&"Main"), is(eq(true)));
+
+        let unknown = client.server.handle(&json!({ "jsonrpc": "2.0", "id": 99, "method": "workspace/unknown", "params": {} }));
+        expect_that!(&unknown[0]["error"]["code"], is(eq(json!(-32601))));
+
+    }
+
+    #[test]
+    fn test_transport() {
+
+        let mut input: Vec<u8> = Vec::new();
+        lsp::transport::write_message(& mut input, &json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} })).unwrap();
+        input.extend_from_slice(b"Content-Length: 13\r\n\r\n{\"jsonrpc\": 2");
+        for message in &[
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" })
+        ] {
+            lsp::transport::write_message(& mut input, message).unwrap();
+        }
+
+        let mut output: Vec<u8> = Vec::new();
+        let mut server = Server::new(Vec::new());
+        lsp::run(& mut server, & mut input.as_slice(), & mut output).unwrap();
+
+        let mut reader = output.as_slice();
+        let initialize = lsp::transport::read_message(& mut reader).unwrap().unwrap().unwrap();
+        expect_that!(&initialize["result"]["capabilities"]["hoverProvider"], is(eq(json!(true))));
+
+        // A malformed body is answered and the server keeps serving
+        let malformed = lsp::transport::read_message(& mut reader).unwrap().unwrap().unwrap();
+        expect_that!(&malformed["id"], is(eq(json!(null))));
+        expect_that!(&malformed["error"]["code"], is(eq(json!(-32700))));
+
+        let shutdown = lsp::transport::read_message(& mut reader).unwrap().unwrap().unwrap();
+        expect_that!(&shutdown, is(eq(json!({ "jsonrpc": "2.0", "id": 2, "result": null }))));
+
+        // Nothing is read after exit
+        expect_that!(&lsp::transport::read_message(& mut reader).unwrap().is_none(), is(eq(true)));
+
+    }
+
+}
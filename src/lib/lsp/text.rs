@@ -0,0 +1,97 @@
+use serde_json::Value;
+
+use parser::typed_ast::Span;
+
+///
+/// Converts byte offsets to LSP positions and back. Positions are lines and columns counted in
+/// UTF-16 code units.
+///
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>
+}
+
+impl<'a> LineIndex<'a> {
+
+    pub fn new(source: &'a str) -> LineIndex<'a> {
+        LineIndex {
+            source,
+            line_starts: std::iter::once(0).chain(source.match_indices('\n').map(|(idx, _)| idx + 1)).collect()
+        }
+    }
+
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.source.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let column = self.source[self.line_starts[line]..offset].encode_utf16().count();
+        (line, column)
+    }
+
+    pub fn offset(&self, line: usize, column: usize) -> usize {
+
+        let start = match self.line_starts.get(line) {
+            Some(start) => *start,
+            None => return self.source.len()
+        };
+
+        let mut units = 0;
+        for (idx, character) in self.source[start..].char_indices() {
+            if units >= column || character == '\n' {
+                return start + idx;
+            }
+            units += character.len_utf16();
+        }
+
+        self.source.len()
+
+    }
+
+    pub fn range(&self, span: Span) -> Value {
+        let (start_line, start_column) = self.position(span.0);
+        let (end_line, end_column) = self.position(span.1);
+        json!({
+            "start": { "line": start_line, "character": start_column },
+            "end": { "line": end_line, "character": end_column }
+        })
+    }
+
+}
+
+///
+/// Path of a `file://` URI.
+///
+pub fn uri_to_path(uri: & str) -> Option<std::path::PathBuf> {
+
+    let encoded = uri.strip_prefix("file://")?;
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut chars = encoded.bytes();
+
+    while let Some(byte) = chars.next() {
+        if byte == b'%' {
+            let high = (chars.next()? as char).to_digit(16)?;
+            let low = (chars.next()? as char).to_digit(16)?;
+            bytes.push((high * 16 + low) as u8);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    std::string::String::from_utf8(bytes).ok().map(std::path::PathBuf::from)
+
+}
+
+pub fn path_to_uri(path: & std::path::Path) -> std::string::String {
+
+    let mut uri = "file://".to_string();
+
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    uri
+
+}
@@ -0,0 +1,52 @@
+use std::io::{BufRead, Write};
+
+use serde_json::Value;
+
+///
+/// Body of a message, or why it is not valid JSON.
+///
+pub type Body = Result<Value, std::string::String>;
+
+///
+/// Reads a message framed by a `Content-Length` header. Returns `None` at the end of the input. A
+/// body that is not valid JSON is returned as an error for the server to answer, the framing being
+/// intact.
+///
+pub fn read_message<R: BufRead>(reader: & mut R) -> std::io::Result<Option<Body>> {
+
+    let mut length: Option<usize> = None;
+
+    loop {
+
+        let mut header = std::string::String::new();
+        if reader.read_line(& mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break ;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse().ok();
+            }
+        }
+
+    }
+
+    let length = length.ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "missing Content-Length header"))?;
+
+    let mut body = vec!(0; length);
+    reader.read_exact(& mut body)?;
+
+    Ok(Some(serde_json::from_slice(&body).map_err(|error| format!("Parse error: {}", error))))
+
+}
+
+pub fn write_message<W: Write>(writer: & mut W, message: & Value) -> std::io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()
+}
@@ -0,0 +1,324 @@
+//
+// Data parallelism for the FFTs and multi-scalar multiplications of the provers.
+//
+// Work is cut in one chunk per thread. The chunks run on a pool of worker threads started once, at
+// the first parallel section, so that the many small sections of the setups do not pay for
+// spawning threads. The thread starting a section runs its first chunk, then runs queued chunks
+// until the ones of its section are done, so sections can be nested and closures can borrow the
+// data they work on.
+//
+// Each parallel section is given its number of chunks: the provers use the global number of
+// threads, which defaults to the number of CPUs and can be set once at startup with `--threads` on
+// the command line, while tests pass their own. The pool is sized from the global number when it
+// starts, later changes only changing how sections are split.
+//
+
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+
+///
+/// Configured number of threads, 0 standing for the number of CPUs.
+///
+static THREADS: AtomicUsize = AtomicUsize::new(0);
+
+///
+/// Sets the number of threads of the provers, 0 restoring the default.
+///
+pub fn set_threads(count: usize) {
+    THREADS.store(count, Ordering::Relaxed);
+}
+
+///
+/// Number of threads the provers use.
+///
+pub fn threads() -> usize {
+    match THREADS.load(Ordering::Relaxed) {
+        0 => std::thread::available_parallelism().map(|count| count.get()).unwrap_or(1),
+        count => count
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+struct Pool {
+    jobs: Mutex<VecDeque<Job>>,
+    queued: Condvar
+}
+
+static POOL: OnceLock<Arc<Pool>> = OnceLock::new();
+
+impl Pool {
+
+    fn global() -> &'static Arc<Pool> {
+        POOL.get_or_init(|| {
+
+            let pool = Arc::new(Pool {
+                jobs: Mutex::new(VecDeque::new()),
+                queued: Condvar::new()
+            });
+
+            // The threads starting sections work too
+            for idx in 1..threads() {
+                let pool = pool.clone();
+                std::thread::Builder::new()
+                    .name(format!("snarkrs-worker-{}", idx))
+                    .spawn(move || pool.work())
+                    .expect("Cannot start the worker threads");
+            }
+
+            pool
+
+        })
+    }
+
+    fn work(&self) {
+        loop {
+            let job = {
+                let mut jobs = self.jobs.lock().unwrap();
+                loop {
+                    match jobs.pop_front() {
+                        Some(job) => break job,
+                        None => jobs = self.queued.wait(jobs).unwrap()
+                    }
+                }
+            };
+            job();
+        }
+    }
+
+    fn try_pop(&self) -> Option<Job> {
+        self.jobs.lock().unwrap().pop_front()
+    }
+
+}
+
+///
+/// Chunks of a section not done yet, and whether one of them panicked.
+///
+struct Latch {
+    state: Mutex<(usize, bool)>,
+    done: Condvar
+}
+
+impl Latch {
+
+    fn finish(&self, panicked: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.0 -= 1;
+        state.1 |= panicked;
+        if state.0 == 0 {
+            self.done.notify_all();
+        }
+    }
+
+}
+
+///
+/// Runs `tasks` on the pool, returning once all of them are done. A panic in a task is raised
+/// again once all of them are done.
+///
+fn run<'s>(mut tasks: Vec<Box<dyn FnOnce() + Send + 's>>) {
+
+    if tasks.is_empty() {
+        return;
+    }
+
+    let first = tasks.remove(0);
+    let pool = Pool::global();
+    let latch = Arc::new(Latch {
+        state: Mutex::new((tasks.len(), false)),
+        done: Condvar::new()
+    });
+
+    {
+        let mut jobs = pool.jobs.lock().unwrap();
+        for task in tasks {
+            let latch = latch.clone();
+            let job: Box<dyn FnOnce() + Send + 's> = Box::new(move || {
+                let result = panic::catch_unwind(AssertUnwindSafe(task));
+                latch.finish(result.is_err());
+            });
+            // SAFETY: the borrows of the task outlive the job, as this function does not return
+            // before every job of the section is done, even when a task panics.
+            jobs.push_back(unsafe { std::mem::transmute::<Box<dyn FnOnce() + Send + 's>, Job>(job) });
+        }
+        pool.queued.notify_all();
+    }
+
+    let first = panic::catch_unwind(AssertUnwindSafe(first));
+
+    loop {
+        if latch.state.lock().unwrap().0 == 0 {
+            break;
+        }
+        match pool.try_pop() {
+            Some(job) => job(),
+            None => {
+                let mut state = latch.state.lock().unwrap();
+                while state.0 > 0 {
+                    state = latch.done.wait(state).unwrap();
+                }
+            }
+        }
+    }
+
+    if let Err(payload) = first {
+        panic::resume_unwind(payload);
+    }
+    if latch.state.lock().unwrap().1 {
+        panic!("A parallel task panicked");
+    }
+
+}
+
+///
+/// Size of the chunks splitting `len` items between the threads: a multiple of `min_chunk`, so that
+/// small inputs are not split and chunks can be made of whole blocks.
+///
+fn chunk_size(len: usize, min_chunk: usize, threads: usize) -> usize {
+    let min_chunk = min_chunk.max(1);
+    let per_thread = len.div_ceil(threads.max(1));
+    per_thread.div_ceil(min_chunk).max(1) * min_chunk
+}
+
+///
+/// Calls `task(offset, chunk)` on chunks of `values`, `offset` being the index of the first item of
+/// the chunk.
+///
+pub fn for_each_chunk_mut<T, F>(threads: usize, values: & mut [T], min_chunk: usize, task: F)
+    where T: Send, F: Fn(usize, & mut [T]) + Sync
+{
+
+    let size = chunk_size(values.len(), min_chunk, threads);
+
+    if size >= values.len() {
+        task(0, values);
+        return;
+    }
+
+    let task = &task;
+    run(values.chunks_mut(size).enumerate()
+        .map(|(idx, chunk)| Box::new(move || task(idx * size, chunk)) as Box<dyn FnOnce() + Send>)
+        .collect());
+
+}
+
+///
+/// Calls `task(offset, columns)` on ranges of columns of `values` seen as a matrix of `rows` rows,
+/// `columns[row]` being the part of the row in the range and `offset` the first column of the range.
+///
+pub fn for_each_columns_mut<T, F>(threads: usize, values: & mut [T], rows: usize, min_columns: usize, task: F)
+    where T: Send, F: Fn(usize, & mut [& mut [T]]) + Sync
+{
+
+    assert_eq!(values.len() % rows, 0, "Splitting values in rows of different lengths");
+
+    let width = values.len() / rows;
+    let size = chunk_size(width, min_columns, threads);
+
+    let mut ranges: Vec<Vec<& mut [T]>> = (0..width.div_ceil(size).max(1)).map(|_| Vec::with_capacity(rows)).collect();
+    for row in values.chunks_mut(width.max(1)) {
+        for (range, part) in ranges.iter_mut().zip(row.chunks_mut(size)) {
+            range.push(part);
+        }
+    }
+
+    if ranges.len() == 1 {
+        task(0, & mut ranges[0]);
+        return;
+    }
+
+    let task = &task;
+    run(ranges.into_iter().enumerate()
+        .map(|(idx, mut columns)| Box::new(move || task(idx * size, & mut columns)) as Box<dyn FnOnce() + Send>)
+        .collect());
+
+}
+
+///
+/// Results of `task(offset, chunk)` on chunks of `values`, in order.
+///
+pub fn map_chunks<T, R, F>(threads: usize, values: & [T], min_chunk: usize, task: F) -> Vec<R>
+    where T: Sync, R: Send, F: Fn(usize, & [T]) -> R + Sync
+{
+
+    let size = chunk_size(values.len(), min_chunk, threads);
+
+    if size >= values.len() {
+        return vec!(task(0, values));
+    }
+
+    let mut results: Vec<Option<R>> = values.chunks(size).map(|_| None).collect();
+
+    let task = &task;
+    run(values.chunks(size).zip(results.iter_mut()).enumerate()
+        .map(|(idx, (chunk, result))| Box::new(move || *result = Some(task(idx * size, chunk))) as Box<dyn FnOnce() + Send>)
+        .collect());
+
+    results.into_iter().map(|result| result.unwrap()).collect()
+
+}
+
+#[cfg(test)]
+mod parallel_test {
+
+    use parallel;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_chunks() {
+
+        let mut values: Vec<usize> = vec!(0; 1000);
+        parallel::for_each_chunk_mut(4, & mut values, 16, |offset, chunk| {
+            for (idx, value) in chunk.iter_mut().enumerate() {
+                *value = offset + idx;
+            }
+        });
+        expect_that!(&values, is(eq((0..1000).collect::<Vec<usize>>())));
+
+        let sums = parallel::map_chunks(4, &values, 16, |_, chunk| chunk.iter().sum::<usize>());
+        expect_that!(&sums.len(), is(eq(4)));
+        expect_that!(&sums.iter().sum::<usize>(), is(eq(999 * 1000 / 2)));
+
+        let sums = parallel::map_chunks(4, &values, 2000, |offset, chunk| (offset, chunk.len()));
+        expect_that!(&sums, is(eq(vec!((0, 1000)))));
+
+        let empty: Vec<usize> = Vec::new();
+        expect_that!(&parallel::map_chunks(4, &empty, 1, |_, chunk| chunk.len()), is(eq(vec!(0))));
+
+        // Columns of a 4 x 250 matrix, each one filled with its index
+        parallel::for_each_columns_mut(3, & mut values, 4, 10, |offset, columns| {
+            expect_that!(&columns.len(), is(eq(4)));
+            for row in columns.iter_mut() {
+                for (idx, value) in row.iter_mut().enumerate() {
+                    *value = offset + idx;
+                }
+            }
+        });
+        expect_that!(&values, is(eq((0..1000).map(|idx| idx % 250).collect::<Vec<usize>>())));
+
+    }
+
+    #[test]
+    fn test_nested_sections() {
+
+        // Every chunk starts a section of its own, waiting for chunks queued behind it
+        let mut values: Vec<usize> = vec!(0; 64);
+        parallel::for_each_chunk_mut(8, & mut values, 1, |offset, chunk| {
+            let sums = parallel::map_chunks(8, &(0..100).collect::<Vec<usize>>(), 1, |_, part| part.iter().sum::<usize>());
+            for value in chunk.iter_mut() {
+                *value = offset + sums.iter().sum::<usize>();
+            }
+        });
+        expect_that!(&values, is(eq((0..64).map(|idx| idx / 8 * 8 + 4950).collect::<Vec<usize>>())));
+
+        let panicked = std::panic::catch_unwind(|| parallel::map_chunks(4, &values, 1, |offset, _| if offset == 48 { panic!("chunk") } else { offset }));
+        expect_that!(&panicked.is_err(), is(eq(true)));
+        expect_that!(&parallel::map_chunks(4, &values, 1, |offset, _| offset), is(eq(vec!(0, 16, 32, 48))));
+
+    }
+
+}
@@ -11,7 +11,7 @@ pub mod tokens {
     ///
     /// Enumeration containing the two types of AST tokens.
     ///
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq)]
     pub enum Token {
         Terminal(TerminalToken),
         NonTerminal(NonTerminalToken)
@@ -20,7 +20,7 @@ pub mod tokens {
     ///
     /// NonTerminal Tokens: tokens that contain sub tokens. In our case, the cpatured value is ignored.
     ///
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq)]
     pub struct NonTerminalToken {
         pub span: (usize, usize),
         pub rule: ast::Rule,
@@ -30,7 +30,7 @@ pub mod tokens {
     ///
     /// Terminal Tokens: tokens that do not contain sub tokens. These are most of the time operators, names, values...
     ///
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq)]
     pub struct TerminalToken {
         pub span: (usize, usize),
         pub rule: ast::Rule,
@@ -40,7 +40,7 @@ pub mod tokens {
     ///
     /// Root Token: Simple wrapper containing the source that has been parsed and the resulting tokens.
     ///
-    #[derive(Debug)]
+    #[derive(Debug, Clone, PartialEq)]
     pub struct RootToken {
         pub source: std::string::String,
         pub ast: Vec<Token>
@@ -76,7 +76,7 @@ pub fn pairs_to_tokens(pairs: Vec<pest::iterators::Pair<Rule>>) -> Vec<tokens::T
 
         let rule: Rule = pair.as_rule();
         let span: pest::Span = pair.as_span();
-        let inner_pairs: Vec<pest::iterators::Pair<Rule>> = pair.into_inner().into_iter().collect();
+        let inner_pairs: Vec<pest::iterators::Pair<Rule>> = pair.into_inner().collect();
 
         match inner_pairs.len() {
 
@@ -99,7 +99,7 @@ pub fn pairs_to_tokens(pairs: Vec<pest::iterators::Pair<Rule>>) -> Vec<tokens::T
         }
     };
 
-    return return_value;
+    return_value
 
 }
 
@@ -135,15 +135,24 @@ pub fn parse_source(sources: & str, maybe_rule: Option<Rule>) -> std::result::Re
 ///
 pub fn parse_file(path: & std::path::PathBuf) -> Result<File, ParseError> {
 
-    let path = std::path::PathBuf::from(path);
-    let content = match std::fs::read_to_string(&path) {
+    let content = match std::fs::read_to_string(path) {
         Ok(val) => val,
         Err(error) => return Err(ParseError::IOError(error))
     };
 
+    parse_file_source(path, &content)
+
+}
+
+///
+/// Same as `parse_file`, with the content of the file given instead of read, as for the unsaved
+/// buffers of an editor.
+///
+pub fn parse_file_source(path: & std::path::Path, content: & str) -> Result<File, ParseError> {
+
     Ok(File {
-        path,
-        root: match parse_source(&content, None) {
+        path: path.to_path_buf(),
+        root: match parse_source(content, None) {
             Ok(val) => val,
             Err(error) => return Err(ParseError::PestError(error))
         },
@@ -155,19 +164,17 @@ pub fn parse_file(path: & std::path::PathBuf) -> Result<File, ParseError> {
 #[cfg(test)]
 mod parser_tests {
 
-    use galvanic_assert::matchers::*;
-
     use parser::ast;
     use pest::error::{LineColLocation, ErrorVariant};
     use parser::ast::{parse_file};
 
-    fn test_untupler(expect_rules_values: &Vec<(ast::Rule, u32)>) -> Vec<ast::Rule> {
+    fn test_untupler(expect_rules_values: &[(ast::Rule, u32)]) -> Vec<ast::Rule> {
 
         let mut ret: Vec<ast::Rule> = Vec::new();
 
-        for rule_idx in 0..expect_rules_values.len() {
+        for expect_rule_value in expect_rules_values {
             ret.push(
-                expect_rules_values[rule_idx].0
+                expect_rule_value.0
             );
         }
 
@@ -220,7 +227,7 @@ mod parser_tests {
     fn complete_parse() {
 
         let pathbuf: std::path::PathBuf = match std::fs::canonicalize("./src/lib/parser/test_material/circuits/bitify.circom") {
-            Err(error) => panic!(error),
+            Err(error) => panic!("{}", error),
             Ok(path) => path
         };
 
@@ -242,11 +249,11 @@ mod parser_tests {
 
         for filename in filenames {
 
-            let contents = std::fs::read_to_string(filename).expect(&format!("Cannot read file {}", filename));
+            let contents = std::fs::read_to_string(filename).unwrap_or_else(|_| panic!("Cannot read file {}", filename));
 
             match ast::parse_source(&contents, Some(ast::Rule::Circuit)) {
                 Ok(_pairs) => {},
-                Err(err) => panic!(err)
+                Err(err) => panic!("{}", err)
             }
 
         }
@@ -259,7 +266,7 @@ mod parser_tests {
     #[test]
     fn include_it_fails_on_missing_semicolon() {
         let filename: &str = "./src/lib/parser/test_material/include/invalid_include__missing_semicolon.circom";
-        let contents = std::fs::read_to_string(filename).expect(&format!("Cannot read file {}", filename));
+        let contents = std::fs::read_to_string(filename).unwrap_or_else(|_| panic!("Cannot read file {}", filename));
         match ast::parse_source(&contents, Some(ast::Rule::IncludeStatement)) {
             Ok(_pairs) => {},
             Err(err) => {
@@ -271,10 +278,25 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn break_and_continue_statements() {
+
+        // With or without a space before the `;`, names starting with the keywords staying names
+        let contents = "{\n    break;\n    continue ;\n    breaker = 1;\n    continue_ = 2;\n}";
+        let pairs = ast::parse_source(contents, Some(ast::Rule::Body)).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(count_rules(&pairs.ast[0], &vec!(ast::Rule::BreakStatement, ast::Rule::ContinueStatement, ast::Rule::E_VariableName)), vec!(1, 1, 2));
+
+        let filename: &str = "./src/lib/parser/test_material/functions/valid_function.circom";
+        let contents = std::fs::read_to_string(filename).unwrap_or_else(|_| panic!("Cannot read file {}", filename));
+        let pairs = ast::parse_source(&contents, Some(ast::Rule::Circuit)).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(count_rules(&pairs.ast[0], &vec!(ast::Rule::BreakStatement)), vec!(2));
+
+    }
+
     #[test]
     fn include_it_fails_on_directive_typo() {
         let filename: &str = "./src/lib/parser/test_material/include/invalid_include__directive_typo.circom";
-        let contents = std::fs::read_to_string(filename).expect(&format!("Cannot read file {}", filename));
+        let contents = std::fs::read_to_string(filename).unwrap_or_else(|_| panic!("Cannot read file {}", filename));
         match ast::parse_source(&contents, Some(ast::Rule::IncludeStatement)) {
             Ok(_pairs) => {},
             Err(err) => {
@@ -289,7 +311,7 @@ mod parser_tests {
     #[test]
     fn include_it_fails_on_empty_value() {
         let filename: &str = "./src/lib/parser/test_material/include/invalid_include__empty_include.circom";
-        let contents = std::fs::read_to_string(filename).expect(&format!("Cannot read file {}", filename));
+        let contents = std::fs::read_to_string(filename).unwrap_or_else(|_| panic!("Cannot read file {}", filename));
         match ast::parse_source(&contents, Some(ast::Rule::IncludeStatement)) {
             Ok(_pairs) => {},
             Err(err) => {
@@ -308,7 +330,7 @@ mod parser_tests {
     #[test]
     fn function_invalid_missing_semicolon() {
         let filename: &str = "./src/lib/parser/test_material/functions/invalid_function__missing_semicolon.circom";
-        let contents = std::fs::read_to_string(filename).expect(&format!("Cannot read file {}", filename));
+        let contents = std::fs::read_to_string(filename).unwrap_or_else(|_| panic!("Cannot read file {}", filename));
         match ast::parse_source(&contents, Some(ast::Rule::FunctionBlock)) {
             Ok(_pairs) => {},
             Err(err) => {
@@ -470,7 +492,7 @@ mod parser_tests {
 
         for test_idx in 0..filenames.len() {
 
-            let contents = std::fs::read_to_string(filenames[test_idx]).expect(&format!("Cannot read file {}", filenames[test_idx]));
+            let contents = std::fs::read_to_string(filenames[test_idx]).unwrap_or_else(|_| panic!("Cannot read file {}", filenames[test_idx]));
             match ast::parse_source(& contents, Some(ast::Rule::Body)) {
                 Ok(tokens) => {
 
@@ -497,7 +519,7 @@ mod parser_tests {
                     println!("----------");
 
                 },
-                Err(err) => panic!(err)
+                Err(err) => panic!("{}", err)
             }
 
         }
@@ -843,7 +865,7 @@ mod parser_tests {
 
         for idx in 0..filenames.len() {
 
-            let contents = std::fs::read_to_string(filenames[idx]).expect(&format!("Cannot read file {}", filenames[idx]));
+            let contents = std::fs::read_to_string(filenames[idx]).unwrap_or_else(|_| panic!("Cannot read file {}", filenames[idx]));
             match ast::parse_source(& contents, Some(ast::Rule::Circuit)) {
                 Ok(tokens) => {
 
@@ -869,7 +891,7 @@ mod parser_tests {
                     }
                     println!("----------");
                 },
-                Err(err) => panic!(err)
+                Err(err) => panic!("{}", err)
             }
 
         }
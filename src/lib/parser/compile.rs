@@ -1,6 +1,7 @@
 use parser::ast;
 use parser::matchers;
 use parser::errors;
+use parser::errors::include::UnknownFileIncludeError;
 
 #[derive(Debug)]
 enum LoadAttempt {
@@ -14,49 +15,125 @@ pub struct Context {
     files: std::collections::HashMap<std::path::PathBuf, LoadAttempt>,
     errors: Vec<errors::CompileError>,
     main: std::path::PathBuf,
-    include_stack: Vec<std::path::PathBuf>
+    include_paths: Vec<std::path::PathBuf>,
+    include_stack: Vec<std::path::PathBuf>,
+    sources: std::collections::HashMap<std::path::PathBuf, std::string::String>
 }
 
-fn context_gather_includes(file: & mut ast::File) {
+impl Context {
 
-    {
-        if file.root.ast.len() != 1 {
-            panic!("Invalid ast");
+    ///
+    /// Path of the main file the context has been built from.
+    ///
+    pub fn main(&self) -> & std::path::Path {
+        &self.main
+    }
+
+    ///
+    /// Errors reported while loading the main file and its includes.
+    ///
+    pub fn errors(&self) -> & [errors::CompileError] {
+        &self.errors
+    }
+
+    ///
+    /// Retrieves the successfully loaded file at the given path.
+    ///
+    pub fn file(&self, path: & std::path::Path) -> Option<& ast::File> {
+        match self.files.get(path) {
+            Some(LoadAttempt::Loaded(file)) => Some(file),
+            _ => None
         }
     }
 
-    let mut includes: Vec<std::path::PathBuf> = Vec::new();
+    ///
+    /// Iterates over all the successfully loaded files, main file included.
+    ///
+    pub fn files(&self) -> impl Iterator<Item = & ast::File> {
+        self.files.values().filter_map(|attempt| match attempt {
+            LoadAttempt::Loaded(file) => Some(file),
+            _ => None
+        })
+    }
+
+}
+
+///
+/// Includes are looked up relatively to the including file first, then in each include path, in
+/// order. An include found nowhere is kept relative to the including file, so that loading it
+/// reports the error.
+///
+fn resolve_include(current_file_dir: & std::path::Path, include_paths: & [std::path::PathBuf], file_name: & str) -> std::path::PathBuf {
+
+    let local = current_file_dir.join(file_name);
+
+    if local.is_file() {
+        return local;
+    }
+
+    include_paths.iter()
+        .map(|include_path| include_path.join(file_name))
+        .find(|path| path.is_file())
+        .unwrap_or(local)
+
+}
+
+///
+/// Reports a file whose token tree is not the one the grammar builds, pointing at its start.
+///
+fn invalid_tree(file: & ast::File, reason: & str) -> errors::CompileError {
+    errors::from_matching(&file.path, &matchers::MatchError { span: (0, 0), reason: reason.to_string() })
+}
+
+fn context_gather_includes(file: & mut ast::File, include_paths: & [std::path::PathBuf]) -> Result<(), errors::CompileError> {
+
+    if file.root.ast.len() != 1 {
+        return Err(invalid_tree(file, "Invalid ast"));
+    }
+
     let current_file_dir: std::path::PathBuf = match file.path.parent() {
-        Some(parent) => parent,
-        None => panic!("Cannot retrieve parent directory of source file !")
-    }.to_path_buf();
+        Some(parent) => parent.to_path_buf(),
+        None => return Err(errors::CompileError::UnknwonFileIncludeError(UnknownFileIncludeError {
+            file_error: file.path.clone(),
+            source_error: "Cannot retrieve parent directory of source file".to_string(),
+            span_error: (0, 0),
+
+            invalid_file: file.path.clone()
+        }))
+    };
+
+    let mut includes: Vec<std::path::PathBuf> = Vec::new();
 
     if let ast::tokens::Token::NonTerminal(circuit) = &file.root.ast[0] {
         for token in &circuit.subrules {
-            match token {
-                ast::tokens::Token::NonTerminal(nt) => {
-                    if nt.rule == ast::Rule::IncludeStatement {
-                        let file_name = matchers::include_statement::process_include_statement(nt);
-                        let mut include_absolute_path = current_file_dir.clone();
-                        include_absolute_path.push(file_name);
-                        includes.push(include_absolute_path);
-                    }
-                },
-                _ => {}
+            if let ast::tokens::Token::NonTerminal(nt) = token {
+                if nt.rule == ast::Rule::IncludeStatement {
+                    let file_name = matchers::include_statement::process_include_statement(nt)
+                        .map_err(|error| errors::from_matching(&file.path, &error))?;
+                    includes.push(resolve_include(&current_file_dir, include_paths, file_name));
+                }
             }
         }
     } else {
-        panic!("Should find Circuit at root");
+        return Err(invalid_tree(file, "Should find Circuit at root"));
     }
 
     file.includes = includes;
 
+    Ok(())
 
 }
 
-fn context_load_file_success(file: & mut ast::File) {
+///
+/// Gathers the includes of a parsed file and checks its token tree builds a typed AST. The file is
+/// kept loaded on error, the tools working on its token tree still being able to use it.
+///
+fn context_load_file_success(file: & mut ast::File, include_paths: & [std::path::PathBuf]) -> Result<(), errors::CompileError> {
 
-    context_gather_includes(file);
+    context_gather_includes(file, include_paths)?;
+    matchers::circuit::process_circuit(&file.root).map_err(|error| errors::from_matching(&file.path, &error))?;
+
+    Ok(())
 
 }
 
@@ -64,9 +141,9 @@ fn add_error_to_context(error: errors::CompileError, ctx: & mut Context) {
     ctx.errors.push(error);
 }
 
-fn context_load_file_error(error: ast::ParseError, ctx: & mut Context, file_path: & std::path::PathBuf) {
+fn context_load_file_error(error: ast::ParseError, ctx: & mut Context, file_path: & std::path::Path) {
 
-    add_error_to_context(errors::from_pest_parsing(&file_path.clone(), &error), ctx);
+    add_error_to_context(errors::from_pest_parsing(file_path, &error), ctx);
 
 }
 
@@ -76,10 +153,20 @@ fn context_load_file(ctx: & mut Context, file_path: & std::path::PathBuf) -> Opt
         return None;
     }
 
-    let res = match ast::parse_file(file_path) {
+    let source = ctx.sources.get(file_path)
+        .or_else(|| std::fs::canonicalize(file_path).ok().and_then(|path| ctx.sources.get(&path)));
+
+    let parsed = match source {
+        Some(source) => ast::parse_file_source(file_path, source),
+        None => ast::parse_file(file_path)
+    };
+
+    let res = match parsed {
 
         Ok(mut file) => {
-            context_load_file_success(& mut file);
+            if let Err(error) = context_load_file_success(& mut file, &ctx.include_paths) {
+                add_error_to_context(error, ctx);
+            }
             file
         },
 
@@ -93,13 +180,10 @@ fn context_load_file(ctx: & mut Context, file_path: & std::path::PathBuf) -> Opt
     ctx.include_stack.push(file_path.clone());
 
     for include in &res.includes {
-        match context_load_file(ctx, include) {
-            Some(file) => {
-                let path = file.path.clone();
+        if let Some(file) = context_load_file(ctx, include) {
+            let path = file.path.clone();
 
-                ctx.files.insert(path, LoadAttempt::Loaded(file));
-            },
-            None => {}
+            ctx.files.insert(path, LoadAttempt::Loaded(file));
         }
     }
 
@@ -116,12 +200,30 @@ fn context_load_file(ctx: & mut Context, file_path: & std::path::PathBuf) -> Opt
 /// structure and reports possible errors.
 ///
 pub fn build_context(file: & std::path::PathBuf) -> Context {
+    build_context_with_include_paths(file, &[])
+}
+
+///
+/// Same as `build_context`, includes not found next to the including file being looked up in the
+/// given directories, in order.
+///
+pub fn build_context_with_include_paths(file: & std::path::PathBuf, include_paths: & [std::path::PathBuf]) -> Context {
+    build_context_with_sources(file, include_paths, &std::collections::HashMap::new())
+}
+
+///
+/// Same as `build_context_with_include_paths`, the files present in `sources` being parsed from the
+/// given content instead of being read from disk. Paths are compared once canonicalized.
+///
+pub fn build_context_with_sources(file: & std::path::PathBuf, include_paths: & [std::path::PathBuf], sources: & std::collections::HashMap<std::path::PathBuf, std::string::String>) -> Context {
 
     let mut ctx = Context {
         files: std::collections::HashMap::new(),
         errors: Vec::new(),
         main: file.clone(),
-        include_stack: Vec::new()
+        include_paths: include_paths.to_vec(),
+        include_stack: Vec::new(),
+        sources: sources.clone()
     };
 
     ctx.files.insert(file.clone(), LoadAttempt::Loading());
@@ -169,4 +271,38 @@ mod compile_test {
 
 
     }
+
+    #[test]
+    fn test_build_context_with_include_paths() {
+
+        let path = std::fs::canonicalize("./src/lib/parser/test_material/include/library_include.circom").expect("Invalid Path");
+        let library = std::fs::canonicalize("./src/lib/parser/test_material/circuits").expect("Invalid Path");
+
+        let ctx = compile::build_context(&path);
+
+        expect_that!(&ctx.errors.len(), is(eq(1)));
+
+        let ctx = compile::build_context_with_include_paths(&path, &[library]);
+
+        expect_that!(&ctx.errors.len(), is(eq(0)));
+        expect_that!(&ctx.files.keys().len(), is(eq(6)));
+
+    }
+
+    #[test]
+    fn test_build_context_without_parent_directory() {
+
+        // The root has no parent directory to resolve includes from
+        let path = std::path::PathBuf::from("/");
+        let mut sources = std::collections::HashMap::new();
+        sources.insert(path.clone(), "template A() {}".to_string());
+
+        let ctx = compile::build_context_with_sources(&path, &[], &sources);
+
+        expect_that!(&ctx.errors.len(), is(eq(1)));
+        expect_that!(&ctx.errors[0].code(), is(eq(201)));
+        expect_that!(&ctx.file(&path).is_some(), is(eq(true)));
+
+    }
+
 }
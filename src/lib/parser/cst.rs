@@ -0,0 +1,448 @@
+use parser::ast::{self, Rule};
+use parser::ast::tokens::{Token, RootToken};
+use parser::typed_ast::Span;
+
+//
+// Lossless concrete syntax tree.
+//
+// The token tree drops what the grammar does not capture: whitespace, literal delimiters such as
+// `(` or `;` inside rules, and the comments matched in atomic rules. The CST keeps every byte of the
+// source: each byte belongs either to a token or to the trivia (whitespace and comments) attached
+// to one, so that concatenating the tokens with their trivia gives the source back.
+//
+// Trivia following a token on the same line, up to and including the line break, trail that token.
+// Other trivia lead the next token, the `EOI` token holding the ones at the end of the file.
+//
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    Comment
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub span: Span,
+    pub text: std::string::String
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstToken {
+
+    ///
+    /// Rule of the token, `None` for the literals the grammar matches without a rule.
+    ///
+    pub rule: Option<Rule>,
+    pub span: Span,
+    pub text: std::string::String,
+    pub leading: Vec<Trivia>,
+    pub trailing: Vec<Trivia>
+
+}
+
+impl CstToken {
+
+    ///
+    /// Span of the token and its trivia.
+    ///
+    pub fn full_span(&self) -> Span {
+        (
+            self.leading.first().map(|trivia| trivia.span.0).unwrap_or(self.span.0),
+            self.trailing.last().map(|trivia| trivia.span.1).unwrap_or(self.span.1)
+        )
+    }
+
+    pub fn push_source(&self, output: & mut std::string::String) {
+        self.leading.iter().for_each(|trivia| output.push_str(&trivia.text));
+        output.push_str(&self.text);
+        self.trailing.iter().for_each(|trivia| output.push_str(&trivia.text));
+    }
+
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CstChild {
+    Node(CstNode),
+
+    ///
+    /// Index of the token in `Cst::tokens`.
+    ///
+    Token(usize)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstNode {
+    pub rule: Rule,
+    pub span: Span,
+    pub children: Vec<CstChild>,
+
+    ///
+    /// Indices of the first and past the last token of the node.
+    ///
+    pub tokens: (usize, usize)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cst {
+    pub source: std::string::String,
+    pub children: Vec<CstChild>,
+    pub tokens: Vec<CstToken>
+}
+
+impl Cst {
+
+    pub fn new(root: & RootToken) -> Cst {
+
+        let mut builder = Builder {
+            source: &root.source,
+            tokens: Vec::new(),
+            pending: Vec::new(),
+            trailing_open: false
+        };
+
+        let mut children = Vec::new();
+        let mut cursor = 0;
+
+        for token in &root.ast {
+            let span = token_span(token);
+            builder.gap(cursor, span.0, & mut children);
+            builder.token(token, & mut children);
+            cursor = span.1;
+        }
+
+        builder.gap(cursor, root.source.len(), & mut children);
+        builder.finish(& mut children);
+
+        Cst {
+            source: root.source.clone(),
+            children,
+            tokens: builder.tokens
+        }
+
+    }
+
+    pub fn parse(source: & str) -> Result<Cst, pest::error::Error<Rule>> {
+        Ok(Cst::new(&ast::parse_source(source, None)?))
+    }
+
+    ///
+    /// Source the tree was built from, rebuilt from the tokens and their trivia.
+    ///
+    pub fn to_source(&self) -> std::string::String {
+
+        let mut output = std::string::String::with_capacity(self.source.len());
+        for token in &self.tokens {
+            token.push_source(& mut output);
+        }
+
+        output
+
+    }
+
+    ///
+    /// Source of a node, with the trivia inside it but without the leading trivia of its first
+    /// token and the trailing trivia of its last one.
+    ///
+    pub fn node_text(&self, node: & CstNode) -> std::string::String {
+        match (self.tokens.get(node.tokens.0), node.tokens.1.checked_sub(1).and_then(|last| self.tokens.get(last))) {
+            (Some(first), Some(last)) if node.tokens.0 < node.tokens.1 => self.source[first.span.0..last.span.1].to_string(),
+            _ => std::string::String::new()
+        }
+    }
+
+    ///
+    /// Token whose text or trivia contains the offset.
+    ///
+    pub fn token_at(&self, offset: usize) -> Option<& CstToken> {
+        let idx = self.tokens.partition_point(|token| token.full_span().1 <= offset);
+        self.tokens.get(idx).filter(|token| token.full_span().0 <= offset)
+    }
+
+    ///
+    /// All comments, in source order.
+    ///
+    pub fn comments(&self) -> impl Iterator<Item = & Trivia> {
+        self.tokens.iter()
+            .flat_map(|token| token.leading.iter().chain(token.trailing.iter()))
+            .filter(|trivia| trivia.kind == TriviaKind::Comment)
+    }
+
+}
+
+fn token_span(token: & Token) -> Span {
+    match token {
+        Token::Terminal(terminal) => terminal.span,
+        Token::NonTerminal(non_terminal) => non_terminal.span
+    }
+}
+
+struct Builder<'a> {
+    source: &'a str,
+    tokens: Vec<CstToken>,
+
+    // Trivia waiting for the next token
+    pending: Vec<Trivia>,
+
+    // No line break since the last token, the trivia met trail it
+    trailing_open: bool
+}
+
+impl<'a> Builder<'a> {
+
+    fn trivia(& mut self, kind: TriviaKind, span: Span) {
+
+        if span.0 == span.1 {
+            return ;
+        }
+
+        let text = &self.source[span.0..span.1];
+
+        if self.trailing_open && !self.tokens.is_empty() {
+
+            let end = match text.find('\n') {
+                Some(newline) => {
+                    self.trailing_open = false;
+                    span.0 + newline + 1
+                },
+                None => span.1
+            };
+
+            self.tokens.last_mut().unwrap().trailing.push(Trivia {
+                kind,
+                span: (span.0, end),
+                text: self.source[span.0..end].to_string()
+            });
+
+            if end < span.1 {
+                self.pending.push(Trivia {
+                    kind,
+                    span: (end, span.1),
+                    text: self.source[end..span.1].to_string()
+                });
+            }
+
+        } else {
+            self.pending.push(Trivia {
+                kind,
+                span,
+                text: text.to_string()
+            });
+        }
+
+    }
+
+    fn leaf(& mut self, rule: Option<Rule>, span: Span, children: & mut Vec<CstChild>) {
+        children.push(CstChild::Token(self.tokens.len()));
+        self.tokens.push(CstToken {
+            rule,
+            span,
+            text: self.source[span.0..span.1].to_string(),
+            leading: std::mem::take(& mut self.pending),
+            trailing: Vec::new()
+        });
+        self.trailing_open = true;
+    }
+
+    ///
+    /// Splits the source between two tokens into whitespace, comments and the literals matched by
+    /// the grammar without a rule.
+    ///
+    fn gap(& mut self, start: usize, end: usize, children: & mut Vec<CstChild>) {
+
+        let mut cursor = start;
+
+        while cursor < end {
+
+            let rest = &self.source[cursor..end];
+            let first = rest.chars().next().unwrap();
+
+            if first.is_whitespace() {
+                let length = rest.find(|character: char| !character.is_whitespace()).unwrap_or(rest.len());
+                self.trivia(TriviaKind::Whitespace, (cursor, cursor + length));
+                cursor += length;
+            } else if rest.starts_with("//") {
+                let length = rest.find('\n').unwrap_or(rest.len());
+                self.trivia(TriviaKind::Comment, (cursor, cursor + length));
+                cursor += length;
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                let length = comment.find("*/").map(|close| close + 4).unwrap_or(rest.len());
+                self.trivia(TriviaKind::Comment, (cursor, cursor + length));
+                cursor += length;
+            } else {
+                let length = rest.char_indices()
+                    .find(|(idx, character)| character.is_whitespace() || rest[*idx..].starts_with("//") || rest[*idx..].starts_with("/*"))
+                    .map(|(idx, _)| idx)
+                    .unwrap_or(rest.len());
+                self.leaf(None, (cursor, cursor + length), children);
+                cursor += length;
+            }
+
+        }
+
+    }
+
+    fn token(& mut self, token: & Token, children: & mut Vec<CstChild>) {
+        match token {
+            Token::Terminal(terminal) if terminal.rule == Rule::COMMENT => {
+                // Line comments include their line break
+                let span = terminal.span;
+                let length = self.source[span.0..span.1].trim_end().len();
+                self.trivia(TriviaKind::Comment, (span.0, span.0 + length));
+                self.trivia(TriviaKind::Whitespace, (span.0 + length, span.1));
+            },
+            Token::Terminal(terminal) => {
+                // Keywords of atomic rules include the whitespace that follows them
+                let (start, end) = terminal.span;
+                let text = &self.source[start..end];
+                let leading = text.len() - text.trim_start().len();
+                let trailing = text.len() - text.trim_end().len();
+
+                if leading == text.len() {
+                    self.trivia(TriviaKind::Whitespace, (start, end));
+                    if start == end {
+                        self.leaf(Some(terminal.rule), (start, end), children);
+                    }
+                    return ;
+                }
+
+                self.trivia(TriviaKind::Whitespace, (start, start + leading));
+                self.leaf(Some(terminal.rule), (start + leading, end - trailing), children);
+                self.trivia(TriviaKind::Whitespace, (end - trailing, end));
+            },
+            Token::NonTerminal(non_terminal) => {
+
+                let first_token = self.tokens.len();
+                let mut node_children = Vec::new();
+                let mut cursor = non_terminal.span.0;
+
+                for subrule in &non_terminal.subrules {
+                    let span = token_span(subrule);
+                    self.gap(cursor, span.0, & mut node_children);
+                    self.token(subrule, & mut node_children);
+                    cursor = span.1;
+                }
+
+                self.gap(cursor, non_terminal.span.1, & mut node_children);
+
+                children.push(CstChild::Node(CstNode {
+                    rule: non_terminal.rule,
+                    span: non_terminal.span,
+                    children: node_children,
+                    tokens: (first_token, self.tokens.len())
+                }));
+
+            }
+        }
+    }
+
+    ///
+    /// Gives the trivia at the end of the file to an empty `EOI` token, adding one if the parsed
+    /// rule did not end with it.
+    ///
+    fn finish(& mut self, children: & mut Vec<CstChild>) {
+
+        if self.pending.is_empty() && !self.tokens.is_empty() {
+            return ;
+        }
+
+        match self.tokens.last_mut() {
+            Some(last) if last.rule == Some(Rule::EOI) => {
+                let pending = std::mem::take(& mut self.pending);
+                last.trailing.extend(pending);
+            },
+            _ => {
+                let end = self.source.len();
+                self.leaf(Some(Rule::EOI), (end, end), children);
+            }
+        }
+
+    }
+
+}
+
+#[cfg(test)]
+mod cst_test {
+
+    use parser::ast::{self, Rule};
+    use parser::cst::{Cst, CstChild, TriviaKind};
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_trivia() {
+
+        let source = "  // header\ntemplate A() {\n    signal input a; // the input\n    /* block */ a === 1;\n}\n\n";
+        let cst = Cst::parse(source).unwrap();
+
+        expect_that!(&cst.to_source(), is(eq(source.to_string())));
+
+        let template = cst.tokens.iter().find(|token| token.rule == Some(Rule::TemplateKW)).unwrap();
+        expect_that!(&template.text, is(eq("template".to_string())));
+        expect_that!(&template.leading.iter().map(|trivia| trivia.text.clone()).collect::<Vec<_>>(), is(eq(vec!("  ".to_string(), "// header".to_string(), "\n".to_string()))));
+        expect_that!(&template.trailing[0].text, is(eq(" ".to_string())));
+
+        let semicolon = cst.token_at(source.find("; //").unwrap()).unwrap();
+        expect_that!(&semicolon.text, is(eq(";".to_string())));
+        expect_that!(&semicolon.trailing.iter().map(|trivia| trivia.text.clone()).collect::<Vec<_>>(), is(eq(vec!(" ".to_string(), "// the input".to_string(), "\n".to_string()))));
+
+        let comments: Vec<std::string::String> = cst.comments().map(|trivia| trivia.text.clone()).collect();
+        expect_that!(&comments, is(eq(vec!("// header".to_string(), "// the input".to_string(), "/* block */".to_string()))));
+
+        let last = cst.tokens.last().unwrap();
+        expect_that!(&last.rule, is(eq(Some(Rule::EOI))));
+        expect_that!(&last.leading.iter().all(|trivia| trivia.kind == TriviaKind::Whitespace), is(eq(true)));
+
+        match &cst.children[0] {
+            CstChild::Node(circuit) => {
+                expect_that!(&circuit.rule, is(eq(Rule::Circuit)));
+                match &circuit.children[0] {
+                    CstChild::Node(template) => {
+                        expect_that!(&cst.node_text(template), is(eq(source[12..source.len() - 2].to_string())));
+                    },
+                    other => panic!("Unexpected child {:?}", other)
+                }
+            },
+            other => panic!("Unexpected child {:?}", other)
+        }
+
+    }
+
+    fn circuit_files(directory: & std::path::Path, files: & mut Vec<std::path::PathBuf>) {
+        for entry in std::fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                circuit_files(&path, files);
+            } else if path.extension().map(|extension| extension == "circom").unwrap_or(false) {
+                files.push(path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_circuits_roundtrip() {
+
+        let mut files = Vec::new();
+        circuit_files(std::path::Path::new("./src/lib/parser/test_material/circuits"), & mut files);
+
+        expect_that!(&files.iter().any(|path| path.ends_with("sha256/sha256compression.circom")), is(eq(true)));
+
+        for path in files {
+
+            let source = std::fs::read_to_string(&path).unwrap();
+            let cst = Cst::new(&ast::parse_source(&source, None).unwrap());
+
+            expect_that!(&cst.to_source(), is(eq(source.clone())));
+
+            let mut end = 0;
+            for token in &cst.tokens {
+                expect_that!(&token.full_span().0, is(eq(end)));
+                expect_that!(&token.text, is(eq(source[token.span.0..token.span.1].to_string())));
+                end = token.full_span().1;
+            }
+
+        }
+
+    }
+
+}
@@ -0,0 +1,301 @@
+use serde_json::{Map, Value};
+
+use parser::ast::Rule;
+use parser::ast::tokens::{Token, TerminalToken, NonTerminalToken, RootToken};
+use parser::typed_ast::Circuit;
+
+///
+/// Serialized forms of the token tree and of the typed AST, for debugging the grammar and for
+/// external tools.
+///
+/// Tokens are written as `{"rule": "Circuit", "span": [0, 68], "children": [...]}`, terminals
+/// holding a `content` string instead of `children`. The root is `{"source": ..., "tokens": [...]}`.
+/// The typed AST uses the serde representation of its types, enum variants being tagged by name.
+///
+#[derive(Debug)]
+pub enum DumpError {
+    JsonError(serde_json::Error),
+    FormatError(std::string::String)
+}
+
+impl std::fmt::Display for DumpError {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DumpError::JsonError(error) => write!(formatter, "Invalid JSON: {}", error),
+            DumpError::FormatError(reason) => write!(formatter, "Invalid dump: {}", reason)
+        }
+    }
+}
+
+fn token_to_json(token: & Token) -> Value {
+
+    let mut object = Map::new();
+
+    match token {
+        Token::Terminal(terminal) => {
+            object.insert("rule".to_string(), Value::from(format!("{:?}", terminal.rule)));
+            object.insert("span".to_string(), Value::from(vec!(terminal.span.0, terminal.span.1)));
+            object.insert("content".to_string(), Value::from(terminal.content.clone()));
+        },
+        Token::NonTerminal(non_terminal) => {
+            object.insert("rule".to_string(), Value::from(format!("{:?}", non_terminal.rule)));
+            object.insert("span".to_string(), Value::from(vec!(non_terminal.span.0, non_terminal.span.1)));
+            object.insert("children".to_string(), Value::Array(non_terminal.subrules.iter().map(token_to_json).collect()));
+        }
+    }
+
+    Value::Object(object)
+
+}
+
+pub fn tokens_to_json(root: & RootToken) -> Value {
+
+    let mut object = Map::new();
+    object.insert("source".to_string(), Value::from(root.source.clone()));
+    object.insert("tokens".to_string(), Value::Array(root.ast.iter().map(token_to_json).collect()));
+
+    Value::Object(object)
+
+}
+
+///
+/// `all_rules` does not list the builtin `EOI` rule.
+///
+fn rule_from_name(name: & str) -> Option<Rule> {
+    if name == "EOI" {
+        return Some(Rule::EOI);
+    }
+    Rule::all_rules().iter().find(|rule| format!("{:?}", rule) == name).cloned()
+}
+
+fn field<'a>(object: &'a Map<std::string::String, Value>, name: & str) -> Result<&'a Value, DumpError> {
+    object.get(name).ok_or_else(|| DumpError::FormatError(format!("missing field {:?}", name)))
+}
+
+fn token_from_json(value: & Value) -> Result<Token, DumpError> {
+
+    let object = value.as_object().ok_or_else(|| DumpError::FormatError("tokens must be objects".to_string()))?;
+
+    let name = field(object, "rule")?.as_str().ok_or_else(|| DumpError::FormatError("rule must be a string".to_string()))?;
+    let rule = rule_from_name(name).ok_or_else(|| DumpError::FormatError(format!("unknown rule {:?}", name)))?;
+
+    let span = match field(object, "span")?.as_array().map(|span| span.as_slice()) {
+        Some([Value::Number(start), Value::Number(end)]) => match (start.as_u64(), end.as_u64()) {
+            (Some(start), Some(end)) => (start as usize, end as usize),
+            _ => return Err(DumpError::FormatError("span bounds must be positive integers".to_string()))
+        },
+        _ => return Err(DumpError::FormatError("span must be a [start, end] array".to_string()))
+    };
+
+    if let Some(content) = object.get("content") {
+        return match content.as_str() {
+            Some(content) => Ok(Token::Terminal(TerminalToken {
+                span,
+                rule,
+                content: content.to_string()
+            })),
+            None => Err(DumpError::FormatError("content must be a string".to_string()))
+        };
+    }
+
+    match field(object, "children")?.as_array() {
+        Some(children) => Ok(Token::NonTerminal(NonTerminalToken {
+            span,
+            rule,
+            subrules: children.iter().map(token_from_json).collect::<Result<Vec<Token>, DumpError>>()?
+        })),
+        None => Err(DumpError::FormatError("children must be an array".to_string()))
+    }
+
+}
+
+///
+/// Reads back the output of `tokens_to_json`.
+///
+pub fn tokens_from_json(json: & str) -> Result<RootToken, DumpError> {
+
+    let document: Value = serde_json::from_str(json).map_err(DumpError::JsonError)?;
+    let object = document.as_object().ok_or_else(|| DumpError::FormatError("expected an object".to_string()))?;
+
+    let source = field(object, "source")?.as_str().ok_or_else(|| DumpError::FormatError("source must be a string".to_string()))?;
+    let tokens = field(object, "tokens")?.as_array().ok_or_else(|| DumpError::FormatError("tokens must be an array".to_string()))?;
+
+    Ok(RootToken {
+        source: source.to_string(),
+        ast: tokens.iter().map(token_from_json).collect::<Result<Vec<Token>, DumpError>>()?
+    })
+
+}
+
+pub fn circuit_to_json(circuit: & Circuit) -> Value {
+    serde_json::to_value(circuit).expect("The typed AST is always serializable")
+}
+
+pub fn circuit_from_json(json: & str) -> Result<Circuit, DumpError> {
+    serde_json::from_str(json).map_err(DumpError::JsonError)
+}
+
+fn push_token_sexp(token: & Token, output: & mut std::string::String) {
+    match token {
+        Token::Terminal(terminal) => {
+            output.push_str(&format!("({:?} {} {} {})", terminal.rule, terminal.span.0, terminal.span.1, Value::from(terminal.content.clone())));
+        },
+        Token::NonTerminal(non_terminal) => {
+            output.push_str(&format!("({:?} {} {}", non_terminal.rule, non_terminal.span.0, non_terminal.span.1));
+            for subrule in &non_terminal.subrules {
+                output.push(' ');
+                push_token_sexp(subrule, output);
+            }
+            output.push(')');
+        }
+    }
+}
+
+///
+/// Compact form of the token tree, one list per token: `(Rule start end children...)`, terminals
+/// ending with their content as a string.
+///
+pub fn tokens_to_sexp(root: & RootToken) -> std::string::String {
+
+    let mut output = std::string::String::new();
+
+    for token in &root.ast {
+        push_token_sexp(token, & mut output);
+        output.push('\n');
+    }
+
+    output
+
+}
+
+fn push_value_sexp(value: & Value, output: & mut std::string::String) {
+    match value {
+        Value::Null => output.push_str("nil"),
+        Value::Bool(_) | Value::Number(_) | Value::String(_) => output.push_str(&value.to_string()),
+        Value::Array(values) => {
+            output.push('(');
+            push_values_sexp(values.iter(), output);
+            output.push(')');
+        },
+        Value::Object(object) => {
+            output.push('(');
+            push_fields_sexp(object, output);
+            output.push(')');
+        }
+    }
+}
+
+fn push_values_sexp<'a, I: Iterator<Item = &'a Value>>(values: I, output: & mut std::string::String) {
+    for (idx, value) in values.enumerate() {
+        if idx > 0 {
+            output.push(' ');
+        }
+        push_value_sexp(value, output);
+    }
+}
+
+///
+/// Enum variants, serialized as `{"Variant": content}`, become `(Variant content...)`, other
+/// objects `(:field value ...)`.
+///
+fn push_fields_sexp(object: & Map<std::string::String, Value>, output: & mut std::string::String) {
+
+    if object.len() == 1 {
+        let (name, content) = object.iter().next().unwrap();
+        if name.starts_with(|character: char| character.is_ascii_uppercase()) {
+            output.push_str(name);
+            match content {
+                Value::Object(fields) => {
+                    output.push(' ');
+                    push_fields_sexp(fields, output);
+                },
+                Value::Array(values) => {
+                    output.push(' ');
+                    push_values_sexp(values.iter(), output);
+                },
+                value => {
+                    output.push(' ');
+                    push_value_sexp(value, output);
+                }
+            }
+            return ;
+        }
+    }
+
+    for (idx, (name, value)) in object.iter().enumerate() {
+        if idx > 0 {
+            output.push(' ');
+        }
+        output.push_str(&format!(":{} ", name));
+        push_value_sexp(value, output);
+    }
+
+}
+
+///
+/// Compact form of the typed AST, one item per line.
+///
+pub fn circuit_to_sexp(circuit: & Circuit) -> std::string::String {
+
+    let mut output = std::string::String::new();
+
+    for item in &circuit.items {
+        push_value_sexp(&serde_json::to_value(item).expect("The typed AST is always serializable"), & mut output);
+        output.push('\n');
+    }
+
+    output
+
+}
+
+#[cfg(test)]
+mod dump_test {
+
+    use parser::ast;
+    use parser::dump;
+    use parser::matchers;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_tokens_roundtrip() {
+
+        let path = std::fs::canonicalize("./src/lib/parser/test_material/witness/num2bits.circom").expect("Invalid Path");
+        let file = ast::parse_file(&path).unwrap();
+
+        let json = dump::tokens_to_json(&file.root).to_string();
+        let root = dump::tokens_from_json(&json).unwrap();
+
+        expect_that!(&root, is(eq(file.root.clone())));
+
+        let sexp = dump::tokens_to_sexp(&file.root);
+        expect_that!(&sexp.starts_with("(Circuit 0 68 (IncludeStatement 0 36 (IncludeKW 0 8 \"include \") (IncludePathString 8 35 (FilesystemPath 9 34 \"../circuits/bitify.circom\")) (END_OF_LINE 35 36 \";\"))"), is(eq(true)));
+
+        match dump::tokens_from_json(r#"{"source": "", "tokens": [{"rule": "Unknown", "span": [0, 0], "children": []}]}"#) {
+            Err(dump::DumpError::FormatError(reason)) => {
+                expect_that!(&reason, is(eq("unknown rule \"Unknown\"".to_string())));
+            },
+            other => panic!("Unexpected result {:?}", other)
+        }
+
+    }
+
+    #[test]
+    fn test_circuit_roundtrip() {
+
+        let path = std::fs::canonicalize("./src/lib/parser/test_material/witness/matrix.circom").expect("Invalid Path");
+        let file = ast::parse_file(&path).unwrap();
+        let circuit = matchers::circuit::process_circuit(&file.root).unwrap();
+
+        let json = dump::circuit_to_json(&circuit).to_string();
+
+        expect_that!(&dump::circuit_from_json(&json).unwrap(), is(eq(circuit.clone())));
+
+        let sexp = dump::circuit_to_sexp(&circuit);
+
+        expect_that!(&sexp.lines().count(), is(eq(circuit.items.len())));
+        expect_that!(&sexp.contains("(Number :span ("), is(eq(true)));
+
+    }
+
+}
@@ -0,0 +1,26 @@
+use std::fmt;
+use parser::errors::common_displayer;
+
+///
+/// E301: Elaboration Error
+///
+/// The circuit is syntactically valid but cannot be instantiated: undefined symbol, non constant
+/// value where one is required, signal assigned twice ...
+///
+#[derive(Debug, Clone)]
+pub struct ElaborationError {
+    pub file_error: std::path::PathBuf,
+    pub source_error: std::string::String,
+    pub span_error: (usize, usize),
+
+    pub reason: std::string::String
+}
+
+impl fmt::Display for ElaborationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+
+        common_displayer::common_displayer(formatter, "ElaborationError", &301, &self.source_error, &self.span_error, &self.file_error)
+            .and(writeln!(formatter, "\t{}", self.reason))
+
+    }
+}
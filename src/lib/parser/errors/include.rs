@@ -19,7 +19,7 @@ impl fmt::Display for UnknownFileIncludeError {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
 
         common_displayer::common_displayer(formatter, "UnknownFileInclude", &201, &self.source_error, &self.span_error, &self.file_error)
-            .and(write!(formatter, "\tUnknown File: {:?}\n", self.invalid_file))
+            .and(writeln!(formatter, "\tUnknown File: {:?}", self.invalid_file))
 
 
     }
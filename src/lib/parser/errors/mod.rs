@@ -1,33 +1,97 @@
 pub mod include;
 pub mod syntax;
+pub mod elaboration;
 pub mod common_displayer;
 
+use pest::error::InputLocation;
+
 use parser::ast::ParseError;
+use parser::matchers::MatchError;
 use parser::errors::include::UnknownFileIncludeError;
 
 #[derive(Debug)]
 pub enum CompileError {
     UnknwonFileIncludeError(include::UnknownFileIncludeError),
-    SyntaxError(syntax::SyntaxError)
+    SyntaxError(syntax::SyntaxError),
+    ElaborationError(elaboration::ElaborationError)
+}
+
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CompileError::UnknwonFileIncludeError(error) => error.fmt(formatter),
+            CompileError::SyntaxError(error) => error.fmt(formatter),
+            CompileError::ElaborationError(error) => error.fmt(formatter)
+        }
+    }
+}
+
+impl CompileError {
+
+    ///
+    /// Error code, as printed in the `E101` form.
+    ///
+    pub fn code(&self) -> u32 {
+        match self {
+            CompileError::SyntaxError(_) => 101,
+            CompileError::UnknwonFileIncludeError(_) => 201,
+            CompileError::ElaborationError(_) => 301
+        }
+    }
+
+    pub fn file(&self) -> & std::path::Path {
+        match self {
+            CompileError::UnknwonFileIncludeError(error) => &error.file_error,
+            CompileError::SyntaxError(error) => &error.file_error,
+            CompileError::ElaborationError(error) => &error.file_error
+        }
+    }
+
+    pub fn span(&self) -> (usize, usize) {
+        match self {
+            CompileError::UnknwonFileIncludeError(error) => error.span_error,
+            CompileError::SyntaxError(error) => error.span_error,
+            CompileError::ElaborationError(error) => error.span_error
+        }
+    }
+
+    ///
+    /// One line description of the error, without its location.
+    ///
+    pub fn message(&self) -> std::string::String {
+        match self {
+            CompileError::UnknwonFileIncludeError(error) => format!("Cannot read {}: {}", error.invalid_file.display(), error.source_error),
+            // The pest report ends with "= expected ..." after the source excerpt
+            CompileError::SyntaxError(error) => match error.source_error.lines().last() {
+                Some(line) => line.trim().trim_start_matches("= ").to_string(),
+                None => "Syntax error".to_string()
+            },
+            CompileError::ElaborationError(error) => error.reason.clone()
+        }
+    }
+
 }
 
-pub fn from_pest_parsing(file: & std::path::PathBuf, err: & ParseError) -> CompileError {
+pub fn from_pest_parsing(file: & std::path::Path, err: & ParseError) -> CompileError {
 
     match err {
-        ParseError::IOError(_io) => {
+        ParseError::IOError(io) => {
             CompileError::UnknwonFileIncludeError(UnknownFileIncludeError {
-                file_error: file.clone(),
-                source_error: "TODO".to_string(),
-                span_error: (10, 12),
+                file_error: file.to_path_buf(),
+                source_error: io.to_string(),
+                span_error: (0, 0),
 
-                invalid_file: file.clone()
+                invalid_file: file.to_path_buf()
             })
         },
-        ParseError::PestError(_pest) => {
+        ParseError::PestError(pest) => {
             CompileError::SyntaxError(syntax::SyntaxError {
-                file_error: file.clone(),
-                source_error: "TODO".to_string(),
-                span_error: (10, 12)
+                file_error: file.to_path_buf(),
+                source_error: pest.to_string().replace('\n', "\n\t"),
+                span_error: match pest.location {
+                    InputLocation::Pos(position) => (position, position),
+                    InputLocation::Span(span) => span
+                }
             })
         }
     }
@@ -35,3 +99,14 @@ pub fn from_pest_parsing(file: & std::path::PathBuf, err: & ParseError) -> Compi
 }
 
 
+
+///
+/// Reports a token tree the matchers could not turn into a typed AST as a syntax error.
+///
+pub fn from_matching(file: & std::path::Path, err: & MatchError) -> CompileError {
+    CompileError::SyntaxError(syntax::SyntaxError {
+        file_error: file.to_path_buf(),
+        source_error: err.reason.clone(),
+        span_error: err.span
+    })
+}
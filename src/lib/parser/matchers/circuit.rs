@@ -0,0 +1,125 @@
+use parser::ast::tokens::*;
+use parser::ast::Rule;
+use parser::typed_ast::*;
+use parser::matchers::MatchError;
+use parser::matchers::expression::*;
+use parser::matchers::statement::*;
+use parser::matchers::include_statement;
+
+///
+/// Processes a `Parameters` token, which is a terminal when the list is empty.
+///
+fn process_parameters(parameters: & Token) -> Result<Vec<std::string::String>, MatchError> {
+    significant_subrules(parameters).into_iter()
+        .map(|parameter| match parameter {
+            Token::Terminal(TerminalToken { rule: Rule::VariableName, content, .. }) => Ok(content.trim().to_string()),
+            _ => invalid(parameter, "Parameters")
+        })
+        .collect()
+}
+
+pub fn process_function_block(function_block: & Token) -> Result<Function, MatchError> {
+    match significant_subrules(function_block).as_slice() {
+        [
+        Token::Terminal(TerminalToken { rule: Rule::FunctionKW, .. }),
+        name,
+        parameters,
+        body
+        ] => Ok(Function {
+            span: token_span(function_block),
+            name: token_text(name),
+            parameters: process_parameters(parameters)?,
+            body: process_body(body)?
+        }),
+        _ => invalid(function_block, "FunctionBlock")
+    }
+}
+
+pub fn process_template_block(template_block: & Token) -> Result<Template, MatchError> {
+    match significant_subrules(template_block).as_slice() {
+        [
+        Token::Terminal(TerminalToken { rule: Rule::TemplateKW, .. }),
+        name,
+        parameters,
+        body
+        ] => Ok(Template {
+            span: token_span(template_block),
+            name: token_text(name),
+            parameters: process_parameters(parameters)?,
+            body: process_body(body)?
+        }),
+        _ => invalid(template_block, "TemplateBlock")
+    }
+}
+
+///
+/// Builds the typed AST of a file from its `Circuit` token.
+///
+pub fn process_circuit(root: & RootToken) -> Result<Circuit, MatchError> {
+
+    let circuit = match root.ast.as_slice() {
+        [circuit @ Token::NonTerminal(NonTerminalToken { rule: Rule::Circuit, .. })] => strip_comments(circuit, &root.source),
+        _ => return Err(MatchError { span: (0, root.source.len()), reason: "Should find Circuit at root".to_string() })
+    };
+
+    let mut items: Vec<Item> = Vec::new();
+
+    for token in significant_subrules(&circuit) {
+        match token {
+            Token::NonTerminal(include @ NonTerminalToken { rule: Rule::IncludeStatement, .. }) => items.push(Item::Include {
+                span: include.span,
+                path: include_statement::process_include_statement(include)?.to_string()
+            }),
+            Token::NonTerminal(NonTerminalToken { rule: Rule::FunctionBlock, .. }) => items.push(Item::Function(process_function_block(token)?)),
+            Token::NonTerminal(NonTerminalToken { rule: Rule::TemplateBlock, .. }) => items.push(Item::Template(process_template_block(token)?)),
+            Token::NonTerminal(NonTerminalToken { rule: Rule::DeclarationStatement, .. }) => items.push(Item::Declaration(process_declaration(token)?)),
+            Token::Terminal(TerminalToken { rule: Rule::END_OF_LINE, .. }) |
+            Token::Terminal(TerminalToken { rule: Rule::EOI, .. }) => {},
+            _ => return invalid(token, "Circuit")
+        }
+    }
+
+    Ok(Circuit {
+        items
+    })
+
+}
+
+#[cfg(test)]
+mod circuit_test {
+
+    use parser::ast;
+    use parser::matchers::circuit::process_circuit;
+    use parser::typed_ast::Item;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_parameters_with_comments() {
+
+        let root = ast::parse_source("template A(n /* c */, m) { signal input a; }\nfunction f(/* none */) { return 1; }", None).unwrap();
+        let circuit = process_circuit(&root).unwrap();
+
+        expect_that!(&circuit.templates().next().unwrap().parameters, is(eq(vec!("n".to_string(), "m".to_string()))));
+        expect_that!(&circuit.functions().next().unwrap().parameters.is_empty(), is(eq(true)));
+
+    }
+
+    #[test]
+    fn test_include_with_comment() {
+
+        let root = ast::parse_source("include /* c */ \"b.circom\";", None).unwrap();
+        let circuit = process_circuit(&root).unwrap();
+
+        let paths: Vec<&str> = circuit.items.iter()
+            .filter_map(|item| match item {
+                Item::Include { path, .. } => Some(path.as_str()),
+                _ => None
+            })
+            .collect();
+
+        expect_that!(&paths, is(eq(vec!("b.circom"))));
+
+    }
+
+}
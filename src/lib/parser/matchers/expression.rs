@@ -0,0 +1,721 @@
+use parser::ast::tokens::*;
+use parser::ast::Rule;
+use parser::typed_ast::*;
+use parser::matchers::MatchError;
+use field::bigint::U256;
+
+pub fn token_rule(token: & Token) -> Rule {
+    match token {
+        Token::Terminal(terminal) => terminal.rule,
+        Token::NonTerminal(non_terminal) => non_terminal.rule
+    }
+}
+
+pub fn token_span(token: & Token) -> Span {
+    match token {
+        Token::Terminal(terminal) => terminal.span,
+        Token::NonTerminal(non_terminal) => non_terminal.span
+    }
+}
+
+///
+/// Concatenation of all the terminal values found under the token.
+///
+pub fn token_text(token: & Token) -> std::string::String {
+    match token {
+        Token::Terminal(terminal) => terminal.content.trim().to_string(),
+        Token::NonTerminal(non_terminal) => non_terminal.subrules.iter().map(token_text).collect()
+    }
+}
+
+///
+/// Sub tokens of a token, comments excluded. Terminal tokens have none.
+///
+pub fn significant_subrules(token: & Token) -> Vec<& Token> {
+    match token {
+        Token::Terminal(_) => Vec::new(),
+        Token::NonTerminal(non_terminal) => non_terminal.subrules.iter()
+            .filter(|sub_token| token_rule(sub_token) != Rule::COMMENT)
+            .collect()
+    }
+}
+
+///
+/// Copy of a token without its comments, however deep they are.
+///
+/// Comments can appear inside rules the matchers expect to be terminals, `n /* c */` being a
+/// `VariableName` and `== /* c */` an `E_11_RelationalEqualityOperator`. Such tokens only have
+/// comments as sub tokens and their own text is lost: they are turned back into terminals, whose
+/// content is their source with the comments cut out.
+///
+pub fn strip_comments(token: & Token, source: & str) -> Token {
+    match token {
+        Token::Terminal(terminal) => Token::Terminal(terminal.clone()),
+        Token::NonTerminal(non_terminal) if non_terminal.subrules.iter().all(|sub_token| token_rule(sub_token) == Rule::COMMENT) => {
+
+            let mut content = std::string::String::new();
+            let mut position = non_terminal.span.0;
+
+            for comment in &non_terminal.subrules {
+                let (start, end) = token_span(comment);
+                content.push_str(&source[position..start]);
+                position = end;
+            }
+            content.push_str(&source[position..non_terminal.span.1]);
+
+            Token::Terminal(TerminalToken {
+                span: non_terminal.span,
+                rule: non_terminal.rule,
+                content
+            })
+
+        },
+        Token::NonTerminal(non_terminal) => Token::NonTerminal(NonTerminalToken {
+            span: non_terminal.span,
+            rule: non_terminal.rule,
+            subrules: non_terminal.subrules.iter()
+                .filter(|sub_token| token_rule(sub_token) != Rule::COMMENT)
+                .map(|sub_token| strip_comments(sub_token, source))
+                .collect()
+        })
+    }
+}
+
+///
+/// Error for a token the matchers do not expect where it is, `name` describing what was expected.
+///
+pub fn invalid<T>(token: & Token, name: & str) -> Result<T, MatchError> {
+    Err(MatchError {
+        span: token_span(token),
+        reason: format!("Invalid {} AST Token", name)
+    })
+}
+
+///
+/// Parses a number literal. Values that do not fit in 256 bits wrap around.
+///
+fn parse_number(digits: & str, radix: u32) -> Option<U256> {
+
+    let mut result = U256::ZERO;
+
+    for character in digits.chars() {
+        let digit = character.to_digit(radix)?;
+        result = result.overflowing_mul_add_u64(radix as u64, digit as u64).0;
+    }
+
+    Some(result)
+
+}
+
+struct Cursor<'a> {
+    expression: &'a Token,
+    tokens: Vec<&'a Token>,
+    position: usize
+}
+
+impl<'a> Cursor<'a> {
+
+    fn peek_rule(&self) -> Option<Rule> {
+        self.tokens.get(self.position).map(|token| token_rule(token))
+    }
+
+    fn next(& mut self) -> Result<&'a Token, MatchError> {
+        let token = match self.tokens.get(self.position) {
+            Some(token) => *token,
+            None => return Err(MatchError {
+                span: (token_span(self.expression).1, token_span(self.expression).1),
+                reason: "Unexpected end of Expression AST Token".to_string()
+            })
+        };
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn next_if(& mut self, rule: Rule) -> Option<&'a Token> {
+        if self.peek_rule() == Some(rule) {
+            self.position += 1;
+            Some(self.tokens[self.position - 1])
+        } else {
+            None
+        }
+    }
+
+}
+
+fn join(left: Span, right: Span) -> Span {
+    (left.0, right.1)
+}
+
+fn binary_rule(level: usize) -> Rule {
+    match level {
+        6 => Rule::E_6_LogicalOrOperator,
+        7 => Rule::E_7_LogicalAndOperator,
+        8 => Rule::E_8_BitwiseOrOperator,
+        9 => Rule::E_9_BitwiseXorOperator,
+        10 => Rule::E_10_BitwiseAndOperator,
+        11 => Rule::E_11_RelationalEqualityOperator,
+        12 => Rule::E_12_RelationalOrderingOperator,
+        13 => Rule::E_13_BitwiseShiftOperator,
+        14 => Rule::E_14_SumOperator,
+        15 => Rule::E_15_ProductOperator,
+        16 => Rule::E_16_ExponentialOperator,
+        _ => panic!("Invalid operator precedence level {}", level)
+    }
+}
+
+fn invalid_operator<T>(operator: & Token, kind: & str) -> Result<T, MatchError> {
+    Err(MatchError {
+        span: token_span(operator),
+        reason: format!("Invalid {} operator {}", kind, token_text(operator))
+    })
+}
+
+fn binary_operator(operator: & Token) -> Result<BinaryOperator, MatchError> {
+    Ok(match token_text(operator).as_str() {
+        "||" => BinaryOperator::Or,
+        "&&" => BinaryOperator::And,
+        "|" => BinaryOperator::BitOr,
+        "^" => BinaryOperator::BitXor,
+        "&" => BinaryOperator::BitAnd,
+        "==" => BinaryOperator::Equal,
+        "!=" => BinaryOperator::NotEqual,
+        "<" => BinaryOperator::Lesser,
+        "<=" => BinaryOperator::LesserEqual,
+        ">" => BinaryOperator::Greater,
+        ">=" => BinaryOperator::GreaterEqual,
+        "<<" => BinaryOperator::ShiftLeft,
+        ">>" => BinaryOperator::ShiftRight,
+        "+" => BinaryOperator::Add,
+        "-" => BinaryOperator::Sub,
+        "*" => BinaryOperator::Mul,
+        "/" => BinaryOperator::Div,
+        "\\" => BinaryOperator::IntDiv,
+        "%" => BinaryOperator::Mod,
+        "**" => BinaryOperator::Pow,
+        _ => return invalid_operator(operator, "binary")
+    })
+}
+
+fn assignment_operator(operator: & Token) -> Result<AssignmentOperator, MatchError> {
+    Ok(match token_text(operator).as_str() {
+        "=" => AssignmentOperator::Assign,
+        "+=" => AssignmentOperator::Add,
+        "-=" => AssignmentOperator::Sub,
+        "*=" => AssignmentOperator::Mul,
+        "/=" => AssignmentOperator::Div,
+        "%=" => AssignmentOperator::Mod,
+        "<<=" => AssignmentOperator::ShiftLeft,
+        ">>=" => AssignmentOperator::ShiftRight,
+        "&=" => AssignmentOperator::BitAnd,
+        "^=" => AssignmentOperator::BitXor,
+        "|=" => AssignmentOperator::BitOr,
+        _ => return invalid_operator(operator, "assignment")
+    })
+}
+
+fn prefix_operator(operator: & Token) -> Result<PrefixOperator, MatchError> {
+    Ok(match token_text(operator).as_str() {
+        "!" => PrefixOperator::Not,
+        "~" => PrefixOperator::Complement,
+        "++" => PrefixOperator::Increment,
+        "--" => PrefixOperator::Decrement,
+        "+" => PrefixOperator::Plus,
+        "-" => PrefixOperator::Minus,
+        _ => return invalid_operator(operator, "prefix")
+    })
+}
+
+fn postfix_operator(operator: & Token) -> Result<PostfixOperator, MatchError> {
+    Ok(match token_text(operator).as_str() {
+        "++" => PostfixOperator::Increment,
+        "--" => PostfixOperator::Decrement,
+        _ => return invalid_operator(operator, "postfix")
+    })
+}
+
+fn signal_operator(operator: & Token, constrained: & str, unconstrained: & str) -> Result<SignalOperator, MatchError> {
+    match token_text(operator) {
+        ref text if text == constrained => Ok(SignalOperator::ConstrainedAssign),
+        ref text if text == unconstrained => Ok(SignalOperator::Assign),
+        _ => invalid_operator(operator, "signal")
+    }
+}
+
+///
+/// Splits a comma separated expression, used for arguments and array elements.
+///
+fn flatten_sequence(expression: Expression) -> Vec<Expression> {
+    match expression {
+        Expression::Sequence { expressions, .. } => expressions,
+        other => vec!(other)
+    }
+}
+
+///
+/// Processes an `E_20_ArgumentsContent` token into a list of expressions.
+///
+pub fn process_arguments(arguments: & Token) -> Result<Vec<Expression>, MatchError> {
+
+    let mut result: Vec<Expression> = Vec::new();
+
+    for token in significant_subrules(arguments) {
+        match token_rule(token) {
+            Rule::Expression => result.extend(flatten_sequence(process_expression(token)?)),
+            _ => return invalid(token, "E_20_ArgumentsContent")
+        }
+    }
+
+    Ok(result)
+
+}
+
+fn process_value(value: & Token) -> Result<Expression, MatchError> {
+
+    let span = token_span(value);
+    let inner = significant_subrules(value);
+
+    if inner.len() != 1 {
+        return invalid(value, "E_Value");
+    }
+
+    Ok(match inner[0] {
+        token @ Token::Terminal(TerminalToken { rule: Rule::E_Decimal, content, .. }) => Expression::Number {
+            span,
+            value: match parse_number(content.trim(), 10) {
+                Some(value) => value,
+                None => return invalid(token, "E_Decimal")
+            }
+        },
+        token @ Token::Terminal(TerminalToken { rule: Rule::E_Hexadecimal, content, .. }) => Expression::Number {
+            span,
+            value: match content.trim().get(2..).and_then(|digits| parse_number(digits, 16)) {
+                Some(value) => value,
+                None => return invalid(token, "E_Hexadecimal")
+            }
+        },
+        Token::Terminal(TerminalToken { rule: Rule::E_VariableName, content, .. }) => Expression::Variable {
+            span,
+            name: content.trim().to_string()
+        },
+        token if token_rule(token) == Rule::E_Brackets => {
+            match significant_subrules(token).iter().find(|sub_token| token_rule(sub_token) == Rule::Expression) {
+                Some(expression) => process_expression(expression)?,
+                None => Expression::Sequence { span, expressions: Vec::new() }
+            }
+        },
+        token if token_rule(token) == Rule::E_Array => {
+            Expression::Array {
+                span,
+                elements: match significant_subrules(token).iter().find(|sub_token| token_rule(sub_token) == Rule::Expression) {
+                    Some(expression) => flatten_sequence(process_expression(expression)?),
+                    None => Vec::new()
+                }
+            }
+        },
+        token => return invalid(token, "E_Value")
+    })
+
+}
+
+fn parse_braces(cursor: & mut Cursor, mut base: Expression) -> Result<Expression, MatchError> {
+
+    while let Some(open) = cursor.next_if(Rule::E_20_BracedOperatorOpen) {
+
+        let arguments = match cursor.next_if(Rule::E_20_ArgumentsContent) {
+            Some(arguments) => process_arguments(arguments)?,
+            None => Vec::new()
+        };
+        let close = cursor.next()?;
+        if token_rule(close) != Rule::E_20_BracedOperatorClose {
+            return invalid(close, "E_20_BracedOp");
+        }
+
+        let span = join(base.span(), token_span(close));
+
+        base = if token_text(open) == "(" {
+            Expression::Call {
+                span,
+                callee: Box::new(base),
+                arguments
+            }
+        } else {
+            Expression::Index {
+                span,
+                target: Box::new(base),
+                index: Box::new(match arguments.len() {
+                    1 => arguments.into_iter().next().unwrap(),
+                    _ => Expression::Sequence { span: token_span(open), expressions: arguments }
+                })
+            }
+        };
+
+    }
+
+    Ok(base)
+
+}
+
+fn parse_member(cursor: & mut Cursor) -> Result<Expression, MatchError> {
+
+    let value = cursor.next()?;
+    if token_rule(value) != Rule::E_Value {
+        return invalid(value, "Expression, expected E_Value,");
+    }
+
+    let mut result = parse_braces(cursor, process_value(value)?)?;
+
+    while cursor.next_if(Rule::E_19_MemberAccessOperator).is_some() {
+
+        let member = cursor.next()?;
+        if token_rule(member) != Rule::E_Value {
+            return invalid(member, "E_19_MemberAccessOp");
+        }
+
+        result = Expression::Member {
+            span: join(result.span(), token_span(member)),
+            target: Box::new(result),
+            member: token_text(member)
+        };
+        result = parse_braces(cursor, result)?;
+
+    }
+
+    Ok(result)
+
+}
+
+fn parse_postfix(cursor: & mut Cursor) -> Result<Expression, MatchError> {
+
+    let mut operand = parse_member(cursor)?;
+
+    while let Some(operator) = cursor.next_if(Rule::E_18_PostfixOperator) {
+        operand = Expression::Postfix {
+            span: join(operand.span(), token_span(operator)),
+            operator: postfix_operator(operator)?,
+            operand: Box::new(operand)
+        };
+    }
+
+    Ok(operand)
+
+}
+
+fn parse_prefix(cursor: & mut Cursor) -> Result<Expression, MatchError> {
+
+    let mut operators: Vec<& Token> = Vec::new();
+
+    while let Some(operator) = cursor.next_if(Rule::E_17_PrefixOperator) {
+        operators.push(operator);
+    }
+
+    let mut operand = parse_postfix(cursor)?;
+
+    for operator in operators.into_iter().rev() {
+        operand = Expression::Prefix {
+            span: join(token_span(operator), operand.span()),
+            operator: prefix_operator(operator)?,
+            operand: Box::new(operand)
+        };
+    }
+
+    Ok(operand)
+
+}
+
+fn parse_binary(cursor: & mut Cursor, level: usize) -> Result<Expression, MatchError> {
+
+    if level > 16 {
+        return parse_prefix(cursor);
+    }
+
+    let mut left = parse_binary(cursor, level + 1)?;
+
+    while let Some(operator) = cursor.next_if(binary_rule(level)) {
+        let right = parse_binary(cursor, level + 1)?;
+        left = Expression::Binary {
+            span: join(left.span(), right.span()),
+            operator: binary_operator(operator)?,
+            left: Box::new(left),
+            right: Box::new(right)
+        };
+    }
+
+    Ok(left)
+
+}
+
+fn parse_ternary(cursor: & mut Cursor) -> Result<Expression, MatchError> {
+
+    let condition = parse_binary(cursor, 6)?;
+
+    let first = match cursor.next_if(Rule::E_5_TernaryFirstOperator) {
+        Some(first) => first,
+        None => return Ok(condition)
+    };
+
+    let if_true = parse_binary(cursor, 6)?;
+    if cursor.next_if(Rule::E_5_TernarySecondOperator).is_none() {
+        return invalid(first, "E_5_TernaryOp");
+    }
+    let if_false = parse_binary(cursor, 6)?;
+
+    Ok(Expression::Ternary {
+        span: join(condition.span(), if_false.span()),
+        condition: Box::new(condition),
+        if_true: Box::new(if_true),
+        if_false: Box::new(if_false)
+    })
+
+}
+
+fn parse_assignment(cursor: & mut Cursor) -> Result<Expression, MatchError> {
+
+    let target = parse_ternary(cursor)?;
+
+    Ok(match cursor.next_if(Rule::E_4_AssignmentOperator) {
+        Some(operator) => {
+            let value = parse_ternary(cursor)?;
+            Expression::Assignment {
+                span: join(target.span(), value.span()),
+                operator: assignment_operator(operator)?,
+                target: Box::new(target),
+                value: Box::new(value)
+            }
+        },
+        None => target
+    })
+
+}
+
+fn parse_signal_right_hand(cursor: & mut Cursor) -> Result<Expression, MatchError> {
+
+    let mut value = parse_assignment(cursor)?;
+
+    while let Some(operator) = cursor.next_if(Rule::E_3_SignalRightHandOperator) {
+        let target = parse_assignment(cursor)?;
+        value = Expression::SignalAssignment {
+            span: join(value.span(), target.span()),
+            operator: signal_operator(operator, "==>", "-->")?,
+            reversed: true,
+            target: Box::new(target),
+            value: Box::new(value)
+        };
+    }
+
+    Ok(value)
+
+}
+
+fn parse_signal_left_hand(cursor: & mut Cursor) -> Result<Expression, MatchError> {
+
+    let target = parse_signal_right_hand(cursor)?;
+
+    Ok(match cursor.next_if(Rule::E_2_SignalLeftHandOperator) {
+        Some(operator) => {
+            let value = parse_signal_right_hand(cursor)?;
+            Expression::SignalAssignment {
+                span: join(target.span(), value.span()),
+                operator: signal_operator(operator, "<==", "<--")?,
+                reversed: false,
+                target: Box::new(target),
+                value: Box::new(value)
+            }
+        },
+        None => target
+    })
+
+}
+
+fn parse_constraint(cursor: & mut Cursor) -> Result<Expression, MatchError> {
+
+    let mut left = parse_signal_left_hand(cursor)?;
+
+    while cursor.next_if(Rule::E_1_SignalAssertionConstraintOperator).is_some() {
+        let right = parse_signal_left_hand(cursor)?;
+        left = Expression::Constraint {
+            span: join(left.span(), right.span()),
+            left: Box::new(left),
+            right: Box::new(right)
+        };
+    }
+
+    Ok(left)
+
+}
+
+fn parse_sequence(cursor: & mut Cursor) -> Result<Expression, MatchError> {
+
+    let first = parse_constraint(cursor)?;
+
+    if cursor.peek_rule() != Some(Rule::E_0_CommaOperator) {
+        return Ok(first);
+    }
+
+    let mut expressions = vec!(first);
+
+    while cursor.next_if(Rule::E_0_CommaOperator).is_some() {
+        expressions.push(parse_constraint(cursor)?);
+    }
+
+    Ok(Expression::Sequence {
+        span: join(expressions[0].span(), expressions[expressions.len() - 1].span()),
+        expressions
+    })
+
+}
+
+///
+/// Processes an `Expression` token. The flat list of operands and operators is turned into a tree
+/// following the precedence levels of the grammar (`E_0` to `E_20`).
+///
+pub fn process_expression(expression: & Token) -> Result<Expression, MatchError> {
+
+    if token_rule(expression) != Rule::Expression {
+        return invalid(expression, "Expression");
+    }
+
+    let mut cursor = Cursor {
+        expression,
+        tokens: significant_subrules(expression),
+        position: 0
+    };
+
+    let result = parse_sequence(& mut cursor)?;
+
+    if let Some(unexpected) = cursor.tokens.get(cursor.position) {
+        return invalid(unexpected, "Expression");
+    }
+
+    Ok(result)
+
+}
+
+#[cfg(test)]
+mod expression_test {
+
+    use serde_json::Value;
+
+    use parser::ast;
+    use parser::ast::Rule;
+    use parser::ast::tokens::*;
+    use parser::dump;
+    use parser::matchers::MatchError;
+    use parser::matchers::circuit::process_circuit;
+
+    use galvanic_assert::matchers::*;
+
+    fn without_spans(value: Value) -> Value {
+        match value {
+            Value::Object(object) => Value::Object(object.into_iter()
+                .filter(|(name, _)| name != "span")
+                .map(|(name, value)| (name, without_spans(value)))
+                .collect()),
+            Value::Array(values) => Value::Array(values.into_iter().map(without_spans).collect()),
+            value => value
+        }
+    }
+
+    fn typed_ast(source: & str) -> Value {
+        let root = ast::parse_source(source, None).expect("Source does not parse");
+        without_spans(dump::circuit_to_json(&process_circuit(&root).unwrap()))
+    }
+
+    // Adds a `/* c */` comment after every operator and comma of a source, comments excluded
+    fn comment_operators(source: & str) -> std::string::String {
+
+        let characters: Vec<char> = source.chars().collect();
+        let mut result = std::string::String::new();
+        let mut position = 0;
+
+        while position < characters.len() {
+
+            let rest: std::string::String = characters[position..].iter().take(2).collect();
+
+            if rest == "//" || rest == "/*" {
+                let end = if rest == "//" { "\n" } else { "*/" };
+                let text: std::string::String = characters[position..].iter().collect();
+                let length = text.find(end).map(|index| index + end.len()).unwrap_or(text.len());
+                result.push_str(&text[..length]);
+                position += text[..length].chars().count();
+                continue ;
+            }
+
+            result.push(characters[position]);
+
+            if "=,+-*<>&|".contains(characters[position]) && characters.get(position + 1).map(|next| next.is_whitespace()).unwrap_or(false) {
+                result.push_str(" /* c */");
+            }
+
+            position += 1;
+
+        }
+
+        result
+
+    }
+
+    #[test]
+    fn test_operators_with_comments() {
+
+        let commented = "template A() { signal input a; signal output b; var x; x = /*c*/ IsZero(); x += /*c*/ 1; b <== a == /*c*/ 2; b <== a * /* k */ 2; b === a /* q */; b <== (/*c*/) + [/*c*/]; }";
+        let plain = "template A() { signal input a; signal output b; var x; x = IsZero(); x += 1; b <== a == 2; b <== a * 2; b === a; b <== () + []; }";
+
+        expect_that!(&typed_ast(commented), is(eq(typed_ast(plain))));
+
+    }
+
+    #[test]
+    fn test_circuits_with_comments() {
+
+        let files = vec!(
+            "./src/lib/parser/test_material/circuits/bitify.circom",
+            "./src/lib/parser/test_material/witness/operators.circom",
+            "./src/lib/parser/test_material/circuits/sha256/sha256compression.circom"
+        );
+
+        for file in files {
+            let source = std::fs::read_to_string(file).unwrap();
+            let commented = comment_operators(&source);
+            expect_that!(&(commented.matches("/* c */").count() > 10), is(eq(true)));
+            expect_that!(&typed_ast(&commented), is(eq(typed_ast(&source))));
+        }
+
+    }
+
+    // Replaces the content of the terminals of the given rule, the way a grammar out of sync with the matchers would
+    fn replace_terminals(token: & mut Token, rule: Rule, content: & str) {
+        match token {
+            Token::Terminal(terminal) => if terminal.rule == rule {
+                terminal.content = content.to_string();
+            },
+            Token::NonTerminal(non_terminal) => for subrule in & mut non_terminal.subrules {
+                replace_terminals(subrule, rule, content);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_operator() {
+
+        let mut root = ast::parse_source("template A() { signal input a; signal output b; b <== a * 2; }", None).unwrap();
+        for token in & mut root.ast {
+            replace_terminals(token, Rule::E_15_ProductOperator, "@");
+        }
+
+        expect_that!(&process_circuit(&root), is(eq(Err(MatchError { span: (56, 57), reason: "Invalid binary operator @".to_string() }))));
+
+    }
+
+    #[test]
+    fn test_unknown_number() {
+
+        let mut root = ast::parse_source("function f() { return 0x1f; }", None).unwrap();
+        for token in & mut root.ast {
+            replace_terminals(token, Rule::E_Hexadecimal, "0xg");
+        }
+
+        expect_that!(&process_circuit(&root), is(eq(Err(MatchError { span: (22, 26), reason: "Invalid E_Hexadecimal AST Token".to_string() }))));
+
+    }
+
+}
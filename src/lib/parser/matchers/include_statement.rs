@@ -1,20 +1,25 @@
 use parser::ast::tokens::*;
 use parser::ast::Rule;
+use parser::matchers::MatchError;
 
-pub fn process_include_path_string(include_path_string: & NonTerminalToken) -> & str {
+pub fn process_include_path_string(include_path_string: & NonTerminalToken) -> Result<& str, MatchError> {
 
     match include_path_string.subrules.as_slice() {
         [
         Token::Terminal(TerminalToken{rule: Rule::FilesystemPath, content, ..})
-        ] => content,
-        _ => panic!("Invalud IncludePathString AST Token")
+        ] => Ok(content),
+        _ => Err(MatchError { span: include_path_string.span, reason: "Invalid IncludePathString AST Token".to_string() })
     }
 
 }
 
-pub fn process_include_statement(include_statement: & NonTerminalToken) -> & str{
+pub fn process_include_statement(include_statement: & NonTerminalToken) -> Result<& str, MatchError> {
 
-    match include_statement.subrules.as_slice() {
+    let subrules: Vec<& Token> = include_statement.subrules.iter()
+        .filter(|token| !matches!(token, Token::Terminal(TerminalToken { rule: Rule::COMMENT, .. })))
+        .collect();
+
+    match subrules.as_slice() {
         [
         Token::Terminal(TerminalToken {rule: Rule::IncludeKW, ..}),
         Token::NonTerminal( ips @
@@ -24,10 +29,9 @@ pub fn process_include_statement(include_statement: & NonTerminalToken) -> & str
                 ..
             }
         ),
-        Token::Terminal(TerminalToken {rule: Rule::END_OF_LINE, ..})
+        ..
         ] => process_include_path_string(ips),
-        _ => panic!("Invalid IncludeStatement AST Token")
+        _ => Err(MatchError { span: include_statement.span, reason: "Invalid IncludeStatement AST Token".to_string() })
     }
 
 }
-
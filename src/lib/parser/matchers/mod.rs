@@ -1,2 +1,22 @@
 pub mod include_statement;
+pub mod expression;
+pub mod statement;
+pub mod circuit;
 
+use parser::typed_ast::Span;
+
+///
+/// Token tree the matchers cannot turn into a typed AST. Sources the grammar accepts are expected
+/// to always match, such an error pointing at a disagreement between the two.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchError {
+    pub span: Span,
+    pub reason: std::string::String
+}
+
+impl std::fmt::Display for MatchError {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "{} at {}..{}", self.reason, self.span.0, self.span.1)
+    }
+}
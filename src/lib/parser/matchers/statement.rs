@@ -0,0 +1,329 @@
+use parser::ast::tokens::*;
+use parser::ast::Rule;
+use parser::typed_ast::*;
+use parser::matchers::MatchError;
+use parser::matchers::expression::*;
+
+fn process_signal_declaration_kw(keyword: & Token) -> Result<DeclarationKind, MatchError> {
+
+    let mut private = false;
+    let mut direction = SignalDirection::Intermediate;
+
+    for token in significant_subrules(keyword) {
+        match (token_rule(token), token_text(token).as_str()) {
+            (Rule::SignalDeclarationMainKW, _) => {},
+            (Rule::SignalDeclarationVisibilityKW, "private") => private = true,
+            (Rule::SignalDeclarationConnectionKW, "input") => direction = SignalDirection::Input,
+            (Rule::SignalDeclarationConnectionKW, "output") => direction = SignalDirection::Output,
+            (Rule::SignalDeclarationVisibilityKW, "") | (Rule::SignalDeclarationConnectionKW, "") => {},
+            _ => return invalid(token, "SignalDeclarationKW")
+        }
+    }
+
+    Ok(DeclarationKind::Signal {
+        private,
+        direction
+    })
+
+}
+
+fn process_array_declaration(array_declaration: & Token) -> Result<Expression, MatchError> {
+    match significant_subrules(array_declaration).as_slice() {
+        [
+        Token::Terminal(TerminalToken { rule: Rule::ArrayDeclarationOperatorOpen, .. }),
+        expression,
+        Token::Terminal(TerminalToken { rule: Rule::ArrayDeclarationOperatorClose, .. })
+        ] => process_expression(expression),
+        _ => invalid(array_declaration, "ArrayDeclaration")
+    }
+}
+
+fn process_typed_component_declaration(span: Span, declaration: & Token) -> Result<Declaration, MatchError> {
+
+    let tokens = significant_subrules(declaration);
+
+    let template = match tokens.get(1) {
+        Some(token) if token_rule(token) == Rule::E_VariableName => Expression::Variable {
+            span: token_span(token),
+            name: token_text(token)
+        },
+        _ => return invalid(declaration, "TypedComponentDeclaration")
+    };
+
+    let mut arguments: Vec<Expression> = Vec::new();
+    let mut name: Option<std::string::String> = None;
+    let mut dimensions: Vec<Expression> = Vec::new();
+    let mut constructor_end = span.0;
+
+    for token in &tokens[2..] {
+        match token_rule(token) {
+            Rule::TypedComponentConstructorOperatorOpen => {},
+            Rule::E_20_ArgumentsContent => arguments = process_arguments(token)?,
+            Rule::TypedComponentConstructorOperatorClose => constructor_end = token_span(token).1,
+            Rule::E_VariableName => name = Some(token_text(token)),
+            Rule::ArrayDeclaration => dimensions.push(process_array_declaration(token)?),
+            _ => return invalid(token, "TypedComponentDeclaration")
+        }
+    }
+
+    Ok(Declaration {
+        span,
+        kind: DeclarationKind::Component,
+        name: match name {
+            Some(name) => name,
+            None => return invalid(declaration, "TypedComponentDeclaration")
+        },
+        dimensions,
+        value: None,
+        constructor: Some(Expression::Call {
+            span: (template.span().0, constructor_end),
+            callee: Box::new(template),
+            arguments
+        })
+    })
+
+}
+
+///
+/// Processes a `DeclarationStatement` token.
+///
+pub fn process_declaration(declaration: & Token) -> Result<Declaration, MatchError> {
+
+    let span = token_span(declaration);
+    let tokens = significant_subrules(declaration);
+
+    if let [typed @ Token::NonTerminal(NonTerminalToken { rule: Rule::TypedComponentDeclaration, .. })] = tokens.as_slice() {
+        return process_typed_component_declaration(span, typed);
+    }
+
+    let kind = match tokens.first().map(|token| token_rule(token)) {
+        Some(Rule::SignalDeclarationKW) => process_signal_declaration_kw(tokens[0])?,
+        Some(Rule::VariableDeclarationKW) => DeclarationKind::Variable,
+        Some(Rule::ComponentDeclarationKW) => DeclarationKind::Component,
+        _ => return invalid(declaration, "DeclarationStatement")
+    };
+
+    let name = match tokens.get(1) {
+        Some(token) if token_rule(token) == Rule::E_VariableName => token_text(token),
+        _ => return invalid(declaration, "DeclarationStatement")
+    };
+
+    let mut dimensions: Vec<Expression> = Vec::new();
+    let mut value: Option<Expression> = None;
+
+    for token in &tokens[2..] {
+        match token_rule(token) {
+            Rule::ArrayDeclaration => dimensions.push(process_array_declaration(token)?),
+            Rule::Expression => value = Some(process_expression(token)?),
+            _ => return invalid(token, "DeclarationStatement")
+        }
+    }
+
+    Ok(Declaration {
+        span,
+        kind,
+        name,
+        dimensions,
+        value,
+        constructor: None
+    })
+
+}
+
+fn is_assignment(expression: & Expression) -> bool {
+    match expression {
+        Expression::Assignment { .. } | Expression::Postfix { .. } => true,
+        Expression::Prefix { operator: PrefixOperator::Increment, .. } | Expression::Prefix { operator: PrefixOperator::Decrement, .. } => true,
+        Expression::Sequence { expressions, .. } => expressions.iter().all(is_assignment),
+        _ => false
+    }
+}
+
+fn is_step(expression: & Expression) -> bool {
+    match expression {
+        Expression::Postfix { .. } => true,
+        Expression::Prefix { operator: PrefixOperator::Increment, .. } | Expression::Prefix { operator: PrefixOperator::Decrement, .. } => true,
+        Expression::Assignment { operator, .. } => *operator != AssignmentOperator::Assign,
+        _ => false
+    }
+}
+
+///
+/// Processes a `ForStatement` token.
+///
+/// The `;` separators are not part of the token tree. When some of the three parts are missing, the
+/// remaining ones are told apart by their shape: declarations and plain assignments initialize,
+/// increments and compound assignments step, anything else is the condition.
+///
+fn process_for_statement(for_statement: & Token) -> Result<Statement, MatchError> {
+
+    let span = token_span(for_statement);
+    let tokens = significant_subrules(for_statement);
+
+    let (parts, body) = match tokens.as_slice() {
+        [Token::Terminal(TerminalToken { rule: Rule::ForKW, .. }), parts @ .., body] => (parts, process_body_or_line(body)?),
+        _ => return invalid(for_statement, "ForStatement")
+    };
+
+    let mut init: Option<Box<Statement>> = None;
+    let mut condition: Option<Expression> = None;
+    let mut step: Option<Expression> = None;
+
+    if parts.len() == 3 {
+        init = match process_statement(parts[0])? {
+            Some(statement) => Some(Box::new(statement)),
+            None => return invalid(parts[0], "ForStatement")
+        };
+        condition = Some(process_expression(parts[1])?);
+        step = Some(process_expression(parts[2])?);
+    } else {
+        for part in parts {
+            if token_rule(part) == Rule::DeclarationStatement {
+                init = Some(Box::new(Statement::Declaration(process_declaration(part)?)));
+                continue ;
+            }
+            let expression = process_expression(part)?;
+            if !is_assignment(&expression) {
+                condition = Some(expression);
+            } else if condition.is_some() || is_step(&expression) || init.is_some() {
+                step = Some(expression);
+            } else {
+                init = Some(Box::new(Statement::Expression { span: expression.span(), expression }));
+            }
+        }
+    }
+
+    Ok(Statement::For {
+        span,
+        init,
+        condition,
+        step,
+        body: Box::new(body)
+    })
+
+}
+
+fn process_if_statement(if_statement: & Token) -> Result<Statement, MatchError> {
+
+    let span = token_span(if_statement);
+    let tokens = significant_subrules(if_statement);
+
+    let (condition, if_true, rest) = match tokens.as_slice() {
+        [Token::Terminal(TerminalToken { rule: Rule::IfKW, .. }), condition, body, rest @ ..] => (process_expression(condition)?, process_body_or_line(body)?, rest),
+        _ => return invalid(if_statement, "IfStatement")
+    };
+
+    let mut if_false: Option<Box<Statement>> = None;
+
+    for token in rest.iter().rev() {
+        let sub_tokens = significant_subrules(token);
+        if_false = Some(Box::new(match (token_rule(token), sub_tokens.as_slice()) {
+            (Rule::ElseStatement, [_, body]) => process_body_or_line(body)?,
+            (Rule::ElseIfStatement, [_, condition, body]) => Statement::If {
+                span: (token_span(token).0, span.1),
+                condition: process_expression(condition)?,
+                if_true: Box::new(process_body_or_line(body)?),
+                if_false
+            },
+            _ => return invalid(token, "IfStatement")
+        }));
+    }
+
+    Ok(Statement::If {
+        span,
+        condition,
+        if_true: Box::new(if_true),
+        if_false
+    })
+
+}
+
+///
+/// Processes a `Body` or a `OneLineBody` token into a single statement.
+///
+pub fn process_body_or_line(body: & Token) -> Result<Statement, MatchError> {
+    match token_rule(body) {
+        Rule::Body => Ok(Statement::Block {
+            span: token_span(body),
+            statements: process_body(body)?
+        }),
+        Rule::OneLineBody => {
+            let mut statements = process_body(body)?;
+            Ok(match statements.len() {
+                1 => statements.remove(0),
+                _ => Statement::Block {
+                    span: token_span(body),
+                    statements
+                }
+            })
+        },
+        _ => invalid(body, "Body")
+    }
+}
+
+///
+/// Processes a single body statement. Returns `None` for lone `;` and comments.
+///
+pub fn process_statement(statement: & Token) -> Result<Option<Statement>, MatchError> {
+
+    let span = token_span(statement);
+
+    Ok(Some(match token_rule(statement) {
+        Rule::DeclarationStatement => Statement::Declaration(process_declaration(statement)?),
+        Rule::Expression => Statement::Expression {
+            span,
+            expression: process_expression(statement)?
+        },
+        Rule::ForStatement => process_for_statement(statement)?,
+        Rule::WhileStatement => match significant_subrules(statement).as_slice() {
+            [_, condition, body] => Statement::While {
+                span,
+                condition: process_expression(condition)?,
+                body: Box::new(process_body_or_line(body)?)
+            },
+            _ => return invalid(statement, "WhileStatement")
+        },
+        Rule::DoWhileStatement => match significant_subrules(statement).as_slice() {
+            [_, body, _, condition] => Statement::DoWhile {
+                span,
+                body: Box::new(process_body_or_line(body)?),
+                condition: process_expression(condition)?
+            },
+            _ => return invalid(statement, "DoWhileStatement")
+        },
+        Rule::IfStatement => process_if_statement(statement)?,
+        Rule::ReturnStatement => match significant_subrules(statement).as_slice() {
+            [_, value] => Statement::Return {
+                span,
+                value: process_expression(value)?
+            },
+            _ => return invalid(statement, "ReturnStatement")
+        },
+        Rule::BreakStatement => Statement::Break { span },
+        Rule::ContinueStatement => Statement::Continue { span },
+        Rule::Body => Statement::Block {
+            span,
+            statements: process_body(statement)?
+        },
+        Rule::END_OF_LINE | Rule::COMMENT => return Ok(None),
+        _ => return invalid(statement, "Body")
+    }))
+
+}
+
+///
+/// Processes the statements of a `Body` or `OneLineBody` token.
+///
+pub fn process_body(body: & Token) -> Result<Vec<Statement>, MatchError> {
+
+    let mut statements: Vec<Statement> = Vec::new();
+
+    for token in significant_subrules(body) {
+        if let Some(statement) = process_statement(token)? {
+            statements.push(statement);
+        }
+    }
+
+    Ok(statements)
+
+}
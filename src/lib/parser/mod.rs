@@ -1,4 +1,8 @@
 pub mod ast;
+pub mod typed_ast;
+pub mod dump;
+pub mod cst;
+pub mod visit;
 pub mod compile;
 pub mod matchers;
 pub mod errors;
@@ -88,14 +88,14 @@ template Sha256compression() {
     }
 
     for (k=0; k<32; k++ ) {
-        a[0][k] <== ha0.out[k]
-        b[0][k] <== hb0.out[k]
-        c[0][k] <== hc0.out[k]
-        d[0][k] <== hd0.out[k]
-        e[0][k] <== he0.out[k]
-        f[0][k] <== hf0.out[k]
-        g[0][k] <== hg0.out[k]
-        h[0][k] <== hh0.out[k]
+        a[0][k] <== ha0.out[k];
+        b[0][k] <== hb0.out[k];
+        c[0][k] <== hc0.out[k];
+        d[0][k] <== hd0.out[k];
+        e[0][k] <== he0.out[k];
+        f[0][k] <== hf0.out[k];
+        g[0][k] <== hg0.out[k];
+        h[0][k] <== hh0.out[k];
     }
 
     for (t = 0; t<64; t++) {
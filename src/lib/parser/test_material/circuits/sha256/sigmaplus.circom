@@ -17,8 +17,8 @@
     along with circom. If not, see <https://www.gnu.org/licenses/>.
 */
 
-include "../binsum.circom"
-include "sigma.circom"
+include "../binsum.circom";
+include "sigma.circom";
 
 template SigmaPlus() {
     signal input in2[32];
@@ -19,7 +19,7 @@
 
 include "../binsum.circom";
 include "sigma.circom";
-include "maj.circom"
+include "maj.circom";
 
 template T2() {
     signal input a[32];
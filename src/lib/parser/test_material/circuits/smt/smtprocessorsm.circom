@@ -144,14 +144,14 @@ template SMTProcessorSM() {
   //      + prev_bot
   //      - prev_bot *                         xor
 
-  st_bot <== (1-xor) * (aux2 - st_old0 + prev_bot)
+  st_bot <== (1-xor) * (aux2 - st_old0 + prev_bot);
 
 
   // st_upd = prev_top * (1-fnc[0]) *levIns;
   //    = + prev_top * levIns
   //      - prev_top * levIns * fnc[0]
 
-  st_upd <== aux1 - aux2
+  st_upd <== aux1 - aux2;
 
   // st_na = prev_new1 + prev_old0 + prev_na + prev_upd;
   //    = + prev_new1
@@ -0,0 +1,3 @@
+include "bitify.circom";
+
+component main = Num2Bits(4);
@@ -0,0 +1,46 @@
+#!/bin/sh
+#
+# Generates the snarkjs fixtures of the interoperability tests, run with
+#
+#     cargo test -- --ignored snarkjs
+#
+# from Num2Bits(8) of ../witness/num2bits.circom and the input 173, as the fixtures of ../proving.
+# The circuits of test_material use the circom 1 syntax, hence circom 0.5.
+#
+#     circom 0.5.46, snarkjs 0.7.4, node 20
+#
+# Run from this directory. The fixtures are not committed yet: until they are, the interoperability
+# tests are ignored and nothing checks snarkrs against files written by circom and snarkjs. Proofs
+# and contributions are random, running the script again replaces all of the fixtures.
+
+set -e
+
+CIRCOM="npx --yes circom@0.5.46"
+SNARKJS="npx --yes snarkjs@0.7.4"
+
+$CIRCOM ../witness/num2bits.circom --r1cs num2bits.r1cs --wasm num2bits.wasm
+echo '{"in": 173}' > input.json
+$SNARKJS wtns calculate num2bits.wasm input.json num2bits.wtns
+$SNARKJS wtns check num2bits.r1cs num2bits.wtns
+
+# Phase 1: 2^8 powers of tau, one contribution, prepared for phase 2
+$SNARKJS powersoftau new bn128 8 pot8_0000.ptau
+$SNARKJS powersoftau contribute pot8_0000.ptau pot8_0001.ptau --name="first" -e="snarkrs fixture phase 1"
+$SNARKJS powersoftau prepare phase2 pot8_0001.ptau pot8.ptau
+$SNARKJS powersoftau verify pot8.ptau
+
+# Groth16: the key before (num2bits_0000.zkey) and after (num2bits.zkey) one phase 2 contribution
+$SNARKJS groth16 setup num2bits.r1cs pot8.ptau num2bits_0000.zkey
+$SNARKJS zkey contribute num2bits_0000.zkey num2bits.zkey --name="second" -e="snarkrs fixture phase 2"
+$SNARKJS zkey verify num2bits.r1cs pot8.ptau num2bits.zkey
+$SNARKJS zkey export verificationkey num2bits.zkey verification_key.json
+$SNARKJS groth16 prove num2bits.zkey num2bits.wtns proof.json public.json
+$SNARKJS groth16 verify verification_key.json public.json proof.json
+
+# PLONK
+$SNARKJS plonk setup num2bits.r1cs pot8.ptau num2bits_plonk.zkey
+$SNARKJS zkey export verificationkey num2bits_plonk.zkey plonk_verification_key.json
+$SNARKJS plonk prove num2bits_plonk.zkey num2bits.wtns plonk_proof.json plonk_public.json
+$SNARKJS plonk verify plonk_verification_key.json plonk_public.json plonk_proof.json
+
+rm pot8_0000.ptau pot8_0001.ptau num2bits.wasm input.json
@@ -0,0 +1,15 @@
+// x * y === z leaves x free when y is zero
+template Conditional() {
+    signal input y;
+    signal input z;
+    signal output out;
+
+    signal x;
+
+    x <-- z / y;
+    x * y === z;
+
+    out <== x + 1;
+}
+
+component main = Conditional();
@@ -0,0 +1,3 @@
+include "../circuits/comparators.circom";
+
+component main = IsZero();
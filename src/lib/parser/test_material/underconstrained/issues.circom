@@ -0,0 +1,25 @@
+template Root() {
+    signal input in;
+    signal output out;
+
+    // Both square roots satisfy the constraint
+    out <-- in / 2;
+    out * out === in;
+}
+
+template Issues() {
+    signal input in;
+    signal output out;
+
+    signal unused;
+    signal free;
+
+    free <-- in + 1;
+
+    component root = Root();
+    root.in <== in;
+
+    out <== root.out + in;
+}
+
+component main = Issues();
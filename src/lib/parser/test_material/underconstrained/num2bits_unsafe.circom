@@ -0,0 +1,15 @@
+// Bit decomposition missing the out[i] * (out[i] - 1) === 0 constraints
+template UnsafeNum2Bits(n) {
+    signal input in;
+    signal output out[n];
+
+    var lc = 0;
+    for (var i = 0; i < n; i++) {
+        out[i] <-- (in >> i) & 1;
+        lc += out[i] * 2 ** i;
+    }
+
+    lc === in;
+}
+
+component main = UnsafeNum2Bits(2);
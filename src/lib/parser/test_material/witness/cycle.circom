@@ -0,0 +1,10 @@
+template Cycle() {
+    signal input a;
+    signal output b;
+    signal c;
+
+    b <-- c + a;
+    c <-- b * 2;
+}
+
+component main = Cycle();
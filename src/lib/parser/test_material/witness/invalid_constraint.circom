@@ -0,0 +1,19 @@
+template Increment() {
+    signal input a;
+    signal output b;
+
+    b <-- a + 1;
+    b === a * 2;
+}
+
+template Main() {
+    signal input a;
+    signal output b;
+
+    component inc = Increment();
+
+    inc.a <== a;
+    b <== inc.b;
+}
+
+component main = Main();
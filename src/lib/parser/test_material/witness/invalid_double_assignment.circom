@@ -0,0 +1,9 @@
+template Double() {
+    signal input in;
+    signal output out;
+
+    out <-- in;
+    out <-- in;
+}
+
+component main = Double();
@@ -0,0 +1,31 @@
+include "../circuits/comparators.circom";
+
+function diagonal(m, n) {
+    var result = 0;
+    for (var i = 0; i < n; i++) {
+        result += m[i][i];
+    }
+    return result;
+}
+
+template Matrix(n) {
+    signal private input m[n][n];
+    signal input x;
+    signal output trace;
+    signal output zero;
+
+    component isz = IsZero();
+
+    trace <-- diagonal(m, n);
+
+    var sum = 0;
+    for (var i = 0; i < n; i++) {
+        sum += m[i][i];
+    }
+    trace === sum;
+
+    isz.in <== x;
+    zero <== isz.out;
+}
+
+component main = Matrix(2);
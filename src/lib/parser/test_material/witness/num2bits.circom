@@ -0,0 +1,3 @@
+include "../circuits/bitify.circom";
+
+component main = Num2Bits(8);
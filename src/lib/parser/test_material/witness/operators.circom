@@ -0,0 +1,32 @@
+template Operators() {
+    signal input a;
+    signal input b;
+    signal output out[24];
+
+    out[0] <-- a + b;
+    out[1] <-- a - b;
+    out[2] <-- a * b;
+    out[3] <-- a / b;
+    out[4] <-- a \ b;
+    out[5] <-- a % b;
+    out[6] <-- a ** b;
+    out[7] <-- a || b;
+    out[8] <-- a && b;
+    out[9] <-- a | b;
+    out[10] <-- a ^ b;
+    out[11] <-- a & b;
+    out[12] <-- a == b;
+    out[13] <-- a != b;
+    out[14] <-- a < b;
+    out[15] <-- a <= b;
+    out[16] <-- a > b;
+    out[17] <-- a >= b;
+    out[18] <-- a << b;
+    out[19] <-- a >> b;
+    out[20] <-- -a;
+    out[21] <-- !a;
+    out[22] <-- ~a;
+    out[23] <-- a != 0 ? b \ a : 0;
+}
+
+component main = Operators();
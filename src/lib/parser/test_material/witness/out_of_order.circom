@@ -0,0 +1,11 @@
+template OutOfOrder() {
+    signal input a;
+    signal output b;
+    signal c;
+
+    b <-- c + 1;
+    c <-- a * 2;
+    b === c + 1;
+}
+
+component main = OutOfOrder();
@@ -0,0 +1,237 @@
+//
+// Typed AST, built from the token tree by the `matchers`.
+//
+// The token tree mirrors the grammar, where operator precedence is expressed through silent rules:
+// an `Expression` token only contains a flat list of operands and operators. The typed AST restores
+// the structure and drops what has no meaning for the later stages (comments, delimiters).
+//
+// All nodes keep the span of the source they come from.
+//
+
+pub type Span = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PrefixOperator {
+    Not,
+    Complement,
+    Increment,
+    Decrement,
+    Plus,
+    Minus
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PostfixOperator {
+    Increment,
+    Decrement
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BinaryOperator {
+    Or,
+    And,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Equal,
+    NotEqual,
+    Lesser,
+    LesserEqual,
+    Greater,
+    GreaterEqual,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    IntDiv,
+    Mod,
+    Pow
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AssignmentOperator {
+    Assign,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    ShiftLeft,
+    ShiftRight,
+    BitAnd,
+    BitXor,
+    BitOr
+}
+
+///
+/// Signal assignment operators. `==>` and `-->` are stored with their operands swapped, so that the
+/// assigned signal is always on the left.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SignalOperator {
+    ConstrainedAssign,
+    Assign
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Expression {
+    Number { span: Span, value: ::field::bigint::U256 },
+    Variable { span: Span, name: std::string::String },
+    Array { span: Span, elements: Vec<Expression> },
+    Prefix { span: Span, operator: PrefixOperator, operand: Box<Expression> },
+    Postfix { span: Span, operator: PostfixOperator, operand: Box<Expression> },
+    Binary { span: Span, operator: BinaryOperator, left: Box<Expression>, right: Box<Expression> },
+    Ternary { span: Span, condition: Box<Expression>, if_true: Box<Expression>, if_false: Box<Expression> },
+    Assignment { span: Span, operator: AssignmentOperator, target: Box<Expression>, value: Box<Expression> },
+    SignalAssignment { span: Span, operator: SignalOperator, reversed: bool, target: Box<Expression>, value: Box<Expression> },
+    Constraint { span: Span, left: Box<Expression>, right: Box<Expression> },
+    Sequence { span: Span, expressions: Vec<Expression> },
+    Call { span: Span, callee: Box<Expression>, arguments: Vec<Expression> },
+    Index { span: Span, target: Box<Expression>, index: Box<Expression> },
+    Member { span: Span, target: Box<Expression>, member: std::string::String }
+}
+
+impl Expression {
+
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Number { span, .. } |
+            Expression::Variable { span, .. } |
+            Expression::Array { span, .. } |
+            Expression::Prefix { span, .. } |
+            Expression::Postfix { span, .. } |
+            Expression::Binary { span, .. } |
+            Expression::Ternary { span, .. } |
+            Expression::Assignment { span, .. } |
+            Expression::SignalAssignment { span, .. } |
+            Expression::Constraint { span, .. } |
+            Expression::Sequence { span, .. } |
+            Expression::Call { span, .. } |
+            Expression::Index { span, .. } |
+            Expression::Member { span, .. } => *span
+        }
+    }
+
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SignalDirection {
+    Input,
+    Output,
+    Intermediate
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DeclarationKind {
+    Variable,
+    Signal { private: bool, direction: SignalDirection },
+    Component
+}
+
+///
+/// `var`, `signal` and `component` declarations.
+///
+/// `constructor` is only set for the `component T(args) name[dims]` form, `value` holds the
+/// initialization expression of the `var x = value` / `component c = T(args)` forms.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Declaration {
+    pub span: Span,
+    pub kind: DeclarationKind,
+    pub name: std::string::String,
+    pub dimensions: Vec<Expression>,
+    pub value: Option<Expression>,
+    pub constructor: Option<Expression>
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Statement {
+    Declaration(Declaration),
+    Expression { span: Span, expression: Expression },
+    Block { span: Span, statements: Vec<Statement> },
+    For { span: Span, init: Option<Box<Statement>>, condition: Option<Expression>, step: Option<Expression>, body: Box<Statement> },
+    While { span: Span, condition: Expression, body: Box<Statement> },
+    DoWhile { span: Span, body: Box<Statement>, condition: Expression },
+    If { span: Span, condition: Expression, if_true: Box<Statement>, if_false: Option<Box<Statement>> },
+    Return { span: Span, value: Expression },
+    Break { span: Span },
+    Continue { span: Span }
+}
+
+impl Statement {
+
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Declaration(declaration) => declaration.span,
+            Statement::Expression { span, .. } |
+            Statement::Block { span, .. } |
+            Statement::For { span, .. } |
+            Statement::While { span, .. } |
+            Statement::DoWhile { span, .. } |
+            Statement::If { span, .. } |
+            Statement::Return { span, .. } |
+            Statement::Break { span } |
+            Statement::Continue { span } => *span
+        }
+    }
+
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Function {
+    pub span: Span,
+    pub name: std::string::String,
+    pub parameters: Vec<std::string::String>,
+    pub body: Vec<Statement>
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    pub span: Span,
+    pub name: std::string::String,
+    pub parameters: Vec<std::string::String>,
+    pub body: Vec<Statement>
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Item {
+    Include { span: Span, path: std::string::String },
+    Function(Function),
+    Template(Template),
+    Declaration(Declaration)
+}
+
+///
+/// Typed version of a whole source file.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Circuit {
+    pub items: Vec<Item>
+}
+
+impl Circuit {
+
+    pub fn templates(&self) -> impl Iterator<Item = & Template> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Template(template) => Some(template),
+            _ => None
+        })
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = & Function> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Function(function) => Some(function),
+            _ => None
+        })
+    }
+
+    pub fn declarations(&self) -> impl Iterator<Item = & Declaration> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Declaration(declaration) => Some(declaration),
+            _ => None
+        })
+    }
+
+}
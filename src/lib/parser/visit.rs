@@ -0,0 +1,1522 @@
+use parser::ast::tokens::{Token, TerminalToken, NonTerminalToken, RootToken};
+use parser::typed_ast::*;
+use field::bigint::U256;
+
+//
+// Traversals of the token tree and of the typed AST.
+//
+// Each trait has a method per node kind whose default implementation walks the children of the
+// node through the matching `walk_*` function. A pass overrides the methods of the nodes it cares
+// about, calling the `walk_*` function itself when it still needs to reach the children.
+//
+// - `TokenVisitor` / `TokenVisitorMut` go over the token tree
+// - `Visitor` / `VisitorMut` go over the typed AST, by reference and by mutable reference
+// - `Fold` rebuilds the typed AST, each method returning the node replacing the one it was given
+//
+
+pub trait TokenVisitor<'t> {
+
+    fn visit_token(& mut self, token: &'t Token) {
+        walk_token(self, token)
+    }
+
+    fn visit_terminal(& mut self, _terminal: &'t TerminalToken) {}
+
+    fn visit_non_terminal(& mut self, non_terminal: &'t NonTerminalToken) {
+        walk_non_terminal(self, non_terminal)
+    }
+
+}
+
+pub fn walk_root<'t, V: TokenVisitor<'t> + ?Sized>(visitor: & mut V, root: &'t RootToken) {
+    for token in &root.ast {
+        visitor.visit_token(token);
+    }
+}
+
+pub fn walk_token<'t, V: TokenVisitor<'t> + ?Sized>(visitor: & mut V, token: &'t Token) {
+    match token {
+        Token::Terminal(terminal) => visitor.visit_terminal(terminal),
+        Token::NonTerminal(non_terminal) => visitor.visit_non_terminal(non_terminal)
+    }
+}
+
+pub fn walk_non_terminal<'t, V: TokenVisitor<'t> + ?Sized>(visitor: & mut V, non_terminal: &'t NonTerminalToken) {
+    for subrule in &non_terminal.subrules {
+        visitor.visit_token(subrule);
+    }
+}
+
+pub trait TokenVisitorMut {
+
+    fn visit_token(& mut self, token: & mut Token) {
+        walk_token_mut(self, token)
+    }
+
+    fn visit_terminal(& mut self, _terminal: & mut TerminalToken) {}
+
+    fn visit_non_terminal(& mut self, non_terminal: & mut NonTerminalToken) {
+        walk_non_terminal_mut(self, non_terminal)
+    }
+
+}
+
+pub fn walk_root_mut<V: TokenVisitorMut + ?Sized>(visitor: & mut V, root: & mut RootToken) {
+    for token in & mut root.ast {
+        visitor.visit_token(token);
+    }
+}
+
+pub fn walk_token_mut<V: TokenVisitorMut + ?Sized>(visitor: & mut V, token: & mut Token) {
+    match token {
+        Token::Terminal(terminal) => visitor.visit_terminal(terminal),
+        Token::NonTerminal(non_terminal) => visitor.visit_non_terminal(non_terminal)
+    }
+}
+
+pub fn walk_non_terminal_mut<V: TokenVisitorMut + ?Sized>(visitor: & mut V, non_terminal: & mut NonTerminalToken) {
+    for subrule in & mut non_terminal.subrules {
+        visitor.visit_token(subrule);
+    }
+}
+
+pub trait Visitor<'ast> {
+
+    fn visit_circuit(& mut self, circuit: &'ast Circuit) {
+        walk_circuit(self, circuit)
+    }
+
+    fn visit_item(& mut self, item: &'ast Item) {
+        walk_item(self, item)
+    }
+
+    fn visit_include(& mut self, _span: Span, _path: &'ast str) {}
+
+    fn visit_template(& mut self, template: &'ast Template) {
+        walk_template(self, template)
+    }
+
+    fn visit_function(& mut self, function: &'ast Function) {
+        walk_function(self, function)
+    }
+
+    fn visit_declaration(& mut self, declaration: &'ast Declaration) {
+        walk_declaration(self, declaration)
+    }
+
+    fn visit_statement(& mut self, statement: &'ast Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_expression_statement(& mut self, _span: Span, expression: &'ast Expression) {
+        walk_expression_statement(self, expression)
+    }
+
+    fn visit_block(& mut self, _span: Span, statements: &'ast [Statement]) {
+        walk_block(self, statements)
+    }
+
+    fn visit_for(& mut self, _span: Span, init: Option<&'ast Statement>, condition: Option<&'ast Expression>, step: Option<&'ast Expression>, body: &'ast Statement) {
+        walk_for(self, init, condition, step, body)
+    }
+
+    fn visit_while(& mut self, _span: Span, condition: &'ast Expression, body: &'ast Statement) {
+        walk_while(self, condition, body)
+    }
+
+    fn visit_do_while(& mut self, _span: Span, body: &'ast Statement, condition: &'ast Expression) {
+        walk_do_while(self, body, condition)
+    }
+
+    fn visit_if(& mut self, _span: Span, condition: &'ast Expression, if_true: &'ast Statement, if_false: Option<&'ast Statement>) {
+        walk_if(self, condition, if_true, if_false)
+    }
+
+    fn visit_return(& mut self, _span: Span, value: &'ast Expression) {
+        walk_return(self, value)
+    }
+
+    fn visit_break(& mut self, _span: Span) {}
+
+    fn visit_continue(& mut self, _span: Span) {}
+
+    fn visit_expression(& mut self, expression: &'ast Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_number(& mut self, _span: Span, _value: &'ast U256) {}
+
+    fn visit_variable(& mut self, _span: Span, _name: &'ast str) {}
+
+    fn visit_array(& mut self, _span: Span, elements: &'ast [Expression]) {
+        walk_array(self, elements)
+    }
+
+    fn visit_prefix(& mut self, _span: Span, _operator: PrefixOperator, operand: &'ast Expression) {
+        walk_prefix(self, operand)
+    }
+
+    fn visit_postfix(& mut self, _span: Span, _operator: PostfixOperator, operand: &'ast Expression) {
+        walk_postfix(self, operand)
+    }
+
+    fn visit_binary(& mut self, _span: Span, _operator: BinaryOperator, left: &'ast Expression, right: &'ast Expression) {
+        walk_binary(self, left, right)
+    }
+
+    fn visit_ternary(& mut self, _span: Span, condition: &'ast Expression, if_true: &'ast Expression, if_false: &'ast Expression) {
+        walk_ternary(self, condition, if_true, if_false)
+    }
+
+    fn visit_assignment(& mut self, _span: Span, _operator: AssignmentOperator, target: &'ast Expression, value: &'ast Expression) {
+        walk_assignment(self, target, value)
+    }
+
+    fn visit_signal_assignment(& mut self, _span: Span, _operator: SignalOperator, _reversed: bool, target: &'ast Expression, value: &'ast Expression) {
+        walk_signal_assignment(self, target, value)
+    }
+
+    fn visit_constraint(& mut self, _span: Span, left: &'ast Expression, right: &'ast Expression) {
+        walk_constraint(self, left, right)
+    }
+
+    fn visit_sequence(& mut self, _span: Span, expressions: &'ast [Expression]) {
+        walk_sequence(self, expressions)
+    }
+
+    fn visit_call(& mut self, _span: Span, callee: &'ast Expression, arguments: &'ast [Expression]) {
+        walk_call(self, callee, arguments)
+    }
+
+    fn visit_index(& mut self, _span: Span, target: &'ast Expression, index: &'ast Expression) {
+        walk_index(self, target, index)
+    }
+
+    fn visit_member(& mut self, _span: Span, target: &'ast Expression, _member: &'ast str) {
+        walk_member(self, target)
+    }
+
+}
+
+pub fn walk_circuit<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, circuit: &'ast Circuit) {
+    for item in &circuit.items {
+        visitor.visit_item(item);
+    }
+}
+
+pub fn walk_item<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, item: &'ast Item) {
+    match item {
+        Item::Include { span, path } => visitor.visit_include(*span, path),
+        Item::Function(function) => visitor.visit_function(function),
+        Item::Template(template) => visitor.visit_template(template),
+        Item::Declaration(declaration) => visitor.visit_declaration(declaration)
+    }
+}
+
+pub fn walk_template<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, template: &'ast Template) {
+    for statement in &template.body {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_function<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, function: &'ast Function) {
+    for statement in &function.body {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_declaration<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, declaration: &'ast Declaration) {
+
+    if let Some(constructor) = &declaration.constructor {
+        visitor.visit_expression(constructor);
+    }
+
+    for dimension in &declaration.dimensions {
+        visitor.visit_expression(dimension);
+    }
+
+    if let Some(value) = &declaration.value {
+        visitor.visit_expression(value);
+    }
+
+}
+
+pub fn walk_statement<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, statement: &'ast Statement) {
+    match statement {
+        Statement::Declaration(declaration) => visitor.visit_declaration(declaration),
+        Statement::Expression { span, expression } => visitor.visit_expression_statement(*span, expression),
+        Statement::Block { span, statements } => visitor.visit_block(*span, statements),
+        Statement::For { span, init, condition, step, body } => visitor.visit_for(*span, init.as_deref(), condition.as_ref(), step.as_ref(), body),
+        Statement::While { span, condition, body } => visitor.visit_while(*span, condition, body),
+        Statement::DoWhile { span, body, condition } => visitor.visit_do_while(*span, body, condition),
+        Statement::If { span, condition, if_true, if_false } => visitor.visit_if(*span, condition, if_true, if_false.as_deref()),
+        Statement::Return { span, value } => visitor.visit_return(*span, value),
+        Statement::Break { span } => visitor.visit_break(*span),
+        Statement::Continue { span } => visitor.visit_continue(*span)
+    }
+}
+
+pub fn walk_expression_statement<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, expression: &'ast Expression) {
+    visitor.visit_expression(expression);
+}
+
+pub fn walk_block<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, statements: &'ast [Statement]) {
+    for statement in statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_for<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, init: Option<&'ast Statement>, condition: Option<&'ast Expression>, step: Option<&'ast Expression>, body: &'ast Statement) {
+    if let Some(init) = init {
+        visitor.visit_statement(init);
+    }
+    if let Some(condition) = condition {
+        visitor.visit_expression(condition);
+    }
+    if let Some(step) = step {
+        visitor.visit_expression(step);
+    }
+    visitor.visit_statement(body);
+}
+
+pub fn walk_while<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, condition: &'ast Expression, body: &'ast Statement) {
+    visitor.visit_expression(condition);
+    visitor.visit_statement(body);
+}
+
+pub fn walk_do_while<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, body: &'ast Statement, condition: &'ast Expression) {
+    visitor.visit_statement(body);
+    visitor.visit_expression(condition);
+}
+
+pub fn walk_if<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, condition: &'ast Expression, if_true: &'ast Statement, if_false: Option<&'ast Statement>) {
+    visitor.visit_expression(condition);
+    visitor.visit_statement(if_true);
+    if let Some(if_false) = if_false {
+        visitor.visit_statement(if_false);
+    }
+}
+
+pub fn walk_return<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, value: &'ast Expression) {
+    visitor.visit_expression(value);
+}
+
+pub fn walk_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, expression: &'ast Expression) {
+    match expression {
+        Expression::Number { span, value } => visitor.visit_number(*span, value),
+        Expression::Variable { span, name } => visitor.visit_variable(*span, name),
+        Expression::Array { span, elements } => visitor.visit_array(*span, elements),
+        Expression::Prefix { span, operator, operand } => visitor.visit_prefix(*span, *operator, operand),
+        Expression::Postfix { span, operator, operand } => visitor.visit_postfix(*span, *operator, operand),
+        Expression::Binary { span, operator, left, right } => visitor.visit_binary(*span, *operator, left, right),
+        Expression::Ternary { span, condition, if_true, if_false } => visitor.visit_ternary(*span, condition, if_true, if_false),
+        Expression::Assignment { span, operator, target, value } => visitor.visit_assignment(*span, *operator, target, value),
+        Expression::SignalAssignment { span, operator, reversed, target, value } => visitor.visit_signal_assignment(*span, *operator, *reversed, target, value),
+        Expression::Constraint { span, left, right } => visitor.visit_constraint(*span, left, right),
+        Expression::Sequence { span, expressions } => visitor.visit_sequence(*span, expressions),
+        Expression::Call { span, callee, arguments } => visitor.visit_call(*span, callee, arguments),
+        Expression::Index { span, target, index } => visitor.visit_index(*span, target, index),
+        Expression::Member { span, target, member } => visitor.visit_member(*span, target, member)
+    }
+}
+
+pub fn walk_array<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, elements: &'ast [Expression]) {
+    for element in elements {
+        visitor.visit_expression(element);
+    }
+}
+
+pub fn walk_prefix<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, operand: &'ast Expression) {
+    visitor.visit_expression(operand);
+}
+
+pub fn walk_postfix<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, operand: &'ast Expression) {
+    visitor.visit_expression(operand);
+}
+
+pub fn walk_binary<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, left: &'ast Expression, right: &'ast Expression) {
+    visitor.visit_expression(left);
+    visitor.visit_expression(right);
+}
+
+pub fn walk_ternary<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, condition: &'ast Expression, if_true: &'ast Expression, if_false: &'ast Expression) {
+    visitor.visit_expression(condition);
+    visitor.visit_expression(if_true);
+    visitor.visit_expression(if_false);
+}
+
+pub fn walk_assignment<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, target: &'ast Expression, value: &'ast Expression) {
+    visitor.visit_expression(target);
+    visitor.visit_expression(value);
+}
+
+pub fn walk_signal_assignment<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, target: &'ast Expression, value: &'ast Expression) {
+    visitor.visit_expression(target);
+    visitor.visit_expression(value);
+}
+
+pub fn walk_constraint<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, left: &'ast Expression, right: &'ast Expression) {
+    visitor.visit_expression(left);
+    visitor.visit_expression(right);
+}
+
+pub fn walk_sequence<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, expressions: &'ast [Expression]) {
+    for expression in expressions {
+        visitor.visit_expression(expression);
+    }
+}
+
+pub fn walk_call<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, callee: &'ast Expression, arguments: &'ast [Expression]) {
+    visitor.visit_expression(callee);
+    for argument in arguments {
+        visitor.visit_expression(argument);
+    }
+}
+
+pub fn walk_index<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, target: &'ast Expression, index: &'ast Expression) {
+    visitor.visit_expression(target);
+    visitor.visit_expression(index);
+}
+
+pub fn walk_member<'ast, V: Visitor<'ast> + ?Sized>(visitor: & mut V, target: &'ast Expression) {
+    visitor.visit_expression(target);
+}
+
+pub trait VisitorMut {
+
+    fn visit_circuit(& mut self, circuit: & mut Circuit) {
+        walk_circuit_mut(self, circuit)
+    }
+
+    fn visit_item(& mut self, item: & mut Item) {
+        walk_item_mut(self, item)
+    }
+
+    fn visit_include(& mut self, _span: & mut Span, _path: & mut std::string::String) {}
+
+    fn visit_template(& mut self, template: & mut Template) {
+        walk_template_mut(self, template)
+    }
+
+    fn visit_function(& mut self, function: & mut Function) {
+        walk_function_mut(self, function)
+    }
+
+    fn visit_declaration(& mut self, declaration: & mut Declaration) {
+        walk_declaration_mut(self, declaration)
+    }
+
+    fn visit_statement(& mut self, statement: & mut Statement) {
+        walk_statement_mut(self, statement)
+    }
+
+    fn visit_expression_statement(& mut self, _span: & mut Span, expression: & mut Expression) {
+        walk_expression_statement_mut(self, expression)
+    }
+
+    fn visit_block(& mut self, _span: & mut Span, statements: & mut Vec<Statement>) {
+        walk_block_mut(self, statements)
+    }
+
+    fn visit_for(& mut self, _span: & mut Span, init: & mut Option<Box<Statement>>, condition: & mut Option<Expression>, step: & mut Option<Expression>, body: & mut Statement) {
+        walk_for_mut(self, init, condition, step, body)
+    }
+
+    fn visit_while(& mut self, _span: & mut Span, condition: & mut Expression, body: & mut Statement) {
+        walk_while_mut(self, condition, body)
+    }
+
+    fn visit_do_while(& mut self, _span: & mut Span, body: & mut Statement, condition: & mut Expression) {
+        walk_do_while_mut(self, body, condition)
+    }
+
+    fn visit_if(& mut self, _span: & mut Span, condition: & mut Expression, if_true: & mut Statement, if_false: & mut Option<Box<Statement>>) {
+        walk_if_mut(self, condition, if_true, if_false)
+    }
+
+    fn visit_return(& mut self, _span: & mut Span, value: & mut Expression) {
+        walk_return_mut(self, value)
+    }
+
+    fn visit_break(& mut self, _span: & mut Span) {}
+
+    fn visit_continue(& mut self, _span: & mut Span) {}
+
+    fn visit_expression(& mut self, expression: & mut Expression) {
+        walk_expression_mut(self, expression)
+    }
+
+    fn visit_number(& mut self, _span: & mut Span, _value: & mut U256) {}
+
+    fn visit_variable(& mut self, _span: & mut Span, _name: & mut std::string::String) {}
+
+    fn visit_array(& mut self, _span: & mut Span, elements: & mut Vec<Expression>) {
+        walk_array_mut(self, elements)
+    }
+
+    fn visit_prefix(& mut self, _span: & mut Span, _operator: & mut PrefixOperator, operand: & mut Expression) {
+        walk_prefix_mut(self, operand)
+    }
+
+    fn visit_postfix(& mut self, _span: & mut Span, _operator: & mut PostfixOperator, operand: & mut Expression) {
+        walk_postfix_mut(self, operand)
+    }
+
+    fn visit_binary(& mut self, _span: & mut Span, _operator: & mut BinaryOperator, left: & mut Expression, right: & mut Expression) {
+        walk_binary_mut(self, left, right)
+    }
+
+    fn visit_ternary(& mut self, _span: & mut Span, condition: & mut Expression, if_true: & mut Expression, if_false: & mut Expression) {
+        walk_ternary_mut(self, condition, if_true, if_false)
+    }
+
+    fn visit_assignment(& mut self, _span: & mut Span, _operator: & mut AssignmentOperator, target: & mut Expression, value: & mut Expression) {
+        walk_assignment_mut(self, target, value)
+    }
+
+    fn visit_signal_assignment(& mut self, _span: & mut Span, _operator: & mut SignalOperator, _reversed: & mut bool, target: & mut Expression, value: & mut Expression) {
+        walk_signal_assignment_mut(self, target, value)
+    }
+
+    fn visit_constraint(& mut self, _span: & mut Span, left: & mut Expression, right: & mut Expression) {
+        walk_constraint_mut(self, left, right)
+    }
+
+    fn visit_sequence(& mut self, _span: & mut Span, expressions: & mut Vec<Expression>) {
+        walk_sequence_mut(self, expressions)
+    }
+
+    fn visit_call(& mut self, _span: & mut Span, callee: & mut Expression, arguments: & mut Vec<Expression>) {
+        walk_call_mut(self, callee, arguments)
+    }
+
+    fn visit_index(& mut self, _span: & mut Span, target: & mut Expression, index: & mut Expression) {
+        walk_index_mut(self, target, index)
+    }
+
+    fn visit_member(& mut self, _span: & mut Span, target: & mut Expression, _member: & mut std::string::String) {
+        walk_member_mut(self, target)
+    }
+
+}
+
+pub fn walk_circuit_mut<V: VisitorMut + ?Sized>(visitor: & mut V, circuit: & mut Circuit) {
+    for item in & mut circuit.items {
+        visitor.visit_item(item);
+    }
+}
+
+pub fn walk_item_mut<V: VisitorMut + ?Sized>(visitor: & mut V, item: & mut Item) {
+    match item {
+        Item::Include { span, path } => visitor.visit_include(span, path),
+        Item::Function(function) => visitor.visit_function(function),
+        Item::Template(template) => visitor.visit_template(template),
+        Item::Declaration(declaration) => visitor.visit_declaration(declaration)
+    }
+}
+
+pub fn walk_template_mut<V: VisitorMut + ?Sized>(visitor: & mut V, template: & mut Template) {
+    for statement in & mut template.body {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_function_mut<V: VisitorMut + ?Sized>(visitor: & mut V, function: & mut Function) {
+    for statement in & mut function.body {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_declaration_mut<V: VisitorMut + ?Sized>(visitor: & mut V, declaration: & mut Declaration) {
+
+    if let Some(constructor) = & mut declaration.constructor {
+        visitor.visit_expression(constructor);
+    }
+
+    for dimension in & mut declaration.dimensions {
+        visitor.visit_expression(dimension);
+    }
+
+    if let Some(value) = & mut declaration.value {
+        visitor.visit_expression(value);
+    }
+
+}
+
+pub fn walk_statement_mut<V: VisitorMut + ?Sized>(visitor: & mut V, statement: & mut Statement) {
+    match statement {
+        Statement::Declaration(declaration) => visitor.visit_declaration(declaration),
+        Statement::Expression { span, expression } => visitor.visit_expression_statement(span, expression),
+        Statement::Block { span, statements } => visitor.visit_block(span, statements),
+        Statement::For { span, init, condition, step, body } => visitor.visit_for(span, init, condition, step, body),
+        Statement::While { span, condition, body } => visitor.visit_while(span, condition, body),
+        Statement::DoWhile { span, body, condition } => visitor.visit_do_while(span, body, condition),
+        Statement::If { span, condition, if_true, if_false } => visitor.visit_if(span, condition, if_true, if_false),
+        Statement::Return { span, value } => visitor.visit_return(span, value),
+        Statement::Break { span } => visitor.visit_break(span),
+        Statement::Continue { span } => visitor.visit_continue(span)
+    }
+}
+
+pub fn walk_expression_statement_mut<V: VisitorMut + ?Sized>(visitor: & mut V, expression: & mut Expression) {
+    visitor.visit_expression(expression);
+}
+
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(visitor: & mut V, statements: & mut [Statement]) {
+    for statement in statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_for_mut<V: VisitorMut + ?Sized>(visitor: & mut V, init: & mut Option<Box<Statement>>, condition: & mut Option<Expression>, step: & mut Option<Expression>, body: & mut Statement) {
+    if let Some(init) = init {
+        visitor.visit_statement(init);
+    }
+    if let Some(condition) = condition {
+        visitor.visit_expression(condition);
+    }
+    if let Some(step) = step {
+        visitor.visit_expression(step);
+    }
+    visitor.visit_statement(body);
+}
+
+pub fn walk_while_mut<V: VisitorMut + ?Sized>(visitor: & mut V, condition: & mut Expression, body: & mut Statement) {
+    visitor.visit_expression(condition);
+    visitor.visit_statement(body);
+}
+
+pub fn walk_do_while_mut<V: VisitorMut + ?Sized>(visitor: & mut V, body: & mut Statement, condition: & mut Expression) {
+    visitor.visit_statement(body);
+    visitor.visit_expression(condition);
+}
+
+pub fn walk_if_mut<V: VisitorMut + ?Sized>(visitor: & mut V, condition: & mut Expression, if_true: & mut Statement, if_false: & mut Option<Box<Statement>>) {
+    visitor.visit_expression(condition);
+    visitor.visit_statement(if_true);
+    if let Some(if_false) = if_false {
+        visitor.visit_statement(if_false);
+    }
+}
+
+pub fn walk_return_mut<V: VisitorMut + ?Sized>(visitor: & mut V, value: & mut Expression) {
+    visitor.visit_expression(value);
+}
+
+pub fn walk_expression_mut<V: VisitorMut + ?Sized>(visitor: & mut V, expression: & mut Expression) {
+    match expression {
+        Expression::Number { span, value } => visitor.visit_number(span, value),
+        Expression::Variable { span, name } => visitor.visit_variable(span, name),
+        Expression::Array { span, elements } => visitor.visit_array(span, elements),
+        Expression::Prefix { span, operator, operand } => visitor.visit_prefix(span, operator, operand),
+        Expression::Postfix { span, operator, operand } => visitor.visit_postfix(span, operator, operand),
+        Expression::Binary { span, operator, left, right } => visitor.visit_binary(span, operator, left, right),
+        Expression::Ternary { span, condition, if_true, if_false } => visitor.visit_ternary(span, condition, if_true, if_false),
+        Expression::Assignment { span, operator, target, value } => visitor.visit_assignment(span, operator, target, value),
+        Expression::SignalAssignment { span, operator, reversed, target, value } => visitor.visit_signal_assignment(span, operator, reversed, target, value),
+        Expression::Constraint { span, left, right } => visitor.visit_constraint(span, left, right),
+        Expression::Sequence { span, expressions } => visitor.visit_sequence(span, expressions),
+        Expression::Call { span, callee, arguments } => visitor.visit_call(span, callee, arguments),
+        Expression::Index { span, target, index } => visitor.visit_index(span, target, index),
+        Expression::Member { span, target, member } => visitor.visit_member(span, target, member)
+    }
+}
+
+pub fn walk_array_mut<V: VisitorMut + ?Sized>(visitor: & mut V, elements: & mut [Expression]) {
+    for element in elements {
+        visitor.visit_expression(element);
+    }
+}
+
+pub fn walk_prefix_mut<V: VisitorMut + ?Sized>(visitor: & mut V, operand: & mut Expression) {
+    visitor.visit_expression(operand);
+}
+
+pub fn walk_postfix_mut<V: VisitorMut + ?Sized>(visitor: & mut V, operand: & mut Expression) {
+    visitor.visit_expression(operand);
+}
+
+pub fn walk_binary_mut<V: VisitorMut + ?Sized>(visitor: & mut V, left: & mut Expression, right: & mut Expression) {
+    visitor.visit_expression(left);
+    visitor.visit_expression(right);
+}
+
+pub fn walk_ternary_mut<V: VisitorMut + ?Sized>(visitor: & mut V, condition: & mut Expression, if_true: & mut Expression, if_false: & mut Expression) {
+    visitor.visit_expression(condition);
+    visitor.visit_expression(if_true);
+    visitor.visit_expression(if_false);
+}
+
+pub fn walk_assignment_mut<V: VisitorMut + ?Sized>(visitor: & mut V, target: & mut Expression, value: & mut Expression) {
+    visitor.visit_expression(target);
+    visitor.visit_expression(value);
+}
+
+pub fn walk_signal_assignment_mut<V: VisitorMut + ?Sized>(visitor: & mut V, target: & mut Expression, value: & mut Expression) {
+    visitor.visit_expression(target);
+    visitor.visit_expression(value);
+}
+
+pub fn walk_constraint_mut<V: VisitorMut + ?Sized>(visitor: & mut V, left: & mut Expression, right: & mut Expression) {
+    visitor.visit_expression(left);
+    visitor.visit_expression(right);
+}
+
+pub fn walk_sequence_mut<V: VisitorMut + ?Sized>(visitor: & mut V, expressions: & mut [Expression]) {
+    for expression in expressions {
+        visitor.visit_expression(expression);
+    }
+}
+
+pub fn walk_call_mut<V: VisitorMut + ?Sized>(visitor: & mut V, callee: & mut Expression, arguments: & mut [Expression]) {
+    visitor.visit_expression(callee);
+    for argument in arguments {
+        visitor.visit_expression(argument);
+    }
+}
+
+pub fn walk_index_mut<V: VisitorMut + ?Sized>(visitor: & mut V, target: & mut Expression, index: & mut Expression) {
+    visitor.visit_expression(target);
+    visitor.visit_expression(index);
+}
+
+pub fn walk_member_mut<V: VisitorMut + ?Sized>(visitor: & mut V, target: & mut Expression) {
+    visitor.visit_expression(target);
+}
+
+pub trait Fold {
+
+    fn fold_circuit(& mut self, circuit: Circuit) -> Circuit {
+        fold_circuit(self, circuit)
+    }
+
+    fn fold_item(& mut self, item: Item) -> Item {
+        fold_item(self, item)
+    }
+
+    fn fold_template(& mut self, template: Template) -> Template {
+        fold_template(self, template)
+    }
+
+    fn fold_function(& mut self, function: Function) -> Function {
+        fold_function(self, function)
+    }
+
+    fn fold_declaration(& mut self, declaration: Declaration) -> Declaration {
+        fold_declaration(self, declaration)
+    }
+
+    fn fold_statement(& mut self, statement: Statement) -> Statement {
+        fold_statement(self, statement)
+    }
+
+    fn fold_expression_statement(& mut self, span: Span, expression: Expression) -> Statement {
+        fold_expression_statement(self, span, expression)
+    }
+
+    fn fold_block(& mut self, span: Span, statements: Vec<Statement>) -> Statement {
+        fold_block(self, span, statements)
+    }
+
+    fn fold_for(& mut self, span: Span, init: Option<Box<Statement>>, condition: Option<Expression>, step: Option<Expression>, body: Box<Statement>) -> Statement {
+        fold_for(self, span, init, condition, step, body)
+    }
+
+    fn fold_while(& mut self, span: Span, condition: Expression, body: Box<Statement>) -> Statement {
+        fold_while(self, span, condition, body)
+    }
+
+    fn fold_do_while(& mut self, span: Span, body: Box<Statement>, condition: Expression) -> Statement {
+        fold_do_while(self, span, body, condition)
+    }
+
+    fn fold_if(& mut self, span: Span, condition: Expression, if_true: Box<Statement>, if_false: Option<Box<Statement>>) -> Statement {
+        fold_if(self, span, condition, if_true, if_false)
+    }
+
+    fn fold_return(& mut self, span: Span, value: Expression) -> Statement {
+        fold_return(self, span, value)
+    }
+
+    fn fold_break(& mut self, span: Span) -> Statement {
+        Statement::Break { span }
+    }
+
+    fn fold_continue(& mut self, span: Span) -> Statement {
+        Statement::Continue { span }
+    }
+
+    fn fold_expression(& mut self, expression: Expression) -> Expression {
+        fold_expression(self, expression)
+    }
+
+    fn fold_number(& mut self, span: Span, value: U256) -> Expression {
+        Expression::Number { span, value }
+    }
+
+    fn fold_variable(& mut self, span: Span, name: std::string::String) -> Expression {
+        Expression::Variable { span, name }
+    }
+
+    fn fold_array(& mut self, span: Span, elements: Vec<Expression>) -> Expression {
+        fold_array(self, span, elements)
+    }
+
+    fn fold_prefix(& mut self, span: Span, operator: PrefixOperator, operand: Box<Expression>) -> Expression {
+        fold_prefix(self, span, operator, operand)
+    }
+
+    fn fold_postfix(& mut self, span: Span, operator: PostfixOperator, operand: Box<Expression>) -> Expression {
+        fold_postfix(self, span, operator, operand)
+    }
+
+    fn fold_binary(& mut self, span: Span, operator: BinaryOperator, left: Box<Expression>, right: Box<Expression>) -> Expression {
+        fold_binary(self, span, operator, left, right)
+    }
+
+    fn fold_ternary(& mut self, span: Span, condition: Box<Expression>, if_true: Box<Expression>, if_false: Box<Expression>) -> Expression {
+        fold_ternary(self, span, condition, if_true, if_false)
+    }
+
+    fn fold_assignment(& mut self, span: Span, operator: AssignmentOperator, target: Box<Expression>, value: Box<Expression>) -> Expression {
+        fold_assignment(self, span, operator, target, value)
+    }
+
+    fn fold_signal_assignment(& mut self, span: Span, operator: SignalOperator, reversed: bool, target: Box<Expression>, value: Box<Expression>) -> Expression {
+        fold_signal_assignment(self, span, operator, reversed, target, value)
+    }
+
+    fn fold_constraint(& mut self, span: Span, left: Box<Expression>, right: Box<Expression>) -> Expression {
+        fold_constraint(self, span, left, right)
+    }
+
+    fn fold_sequence(& mut self, span: Span, expressions: Vec<Expression>) -> Expression {
+        fold_sequence(self, span, expressions)
+    }
+
+    fn fold_call(& mut self, span: Span, callee: Box<Expression>, arguments: Vec<Expression>) -> Expression {
+        fold_call(self, span, callee, arguments)
+    }
+
+    fn fold_index(& mut self, span: Span, target: Box<Expression>, index: Box<Expression>) -> Expression {
+        fold_index(self, span, target, index)
+    }
+
+    fn fold_member(& mut self, span: Span, target: Box<Expression>, member: std::string::String) -> Expression {
+        fold_member(self, span, target, member)
+    }
+
+}
+
+pub fn fold_circuit<F: Fold + ?Sized>(folder: & mut F, circuit: Circuit) -> Circuit {
+    Circuit {
+        items: circuit.items.into_iter().map(|item| folder.fold_item(item)).collect()
+    }
+}
+
+pub fn fold_item<F: Fold + ?Sized>(folder: & mut F, item: Item) -> Item {
+    match item {
+        Item::Include { span, path } => Item::Include { span, path },
+        Item::Function(function) => Item::Function(folder.fold_function(function)),
+        Item::Template(template) => Item::Template(folder.fold_template(template)),
+        Item::Declaration(declaration) => Item::Declaration(folder.fold_declaration(declaration))
+    }
+}
+
+pub fn fold_template<F: Fold + ?Sized>(folder: & mut F, template: Template) -> Template {
+    Template {
+        body: template.body.into_iter().map(|statement| folder.fold_statement(statement)).collect(),
+        ..template
+    }
+}
+
+pub fn fold_function<F: Fold + ?Sized>(folder: & mut F, function: Function) -> Function {
+    Function {
+        body: function.body.into_iter().map(|statement| folder.fold_statement(statement)).collect(),
+        ..function
+    }
+}
+
+pub fn fold_declaration<F: Fold + ?Sized>(folder: & mut F, declaration: Declaration) -> Declaration {
+    Declaration {
+        constructor: declaration.constructor.map(|constructor| folder.fold_expression(constructor)),
+        dimensions: declaration.dimensions.into_iter().map(|dimension| folder.fold_expression(dimension)).collect(),
+        value: declaration.value.map(|value| folder.fold_expression(value)),
+        ..declaration
+    }
+}
+
+fn fold_boxed_statement<F: Fold + ?Sized>(folder: & mut F, mut statement: Box<Statement>) -> Box<Statement> {
+    *statement = folder.fold_statement(*statement);
+    statement
+}
+
+fn fold_boxed_expression<F: Fold + ?Sized>(folder: & mut F, mut expression: Box<Expression>) -> Box<Expression> {
+    *expression = folder.fold_expression(*expression);
+    expression
+}
+
+pub fn fold_statement<F: Fold + ?Sized>(folder: & mut F, statement: Statement) -> Statement {
+    match statement {
+        Statement::Declaration(declaration) => Statement::Declaration(folder.fold_declaration(declaration)),
+        Statement::Expression { span, expression } => folder.fold_expression_statement(span, expression),
+        Statement::Block { span, statements } => folder.fold_block(span, statements),
+        Statement::For { span, init, condition, step, body } => folder.fold_for(span, init, condition, step, body),
+        Statement::While { span, condition, body } => folder.fold_while(span, condition, body),
+        Statement::DoWhile { span, body, condition } => folder.fold_do_while(span, body, condition),
+        Statement::If { span, condition, if_true, if_false } => folder.fold_if(span, condition, if_true, if_false),
+        Statement::Return { span, value } => folder.fold_return(span, value),
+        Statement::Break { span } => folder.fold_break(span),
+        Statement::Continue { span } => folder.fold_continue(span)
+    }
+}
+
+pub fn fold_expression_statement<F: Fold + ?Sized>(folder: & mut F, span: Span, expression: Expression) -> Statement {
+    Statement::Expression { span, expression: folder.fold_expression(expression) }
+}
+
+pub fn fold_block<F: Fold + ?Sized>(folder: & mut F, span: Span, statements: Vec<Statement>) -> Statement {
+    Statement::Block {
+        span,
+        statements: statements.into_iter().map(|statement| folder.fold_statement(statement)).collect()
+    }
+}
+
+pub fn fold_for<F: Fold + ?Sized>(folder: & mut F, span: Span, init: Option<Box<Statement>>, condition: Option<Expression>, step: Option<Expression>, body: Box<Statement>) -> Statement {
+    Statement::For {
+        span,
+        init: init.map(|init| fold_boxed_statement(folder, init)),
+        condition: condition.map(|condition| folder.fold_expression(condition)),
+        step: step.map(|step| folder.fold_expression(step)),
+        body: fold_boxed_statement(folder, body)
+    }
+}
+
+pub fn fold_while<F: Fold + ?Sized>(folder: & mut F, span: Span, condition: Expression, body: Box<Statement>) -> Statement {
+    Statement::While {
+        span,
+        condition: folder.fold_expression(condition),
+        body: fold_boxed_statement(folder, body)
+    }
+}
+
+pub fn fold_do_while<F: Fold + ?Sized>(folder: & mut F, span: Span, body: Box<Statement>, condition: Expression) -> Statement {
+    Statement::DoWhile {
+        span,
+        body: fold_boxed_statement(folder, body),
+        condition: folder.fold_expression(condition)
+    }
+}
+
+pub fn fold_if<F: Fold + ?Sized>(folder: & mut F, span: Span, condition: Expression, if_true: Box<Statement>, if_false: Option<Box<Statement>>) -> Statement {
+    Statement::If {
+        span,
+        condition: folder.fold_expression(condition),
+        if_true: fold_boxed_statement(folder, if_true),
+        if_false: if_false.map(|if_false| fold_boxed_statement(folder, if_false))
+    }
+}
+
+pub fn fold_return<F: Fold + ?Sized>(folder: & mut F, span: Span, value: Expression) -> Statement {
+    Statement::Return { span, value: folder.fold_expression(value) }
+}
+
+pub fn fold_expression<F: Fold + ?Sized>(folder: & mut F, expression: Expression) -> Expression {
+    match expression {
+        Expression::Number { span, value } => folder.fold_number(span, value),
+        Expression::Variable { span, name } => folder.fold_variable(span, name),
+        Expression::Array { span, elements } => folder.fold_array(span, elements),
+        Expression::Prefix { span, operator, operand } => folder.fold_prefix(span, operator, operand),
+        Expression::Postfix { span, operator, operand } => folder.fold_postfix(span, operator, operand),
+        Expression::Binary { span, operator, left, right } => folder.fold_binary(span, operator, left, right),
+        Expression::Ternary { span, condition, if_true, if_false } => folder.fold_ternary(span, condition, if_true, if_false),
+        Expression::Assignment { span, operator, target, value } => folder.fold_assignment(span, operator, target, value),
+        Expression::SignalAssignment { span, operator, reversed, target, value } => folder.fold_signal_assignment(span, operator, reversed, target, value),
+        Expression::Constraint { span, left, right } => folder.fold_constraint(span, left, right),
+        Expression::Sequence { span, expressions } => folder.fold_sequence(span, expressions),
+        Expression::Call { span, callee, arguments } => folder.fold_call(span, callee, arguments),
+        Expression::Index { span, target, index } => folder.fold_index(span, target, index),
+        Expression::Member { span, target, member } => folder.fold_member(span, target, member)
+    }
+}
+
+pub fn fold_array<F: Fold + ?Sized>(folder: & mut F, span: Span, elements: Vec<Expression>) -> Expression {
+    Expression::Array {
+        span,
+        elements: elements.into_iter().map(|element| folder.fold_expression(element)).collect()
+    }
+}
+
+pub fn fold_prefix<F: Fold + ?Sized>(folder: & mut F, span: Span, operator: PrefixOperator, operand: Box<Expression>) -> Expression {
+    Expression::Prefix { span, operator, operand: fold_boxed_expression(folder, operand) }
+}
+
+pub fn fold_postfix<F: Fold + ?Sized>(folder: & mut F, span: Span, operator: PostfixOperator, operand: Box<Expression>) -> Expression {
+    Expression::Postfix { span, operator, operand: fold_boxed_expression(folder, operand) }
+}
+
+pub fn fold_binary<F: Fold + ?Sized>(folder: & mut F, span: Span, operator: BinaryOperator, left: Box<Expression>, right: Box<Expression>) -> Expression {
+    Expression::Binary {
+        span,
+        operator,
+        left: fold_boxed_expression(folder, left),
+        right: fold_boxed_expression(folder, right)
+    }
+}
+
+pub fn fold_ternary<F: Fold + ?Sized>(folder: & mut F, span: Span, condition: Box<Expression>, if_true: Box<Expression>, if_false: Box<Expression>) -> Expression {
+    Expression::Ternary {
+        span,
+        condition: fold_boxed_expression(folder, condition),
+        if_true: fold_boxed_expression(folder, if_true),
+        if_false: fold_boxed_expression(folder, if_false)
+    }
+}
+
+pub fn fold_assignment<F: Fold + ?Sized>(folder: & mut F, span: Span, operator: AssignmentOperator, target: Box<Expression>, value: Box<Expression>) -> Expression {
+    Expression::Assignment {
+        span,
+        operator,
+        target: fold_boxed_expression(folder, target),
+        value: fold_boxed_expression(folder, value)
+    }
+}
+
+pub fn fold_signal_assignment<F: Fold + ?Sized>(folder: & mut F, span: Span, operator: SignalOperator, reversed: bool, target: Box<Expression>, value: Box<Expression>) -> Expression {
+    Expression::SignalAssignment {
+        span,
+        operator,
+        reversed,
+        target: fold_boxed_expression(folder, target),
+        value: fold_boxed_expression(folder, value)
+    }
+}
+
+pub fn fold_constraint<F: Fold + ?Sized>(folder: & mut F, span: Span, left: Box<Expression>, right: Box<Expression>) -> Expression {
+    Expression::Constraint {
+        span,
+        left: fold_boxed_expression(folder, left),
+        right: fold_boxed_expression(folder, right)
+    }
+}
+
+pub fn fold_sequence<F: Fold + ?Sized>(folder: & mut F, span: Span, expressions: Vec<Expression>) -> Expression {
+    Expression::Sequence {
+        span,
+        expressions: expressions.into_iter().map(|expression| folder.fold_expression(expression)).collect()
+    }
+}
+
+pub fn fold_call<F: Fold + ?Sized>(folder: & mut F, span: Span, callee: Box<Expression>, arguments: Vec<Expression>) -> Expression {
+    Expression::Call {
+        span,
+        callee: fold_boxed_expression(folder, callee),
+        arguments: arguments.into_iter().map(|argument| folder.fold_expression(argument)).collect()
+    }
+}
+
+pub fn fold_index<F: Fold + ?Sized>(folder: & mut F, span: Span, target: Box<Expression>, index: Box<Expression>) -> Expression {
+    Expression::Index {
+        span,
+        target: fold_boxed_expression(folder, target),
+        index: fold_boxed_expression(folder, index)
+    }
+}
+
+pub fn fold_member<F: Fold + ?Sized>(folder: & mut F, span: Span, target: Box<Expression>, member: std::string::String) -> Expression {
+    Expression::Member { span, target: fold_boxed_expression(folder, target), member }
+}
+
+#[cfg(test)]
+mod visit_test {
+
+    use parser::ast::{self, Rule};
+    use parser::ast::tokens::{Token, TerminalToken};
+    use parser::matchers;
+    use field::bigint::U256;
+    use parser::typed_ast::*;
+    use parser::visit::{self, TokenVisitor, TokenVisitorMut, Visitor, VisitorMut, Fold};
+
+    use galvanic_assert::matchers::*;
+
+    const SOURCE: & str = "template a(n) {\n    signal input in[n];\n    signal output out;\n    var sum = 0;\n    for (var i = 0; i < n; i++) {\n        sum += in[i] * (1 + 1);\n    }\n    out <== sum;\n}\n";
+
+    ///
+    /// Every statement and expression kind, blocks nested in `if`, `while`, `do while` and bare
+    /// blocks.
+    ///
+    const KINDS: & str = "function f(x) {
+    var r[2] = [x, 1];
+    if (x > 0) {
+        r[0] = x;
+    } else {
+        while (x < 3) {
+            x++;
+            if (x == 2) {
+                break;
+            } else {
+                continue;
+            }
+        }
+    }
+    do {
+        x -= 1;
+    } while (x > 0);
+    return r[0];
+}
+
+template t(n) {
+    signal input in;
+    signal output out;
+    component c = T();
+    c.a <== in;
+    {
+        {
+            out <-- n > 1 ? f(in) : -in;
+        }
+    }
+    out === c.b * 2;
+}
+";
+
+    ///
+    /// Kind of a node, the name of its variant.
+    ///
+    fn kind<T: std::fmt::Debug>(node: & T) -> std::string::String {
+        format!("{:?}", node).split(|character: char| !character.is_alphanumeric()).next().unwrap().to_string()
+    }
+
+    fn counts(kinds: & [std::string::String]) -> Vec<(std::string::String, usize)> {
+        let mut counts: std::collections::BTreeMap<std::string::String, usize> = std::collections::BTreeMap::new();
+        for kind in kinds {
+            *counts.entry(kind.clone()).or_insert(0) += 1;
+        }
+        counts.into_iter().collect()
+    }
+
+    #[derive(Default)]
+    struct Kinds {
+        statements: Vec<std::string::String>,
+        expressions: Vec<std::string::String>
+    }
+
+    impl<'ast> Visitor<'ast> for Kinds {
+
+        fn visit_statement(& mut self, statement: &'ast Statement) {
+            self.statements.push(kind(statement));
+            visit::walk_statement(self, statement);
+        }
+
+        fn visit_expression(& mut self, expression: &'ast Expression) {
+            self.expressions.push(kind(expression));
+            visit::walk_expression(self, expression);
+        }
+
+    }
+
+    ///
+    /// Counts the nodes it reaches, like `Kinds`, and renames every variable `<name>_`.
+    ///
+    #[derive(Default)]
+    struct KindsMut(Kinds);
+
+    impl VisitorMut for KindsMut {
+
+        fn visit_statement(& mut self, statement: & mut Statement) {
+            self.0.statements.push(kind(statement));
+            visit::walk_statement_mut(self, statement);
+        }
+
+        fn visit_expression(& mut self, expression: & mut Expression) {
+            self.0.expressions.push(kind(expression));
+            if let Expression::Variable { name, .. } = expression {
+                name.push('_');
+            }
+            visit::walk_expression_mut(self, expression);
+        }
+
+    }
+
+    #[derive(Default)]
+    struct KindsFold(Kinds);
+
+    impl Fold for KindsFold {
+
+        fn fold_statement(& mut self, statement: Statement) -> Statement {
+            self.0.statements.push(kind(&statement));
+            visit::fold_statement(self, statement)
+        }
+
+        fn fold_expression(& mut self, expression: Expression) -> Expression {
+            self.0.expressions.push(kind(&expression));
+            match visit::fold_expression(self, expression) {
+                Expression::Variable { span, name } => Expression::Variable { span, name: format!("{}_", name) },
+                expression => expression
+            }
+        }
+
+    }
+
+    ///
+    /// Counts the nodes it reaches, like `Kinds`, through the method of each node kind.
+    ///
+    #[derive(Default)]
+    struct KindMethods(Kinds);
+
+    impl KindMethods {
+        fn statement(& mut self, kind: & str) {
+            self.0.statements.push(kind.to_string());
+        }
+        fn expression(& mut self, kind: & str) {
+            self.0.expressions.push(kind.to_string());
+        }
+    }
+
+    impl<'ast> Visitor<'ast> for KindMethods {
+
+        fn visit_declaration(& mut self, declaration: &'ast Declaration) {
+            self.statement("Declaration");
+            visit::walk_declaration(self, declaration);
+        }
+
+        fn visit_expression_statement(& mut self, _span: Span, expression: &'ast Expression) {
+            self.statement("Expression");
+            visit::walk_expression_statement(self, expression);
+        }
+
+        fn visit_block(& mut self, _span: Span, statements: &'ast [Statement]) {
+            self.statement("Block");
+            visit::walk_block(self, statements);
+        }
+
+        fn visit_for(& mut self, _span: Span, init: Option<&'ast Statement>, condition: Option<&'ast Expression>, step: Option<&'ast Expression>, body: &'ast Statement) {
+            self.statement("For");
+            visit::walk_for(self, init, condition, step, body);
+        }
+
+        fn visit_while(& mut self, _span: Span, condition: &'ast Expression, body: &'ast Statement) {
+            self.statement("While");
+            visit::walk_while(self, condition, body);
+        }
+
+        fn visit_do_while(& mut self, _span: Span, body: &'ast Statement, condition: &'ast Expression) {
+            self.statement("DoWhile");
+            visit::walk_do_while(self, body, condition);
+        }
+
+        fn visit_if(& mut self, _span: Span, condition: &'ast Expression, if_true: &'ast Statement, if_false: Option<&'ast Statement>) {
+            self.statement("If");
+            visit::walk_if(self, condition, if_true, if_false);
+        }
+
+        fn visit_return(& mut self, _span: Span, value: &'ast Expression) {
+            self.statement("Return");
+            visit::walk_return(self, value);
+        }
+
+        fn visit_break(& mut self, _span: Span) {
+            self.statement("Break");
+        }
+
+        fn visit_continue(& mut self, _span: Span) {
+            self.statement("Continue");
+        }
+
+        fn visit_number(& mut self, _span: Span, _value: &'ast U256) {
+            self.expression("Number");
+        }
+
+        fn visit_variable(& mut self, _span: Span, _name: &'ast str) {
+            self.expression("Variable");
+        }
+
+        fn visit_array(& mut self, _span: Span, elements: &'ast [Expression]) {
+            self.expression("Array");
+            visit::walk_array(self, elements);
+        }
+
+        fn visit_prefix(& mut self, _span: Span, _operator: PrefixOperator, operand: &'ast Expression) {
+            self.expression("Prefix");
+            visit::walk_prefix(self, operand);
+        }
+
+        fn visit_postfix(& mut self, _span: Span, _operator: PostfixOperator, operand: &'ast Expression) {
+            self.expression("Postfix");
+            visit::walk_postfix(self, operand);
+        }
+
+        fn visit_binary(& mut self, _span: Span, _operator: BinaryOperator, left: &'ast Expression, right: &'ast Expression) {
+            self.expression("Binary");
+            visit::walk_binary(self, left, right);
+        }
+
+        fn visit_ternary(& mut self, _span: Span, condition: &'ast Expression, if_true: &'ast Expression, if_false: &'ast Expression) {
+            self.expression("Ternary");
+            visit::walk_ternary(self, condition, if_true, if_false);
+        }
+
+        fn visit_assignment(& mut self, _span: Span, _operator: AssignmentOperator, target: &'ast Expression, value: &'ast Expression) {
+            self.expression("Assignment");
+            visit::walk_assignment(self, target, value);
+        }
+
+        fn visit_signal_assignment(& mut self, _span: Span, _operator: SignalOperator, _reversed: bool, target: &'ast Expression, value: &'ast Expression) {
+            self.expression("SignalAssignment");
+            visit::walk_signal_assignment(self, target, value);
+        }
+
+        fn visit_constraint(& mut self, _span: Span, left: &'ast Expression, right: &'ast Expression) {
+            self.expression("Constraint");
+            visit::walk_constraint(self, left, right);
+        }
+
+        fn visit_sequence(& mut self, _span: Span, expressions: &'ast [Expression]) {
+            self.expression("Sequence");
+            visit::walk_sequence(self, expressions);
+        }
+
+        fn visit_call(& mut self, _span: Span, callee: &'ast Expression, arguments: &'ast [Expression]) {
+            self.expression("Call");
+            visit::walk_call(self, callee, arguments);
+        }
+
+        fn visit_index(& mut self, _span: Span, target: &'ast Expression, index: &'ast Expression) {
+            self.expression("Index");
+            visit::walk_index(self, target, index);
+        }
+
+        fn visit_member(& mut self, _span: Span, target: &'ast Expression, _member: &'ast str) {
+            self.expression("Member");
+            visit::walk_member(self, target);
+        }
+
+    }
+
+    ///
+    /// Drops the `else` branch of every `if`.
+    ///
+    struct DropElse;
+
+    impl VisitorMut for DropElse {
+        fn visit_if(& mut self, _span: & mut Span, condition: & mut Expression, if_true: & mut Statement, if_false: & mut Option<Box<Statement>>) {
+            *if_false = None;
+            visit::walk_if_mut(self, condition, if_true, if_false);
+        }
+    }
+
+    ///
+    /// Turns every `while` loop into a `do while` loop.
+    ///
+    struct DoWhile;
+
+    impl Fold for DoWhile {
+        fn fold_while(& mut self, span: Span, condition: Expression, body: Box<Statement>) -> Statement {
+            visit::fold_do_while(self, span, body, condition)
+        }
+    }
+
+    fn expected_statements() -> Vec<(std::string::String, usize)> {
+        [("Block", 8), ("Break", 1), ("Continue", 1), ("Declaration", 4), ("DoWhile", 1), ("Expression", 6), ("If", 2), ("Return", 1), ("While", 1)].iter()
+            .map(|(kind, count)| (kind.to_string(), *count))
+            .collect()
+    }
+
+    fn expected_expressions() -> Vec<(std::string::String, usize)> {
+        [
+            ("Array", 1), ("Assignment", 2), ("Binary", 6), ("Call", 2), ("Constraint", 1), ("Index", 2), ("Member", 2), ("Number", 11),
+            ("Postfix", 1), ("Prefix", 1), ("SignalAssignment", 2), ("Ternary", 1), ("Variable", 20)
+        ].iter()
+            .map(|(kind, count)| (kind.to_string(), *count))
+            .collect()
+    }
+
+    ///
+    /// Checks that every variable of a circuit has been renamed `<name>_`.
+    ///
+    fn expect_renamed(circuit: & Circuit) {
+        let mut names = Names::default();
+        names.visit_circuit(circuit);
+        expect_that!(&names.used.len(), is(eq(20)));
+        expect_that!(&names.used.iter().all(|name| name.ends_with('_') && !name.ends_with("__")), is(eq(true)));
+    }
+
+    #[derive(Default)]
+    struct Names {
+        declared: Vec<std::string::String>,
+        used: Vec<std::string::String>
+    }
+
+    impl<'ast> Visitor<'ast> for Names {
+
+        fn visit_declaration(& mut self, declaration: &'ast Declaration) {
+            self.declared.push(declaration.name.clone());
+            visit::walk_declaration(self, declaration);
+        }
+
+        fn visit_variable(& mut self, _span: Span, name: &'ast str) {
+            self.used.push(name.to_string());
+        }
+
+    }
+
+    struct Rename;
+
+    impl VisitorMut for Rename {
+        fn visit_variable(& mut self, _span: & mut Span, name: & mut std::string::String) {
+            if name == "sum" {
+                *name = "total".to_string();
+            }
+        }
+    }
+
+    ///
+    /// Replaces the sum of two number literals by its value.
+    ///
+    struct ConstantSum;
+
+    impl Fold for ConstantSum {
+        fn fold_binary(& mut self, span: Span, operator: BinaryOperator, left: Box<Expression>, right: Box<Expression>) -> Expression {
+            match visit::fold_binary(self, span, operator, left, right) {
+                Expression::Binary { span, operator: BinaryOperator::Add, left, right } => match (*left, *right) {
+                    (Expression::Number { value: left, .. }, Expression::Number { value: right, .. }) => Expression::Number { span, value: left.overflowing_add(&right).0 },
+                    (left, right) => Expression::Binary { span, operator: BinaryOperator::Add, left: Box::new(left), right: Box::new(right) }
+                },
+                expression => expression
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct RuleCount(Vec<Rule>);
+
+    impl<'t> TokenVisitor<'t> for RuleCount {
+        fn visit_terminal(& mut self, terminal: &'t TerminalToken) {
+            self.0.push(terminal.rule);
+        }
+    }
+
+    struct Uppercase;
+
+    impl TokenVisitorMut for Uppercase {
+        fn visit_terminal(& mut self, terminal: & mut TerminalToken) {
+            if terminal.rule == Rule::TemplateName {
+                terminal.content = terminal.content.to_uppercase();
+            }
+        }
+    }
+
+    #[test]
+    fn test_visitors() {
+
+        let mut root = ast::parse_source(SOURCE, None).unwrap();
+        let mut circuit = matchers::circuit::process_circuit(&root).unwrap();
+
+        let mut names = Names::default();
+        names.visit_circuit(&circuit);
+        expect_that!(&names.declared, is(eq(vec!("in".to_string(), "out".to_string(), "sum".to_string(), "i".to_string()))));
+        expect_that!(&names.used.iter().filter(|name| *name == "sum").count(), is(eq(2)));
+
+        Rename.visit_circuit(& mut circuit);
+        let mut names = Names::default();
+        names.visit_circuit(&circuit);
+        expect_that!(&names.used.iter().filter(|name| *name == "total").count(), is(eq(2)));
+
+        let folded = ConstantSum.fold_circuit(circuit.clone());
+        let mut numbers: Vec<std::string::String> = Vec::new();
+        struct Numbers<'a>(&'a mut Vec<std::string::String>);
+        impl<'a, 'ast> Visitor<'ast> for Numbers<'a> {
+            fn visit_expression(& mut self, expression: &'ast Expression) {
+                if let Expression::Number { value, .. } = expression {
+                    self.0.push(value.to_string());
+                }
+                visit::walk_expression(self, expression);
+            }
+        }
+        Numbers(& mut numbers).visit_circuit(&folded);
+        expect_that!(&numbers, is(eq(vec!("0".to_string(), "0".to_string(), "2".to_string()))));
+
+        let mut rules = RuleCount::default();
+        visit::walk_root(& mut rules, &root);
+        expect_that!(&rules.0.iter().filter(|rule| **rule == Rule::E_VariableName).count(), is(eq(13)));
+
+        visit::walk_root_mut(& mut Uppercase, & mut root);
+        match &root.ast[0] {
+            Token::NonTerminal(circuit) => match &circuit.subrules[0] {
+                Token::NonTerminal(template) => {
+                    expect_that!(&template.subrules[1], is(eq(Token::Terminal(TerminalToken { span: (9, 10), rule: Rule::TemplateName, content: "A".to_string() }))));
+                },
+                other => panic!("Unexpected token {:?}", other)
+            },
+            other => panic!("Unexpected token {:?}", other)
+        }
+
+    }
+
+    #[test]
+    fn test_visitor_reaches_every_node() {
+
+        let circuit = matchers::circuit::process_circuit(&ast::parse_source(KINDS, None).unwrap()).unwrap();
+
+        let mut kinds = Kinds::default();
+        kinds.visit_circuit(&circuit);
+
+        expect_that!(&counts(&kinds.statements), is(eq(expected_statements())));
+        expect_that!(&counts(&kinds.expressions), is(eq(expected_expressions())));
+
+    }
+
+    #[test]
+    fn test_visitor_mut_reaches_every_node() {
+
+        let mut circuit = matchers::circuit::process_circuit(&ast::parse_source(KINDS, None).unwrap()).unwrap();
+
+        let mut kinds = KindsMut::default();
+        kinds.visit_circuit(& mut circuit);
+
+        expect_that!(&counts(&kinds.0.statements), is(eq(expected_statements())));
+        expect_that!(&counts(&kinds.0.expressions), is(eq(expected_expressions())));
+        expect_renamed(&circuit);
+
+    }
+
+    #[test]
+    fn test_fold_reaches_every_node() {
+
+        let circuit = matchers::circuit::process_circuit(&ast::parse_source(KINDS, None).unwrap()).unwrap();
+
+        let mut kinds = KindsFold::default();
+        let folded = kinds.fold_circuit(circuit);
+
+        expect_that!(&counts(&kinds.0.statements), is(eq(expected_statements())));
+        expect_that!(&counts(&kinds.0.expressions), is(eq(expected_expressions())));
+        expect_renamed(&folded);
+
+    }
+
+    #[test]
+    fn test_visitor_kind_methods() {
+
+        let mut circuit = matchers::circuit::process_circuit(&ast::parse_source(KINDS, None).unwrap()).unwrap();
+
+        let mut kinds = KindMethods::default();
+        kinds.visit_circuit(&circuit);
+        expect_that!(&counts(&kinds.0.statements), is(eq(expected_statements())));
+        expect_that!(&counts(&kinds.0.expressions), is(eq(expected_expressions())));
+
+        let folded = DoWhile.fold_circuit(circuit.clone());
+        let mut kinds = Kinds::default();
+        kinds.visit_circuit(&folded);
+        let statements = counts(&kinds.statements);
+        expect_that!(&statements.iter().any(|(kind, _)| kind == "While"), is(eq(false)));
+        expect_that!(&statements.contains(&("DoWhile".to_string(), 2)), is(eq(true)));
+
+        DropElse.visit_circuit(& mut circuit);
+        let mut kinds = Kinds::default();
+        kinds.visit_circuit(&circuit);
+        let statements = counts(&kinds.statements);
+        expect_that!(&statements.contains(&("If".to_string(), 1)), is(eq(true)));
+        expect_that!(&statements.iter().any(|(kind, _)| kind == "While" || kind == "Break" || kind == "Continue"), is(eq(false)));
+
+    }
+
+}
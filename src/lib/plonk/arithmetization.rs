@@ -0,0 +1,297 @@
+//
+// Conversion of R1CS constraints to PLONK gates `qm * a * b + ql * a + qr * b + qo * c + qc = 0`,
+// following snarkjs so that both derive the same gates from a `.r1cs` file:
+//
+// - one gate `a = public` per public signal, the value being brought by the public input
+//   polynomial of the prover and the verifier;
+// - `A * B = C` with `A` or `B` constant (or zero) is the linear gate `kA * B - C = 0`;
+// - other constraints are multiplication gates `A * B - C = 0`.
+//
+// Linear combinations with more terms than the gate has wires are folded, two terms at a time, into
+// new wires computed from the witness by `Addition`s, each one checked by its own gate.
+//
+
+use std::collections::VecDeque;
+
+use circuit::r1cs::{LinearCombination, R1CS};
+use field::PrimeField;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gate<F: PrimeField> {
+    pub left: usize,
+    pub right: usize,
+    pub output: usize,
+    pub qm: F,
+    pub ql: F,
+    pub qr: F,
+    pub qo: F,
+    pub qc: F
+}
+
+impl<F: PrimeField> Gate<F> {
+
+    pub fn is_satisfied(&self, values: & [F]) -> bool {
+
+        let a = values[self.left];
+        let b = values[self.right];
+        let c = values[self.output];
+
+        (self.qm * a * b + self.ql * a + self.qr * b + self.qo * c + self.qc).is_zero()
+
+    }
+
+}
+
+///
+/// Wire `left_coefficient * left + right_coefficient * right` introduced by the folding of a linear
+/// combination.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Addition<F: PrimeField> {
+    pub left: usize,
+    pub right: usize,
+    pub left_coefficient: F,
+    pub right_coefficient: F
+}
+
+///
+/// Gates of a constraint system. The wires are the ones of the R1CS followed by one wire per
+/// addition, wire 0 being the constant `one` and padding the unused inputs of the gates.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlonkCircuit<F: PrimeField> {
+    pub n_wires: usize,
+    pub n_public: usize,
+    pub gates: Vec<Gate<F>>,
+    pub additions: Vec<Addition<F>>
+}
+
+///
+/// Constant part of a linear combination and its other terms, folded until there are at most
+/// `max_terms` of them and padded with zero terms on wire 0.
+///
+struct ReducedCombination<F: PrimeField> {
+    constant: F,
+    terms: Vec<(usize, F)>
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+
+    pub fn from_r1cs(r1cs: & R1CS<F>) -> PlonkCircuit<F> {
+
+        let mut circuit = PlonkCircuit {
+            n_wires: r1cs.n_wires,
+            n_public: r1cs.n_public(),
+            gates: Vec::new(),
+            additions: Vec::new()
+        };
+
+        for signal in 1..=circuit.n_public {
+            circuit.gates.push(Gate {
+                left: signal,
+                right: 0,
+                output: 0,
+                qm: F::zero(),
+                ql: F::one(),
+                qr: F::zero(),
+                qo: F::zero(),
+                qc: F::zero()
+            });
+        }
+
+        for constraint in &r1cs.constraints {
+
+            let (a, b, c) = (&constraint.a, &constraint.b, &constraint.c);
+
+            if a.is_empty() || b.is_empty() {
+                circuit.add_linear(c);
+            } else if a.is_constant() {
+                let mut lc = c.clone();
+                lc.add_scaled(b, -a.constant_value());
+                circuit.add_linear(&lc);
+            } else if b.is_constant() {
+                let mut lc = c.clone();
+                lc.add_scaled(a, -b.constant_value());
+                circuit.add_linear(&lc);
+            } else {
+                circuit.add_multiplication(a, b, c);
+            }
+
+        }
+
+        circuit
+
+    }
+
+    ///
+    /// Number of wires, the ones of the additions included.
+    ///
+    pub fn n_vars(&self) -> usize {
+        self.n_wires + self.additions.len()
+    }
+
+    fn reduce(& mut self, lc: & LinearCombination<F>, max_terms: usize) -> ReducedCombination<F> {
+
+        let mut terms: VecDeque<(usize, F)> = lc.terms()
+            .filter(|(wire, _)| **wire != 0)
+            .map(|(wire, coefficient)| (*wire, *coefficient))
+            .collect();
+
+        while terms.len() > max_terms {
+
+            let (left, left_coefficient) = terms.pop_front().unwrap();
+            let (right, right_coefficient) = terms.pop_front().unwrap();
+            let output = self.n_vars();
+
+            self.gates.push(Gate {
+                left,
+                right,
+                output,
+                qm: F::zero(),
+                ql: -left_coefficient,
+                qr: -right_coefficient,
+                qo: F::one(),
+                qc: F::zero()
+            });
+            self.additions.push(Addition {
+                left,
+                right,
+                left_coefficient,
+                right_coefficient
+            });
+
+            terms.push_back((output, F::one()));
+
+        }
+
+        let mut terms: Vec<(usize, F)> = terms.into_iter().collect();
+        terms.resize(max_terms, (0, F::zero()));
+
+        ReducedCombination {
+            constant: lc.constant_value(),
+            terms
+        }
+
+    }
+
+    ///
+    /// Gate for `lc = 0`.
+    ///
+    fn add_linear(& mut self, lc: & LinearCombination<F>) {
+
+        let reduced = self.reduce(lc, 3);
+
+        self.gates.push(Gate {
+            left: reduced.terms[0].0,
+            right: reduced.terms[1].0,
+            output: reduced.terms[2].0,
+            qm: F::zero(),
+            ql: reduced.terms[0].1,
+            qr: reduced.terms[1].1,
+            qo: reduced.terms[2].1,
+            qc: reduced.constant
+        });
+
+    }
+
+    ///
+    /// Gate for `a * b - c = 0`.
+    ///
+    fn add_multiplication(& mut self, a: & LinearCombination<F>, b: & LinearCombination<F>, c: & LinearCombination<F>) {
+
+        let a = self.reduce(a, 1);
+        let b = self.reduce(b, 1);
+        let c = self.reduce(c, 1);
+
+        self.gates.push(Gate {
+            left: a.terms[0].0,
+            right: b.terms[0].0,
+            output: c.terms[0].0,
+            qm: a.terms[0].1 * b.terms[0].1,
+            ql: a.terms[0].1 * b.constant,
+            qr: a.constant * b.terms[0].1,
+            qo: -c.terms[0].1,
+            qc: a.constant * b.constant - c.constant
+        });
+
+    }
+
+    ///
+    /// Values of all the wires from the R1CS witness.
+    ///
+    pub fn extend_witness(&self, witness: & [F]) -> Vec<F> {
+
+        let mut values = witness.to_vec();
+
+        for addition in &self.additions {
+            let value = addition.left_coefficient * values[addition.left] + addition.right_coefficient * values[addition.right];
+            values.push(value);
+        }
+
+        values
+
+    }
+
+}
+
+#[cfg(test)]
+mod arithmetization_test {
+
+    use circuit::r1cs::{Constraint, LinearCombination, R1CS};
+    use field::bn254::Fr;
+    use field::PrimeField;
+    use plonk::arithmetization::PlonkCircuit;
+
+    use galvanic_assert::matchers::*;
+
+    fn lc(terms: & [(usize, i64)]) -> LinearCombination<Fr> {
+        let mut lc = LinearCombination::new();
+        for (wire, coefficient) in terms {
+            let value = Fr::from_u64(coefficient.unsigned_abs());
+            lc.add_term(*wire, if *coefficient < 0 { -value } else { value });
+        }
+        lc
+    }
+
+    #[test]
+    fn test_gates() {
+
+        // out = (a + b + 1) * (a - 2), sum = a + 2b + 3out + 4, 2 * sum = 2a + 4b + 6out + 8
+        let mut r1cs: R1CS<Fr> = R1CS::new(1, 0, 2);
+        let sum = r1cs.add_wire();
+
+        r1cs.add_constraint(Constraint::new(lc(&[(0, 1), (2, 1), (3, 1)]), lc(&[(0, -2), (2, 1)]), lc(&[(1, 1)])));
+        r1cs.add_constraint(Constraint::linear(lc(&[(0, 4), (1, 3), (2, 1), (3, 2), (sum, -1)])));
+        r1cs.add_constraint(Constraint::new(lc(&[(0, 2)]), lc(&[(sum, 1)]), lc(&[(0, 8), (1, 6), (2, 2), (3, 4)])));
+
+        let circuit = PlonkCircuit::from_r1cs(&r1cs);
+
+        // One addition for `a + b` in the multiplication, and one for each of the linear
+        // constraints, which have 4 wires.
+        expect_that!(&circuit.n_public, is(eq(1)));
+        expect_that!(&circuit.additions.len(), is(eq(3)));
+        expect_that!(&circuit.gates.len(), is(eq(7)));
+        expect_that!(&circuit.n_vars(), is(eq(8)));
+
+        let (a, b) = (Fr::from_u64(6), Fr::from_u64(9));
+        let out = (a + b + Fr::one()) * (a - Fr::from_u64(2));
+        let witness = vec!(Fr::one(), out, a, b, a + b.double() + out * Fr::from_u64(3) + Fr::from_u64(4));
+
+        expect_that!(&r1cs.unsatisfied(&witness).is_empty(), is(eq(true)));
+
+        // The public gates are only satisfied once the public input polynomial is added.
+        let values = circuit.extend_witness(&witness);
+        let gates = &circuit.gates[circuit.n_public..];
+
+        expect_that!(&values.len(), is(eq(circuit.n_vars())));
+        expect_that!(&gates.iter().all(|gate| gate.is_satisfied(&values)), is(eq(true)));
+
+        let mut wrong = witness.clone();
+        wrong[1] += Fr::one();
+        let values = circuit.extend_witness(&wrong);
+
+        expect_that!(&gates.iter().all(|gate| gate.is_satisfied(&values)), is(eq(false)));
+
+    }
+
+}
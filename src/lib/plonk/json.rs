@@ -0,0 +1,169 @@
+//
+// snarkjs `proof.json` and `verification_key.json` files of PLONK proofs (see `curve::bn254::json`
+// for the encoding of the points).
+//
+
+use curve::bn254::json::{self, JsonError};
+use field::bn254::Fr;
+use field::domain;
+use plonk::{Proof, VerifyingKey};
+
+use serde_json::Value;
+
+pub fn proof_to_json(proof: & Proof) -> Value {
+    json!({
+        "A": json::g1_to_json(&proof.a),
+        "B": json::g1_to_json(&proof.b),
+        "C": json::g1_to_json(&proof.c),
+        "Z": json::g1_to_json(&proof.z),
+        "T1": json::g1_to_json(&proof.t1),
+        "T2": json::g1_to_json(&proof.t2),
+        "T3": json::g1_to_json(&proof.t3),
+        "Wxi": json::g1_to_json(&proof.wxi),
+        "Wxiw": json::g1_to_json(&proof.wxiw),
+        "eval_a": json::scalar_to_json(&proof.eval_a),
+        "eval_b": json::scalar_to_json(&proof.eval_b),
+        "eval_c": json::scalar_to_json(&proof.eval_c),
+        "eval_s1": json::scalar_to_json(&proof.eval_s1),
+        "eval_s2": json::scalar_to_json(&proof.eval_s2),
+        "eval_zw": json::scalar_to_json(&proof.eval_zw),
+        "protocol": "plonk",
+        "curve": "bn128"
+    })
+}
+
+pub fn proof_from_json(document: & Value) -> Result<Proof, JsonError> {
+
+    json::check_header(document, "plonk")?;
+
+    let point = |name: & str| json::g1_from_json(json::member(document, name)?, name);
+    let scalar = |name: & str| json::scalar_from_json::<Fr>(json::member(document, name)?, name);
+
+    Ok(Proof {
+        a: point("A")?,
+        b: point("B")?,
+        c: point("C")?,
+        z: point("Z")?,
+        t1: point("T1")?,
+        t2: point("T2")?,
+        t3: point("T3")?,
+        wxi: point("Wxi")?,
+        wxiw: point("Wxiw")?,
+        eval_a: scalar("eval_a")?,
+        eval_b: scalar("eval_b")?,
+        eval_c: scalar("eval_c")?,
+        eval_s1: scalar("eval_s1")?,
+        eval_s2: scalar("eval_s2")?,
+        eval_zw: scalar("eval_zw")?
+    })
+
+}
+
+pub fn verifying_key_to_json(vk: & VerifyingKey) -> Value {
+    json!({
+        "protocol": "plonk",
+        "curve": "bn128",
+        "nPublic": vk.n_public,
+        "power": vk.power,
+        "k1": json::scalar_to_json(&vk.k1),
+        "k2": json::scalar_to_json(&vk.k2),
+        "Qm": json::g1_to_json(&vk.qm),
+        "Ql": json::g1_to_json(&vk.ql),
+        "Qr": json::g1_to_json(&vk.qr),
+        "Qo": json::g1_to_json(&vk.qo),
+        "Qc": json::g1_to_json(&vk.qc),
+        "S1": json::g1_to_json(&vk.s1),
+        "S2": json::g1_to_json(&vk.s2),
+        "S3": json::g1_to_json(&vk.s3),
+        "X_2": json::g2_to_json(&vk.x_2),
+        "w": json::scalar_to_json(&vk.omega())
+    })
+}
+
+///
+/// Reads a verification key. The generator `w` of the domain is derived from `power`, and checked
+/// against the one of the file when present.
+///
+pub fn verifying_key_from_json(document: & Value) -> Result<VerifyingKey, JsonError> {
+
+    json::check_header(document, "plonk")?;
+
+    let count = |name: & str| match json::member(document, name)?.as_u64() {
+        Some(value) => Ok(value as usize),
+        None => Err(JsonError::FormatError(format!("{} is not an integer", name)))
+    };
+    let point = |name: & str| json::g1_from_json(json::member(document, name)?, name);
+    let scalar = |name: & str| json::scalar_from_json::<Fr>(json::member(document, name)?, name);
+
+    let power = count("power")?;
+    let omega = match domain::root_of_unity::<Fr>(power) {
+        Some(omega) => omega,
+        None => return Err(JsonError::FormatError(format!("no domain of size 2^{}", power)))
+    };
+
+    if document.get("w").is_some() && scalar("w")? != omega {
+        return Err(JsonError::FormatError(format!("w is not the generator of the domain of size 2^{}", power)));
+    }
+
+    Ok(VerifyingKey {
+        n_public: count("nPublic")?,
+        power,
+        k1: scalar("k1")?,
+        k2: scalar("k2")?,
+        qm: point("Qm")?,
+        ql: point("Ql")?,
+        qr: point("Qr")?,
+        qo: point("Qo")?,
+        qc: point("Qc")?,
+        s1: point("S1")?,
+        s2: point("S2")?,
+        s3: point("S3")?,
+        x_2: json::g2_from_json(json::member(document, "X_2")?, "X_2")?
+    })
+
+}
+
+#[cfg(test)]
+mod json_test {
+
+    use circuit::r1cs_file::R1CSFile;
+    use curve::bn254::json::scalars_from_json;
+    use curve::bn254::json::scalars_to_json;
+    use field::bn254::Fr;
+    use plonk::{self, json};
+    use ptau::Ptau;
+    use rng::TestRng;
+    use witness::wtns;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_proof_and_key() {
+
+        let r1cs = R1CSFile::<Fr>::load(std::path::Path::new("./src/lib/parser/test_material/proving/num2bits.r1cs")).unwrap().r1cs;
+        let witness = wtns::load::<Fr>(std::path::Path::new("./src/lib/parser/test_material/proving/num2bits.wtns")).unwrap();
+
+        let mut rng = TestRng::new(4);
+        let pk = plonk::setup(&r1cs, &Ptau::generate(5, & mut rng)).unwrap();
+        let proof = plonk::prove(&pk, &witness, & mut rng).unwrap();
+
+        let vk = json::verifying_key_from_json(&json::verifying_key_to_json(&pk.verifying_key())).unwrap();
+        let proof = json::proof_from_json(&json::proof_to_json(&proof)).unwrap();
+        let public: Vec<Fr> = scalars_from_json(&scalars_to_json(&witness[1..=r1cs.n_public()]), "public").unwrap();
+
+        expect_that!(&vk, is(eq(pk.verifying_key())));
+        expect_that!(&json::verifying_key_to_json(&vk)["k1"], is(eq(json!("2"))));
+        expect_that!(&json::verifying_key_to_json(&vk)["power"], is(eq(json!(5))));
+        expect_that!(&plonk::verify(&vk, &public, &proof), is(eq(Ok(true))));
+
+        let mut document = json::verifying_key_to_json(&vk);
+        document["w"] = json!("2");
+        expect_that!(&json::verifying_key_from_json(&document).is_err(), is(eq(true)));
+
+        let mut document = json::proof_to_json(&proof);
+        document["protocol"] = json!("groth16");
+        expect_that!(&json::proof_from_json(&document).is_err(), is(eq(true)));
+
+    }
+
+}
@@ -0,0 +1,272 @@
+//
+// PLONK zk-SNARK over BN254 with KZG commitments, on the universal powers of tau setup.
+//
+// The R1CS constraints are turned into PLONK gates as snarkjs does (see `arithmetization`), the
+// public signals being the first gates. Proofs have the fields of the snarkjs proofs and are
+// checked by the equations of the snarkjs verifier, challenges included: they are drawn from a
+// Keccak-256 transcript of the verification key, the public signals and the proof (see
+// `transcript`).
+//
+// Unlike `groth16`, it is not generic over the pairing `Engine`: the transcript hashes points in the
+// snarkjs encoding of BN254.
+//
+
+pub mod arithmetization;
+pub mod transcript;
+pub mod setup;
+pub mod prover;
+pub mod verifier;
+pub mod json;
+
+use curve::bn254::{G1Affine, G2Affine};
+use field::bn254::Fr;
+use field::domain;
+use plonk::arithmetization::PlonkCircuit;
+
+pub use self::setup::setup;
+pub use self::prover::prove;
+pub use self::verifier::verify;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyingKey {
+    pub n_public: usize,
+
+    ///
+    /// Log2 of the size of the domain the gates are interpolated over.
+    ///
+    pub power: usize,
+
+    ///
+    /// Factors of the cosets of the domain labelling the right and output wires in the permutation.
+    ///
+    pub k1: Fr,
+    pub k2: Fr,
+
+    ///
+    /// Commitments to the selector and permutation polynomials.
+    ///
+    pub qm: G1Affine,
+    pub ql: G1Affine,
+    pub qr: G1Affine,
+    pub qo: G1Affine,
+    pub qc: G1Affine,
+    pub s1: G1Affine,
+    pub s2: G1Affine,
+    pub s3: G1Affine,
+
+    ///
+    /// `tau * G2`.
+    ///
+    pub x_2: G2Affine
+}
+
+impl VerifyingKey {
+
+    pub fn domain_size(&self) -> usize {
+        1 << self.power
+    }
+
+    ///
+    /// Generator of the domain.
+    ///
+    pub fn omega(&self) -> Fr {
+        domain::root_of_unity(self.power).unwrap()
+    }
+
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvingKey {
+    pub circuit: PlonkCircuit<Fr>,
+    pub domain_size: usize,
+
+    ///
+    /// Coefficients of the selector polynomials.
+    ///
+    pub qm: Vec<Fr>,
+    pub ql: Vec<Fr>,
+    pub qr: Vec<Fr>,
+    pub qo: Vec<Fr>,
+    pub qc: Vec<Fr>,
+
+    ///
+    /// Image of every wire position by the copy permutation, the left, right and output columns
+    /// one after the other. Positions are labelled `w^i`, `k1 * w^i` and `k2 * w^i` for the row
+    /// `i` of each column.
+    ///
+    pub sigma: Vec<Fr>,
+
+    ///
+    /// `tau^i * G1`, enough of them to commit to the blinded quotient.
+    ///
+    pub tau_g1: Vec<G1Affine>,
+    pub vk: VerifyingKey
+}
+
+impl ProvingKey {
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.vk.clone()
+    }
+
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Proof {
+
+    ///
+    /// Commitments to the wire polynomials, the permutation accumulator and the three parts of
+    /// the quotient.
+    ///
+    pub a: G1Affine,
+    pub b: G1Affine,
+    pub c: G1Affine,
+    pub z: G1Affine,
+    pub t1: G1Affine,
+    pub t2: G1Affine,
+    pub t3: G1Affine,
+
+    ///
+    /// Opening proofs at the challenge `xi` and at `xi * w`.
+    ///
+    pub wxi: G1Affine,
+    pub wxiw: G1Affine,
+
+    pub eval_a: Fr,
+    pub eval_b: Fr,
+    pub eval_c: Fr,
+    pub eval_s1: Fr,
+    pub eval_s2: Fr,
+    pub eval_zw: Fr
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlonkError {
+    DomainTooLarge(usize),
+    WitnessLength { expected: usize, found: usize },
+    PublicSignalCount { expected: usize, found: usize },
+    PtauTooSmall { needed: usize, available: usize }
+}
+
+impl std::fmt::Display for PlonkError {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlonkError::DomainTooLarge(size) => write!(formatter, "The circuit needs a domain of {} elements, more than the field supports", size),
+            PlonkError::WitnessLength { expected, found } => write!(formatter, "The witness has {} values but the circuit has {} wires", found, expected),
+            PlonkError::PublicSignalCount { expected, found } => write!(formatter, "{} public signals given but the circuit has {}", found, expected),
+            PlonkError::PtauTooSmall { needed, available } => write!(formatter, "The circuit needs {} powers of tau but the file only has {}", needed, available)
+        }
+    }
+}
+
+#[cfg(test)]
+mod plonk_test {
+
+    use circuit::r1cs_file::R1CSFile;
+    use curve::bn254::json::{load, scalars_from_json};
+    use elaborator;
+    use field::bn254::Fr;
+    use field::PrimeField;
+    use parser::compile;
+    use plonk::{self, json, PlonkError};
+    use ptau::Ptau;
+    use rng::TestRng;
+    use witness::{self, wtns};
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_num2bits_fixtures() {
+
+        let r1cs = R1CSFile::<Fr>::load(std::path::Path::new("./src/lib/parser/test_material/proving/num2bits.r1cs")).unwrap().r1cs;
+        let witness = wtns::load::<Fr>(std::path::Path::new("./src/lib/parser/test_material/proving/num2bits.wtns")).unwrap();
+
+        let mut rng = TestRng::new(1);
+        let ptau = Ptau::generate(5, & mut rng);
+
+        expect_that!(&plonk::setup(&r1cs, &Ptau::generate(3, & mut rng)), is(eq(Err(PlonkError::PtauTooSmall { needed: 32, available: 8 }))));
+
+        let pk = plonk::setup(&r1cs, &ptau).unwrap();
+        let vk = pk.verifying_key();
+
+        expect_that!(&vk.power, is(eq(5)));
+        expect_that!(&vk.k1, is(eq(Fr::from_u64(2))));
+        expect_that!(&vk.k2, is(eq(Fr::from_u64(3))));
+
+        let proof = plonk::prove(&pk, &witness, & mut rng).unwrap();
+        let public = &witness[1..=r1cs.n_public()];
+
+        expect_that!(&plonk::verify(&vk, public, &proof), is(eq(Ok(true))));
+
+        let mut tampered = public.to_vec();
+        tampered[0] = Fr::zero();
+        expect_that!(&plonk::verify(&vk, &tampered, &proof), is(eq(Ok(false))));
+
+        let mut forged = proof;
+        forged.eval_a += Fr::one();
+        expect_that!(&plonk::verify(&vk, public, &forged), is(eq(Ok(false))));
+
+        let mut forged = proof;
+        forged.z = proof.a;
+        expect_that!(&plonk::verify(&vk, public, &forged), is(eq(Ok(false))));
+
+        expect_that!(&plonk::verify(&vk, &public[1..], &proof), is(eq(Err(PlonkError::PublicSignalCount { expected: 9, found: 8 }))));
+        expect_that!(&plonk::prove(&pk, &witness[1..], & mut rng), is(eq(Err(PlonkError::WitnessLength { expected: 10, found: 9 }))));
+
+        let mut invalid = witness.clone();
+        invalid[2] += Fr::one();
+        let proof = plonk::prove(&pk, &invalid, & mut rng).unwrap();
+        expect_that!(&plonk::verify(&vk, &invalid[1..=r1cs.n_public()], &proof), is(eq(Ok(false))));
+
+    }
+
+    #[test]
+    #[ignore = "needs the fixtures written by src/lib/parser/test_material/snarkjs/generate.sh"]
+    fn test_snarkjs_key_and_proof() {
+
+        let directory = std::path::Path::new("./src/lib/parser/test_material/snarkjs");
+
+        let r1cs = R1CSFile::<Fr>::load(&directory.join("num2bits.r1cs")).unwrap().r1cs;
+        let witness = wtns::load::<Fr>(&directory.join("num2bits.wtns")).unwrap();
+        let ptau = Ptau::load(&directory.join("pot8.ptau")).unwrap();
+
+        let vk = json::verifying_key_from_json(&load(&directory.join("plonk_verification_key.json")).unwrap()).unwrap();
+        let proof = json::proof_from_json(&load(&directory.join("plonk_proof.json")).unwrap()).unwrap();
+        let public: Vec<Fr> = scalars_from_json(&load(&directory.join("plonk_public.json")).unwrap(), "public").unwrap();
+
+        expect_that!(&public.as_slice(), is(eq(&witness[1..=r1cs.n_public()])));
+        expect_that!(&plonk::verify(&vk, &public, &proof), is(eq(Ok(true))));
+
+        // Same gates and permutation as snarkjs, so the same key from the same powers of tau
+        let pk = plonk::setup(&r1cs, &ptau).unwrap();
+        expect_that!(&pk.verifying_key(), is(eq(vk.clone())));
+
+        let proof = plonk::prove(&pk, &witness, & mut TestRng::new(12)).unwrap();
+        expect_that!(&plonk::verify(&vk, &public, &proof), is(eq(Ok(true))));
+
+    }
+
+    #[test]
+    fn test_num2bits_elaborated() {
+
+        let path = std::fs::canonicalize("./src/lib/parser/test_material/witness/num2bits.circom").expect("Invalid Path");
+        let ctx = compile::build_context(&path);
+        let circuit = elaborator::elaborate::<Fr>(&ctx).expect("Elaboration failed");
+        let r1cs = circuit.r1cs().expect("Constraint generation failed");
+
+        let inputs = witness::input::parse_inputs::<Fr>("{\"in\": 200}").unwrap();
+        let witness = witness::calculate_witness(&circuit, &inputs).unwrap();
+
+        let mut rng = TestRng::new(2);
+        let pk = plonk::setup(&r1cs, &Ptau::generate(5, & mut rng)).unwrap();
+        let proof = plonk::prove(&pk, &witness, & mut rng).unwrap();
+
+        expect_that!(&plonk::verify(&pk.verifying_key(), &witness[1..=r1cs.n_public()], &proof), is(eq(Ok(true))));
+
+        let again = plonk::prove(&pk, &witness, & mut TestRng::new(3)).unwrap();
+        expect_that!(&(again == proof), is(eq(false)));
+        expect_that!(&plonk::verify(&pk.verifying_key(), &witness[1..=r1cs.n_public()], &again), is(eq(Ok(true))));
+
+    }
+
+}
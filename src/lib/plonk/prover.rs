@@ -0,0 +1,271 @@
+use field::bigint::U256;
+use field::bn254::Fr;
+use field::domain::{self, Domain};
+use field::polynomial;
+use field::PrimeField;
+use plonk::arithmetization::Gate;
+use plonk::setup::commit;
+use plonk::transcript::Transcript;
+use plonk::{PlonkError, Proof, ProvingKey};
+use rng::Rng;
+
+///
+/// Coefficients of the polynomial of evaluations `values` over the domain, plus
+/// `(sum(blinding[i] * X^i)) * (X^n - 1)` which vanishes on the domain.
+///
+fn interpolate_blinded(domain: & Domain<Fr>, values: & [Fr], blinding: & [Fr]) -> Vec<Fr> {
+
+    let mut coefficients = values.to_vec();
+    domain.ifft(& mut coefficients);
+
+    coefficients.resize(domain.size + blinding.len(), Fr::zero());
+    for (idx, factor) in blinding.iter().enumerate() {
+        coefficients[domain.size + idx] += *factor;
+        coefficients[idx] -= *factor;
+    }
+
+    coefficients
+
+}
+
+///
+/// `target += factor * source`.
+///
+fn add_scaled(target: & mut Vec<Fr>, source: & [Fr], factor: & Fr) {
+
+    if target.len() < source.len() {
+        target.resize(source.len(), Fr::zero());
+    }
+
+    for (target, source) in target.iter_mut().zip(source.iter()) {
+        *target += *source * *factor;
+    }
+
+}
+
+///
+/// Evaluations over the coset `shift * extended` of the polynomial of coefficients `coefficients`.
+///
+fn coset_evaluations(extended: & Domain<Fr>, shift: & Fr, coefficients: & [Fr]) -> Vec<Fr> {
+    let mut values = coefficients.to_vec();
+    values.resize(extended.size, Fr::zero());
+    extended.coset_fft(& mut values, shift);
+    values
+}
+
+///
+/// Proof for a full R1CS witness (the one wire first, as in `.wtns` files), blinded with randomness
+/// drawn from `rng`.
+///
+pub fn prove<R: Rng>(pk: & ProvingKey, witness: & [Fr], rng: & mut R) -> Result<Proof, PlonkError> {
+
+    let circuit = &pk.circuit;
+    let vk = &pk.vk;
+
+    if witness.len() != circuit.n_wires {
+        return Err(PlonkError::WitnessLength { expected: circuit.n_wires, found: witness.len() });
+    }
+
+    let domain = Domain::<Fr>::new(pk.domain_size).unwrap();
+    let n = domain.size;
+    let extended = Domain::<Fr>::new(4 * n).ok_or(PlonkError::DomainTooLarge(4 * n))?;
+    let shift = domain::root_of_unity::<Fr>(extended.log_size + 1).ok_or(PlonkError::DomainTooLarge(8 * n))?;
+
+    let values = circuit.extend_witness(witness);
+    let public = &witness[1..=circuit.n_public];
+    let blinding: Vec<Fr> = (0..11).map(|_| rng.field()).collect();
+
+    let mut transcript = Transcript::new();
+    for point in &[vk.qm, vk.ql, vk.qr, vk.qo, vk.qc, vk.s1, vk.s2, vk.s3] {
+        transcript.add_point(point);
+    }
+    for value in public {
+        transcript.add_scalar(value);
+    }
+
+    // Round 1: the wire polynomials, the unused rows holding the one wire.
+    let wire_values = |wire: fn(& Gate<Fr>) -> usize| -> Vec<Fr> {
+        (0..n).map(|row| values[circuit.gates.get(row).map_or(0, wire)]).collect()
+    };
+
+    let a_values = wire_values(|gate| gate.left);
+    let b_values = wire_values(|gate| gate.right);
+    let c_values = wire_values(|gate| gate.output);
+
+    let a = interpolate_blinded(&domain, &a_values, &blinding[0..2]);
+    let b = interpolate_blinded(&domain, &b_values, &blinding[2..4]);
+    let c = interpolate_blinded(&domain, &c_values, &blinding[4..6]);
+
+    let commitment_a = commit(&pk.tau_g1, &a);
+    let commitment_b = commit(&pk.tau_g1, &b);
+    let commitment_c = commit(&pk.tau_g1, &c);
+
+    transcript.add_point(&commitment_a);
+    transcript.add_point(&commitment_b);
+    transcript.add_point(&commitment_c);
+    let beta = transcript.challenge();
+
+    transcript.add_scalar(&beta);
+    let gamma = transcript.challenge();
+
+    // Round 2: the accumulator of the permutation argument, z(w^(i+1)) = z(w^i) * f(w^i) / g(w^i).
+    let elements = domain.elements();
+    let sigma = &pk.sigma;
+
+    let mut denominators: Vec<Fr> = (0..n)
+        .map(|i| (a_values[i] + beta * sigma[i] + gamma) * (b_values[i] + beta * sigma[n + i] + gamma) * (c_values[i] + beta * sigma[2 * n + i] + gamma))
+        .collect();
+    domain::batch_inverse(& mut denominators);
+
+    let mut z_values = vec!(Fr::one(); n);
+    for i in 0..n - 1 {
+        let x = elements[i];
+        let numerator = (a_values[i] + beta * x + gamma) * (b_values[i] + beta * vk.k1 * x + gamma) * (c_values[i] + beta * vk.k2 * x + gamma);
+        z_values[i + 1] = z_values[i] * numerator * denominators[i];
+    }
+
+    let z = interpolate_blinded(&domain, &z_values, &blinding[6..9]);
+    let commitment_z = commit(&pk.tau_g1, &z);
+
+    transcript.add_scalar(&beta);
+    transcript.add_scalar(&gamma);
+    transcript.add_point(&commitment_z);
+    let alpha = transcript.challenge();
+
+    // Round 3: the quotient t(X), of degree 3n + 5, from its evaluations over a coset of the
+    // domain of size 4n, where the vanishing polynomial X^n - 1 takes 4 values, none of them zero.
+    let sigma_coefficients: Vec<Vec<Fr>> = (0..3).map(|column| {
+        let mut coefficients = sigma[column * n..(column + 1) * n].to_vec();
+        domain.ifft(& mut coefficients);
+        coefficients
+    }).collect();
+
+    let mut pi = vec!(Fr::zero(); n);
+    for (value, signal) in pi.iter_mut().zip(public.iter()) {
+        *value = -*signal;
+    }
+    domain.ifft(& mut pi);
+
+    let mut l1 = vec!(Fr::zero(); n);
+    l1[0] = Fr::one();
+    domain.ifft(& mut l1);
+
+    let evaluations = |coefficients: & [Fr]| coset_evaluations(&extended, &shift, coefficients);
+    let (a_ext, b_ext, c_ext, z_ext) = (evaluations(&a), evaluations(&b), evaluations(&c), evaluations(&z));
+    let (qm_ext, ql_ext, qr_ext, qo_ext, qc_ext) = (evaluations(&pk.qm), evaluations(&pk.ql), evaluations(&pk.qr), evaluations(&pk.qo), evaluations(&pk.qc));
+    let (s1_ext, s2_ext, s3_ext) = (evaluations(&sigma_coefficients[0]), evaluations(&sigma_coefficients[1]), evaluations(&sigma_coefficients[2]));
+    let (pi_ext, l1_ext) = (evaluations(&pi), evaluations(&l1));
+
+    let shift_n = shift.pow(&U256::from_u64(n as u64));
+    let mut vanishing_inverses: Vec<Fr> = domain::powers(&extended.omega.pow(&U256::from_u64(n as u64)), 4).iter().map(|power| shift_n * *power - Fr::one()).collect();
+    domain::batch_inverse(& mut vanishing_inverses);
+
+    let alpha_2 = alpha.square();
+    let mut t: Vec<Fr> = domain::powers(&extended.omega, extended.size).iter().enumerate().map(|(i, power)| {
+
+        let x = shift * *power;
+        let z_shifted = z_ext[(i + 4) % extended.size];
+
+        let gates = qm_ext[i] * a_ext[i] * b_ext[i] + ql_ext[i] * a_ext[i] + qr_ext[i] * b_ext[i] + qo_ext[i] * c_ext[i] + pi_ext[i] + qc_ext[i];
+
+        let permutation = (a_ext[i] + beta * x + gamma) * (b_ext[i] + beta * vk.k1 * x + gamma) * (c_ext[i] + beta * vk.k2 * x + gamma) * z_ext[i]
+            - (a_ext[i] + beta * s1_ext[i] + gamma) * (b_ext[i] + beta * s2_ext[i] + gamma) * (c_ext[i] + beta * s3_ext[i] + gamma) * z_shifted;
+
+        let first = (z_ext[i] - Fr::one()) * l1_ext[i];
+
+        (gates + alpha * permutation + alpha_2 * first) * vanishing_inverses[i % 4]
+
+    }).collect();
+
+    extended.coset_ifft(& mut t, &shift);
+
+    // Split in t1 + X^n * t2 + X^2n * t3, blinded by terms cancelling each other.
+    let mut t1 = t[..n].to_vec();
+    let mut t2 = t[n..2 * n].to_vec();
+    let mut t3 = t[2 * n..3 * n + 6].to_vec();
+    t1.push(blinding[9]);
+    t2[0] -= blinding[9];
+    t2.push(blinding[10]);
+    t3[0] -= blinding[10];
+
+    let commitment_t1 = commit(&pk.tau_g1, &t1);
+    let commitment_t2 = commit(&pk.tau_g1, &t2);
+    let commitment_t3 = commit(&pk.tau_g1, &t3);
+
+    transcript.add_scalar(&alpha);
+    transcript.add_point(&commitment_t1);
+    transcript.add_point(&commitment_t2);
+    transcript.add_point(&commitment_t3);
+    let xi = transcript.challenge();
+
+    // Round 4: the evaluations at xi and xi * w.
+    let xi_omega = xi * domain.omega;
+    let eval_a = polynomial::evaluate(&a, &xi);
+    let eval_b = polynomial::evaluate(&b, &xi);
+    let eval_c = polynomial::evaluate(&c, &xi);
+    let eval_s1 = polynomial::evaluate(&sigma_coefficients[0], &xi);
+    let eval_s2 = polynomial::evaluate(&sigma_coefficients[1], &xi);
+    let eval_zw = polynomial::evaluate(&z, &xi_omega);
+
+    transcript.add_scalar(&xi);
+    for value in &[eval_a, eval_b, eval_c, eval_s1, eval_s2, eval_zw] {
+        transcript.add_scalar(value);
+    }
+    let v = transcript.challenge();
+
+    // Round 5: the linearisation polynomial r(X), which vanishes at xi, and the opening proofs.
+    let xi_n = xi.pow(&U256::from_u64(n as u64));
+    let lagrange = domain.lagrange_coefficients(&xi);
+    let pi_xi = public.iter().zip(lagrange.iter()).fold(Fr::zero(), |acc, (signal, l)| acc - *signal * *l);
+
+    let mut r = Vec::new();
+    add_scaled(& mut r, &pk.qm, &(eval_a * eval_b));
+    add_scaled(& mut r, &pk.ql, &eval_a);
+    add_scaled(& mut r, &pk.qr, &eval_b);
+    add_scaled(& mut r, &pk.qo, &eval_c);
+    add_scaled(& mut r, &pk.qc, &Fr::one());
+
+    let permutation_z = alpha * (eval_a + beta * xi + gamma) * (eval_b + beta * vk.k1 * xi + gamma) * (eval_c + beta * vk.k2 * xi + gamma)
+        + alpha_2 * lagrange[0];
+    add_scaled(& mut r, &z, &permutation_z);
+
+    let permutation_s3 = alpha * (eval_a + beta * eval_s1 + gamma) * (eval_b + beta * eval_s2 + gamma) * eval_zw;
+    add_scaled(& mut r, &sigma_coefficients[2], &-(permutation_s3 * beta));
+
+    r[0] += pi_xi - permutation_s3 * (eval_c + gamma) - alpha_2 * lagrange[0];
+
+    let vanishing = xi_n - Fr::one();
+    add_scaled(& mut r, &t1, &-vanishing);
+    add_scaled(& mut r, &t2, &-(vanishing * xi_n));
+    add_scaled(& mut r, &t3, &-(vanishing * xi_n.square()));
+
+    let mut opened = r;
+    let mut power = Fr::one();
+    for (polynomial, value) in [(&a, eval_a), (&b, eval_b), (&c, eval_c), (&sigma_coefficients[0], eval_s1), (&sigma_coefficients[1], eval_s2)].iter() {
+        power *= v;
+        add_scaled(& mut opened, polynomial, &power);
+        opened[0] -= power * *value;
+    }
+
+    let wxi = polynomial::divide_by_linear(&opened, &xi);
+    let wxiw = polynomial::divide_by_linear(&z, &xi_omega);
+
+    Ok(Proof {
+        a: commitment_a,
+        b: commitment_b,
+        c: commitment_c,
+        z: commitment_z,
+        t1: commitment_t1,
+        t2: commitment_t2,
+        t3: commitment_t3,
+        wxi: commit(&pk.tau_g1, &wxi),
+        wxiw: commit(&pk.tau_g1, &wxiw),
+        eval_a,
+        eval_b,
+        eval_c,
+        eval_s1,
+        eval_s2,
+        eval_zw
+    })
+
+}
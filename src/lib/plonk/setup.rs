@@ -0,0 +1,162 @@
+use circuit::r1cs::R1CS;
+use curve::bn254::G1Affine;
+use curve::multi_exp;
+use field::bigint::U256;
+use field::bn254::Fr;
+use field::domain::Domain;
+use field::PrimeField;
+use plonk::arithmetization::{Gate, PlonkCircuit};
+use plonk::{PlonkError, ProvingKey, VerifyingKey};
+use ptau::Ptau;
+
+///
+/// Smallest domain, as in snarkjs.
+///
+const MIN_DOMAIN_SIZE: usize = 8;
+
+///
+/// Commitment `p(tau) * G1` to the polynomial of coefficients `coefficients`.
+///
+pub fn commit(tau_g1: & [G1Affine], coefficients: & [Fr]) -> G1Affine {
+    multi_exp(&tau_g1[..coefficients.len()], coefficients).to_affine()
+}
+
+///
+/// `k1` and `k2` such that the domain and its cosets `k1 * domain` and `k2 * domain` are disjoint:
+/// the smallest integers from 2, as in snarkjs.
+///
+fn coset_factors(domain: & Domain<Fr>) -> (Fr, Fr) {
+
+    let size = U256::from_u64(domain.size as u64);
+    let in_domain = |x: & Fr| x.pow(&size).is_one();
+
+    let mut k1 = Fr::from_u64(2);
+    while in_domain(&k1) {
+        k1 += Fr::one();
+    }
+
+    let mut k2 = k1 + Fr::one();
+    while in_domain(&k2) || in_domain(&(k2 * k1.inverse().unwrap())) {
+        k2 += Fr::one();
+    }
+
+    (k1, k2)
+
+}
+
+///
+/// Copy permutation of the wire positions: each position is sent to the previous use of the same
+/// wire, the first use to the last one. The unused rows use wire 0.
+///
+fn permutation(circuit: & PlonkCircuit<Fr>, domain: & Domain<Fr>, k1: & Fr, k2: & Fr) -> Vec<Fr> {
+
+    let n = domain.size;
+    let elements = domain.elements();
+    let factors = [Fr::one(), *k1, *k2];
+    let label = |position: usize| factors[position / n] * elements[position % n];
+
+    let mut sigma = vec!(Fr::zero(); 3 * n);
+    let mut first: Vec<Option<usize>> = vec!(None; circuit.n_vars());
+    let mut last: Vec<Option<usize>> = vec!(None; circuit.n_vars());
+
+    for row in 0..n {
+
+        let wires = match circuit.gates.get(row) {
+            Some(gate) => [gate.left, gate.right, gate.output],
+            None => [0, 0, 0]
+        };
+
+        for (column, wire) in wires.iter().enumerate() {
+            let position = column * n + row;
+            match last[*wire] {
+                Some(previous) => sigma[position] = label(previous),
+                None => first[*wire] = Some(position)
+            }
+            last[*wire] = Some(position);
+        }
+
+    }
+
+    for (first, last) in first.iter().zip(last.iter()) {
+        if let (Some(first), Some(last)) = (first, last) {
+            sigma[*first] = label(*last);
+        }
+    }
+
+    sigma
+
+}
+
+fn selector(circuit: & PlonkCircuit<Fr>, domain: & Domain<Fr>, select: fn(& Gate<Fr>) -> Fr) -> Vec<Fr> {
+
+    let mut values = vec!(Fr::zero(); domain.size);
+    for (value, gate) in values.iter_mut().zip(circuit.gates.iter()) {
+        *value = select(gate);
+    }
+
+    domain.ifft(& mut values);
+
+    values
+
+}
+
+///
+/// Circuit specific keys derived from the universal powers of tau, which must cover the domain of
+/// the gates.
+///
+pub fn setup(r1cs: & R1CS<Fr>, ptau: & Ptau) -> Result<ProvingKey, PlonkError> {
+
+    let circuit = PlonkCircuit::from_r1cs(r1cs);
+    let size = circuit.gates.len().max(MIN_DOMAIN_SIZE);
+    let domain = Domain::<Fr>::new(size).ok_or(PlonkError::DomainTooLarge(size))?;
+
+    if domain.size > 1 << ptau.power {
+        return Err(PlonkError::PtauTooSmall { needed: domain.size, available: 1 << ptau.power });
+    }
+
+    let (k1, k2) = coset_factors(&domain);
+    let sigma = permutation(&circuit, &domain, &k1, &k2);
+    let tau_g1 = ptau.tau_g1[..domain.size + 6].to_vec();
+
+    let qm = selector(&circuit, &domain, |gate| gate.qm);
+    let ql = selector(&circuit, &domain, |gate| gate.ql);
+    let qr = selector(&circuit, &domain, |gate| gate.qr);
+    let qo = selector(&circuit, &domain, |gate| gate.qo);
+    let qc = selector(&circuit, &domain, |gate| gate.qc);
+
+    let sigma_commitment = |column: usize| {
+        let mut coefficients = sigma[column * domain.size..(column + 1) * domain.size].to_vec();
+        domain.ifft(& mut coefficients);
+        commit(&tau_g1, &coefficients)
+    };
+
+    let vk = VerifyingKey {
+        n_public: circuit.n_public,
+        power: domain.log_size,
+        k1,
+        k2,
+        qm: commit(&tau_g1, &qm),
+        ql: commit(&tau_g1, &ql),
+        qr: commit(&tau_g1, &qr),
+        qo: commit(&tau_g1, &qo),
+        qc: commit(&tau_g1, &qc),
+        s1: sigma_commitment(0),
+        s2: sigma_commitment(1),
+        s3: sigma_commitment(2),
+        x_2: ptau.tau_g2[1]
+    };
+
+    Ok(ProvingKey {
+        circuit,
+        domain_size: domain.size,
+        qm,
+        ql,
+        qr,
+        qo,
+        qc,
+        sigma,
+        tau_g1,
+        vk
+    })
+
+}
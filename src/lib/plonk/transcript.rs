@@ -0,0 +1,48 @@
+use curve::bn254::binary;
+use curve::bn254::G1Affine;
+use field::bigint::U256;
+use field::bn254::Fr;
+use field::PrimeField;
+use keccak;
+
+///
+/// Fiat-Shamir transcript of snarkjs: points in uncompressed form and scalars as 32 bytes big
+/// endian integers are hashed with Keccak-256, the digest being read as a big endian integer and
+/// reduced modulo `r`.
+///
+/// Drawing a challenge clears the transcript, the next rounds starting from the previous challenges.
+///
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    data: Vec<u8>
+}
+
+impl Transcript {
+
+    pub fn new() -> Transcript {
+        Transcript {
+            data: Vec::new()
+        }
+    }
+
+    pub fn add_point(& mut self, point: & G1Affine) {
+        binary::push_g1_uncompressed(& mut self.data, point);
+    }
+
+    pub fn add_scalar(& mut self, scalar: & Fr) {
+        let mut bytes = scalar.to_le_bytes();
+        bytes.reverse();
+        self.data.extend_from_slice(&bytes);
+    }
+
+    pub fn challenge(& mut self) -> Fr {
+
+        let mut digest = keccak::hash(&self.data);
+        digest.reverse();
+        self.data.clear();
+
+        Fr::from_u256(&U256::from_le_bytes(&digest))
+
+    }
+
+}
@@ -0,0 +1,154 @@
+use curve::bn254::pairing;
+use curve::bn254::{G1Affine, G1Params, G2Params};
+use curve::{multi_exp, CurveParams};
+use field::bn254::Fr;
+use field::PrimeField;
+use plonk::transcript::Transcript;
+use plonk::{PlonkError, Proof, VerifyingKey};
+
+struct Challenges {
+    beta: Fr,
+    gamma: Fr,
+    alpha: Fr,
+    xi: Fr,
+    v: Fr,
+    u: Fr
+}
+
+fn challenges(vk: & VerifyingKey, public: & [Fr], proof: & Proof) -> Challenges {
+
+    let mut transcript = Transcript::new();
+
+    for point in &[vk.qm, vk.ql, vk.qr, vk.qo, vk.qc, vk.s1, vk.s2, vk.s3] {
+        transcript.add_point(point);
+    }
+    for value in public {
+        transcript.add_scalar(value);
+    }
+    transcript.add_point(&proof.a);
+    transcript.add_point(&proof.b);
+    transcript.add_point(&proof.c);
+    let beta = transcript.challenge();
+
+    transcript.add_scalar(&beta);
+    let gamma = transcript.challenge();
+
+    transcript.add_scalar(&beta);
+    transcript.add_scalar(&gamma);
+    transcript.add_point(&proof.z);
+    let alpha = transcript.challenge();
+
+    transcript.add_scalar(&alpha);
+    transcript.add_point(&proof.t1);
+    transcript.add_point(&proof.t2);
+    transcript.add_point(&proof.t3);
+    let xi = transcript.challenge();
+
+    transcript.add_scalar(&xi);
+    for value in &[proof.eval_a, proof.eval_b, proof.eval_c, proof.eval_s1, proof.eval_s2, proof.eval_zw] {
+        transcript.add_scalar(value);
+    }
+    let v = transcript.challenge();
+
+    transcript.add_point(&proof.wxi);
+    transcript.add_point(&proof.wxiw);
+    let u = transcript.challenge();
+
+    Challenges {
+        beta,
+        gamma,
+        alpha,
+        xi,
+        v,
+        u
+    }
+
+}
+
+///
+/// Checks a proof with the equations of the snarkjs verifier: the commitment to the linearisation
+/// polynomial is rebuilt from the key and the proof, and the openings at `xi` and `xi * w` are
+/// checked by a single pairing equation. Proofs whose points are not on the curve are rejected.
+///
+pub fn verify(vk: & VerifyingKey, public: & [Fr], proof: & Proof) -> Result<bool, PlonkError> {
+
+    if public.len() != vk.n_public {
+        return Err(PlonkError::PublicSignalCount { expected: vk.n_public, found: public.len() });
+    }
+
+    let points = [proof.a, proof.b, proof.c, proof.z, proof.t1, proof.t2, proof.t3, proof.wxi, proof.wxiw];
+    if !points.iter().all(|point| point.is_on_curve()) {
+        return Ok(false);
+    }
+
+    let Challenges { beta, gamma, alpha, xi, v, u } = challenges(vk, public, proof);
+
+    let mut xi_n = xi;
+    for _ in 0..vk.power {
+        xi_n = xi_n.square();
+    }
+    let vanishing = xi_n - Fr::one();
+
+    // L_(i+1)(xi) = w^i * (xi^n - 1) / (n * (xi - w^i)) for the rows of the public signals.
+    let n = Fr::from_u64(vk.domain_size() as u64);
+    let omega = vk.omega();
+    let mut lagrange = Vec::with_capacity(vk.n_public.max(1));
+    let mut power = Fr::one();
+    for _ in 0..vk.n_public.max(1) {
+        let denominator = match (n * (xi - power)).inverse() {
+            Some(inverse) => inverse,
+            None => return Ok(false)
+        };
+        lagrange.push(power * vanishing * denominator);
+        power *= omega;
+    }
+
+    let pi = public.iter().zip(lagrange.iter()).fold(Fr::zero(), |acc, (signal, l)| acc - *signal * *l);
+    let alpha_2 = alpha.square();
+
+    let permutation_s = alpha * (proof.eval_a + beta * proof.eval_s1 + gamma) * (proof.eval_b + beta * proof.eval_s2 + gamma) * proof.eval_zw;
+    let r0 = pi - lagrange[0] * alpha_2 - permutation_s * (proof.eval_c + gamma);
+
+    let beta_xi = beta * xi;
+    let permutation_z = alpha * (proof.eval_a + beta_xi + gamma) * (proof.eval_b + beta_xi * vk.k1 + gamma) * (proof.eval_c + beta_xi * vk.k2 + gamma)
+        + lagrange[0] * alpha_2
+        + u;
+
+    let mut powers = vec!(v);
+    for idx in 1..5 {
+        powers.push(powers[idx - 1] * v);
+    }
+
+    // F = D + v * A + v^2 * B + v^3 * C + v^4 * S1 + v^5 * S2, D being the commitment to the non
+    // constant part of the linearisation polynomial plus u * Z.
+    let bases: Vec<G1Affine> = vec!(
+        vk.qm, vk.ql, vk.qr, vk.qo, vk.qc, proof.z, vk.s3, proof.t1, proof.t2, proof.t3,
+        proof.a, proof.b, proof.c, vk.s1, vk.s2
+    );
+    let scalars: Vec<Fr> = vec!(
+        proof.eval_a * proof.eval_b, proof.eval_a, proof.eval_b, proof.eval_c, Fr::one(),
+        permutation_z, -(permutation_s * beta), -vanishing, -(vanishing * xi_n), -(vanishing * xi_n.square()),
+        powers[0], powers[1], powers[2], powers[3], powers[4]
+    );
+    let f = multi_exp(&bases, &scalars);
+
+    let e = -r0
+        + powers[0] * proof.eval_a
+        + powers[1] * proof.eval_b
+        + powers[2] * proof.eval_c
+        + powers[3] * proof.eval_s1
+        + powers[4] * proof.eval_s2
+        + u * proof.eval_zw;
+
+    let a1 = proof.wxi.to_projective() + proof.wxiw.mul(&u.to_u256());
+    let b1 = proof.wxi.mul(&xi.to_u256())
+        + proof.wxiw.mul(&(u * xi * omega).to_u256())
+        + f
+        - G1Params::generator().mul(&e.to_u256());
+
+    Ok(pairing::pairing_check(&[
+        (-a1.to_affine(), vk.x_2),
+        (b1.to_affine(), G2Params::generator())
+    ]))
+
+}
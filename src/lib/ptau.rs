@@ -0,0 +1,433 @@
+use binfile::{self, BinFile, BinFileError, SectionReader};
+use curve::bn254::binary::{self, G1_SIZE, G2_SIZE};
+use curve::bn254::pairing;
+use curve::bn254::{G1, G1Affine, G2, G2Affine};
+use curve::{self, multi_exp};
+use field::bn254::{Fq, Fr};
+use field::domain::Domain;
+use rng::Rng;
+
+///
+/// snarkjs `.ptau` powers of tau file (version 1), the universal phase 1 of the BN254 setups:
+///
+/// - section 1, header: `n8:u32 q[n8] power:u32 ceremony_power:u32`
+/// - section 2: `tau^i * G1` for `i < 2^(power + 1) - 1`
+/// - section 3: `tau^i * G2` for `i < 2^power`
+/// - section 4: `alpha * tau^i * G1` for `i < 2^power`
+/// - section 5: `beta * tau^i * G1` for `i < 2^power`
+/// - section 6: `beta * G2`
+/// - section 7: contributions of the ceremony, kept as is
+/// - sections 12 to 15, present once prepared for phase 2: the Lagrange bases matching sections 2
+///   to 5, for every domain of size `2^k` with `k <= power`, the smallest domains first
+///
+/// Points are encoded as described in `curve::bn254::binary`.
+///
+pub const MAGIC: & [u8; 4] = b"ptau";
+pub const VERSION: u32 = 1;
+
+const HEADER_SECTION: u32 = 1;
+const TAU_G1_SECTION: u32 = 2;
+const TAU_G2_SECTION: u32 = 3;
+const ALPHA_TAU_G1_SECTION: u32 = 4;
+const BETA_TAU_G1_SECTION: u32 = 5;
+const BETA_G2_SECTION: u32 = 6;
+const CONTRIBUTIONS_SECTION: u32 = 7;
+const LAGRANGE_TAU_G1_SECTION: u32 = 12;
+const LAGRANGE_TAU_G2_SECTION: u32 = 13;
+const LAGRANGE_ALPHA_TAU_G1_SECTION: u32 = 14;
+const LAGRANGE_BETA_TAU_G1_SECTION: u32 = 15;
+
+///
+/// Lagrange bases of a prepared file, concatenated from the smallest domain to the largest.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LagrangeBases {
+    pub tau_g1: Vec<G1Affine>,
+    pub tau_g2: Vec<G2Affine>,
+    pub alpha_tau_g1: Vec<G1Affine>,
+    pub beta_tau_g1: Vec<G1Affine>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ptau {
+    pub power: usize,
+    pub ceremony_power: usize,
+    pub tau_g1: Vec<G1Affine>,
+    pub tau_g2: Vec<G2Affine>,
+    pub alpha_tau_g1: Vec<G1Affine>,
+    pub beta_tau_g1: Vec<G1Affine>,
+    pub beta_g2: G2Affine,
+    pub contributions: Vec<u8>,
+    pub lagrange: Option<LagrangeBases>
+}
+
+///
+/// Powers of tau whose points do not encode consistent powers.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InconsistentPtau(pub &'static str);
+
+impl std::fmt::Display for InconsistentPtau {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "The {} of the powers of tau are inconsistent", self.0)
+    }
+}
+
+fn g1_section(points: & [G1Affine]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(points.len() * G1_SIZE);
+    for point in points {
+        binary::push_g1(& mut data, point);
+    }
+    data
+}
+
+fn g2_section(points: & [G2Affine]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(points.len() * G2_SIZE);
+    for point in points {
+        binary::push_g2(& mut data, point);
+    }
+    data
+}
+
+fn powers_of<C: curve::CurveParams>(base: & curve::Projective<C>, x: & Fr, count: usize) -> Vec<curve::Affine<C>> {
+
+    let mut points = Vec::with_capacity(count);
+    let mut point = *base;
+
+    for _ in 0..count {
+        points.push(point.to_affine());
+        point = point.mul_scalar(x);
+    }
+
+    points
+
+}
+
+///
+/// Checks that `points[i + 1] = x * points[i]` where `x_g2 = x * G2`, on a random linear combination.
+///
+fn consecutive_powers<R: Rng>(points: & [G1Affine], x_g2: & G2Affine, rng: & mut R) -> bool {
+
+    if points.len() < 2 {
+        return true;
+    }
+
+    let factors: Vec<Fr> = (0..points.len() - 1).map(|_| rng.field()).collect();
+    let lower = multi_exp(&points[..points.len() - 1], &factors);
+    let upper = multi_exp(&points[1..], &factors);
+
+    pairing::pairing_check(&[(-upper.to_affine(), G2::generator().to_affine()), (lower.to_affine(), *x_g2)])
+
+}
+
+impl Ptau {
+
+    ///
+    /// Powers of tau from a single party drawing the secrets from `rng`. The result is only as
+    /// trustworthy as that party: meant for tests and development, production setups use the
+    /// output of a ceremony.
+    ///
+    pub fn generate<R: Rng>(power: usize, rng: & mut R) -> Ptau {
+
+        let tau: Fr = rng.nonzero_field();
+        let alpha: Fr = rng.nonzero_field();
+        let beta: Fr = rng.nonzero_field();
+
+        let size = 1 << power;
+
+        Ptau {
+            power,
+            ceremony_power: power,
+            tau_g1: powers_of(&G1::generator(), &tau, 2 * size - 1),
+            tau_g2: powers_of(&G2::generator(), &tau, size),
+            alpha_tau_g1: powers_of(&G1::generator().mul_scalar(&alpha), &tau, size),
+            beta_tau_g1: powers_of(&G1::generator().mul_scalar(&beta), &tau, size),
+            beta_g2: G2::generator().mul_scalar(&beta).to_affine(),
+            contributions: 0u32.to_le_bytes().to_vec(),
+            lagrange: None
+        }
+
+    }
+
+    ///
+    /// Computes the Lagrange bases snarkjs stores in prepared files.
+    ///
+    pub fn prepare(& mut self) {
+
+        let mut lagrange = LagrangeBases {
+            tau_g1: Vec::new(),
+            tau_g2: Vec::new(),
+            alpha_tau_g1: Vec::new(),
+            beta_tau_g1: Vec::new()
+        };
+
+        for log_size in 0..=self.power {
+            let domain = Domain::<Fr>::new(1 << log_size).unwrap();
+            lagrange.tau_g1.extend(curve::lagrange_basis(&self.tau_g1, &domain));
+            lagrange.tau_g2.extend(curve::lagrange_basis(&self.tau_g2, &domain));
+            lagrange.alpha_tau_g1.extend(curve::lagrange_basis(&self.alpha_tau_g1, &domain));
+            lagrange.beta_tau_g1.extend(curve::lagrange_basis(&self.beta_tau_g1, &domain));
+        }
+
+        self.lagrange = Some(lagrange);
+
+    }
+
+    fn lagrange_g1(&self, points: & [G1Affine], prepared: Option<& Vec<G1Affine>>, size: usize) -> Vec<G1Affine> {
+        match prepared {
+            Some(prepared) => prepared[size - 1..2 * size - 1].to_vec(),
+            None => curve::lagrange_basis(points, &Domain::<Fr>::new(size).unwrap())
+        }
+    }
+
+    ///
+    /// `L_i(tau) * G1` for the Lagrange polynomials of the domain of `size` elements, which must be at
+    /// most `2^power`.
+    ///
+    pub fn lagrange_tau_g1(&self, size: usize) -> Vec<G1Affine> {
+        self.lagrange_g1(&self.tau_g1, self.lagrange.as_ref().map(|lagrange| &lagrange.tau_g1), size)
+    }
+
+    ///
+    /// `alpha * L_i(tau) * G1`, `size` being at most `2^power`.
+    ///
+    pub fn lagrange_alpha_tau_g1(&self, size: usize) -> Vec<G1Affine> {
+        self.lagrange_g1(&self.alpha_tau_g1, self.lagrange.as_ref().map(|lagrange| &lagrange.alpha_tau_g1), size)
+    }
+
+    ///
+    /// `beta * L_i(tau) * G1`, `size` being at most `2^power`.
+    ///
+    pub fn lagrange_beta_tau_g1(&self, size: usize) -> Vec<G1Affine> {
+        self.lagrange_g1(&self.beta_tau_g1, self.lagrange.as_ref().map(|lagrange| &lagrange.beta_tau_g1), size)
+    }
+
+    ///
+    /// `L_i(tau) * G2`, `size` being at most `2^power`.
+    ///
+    pub fn lagrange_tau_g2(&self, size: usize) -> Vec<G2Affine> {
+        match &self.lagrange {
+            Some(lagrange) => lagrange.tau_g2[size - 1..2 * size - 1].to_vec(),
+            None => curve::lagrange_basis(&self.tau_g2, &Domain::<Fr>::new(size).unwrap())
+        }
+    }
+
+    ///
+    /// Checks that the points are powers of the same `tau`, with the same `alpha` and `beta`, on
+    /// random linear combinations drawn from `rng`. The contributions that led to them are not
+    /// checked.
+    ///
+    pub fn check<R: Rng>(&self, rng: & mut R) -> Result<(), InconsistentPtau> {
+
+        let g1 = G1::generator().to_affine();
+        let g2 = G2::generator().to_affine();
+
+        if self.tau_g1[0] != g1 || self.tau_g2[0] != g2 {
+            return Err(InconsistentPtau("generators"));
+        }
+
+        let tau_g2 = self.tau_g2.get(1).copied().unwrap_or(g2);
+
+        if !consecutive_powers(&self.tau_g1, &tau_g2, rng) {
+            return Err(InconsistentPtau("tau G1 powers"));
+        }
+        if !consecutive_powers(&self.alpha_tau_g1, &tau_g2, rng) {
+            return Err(InconsistentPtau("alpha tau G1 powers"));
+        }
+        if !consecutive_powers(&self.beta_tau_g1, &tau_g2, rng) {
+            return Err(InconsistentPtau("beta tau G1 powers"));
+        }
+
+        let factors: Vec<Fr> = (0..self.tau_g2.len()).map(|_| rng.field()).collect();
+        let g1_side = multi_exp(&self.tau_g1[..self.tau_g2.len()], &factors).to_affine();
+        let g2_side = multi_exp(&self.tau_g2, &factors).to_affine();
+        if !pairing::pairing_check(&[(-g1_side, g2), (g1, g2_side)]) {
+            return Err(InconsistentPtau("tau G2 powers"));
+        }
+
+        if !pairing::pairing_check(&[(-self.beta_tau_g1[0], g2), (g1, self.beta_g2)]) {
+            return Err(InconsistentPtau("beta points"));
+        }
+
+        if let Some(lagrange) = &self.lagrange {
+            let mut expected = self.clone();
+            expected.prepare();
+            if expected.lagrange.as_ref() != Some(lagrange) {
+                return Err(InconsistentPtau("Lagrange bases"));
+            }
+        }
+
+        Ok(())
+
+    }
+
+    pub fn to_binfile(&self) -> BinFile {
+
+        let mut file = BinFile::new(MAGIC, VERSION);
+
+        let mut header: Vec<u8> = Vec::new();
+        binfile::push_u32(& mut header, binfile::field_size::<Fq>() as u32);
+        binfile::push_prime::<Fq>(& mut header);
+        binfile::push_u32(& mut header, self.power as u32);
+        binfile::push_u32(& mut header, self.ceremony_power as u32);
+        file.add_section(HEADER_SECTION, header);
+
+        file.add_section(TAU_G1_SECTION, g1_section(&self.tau_g1));
+        file.add_section(TAU_G2_SECTION, g2_section(&self.tau_g2));
+        file.add_section(ALPHA_TAU_G1_SECTION, g1_section(&self.alpha_tau_g1));
+        file.add_section(BETA_TAU_G1_SECTION, g1_section(&self.beta_tau_g1));
+        file.add_section(BETA_G2_SECTION, g2_section(&[self.beta_g2]));
+        file.add_section(CONTRIBUTIONS_SECTION, self.contributions.clone());
+
+        if let Some(lagrange) = &self.lagrange {
+            file.add_section(LAGRANGE_TAU_G1_SECTION, g1_section(&lagrange.tau_g1));
+            file.add_section(LAGRANGE_TAU_G2_SECTION, g2_section(&lagrange.tau_g2));
+            file.add_section(LAGRANGE_ALPHA_TAU_G1_SECTION, g1_section(&lagrange.alpha_tau_g1));
+            file.add_section(LAGRANGE_BETA_TAU_G1_SECTION, g1_section(&lagrange.beta_tau_g1));
+        }
+
+        file
+
+    }
+
+    pub fn from_binfile(file: & BinFile) -> Result<Ptau, BinFileError> {
+
+        if file.version != VERSION {
+            return Err(BinFileError::FormatError(format!("unsupported ptau version {}", file.version)));
+        }
+
+        let mut header = SectionReader::new(file.required_section(HEADER_SECTION)?);
+        let n8 = header.read_u32()? as usize;
+        header.expect_prime::<Fq>(n8)?;
+        let power = header.read_u32()? as usize;
+        let ceremony_power = header.read_u32()? as usize;
+
+        if power > 28 {
+            return Err(BinFileError::FormatError(format!("power {} exceeds the two-adicity of the field", power)));
+        }
+
+        let size = 1 << power;
+
+        let g1_points = |section: u32, count: usize| {
+            binary::read_g1_points(& mut SectionReader::new(file.required_section(section)?), count)
+        };
+        let g2_points = |section: u32, count: usize| {
+            binary::read_g2_points(& mut SectionReader::new(file.required_section(section)?), count)
+        };
+
+        let lagrange = if file.section(LAGRANGE_TAU_G1_SECTION).is_some() {
+            Some(LagrangeBases {
+                tau_g1: g1_points(LAGRANGE_TAU_G1_SECTION, 2 * size - 1)?,
+                tau_g2: g2_points(LAGRANGE_TAU_G2_SECTION, 2 * size - 1)?,
+                alpha_tau_g1: g1_points(LAGRANGE_ALPHA_TAU_G1_SECTION, 2 * size - 1)?,
+                beta_tau_g1: g1_points(LAGRANGE_BETA_TAU_G1_SECTION, 2 * size - 1)?
+            })
+        } else {
+            None
+        };
+
+        Ok(Ptau {
+            power,
+            ceremony_power,
+            tau_g1: g1_points(TAU_G1_SECTION, 2 * size - 1)?,
+            tau_g2: g2_points(TAU_G2_SECTION, size)?,
+            alpha_tau_g1: g1_points(ALPHA_TAU_G1_SECTION, size)?,
+            beta_tau_g1: g1_points(BETA_TAU_G1_SECTION, size)?,
+            beta_g2: g2_points(BETA_G2_SECTION, 1)?[0],
+            contributions: file.section(CONTRIBUTIONS_SECTION).map(|data| data.to_vec()).unwrap_or_default(),
+            lagrange
+        })
+
+    }
+
+    pub fn write<W: std::io::Write>(&self, writer: & mut W) -> std::io::Result<()> {
+        self.to_binfile().write(writer)
+    }
+
+    pub fn read<R: std::io::Read>(reader: R) -> Result<Ptau, BinFileError> {
+        Ptau::from_binfile(&BinFile::read(reader, MAGIC)?)
+    }
+
+    pub fn save(&self, path: & std::path::Path) -> std::io::Result<()> {
+        self.to_binfile().save(path)
+    }
+
+    pub fn load(path: & std::path::Path) -> Result<Ptau, BinFileError> {
+        Ptau::from_binfile(&BinFile::load(path, MAGIC)?)
+    }
+
+}
+
+#[cfg(test)]
+mod ptau_test {
+
+    use curve::bn254::{G1, G2};
+    use field::bn254::Fr;
+    use field::domain::Domain;
+    use field::PrimeField;
+        use ptau::{InconsistentPtau, Ptau};
+    use rng::{Rng, TestRng};
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_ptau() {
+
+        let mut rng = TestRng::new(5);
+        let mut ptau = Ptau::generate(2, & mut rng);
+
+        expect_that!(&ptau.tau_g1.len(), is(eq(7)));
+        expect_that!(&ptau.check(& mut rng), is(eq(Ok(()))));
+
+        let mut data: Vec<u8> = Vec::new();
+        ptau.write(& mut data).unwrap();
+        expect_that!(&Ptau::read(data.as_slice()).unwrap(), is(eq(ptau.clone())));
+
+        let lagrange = ptau.lagrange_tau_g1(4);
+        ptau.prepare();
+        expect_that!(&ptau.lagrange_tau_g1(4), is(eq(lagrange.clone())));
+        expect_that!(&ptau.lagrange.as_ref().unwrap().tau_g1.len(), is(eq(7)));
+        expect_that!(&ptau.check(& mut rng), is(eq(Ok(()))));
+
+        let mut data: Vec<u8> = Vec::new();
+        ptau.write(& mut data).unwrap();
+        expect_that!(&Ptau::read(data.as_slice()).unwrap(), is(eq(ptau.clone())));
+
+        // sum(L_i(tau)) = 1 and sum(w^i * L_i(tau)) = tau.
+        let domain = Domain::<Fr>::new(4).unwrap();
+        let ones = vec!(Fr::one(); 4);
+        expect_that!(&::curve::multi_exp(&lagrange, &ones).to_affine(), is(eq(G1::generator().to_affine())));
+        expect_that!(&::curve::multi_exp(&lagrange, &domain.elements()).to_affine(), is(eq(ptau.tau_g1[1])));
+
+        let mut tampered = ptau.clone();
+        tampered.tau_g1[3] = G1::generator().mul_scalar(&rng.field::<Fr>()).to_affine();
+        expect_that!(&tampered.check(& mut rng), is(eq(Err(InconsistentPtau("tau G1 powers")))));
+
+        let mut tampered = ptau.clone();
+        tampered.beta_g2 = G2::generator().to_affine();
+        expect_that!(&tampered.check(& mut rng), is(eq(Err(InconsistentPtau("beta points")))));
+
+    }
+
+    #[test]
+    #[ignore = "needs the fixtures written by src/lib/parser/test_material/snarkjs/generate.sh"]
+    fn test_snarkjs_ptau() {
+
+        let ptau = Ptau::load(std::path::Path::new("./src/lib/parser/test_material/snarkjs/pot8.ptau")).unwrap();
+
+        expect_that!(&(ptau.power, ptau.ceremony_power), is(eq((8, 8))));
+        expect_that!(&ptau.tau_g1.len(), is(eq(511)));
+
+        let mut rng = TestRng::new(10);
+        expect_that!(&ptau.check(& mut rng), is(eq(Ok(()))));
+
+        // The bases snarkjs prepared are the ones computed from the powers
+        let mut unprepared = ptau.clone();
+        unprepared.lagrange = None;
+        expect_that!(&ptau.lagrange.is_some(), is(eq(true)));
+        expect_that!(&ptau.lagrange_tau_g1(32), is(eq(unprepared.lagrange_tau_g1(32))));
+        expect_that!(&ptau.lagrange_tau_g2(32), is(eq(unprepared.lagrange_tau_g2(32))));
+
+    }
+
+}
@@ -0,0 +1,219 @@
+//
+// Randomness for the setups and the provers.
+//
+// Setups and proofs take the generator as a parameter so that they can be reproduced in tests with
+// `TestRng`; anything meant to be used for real must draw from `OsRng`.
+//
+
+use std::io::Read;
+
+use field::bigint::U256;
+use field::PrimeField;
+
+pub trait Rng {
+
+    fn next_u64(& mut self) -> u64;
+
+    ///
+    /// Uniform field element, by rejection sampling of integers of the bit size of the modulus.
+    ///
+    fn field<F: PrimeField>(& mut self) -> F where Self: Sized {
+
+        let modulus = F::modulus();
+        let bits = modulus.bits();
+
+        loop {
+
+            let mut limbs = [0u64; 4];
+            for (idx, limb) in limbs.iter_mut().enumerate() {
+                let used = bits.saturating_sub(64 * idx).min(64);
+                *limb = match used {
+                    0 => 0,
+                    64 => self.next_u64(),
+                    used => self.next_u64() & ((1 << used) - 1)
+                };
+            }
+
+            let value = U256(limbs);
+            if value < modulus {
+                return F::from_u256(&value);
+            }
+
+        }
+
+    }
+
+    ///
+    /// Uniform non zero field element.
+    ///
+    fn nonzero_field<F: PrimeField>(& mut self) -> F where Self: Sized {
+        loop {
+            let value: F = self.field();
+            if !value.is_zero() {
+                return value;
+            }
+        }
+    }
+
+}
+
+///
+/// Deterministic SplitMix64 generator, for tests and reproducible outputs only.
+///
+#[derive(Debug, Clone)]
+pub struct TestRng {
+    state: u64
+}
+
+impl TestRng {
+
+    pub fn new(seed: u64) -> TestRng {
+        TestRng {
+            state: seed
+        }
+    }
+
+}
+
+impl Rng for TestRng {
+    fn next_u64(& mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+///
+/// Generator reading from the operating system (`/dev/urandom`).
+///
+#[derive(Debug)]
+pub struct OsRng {
+    source: std::fs::File
+}
+
+impl OsRng {
+
+    pub fn new() -> std::io::Result<OsRng> {
+        Ok(OsRng {
+            source: std::fs::File::open("/dev/urandom")?
+        })
+    }
+
+}
+
+impl Rng for OsRng {
+    fn next_u64(& mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.source.read_exact(& mut bytes).expect("Cannot read /dev/urandom");
+        u64::from_le_bytes(bytes)
+    }
+}
+
+///
+/// ChaCha20 block function keyed by `seed`, with a 64 bits block counter and a zero nonce, as the
+/// `ChaCha` of ffjavascript which snarkjs seeds from transcript hashes. `next_u64` takes the high
+/// word first, like ffjavascript.
+///
+#[derive(Debug, Clone)]
+pub struct ChaCha {
+    state: [u32; 16],
+    block: [u32; 16],
+    idx: usize
+}
+
+fn quarter_round(state: & mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+impl ChaCha {
+
+    pub fn new(seed: & [u32; 8]) -> ChaCha {
+
+        let mut state = [0u32; 16];
+        state[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+        state[4..12].copy_from_slice(seed);
+
+        ChaCha {
+            state,
+            block: [0; 16],
+            idx: 16
+        }
+
+    }
+
+    fn update(& mut self) {
+
+        self.block = self.state;
+        for _ in 0..10 {
+            quarter_round(& mut self.block, 0, 4, 8, 12);
+            quarter_round(& mut self.block, 1, 5, 9, 13);
+            quarter_round(& mut self.block, 2, 6, 10, 14);
+            quarter_round(& mut self.block, 3, 7, 11, 15);
+            quarter_round(& mut self.block, 0, 5, 10, 15);
+            quarter_round(& mut self.block, 1, 6, 11, 12);
+            quarter_round(& mut self.block, 2, 7, 8, 13);
+            quarter_round(& mut self.block, 3, 4, 9, 14);
+        }
+        for (word, initial) in self.block.iter_mut().zip(self.state.iter()) {
+            *word = word.wrapping_add(*initial);
+        }
+
+        self.idx = 0;
+        self.state[12] = self.state[12].wrapping_add(1);
+        if self.state[12] == 0 {
+            self.state[13] = self.state[13].wrapping_add(1);
+        }
+
+    }
+
+    pub fn next_u32(& mut self) -> u32 {
+        if self.idx == 16 {
+            self.update();
+        }
+        self.idx += 1;
+        self.block[self.idx - 1]
+    }
+
+}
+
+impl Rng for ChaCha {
+    fn next_u64(& mut self) -> u64 {
+        let high = self.next_u32() as u64;
+        (high << 32) | self.next_u32() as u64
+    }
+}
+
+#[cfg(test)]
+mod rng_test {
+
+    use rng::ChaCha;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_chacha() {
+
+        // First keystream block of ChaCha20 with a zero key and nonce, RFC 8439 appendix A.1
+        let expected = [
+            0x76, 0xb8, 0xe0, 0xad, 0xa0, 0xf1, 0x3d, 0x90, 0x40, 0x5d, 0x6a, 0xe5, 0x53, 0x86, 0xbd, 0x28,
+            0xbd, 0xd2, 0x19, 0xb8, 0xa0, 0x8d, 0xed, 0x1a, 0xa8, 0x36, 0xef, 0xcc, 0x8b, 0x77, 0x0d, 0xc7,
+            0xda, 0x41, 0x59, 0x7c, 0x51, 0x57, 0x48, 0x8d, 0x77, 0x24, 0xe0, 0x3f, 0xb8, 0xd8, 0x4a, 0x37,
+            0x6a, 0x43, 0xb8, 0xf4, 0x15, 0x18, 0xa1, 0x1c, 0xc3, 0x87, 0xb6, 0x69, 0xb2, 0xee, 0x65, 0x86u8
+        ];
+
+        let mut chacha = ChaCha::new(&[0; 8]);
+        let keystream: Vec<u8> = (0..16).flat_map(|_| chacha.next_u32().to_le_bytes().to_vec()).collect();
+        expect_that!(&keystream.as_slice(), is(eq(&expected[..])));
+
+    }
+
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use field::PrimeField;
+use witness::InputValue;
+
+///
+/// Errors raised while reading an `input.json` file. Checking the inputs against the circuit is done
+/// by `witness::check_inputs`.
+///
+#[derive(Debug)]
+pub enum InputError {
+    IOError(std::io::Error),
+    JsonError(serde_json::Error),
+    InvalidValue(std::string::String, std::string::String)
+}
+
+impl std::fmt::Display for InputError {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InputError::IOError(error) => write!(formatter, "{}", error),
+            InputError::JsonError(error) => write!(formatter, "Invalid JSON: {}", error),
+            InputError::InvalidValue(name, reason) => write!(formatter, "Invalid value for input {}: {}", name, reason)
+        }
+    }
+}
+
+fn parse_value<F: PrimeField>(name: & str, value: & serde_json::Value) -> Result<InputValue<F>, InputError> {
+    match value {
+        serde_json::Value::Number(number) => {
+            if let Some(value) = number.as_u64() {
+                Ok(InputValue::Scalar(F::from_u64(value)))
+            } else if let Some(value) = number.as_i64() {
+                Ok(InputValue::Scalar(-F::from_u64(value.unsigned_abs())))
+            } else {
+                Err(InputError::InvalidValue(name.to_string(), format!("{} is not an integer, large values must be given as strings", number)))
+            }
+        },
+        serde_json::Value::String(string) => match F::parse(string) {
+            Some(value) => Ok(InputValue::Scalar(value)),
+            None => Err(InputError::InvalidValue(name.to_string(), format!("{:?} is not a decimal or hexadecimal number", string)))
+        },
+        serde_json::Value::Array(values) => values.iter().enumerate()
+            .map(|(index, value)| parse_value(&format!("{}[{}]", name, index), value))
+            .collect::<Result<Vec<InputValue<F>>, InputError>>()
+            .map(InputValue::Array),
+        _ => Err(InputError::InvalidValue(name.to_string(), "expected a number, a string or an array".to_string()))
+    }
+}
+
+///
+/// Parses circom style inputs: a JSON object mapping the input signals of the main component to
+/// their values. Values are numbers, strings holding a decimal (possibly negative) or `0x`
+/// hexadecimal number, or nested arrays of those.
+///
+pub fn parse_inputs<F: PrimeField>(json: & str) -> Result<HashMap<std::string::String, InputValue<F>>, InputError> {
+
+    let document: serde_json::Value = serde_json::from_str(json).map_err(InputError::JsonError)?;
+
+    let object = match document.as_object() {
+        Some(object) => object,
+        None => return Err(InputError::InvalidValue("".to_string(), "expected an object mapping input names to values".to_string()))
+    };
+
+    object.iter()
+        .map(|(name, value)| parse_value(name, value).map(|value| (name.clone(), value)))
+        .collect()
+
+}
+
+pub fn load_inputs<F: PrimeField>(path: & std::path::Path) -> Result<HashMap<std::string::String, InputValue<F>>, InputError> {
+    parse_inputs(&std::fs::read_to_string(path).map_err(InputError::IOError)?)
+}
+
+#[cfg(test)]
+mod input_test {
+
+    use field::PrimeField;
+    use field::bn254::Fr;
+    use witness::InputValue;
+    use witness::input;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_parse_inputs() {
+
+        let inputs = input::parse_inputs::<Fr>(r#"{
+            "a": 12,
+            "b": "-1",
+            "c": "0xff",
+            "d": [["1", 2], [3, "21888242871839275222246405745257275088548364400416034343698204186575808495618"]]
+        }"#).unwrap();
+
+        let scalar = |value: u64| InputValue::Scalar(Fr::from_u64(value));
+
+        expect_that!(&inputs.len(), is(eq(4)));
+        expect_that!(&inputs["a"], is(eq(scalar(12))));
+        expect_that!(&inputs["b"], is(eq(InputValue::Scalar(-Fr::one()))));
+        expect_that!(&inputs["c"], is(eq(scalar(255))));
+        expect_that!(&inputs["d"], is(eq(InputValue::Array(vec!(
+            InputValue::Array(vec!(scalar(1), scalar(2))),
+            InputValue::Array(vec!(scalar(3), scalar(1)))
+        )))));
+
+    }
+
+    #[test]
+    fn test_parse_invalid_inputs() {
+
+        let error = |json: & str| match input::parse_inputs::<Fr>(json) {
+            Err(input::InputError::InvalidValue(name, _)) => name,
+            other => panic!("Unexpected result {:?}", other)
+        };
+
+        expect_that!(&error(r#"{"a": 1.5}"#), is(eq("a".to_string())));
+        expect_that!(&error(r#"{"a": [1, "x"]}"#), is(eq("a[1]".to_string())));
+        expect_that!(&error(r#"{"a": {"b": 1}}"#), is(eq("a".to_string())));
+        expect_that!(&error(r#"[1, 2]"#), is(eq("".to_string())));
+
+        match input::parse_inputs::<Fr>("{") {
+            Err(input::InputError::JsonError(_)) => {},
+            other => panic!("Unexpected result {:?}", other)
+        }
+
+    }
+
+}
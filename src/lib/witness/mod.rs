@@ -0,0 +1,371 @@
+pub mod input;
+pub mod wtns;
+
+use std::collections::HashMap;
+
+use field::PrimeField;
+use elaborator::{ElaboratedCircuit, Location};
+
+///
+/// Value given to an input signal of the main component. Array inputs are nested arrays following
+/// the declared dimensions.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputValue<F> {
+    Scalar(F),
+    Array(Vec<InputValue<F>>)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WitnessError {
+    MissingInput(std::string::String),
+    UnknownInput(std::string::String),
+    InvalidInput(std::string::String, std::string::String),
+    ConstraintFailed { location: Box<Location>, left: std::string::String, right: std::string::String },
+    EvaluationError { location: Box<Location>, reason: std::string::String },
+    UnassignedSignal(std::string::String)
+}
+
+impl std::fmt::Display for WitnessError {
+    fn fmt(&self, formatter: & mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            WitnessError::MissingInput(name) => write!(formatter, "No value given for input signal {}", name),
+            WitnessError::UnknownInput(name) => write!(formatter, "The main component has no input signal {}", name),
+            WitnessError::InvalidInput(name, reason) => write!(formatter, "Invalid value for input signal {}: {}", name, reason),
+            WitnessError::ConstraintFailed { location, left, right } => write!(formatter, "Constraint not satisfied at {}\n\tleft: {}\n\tright: {}", location, left, right),
+            WitnessError::EvaluationError { location, reason } => write!(formatter, "{} at {}", reason, location),
+            WitnessError::UnassignedSignal(name) => write!(formatter, "Signal {} is never assigned", name)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Task {
+    Assignment(usize),
+    Check(usize)
+}
+
+///
+/// Appends the values of an input of the given dimensions to `values`, in row major order. Multi
+/// dimensional inputs can also be given as a flat array, as snarkjs does.
+///
+pub fn flatten_input<F: PrimeField>(name: & str, dimensions: & [usize], input: & InputValue<F>, values: & mut Vec<F>) -> Result<(), WitnessError> {
+    match (dimensions.split_first(), input) {
+        (None, InputValue::Scalar(value)) => {
+            values.push(*value);
+            Ok(())
+        },
+        (Some((size, inner)), InputValue::Array(inputs)) => {
+            if *size == inputs.len() {
+                for (index, input) in inputs.iter().enumerate() {
+                    flatten_input(&format!("{}[{}]", name, index), inner, input, values)?;
+                }
+                return Ok(());
+            }
+            let flat = inputs.iter().all(|input| matches!(input, InputValue::Scalar(_)));
+            if flat && dimensions.iter().product::<usize>() == inputs.len() {
+                for input in inputs {
+                    flatten_input(name, &[], input, values)?;
+                }
+                return Ok(());
+            }
+            Err(WitnessError::InvalidInput(name.to_string(), format!("expected {} values, found {}", size, inputs.len())))
+        },
+        (None, InputValue::Array(_)) => Err(WitnessError::InvalidInput(name.to_string(), "expected a single value, found an array".to_string())),
+        (Some(_), InputValue::Scalar(_)) => Err(WitnessError::InvalidInput(name.to_string(), "expected an array, found a single value".to_string()))
+    }
+}
+
+///
+/// Maps the inputs onto the input signals of the main component.
+///
+fn input_values<F: PrimeField>(circuit: & ElaboratedCircuit<F>, inputs: & HashMap<std::string::String, InputValue<F>>) -> Result<Vec<Option<F>>, WitnessError> {
+
+    let mut values: Vec<Option<F>> = vec!(None; circuit.signals.len());
+
+    let mut names: Vec<& std::string::String> = inputs.keys().collect();
+    names.sort();
+
+    for name in names {
+        if !circuit.inputs().any(|(input, _)| input == name) {
+            return Err(WitnessError::UnknownInput(name.clone()));
+        }
+    }
+
+    for (name, tree) in circuit.inputs() {
+        match inputs.get(name) {
+            Some(input) => {
+                let mut flat: Vec<F> = Vec::new();
+                flatten_input(name, &tree.dimensions(), input, & mut flat)?;
+                for (signal, value) in tree.signals().into_iter().zip(flat) {
+                    values[signal] = Some(value);
+                }
+            },
+            None => return Err(WitnessError::MissingInput(name.clone()))
+        }
+    }
+
+    Ok(values)
+
+}
+
+///
+/// Checks that the inputs match the input signals of the main component: no missing or unknown
+/// input, and arrays of the declared dimensions.
+///
+pub fn check_inputs<F: PrimeField>(circuit: & ElaboratedCircuit<F>, inputs: & HashMap<std::string::String, InputValue<F>>) -> Result<(), WitnessError> {
+    input_values(circuit, inputs).map(|_| ())
+}
+
+///
+/// Computes all the signals of an elaborated circuit from the inputs of its main component.
+///
+/// Assignments run as soon as the signals they read are known, and each constraint is checked as
+/// soon as all its signals are known: the first unsatisfied one is reported with its location.
+///
+/// Returns the witness in wire order, wire 0 being the constant `1`.
+///
+pub fn calculate_witness<F: PrimeField>(circuit: & ElaboratedCircuit<F>, inputs: & HashMap<std::string::String, InputValue<F>>) -> Result<Vec<F>, WitnessError> {
+
+    let mut values = input_values(circuit, inputs)?;
+
+    let mut dependents: Vec<Vec<Task>> = vec!(Vec::new(); circuit.signals.len());
+    let mut pending: Vec<usize> = Vec::with_capacity(circuit.assignments.len() + circuit.constraints.len());
+    let mut ready: std::collections::VecDeque<Task> = std::collections::VecDeque::new();
+
+    let tasks = circuit.assignments.iter().enumerate()
+        .map(|(index, assignment)| (Task::Assignment(index), assignment.value.signals()))
+        .chain(circuit.constraints.iter().enumerate().map(|(index, check)| {
+            let mut signals = check.left.signals();
+            signals.extend(check.right.signals());
+            signals.sort_unstable();
+            signals.dedup();
+            (Task::Check(index), signals)
+        }));
+
+    for (task, signals) in tasks {
+        let unknown: Vec<usize> = signals.into_iter().filter(|signal| values[*signal].is_none()).collect();
+        for signal in &unknown {
+            dependents[*signal].push(task);
+        }
+        pending.push(unknown.len());
+        if unknown.is_empty() {
+            ready.push_back(task);
+        }
+    }
+
+    let call = |name: & str, arguments| circuit.call_function(name, arguments);
+
+    while let Some(task) = ready.pop_front() {
+
+        let resolved = match task {
+
+            Task::Assignment(index) => {
+                let assignment = &circuit.assignments[index];
+                let value = {
+                    let signal = |signal: usize| values[signal].unwrap();
+                    assignment.value.evaluate(&signal, &call)
+                };
+                match value {
+                    Ok(value) => values[assignment.signal] = Some(value),
+                    Err(reason) => return Err(WitnessError::EvaluationError {
+                        location: Box::new(circuit.location(assignment.component, assignment.span)),
+                        reason
+                    })
+                }
+                assignment.signal
+            },
+
+            Task::Check(index) => {
+                let check = &circuit.constraints[index];
+                let (left, right) = {
+                    let signal = |signal: usize| values[signal].unwrap();
+                    (check.left.evaluate(&signal, &call), check.right.evaluate(&signal, &call))
+                };
+                match (left, right) {
+                    (Ok(left), Ok(right)) => if left != right {
+                        return Err(WitnessError::ConstraintFailed {
+                            location: Box::new(circuit.location(check.component, check.span)),
+                            left: left.to_string(),
+                            right: right.to_string()
+                        });
+                    },
+                    (Err(reason), _) | (_, Err(reason)) => return Err(WitnessError::EvaluationError {
+                        location: Box::new(circuit.location(check.component, check.span)),
+                        reason
+                    })
+                }
+                continue ;
+            }
+
+        };
+
+        for dependent in std::mem::take(& mut dependents[resolved]) {
+            let position = match dependent {
+                Task::Assignment(index) => index,
+                Task::Check(index) => circuit.assignments.len() + index
+            };
+            pending[position] -= 1;
+            if pending[position] == 0 {
+                ready.push_back(dependent);
+            }
+        }
+
+    }
+
+    let mut witness: Vec<F> = vec!(F::zero(); circuit.n_wires());
+    witness[0] = F::one();
+
+    for (signal, value) in values.into_iter().enumerate() {
+        match value {
+            Some(value) => witness[circuit.wire_of(signal)] = value,
+            None => return Err(WitnessError::UnassignedSignal(circuit.signals[signal].name.clone()))
+        }
+    }
+
+    Ok(witness)
+
+}
+
+#[cfg(test)]
+mod witness_test {
+
+    use std::collections::HashMap;
+
+    use elaborator;
+    use field::PrimeField;
+    use field::bn254::Fr;
+    use parser::compile;
+    use witness;
+    use witness::{InputValue, WitnessError};
+    use witness::{input, wtns};
+
+    use galvanic_assert::matchers::*;
+
+    fn load(name: & str) -> elaborator::ElaboratedCircuit<Fr> {
+
+        let path = std::fs::canonicalize(format!("./src/lib/parser/test_material/witness/{}", name)).expect("Invalid Path");
+        let ctx = compile::build_context(&path);
+
+        elaborator::elaborate::<Fr>(&ctx).expect("Elaboration failed")
+
+    }
+
+    #[test]
+    fn test_calculate_witness() {
+
+        let circuit = load("num2bits.circom");
+        let symbols = circuit.symbols();
+
+        let mut inputs = HashMap::new();
+        inputs.insert("in".to_string(), InputValue::Scalar(Fr::from_u64(13)));
+
+        let witness = witness::calculate_witness(&circuit, &inputs).expect("Witness calculation failed");
+
+        expect_that!(&witness.len(), is(eq(10)));
+        expect_that!(&witness[0], is(eq(Fr::one())));
+        expect_that!(&witness[symbols.wire_of("main.in").unwrap()], is(eq(Fr::from_u64(13))));
+
+        for (bit, expected) in [1, 0, 1, 1, 0, 0, 0, 0].iter().enumerate() {
+            let wire = symbols.wire_of(&format!("main.out[{}]", bit)).unwrap();
+            expect_that!(&witness[wire], is(eq(Fr::from_u64(*expected))));
+        }
+
+    }
+
+    #[test]
+    fn test_calculate_witness_with_components_and_arrays() {
+
+        let circuit = load("matrix.circom");
+        let symbols = circuit.symbols();
+
+        let row = |a: u64, b: u64| InputValue::Array(vec!(InputValue::Scalar(Fr::from_u64(a)), InputValue::Scalar(Fr::from_u64(b))));
+
+        let mut inputs = HashMap::new();
+        inputs.insert("m".to_string(), InputValue::Array(vec!(row(1, 2), row(3, 4))));
+        inputs.insert("x".to_string(), InputValue::Scalar(Fr::from_u64(0)));
+
+        let witness = witness::calculate_witness(&circuit, &inputs).expect("Witness calculation failed");
+
+        expect_that!(&witness[symbols.wire_of("main.trace").unwrap()], is(eq(Fr::from_u64(5))));
+        expect_that!(&witness[symbols.wire_of("main.zero").unwrap()], is(eq(Fr::one())));
+        expect_that!(&witness[symbols.wire_of("main.isz.inv").unwrap()], is(eq(Fr::zero())));
+
+        inputs.insert("x".to_string(), InputValue::Scalar(Fr::from_u64(2)));
+
+        let witness = witness::calculate_witness(&circuit, &inputs).expect("Witness calculation failed");
+
+        expect_that!(&witness[symbols.wire_of("main.zero").unwrap()], is(eq(Fr::zero())));
+        expect_that!(&(witness[symbols.wire_of("main.isz.inv").unwrap()] * Fr::from_u64(2)), is(eq(Fr::one())));
+
+    }
+
+    #[test]
+    fn test_calculate_witness_from_json() {
+
+        let circuit = load("matrix.circom");
+        let symbols = circuit.symbols();
+
+        let inputs = input::parse_inputs::<Fr>(r#"{"m": ["1", "2", "3", "0x04"], "x": 0}"#).unwrap();
+
+        expect_that!(&witness::check_inputs(&circuit, &inputs), is(eq(Ok(()))));
+
+        let witness = witness::calculate_witness(&circuit, &inputs).expect("Witness calculation failed");
+
+        expect_that!(&witness[symbols.wire_of("main.m[1][1]").unwrap()], is(eq(Fr::from_u64(4))));
+        expect_that!(&witness[symbols.wire_of("main.trace").unwrap()], is(eq(Fr::from_u64(5))));
+
+        let mut buffer: Vec<u8> = Vec::new();
+        wtns::write(&witness, & mut buffer).unwrap();
+
+        expect_that!(&wtns::read::<Fr, _>(buffer.as_slice()).unwrap(), is(eq(witness)));
+
+        let inputs = input::parse_inputs::<Fr>(r#"{"m": [1, 2, 3, 4], "x": 0, "y": 1}"#).unwrap();
+
+        expect_that!(&witness::check_inputs(&circuit, &inputs), is(eq(Err(WitnessError::UnknownInput("y".to_string())))));
+
+    }
+
+    #[test]
+    fn test_calculate_witness_errors() {
+
+        let circuit = load("matrix.circom");
+
+        let mut inputs = HashMap::new();
+        inputs.insert("x".to_string(), InputValue::Scalar(Fr::from_u64(0)));
+
+        expect_that!(&witness::calculate_witness(&circuit, &inputs), is(eq(Err(WitnessError::MissingInput("m".to_string())))));
+
+        inputs.insert("m".to_string(), InputValue::Array(vec!(InputValue::Scalar(Fr::from_u64(1)))));
+
+        match witness::calculate_witness(&circuit, &inputs) {
+            Err(WitnessError::InvalidInput(name, _)) => {
+                expect_that!(&name.as_str(), is(eq("m")));
+            },
+            other => panic!("Unexpected result {:?}", other)
+        }
+
+        let circuit = load("invalid_constraint.circom");
+
+        let mut inputs = HashMap::new();
+        inputs.insert("a".to_string(), InputValue::Scalar(Fr::from_u64(3)));
+
+        match witness::calculate_witness(&circuit, &inputs) {
+            Err(WitnessError::ConstraintFailed { location, left, right }) => {
+                expect_that!(&location.component.as_str(), is(eq("main.inc")));
+                expect_that!(&location.template.as_str(), is(eq("Increment")));
+                expect_that!(&location.source.as_str(), is(eq("b === a * 2")));
+                expect_that!(&location.line, is(eq(6)));
+                expect_that!(&left.as_str(), is(eq("4")));
+                expect_that!(&right.as_str(), is(eq("6")));
+            },
+            other => panic!("Unexpected result {:?}", other)
+        }
+
+        inputs.insert("a".to_string(), InputValue::Scalar(Fr::from_u64(1)));
+
+        expect_that!(&witness::calculate_witness(&circuit, &inputs).is_ok(), is(eq(true)));
+
+    }
+
+}
@@ -0,0 +1,133 @@
+use field::PrimeField;
+use binfile::{self, BinFile, BinFileError, SectionReader};
+
+///
+/// iden3 `.wtns` witness file, as produced and consumed by snarkjs (version 2):
+///
+/// - section 1, header: `n8:u32 prime[n8] n_witness:u32`
+/// - section 2, values: `n_witness` elements of `n8` bytes, canonical little endian
+///
+pub const MAGIC: & [u8; 4] = b"wtns";
+pub const VERSION: u32 = 2;
+
+const HEADER_SECTION: u32 = 1;
+const WITNESS_SECTION: u32 = 2;
+
+pub fn to_binfile<F: PrimeField>(witness: & [F]) -> BinFile {
+
+    let mut file = BinFile::new(MAGIC, VERSION);
+
+    let mut header: Vec<u8> = Vec::new();
+    binfile::push_u32(& mut header, binfile::field_size::<F>() as u32);
+    binfile::push_prime::<F>(& mut header);
+    binfile::push_u32(& mut header, witness.len() as u32);
+    file.add_section(HEADER_SECTION, header);
+
+    let mut values: Vec<u8> = Vec::with_capacity(witness.len() * binfile::field_size::<F>());
+    for value in witness {
+        binfile::push_field(& mut values, value);
+    }
+    file.add_section(WITNESS_SECTION, values);
+
+    file
+
+}
+
+pub fn from_binfile<F: PrimeField>(file: & BinFile) -> Result<Vec<F>, BinFileError> {
+
+    if file.version != VERSION {
+        return Err(BinFileError::FormatError(format!("unsupported wtns version {}", file.version)));
+    }
+
+    let mut header = SectionReader::new(file.required_section(HEADER_SECTION)?);
+    let n8 = header.read_u32()? as usize;
+    header.expect_prime::<F>(n8)?;
+    let n_witness = header.read_u32()? as usize;
+
+    let mut values = SectionReader::new(file.required_section(WITNESS_SECTION)?);
+
+    if values.remaining() != n_witness * n8 {
+        return Err(BinFileError::FormatError(format!("expected {} witness values", n_witness)));
+    }
+
+    (0..n_witness).map(|_| values.read_field::<F>()).collect()
+
+}
+
+pub fn write<F: PrimeField, W: std::io::Write>(witness: & [F], writer: & mut W) -> std::io::Result<()> {
+    to_binfile(witness).write(writer)
+}
+
+pub fn read<F: PrimeField, R: std::io::Read>(reader: R) -> Result<Vec<F>, BinFileError> {
+    from_binfile(&BinFile::read(reader, MAGIC)?)
+}
+
+pub fn save<F: PrimeField>(witness: & [F], path: & std::path::Path) -> std::io::Result<()> {
+    to_binfile(witness).save(path)
+}
+
+pub fn load<F: PrimeField>(path: & std::path::Path) -> Result<Vec<F>, BinFileError> {
+    from_binfile(&BinFile::load(path, MAGIC)?)
+}
+
+#[cfg(test)]
+mod wtns_test {
+
+    use field::PrimeField;
+    use field::bn254::Fr;
+    use binfile::BinFileError;
+    use witness::wtns;
+
+    use galvanic_assert::matchers::*;
+
+    #[test]
+    fn test_write_read_roundtrip() {
+
+        let witness = vec!(Fr::one(), Fr::from_u64(13), -Fr::one(), Fr::zero());
+        let mut buffer: Vec<u8> = Vec::new();
+
+        wtns::write(&witness, & mut buffer).unwrap();
+
+        expect_that!(&&buffer[0..4], is(eq(&b"wtns"[..])));
+        expect_that!(&buffer.len(), is(eq(12 + 12 + 40 + 12 + 4 * 32)));
+
+        let read: Vec<Fr> = wtns::read(buffer.as_slice()).unwrap();
+
+        expect_that!(&read, is(eq(witness)));
+
+    }
+
+    #[test]
+    fn test_read_invalid_files() {
+
+        let mut buffer: Vec<u8> = Vec::new();
+        wtns::write(&[Fr::one()], & mut buffer).unwrap();
+
+        let mut wrong_magic = buffer.clone();
+        wrong_magic[0] = b'x';
+
+        match wtns::read::<Fr, _>(wrong_magic.as_slice()) {
+            Err(BinFileError::FormatError(_)) => {},
+            other => panic!("Unexpected result {:?}", other)
+        }
+
+        let mut wrong_prime = buffer.clone();
+        wrong_prime[28] ^= 1;
+
+        match wtns::read::<Fr, _>(wrong_prime.as_slice()) {
+            Err(BinFileError::FormatError(reason)) => {
+                expect_that!(&reason.as_str(), is(eq("the file does not use the bn128 prime")));
+            },
+            other => panic!("Unexpected result {:?}", other)
+        }
+
+        buffer.truncate(buffer.len() - 1);
+
+        match wtns::read::<Fr, _>(buffer.as_slice()) {
+            Err(BinFileError::FormatError(_)) => {},
+            other => panic!("Unexpected result {:?}", other)
+        }
+
+    }
+
+}
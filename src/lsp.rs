@@ -0,0 +1,42 @@
+extern crate libsnarkrs;
+#[macro_use]
+extern crate clap;
+
+use std::path::PathBuf;
+
+use clap::{App, Arg};
+
+use libsnarkrs::lsp::{self, Server};
+
+///
+/// Language server for circom, speaking LSP over stdin and stdout.
+///
+fn main() {
+
+    let matches = App::new("snarkrs-lsp")
+        .version(crate_version!())
+        .about("circom language server, communicating over stdin and stdout")
+        .arg(Arg::with_name("include")
+            .long("include")
+            .short("l")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .help("Directory where includes not found next to the including file are looked up"))
+        .get_matches();
+
+    let include_paths: Vec<PathBuf> = match matches.values_of("include") {
+        Some(values) => values.map(PathBuf::from).collect(),
+        None => Vec::new()
+    };
+
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let mut server = Server::new(include_paths);
+
+    if let Err(error) = lsp::run(& mut server, & mut stdin.lock(), & mut stdout.lock()) {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+
+}
@@ -0,0 +1,108 @@
+//
+// Runs the `snarkrs` binary and checks its exit codes: 0 on success, 1 when the input is rejected
+// and 2 when the command cannot run, with the diagnostics it prints.
+//
+
+#[macro_use]
+extern crate galvanic_assert;
+
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+
+use galvanic_assert::matchers::*;
+
+const MATERIAL: & str = "src/lib/parser/test_material";
+
+fn snarkrs(args: & [& str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_snarkrs")).args(args).output().expect("Cannot run snarkrs")
+}
+
+fn material(path: & str) -> std::string::String {
+    format!("{}/{}", MATERIAL, path)
+}
+
+fn stdout(output: & Output) -> std::string::String {
+    std::string::String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: & Output) -> std::string::String {
+    std::string::String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_success() {
+
+    let output = snarkrs(&["check", &material("underconstrained/is_zero.circom")]);
+
+    expect_that!(&output.status.code(), is(eq(Some(0))));
+    expect_that!(&stdout(&output), is(eq("main: IsZero (1 components, 3 signals, 2 constraints)\nNo errors found in 6 file(s)\n".to_string())));
+    expect_that!(&stderr(&output).is_empty(), is(eq(true)));
+
+}
+
+#[test]
+fn test_rejected() {
+
+    let output = snarkrs(&["check", &material("include/invalid_include__missing_semicolon.circom")]);
+
+    expect_that!(&output.status.code(), is(eq(Some(1))));
+    expect_that!(&stdout(&output).is_empty(), is(eq(true)));
+
+    let diagnostics = stderr(&output);
+    expect_that!(&diagnostics.starts_with("SyntaxError[E101]:\nin \""), is(eq(true)));
+    expect_that!(&diagnostics.contains("invalid_include__missing_semicolon.circom\"\n"), is(eq(true)));
+    expect_that!(&diagnostics.contains("\t1 | include \"my_other_circuit.circom\"\n"), is(eq(true)));
+    expect_that!(&diagnostics.contains("= expected COMMENT or END_OF_LINE"), is(eq(true)));
+    expect_that!(&diagnostics.ends_with("\n1 error found\n"), is(eq(true)));
+
+    let output = snarkrs(&["audit", &material("underconstrained/num2bits_unsafe.circom")]);
+
+    expect_that!(&output.status.code(), is(eq(Some(1))));
+    expect_that!(&stdout(&output).starts_with("Output main.out[0] is not determined by the inputs through the constraints\n\t"), is(eq(true)));
+    expect_that!(&stdout(&output).contains("num2bits_unsafe.circom:8:9 in main (UnsafeNum2Bits): out[i] <-- (in >> i) & 1\n"), is(eq(true)));
+    expect_that!(&stderr(&output), is(eq("2 under-constrained signals found\n".to_string())));
+
+}
+
+#[test]
+fn test_error() {
+
+    let output = snarkrs(&["check", "missing.circom"]);
+
+    expect_that!(&output.status.code(), is(eq(Some(2))));
+    expect_that!(&stdout(&output).is_empty(), is(eq(true)));
+    expect_that!(&stderr(&output).starts_with("missing.circom: "), is(eq(true)));
+
+    let output = snarkrs(&["check"]);
+    expect_that!(&output.status.code(), is(eq(Some(2))));
+    expect_that!(&stderr(&output).starts_with("error: The following required arguments were not provided:"), is(eq(true)));
+
+    let output = snarkrs(&["check", "--prime", "goldilocks", &material("underconstrained/is_zero.circom")]);
+    expect_that!(&output.status.code(), is(eq(Some(2))));
+
+    let output = snarkrs(&["--threads", "0", "check", &material("underconstrained/is_zero.circom")]);
+    expect_that!(&output.status.code(), is(eq(Some(2))));
+    expect_that!(&stderr(&output), is(eq("Invalid number of threads 0\n".to_string())));
+
+}
+
+#[test]
+fn test_closed_output() {
+
+    // The token tree is larger than a pipe buffer, the reader stops after its first line
+    let mut child = Command::new(env!("CARGO_BIN_EXE_snarkrs"))
+        .args(["parse", "--dump", "tokens", &material("circuits/sha256/sha256compression.circom")])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Cannot run snarkrs");
+
+    let mut first = [0u8; 8];
+    child.stdout.take().unwrap().read_exact(& mut first).unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    expect_that!(&first, is(eq(*b"Circuit ")));
+    expect_that!(&output.status.code(), is(eq(Some(0))));
+    expect_that!(&stderr(&output).is_empty(), is(eq(true)));
+
+}